@@ -0,0 +1,333 @@
+use super::{Pickup, Weapon, WeaponInventory, WeaponType};
+use crate::enemies::ZombieDied;
+use crate::player::{PickupCollected, PickupKind, Player, PlayerActions};
+use crate::ui::{in_wave_mode, DifficultyModifiers, GameState};
+use crate::world::{BalanceData, BudgetCategory, Budgeted, GameRng};
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Ammo types a weapon can load, cycled with B, and the boxes of them lying around
+pub struct AmmoPlugin;
+
+impl Plugin for AmmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_ammo_box_assets)
+            .add_systems(
+                Update,
+                (
+                    cycle_ammo_type,
+                    drop_ammo_boxes,
+                    spin_ammo_boxes,
+                    collect_ammo_boxes,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                (
+                    despawn_ammo_boxes,
+                    spawn_world_ammo_boxes.run_if(in_wave_mode),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                (
+                    despawn_ammo_boxes,
+                    spawn_world_ammo_boxes.run_if(in_wave_mode),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Number of ammo types, the length of a weapon's reserves
+pub const AMMO_TYPES: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum AmmoType {
+    #[default]
+    Standard,
+    /// Tears up bare flesh, flattens against armor, never goes through anything
+    HollowPoint,
+    /// Mostly ignores armor and punches through one obstacle, at a little less damage
+    ArmorPiercing,
+    /// Sets what it hits alight (see StatusEffects)
+    Incendiary,
+}
+
+impl AmmoType {
+    pub const ALL: [AmmoType; AMMO_TYPES] = [
+        AmmoType::Standard,
+        AmmoType::HollowPoint,
+        AmmoType::ArmorPiercing,
+        AmmoType::Incendiary,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmmoType::Standard => "Standard",
+            AmmoType::HollowPoint => "Hollow-point",
+            AmmoType::ArmorPiercing => "AP",
+            AmmoType::Incendiary => "Incendiary",
+        }
+    }
+
+    /// Slot in a weapon's reserves
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn modifiers(&self) -> AmmoModifiers {
+        match self {
+            AmmoType::Standard | AmmoType::Incendiary => AmmoModifiers::default(),
+            AmmoType::HollowPoint => AmmoModifiers {
+                unarmored: 1.4,
+                armored: 0.5,
+                ..default()
+            },
+            AmmoType::ArmorPiercing => AmmoModifiers {
+                damage: 0.85,
+                armor_ignored: 0.7,
+                penetration: 1,
+                ..default()
+            },
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            AmmoType::Standard => Color::srgb(0.8, 0.7, 0.3),
+            AmmoType::HollowPoint => Color::srgb(0.9, 0.3, 0.3),
+            AmmoType::ArmorPiercing => Color::srgb(0.3, 0.8, 0.4),
+            AmmoType::Incendiary => Color::srgb(1.0, 0.5, 0.1),
+        }
+    }
+}
+
+/// How an ammo type changes a round's damage and flight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AmmoModifiers {
+    /// Scale on the weapon's damage against everything
+    pub damage: f32,
+    /// Scale against targets with no armor
+    pub unarmored: f32,
+    /// Scale against armored targets
+    pub armored: f32,
+    /// Share of the target's armor the round ignores, 0.0 to 1.0
+    pub armor_ignored: f32,
+    /// Obstacles the round passes through before stopping
+    pub penetration: u8,
+}
+
+impl Default for AmmoModifiers {
+    fn default() -> Self {
+        Self {
+            damage: 1.0,
+            unarmored: 1.0,
+            armored: 1.0,
+            armor_ignored: 0.0,
+            penetration: 0,
+        }
+    }
+}
+
+impl AmmoModifiers {
+    /// Damage of one round against a target whose armor blocks `armor` (0.0 to 1.0)
+    /// of each hit
+    pub fn damage_against(&self, base: f32, armor: f32) -> f32 {
+        let scale = if armor > 0.0 {
+            self.armored
+        } else {
+            self.unarmored
+        };
+        let blocked = armor * (1.0 - self.armor_ignored);
+        base * self.damage * scale * (1.0 - blocked)
+    }
+}
+
+/// Ammo box lying in the world, picked up by walking over it
+#[derive(Component)]
+#[require(Pickup)]
+pub struct AmmoBox(pub AmmoType);
+
+#[derive(Resource)]
+struct AmmoBoxAssets {
+    mesh: Handle<Mesh>,
+    materials: [Handle<StandardMaterial>; AMMO_TYPES],
+}
+
+const AMMO_PICKUP_RADIUS: f32 = 1.2;
+/// Base chance of a kill leaving an ammo box, before the difficulty's drop modifier
+const AMMO_DROP_CHANCE: f32 = 0.08;
+
+/// Fixed boxes around the arena at the start of a survival run
+const WORLD_AMMO_BOXES: [(Vec3, AmmoType); 3] = [
+    (Vec3::new(-20.0, 0.3, -5.0), AmmoType::HollowPoint),
+    (Vec3::new(12.0, 0.3, 20.0), AmmoType::ArmorPiercing),
+    (Vec3::new(25.0, 0.3, 0.0), AmmoType::Incendiary),
+];
+
+fn setup_ammo_box_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(AmmoBoxAssets {
+        mesh: meshes.add(Cuboid::new(0.5, 0.3, 0.3)),
+        materials: AmmoType::ALL.map(|ammo| {
+            materials.add(StandardMaterial {
+                base_color: ammo.color(),
+                emissive: ammo.color().to_linear() * 0.5,
+                ..default()
+            })
+        }),
+    });
+}
+
+fn spawn_ammo_box(
+    commands: &mut Commands,
+    assets: &AmmoBoxAssets,
+    position: Vec3,
+    ammo: AmmoType,
+) -> Entity {
+    commands
+        .spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.materials[ammo.index()].clone()),
+            Transform::from_translation(position.with_y(0.3)),
+            AmmoBox(ammo),
+        ))
+        .id()
+}
+
+fn spawn_world_ammo_boxes(mut commands: Commands, assets: Res<AmmoBoxAssets>) {
+    for (position, ammo) in WORLD_AMMO_BOXES {
+        spawn_ammo_box(&mut commands, &assets, position, ammo);
+    }
+}
+
+/// Step the held weapon's selection on to the next type it takes. The magazine keeps
+/// its rounds until the reload this triggers (see handle_reload_input).
+fn cycle_ammo_type(
+    actions: Res<PlayerActions>,
+    mut players: Query<&mut WeaponInventory, With<Player>>,
+) {
+    if !actions.toggle_ammo {
+        return;
+    }
+    for mut inventory in players.iter_mut() {
+        if let Some(weapon) = inventory.current_weapon_mut() {
+            weapon.cycle_ammo();
+        }
+    }
+}
+
+/// Kills sometimes leave a box of any type
+fn drop_ammo_boxes(
+    mut commands: Commands,
+    mut died_events: MessageReader<ZombieDied>,
+    assets: Res<AmmoBoxAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in died_events.read() {
+        if rng.random::<f32>() < AMMO_DROP_CHANCE * difficulty.drop_chance {
+            let ammo = AmmoType::ALL[rng.random_range(0..AMMO_TYPES)];
+            let ammo_box = spawn_ammo_box(&mut commands, &assets, event.position, ammo);
+            commands
+                .entity(ammo_box)
+                .insert(Budgeted(BudgetCategory::Pickup));
+        }
+    }
+}
+
+fn spin_ammo_boxes(time: Res<Time>, mut boxes: Query<&mut Transform, With<AmmoBox>>) {
+    for mut transform in boxes.iter_mut() {
+        transform.rotate_y(1.5 * time.delta_secs());
+    }
+}
+
+/// Every carried weapon that takes the box's type gets magazines of it. Boxes nothing
+/// can use stay on the ground.
+fn collect_ammo_boxes(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut WeaponInventory), With<Player>>,
+    boxes: Query<(Entity, &Transform, &AmmoBox), Without<Player>>,
+    mut collected_events: MessageWriter<PickupCollected>,
+    balance: Res<BalanceData>,
+) {
+    let Ok((player_transform, mut inventory)) = player_q.single_mut() else {
+        return;
+    };
+
+    for (entity, box_transform, ammo_box) in boxes.iter() {
+        let distance = (box_transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        if distance >= AMMO_PICKUP_RADIUS {
+            continue;
+        }
+
+        let mut used = false;
+        for weapon in inventory.weapons.iter_mut().flatten() {
+            if weapon.ammo_types().contains(&ammo_box.0) {
+                *weapon.reserve_mut(ammo_box.0) +=
+                    weapon.magazine_size * balance.pickups.ammo_box_magazines;
+                used = true;
+            }
+        }
+        if used {
+            commands.entity(entity).despawn();
+            collected_events.write(PickupCollected {
+                kind: PickupKind::Ammo(ammo_box.0),
+                position: box_transform.translation,
+            });
+        }
+    }
+}
+
+fn despawn_ammo_boxes(mut commands: Commands, boxes: Query<Entity, With<AmmoBox>>) {
+    for entity in boxes.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+impl Weapon {
+    /// Types this weapon can load; the railgun and arc gun take only their own cells
+    pub fn ammo_types(&self) -> &'static [AmmoType] {
+        match self.weapon_type {
+            WeaponType::Railgun | WeaponType::Arc => &[AmmoType::Standard],
+            WeaponType::Shotgun => &AmmoType::ALL,
+            _ => &[
+                AmmoType::Standard,
+                AmmoType::HollowPoint,
+                AmmoType::ArmorPiercing,
+            ],
+        }
+    }
+
+    /// Select the next type this weapon takes, wrapping back to the first
+    pub fn cycle_ammo(&mut self) {
+        let types = self.ammo_types();
+        let current = types
+            .iter()
+            .position(|ammo| *ammo == self.selected_ammo)
+            .unwrap_or(0);
+        self.selected_ammo = types[(current + 1) % types.len()];
+    }
+
+    pub fn reserve(&self, ammo: AmmoType) -> u32 {
+        self.reserves[ammo.index()]
+    }
+
+    pub fn reserve_mut(&mut self, ammo: AmmoType) -> &mut u32 {
+        &mut self.reserves[ammo.index()]
+    }
+}
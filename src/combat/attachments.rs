@@ -0,0 +1,386 @@
+use super::{Pickup, Weapon};
+use crate::enemies::ZombieDied;
+use crate::player::{PickupCollected, PickupKind, Player, PlayerPerks};
+use crate::ui::{DifficultyModifiers, GameState};
+use crate::world::{BudgetCategory, Budgeted, GameRng};
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Weapon attachments: rare drops from kills, kept as spares until fitted from the
+/// loadout screen. Systems read a weapon's stats through compute_effective_stats,
+/// which layers the player's perks on top.
+pub struct AttachmentPlugin;
+
+impl Plugin for AttachmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AttachmentInventory>()
+            .add_systems(Startup, setup_attachment_assets)
+            .add_systems(
+                Update,
+                (
+                    drop_attachments,
+                    spin_attachment_pickups,
+                    collect_attachment_pickups,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_attachments,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_attachments,
+            );
+    }
+}
+
+/// Attachment slots on every weapon
+pub const ATTACHMENT_SLOTS: usize = 2;
+/// Chance of a kill leaving an attachment, before the difficulty's drop modifier
+const ATTACHMENT_DROP_CHANCE: f32 = 0.02;
+const ATTACHMENT_PICKUP_RADIUS: f32 = 1.2;
+
+/// Field of view taken off while firing through a scope
+const SCOPE_ZOOM: f32 = 0.17; // ~10 degrees
+/// Extended magazines hold half as many rounds again
+const EXTENDED_MAG_SCALE: f32 = 1.5;
+/// Share of the spread left with a compensator fitted
+const COMPENSATOR_SPREAD_SCALE: f32 = 0.7;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Attachment {
+    Scope,
+    ExtendedMag,
+    Compensator,
+}
+
+impl Attachment {
+    pub const ALL: [Attachment; 3] = [
+        Attachment::Scope,
+        Attachment::ExtendedMag,
+        Attachment::Compensator,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attachment::Scope => "Scope",
+            Attachment::ExtendedMag => "Extended Mag",
+            Attachment::Compensator => "Compensator",
+        }
+    }
+}
+
+/// Weapon stats with its attachments and the player's perks applied
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EffectiveStats {
+    pub magazine_size: u32,
+    /// Spread angle in radians
+    pub spread: f32,
+    /// Seconds
+    pub reload_time: f32,
+    /// Field of view taken off while firing, in radians
+    pub zoom: f32,
+}
+
+/// Apply a weapon's attachments and the player's perks to its base stats. Modifiers
+/// multiply, so the order attachments are fitted or perks taken in doesn't matter.
+pub fn compute_effective_stats(weapon: &Weapon, perks: &PlayerPerks) -> EffectiveStats {
+    let mut magazine_scale = 1.0;
+    let mut spread_scale = perks.spread_scale();
+    let mut zoom = 0.0;
+    for attachment in weapon.attachments.iter().flatten() {
+        match attachment {
+            Attachment::Scope => zoom += SCOPE_ZOOM,
+            Attachment::ExtendedMag => magazine_scale *= EXTENDED_MAG_SCALE,
+            Attachment::Compensator => spread_scale *= COMPENSATOR_SPREAD_SCALE,
+        }
+    }
+
+    EffectiveStats {
+        magazine_size: (weapon.magazine_size as f32 * magazine_scale).round() as u32,
+        spread: weapon.spread * spread_scale,
+        reload_time: weapon.reload_time * perks.reload_time_scale(),
+        zoom,
+    }
+}
+
+impl Weapon {
+    pub fn has_attachment(&self, attachment: Attachment) -> bool {
+        self.attachments.contains(&Some(attachment))
+    }
+
+    /// Fit an attachment into a slot, handing back whatever was there
+    pub fn attach(
+        &mut self,
+        slot: usize,
+        attachment: Attachment,
+        perks: &PlayerPerks,
+    ) -> Option<Attachment> {
+        let previous = self.attachments.get_mut(slot)?.replace(attachment);
+        self.unload_excess(perks);
+        previous
+    }
+
+    /// Take the attachment out of a slot
+    pub fn detach(&mut self, slot: usize, perks: &PlayerPerks) -> Option<Attachment> {
+        let removed = self.attachments.get_mut(slot)?.take();
+        self.unload_excess(perks);
+        removed
+    }
+
+    /// Rounds that no longer fit after losing an extended mag go back to the reserve
+    fn unload_excess(&mut self, perks: &PlayerPerks) {
+        let capacity = compute_effective_stats(self, perks).magazine_size;
+        if self.current_ammo > capacity {
+            *self.reserve_mut(self.loaded_ammo) += self.current_ammo - capacity;
+            self.current_ammo = capacity;
+        }
+    }
+}
+
+/// Step a weapon's attachment slot on to the next spare it doesn't already carry,
+/// then back to empty. Whatever comes off goes back into the spares.
+pub fn cycle_attachment(
+    weapon: &mut Weapon,
+    slot: usize,
+    spares: &mut Vec<Attachment>,
+    perks: &PlayerPerks,
+) {
+    let removed = weapon.detach(slot, perks);
+    let start = removed.map_or(0, |removed| {
+        Attachment::ALL
+            .iter()
+            .position(|attachment| *attachment == removed)
+            .map_or(0, |index| index + 1)
+    });
+
+    let next = Attachment::ALL[start..]
+        .iter()
+        .copied()
+        .find(|attachment| spares.contains(attachment) && !weapon.has_attachment(*attachment));
+    if let Some(next) = next {
+        if let Some(index) = spares.iter().position(|spare| *spare == next) {
+            spares.remove(index);
+        }
+        weapon.attach(slot, next, perks);
+    }
+    if let Some(removed) = removed {
+        spares.push(removed);
+    }
+}
+
+/// Attachments collected but not fitted to any weapon
+#[derive(Resource, Default)]
+pub struct AttachmentInventory {
+    pub spares: Vec<Attachment>,
+}
+
+/// Attachment lying in the world, picked up by walking over it
+#[derive(Component)]
+#[require(Pickup)]
+pub struct AttachmentPickup(pub Attachment);
+
+#[derive(Resource)]
+struct AttachmentAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_attachment_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(AttachmentAssets {
+        mesh: meshes.add(Cuboid::new(0.3, 0.3, 0.3)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.7, 0.3, 0.9),
+            emissive: LinearRgba::rgb(1.2, 0.4, 1.6),
+            ..default()
+        }),
+    });
+}
+
+fn drop_attachments(
+    mut commands: Commands,
+    mut died_events: MessageReader<ZombieDied>,
+    assets: Res<AttachmentAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in died_events.read() {
+        if rng.random::<f32>() >= ATTACHMENT_DROP_CHANCE * difficulty.drop_chance {
+            continue;
+        }
+        let attachment = Attachment::ALL[rng.random_range(0..Attachment::ALL.len())];
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation(event.position.with_y(0.4)),
+            AttachmentPickup(attachment),
+            Budgeted(BudgetCategory::Pickup),
+        ));
+    }
+}
+
+fn spin_attachment_pickups(
+    time: Res<Time>,
+    mut pickups: Query<&mut Transform, With<AttachmentPickup>>,
+) {
+    for mut transform in pickups.iter_mut() {
+        transform.rotate_y(1.5 * time.delta_secs());
+    }
+}
+
+fn collect_attachment_pickups(
+    mut commands: Commands,
+    player_q: Query<&Transform, With<Player>>,
+    pickups: Query<(Entity, &Transform, &AttachmentPickup), Without<Player>>,
+    mut inventory: ResMut<AttachmentInventory>,
+    mut collected_events: MessageWriter<PickupCollected>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+
+    for (entity, pickup_transform, pickup) in pickups.iter() {
+        let distance = (pickup_transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        if distance < ATTACHMENT_PICKUP_RADIUS {
+            inventory.spares.push(pickup.0);
+            commands.entity(entity).despawn();
+            collected_events.write(PickupCollected {
+                kind: PickupKind::Attachment(pickup.0),
+                position: pickup_transform.translation,
+            });
+        }
+    }
+}
+
+/// Weapons are rebuilt for a new run, so the spares go too
+fn reset_attachments(
+    mut commands: Commands,
+    mut inventory: ResMut<AttachmentInventory>,
+    pickups: Query<Entity, With<AttachmentPickup>>,
+) {
+    inventory.spares.clear();
+    for entity in pickups.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::{AmmoType, WeaponType};
+    use crate::player::Perk;
+    use crate::world::BalanceData;
+
+    fn smg() -> Weapon {
+        Weapon::new(WeaponType::Smg, &BalanceData::embedded())
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn bare_weapons_keep_their_base_stats() {
+        let weapon = smg();
+        let stats = compute_effective_stats(&weapon, &PlayerPerks::default());
+        assert_eq!(stats.magazine_size, weapon.magazine_size);
+        assert_eq!(stats.spread, weapon.spread);
+        assert_eq!(stats.reload_time, weapon.reload_time);
+        assert_eq!(stats.zoom, 0.0);
+    }
+
+    #[test]
+    fn attachments_and_perks_stack() {
+        let mut weapon = smg();
+        let perks = PlayerPerks {
+            taken: vec![Perk::SteadyAim, Perk::QuickHands],
+        };
+        weapon.attach(0, Attachment::ExtendedMag, &perks);
+        weapon.attach(1, Attachment::Compensator, &perks);
+
+        let stats = compute_effective_stats(&weapon, &perks);
+        assert_eq!(
+            stats.magazine_size,
+            (weapon.magazine_size as f32 * EXTENDED_MAG_SCALE).round() as u32
+        );
+        assert!(close(
+            stats.spread,
+            weapon.spread * COMPENSATOR_SPREAD_SCALE * 0.85
+        ));
+        assert!(close(stats.reload_time, weapon.reload_time / 1.15));
+        assert_eq!(stats.zoom, 0.0);
+
+        // The order they went on in makes no difference
+        let mut swapped = smg();
+        swapped.attach(0, Attachment::Compensator, &perks);
+        swapped.attach(1, Attachment::ExtendedMag, &perks);
+        assert_eq!(compute_effective_stats(&swapped, &perks), stats);
+    }
+
+    #[test]
+    fn removing_attachments_restores_the_base_stats() {
+        let perks = PlayerPerks::default();
+        let base = compute_effective_stats(&smg(), &perks);
+        let mut weapon = smg();
+        weapon.attach(0, Attachment::Scope, &perks);
+        weapon.attach(1, Attachment::Compensator, &perks);
+        assert_ne!(compute_effective_stats(&weapon, &perks), base);
+
+        assert_eq!(weapon.detach(0, &perks), Some(Attachment::Scope));
+        assert_eq!(weapon.detach(1, &perks), Some(Attachment::Compensator));
+        assert_eq!(weapon.detach(1, &perks), None);
+        assert_eq!(compute_effective_stats(&weapon, &perks), base);
+    }
+
+    #[test]
+    fn extended_mags_reload_to_their_own_size() {
+        let perks = PlayerPerks::default();
+        let mut weapon = smg();
+        let base_size = weapon.magazine_size;
+        let reserve = weapon.reserve(AmmoType::Standard);
+        weapon.attach(0, Attachment::ExtendedMag, &perks);
+        let extended = compute_effective_stats(&weapon, &perks).magazine_size;
+
+        assert!(weapon.can_reload(&perks));
+        assert_eq!(weapon.reload(&perks), extended - base_size);
+        assert_eq!(weapon.current_ammo, extended);
+
+        // Taking it off puts the rounds that no longer fit back in the reserve
+        weapon.detach(0, &perks);
+        assert_eq!(weapon.current_ammo, base_size);
+        assert_eq!(weapon.reserve(AmmoType::Standard), reserve);
+    }
+
+    #[test]
+    fn cycling_a_slot_trades_with_the_spares() {
+        let perks = PlayerPerks::default();
+        let mut weapon = smg();
+        let mut spares = vec![Attachment::Compensator, Attachment::Scope];
+
+        cycle_attachment(&mut weapon, 0, &mut spares, &perks);
+        assert_eq!(weapon.attachments[0], Some(Attachment::Scope));
+        assert_eq!(spares, vec![Attachment::Compensator]);
+
+        cycle_attachment(&mut weapon, 0, &mut spares, &perks);
+        assert_eq!(weapon.attachments[0], Some(Attachment::Compensator));
+        assert_eq!(spares, vec![Attachment::Scope]);
+
+        // Past the last spare the slot empties again
+        cycle_attachment(&mut weapon, 0, &mut spares, &perks);
+        assert_eq!(weapon.attachments[0], None);
+        assert_eq!(spares, vec![Attachment::Scope, Attachment::Compensator]);
+    }
+}
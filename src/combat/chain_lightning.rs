@@ -0,0 +1,193 @@
+use super::{DebugRay, HitEvent, WeaponInventory};
+use crate::enemies::{SpawnProtection, Zombie};
+use crate::player::Player;
+use crate::ui::GameState;
+use crate::world::{
+    BudgetCategory, Budgeted, GameRng, PhysicsLayer, SpatialIndex, SpatialKind, SurfaceMaterial,
+};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+/// Shots from a chain weapon arc on from the zombie they hit to the nearest ones
+/// around it, weaker with every jump. Jumps need a clear line between zombies and
+/// never come back to one already struck by the same discharge.
+pub struct ChainLightningPlugin;
+
+impl Plugin for ChainLightningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_arc_assets)
+            .add_systems(Update, chain_lightning.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// How long each arc stays on screen
+const ARC_LIFETIME: f32 = 0.2;
+/// Kinks drawn in each jump
+const ARC_SEGMENTS: usize = 4;
+/// Furthest a kink strays from the straight line
+const ARC_JITTER: f32 = 0.25;
+/// Height above a zombie's origin the arcs connect at
+const ARC_CHEST: f32 = 0.4;
+
+/// One unit-length bolt segment, stretched per segment so arcs allocate nothing
+#[derive(Resource)]
+struct ArcAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_arc_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ArcAssets {
+        mesh: meshes.add(Cuboid::new(0.04, 1.0, 0.04)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.7, 0.8, 1.0),
+            emissive: LinearRgba::rgb(3.0, 4.0, 8.0),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Shots (not the jumps themselves, which travel no distance) from a chain weapon
+/// start a discharge at the zombie they hit
+fn chain_lightning(
+    mut commands: Commands,
+    assets: Res<ArcAssets>,
+    mut hit_events: ParamSet<(MessageReader<HitEvent>, MessageWriter<HitEvent>)>,
+    players: Query<(Entity, &WeaponInventory), With<Player>>,
+    zombies: Query<(Entity, &Transform, &Zombie), Without<SpawnProtection>>,
+    index: Res<SpatialIndex>,
+    rapier_context: ReadRapierContext,
+    mut rng: ResMut<GameRng>,
+) {
+    let Ok((player, inventory)) = players.single() else {
+        return;
+    };
+    let Some((chain, damage)) = inventory
+        .current_weapon()
+        .and_then(|weapon| weapon.chain.map(|chain| (chain, weapon.damage)))
+    else {
+        // Keep the reader up to date so old hits don't start a chain after a switch
+        hit_events.p0().clear();
+        return;
+    };
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+
+    let struck: Vec<Entity> = hit_events
+        .p0()
+        .read()
+        .filter(|event| event.source == Some(player) && event.distance > 0.0)
+        .map(|event| event.entity)
+        .filter(|entity| zombies.contains(*entity))
+        .collect();
+
+    for first in struck {
+        let mut visited = vec![first];
+        let mut previous = first;
+        let mut link_damage = damage;
+
+        for _ in 0..chain.links {
+            let Ok((_, previous_transform, _)) = zombies.get(previous) else {
+                break;
+            };
+            let from = previous_transform.translation + Vec3::Y * ARC_CHEST;
+
+            // Nearest zombie in reach that hasn't been struck and can be seen
+            let mut candidates: Vec<(Entity, Vec3, f32)> = index
+                .neighbors_within(from, chain.radius)
+                .filter(|entry| {
+                    entry.kind == SpatialKind::Zombie && !visited.contains(&entry.entity)
+                })
+                .filter_map(|entry| zombies.get(entry.entity).ok())
+                .filter(|(_, _, zombie)| zombie.health > 0.0)
+                .map(|(entity, transform, _)| {
+                    let to = transform.translation + Vec3::Y * ARC_CHEST;
+                    (entity, to, from.distance(to))
+                })
+                .filter(|(_, _, distance)| *distance <= chain.radius)
+                .collect();
+            candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+            let next = candidates.into_iter().find(|(entity, to, distance)| {
+                let filter = QueryFilter::default()
+                    .exclude_rigid_body(previous)
+                    .exclude_sensors()
+                    .groups(PhysicsLayer::Projectiles.groups());
+                let mut clear = false;
+                context.with_query_pipeline(filter, |query_pipeline| {
+                    clear = query_pipeline
+                        .cast_ray(from, (*to - from).normalize_or_zero(), *distance, true)
+                        .is_some_and(|(hit, _)| hit == *entity);
+                });
+                clear
+            });
+            let Some((next, to, _)) = next else {
+                break;
+            };
+
+            link_damage *= chain.falloff;
+            hit_events.p1().write(HitEvent {
+                entity: next,
+                damage: link_damage,
+                direction: (to - from).normalize_or_zero(),
+                point: to,
+                normal: (from - to).normalize_or_zero(),
+                distance: 0.0,
+                source: Some(player),
+                zone: None,
+                surface: SurfaceMaterial::default(),
+                headshot: false,
+                crit: false,
+            });
+            spawn_arc(&mut commands, &assets, from, to, &mut **rng);
+
+            visited.push(next);
+            previous = next;
+        }
+    }
+}
+
+/// Jagged bolt from `from` to `to`, built from stretched segments between kinks
+fn spawn_arc(
+    commands: &mut Commands,
+    assets: &ArcAssets,
+    from: Vec3,
+    to: Vec3,
+    rng: &mut impl Rng,
+) {
+    let mut points = vec![from];
+    for i in 1..ARC_SEGMENTS {
+        let along = from.lerp(to, i as f32 / ARC_SEGMENTS as f32);
+        let jitter = Vec3::new(
+            rng.random_range(-ARC_JITTER..ARC_JITTER),
+            rng.random_range(-ARC_JITTER..ARC_JITTER),
+            rng.random_range(-ARC_JITTER..ARC_JITTER),
+        );
+        points.push(along + jitter);
+    }
+    points.push(to);
+
+    for pair in points.windows(2) {
+        let segment = pair[1] - pair[0];
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation((pair[0] + pair[1]) / 2.0)
+                .with_rotation(Quat::from_rotation_arc(
+                    Vec3::Y,
+                    segment.normalize_or_zero(),
+                ))
+                .with_scale(Vec3::new(1.0, segment.length(), 1.0)),
+            DebugRay {
+                timer: Timer::from_seconds(ARC_LIFETIME, TimerMode::Once),
+            },
+            Budgeted(BudgetCategory::Tracer),
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+use super::{Pickup, Weapon, WeaponInventory};
+use crate::enemies::{WaveCleared, ZombieDied};
+use crate::player::{PickupCollected, PickupKind, Player, PlayerActions};
+use crate::ui::{DifficultyModifiers, GameState};
+use crate::world::{BalanceData, BudgetCategory, Budgeted, GameRng};
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Weapon wear and jams, on difficulties whose modifiers turn them on. Every round
+/// wears the weapon a little; below JAM_CONDITION a round may jam it instead of firing,
+/// and it stays jammed until R is held for JAM_CLEAR_TIME. Cleaning kits dropped by
+/// kills restore condition, and clearing a wave restores it fully.
+pub struct WeaponConditionPlugin;
+
+impl Plugin for WeaponConditionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_cleaning_kit_assets)
+            .add_systems(
+                Update,
+                (
+                    clear_jams,
+                    drop_cleaning_kits,
+                    spin_cleaning_kits,
+                    collect_cleaning_kits,
+                    restore_condition_between_waves,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_cleaning_kits,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_cleaning_kits,
+            );
+    }
+}
+
+/// Condition lost per round at a weapon wear of 1.0
+const WEAR_PER_SHOT: f32 = 0.004;
+/// Condition below which rounds can jam
+const JAM_CONDITION: f32 = 0.3;
+/// Chance of a round jamming at zero condition, ramping up from nothing at JAM_CONDITION
+const MAX_JAM_CHANCE: f32 = 0.08;
+/// Seconds R has to be held to clear a jam
+const JAM_CLEAR_TIME: f32 = 1.2;
+/// Base chance of a kill leaving a cleaning kit, before the difficulty's drop modifier
+const KIT_DROP_CHANCE: f32 = 0.04;
+const KIT_PICKUP_RADIUS: f32 = 1.2;
+
+/// Progress on clearing the held weapon's jam; dropped when R is let go
+#[derive(Component)]
+pub struct ClearingJam(pub Timer);
+
+/// Cleaning kit lying in the world, picked up by walking over it
+#[derive(Component)]
+#[require(Pickup)]
+pub struct CleaningKit;
+
+#[derive(Resource)]
+struct CleaningKitAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// Wear `weapon` by one round and roll whether the round jams instead of firing. Never
+/// jams with weapon wear off, or on the first round after a reload.
+pub fn roll_jam(weapon: &mut Weapon, difficulty: &DifficultyModifiers, rng: &mut GameRng) -> bool {
+    if difficulty.weapon_wear <= 0.0 {
+        return false;
+    }
+    let fresh = std::mem::take(&mut weapon.fresh_magazine);
+    weapon.condition = (weapon.condition - WEAR_PER_SHOT * difficulty.weapon_wear).max(0.0);
+    if fresh || weapon.condition >= JAM_CONDITION {
+        return false;
+    }
+
+    let chance = MAX_JAM_CHANCE * (1.0 - weapon.condition / JAM_CONDITION);
+    weapon.jammed = rng.random::<f32>() < chance;
+    weapon.jammed
+}
+
+fn setup_cleaning_kit_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let color = Color::srgb(0.9, 0.9, 0.85);
+    commands.insert_resource(CleaningKitAssets {
+        mesh: meshes.add(Cuboid::new(0.4, 0.15, 0.25)),
+        material: materials.add(StandardMaterial {
+            base_color: color,
+            emissive: color.to_linear() * 0.3,
+            ..default()
+        }),
+    });
+}
+
+/// Holding R works the held weapon's jam loose. Letting go, or switching to a weapon
+/// that isn't jammed, throws the progress away.
+fn clear_jams(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<PlayerActions>,
+    mut players: Query<(Entity, &mut WeaponInventory, Option<&mut ClearingJam>), With<Player>>,
+) {
+    for (entity, mut inventory, clearing) in players.iter_mut() {
+        let jammed = inventory
+            .current_weapon()
+            .is_some_and(|weapon| weapon.jammed);
+        if !jammed || !actions.reload_held {
+            if clearing.is_some() {
+                commands.entity(entity).remove::<ClearingJam>();
+            }
+            continue;
+        }
+
+        let Some(mut clearing) = clearing else {
+            commands
+                .entity(entity)
+                .insert(ClearingJam(Timer::from_seconds(
+                    JAM_CLEAR_TIME,
+                    TimerMode::Once,
+                )));
+            continue;
+        };
+        clearing.0.tick(time.delta());
+        if clearing.0.is_finished() {
+            if let Some(weapon) = inventory.current_weapon_mut() {
+                weapon.jammed = false;
+            }
+            commands.entity(entity).remove::<ClearingJam>();
+        }
+    }
+}
+
+/// Kills sometimes leave a kit, but only when weapons wear at all
+fn drop_cleaning_kits(
+    mut commands: Commands,
+    mut died_events: MessageReader<ZombieDied>,
+    assets: Res<CleaningKitAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in died_events.read() {
+        if difficulty.weapon_wear <= 0.0 {
+            continue;
+        }
+        if rng.random::<f32>() < KIT_DROP_CHANCE * difficulty.drop_chance {
+            commands.spawn((
+                Mesh3d(assets.mesh.clone()),
+                MeshMaterial3d(assets.material.clone()),
+                Transform::from_translation(event.position.with_y(0.3)),
+                CleaningKit,
+                Budgeted(BudgetCategory::Pickup),
+            ));
+        }
+    }
+}
+
+fn spin_cleaning_kits(time: Res<Time>, mut kits: Query<&mut Transform, With<CleaningKit>>) {
+    for mut transform in kits.iter_mut() {
+        transform.rotate_y(1.5 * time.delta_secs());
+    }
+}
+
+/// Every carried weapon gets some condition back. Kits stay on the ground while
+/// everything is already clean.
+fn collect_cleaning_kits(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut WeaponInventory), With<Player>>,
+    kits: Query<(Entity, &Transform), (With<CleaningKit>, Without<Player>)>,
+    mut collected_events: MessageWriter<PickupCollected>,
+    balance: Res<BalanceData>,
+) {
+    let Ok((player_transform, mut inventory)) = player_q.single_mut() else {
+        return;
+    };
+
+    for (entity, kit_transform) in kits.iter() {
+        let distance = (kit_transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        if distance >= KIT_PICKUP_RADIUS {
+            continue;
+        }
+        if inventory
+            .weapons
+            .iter()
+            .flatten()
+            .all(|weapon| weapon.condition >= 1.0)
+        {
+            continue;
+        }
+
+        for weapon in inventory.weapons.iter_mut().flatten() {
+            weapon.condition = (weapon.condition + balance.pickups.repair_kit).min(1.0);
+        }
+        commands.entity(entity).despawn();
+        collected_events.write(PickupCollected {
+            kind: PickupKind::CleaningKit,
+            position: kit_transform.translation,
+        });
+    }
+}
+
+/// The break between waves is long enough to strip and clean everything
+fn restore_condition_between_waves(
+    mut cleared_events: MessageReader<WaveCleared>,
+    mut players: Query<&mut WeaponInventory, With<Player>>,
+) {
+    if cleared_events.read().count() == 0 {
+        return;
+    }
+    for mut inventory in players.iter_mut() {
+        for weapon in inventory.weapons.iter_mut().flatten() {
+            weapon.condition = 1.0;
+        }
+    }
+}
+
+fn despawn_cleaning_kits(mut commands: Commands, kits: Query<Entity, With<CleaningKit>>) {
+    for entity in kits.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,107 @@
+use super::HitSystems;
+use crate::enemies::TargetHitEvent;
+use crate::player::ThirdPersonCamera;
+use crate::ui::{ColorPalette, GameState};
+use bevy::prelude::*;
+
+/// Floating damage over targets as they're hit, brighter on the killing blow
+pub struct DamageNumberPlugin;
+
+impl Plugin for DamageNumberPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_damage_numbers, update_damage_numbers)
+                .chain()
+                .in_set(HitSystems::Feedback)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), despawn_damage_numbers);
+    }
+}
+
+/// Floating "+25" that drifts up from the hit point and fades
+#[derive(Component)]
+struct DamageNumber {
+    world_position: Vec3,
+    timer: Timer,
+    color: Color,
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    palette: Res<ColorPalette>,
+    mut target_hits: MessageReader<TargetHitEvent>,
+) {
+    for event in target_hits.read() {
+        let color = if event.killed {
+            palette.kill_marker
+        } else {
+            palette.hit_marker
+        };
+
+        commands.spawn((
+            Text::new(format!("+{:.0}", event.damage)),
+            TextFont {
+                font_size: if event.killed { 28.0 } else { 22.0 },
+                ..default()
+            },
+            TextColor(color),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            DamageNumber {
+                world_position: event.point + Vec3::Y * 0.3,
+                timer: Timer::from_seconds(0.8, TimerMode::Once),
+                color,
+            },
+        ));
+    }
+}
+
+fn update_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<ThirdPersonCamera>>,
+    mut numbers: Query<(
+        Entity,
+        &mut DamageNumber,
+        &mut Node,
+        &mut TextColor,
+        &mut Visibility,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+
+    for (entity, mut number, mut node, mut text_color, mut visibility) in numbers.iter_mut() {
+        number.timer.tick(time.delta());
+        if number.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        number.world_position.y += 1.5 * time.delta_secs();
+
+        // Project onto the screen, hiding numbers that end up behind the camera
+        match camera.world_to_viewport(camera_transform, number.world_position) {
+            Ok(screen) => {
+                node.left = Val::Px(screen.x - 15.0);
+                node.top = Val::Px(screen.y - 15.0);
+                *visibility = Visibility::Inherited;
+            }
+            Err(_) => *visibility = Visibility::Hidden,
+        }
+
+        text_color.0 = number.color.with_alpha(1.0 - number.timer.fraction());
+    }
+}
+
+fn despawn_damage_numbers(mut commands: Commands, numbers: Query<Entity, With<DamageNumber>>) {
+    for entity in numbers.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,199 @@
+use super::aim_ray;
+use crate::enemies::NoiseEvent;
+use crate::player::{DeathCamera, Player, PlayerActions, ThirdPersonCamera};
+use crate::ui::GameState;
+use crate::world::{Interpolated, PhysicsLayer};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub struct FlarePlugin;
+
+impl Plugin for FlarePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlareStock>()
+            .add_systems(
+                Update,
+                (
+                    throw_flare.run_if(not(any_with_component::<DeathCamera>)),
+                    update_flares,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_flares,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_flares,
+            );
+    }
+}
+
+/// Flares the player can still throw this run
+#[derive(Resource)]
+pub struct FlareStock {
+    pub remaining: u32,
+}
+
+impl Default for FlareStock {
+    fn default() -> Self {
+        Self {
+            remaining: FLARES_PER_RUN,
+        }
+    }
+}
+
+/// Thrown flare; lights up and makes noise once it comes to rest
+#[derive(Component)]
+#[require(Interpolated)]
+pub struct Flare {
+    /// Time in the air, so it isn't counted as landed on the frame it's thrown
+    flight: Timer,
+    landed: bool,
+    burn: Timer,
+    noise: Timer,
+}
+
+impl Default for Flare {
+    fn default() -> Self {
+        Self {
+            flight: Timer::from_seconds(0.3, TimerMode::Once),
+            landed: false,
+            burn: Timer::from_seconds(FLARE_BURN_TIME, TimerMode::Once),
+            noise: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+}
+
+const FLARES_PER_RUN: u32 = 3;
+const FLARE_BURN_TIME: f32 = 10.0;
+/// Final stretch of the burn where the light gutters out
+const FLARE_FADE_TIME: f32 = 2.0;
+const FLARE_LIGHT_INTENSITY: f32 = 400_000.0;
+/// Zombies further away than this don't hear the flare
+const FLARE_HEARING_RANGE: f32 = 25.0;
+/// Cap on how many zombies one flare can pull away from the player
+const FLARE_MAX_LISTENERS: usize = 6;
+
+fn throw_flare(
+    mut commands: Commands,
+    actions: Res<PlayerActions>,
+    mut stock: ResMut<FlareStock>,
+    player_q: Query<(Entity, &Transform), With<Player>>,
+    camera_q: Query<&Transform, With<ThirdPersonCamera>>,
+    rapier_context: ReadRapierContext,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !actions.throw_flare || stock.remaining == 0 {
+        return;
+    }
+    let (Ok(context), Ok((player_entity, player_transform)), Ok(camera_transform)) = (
+        rapier_context.single(),
+        player_q.single(),
+        camera_q.single(),
+    ) else {
+        return;
+    };
+
+    let (origin, direction) = aim_ray(&context, player_entity, player_transform, camera_transform);
+    stock.remaining -= 1;
+
+    commands
+        .spawn((
+            Mesh3d(meshes.add(Capsule3d::new(0.05, 0.2))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(1.0, 0.2, 0.1),
+                emissive: LinearRgba::rgb(8.0, 1.0, 0.5),
+                ..default()
+            })),
+            // Clear of the player's capsule so the throw doesn't bounce off ourselves
+            Transform::from_translation(origin + direction * 0.8),
+            Flare::default(),
+            RigidBody::Dynamic,
+            Collider::ball(0.1),
+            PhysicsLayer::Projectiles.groups(),
+            Velocity::linear(direction * 14.0 + Vec3::Y * 3.0),
+            Restitution::coefficient(0.2),
+            Damping {
+                linear_damping: 0.3,
+                angular_damping: 1.0,
+            },
+            Ccd::enabled(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PointLight {
+                    color: Color::srgb(1.0, 0.3, 0.2),
+                    intensity: FLARE_LIGHT_INTENSITY * 0.2,
+                    range: 15.0,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 0.3, 0.0),
+            ));
+        });
+}
+
+fn update_flares(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flares: Query<(Entity, &Transform, &Velocity, &mut Flare, &Children)>,
+    mut lights: Query<&mut PointLight>,
+    mut noise_events: MessageWriter<NoiseEvent>,
+) {
+    for (entity, transform, velocity, mut flare, children) in flares.iter_mut() {
+        if !flare.landed {
+            flare.flight.tick(time.delta());
+            if flare.flight.is_finished() && velocity.linvel.length() < 1.0 {
+                flare.landed = true;
+            } else {
+                continue;
+            }
+        }
+
+        flare.burn.tick(time.delta());
+        if flare.burn.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Full brightness until the last couple of seconds, then gutter out
+        let remaining = flare.burn.remaining_secs();
+        let brightness = (remaining / FLARE_FADE_TIME).min(1.0);
+        let flicker = 0.85 + 0.15 * (time.elapsed_secs() * 23.0).sin();
+        for child in children.iter() {
+            if let Ok(mut light) = lights.get_mut(child) {
+                light.intensity = FLARE_LIGHT_INTENSITY * brightness * flicker;
+            }
+        }
+
+        flare.noise.tick(time.delta());
+        if flare.noise.just_finished() {
+            noise_events.write(NoiseEvent {
+                source: entity,
+                position: transform.translation,
+                radius: FLARE_HEARING_RANGE,
+                max_listeners: FLARE_MAX_LISTENERS,
+            });
+        }
+    }
+}
+
+fn reset_flares(
+    mut commands: Commands,
+    mut stock: ResMut<FlareStock>,
+    flares: Query<Entity, With<Flare>>,
+) {
+    *stock = FlareStock::default();
+    for entity in flares.iter() {
+        commands.entity(entity).despawn();
+    }
+}
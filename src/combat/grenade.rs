@@ -0,0 +1,531 @@
+use super::aim_ray;
+use crate::enemies::{ExplosionFuse, EXPLOSION_RADIUS};
+use crate::player::{
+    apply_player_damage, ActionState, DeathCamera, FeedbackEvent, Flinch, Player,
+    PlayerActionState, PlayerActions, PlayerArmor, PlayerDamageSource, PlayerHealth, PlayerHit,
+    PlayerPerks, ThirdPersonCamera, EXPLOSION_FLINCH, PLAYER_HALF_HEIGHT, PLAYER_RADIUS,
+};
+use crate::ui::{DifficultyModifiers, GameState};
+use crate::world::{Interpolated, PhysicsLayer};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_rapier3d::prelude::*;
+
+/// Frag grenades on T. Holding the key cooks one, with the fuse already burning, and
+/// letting go throws it with whatever is left; hold on too long and it goes off in
+/// hand. Thrown grenades detonate through the same ExplosionFuse as explosive
+/// targets, so zombies already steer clear of them. Any landing near the player gets
+/// a danger circle on the ground the size of the blast, and an arrow at the screen
+/// edge while it's out of view.
+pub struct GrenadePlugin;
+
+impl Plugin for GrenadePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GrenadeStock>()
+            .add_systems(Startup, setup_grenade_assets)
+            .add_systems(
+                Update,
+                (
+                    cook_grenade.run_if(not(any_with_component::<DeathCamera>)),
+                    clear_detonated_grenades,
+                    update_danger_circles,
+                    update_grenade_warnings,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // A cook in progress can't be carried into a menu or past death, so it's
+            // dropped where the player stands with the fuse still burning
+            .add_systems(
+                OnExit(GameState::Playing),
+                (drop_cooked_grenade, hide_grenade_warnings),
+            )
+            .add_systems(
+                Update,
+                drop_cooked_grenade.run_if(any_with_component::<DeathCamera>),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_grenades,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_grenades,
+            );
+    }
+}
+
+const GRENADES_PER_RUN: u32 = 2;
+/// Seconds from pulling the pin to the blast
+pub const GRENADE_FUSE: f32 = 3.0;
+/// Damage taken when a grenade goes off in hand, before armor
+const COOK_OFF_DAMAGE: f32 = 60.0;
+const GRENADE_RADIUS: f32 = 0.08;
+const THROW_SPEED: f32 = 14.0;
+/// Grenades further from the player than this get no circle or arrow
+const WARNING_RANGE: f32 = 12.0;
+/// Screen-edge arrows keep this far in from the edge, in pixels
+const WARNING_MARGIN: f32 = 40.0;
+const WARNING_SIZE: f32 = 28.0;
+/// Pixels across the generated danger circle texture
+const DANGER_TEXTURE_SIZE: u32 = 128;
+
+/// Grenades the player can still throw this run
+#[derive(Resource)]
+pub struct GrenadeStock {
+    pub remaining: u32,
+}
+
+impl Default for GrenadeStock {
+    fn default() -> Self {
+        Self {
+            remaining: GRENADES_PER_RUN,
+        }
+    }
+}
+
+/// Grenade held with the pin out. The fuse keeps burning whatever happens; a throw,
+/// a drop or a cook-off takes it over.
+#[derive(Component)]
+pub struct CookingGrenade {
+    fuse: Timer,
+}
+
+impl CookingGrenade {
+    /// How far through the fuse the cook has got, 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        self.fuse.fraction()
+    }
+}
+
+/// Live grenade in the world; its ExplosionFuse sets it off
+#[derive(Component)]
+#[require(Interpolated)]
+pub struct Grenade {
+    /// Ground decal showing the blast radius
+    circle: Entity,
+    /// Screen-edge arrow shown while the grenade is out of view
+    warning: Entity,
+}
+
+/// Blast of a grenade that went off in hand, with nothing in the world to show for it
+#[derive(Component)]
+struct CookOff;
+
+/// Flattened quad under a grenade, scaled to the blast radius
+#[derive(Component)]
+struct DangerCircle;
+
+#[derive(Component)]
+struct GrenadeWarning;
+
+#[derive(Resource)]
+struct GrenadeAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    circle_mesh: Handle<Mesh>,
+    circle_texture: Handle<Image>,
+}
+
+fn setup_grenade_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands.insert_resource(GrenadeAssets {
+        mesh: meshes.add(Sphere::new(GRENADE_RADIUS)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.25, 0.3, 0.2),
+            perceptual_roughness: 0.6,
+            ..default()
+        }),
+        // Unit half-size, scaled up to the blast radius where it's used
+        circle_mesh: meshes.add(Plane3d::new(Vec3::Y, Vec2::ONE)),
+        circle_texture: images.add(danger_circle_image()),
+    });
+}
+
+/// White ring with a faint fill, tinted by the circle's material
+fn danger_circle_image() -> Image {
+    let size = DANGER_TEXTURE_SIZE;
+    let data = (0..size * size)
+        .flat_map(|index| {
+            let x = (index % size) as f32 + 0.5;
+            let y = (index / size) as f32 + 0.5;
+            let r = Vec2::new(x, y).distance(Vec2::splat(size as f32 / 2.0)) / (size as f32 / 2.0);
+            let alpha = if r > 1.0 {
+                0.0
+            } else if r > 0.9 {
+                1.0
+            } else {
+                0.25
+            };
+            [255, 255, 255, (alpha * 255.0) as u8]
+        })
+        .collect();
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// Pull the pin on T, then throw on release, or let it go off in hand if held too long
+fn cook_grenade(
+    mut commands: Commands,
+    time: Res<Time>,
+    difficulty: Res<DifficultyModifiers>,
+    actions: Res<PlayerActions>,
+    assets: Res<GrenadeAssets>,
+    mut stock: ResMut<GrenadeStock>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut feedback: MessageWriter<FeedbackEvent>,
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &PlayerActionState,
+            Option<&mut CookingGrenade>,
+            &mut PlayerHealth,
+            Option<&mut PlayerArmor>,
+            Option<&mut Flinch>,
+        ),
+        With<Player>,
+    >,
+    camera_q: Query<&Transform, With<ThirdPersonCamera>>,
+    rapier_context: ReadRapierContext,
+) {
+    let Ok((entity, transform, action_state, cooking, mut health, mut armor, mut flinch)) =
+        players.single_mut()
+    else {
+        return;
+    };
+
+    let Some(mut cooking) = cooking else {
+        if actions.grenade && stock.remaining > 0 && action_state.state != ActionState::Staggered {
+            stock.remaining -= 1;
+            commands.entity(entity).insert(CookingGrenade {
+                fuse: Timer::from_seconds(GRENADE_FUSE, TimerMode::Once),
+            });
+        }
+        return;
+    };
+
+    cooking.fuse.tick(time.delta());
+    if cooking.fuse.is_finished() {
+        commands.entity(entity).remove::<CookingGrenade>();
+        // The blast itself goes off from the player's hand next pass
+        commands.spawn((
+            Transform::from_translation(transform.translation),
+            CookOff,
+            ExplosionFuse {
+                timer: Timer::from_seconds(0.0, TimerMode::Once),
+                detonated: false,
+            },
+        ));
+        let taken = apply_player_damage(
+            &mut health,
+            armor.as_deref_mut(),
+            flinch.as_deref_mut(),
+            PlayerHit {
+                damage: COOK_OFF_DAMAGE,
+                flinch: EXPLOSION_FLINCH,
+                source: PlayerDamageSource::Explosion,
+                now: time.elapsed_secs_f64(),
+            },
+            &difficulty.burst,
+        );
+        if taken > 0.0 {
+            feedback.write(FeedbackEvent::Damage { amount: taken });
+        }
+        return;
+    }
+
+    // A heavy hit knocks it out of the player's hand
+    if action_state.state == ActionState::Staggered {
+        commands.entity(entity).remove::<CookingGrenade>();
+        let fuse = Timer::new(cooking.fuse.remaining(), TimerMode::Once);
+        let position = at_feet(transform);
+        spawn_grenade(
+            &mut commands,
+            &assets,
+            &mut materials,
+            position,
+            Vec3::ZERO,
+            fuse,
+        );
+        return;
+    }
+
+    if actions.grenade_held {
+        return;
+    }
+    let (Ok(context), Ok(camera_transform)) = (rapier_context.single(), camera_q.single()) else {
+        return;
+    };
+    let (origin, direction) = aim_ray(&context, entity, transform, camera_transform);
+    commands.entity(entity).remove::<CookingGrenade>();
+    let fuse = Timer::new(cooking.fuse.remaining(), TimerMode::Once);
+    // Clear of the player's capsule so the throw doesn't bounce off ourselves
+    spawn_grenade(
+        &mut commands,
+        &assets,
+        &mut materials,
+        origin + direction * 0.8,
+        direction * THROW_SPEED + Vec3::Y * 3.0,
+        fuse,
+    );
+}
+
+/// On the floor just in front of the player, clear of their capsule
+fn at_feet(transform: &Transform) -> Vec3 {
+    let feet = transform.translation - Vec3::Y * (PLAYER_HALF_HEIGHT + PLAYER_RADIUS);
+    feet + *transform.forward() * 0.6 + Vec3::Y * GRENADE_RADIUS
+}
+
+fn spawn_grenade(
+    commands: &mut Commands,
+    assets: &GrenadeAssets,
+    materials: &mut Assets<StandardMaterial>,
+    position: Vec3,
+    velocity: Vec3,
+    fuse: Timer,
+) {
+    let circle = commands
+        .spawn((
+            Mesh3d(assets.circle_mesh.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(1.0, 0.2, 0.1, 0.5),
+                base_color_texture: Some(assets.circle_texture.clone()),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            Transform::from_translation(position),
+            Visibility::Hidden,
+            DangerCircle,
+        ))
+        .id();
+    let warning = commands
+        .spawn((
+            Text::new("!"),
+            TextFont {
+                font_size: WARNING_SIZE,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.3, 0.2)),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            GrenadeWarning,
+        ))
+        .id();
+    commands.spawn((
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(assets.material.clone()),
+        Transform::from_translation(position),
+        Grenade { circle, warning },
+        ExplosionFuse {
+            timer: fuse,
+            detonated: false,
+        },
+        RigidBody::Dynamic,
+        Collider::ball(GRENADE_RADIUS),
+        PhysicsLayer::Projectiles.groups(),
+        Velocity::linear(velocity),
+        Restitution::coefficient(0.3),
+        Damping {
+            linear_damping: 0.5,
+            angular_damping: 1.0,
+        },
+        Ccd::enabled(),
+    ));
+}
+
+/// The blast is dealt with by burn_explosion_fuses; this only tidies up after it
+fn clear_detonated_grenades(
+    mut commands: Commands,
+    grenades: Query<(Entity, Option<&Grenade>, &ExplosionFuse), Or<(With<Grenade>, With<CookOff>)>>,
+) {
+    for (entity, grenade, fuse) in grenades.iter() {
+        if !fuse.detonated {
+            continue;
+        }
+        if let Some(grenade) = grenade {
+            commands.entity(grenade.circle).try_despawn();
+            commands.entity(grenade.warning).try_despawn();
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Lay each nearby grenade's circle on whatever is under it, flashing faster as the
+/// fuse runs down
+fn update_danger_circles(
+    time: Res<Time>,
+    perks: Res<PlayerPerks>,
+    rapier_context: ReadRapierContext,
+    player_q: Query<&Transform, With<Player>>,
+    grenades: Query<(Entity, &Transform, &Grenade, &ExplosionFuse), Without<DangerCircle>>,
+    mut circles: Query<
+        (
+            &mut Transform,
+            &mut Visibility,
+            &MeshMaterial3d<StandardMaterial>,
+        ),
+        (With<DangerCircle>, Without<Player>),
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let (Ok(context), Ok(player)) = (rapier_context.single(), player_q.single()) else {
+        return;
+    };
+    let radius = EXPLOSION_RADIUS * perks.explosion_radius_scale();
+    // Same layer as debris, so only level geometry counts as the ground
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .groups(PhysicsLayer::Debris.groups());
+
+    for (entity, transform, grenade, fuse) in grenades.iter() {
+        let Ok((mut circle, mut visibility, material)) = circles.get_mut(grenade.circle) else {
+            continue;
+        };
+        let near = (transform.translation - player.translation)
+            .with_y(0.0)
+            .length()
+            <= WARNING_RANGE;
+        visibility.set_if_neq(if near {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if !near {
+            continue;
+        }
+
+        let mut ground = transform.translation.y - GRENADE_RADIUS;
+        context.with_query_pipeline(filter.exclude_collider(entity), |query_pipeline| {
+            if let Some((_, distance)) =
+                query_pipeline.cast_ray(transform.translation, Vec3::NEG_Y, 20.0, true)
+            {
+                ground = transform.translation.y - distance;
+            }
+        });
+        // Lifted a hair, above footprints and blood
+        circle.translation = transform.translation.with_y(ground + 0.035);
+        circle.scale = Vec3::splat(radius);
+
+        let left = fuse.timer.remaining_secs();
+        let rate = 3.0 + 9.0 * (1.0 - left / GRENADE_FUSE).max(0.0);
+        let flash = 0.5 + 0.5 * (time.elapsed_secs() * rate).sin();
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color.set_alpha(0.3 + 0.4 * flash);
+        }
+    }
+}
+
+/// Arrow at the screen edge pointing to each nearby grenade the camera can't see
+fn update_grenade_warnings(
+    player_q: Query<&Transform, With<Player>>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<ThirdPersonCamera>>,
+    grenades: Query<(&Transform, &Grenade)>,
+    mut warnings: Query<(&mut Node, &mut Visibility), With<GrenadeWarning>>,
+) {
+    let (Ok(player), Ok((camera, camera_transform))) = (player_q.single(), camera_q.single())
+    else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    for (transform, grenade) in grenades.iter() {
+        let Ok((mut node, mut visibility)) = warnings.get_mut(grenade.warning) else {
+            continue;
+        };
+        let near = (transform.translation - player.translation)
+            .with_y(0.0)
+            .length()
+            <= WARNING_RANGE;
+        let on_screen = camera
+            .world_to_viewport(camera_transform, transform.translation)
+            .is_ok_and(|screen| screen.cmpge(Vec2::ZERO).all() && screen.cmple(viewport).all());
+        if !near || on_screen {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+
+        // Which way the grenade lies across the view, behind the camera or not
+        let local = camera_transform
+            .affine()
+            .inverse()
+            .transform_point3(transform.translation);
+        let direction = Vec2::new(local.x, -local.y).normalize_or(Vec2::Y);
+        let half = viewport / 2.0 - Vec2::splat(WARNING_MARGIN);
+        let reach = (half.x / direction.x.abs()).min(half.y / direction.y.abs());
+        let point = viewport / 2.0 + direction * reach;
+        node.left = Val::Px(point.x - WARNING_SIZE / 4.0);
+        node.top = Val::Px(point.y - WARNING_SIZE / 2.0);
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+fn hide_grenade_warnings(mut warnings: Query<&mut Visibility, With<GrenadeWarning>>) {
+    for mut visibility in warnings.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn drop_cooked_grenade(
+    mut commands: Commands,
+    assets: Res<GrenadeAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    players: Query<(Entity, &Transform, &CookingGrenade), With<Player>>,
+) {
+    for (entity, transform, cooking) in players.iter() {
+        commands.entity(entity).remove::<CookingGrenade>();
+        let fuse = Timer::new(cooking.fuse.remaining(), TimerMode::Once);
+        let position = at_feet(transform);
+        spawn_grenade(
+            &mut commands,
+            &assets,
+            &mut materials,
+            position,
+            Vec3::ZERO,
+            fuse,
+        );
+    }
+}
+
+fn reset_grenades(
+    mut commands: Commands,
+    mut stock: ResMut<GrenadeStock>,
+    grenades: Query<(Entity, &Grenade)>,
+    players: Query<Entity, With<CookingGrenade>>,
+) {
+    *stock = GrenadeStock::default();
+    for (entity, grenade) in grenades.iter() {
+        commands.entity(grenade.circle).try_despawn();
+        commands.entity(grenade.warning).try_despawn();
+        commands.entity(entity).despawn();
+    }
+    for entity in players.iter() {
+        commands.entity(entity).remove::<CookingGrenade>();
+    }
+}
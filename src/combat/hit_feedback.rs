@@ -0,0 +1,337 @@
+use super::{DamageNumberPlugin, HitEvent, HitSystems, ImpactPlugin, ShotFired, TracerPlugin};
+use crate::enemies::{
+    BloodPlugin, HitFlashPlugin, RangeScoringPlugin, TargetDestroyed, ZombieDied,
+};
+use crate::player::{PickupCollected, PickupKind, Player};
+use crate::ui::GameState;
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+/// Everything the player sees and hears when rounds land. Each part reads HitEvent,
+/// ShotFired and the death messages on its own in HitSystems::Feedback, after damage
+/// is applied, so any of them can be disabled without touching the others or the damage.
+pub struct HitFeedbackPlugins;
+
+impl PluginGroup for HitFeedbackPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(HitMarkerPlugin)
+            .add(ImpactPlugin)
+            .add(TracerPlugin)
+            .add(DamageNumberPlugin)
+            .add(HitFlashPlugin)
+            .add(BloodPlugin)
+            .add(RangeScoringPlugin)
+    }
+}
+
+/// The streak counter, hit distance and kill feed in the top right
+pub struct HitMarkerPlugin;
+
+impl Plugin for HitMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitStreak>()
+            .add_systems(OnEnter(GameState::Playing), spawn_streak_hud)
+            .add_systems(OnExit(GameState::Playing), despawn_hit_feedback)
+            .add_systems(
+                Update,
+                (
+                    track_hit_streak,
+                    update_streak_hud,
+                    update_hit_distance,
+                    fade_streak_banner,
+                    push_kill_feed,
+                    expire_kill_feed,
+                )
+                    .chain()
+                    .in_set(HitSystems::Feedback)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Consecutive hits without a miss
+#[derive(Resource)]
+pub struct HitStreak {
+    pub count: u32,
+    /// Streak expires if no shot lands for this long
+    pub idle: Timer,
+}
+
+impl Default for HitStreak {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            idle: Timer::from_seconds(4.0, TimerMode::Once),
+        }
+    }
+}
+
+const STREAK_MILESTONES: [u32; 3] = [5, 10, 20];
+
+// === STREAK HUD (top-right) ===
+
+#[derive(Component)]
+struct StreakHud;
+
+#[derive(Component)]
+struct StreakText;
+
+#[derive(Component)]
+struct HitDistanceText;
+
+/// Column under the streak counter listing recent kills and pickups
+#[derive(Component)]
+struct KillFeed;
+
+#[derive(Component)]
+struct KillFeedEntry {
+    timer: Timer,
+}
+
+const KILL_FEED_LINES: usize = 4;
+
+#[derive(Component)]
+struct StreakBanner {
+    timer: Timer,
+}
+
+fn spawn_streak_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            StreakHud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("STREAK 0"),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                StreakText,
+            ));
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                HitDistanceText,
+            ));
+
+            parent.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::End,
+                    ..default()
+                },
+                KillFeed,
+            ));
+        });
+}
+
+fn despawn_hit_feedback(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<StreakHud>, With<StreakBanner>)>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn track_hit_streak(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shots: MessageReader<ShotFired>,
+    mut streak: ResMut<HitStreak>,
+) {
+    streak.idle.tick(time.delta());
+
+    for shot in shots.read() {
+        if !shot.hit {
+            streak.count = 0;
+            continue;
+        }
+
+        streak.count += 1;
+        streak.idle.reset();
+
+        if STREAK_MILESTONES.contains(&streak.count) {
+            spawn_streak_banner(&mut commands, streak.count);
+        }
+    }
+
+    if streak.idle.is_finished() {
+        streak.count = 0;
+    }
+}
+
+fn spawn_streak_banner(commands: &mut Commands, count: u32) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            StreakBanner {
+                timer: Timer::from_seconds(1.5, TimerMode::Once),
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("{} HIT STREAK!", count)),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.8, 0.2)),
+            ));
+        });
+}
+
+fn update_streak_hud(streak: Res<HitStreak>, mut text_query: Query<&mut Text, With<StreakText>>) {
+    for mut text in text_query.iter_mut() {
+        **text = format!("STREAK {}", streak.count);
+    }
+}
+
+/// Show how far the player's last shot flew, handy on the long-range lane
+fn update_hit_distance(
+    mut hit_events: MessageReader<HitEvent>,
+    players: Query<Entity, With<Player>>,
+    mut text_query: Query<&mut Text, With<HitDistanceText>>,
+) {
+    let Ok(player) = players.single() else {
+        hit_events.clear();
+        return;
+    };
+    let Some(distance) = hit_events
+        .read()
+        .filter(|event| event.source == Some(player) && event.distance > 0.0)
+        .map(|event| event.distance)
+        .last()
+    else {
+        return;
+    };
+
+    for mut text in text_query.iter_mut() {
+        **text = format!("Hit: {:.0}m", distance);
+    }
+}
+
+fn fade_streak_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut StreakBanner, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut banner, children) in banners.iter_mut() {
+        banner.timer.tick(time.delta());
+        if banner.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        for child in children.iter() {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0.set_alpha(1.0 - banner.timer.fraction());
+            }
+        }
+    }
+}
+
+/// Feed lines come purely from lifecycle messages, so this knows nothing about who sent them
+fn push_kill_feed(
+    mut commands: Commands,
+    mut zombie_deaths: MessageReader<ZombieDied>,
+    mut targets_destroyed: MessageReader<TargetDestroyed>,
+    mut pickups: MessageReader<PickupCollected>,
+    feed_q: Query<(Entity, Option<&Children>), With<KillFeed>>,
+) {
+    let mut lines: Vec<(String, Color)> = Vec::new();
+    for event in zombie_deaths.read() {
+        lines.push((
+            format!("{} zombie killed", event.kind.name()),
+            Color::srgb(1.0, 0.5, 0.4),
+        ));
+    }
+    for _ in targets_destroyed.read() {
+        lines.push(("Target destroyed".to_string(), Color::WHITE));
+    }
+    for event in pickups.read() {
+        let name = match event.kind {
+            PickupKind::ArmorPlate => "Armor plate",
+            PickupKind::Weapon(weapon_type) => weapon_type.name(),
+            PickupKind::Attachment(attachment) => attachment.name(),
+            PickupKind::Ammo(ammo) => ammo.name(),
+            PickupKind::CleaningKit => "Cleaning kit",
+            PickupKind::Backpack => "Backpack",
+        };
+        lines.push((format!("+ {}", name), Color::srgb(0.4, 0.7, 1.0)));
+    }
+
+    let Ok((feed, children)) = feed_q.single() else {
+        return;
+    };
+    if lines.is_empty() {
+        return;
+    }
+
+    // Drop the oldest lines to stay within the cap
+    let existing: Vec<Entity> = children.map(|c| c.iter().collect()).unwrap_or_default();
+    let overflow = (existing.len() + lines.len()).saturating_sub(KILL_FEED_LINES);
+    for entity in existing.into_iter().take(overflow) {
+        commands.entity(entity).despawn();
+    }
+
+    let skip = lines.len().saturating_sub(KILL_FEED_LINES);
+    for (line, color) in lines.into_iter().skip(skip) {
+        let entry = commands
+            .spawn((
+                Text::new(line),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(color),
+                KillFeedEntry {
+                    timer: Timer::from_seconds(4.0, TimerMode::Once),
+                },
+            ))
+            .id();
+        commands.entity(feed).add_child(entry);
+    }
+}
+
+fn expire_kill_feed(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut entries: Query<(Entity, &mut KillFeedEntry, &mut TextColor)>,
+) {
+    for (entity, mut entry, mut color) in entries.iter_mut() {
+        entry.timer.tick(time.delta());
+        if entry.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Fade out over the last second
+        color.0.set_alpha(entry.timer.remaining_secs().min(1.0));
+    }
+}
@@ -0,0 +1,320 @@
+use super::{HitSystems, HitZone, Shootable, ShotFired};
+use crate::enemies::Team;
+use crate::ui::{AudioBus, GameState};
+use crate::world::{surface_of, BudgetCategory, Budgeted, SurfaceMaterial};
+use bevy::asset::RenderAssetUsages;
+use bevy::audio::{Pitch, Volume};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::time::Duration;
+
+/// What a round leaves where it strikes the level: a puff of chips or spray, a mark and
+/// a crack, each picked by the SurfaceMaterial of what it hit. Zombies and allies bleed
+/// instead (see BloodPlugin), so they get none of this.
+pub struct ImpactPlugin;
+
+impl Plugin for ImpactPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_impact_assets)
+            .add_systems(
+                Update,
+                (
+                    spawn_impact_effects.in_set(HitSystems::Feedback),
+                    fly_impact_puffs,
+                    age_impact_marks,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_impact_effects,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_impact_effects,
+            );
+    }
+}
+
+const PUFF_LIFETIME: f32 = 0.4;
+const MARK_LIFETIME: f32 = 20.0;
+/// Half-width of an impact mark
+const MARK_SIZE: f32 = 0.12;
+const MARK_TEXTURE_SIZE: u32 = 32;
+const IMPACT_VOLUME: f32 = 0.12;
+
+/// How each surface takes a round
+struct SurfaceImpact {
+    puff_color: Color,
+    puffs: usize,
+    mark_color: Color,
+    /// Frequency and length of the crack
+    tone: (f32, f32),
+}
+
+fn surface_impact(surface: SurfaceMaterial) -> SurfaceImpact {
+    match surface {
+        SurfaceMaterial::Concrete => SurfaceImpact {
+            puff_color: Color::srgba(0.7, 0.7, 0.68, 0.8),
+            puffs: 4,
+            mark_color: Color::srgba(0.15, 0.15, 0.15, 0.9),
+            tone: (320.0, 0.05),
+        },
+        SurfaceMaterial::Wood => SurfaceImpact {
+            puff_color: Color::srgba(0.6, 0.42, 0.2, 0.9),
+            puffs: 3,
+            mark_color: Color::srgba(0.2, 0.12, 0.05, 0.9),
+            tone: (210.0, 0.06),
+        },
+        SurfaceMaterial::Metal => SurfaceImpact {
+            puff_color: Color::srgba(1.0, 0.8, 0.4, 1.0),
+            puffs: 5,
+            mark_color: Color::srgba(0.55, 0.55, 0.6, 0.9),
+            tone: (1400.0, 0.08),
+        },
+        SurfaceMaterial::Dirt => SurfaceImpact {
+            puff_color: Color::srgba(0.35, 0.28, 0.18, 0.8),
+            puffs: 4,
+            mark_color: Color::srgba(0.1, 0.08, 0.05, 0.8),
+            tone: (120.0, 0.07),
+        },
+        SurfaceMaterial::Water => SurfaceImpact {
+            puff_color: Color::srgba(0.5, 0.9, 0.4, 0.7),
+            puffs: 6,
+            mark_color: Color::srgba(0.6, 1.0, 0.5, 0.6),
+            tone: (520.0, 0.1),
+        },
+    }
+}
+
+#[derive(Component)]
+struct ImpactPuff {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+struct ImpactMark {
+    age: Timer,
+}
+
+/// Per-surface assets, indexed by SurfaceMaterial::index
+#[derive(Resource)]
+struct ImpactAssets {
+    puff_mesh: Handle<Mesh>,
+    mark_mesh: Handle<Mesh>,
+    puffs: Vec<Handle<StandardMaterial>>,
+    marks: Vec<Handle<StandardMaterial>>,
+    /// Synthesized, like the stinger; the game ships no audio files
+    sounds: Vec<Handle<Pitch>>,
+}
+
+fn setup_impact_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    let mut puffs = Vec::new();
+    let mut marks = Vec::new();
+    let mut sounds = Vec::new();
+    for surface in SurfaceMaterial::ALL {
+        let impact = surface_impact(surface);
+        puffs.push(materials.add(StandardMaterial {
+            base_color: impact.puff_color,
+            emissive: if surface == SurfaceMaterial::Metal {
+                LinearRgba::rgb(3.0, 2.0, 0.6)
+            } else {
+                LinearRgba::BLACK
+            },
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }));
+        marks.push(materials.add(StandardMaterial {
+            base_color: impact.mark_color,
+            base_color_texture: Some(images.add(impact_mark_image(surface))),
+            alpha_mode: AlphaMode::Blend,
+            perceptual_roughness: 1.0,
+            ..default()
+        }));
+        let (frequency, seconds) = impact.tone;
+        sounds.push(pitches.add(Pitch::new(frequency, Duration::from_secs_f32(seconds))));
+    }
+
+    commands.insert_resource(ImpactAssets {
+        puff_mesh: meshes.add(Sphere::new(0.04)),
+        mark_mesh: meshes.add(Plane3d::new(Vec3::Y, Vec2::splat(MARK_SIZE))),
+        puffs,
+        marks,
+        sounds,
+    });
+}
+
+/// White mark shaped for the surface, tinted by its material: a chipped hole in
+/// concrete, a splintered gouge in wood, a bright dent in metal, a soft blotch in dirt
+/// and a ripple on water
+fn impact_mark_image(surface: SurfaceMaterial) -> Image {
+    let size = MARK_TEXTURE_SIZE;
+    let data = (0..size * size)
+        .flat_map(|index| {
+            let x = ((index % size) as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let y = ((index / size) as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+            let r = Vec2::new(x, y).length();
+            let angle = y.atan2(x);
+            let alpha = match surface {
+                SurfaceMaterial::Concrete => {
+                    let edge = 0.7 + 0.2 * (angle * 5.0).sin() * (angle * 3.0).cos();
+                    if r < 0.3 {
+                        1.0
+                    } else {
+                        (1.0 - (r - 0.3) / (edge - 0.3)).clamp(0.0, 1.0) * 0.6
+                    }
+                }
+                SurfaceMaterial::Wood => {
+                    let gouge = (x * 3.0).powi(2) + (y * 1.1).powi(2);
+                    (1.0 - gouge).clamp(0.0, 1.0)
+                }
+                SurfaceMaterial::Metal => {
+                    if r < 0.35 {
+                        0.9
+                    } else {
+                        (1.0 - (r - 0.35) / 0.2).clamp(0.0, 1.0) * 0.5
+                    }
+                }
+                SurfaceMaterial::Dirt => (1.0 - r).clamp(0.0, 1.0).powi(2),
+                SurfaceMaterial::Water => {
+                    let ring = 1.0 - ((r - 0.75).abs() / 0.12);
+                    ring.clamp(0.0, 1.0)
+                }
+            };
+            [255, 255, 255, (alpha * 255.0) as u8]
+        })
+        .collect();
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// A puff, a mark and a crack wherever a round stopped on the level. Shotgun pellets
+/// land together, so each surface cracks at most once a frame.
+fn spawn_impact_effects(
+    mut commands: Commands,
+    assets: Res<ImpactAssets>,
+    mut shots: MessageReader<ShotFired>,
+    surfaces: Query<&SurfaceMaterial>,
+    parents: Query<&ChildOf>,
+    zones: Query<&HitZone>,
+    teams: Query<(), With<Team>>,
+    shootables: Query<(), With<Shootable>>,
+) {
+    let mut cracked = [false; SurfaceMaterial::ALL.len()];
+    for shot in shots.read() {
+        let (Some(point), Some((entity, normal))) = (shot.point, shot.struck) else {
+            continue;
+        };
+        let body = zones.get(entity).map_or(entity, |zone| zone.owner);
+        if teams.contains(body) {
+            continue;
+        }
+        let surface = surface_of(entity, &surfaces, &parents);
+        let impact = surface_impact(surface);
+        let index = surface.index();
+        let normal = normal.try_normalize().unwrap_or(Vec3::Y);
+
+        // Fanned out around the normal, evenly so no GameRng draw is spent on looks
+        let side = normal.any_orthonormal_vector();
+        for puff in 0..impact.puffs {
+            let angle = puff as f32 / impact.puffs as f32 * std::f32::consts::TAU;
+            let spread = Quat::from_axis_angle(normal, angle) * side;
+            commands.spawn((
+                Mesh3d(assets.puff_mesh.clone()),
+                MeshMaterial3d(assets.puffs[index].clone()),
+                Transform::from_translation(point + normal * 0.05),
+                ImpactPuff {
+                    velocity: normal * 2.0 + spread * 1.2,
+                    lifetime: Timer::from_seconds(PUFF_LIFETIME, TimerMode::Once),
+                },
+                Budgeted(BudgetCategory::Debris),
+            ));
+        }
+
+        // Crates and barrels break, and would leave their marks hanging in the air.
+        // Lifted a hair off the surface so it doesn't flicker.
+        if !shootables.contains(entity) {
+            commands.spawn((
+                Mesh3d(assets.mark_mesh.clone()),
+                MeshMaterial3d(assets.marks[index].clone()),
+                Transform::from_translation(point + normal * 0.01)
+                    .with_rotation(Quat::from_rotation_arc(Vec3::Y, normal)),
+                ImpactMark {
+                    age: Timer::from_seconds(MARK_LIFETIME, TimerMode::Once),
+                },
+                Budgeted(BudgetCategory::Decal),
+            ));
+        }
+
+        if !cracked[index] {
+            cracked[index] = true;
+            commands.spawn((
+                AudioPlayer(assets.sounds[index].clone()),
+                PlaybackSettings::DESPAWN.with_volume(Volume::Linear(IMPACT_VOLUME)),
+                AudioBus::Sfx,
+            ));
+        }
+    }
+}
+
+fn fly_impact_puffs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puffs: Query<(Entity, &mut Transform, &mut ImpactPuff)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut transform, mut puff) in puffs.iter_mut() {
+        puff.lifetime.tick(time.delta());
+        if puff.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        puff.velocity.y -= 6.0 * dt;
+        transform.translation += puff.velocity * dt;
+        transform.scale = Vec3::splat(1.0 - puff.lifetime.fraction());
+    }
+}
+
+fn age_impact_marks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut marks: Query<(Entity, &mut ImpactMark)>,
+) {
+    for (entity, mut mark) in marks.iter_mut() {
+        if mark.age.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn despawn_impact_effects(
+    mut commands: Commands,
+    effects: Query<Entity, Or<(With<ImpactPuff>, With<ImpactMark>)>>,
+) {
+    for entity in effects.iter() {
+        commands.entity(entity).despawn();
+    }
+}
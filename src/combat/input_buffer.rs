@@ -0,0 +1,93 @@
+use crate::player::{BulletTime, PlayerActions};
+use bevy::prelude::*;
+
+/// Seconds a press is held on to by default, see InputBufferSettings
+pub const INPUT_BUFFER_WINDOW: f32 = 0.25;
+
+/// How long a blocked fire, reload or weapon switch press waits to go through
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct InputBufferSettings {
+    /// Seconds; 0.0 turns buffering off, so presses only count on the frame they land
+    pub window: f32,
+}
+
+impl Default for InputBufferSettings {
+    fn default() -> Self {
+        Self {
+            window: INPUT_BUFFER_WINDOW,
+        }
+    }
+}
+
+/// A combat press that can wait for whatever is blocking it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BufferedAction {
+    Fire,
+    Reload,
+    SwitchTo(usize),
+    /// Scroll wheel, towards the next slot or the previous one
+    Cycle {
+        next: bool,
+    },
+}
+
+/// The player's latest combat press, held until the systems that act on it can, or
+/// until it's older than the window. Only one is kept: pressing something else
+/// replaces it, so a queued reload is dropped the moment the player fires instead.
+#[derive(Resource, Default)]
+pub struct InputBuffer {
+    pending: Option<(BufferedAction, f64)>,
+}
+
+impl InputBuffer {
+    pub fn pending(&self) -> Option<BufferedAction> {
+        self.pending.map(|(action, _)| action)
+    }
+
+    /// Whether `action` is waiting to go through
+    pub fn has(&self, action: BufferedAction) -> bool {
+        self.pending() == Some(action)
+    }
+
+    /// Drop the pending press, once it's gone through or been cancelled
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// Take this frame's presses into the buffer, newest last, and forget stale ones.
+/// Runs on the player's clock, so bullet time doesn't stretch the window.
+pub(super) fn buffer_combat_inputs(
+    actions: Res<PlayerActions>,
+    bullet_time: Res<BulletTime>,
+    settings: Res<InputBufferSettings>,
+    mut buffer: ResMut<InputBuffer>,
+) {
+    let now = bullet_time.player_elapsed;
+    if let Some((_, pressed_at)) = buffer.pending {
+        if now - pressed_at > settings.window as f64 {
+            buffer.clear();
+        }
+    }
+
+    let pressed = if let Some(slot) = actions.select_slot {
+        Some(BufferedAction::SwitchTo(slot))
+    } else if actions.scroll != 0.0 {
+        Some(BufferedAction::Cycle {
+            next: actions.scroll < 0.0,
+        })
+    } else if actions.reload {
+        Some(BufferedAction::Reload)
+    } else if actions.fire_pressed {
+        Some(BufferedAction::Fire)
+    } else {
+        None
+    };
+    if let Some(action) = pressed {
+        buffer.pending = Some((action, now));
+    }
+}
+
+pub(super) fn clear_input_buffer(mut buffer: ResMut<InputBuffer>) {
+    buffer.clear();
+}
@@ -1,5 +1,39 @@
+mod ammo;
+mod attachments;
+mod chain_lightning;
+mod condition;
+mod damage_numbers;
+mod flare;
+mod grenade;
+mod hit_feedback;
+mod impacts;
+mod input_buffer;
+mod pickup_highlight;
+mod recoil;
 mod shooting;
+mod status_effects;
+mod target_highlight;
+mod tracers;
+mod weapon_pickup;
 mod weapon_ui;
+mod weapon_wheel;
 
+pub use ammo::*;
+pub use attachments::*;
+pub use chain_lightning::*;
+pub use condition::*;
+pub use damage_numbers::*;
+pub use flare::*;
+pub use grenade::*;
+pub use hit_feedback::*;
+pub use impacts::*;
+pub use input_buffer::*;
+pub use pickup_highlight::*;
+pub use recoil::*;
 pub use shooting::*;
+pub use status_effects::*;
+pub use target_highlight::*;
+pub use tracers::*;
+pub use weapon_pickup::*;
 pub use weapon_ui::*;
+pub use weapon_wheel::*;
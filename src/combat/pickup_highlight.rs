@@ -0,0 +1,264 @@
+use crate::player::Player;
+use crate::ui::GameState;
+use bevy::prelude::*;
+use bevy::render::render_resource::Face;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+/// Makes items lying in the world easier to spot on a dark floor. Every pickup
+/// category's shared material pulses, anything within BEAM_RANGE of the player gets a
+/// thin beam of light rising from it, and the nearest Interactable gets a brighter
+/// outline beating in time with its prompt. Only the per-category materials are
+/// animated, never one per item.
+pub struct PickupHighlightPlugin;
+
+impl Plugin for PickupHighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickupHighlightSettings>()
+            .init_resource::<PickupHighlights>()
+            .add_systems(Startup, setup_highlight_assets)
+            .add_systems(
+                Update,
+                (
+                    register_pickup_categories,
+                    pulse_pickup_materials.run_if(in_state(GameState::Playing)),
+                    update_pickup_beams,
+                    update_interactable_focus,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Items further away than this have no beam
+const BEAM_RANGE: f32 = 6.0;
+const BEAM_HEIGHT: f32 = 2.5;
+const BEAM_RADIUS: f32 = 0.03;
+/// Pulses per second
+const PULSE_RATE: f32 = 0.8;
+/// How far the emissive swings either side of a category's own
+const PULSE_DEPTH: f32 = 0.35;
+/// How much bigger the focus outline is than the item
+const FOCUS_OUTLINE_SCALE: f32 = 1.15;
+
+/// Item lying in the world that's collected by walking over it
+#[derive(Component, Default)]
+pub struct Pickup;
+
+/// Item lying in the world the player acts on with E
+#[derive(Component, Default)]
+pub struct Interactable;
+
+/// Off on low-end machines, since every beam is its own transparent draw
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PickupHighlightSettings {
+    pub beams: bool,
+}
+
+impl Default for PickupHighlightSettings {
+    fn default() -> Self {
+        Self { beams: true }
+    }
+}
+
+/// 0 to 1 and back, shared by every highlight and the interaction prompt so they beat
+/// together
+pub fn highlight_pulse(elapsed: f32) -> f32 {
+    0.5 - 0.5 * (elapsed * PULSE_RATE * TAU).cos()
+}
+
+struct CategoryMaterials {
+    /// The category's own emissive, which the pulse swings around
+    emissive: LinearRgba,
+    beam: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Default)]
+struct PickupHighlights {
+    /// Keyed by the material a category's items share
+    categories: HashMap<AssetId<StandardMaterial>, CategoryMaterials>,
+    /// Beam rising from each item close enough to have one
+    beams: HashMap<Entity, Entity>,
+    /// Nearest interactable and the outline around it
+    focus: Option<(Entity, Entity)>,
+}
+
+#[derive(Resource)]
+struct HighlightAssets {
+    beam_mesh: Handle<Mesh>,
+    focus_material: Handle<StandardMaterial>,
+}
+
+const FOCUS_EMISSIVE: LinearRgba = LinearRgba::rgb(1.0, 0.9, 0.5);
+
+fn setup_highlight_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(HighlightAssets {
+        beam_mesh: meshes.add(Cylinder::new(BEAM_RADIUS, BEAM_HEIGHT)),
+        focus_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.9, 0.5),
+            emissive: FOCUS_EMISSIVE,
+            unlit: true,
+            // Only the inside of the shell shows, as a rim around the item
+            cull_mode: Some(Face::Front),
+            ..default()
+        }),
+    });
+}
+
+/// Give each material items turn up with a beam of its own colour
+fn register_pickup_categories(
+    mut highlights: ResMut<PickupHighlights>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    items: Query<&MeshMaterial3d<StandardMaterial>, Or<(Added<Pickup>, Added<Interactable>)>>,
+) {
+    for material in items.iter() {
+        if highlights.categories.contains_key(&material.id()) {
+            continue;
+        }
+        let Some(shared) = materials.get(&material.0) else {
+            continue;
+        };
+        let emissive = shared.emissive;
+        let color = shared.base_color;
+        let beam = materials.add(StandardMaterial {
+            base_color: color.with_alpha(0.3),
+            emissive: color.to_linear() * 1.5,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        });
+        highlights
+            .categories
+            .insert(material.id(), CategoryMaterials { emissive, beam });
+    }
+}
+
+/// One write per category, however many of its items are lying around
+fn pulse_pickup_materials(
+    time: Res<Time>,
+    highlights: Res<PickupHighlights>,
+    assets: Res<HighlightAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let pulse = highlight_pulse(time.elapsed_secs());
+    let swing = 1.0 + PULSE_DEPTH * (2.0 * pulse - 1.0);
+    for (id, category) in highlights.categories.iter() {
+        if let Some(material) = materials.get_mut(*id) {
+            material.emissive = category.emissive * swing;
+        }
+        if let Some(beam) = materials.get_mut(&category.beam) {
+            beam.base_color = beam.base_color.with_alpha(0.15 + 0.25 * pulse);
+        }
+    }
+    if let Some(focus) = materials.get_mut(&assets.focus_material) {
+        focus.emissive = FOCUS_EMISSIVE * (1.0 + 2.0 * pulse);
+    }
+}
+
+/// Put up beams on items coming into range and take them down from items that have
+/// gone out of range or been collected
+fn update_pickup_beams(
+    mut commands: Commands,
+    settings: Res<PickupHighlightSettings>,
+    assets: Res<HighlightAssets>,
+    mut highlights: ResMut<PickupHighlights>,
+    players: Query<&Transform, With<Player>>,
+    items: Query<
+        (Entity, &Transform, &MeshMaterial3d<StandardMaterial>),
+        Or<(With<Pickup>, With<Interactable>)>,
+    >,
+) {
+    let player = players
+        .single()
+        .ok()
+        .map(|transform| transform.translation)
+        .filter(|_| settings.beams);
+    let in_range = |position: Vec3| {
+        player.is_some_and(|player| (position - player).with_y(0.0).length() <= BEAM_RANGE)
+    };
+
+    highlights.beams.retain(|item, beam| {
+        let keep = items
+            .get(*item)
+            .is_ok_and(|(_, transform, _)| in_range(transform.translation));
+        if !keep {
+            commands.entity(*beam).try_despawn();
+        }
+        keep
+    });
+
+    for (item, transform, material) in items.iter() {
+        if highlights.beams.contains_key(&item) || !in_range(transform.translation) {
+            continue;
+        }
+        let Some(beam_material) = highlights
+            .categories
+            .get(&material.id())
+            .map(|category| category.beam.clone())
+        else {
+            continue;
+        };
+        let beam = commands
+            .spawn((
+                Mesh3d(assets.beam_mesh.clone()),
+                MeshMaterial3d(beam_material),
+                Transform::from_translation(transform.translation.with_y(BEAM_HEIGHT / 2.0)),
+            ))
+            .id();
+        highlights.beams.insert(item, beam);
+    }
+}
+
+/// Keep the focus outline on whichever interactable is nearest, if any is in range
+fn update_interactable_focus(
+    mut commands: Commands,
+    assets: Res<HighlightAssets>,
+    mut highlights: ResMut<PickupHighlights>,
+    players: Query<&Transform, With<Player>>,
+    interactables: Query<(Entity, &Transform, &Mesh3d), With<Interactable>>,
+    mut outlines: Query<&mut Transform, (Without<Interactable>, Without<Player>)>,
+) {
+    let nearest = players.single().ok().and_then(|player| {
+        interactables
+            .iter()
+            .map(|(entity, transform, _)| {
+                let distance = (transform.translation - player.translation)
+                    .with_y(0.0)
+                    .length();
+                (entity, distance)
+            })
+            .filter(|(_, distance)| *distance <= BEAM_RANGE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    });
+
+    if highlights.focus.map(|(item, _)| item) != nearest {
+        if let Some((_, outline)) = highlights.focus.take() {
+            commands.entity(outline).try_despawn();
+        }
+        if let Some((item, _, mesh)) = nearest.and_then(|item| interactables.get(item).ok()) {
+            let outline = commands
+                .spawn((
+                    Mesh3d(mesh.0.clone()),
+                    MeshMaterial3d(assets.focus_material.clone()),
+                    Transform::default(),
+                ))
+                .id();
+            highlights.focus = Some((item, outline));
+        }
+    }
+
+    // Follows the item as it spins
+    let Some((item, outline)) = highlights.focus else {
+        return;
+    };
+    if let (Ok((_, item_transform, _)), Ok(mut outline_transform)) =
+        (interactables.get(item), outlines.get_mut(outline))
+    {
+        *outline_transform = item_transform.with_scale(Vec3::splat(FOCUS_OUTLINE_SCALE));
+    }
+}
@@ -0,0 +1,47 @@
+use super::Weapon;
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Camera kick a weapon plays through during sustained fire. The camera takes it
+/// through ThirdPersonCamera::add_recoil and recovers on its own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecoilPattern {
+    /// Kick per round in order, in radians: x turns the view right, y lifts it. Past
+    /// the end of the table the last kick repeats.
+    pub kicks: &'static [Vec2],
+    /// Random kick in radians added on top of each step, on both axes
+    pub jitter: f32,
+    /// Seconds without firing after which the pattern starts over
+    pub reset_after: f32,
+}
+
+impl RecoilPattern {
+    /// No kick at all
+    pub const NONE: RecoilPattern = RecoilPattern {
+        kicks: &[],
+        jitter: 0.0,
+        reset_after: 0.0,
+    };
+}
+
+impl Weapon {
+    /// Kick for a round fired at `now`: the next step of the pattern, restarted after a
+    /// pause, plus a little noise. Call before updating `last_shot`.
+    pub fn recoil_kick(&mut self, now: f64, rng: &mut impl Rng) -> Vec2 {
+        let pattern = self.recoil;
+        if pattern.kicks.is_empty() {
+            return Vec2::ZERO;
+        }
+        let paused = self
+            .last_shot
+            .is_none_or(|last| now - last > pattern.reset_after as f64);
+        if paused {
+            self.recoil_shot = 0;
+        }
+
+        let kick = pattern.kicks[self.recoil_shot.min(pattern.kicks.len() - 1)];
+        self.recoil_shot += 1;
+        let noise = Vec2::new(rng.random_range(-1.0..=1.0), rng.random_range(-1.0..=1.0));
+        kick + noise * pattern.jitter
+    }
+}
@@ -1,6 +1,19 @@
-use crate::player::Player;
-use crate::ui::GameState;
-use bevy::input::mouse::AccumulatedMouseScroll;
+use super::{
+    buffer_combat_inputs, clear_input_buffer, compute_effective_stats, roll_jam, AmmoModifiers,
+    AmmoType, Attachment, BufferedAction, InputBuffer, InputBufferSettings, RecoilPattern,
+    AMMO_TYPES, ATTACHMENT_SLOTS,
+};
+use crate::enemies::{is_headshot, Zombie};
+use crate::player::{
+    update_action_state, BulletTime, CameraShake, DeathCamera, Flinch, Inspecting, Player,
+    PlayerActionState, PlayerActions, PlayerPerks, ThirdPersonCamera, PLAYER_MUZZLE_HEIGHT,
+};
+use crate::ui::{DifficultyModifiers, GameState, Subtitle};
+use crate::world::{
+    surface_of, BalanceData, GameRng, Interpolated, PhysicsLayer, SurfaceMaterial, WeaponBalance,
+};
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use rand::Rng;
@@ -9,22 +22,47 @@ pub struct ShootingPlugin;
 
 impl Plugin for ShootingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_message::<HitEvent>()
+        app.init_resource::<AimRay>()
+            .init_resource::<InputBuffer>()
+            .init_resource::<InputBufferSettings>()
+            .add_message::<HitEvent>()
+            .add_message::<ShotFired>()
+            .configure_sets(Update, HitSystems::Damage.before(HitSystems::Feedback))
+            .add_systems(Startup, setup_projectile_assets)
             .add_systems(
                 Update,
                 (
+                    update_aim_ray,
+                    buffer_combat_inputs,
+                    update_action_state,
                     handle_weapon_switch,
                     handle_reload_input,
                     process_reload,
                     process_burst,
+                    process_charge,
                     shoot,
-                    update_shoot_cooldown,
                 )
                     .chain()
-                    .run_if(in_state(GameState::Playing)),
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(any_with_component::<DeathCamera>)),
+            )
+            // Nothing carries over a pause or a death
+            .add_systems(OnExit(GameState::Playing), clear_input_buffer)
+            .add_systems(
+                Update,
+                clear_input_buffer.run_if(any_with_component::<DeathCamera>),
             )
-            // Debug rays should always clean up, even when paused
-            .add_systems(Update, update_debug_rays);
+            .add_systems(
+                FixedUpdate,
+                update_projectiles.run_if(in_state(GameState::Playing)),
+            )
+            // Layered on top of whatever movement the character chose this tick
+            .add_systems(
+                FixedPostUpdate,
+                apply_knockback
+                    .before(PhysicsSet::SyncBackend)
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
@@ -40,15 +78,31 @@ pub enum WeaponType {
     Smg,
     Rifle,
     Shotgun,
+    Marksman,
+    Railgun,
+    Arc,
 }
 
 impl WeaponType {
+    pub const ALL: [WeaponType; 7] = [
+        WeaponType::Pistol,
+        WeaponType::Smg,
+        WeaponType::Rifle,
+        WeaponType::Shotgun,
+        WeaponType::Marksman,
+        WeaponType::Railgun,
+        WeaponType::Arc,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             WeaponType::Pistol => "PISTOL",
             WeaponType::Smg => "SMG",
             WeaponType::Rifle => "RIFLE",
             WeaponType::Shotgun => "SHOTGUN",
+            WeaponType::Marksman => "MARKSMAN",
+            WeaponType::Railgun => "RAILGUN",
+            WeaponType::Arc => "ARC",
         }
     }
 }
@@ -60,6 +114,7 @@ pub enum FireMode {
     SemiAuto,
     FullAuto,
     Burst(u8), // Number of shots per burst
+    Charge,    // Hold to charge, release to fire
 }
 
 impl FireMode {
@@ -68,6 +123,7 @@ impl FireMode {
             FireMode::SemiAuto => "Semi-Auto",
             FireMode::FullAuto => "Full-Auto",
             FireMode::Burst(_) => "Burst",
+            FireMode::Charge => "Charge",
         }
     }
 }
@@ -83,79 +139,162 @@ pub struct Weapon {
     pub spread: f32,    // Spread angle in radians
     pub magazine_size: u32,
     pub current_ammo: u32,
-    pub reserve_ammo: u32,
+    /// Spare rounds of each AmmoType, indexed by AmmoType::index
+    pub reserves: [u32; AMMO_TYPES],
     pub reload_time: f32, // Seconds
+    /// None for flat hitscan, Some to fire Projectile entities that drop over distance
+    pub ballistics: Option<Ballistics>,
+    /// Player-clock time (see BulletTime) of the last round fired, None if never fired
+    pub last_shot: Option<f64>,
+    /// Fitted attachments; read stats through compute_effective_stats, which also applies perks
+    pub attachments: [Option<Attachment>; ATTACHMENT_SLOTS],
+    /// Type of the rounds in the magazine
+    pub loaded_ammo: AmmoType,
+    /// Type the next reload loads, cycled with B
+    pub selected_ammo: AmmoType,
+    /// Some for weapons whose hits arc on to nearby zombies (see ChainLightningPlugin)
+    pub chain: Option<ChainLightning>,
+    /// Camera kick through sustained fire, read by recoil_kick
+    pub recoil: RecoilPattern,
+    /// Rounds into the recoil pattern since it last started over
+    pub recoil_shot: usize,
+    /// 1.0 when clean; wears down with every round on difficulties with weapon wear
+    pub condition: f32,
+    /// Won't fire until the jam is cleared (see WeaponConditionPlugin)
+    pub jammed: bool,
+    /// Set by a reload and cleared by the next round, which never jams
+    pub fresh_magazine: bool,
 }
 
-impl Default for Weapon {
-    fn default() -> Self {
-        Self::pistol()
-    }
+/// How a chain weapon's hit jumps between zombies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChainLightning {
+    /// Extra zombies after the one hit
+    pub links: u8,
+    /// Share of the previous link's damage each jump deals
+    pub falloff: f32,
+    /// Furthest a jump reaches, in metres
+    pub radius: f32,
+}
+
+/// Flight parameters for weapons that fire physical projectiles
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ballistics {
+    pub muzzle_velocity: f32, // Metres per second
+    pub gravity: f32,         // Downward acceleration, metres per second squared
 }
 
+/// Climbs, drifts right, then swings back left and settles
+const SMG_RECOIL: [Vec2; 12] = [
+    Vec2::new(0.0, 0.012),
+    Vec2::new(0.001, 0.012),
+    Vec2::new(0.002, 0.011),
+    Vec2::new(0.003, 0.010),
+    Vec2::new(0.004, 0.009),
+    Vec2::new(0.003, 0.008),
+    Vec2::new(0.0, 0.007),
+    Vec2::new(-0.003, 0.006),
+    Vec2::new(-0.005, 0.005),
+    Vec2::new(-0.005, 0.004),
+    Vec2::new(-0.002, 0.004),
+    Vec2::new(0.002, 0.004),
+];
+
+/// Hard climb through a burst with a slight pull left
+const RIFLE_RECOIL: [Vec2; 6] = [
+    Vec2::new(0.0, 0.018),
+    Vec2::new(-0.002, 0.016),
+    Vec2::new(-0.003, 0.014),
+    Vec2::new(-0.002, 0.012),
+    Vec2::new(0.001, 0.010),
+    Vec2::new(0.002, 0.010),
+];
+
 impl Weapon {
-    /// Create a pistol - Semi-auto, reliable damage
-    pub fn pistol() -> Self {
-        Self {
-            weapon_type: WeaponType::Pistol,
+    /// A fresh, full weapon of the given type. Damage, rate, magazine and the other
+    /// numbers come from the balance file; fire mode, recoil and the like are the
+    /// weapon's character and stay here.
+    pub fn new(weapon_type: WeaponType, balance: &BalanceData) -> Self {
+        let stats = balance.weapon(weapon_type);
+        let mut weapon = Self {
+            weapon_type,
             fire_mode: FireMode::SemiAuto,
-            damage: 15.0,
-            fire_rate: 3.0,
-            pellets: 1,
-            spread: 0.0,
-            magazine_size: 12,
-            current_ammo: 12,
-            reserve_ammo: 48,
-            reload_time: 1.5,
-        }
-    }
-
-    /// Create an SMG - Full-auto, high fire rate, low damage
-    pub fn smg() -> Self {
-        Self {
-            weapon_type: WeaponType::Smg,
-            fire_mode: FireMode::FullAuto,
-            damage: 8.0,
-            fire_rate: 10.0,
-            pellets: 1,
-            spread: 0.02,
-            magazine_size: 30,
-            current_ammo: 30,
-            reserve_ammo: 120,
-            reload_time: 2.0,
+            damage: stats.damage,
+            fire_rate: stats.fire_rate,
+            pellets: stats.pellets,
+            spread: stats.spread,
+            magazine_size: stats.magazine_size,
+            current_ammo: stats.magazine_size,
+            reserves: stats.reserves,
+            reload_time: stats.reload_time,
+            ballistics: None,
+            last_shot: None,
+            attachments: [None; ATTACHMENT_SLOTS],
+            loaded_ammo: AmmoType::Standard,
+            selected_ammo: AmmoType::Standard,
+            chain: None,
+            recoil: RecoilPattern::NONE,
+            recoil_shot: 0,
+            condition: 1.0,
+            jammed: false,
+            fresh_magazine: false,
+        };
+        match weapon_type {
+            // Semi-auto, reliable damage; and semi-auto pellets with spread
+            WeaponType::Pistol | WeaponType::Shotgun => {}
+            // Full-auto, high fire rate, low damage
+            WeaponType::Smg => {
+                weapon.fire_mode = FireMode::FullAuto;
+                weapon.recoil = RecoilPattern {
+                    kicks: &SMG_RECOIL,
+                    jitter: 0.002,
+                    reset_after: 0.3,
+                };
+            }
+            // 3-round burst, high damage
+            WeaponType::Rifle => {
+                weapon.fire_mode = FireMode::Burst(3);
+                weapon.recoil = RecoilPattern {
+                    kicks: &RIFLE_RECOIL,
+                    jitter: 0.001,
+                    reset_after: 0.4,
+                };
+            }
+            // Semi-auto, hard hitting, ballistic drop
+            WeaponType::Marksman => {
+                weapon.ballistics = Some(Ballistics {
+                    muzzle_velocity: 150.0,
+                    gravity: 9.81,
+                });
+            }
+            // Hold to charge, one piercing shot scaled by charge
+            WeaponType::Railgun => weapon.fire_mode = FireMode::Charge,
+            // Semi-auto, each hit jumps on to nearby zombies
+            WeaponType::Arc => {
+                weapon.chain = Some(ChainLightning {
+                    links: 3,
+                    falloff: 0.6,
+                    radius: 4.0,
+                });
+            }
         }
+        weapon
     }
 
-    /// Create a rifle - 3-round burst, high damage
-    pub fn rifle() -> Self {
-        Self {
-            weapon_type: WeaponType::Rifle,
-            fire_mode: FireMode::Burst(3),
-            damage: 35.0,
-            fire_rate: 2.0,
-            pellets: 1,
-            spread: 0.01,
-            magazine_size: 20,
-            current_ammo: 20,
-            reserve_ammo: 60,
-            reload_time: 2.5,
-        }
-    }
-
-    /// Create a shotgun - Semi-auto, 6 pellets with spread
-    pub fn shotgun() -> Self {
-        Self {
-            weapon_type: WeaponType::Shotgun,
-            fire_mode: FireMode::SemiAuto,
-            damage: 12.0, // Per pellet
-            fire_rate: 1.0,
-            pellets: 6,
-            spread: 0.15, // Wide spread
-            magazine_size: 6,
-            current_ammo: 6,
-            reserve_ammo: 24,
-            reload_time: 2.5,
-        }
+    /// Takes new balance numbers without touching what's fitted or worn. A magazine
+    /// holding more than the new size puts the extra rounds back in their reserve.
+    pub fn apply_balance(&mut self, stats: &WeaponBalance, perks: &PlayerPerks) {
+        self.damage = stats.damage;
+        self.fire_rate = stats.fire_rate;
+        self.pellets = stats.pellets;
+        self.spread = stats.spread;
+        self.magazine_size = stats.magazine_size;
+        self.reload_time = stats.reload_time;
+
+        let magazine = compute_effective_stats(self, perks).magazine_size;
+        let excess = self.current_ammo.saturating_sub(magazine);
+        self.current_ammo -= excess;
+        *self.reserve_mut(self.loaded_ammo) += excess;
     }
 
     /// Check if magazine is empty
@@ -163,64 +302,174 @@ impl Weapon {
         self.current_ammo == 0
     }
 
-    /// Check if can reload (has reserve ammo and not full)
-    pub fn can_reload(&self) -> bool {
-        self.reserve_ammo > 0 && self.current_ammo < self.magazine_size
+    /// Check if can reload (has reserve of the selected type, and the magazine is
+    /// either not full or holds another type)
+    pub fn can_reload(&self, perks: &PlayerPerks) -> bool {
+        self.reserve(self.selected_ammo) > 0
+            && (self.selected_ammo != self.loaded_ammo
+                || self.current_ammo < compute_effective_stats(self, perks).magazine_size)
+    }
+
+    /// Move rounds of the selected type from reserve into the magazine, returning how
+    /// many were loaded. Rounds of another type go back to their own reserve first.
+    pub fn reload(&mut self, perks: &PlayerPerks) -> u32 {
+        if self.selected_ammo != self.loaded_ammo {
+            let unloaded = std::mem::take(&mut self.current_ammo);
+            *self.reserve_mut(self.loaded_ammo) += unloaded;
+            self.loaded_ammo = self.selected_ammo;
+        }
+        let needed = compute_effective_stats(self, perks)
+            .magazine_size
+            .saturating_sub(self.current_ammo);
+        let loaded = self.reserve(self.loaded_ammo).min(needed);
+        self.current_ammo += loaded;
+        *self.reserve_mut(self.loaded_ammo) -= loaded;
+        self.fresh_magazine = true;
+        loaded
     }
 
     /// Get cooldown duration between shots
     pub fn shot_cooldown(&self) -> f32 {
         1.0 / self.fire_rate
     }
+
+    /// Seconds left before the fire rate allows another round at time `now`
+    pub fn cooldown_remaining(&self, now: f64) -> f64 {
+        self.last_shot.map_or(0.0, |last| {
+            (last + self.shot_cooldown() as f64 - now).max(0.0)
+        })
+    }
+
+    /// Whether the fire rate allows a round at time `now`
+    pub fn ready_at(&self, now: f64) -> bool {
+        self.cooldown_remaining(now) <= 0.0
+    }
+
+    /// Damage of one trigger pull if every pellet lands
+    pub fn damage_per_shot(&self) -> f32 {
+        self.damage * self.pellets as f32
+    }
+
+    /// Spread cone in degrees, with attachments and perks applied
+    pub fn spread_degrees(&self, perks: &PlayerPerks) -> f32 {
+        compute_effective_stats(self, perks).spread.to_degrees()
+    }
+
+    /// Stats shown on the loadout screen, each filled relative to the strongest weapon
+    pub fn stats(&self, perks: &PlayerPerks) -> [WeaponStat; 5] {
+        let effective = compute_effective_stats(self, perks);
+        [
+            WeaponStat::new(
+                "Damage",
+                format!("{:.0}", self.damage_per_shot()),
+                self.damage_per_shot() / 150.0,
+            ),
+            WeaponStat::new(
+                "Fire rate",
+                format!("{:.1}/s", self.fire_rate),
+                self.fire_rate / 10.0,
+            ),
+            WeaponStat::new(
+                "Magazine",
+                effective.magazine_size.to_string(),
+                effective.magazine_size as f32 / 30.0,
+            ),
+            WeaponStat::new(
+                "Reserve",
+                self.reserve(self.selected_ammo).to_string(),
+                self.reserve(self.selected_ammo) as f32 / 120.0,
+            ),
+            WeaponStat::new(
+                "Spread",
+                format!("{:.1}°", self.spread_degrees(perks)),
+                effective.spread / 0.15,
+            ),
+        ]
+    }
+}
+
+/// One labelled bar on the loadout screen
+#[derive(Clone, Debug)]
+pub struct WeaponStat {
+    pub label: &'static str,
+    pub value: String,
+    /// Bar length from 0.0 to 1.0
+    pub fill: f32,
+}
+
+impl WeaponStat {
+    fn new(label: &'static str, value: String, fill: f32) -> Self {
+        Self {
+            label,
+            value,
+            fill: fill.clamp(0.0, 1.0),
+        }
+    }
 }
 
 // =============================================================================
 // INVENTORY AND STATE COMPONENTS
 // =============================================================================
 
+/// Weapon slots at the start of a run
+pub const STARTING_WEAPON_SLOTS: usize = 2;
+/// Weapon slots once the backpack upgrade is picked up or bought
+pub const BACKPACK_WEAPON_SLOTS: usize = 3;
+
 /// Holds all weapons the player has
 #[derive(Component)]
 pub struct WeaponInventory {
-    pub weapons: [Option<Weapon>; 4],
+    /// One entry per slot, so the length is the capacity; grow it with expand_to
+    pub weapons: Vec<Option<Weapon>>,
     pub current_slot: usize,
 }
 
-impl Default for WeaponInventory {
-    fn default() -> Self {
+impl WeaponInventory {
+    /// Start-of-run inventory: the pistol plus `second`, picked on the Unlocks screen
+    pub fn starting(second: WeaponType, balance: &BalanceData) -> Self {
+        let mut weapons = vec![None; STARTING_WEAPON_SLOTS];
+        weapons[0] = Some(Weapon::new(WeaponType::Pistol, balance));
+        weapons[1] = Some(Weapon::new(second, balance));
         Self {
-            weapons: [
-                Some(Weapon::pistol()),
-                Some(Weapon::smg()),
-                Some(Weapon::rifle()),
-                Some(Weapon::shotgun()),
-            ],
+            weapons,
             current_slot: 0,
         }
     }
-}
 
-impl WeaponInventory {
     /// Get the currently equipped weapon
     pub fn current_weapon(&self) -> Option<&Weapon> {
-        self.weapons[self.current_slot].as_ref()
+        self.weapons.get(self.current_slot)?.as_ref()
     }
 
     /// Get mutable reference to current weapon
     pub fn current_weapon_mut(&mut self) -> Option<&mut Weapon> {
-        self.weapons[self.current_slot].as_mut()
+        self.weapons.get_mut(self.current_slot)?.as_mut()
+    }
+
+    /// Number of slots, filled or not
+    pub fn capacity(&self) -> usize {
+        self.weapons.len()
+    }
+
+    /// Add empty slots up to `capacity`; never takes slots away
+    pub fn expand_to(&mut self, capacity: usize) {
+        if capacity > self.weapons.len() {
+            self.weapons.resize(capacity, None);
+        }
     }
 
-    /// Switch to a specific slot (0-3)
+    /// Switch to a specific slot, if it holds a weapon
     pub fn switch_to(&mut self, slot: usize) {
-        if slot < 4 && self.weapons[slot].is_some() {
+        if self.weapons.get(slot).is_some_and(Option::is_some) {
             self.current_slot = slot;
         }
     }
 
     /// Cycle to next available weapon
     pub fn cycle_next(&mut self) {
-        for i in 1..=4 {
-            let next_slot = (self.current_slot + i) % 4;
+        let capacity = self.capacity();
+        for i in 1..=capacity {
+            let next_slot = (self.current_slot + i) % capacity;
             if self.weapons[next_slot].is_some() {
                 self.current_slot = next_slot;
                 return;
@@ -230,26 +479,91 @@ impl WeaponInventory {
 
     /// Cycle to previous available weapon
     pub fn cycle_prev(&mut self) {
-        for i in 1..=4 {
-            let prev_slot = (self.current_slot + 4 - i) % 4;
+        let capacity = self.capacity();
+        for i in 1..=capacity {
+            let prev_slot = (self.current_slot + capacity - i) % capacity;
             if self.weapons[prev_slot].is_some() {
                 self.current_slot = prev_slot;
                 return;
             }
         }
     }
+
+    /// Number of slots holding a weapon
+    pub fn weapon_count(&self) -> usize {
+        self.weapons.iter().flatten().count()
+    }
+
+    /// Swap two slots, keeping the equipped weapon selected
+    pub fn swap_slots(&mut self, a: usize, b: usize) {
+        if a >= self.capacity() || b >= self.capacity() {
+            return;
+        }
+        self.weapons.swap(a, b);
+        if self.current_slot == a {
+            self.current_slot = b;
+        } else if self.current_slot == b {
+            self.current_slot = a;
+        }
+    }
+
+    /// Take the weapon out of a slot, equipping another if it was the current one
+    pub fn take(&mut self, slot: usize) -> Option<Weapon> {
+        let weapon = self.weapons.get_mut(slot)?.take();
+        if slot == self.current_slot {
+            self.cycle_next();
+        }
+        weapon
+    }
+
+    /// Put a weapon in the first empty slot, handing it back if there is none
+    #[allow(clippy::result_large_err)]
+    pub fn add(&mut self, weapon: Weapon) -> Result<usize, Weapon> {
+        match self.weapons.iter().position(Option::is_none) {
+            Some(slot) => {
+                self.weapons[slot] = Some(weapon);
+                Ok(slot)
+            }
+            None => Err(weapon),
+        }
+    }
+
+    /// Put a weapon in the equipped slot, handing back the one that was there
+    pub fn replace_current(&mut self, weapon: Weapon) -> Option<Weapon> {
+        self.weapons[self.current_slot].replace(weapon)
+    }
 }
 
-/// Cooldown timer between shots
-#[derive(Component)]
-pub struct ShootCooldown(pub Timer);
+/// Railgun charge built up while the trigger is held
+#[derive(Component, Default)]
+pub struct ChargingState {
+    /// Seconds the trigger has been held
+    pub held: f32,
+}
 
-impl Default for ShootCooldown {
-    fn default() -> Self {
-        Self(Timer::from_seconds(0.0, TimerMode::Once))
+impl ChargingState {
+    /// Charge from 0.0 to 1.0
+    pub fn fraction(&self) -> f32 {
+        (self.held / CHARGE_TIME).min(1.0)
     }
 }
 
+/// Seconds to reach full charge
+pub const CHARGE_TIME: f32 = 1.5;
+/// Holding this long forces the shot out and kicks the shooter back
+pub const OVERCHARGE_TIME: f32 = 2.5;
+/// Share of full damage dealt by an uncharged shot
+const MIN_CHARGE_DAMAGE: f32 = 0.2;
+/// Shootables a single rail shot can pass through
+const RAIL_MAX_PIERCE: usize = 4;
+
+/// A click that landed just before the weapon was ready, fired as soon as it is
+#[derive(Component)]
+pub struct QueuedShot;
+
+/// Clicks this close to the end of the cooldown are queued instead of dropped
+pub const SHOT_BUFFER: f64 = 0.05;
+
 /// Active reload state
 #[derive(Component)]
 pub struct ReloadState(pub Timer);
@@ -258,7 +572,6 @@ pub struct ReloadState(pub Timer);
 #[derive(Component)]
 pub struct BurstState {
     pub shots_remaining: u8,
-    pub timer: Timer,
 }
 
 // =============================================================================
@@ -269,17 +582,172 @@ pub struct BurstState {
 #[derive(Component)]
 pub struct Shootable;
 
-/// Event sent when something is shot
+/// Shootable collider on part of a body, e.g. a boss's armor plates and weak points.
+/// Rounds that strike it hit the owner, with their damage scaled by `multiplier`.
+#[derive(Component)]
+pub struct HitZone {
+    pub owner: Entity,
+    pub multiplier: f32,
+}
+
+/// Everything a round can hit, with what decides how hard it lands and what it
+/// strikes
+#[derive(SystemParam)]
+struct Shootables<'w, 's> {
+    bodies: Query<'w, 's, (Option<&'static Zombie>, Option<&'static HitZone>), With<Shootable>>,
+    zombies: Query<'w, 's, &'static Transform, (With<Zombie>, Without<Projectile>)>,
+    surfaces: Query<'w, 's, &'static SurfaceMaterial>,
+    parents: Query<'w, 's, &'static ChildOf>,
+}
+
+/// A round that landed on something shootable, worked out by Shootables::resolve
+struct ResolvedHit {
+    body: Entity,
+    zone: Option<Entity>,
+    damage: f32,
+    headshot: bool,
+    crit: bool,
+    surface: SurfaceMaterial,
+}
+
+impl ResolvedHit {
+    fn event(
+        self,
+        source: Entity,
+        direction: Vec3,
+        point: Vec3,
+        normal: Vec3,
+        distance: f32,
+    ) -> HitEvent {
+        HitEvent {
+            entity: self.body,
+            damage: self.damage,
+            direction,
+            point,
+            normal,
+            distance,
+            source: Some(source),
+            zone: self.zone,
+            surface: self.surface,
+            headshot: self.headshot,
+            crit: self.crit,
+        }
+    }
+}
+
+/// Event sent when something is shot. Damage is applied in HitSystems::Damage; sounds,
+/// marks and numbers read it afterwards in HitSystems::Feedback and never change it.
 #[derive(Message)]
 pub struct HitEvent {
     pub entity: Entity,
     pub damage: f32,
+    /// Direction the shot was travelling, used to push debris away from the shooter
+    pub direction: Vec3,
+    /// World position where the ray struck
+    pub point: Vec3,
+    /// Surface normal where the ray struck, or facing the source for hits that aren't
+    /// rays. Impacts take theirs from ShotFired, so nothing reads this yet.
+    #[allow(dead_code)]
+    pub normal: Vec3,
+    /// How far the shot travelled before hitting, 0.0 for non-shot damage like explosions
+    pub distance: f32,
+    /// Who fired, None for environmental damage like explosions
+    pub source: Option<Entity>,
+    /// The HitZone collider the round struck, if it landed on one. Damage is routed to
+    /// the body before the event goes out, so nothing reads this yet.
+    #[allow(dead_code)]
+    pub zone: Option<Entity>,
+    /// What the struck collider is made of; read by tests, not yet by any feedback
+    #[allow(dead_code)]
+    pub surface: SurfaceMaterial,
+    /// A round that landed on a zombie's head
+    pub headshot: bool,
+    /// A round that landed on a HitZone weak point. Damage already has the multiplier
+    /// in it, so nothing reads this yet.
+    #[allow(dead_code)]
+    pub crit: bool,
 }
 
-/// Debug ray visualization
+/// Hits are applied before anything reacts to them, so feedback sees the health and
+/// deaths (ZombieDied, TargetHitEvent::killed) they caused the same frame. Feedback
+/// only reads; removing any of it leaves damage as it was.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HitSystems {
+    Damage,
+    Feedback,
+}
+
+/// Push applied to kinematic characters (explosions), decaying over a fraction of a second
 #[derive(Component)]
-pub struct DebugRay {
-    pub timer: Timer,
+pub struct Knockback {
+    pub velocity: Vec3,
+}
+
+/// How a shot travelled, which picks its tracer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShotKind {
+    Hitscan,
+    Rail,
+    /// Drawn by its Projectile while in flight, so it leaves no tracer
+    Projectile,
+}
+
+/// Event sent for every ray fired, whether or not it hit anything shootable
+#[derive(Message)]
+pub struct ShotFired {
+    pub shooter: Entity,
+    pub kind: ShotKind,
+    /// Where the round set off from, and its heading
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub hit: bool,
+    /// Where the round stopped, None if it flew off without hitting anything
+    pub point: Option<Vec3>,
+    /// Collider it stopped on and the surface normal there, for impact effects
+    pub struck: Option<(Entity, Vec3)>,
+}
+
+/// Bullet in flight for ballistic weapons, moved and collided in update_projectiles
+#[derive(Component)]
+#[require(Interpolated)]
+pub struct Projectile {
+    pub shooter: Entity,
+    pub velocity: Vec3,
+    pub gravity: f32,
+    /// Before the ammo type's modifiers, which are applied on impact
+    pub damage: f32,
+    pub ammo: AmmoType,
+    /// Obstacles already punched through (AP)
+    pub passed: Vec<Entity>,
+    /// Path length flown so far, reported on HitEvent
+    pub distance: f32,
+    pub lifetime: Timer,
+}
+
+/// Max length of each raycast segment so arcs stay accurate at high speed
+const PROJECTILE_SUBSTEP: f32 = 1.0;
+
+/// Shared by every projectile in flight
+#[derive(Resource)]
+struct ProjectileAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_projectile_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ProjectileAssets {
+        mesh: meshes.add(Cuboid::new(0.05, 0.05, 0.4)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.9, 0.5),
+            emissive: LinearRgba::rgb(3.0, 2.5, 1.0),
+            unlit: true,
+            ..default()
+        }),
+    });
 }
 
 // =============================================================================
@@ -287,42 +755,40 @@ pub struct DebugRay {
 // =============================================================================
 
 fn handle_weapon_switch(
-    keys: Res<ButtonInput<KeyCode>>,
-    scroll: Res<AccumulatedMouseScroll>,
-    mut players: Query<(&mut WeaponInventory, Option<&ReloadState>), With<Player>>,
+    mut commands: Commands,
+    mut buffer: ResMut<InputBuffer>,
+    mut players: Query<(Entity, &mut WeaponInventory, Option<&ReloadState>), With<Player>>,
 ) {
-    let Ok((mut inventory, reload_state)) = players.single_mut() else {
+    let Ok((entity, mut inventory, reload_state)) = players.single_mut() else {
         return;
     };
+    let previous_slot = inventory.current_slot;
 
-    // Can't switch weapons while reloading
+    // Can't switch weapons while reloading; a switch pressed near the end goes
+    // through once it's done
     if reload_state.is_some() {
         return;
     }
 
-    // Number keys 1-4
-    if keys.just_pressed(KeyCode::Digit1) {
-        inventory.switch_to(0);
-    } else if keys.just_pressed(KeyCode::Digit2) {
-        inventory.switch_to(1);
-    } else if keys.just_pressed(KeyCode::Digit3) {
-        inventory.switch_to(2);
-    } else if keys.just_pressed(KeyCode::Digit4) {
-        inventory.switch_to(3);
+    // Number keys 1-6, or the scroll wheel
+    match buffer.pending() {
+        Some(BufferedAction::SwitchTo(slot)) => inventory.switch_to(slot),
+        Some(BufferedAction::Cycle { next: true }) => inventory.cycle_next(),
+        Some(BufferedAction::Cycle { next: false }) => inventory.cycle_prev(),
+        _ => return,
     }
+    buffer.clear();
 
-    // Scroll wheel
-    let scroll_y = scroll.delta.y;
-    if scroll_y > 0.0 {
-        inventory.cycle_prev();
-    } else if scroll_y < 0.0 {
-        inventory.cycle_next();
+    // Switching away drops any railgun charge
+    if inventory.current_slot != previous_slot {
+        commands.entity(entity).remove::<ChargingState>();
     }
 }
 
 fn handle_reload_input(
     mut commands: Commands,
-    keys: Res<ButtonInput<KeyCode>>,
+    mut buffer: ResMut<InputBuffer>,
+    perks: Res<PlayerPerks>,
     players: Query<(Entity, &WeaponInventory, Option<&ReloadState>), With<Player>>,
 ) {
     let Ok((entity, inventory, reload_state)) = players.single() else {
@@ -333,23 +799,33 @@ fn handle_reload_input(
     if reload_state.is_some() {
         return;
     }
+    let should_reload = buffer.has(BufferedAction::Reload);
+    if should_reload {
+        buffer.clear();
+    }
+    // R clears a jam instead (see clear_jams)
+    if inventory.current_weapon().is_some_and(|w| w.jammed) {
+        return;
+    }
 
-    let should_reload = keys.just_pressed(KeyCode::KeyR);
+    // Also swap the magazine out as soon as another ammo type is picked
     let auto_reload = inventory
         .current_weapon()
-        .map(|w| w.is_empty())
+        .map(|w| w.is_empty() || w.selected_ammo != w.loaded_ammo)
         .unwrap_or(false);
 
     if should_reload || auto_reload {
         if let Some(weapon) = inventory.current_weapon() {
-            if weapon.can_reload() {
-                let reload_time = weapon.reload_time;
+            if weapon.can_reload(&perks) {
+                let reload_time = compute_effective_stats(weapon, &perks).reload_time;
+                // Reloading cancels a charge in progress
                 commands
                     .entity(entity)
                     .insert(ReloadState(Timer::from_seconds(
                         reload_time,
                         TimerMode::Once,
-                    )));
+                    )))
+                    .remove::<ChargingState>();
             }
         }
     }
@@ -358,17 +834,26 @@ fn handle_reload_input(
 fn process_reload(
     mut commands: Commands,
     time: Res<Time>,
-    mut players: Query<(Entity, &mut WeaponInventory, &mut ReloadState), With<Player>>,
+    perks: Res<PlayerPerks>,
+    mut players: Query<
+        (
+            Entity,
+            &mut WeaponInventory,
+            &mut ReloadState,
+            &PlayerActionState,
+        ),
+        With<Player>,
+    >,
 ) {
-    for (entity, mut inventory, mut reload) in players.iter_mut() {
-        reload.0.tick(time.delta());
+    for (entity, mut inventory, mut reload, action_state) in players.iter_mut() {
+        // Slower on the run, however the sprint and the reload overlapped
+        reload
+            .0
+            .tick(time.delta().mul_f32(action_state.reload_rate()));
 
         if reload.0.is_finished() {
             if let Some(weapon) = inventory.current_weapon_mut() {
-                let needed = weapon.magazine_size - weapon.current_ammo;
-                let available = weapon.reserve_ammo.min(needed);
-                weapon.current_ammo += available;
-                weapon.reserve_ammo -= available;
+                weapon.reload(&perks);
             }
             commands.entity(entity).remove::<ReloadState>();
         }
@@ -377,41 +862,82 @@ fn process_reload(
 
 fn process_burst(
     mut commands: Commands,
-    time: Res<Time>,
-    mut players: Query<(Entity, &Transform, &mut WeaponInventory, &mut BurstState), With<Player>>,
+    bullet_time: Res<BulletTime>,
+    perks: Res<PlayerPerks>,
+    difficulty: Res<DifficultyModifiers>,
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &mut WeaponInventory,
+            &mut BurstState,
+            Option<&Flinch>,
+        ),
+        With<Player>,
+    >,
+    mut camera_q: Query<&mut ThirdPersonCamera>,
+    aim: Res<AimRay>,
     rapier_context: ReadRapierContext,
-    shootables: Query<Entity, With<Shootable>>,
+    shootables: Shootables,
     mut hit_events: MessageWriter<HitEvent>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut shot_events: MessageWriter<ShotFired>,
+    projectile_assets: Res<ProjectileAssets>,
+    mut rng: ResMut<GameRng>,
 ) {
     let Ok(context) = rapier_context.single() else {
         return;
     };
+    let Ok(mut camera) = camera_q.single_mut() else {
+        return;
+    };
 
-    for (player_entity, player_transform, mut inventory, mut burst) in players.iter_mut() {
-        burst.timer.tick(time.delta());
+    // Cadence runs on the player's clock so bullet time doesn't slow the trigger
+    let now = bullet_time.player_elapsed;
 
-        if burst.timer.is_finished() && burst.shots_remaining > 0 {
+    for (player_entity, player_transform, mut inventory, mut burst, flinch) in players.iter_mut() {
+        // Free-look blocks firing, so a burst in progress is cut short
+        if camera.is_free_looking() {
+            burst.shots_remaining = 0;
+        }
+
+        if burst.shots_remaining > 0 {
             if let Some(weapon) = inventory.current_weapon_mut() {
-                if weapon.current_ammo > 0 {
-                    // Fire one shot of the burst
-                    fire_weapon(
-                        &mut commands,
+                if weapon.current_ammo == 0 {
+                    burst.shots_remaining = 0;
+                } else if weapon.ready_at(now) {
+                    // Fire one shot of the burst, paced by the weapon's fire rate
+                    let (ray_origin, aim_direction) = (aim.origin, aim.direction);
+                    if muzzle_blocked(
+                        &context,
                         player_entity,
                         player_transform,
-                        weapon,
-                        &context,
-                        &shootables,
-                        &mut hit_events,
-                        &mut meshes,
-                        &mut materials,
-                    );
-
-                    burst.shots_remaining -= 1;
-                    burst.timer.reset();
-                } else {
-                    burst.shots_remaining = 0;
+                        aim_direction,
+                        &shootables.bodies,
+                    ) {
+                        // The rest of the burst would hit the same wall
+                        burst.shots_remaining = 0;
+                    } else if roll_jam(weapon, &difficulty, &mut rng) {
+                        burst.shots_remaining = 0;
+                    } else {
+                        let kick = fire_weapon(
+                            &mut commands,
+                            player_entity,
+                            ray_origin,
+                            aim_direction,
+                            weapon,
+                            &perks,
+                            flinch,
+                            now,
+                            &context,
+                            &shootables,
+                            &mut hit_events,
+                            &mut shot_events,
+                            &projectile_assets,
+                            &mut rng,
+                        );
+                        camera.add_recoil(kick);
+                        burst.shots_remaining -= 1;
+                    }
                 }
             }
         }
@@ -422,40 +948,160 @@ fn process_burst(
     }
 }
 
+fn process_charge(
+    mut commands: Commands,
+    time: Res<Time>,
+    bullet_time: Res<BulletTime>,
+    actions: Res<PlayerActions>,
+    mut players: Query<
+        (Entity, &Transform, &mut WeaponInventory, &mut ChargingState),
+        With<Player>,
+    >,
+    camera_q: Query<&ThirdPersonCamera>,
+    aim: Res<AimRay>,
+    rapier_context: ReadRapierContext,
+    shootables: Shootables,
+    mut hit_events: MessageWriter<HitEvent>,
+    mut shot_events: MessageWriter<ShotFired>,
+    mut shake_events: MessageWriter<CameraShake>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let Ok(camera) = camera_q.single() else {
+        return;
+    };
+    // Cadence runs on the player's clock so bullet time doesn't slow the trigger
+    let now = bullet_time.player_elapsed;
+
+    for (player_entity, player_transform, mut inventory, mut charging) in players.iter_mut() {
+        let Some(weapon) = inventory
+            .current_weapon_mut()
+            .filter(|weapon| weapon.fire_mode == FireMode::Charge && !weapon.is_empty())
+        else {
+            commands.entity(player_entity).remove::<ChargingState>();
+            continue;
+        };
+
+        // Free-look drops the charge rather than firing somewhere unexpected
+        if camera.is_free_looking() {
+            commands.entity(player_entity).remove::<ChargingState>();
+            continue;
+        }
+
+        let held = actions.fire_held;
+        if held {
+            charging.held += bullet_time.player_delta(&time);
+        }
+        let overcharged = charging.held >= OVERCHARGE_TIME;
+        if held && !overcharged {
+            continue;
+        }
+
+        let (ray_origin, aim_direction) = (aim.origin, aim.direction);
+        // Released against a wall: the charge is lost but the round is kept
+        if muzzle_blocked(
+            &context,
+            player_entity,
+            player_transform,
+            aim_direction,
+            &shootables.bodies,
+        ) {
+            commands.entity(player_entity).remove::<ChargingState>();
+            continue;
+        }
+        fire_railgun(
+            player_entity,
+            ray_origin,
+            aim_direction,
+            weapon,
+            charging.fraction(),
+            now,
+            &context,
+            &shootables,
+            &mut hit_events,
+            &mut shot_events,
+        );
+        commands.entity(player_entity).remove::<ChargingState>();
+
+        // Overcharge vents straight back into the shooter
+        if overcharged {
+            commands.entity(player_entity).insert(Knockback {
+                velocity: -aim_direction.with_y(0.0).normalize_or_zero() * 6.0,
+            });
+            shake_events.write(CameraShake { trauma: 0.3 });
+        }
+    }
+}
+
 fn shoot(
     mut commands: Commands,
-    mouse_button: Res<ButtonInput<MouseButton>>,
+    bullet_time: Res<BulletTime>,
+    perks: Res<PlayerPerks>,
+    difficulty: Res<DifficultyModifiers>,
+    actions: Res<PlayerActions>,
+    mut buffer: ResMut<InputBuffer>,
     mut players: Query<
         (
             Entity,
             &Transform,
             &mut WeaponInventory,
-            &mut ShootCooldown,
             Option<&ReloadState>,
             Option<&BurstState>,
+            Option<&QueuedShot>,
+            Option<&Flinch>,
+            &PlayerActionState,
         ),
-        With<Player>,
+        (With<Player>, Without<ChargingState>, Without<Inspecting>),
     >,
+    mut camera_q: Query<&mut ThirdPersonCamera>,
+    aim: Res<AimRay>,
     rapier_context: ReadRapierContext,
-    shootables: Query<Entity, With<Shootable>>,
+    shootables: Shootables,
     mut hit_events: MessageWriter<HitEvent>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut shot_events: MessageWriter<ShotFired>,
+    mut subtitles: MessageWriter<Subtitle>,
+    projectile_assets: Res<ProjectileAssets>,
+    mut rng: ResMut<GameRng>,
 ) {
     let Ok(context) = rapier_context.single() else {
         return;
     };
+    let Ok(mut camera) = camera_q.single_mut() else {
+        return;
+    };
+
+    // Can't shoot while free-looking: the crosshair isn't where the player is facing
+    if camera.is_free_looking() {
+        return;
+    }
 
-    for (player_entity, player_transform, mut inventory, mut cooldown, reload_state, burst_state) in
-        players.iter_mut()
+    // Cadence runs on the player's clock so bullet time doesn't slow the trigger
+    let now = bullet_time.player_elapsed;
+
+    for (
+        player_entity,
+        player_transform,
+        mut inventory,
+        reload_state,
+        burst_state,
+        queued,
+        flinch,
+        action_state,
+    ) in players.iter_mut()
     {
-        // Can't shoot while reloading or in burst
+        // Can't shoot while reloading or in burst; a queued click doesn't survive a
+        // reload, but a fresh press near its end is still in the InputBuffer after
         if reload_state.is_some() || burst_state.is_some() {
+            if reload_state.is_some() && queued.is_some() {
+                commands.entity(player_entity).remove::<QueuedShot>();
+            }
             continue;
         }
 
-        // Check cooldown
-        if !cooldown.0.is_finished() {
+        // The weapon is down while sprinting; the press stays buffered while it's
+        // lowered, and update_action_state queues the shot for when it's up
+        if !action_state.can_fire() {
             continue;
         }
 
@@ -463,116 +1109,641 @@ fn shoot(
             continue;
         };
 
-        // Check ammo
+        // A jammed weapon only clicks until it's cleared
+        if weapon.jammed {
+            if actions.fire_pressed {
+                subtitles.write(Subtitle("[Jammed click]".to_string()));
+            }
+            continue;
+        }
+
+        // An empty magazine only clicks; the reload itself starts automatically
         if weapon.is_empty() {
+            if actions.fire_pressed {
+                subtitles.write(Subtitle("[Dry fire click]".to_string()));
+            }
             continue;
         }
 
         // Check fire mode input
-        let should_fire = match weapon.fire_mode {
-            FireMode::SemiAuto => mouse_button.just_pressed(MouseButton::Left),
-            FireMode::FullAuto => mouse_button.pressed(MouseButton::Left),
-            FireMode::Burst(_) => mouse_button.just_pressed(MouseButton::Left),
+        let pressed = buffer.has(BufferedAction::Fire);
+        let clicked = match weapon.fire_mode {
+            FireMode::SemiAuto => pressed,
+            FireMode::FullAuto => actions.fire_held || pressed,
+            FireMode::Burst(_) => pressed,
+            FireMode::Charge => pressed,
         };
+        if pressed {
+            buffer.clear();
+        }
 
-        if !should_fire {
+        // Every fire mode goes through the same per-weapon cooldown. It keeps its own
+        // tighter queue, so clicking faster than the weapon fires doesn't bank shots.
+        if !weapon.ready_at(now) {
+            if clicked && queued.is_none() && weapon.cooldown_remaining(now) <= SHOT_BUFFER {
+                commands.entity(player_entity).insert(QueuedShot);
+            }
             continue;
         }
 
-        // Handle burst mode specially
+        if !clicked && queued.is_none() {
+            continue;
+        }
+        if queued.is_some() {
+            commands.entity(player_entity).remove::<QueuedShot>();
+        }
+
+        // Charge weapons start charging here and fire from process_charge on release.
+        // The jam roll comes first, so a jammed railgun never starts charging.
+        if weapon.fire_mode == FireMode::Charge {
+            let weapon_mut = inventory.current_weapon_mut().unwrap();
+            if roll_jam(weapon_mut, &difficulty, &mut rng) {
+                continue;
+            }
+            commands
+                .entity(player_entity)
+                .insert(ChargingState::default());
+            continue;
+        }
+
+        // Burst rounds are fired one by one by process_burst
         if let FireMode::Burst(count) = weapon.fire_mode {
-            let cooldown_time = weapon.shot_cooldown();
             commands.entity(player_entity).insert(BurstState {
                 shots_remaining: count,
-                timer: Timer::from_seconds(0.0, TimerMode::Repeating),
             });
-            cooldown.0 = Timer::from_seconds(cooldown_time, TimerMode::Once);
             continue;
         }
 
-        // Fire the weapon
+        // Fire the weapon, unless the barrel is jammed against something
+        let (ray_origin, aim_direction) = (aim.origin, aim.direction);
+        if muzzle_blocked(
+            &context,
+            player_entity,
+            player_transform,
+            aim_direction,
+            &shootables.bodies,
+        ) {
+            continue;
+        }
         let weapon_mut = inventory.current_weapon_mut().unwrap();
-        fire_weapon(
+        if roll_jam(weapon_mut, &difficulty, &mut rng) {
+            continue;
+        }
+        let kick = fire_weapon(
             &mut commands,
             player_entity,
-            player_transform,
+            ray_origin,
+            aim_direction,
             weapon_mut,
+            &perks,
+            flinch,
+            now,
             &context,
             &shootables,
             &mut hit_events,
-            &mut meshes,
-            &mut materials,
+            &mut shot_events,
+            &projectile_assets,
+            &mut rng,
         );
+        camera.add_recoil(kick);
+    }
+}
 
-        // Set cooldown
-        let cooldown_time = weapon_mut.shot_cooldown();
-        cooldown.0 = Timer::from_seconds(cooldown_time, TimerMode::Once);
+/// How far the aim ray reaches before falling back to a far point
+pub const AIM_DISTANCE: f32 = 100.0;
+
+/// This frame's aim, cast once and shared by the shooting systems and the target
+/// highlight
+#[derive(Resource, Default)]
+pub struct AimRay {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    /// Whatever the crosshair is resting on
+    pub target: Option<Entity>,
+}
+
+pub(super) fn update_aim_ray(
+    rapier_context: ReadRapierContext,
+    player_q: Query<(Entity, &Transform), With<Player>>,
+    camera_q: Query<&Transform, With<ThirdPersonCamera>>,
+    mut aim: ResMut<AimRay>,
+) {
+    let (Ok(context), Ok((player_entity, player_transform)), Ok(camera_transform)) = (
+        rapier_context.single(),
+        player_q.single(),
+        camera_q.single(),
+    ) else {
+        *aim = AimRay::default();
+        return;
+    };
+    *aim = cast_aim(&context, player_entity, player_transform, camera_transform);
+}
+
+/// Build the gameplay ray: from the player towards whatever is under the crosshair.
+///
+/// The camera sits behind and beside the player, so firing along the player's forward
+/// would land off the reticle. Instead we cast from the camera through screen center to
+/// find the aim point, then shoot from the player's muzzle towards that point.
+pub fn aim_ray(
+    context: &RapierContext,
+    player_entity: Entity,
+    player_transform: &Transform,
+    camera_transform: &Transform,
+) -> (Vec3, Vec3) {
+    let aim = cast_aim(context, player_entity, player_transform, camera_transform);
+    (aim.origin, aim.direction)
+}
+
+fn cast_aim(
+    context: &RapierContext,
+    player_entity: Entity,
+    player_transform: &Transform,
+    camera_transform: &Transform,
+) -> AimRay {
+    let ray_origin = player_transform.translation + Vec3::Y * PLAYER_MUZZLE_HEIGHT;
+    let camera_forward = *camera_transform.forward();
+
+    // Start the camera ray level with the player so walls or props between the
+    // camera and the player can't steal the aim point
+    let player_depth = (ray_origin - camera_transform.translation)
+        .dot(camera_forward)
+        .max(0.0);
+    let camera_origin = camera_transform.translation + camera_forward * player_depth;
+
+    let filter = QueryFilter::default()
+        .exclude_rigid_body(player_entity)
+        .exclude_sensors()
+        .groups(PhysicsLayer::Projectiles.groups());
+
+    let mut aim_point = camera_origin + camera_forward * AIM_DISTANCE;
+    let mut target = None;
+    context.with_query_pipeline(filter, |query_pipeline| {
+        if let Some((entity, distance)) =
+            query_pipeline.cast_ray(camera_origin, camera_forward, AIM_DISTANCE, true)
+        {
+            aim_point = camera_origin + camera_forward * distance;
+            target = Some(entity);
+        }
+    });
+
+    let aim_direction = (aim_point - ray_origin).normalize_or_zero();
+    AimRay {
+        origin: ray_origin,
+        direction: if aim_direction == Vec3::ZERO {
+            *player_transform.forward()
+        } else {
+            aim_direction
+        },
+        target,
     }
 }
 
+/// How far in front of the player the barrel reaches
+const MUZZLE_REACH: f32 = 0.7;
+
+/// Whether level geometry sits between the player's centre and the end of the barrel.
+///
+/// The aim ray starts inside the player, so with the chest pressed against a crate or
+/// into a corner it would otherwise begin past the wall and hit whatever is behind it.
+/// Shootable things are fine to touch: a zombie in your face can still be shot.
+pub fn muzzle_blocked<D: QueryData>(
+    context: &RapierContext,
+    player_entity: Entity,
+    player_transform: &Transform,
+    aim_direction: Vec3,
+    shootables: &Query<D, With<Shootable>>,
+) -> bool {
+    let center = player_transform.translation;
+    let muzzle =
+        center + Vec3::Y * PLAYER_MUZZLE_HEIGHT + aim_direction.normalize_or_zero() * MUZZLE_REACH;
+    let to_muzzle = muzzle - center;
+
+    let filter = QueryFilter::default()
+        .exclude_rigid_body(player_entity)
+        .exclude_sensors()
+        .groups(PhysicsLayer::Projectiles.groups());
+    let mut blocked = false;
+    context.with_query_pipeline(filter, |query_pipeline| {
+        if let Some((entity, _)) = query_pipeline.cast_ray(
+            center,
+            to_muzzle.normalize_or_zero(),
+            to_muzzle.length(),
+            true,
+        ) {
+            blocked = !shootables.contains(entity);
+        }
+    });
+    blocked
+}
+
+/// Where a shot fired along `aim` would land, for the converged aim dot
+pub fn converged_aim_point(context: &RapierContext, player_entity: Entity, aim: &AimRay) -> Vec3 {
+    let (ray_origin, aim_direction) = (aim.origin, aim.direction);
+
+    let filter = QueryFilter::default()
+        .exclude_rigid_body(player_entity)
+        .groups(PhysicsLayer::Projectiles.groups());
+    let mut point = ray_origin + aim_direction * AIM_DISTANCE;
+    context.with_query_pipeline(filter, |query_pipeline| {
+        if let Some((_, distance)) =
+            query_pipeline.cast_ray(ray_origin, aim_direction, AIM_DISTANCE, true)
+        {
+            point = ray_origin + aim_direction * distance;
+        }
+    });
+    point
+}
+
+/// Fire one round (all its pellets), returning the recoil kick for the camera
 fn fire_weapon(
     commands: &mut Commands,
     player_entity: Entity,
-    player_transform: &Transform,
+    ray_origin: Vec3,
+    aim_direction: Vec3,
     weapon: &mut Weapon,
+    perks: &PlayerPerks,
+    flinch: Option<&Flinch>,
+    now: f64,
     context: &RapierContext,
-    shootables: &Query<Entity, With<Shootable>>,
+    shootables: &Shootables,
     hit_events: &mut MessageWriter<HitEvent>,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-) {
+    shot_events: &mut MessageWriter<ShotFired>,
+    projectile_assets: &ProjectileAssets,
+    rng: &mut GameRng,
+) -> Vec2 {
     weapon.current_ammo -= 1;
+    let kick = weapon.recoil_kick(now, &mut **rng);
+    weapon.last_shot = Some(now);
 
-    let player_pos = player_transform.translation;
-    let player_forward = *player_transform.forward();
-    let ray_origin = player_pos + Vec3::Y * 0.5;
     let max_distance = 100.0;
 
-    let filter = QueryFilter::default().exclude_rigid_body(player_entity);
+    let filter = QueryFilter::default()
+        .exclude_rigid_body(player_entity)
+        .groups(PhysicsLayer::Projectiles.groups());
 
-    // Generate ray directions based on pellet count and spread
-    let directions = generate_spread_directions(player_forward, weapon.spread, weapon.pellets);
+    // Generate ray directions based on pellet count and spread; a scope steadies
+    // some of the flinch from recent hits
+    let stats = compute_effective_stats(weapon, perks);
+    let spread = stats.spread + flinch.map_or(0.0, |flinch| flinch.spread(stats.zoom > 0.0));
+    let directions = generate_spread_directions(rng, aim_direction, spread, weapon.pellets);
+    let modifiers = weapon.loaded_ammo.modifiers();
 
     for ray_direction in directions {
-        let mut hit_entity: Option<(Entity, f32)> = None;
+        // Ballistic weapons hand the shot off to a projectile that resolves over time
+        if let Some(ballistics) = weapon.ballistics {
+            commands.spawn((
+                Mesh3d(projectile_assets.mesh.clone()),
+                MeshMaterial3d(projectile_assets.material.clone()),
+                Transform::from_translation(ray_origin).looking_to(ray_direction, Vec3::Y),
+                Projectile {
+                    shooter: player_entity,
+                    velocity: ray_direction * ballistics.muzzle_velocity,
+                    gravity: ballistics.gravity,
+                    damage: weapon.damage,
+                    ammo: weapon.loaded_ammo,
+                    passed: Vec::new(),
+                    distance: 0.0,
+                    lifetime: Timer::from_seconds(3.0, TimerMode::Once),
+                },
+            ));
+            continue;
+        }
+
+        // Recast past obstacles the round is allowed to punch through (AP)
+        let mut passed: Vec<Entity> = Vec::new();
+        let hit_entity = loop {
+            let not_passed = |entity: Entity| !passed.contains(&entity);
+            let filter = filter.predicate(&not_passed);
+            let mut hit_entity: Option<(Entity, RayIntersection)> = None;
+            context.with_query_pipeline(filter, |query_pipeline| {
+                hit_entity = query_pipeline.cast_ray_and_get_normal(
+                    ray_origin,
+                    ray_direction,
+                    max_distance,
+                    true,
+                );
+            });
+            match hit_entity {
+                Some((entity, _))
+                    if !shootables.contains(entity)
+                        && passed.len() < modifiers.penetration as usize =>
+                {
+                    passed.push(entity);
+                }
+                _ => break hit_entity,
+            }
+        };
+
+        // Send hit event
+        let mut hit = false;
+        if let Some((entity, intersection)) = hit_entity {
+            let distance = intersection.time_of_impact;
+            let point = ray_origin + ray_direction * distance;
+            if let Some(resolved) = shootables.resolve(entity, weapon.damage, &modifiers, point) {
+                hit = true;
+                hit_events.write(resolved.event(
+                    player_entity,
+                    ray_direction,
+                    point,
+                    intersection.normal,
+                    distance,
+                ));
+            }
+        }
+
+        let ray_end = hit_entity
+            .map(|(_, intersection)| ray_origin + ray_direction * intersection.time_of_impact);
+        shot_events.write(ShotFired {
+            shooter: player_entity,
+            kind: ShotKind::Hitscan,
+            origin: ray_origin,
+            direction: ray_direction,
+            hit,
+            point: ray_end,
+            struck: hit_entity.map(|(entity, intersection)| (entity, intersection.normal)),
+        });
+    }
+
+    kick
+}
+
+/// Fire a single piercing rail shot; damage scales with charge
+fn fire_railgun(
+    player_entity: Entity,
+    ray_origin: Vec3,
+    aim_direction: Vec3,
+    weapon: &mut Weapon,
+    charge: f32,
+    now: f64,
+    context: &RapierContext,
+    shootables: &Shootables,
+    hit_events: &mut MessageWriter<HitEvent>,
+    shot_events: &mut MessageWriter<ShotFired>,
+) {
+    weapon.current_ammo -= 1;
+    weapon.last_shot = Some(now);
+
+    let max_distance = 100.0;
+    let damage = weapon.damage * (MIN_CHARGE_DAMAGE + (1.0 - MIN_CHARGE_DAMAGE) * charge);
+    let modifiers = weapon.loaded_ammo.modifiers();
+
+    // Recast past every shootable we go through until a wall stops the beam
+    let mut pierced: Vec<Entity> = Vec::new();
+    let mut ray_end = ray_origin + aim_direction * max_distance;
+    let mut struck = None;
+    loop {
+        let not_pierced = |entity: Entity| !pierced.contains(&entity);
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(player_entity)
+            .groups(PhysicsLayer::Projectiles.groups())
+            .predicate(&not_pierced);
+
+        let mut hit_entity: Option<(Entity, RayIntersection)> = None;
         context.with_query_pipeline(filter, |query_pipeline| {
-            hit_entity = query_pipeline.cast_ray(ray_origin, ray_direction, max_distance, true);
+            hit_entity = query_pipeline.cast_ray_and_get_normal(
+                ray_origin,
+                aim_direction,
+                max_distance,
+                true,
+            );
         });
 
-        // Debug ray visualization
-        let ray_end = if let Some((_, distance)) = hit_entity {
-            ray_origin + ray_direction * distance
-        } else {
-            ray_origin + ray_direction * max_distance
+        let Some((entity, intersection)) = hit_entity else {
+            break;
+        };
+        let distance = intersection.time_of_impact;
+        let point = ray_origin + aim_direction * distance;
+        let Some(resolved) = shootables.resolve(entity, damage, &modifiers, point) else {
+            ray_end = point;
+            struck = Some((entity, intersection.normal));
+            break;
         };
 
-        spawn_debug_ray(
-            commands,
-            ray_origin,
-            ray_end,
-            ray_direction,
-            meshes,
-            materials,
-        );
+        hit_events.write(resolved.event(
+            player_entity,
+            aim_direction,
+            point,
+            intersection.normal,
+            distance,
+        ));
+        pierced.push(entity);
+        if pierced.len() >= RAIL_MAX_PIERCE {
+            ray_end = point;
+            struck = Some((entity, intersection.normal));
+            break;
+        }
+    }
 
-        // Send hit event
-        if let Some((entity, _)) = hit_entity {
-            if shootables.get(entity).is_ok() {
-                hit_events.write(HitEvent {
-                    entity,
-                    damage: weapon.damage,
+    shot_events.write(ShotFired {
+        shooter: player_entity,
+        kind: ShotKind::Rail,
+        origin: ray_origin,
+        direction: aim_direction,
+        hit: !pierced.is_empty(),
+        point: struck.map(|_| ray_end),
+        struck,
+    });
+}
+
+/// Fly projectiles under gravity, raycasting each sub-step so thin targets can't be skipped
+fn update_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    shootables: Shootables,
+    mut projectiles: Query<(Entity, &mut Transform, &mut Projectile)>,
+    mut hit_events: MessageWriter<HitEvent>,
+    mut shot_events: MessageWriter<ShotFired>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let dt = time.delta_secs();
+
+    for (entity, mut transform, mut projectile) in projectiles.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.is_finished() {
+            shot_events.write(ShotFired {
+                shooter: projectile.shooter,
+                kind: ShotKind::Projectile,
+                origin: transform.translation,
+                direction: projectile.velocity.normalize_or_zero(),
+                hit: false,
+                point: None,
+                struck: None,
+            });
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let modifiers = projectile.ammo.modifiers();
+        let mut passed = std::mem::take(&mut projectile.passed);
+
+        let frame_length = projectile.velocity.length() * dt;
+        let substeps = (frame_length / PROJECTILE_SUBSTEP).ceil().max(1.0) as u32;
+        let step_dt = dt / substeps as f32;
+
+        let mut position = transform.translation;
+        let mut resolved = false;
+
+        for _ in 0..substeps {
+            projectile.velocity.y -= projectile.gravity * step_dt;
+            let segment = projectile.velocity * step_dt;
+            let segment_length = segment.length();
+            if segment_length <= 0.0 {
+                continue;
+            }
+            let direction = segment / segment_length;
+
+            let not_passed = |entity: Entity| !passed.contains(&entity);
+            let filter = QueryFilter::default()
+                .exclude_rigid_body(projectile.shooter)
+                .exclude_sensors()
+                .groups(PhysicsLayer::Projectiles.groups())
+                .predicate(&not_passed);
+            let mut hit_entity: Option<(Entity, RayIntersection)> = None;
+            context.with_query_pipeline(filter, |query_pipeline| {
+                hit_entity = query_pipeline.cast_ray_and_get_normal(
+                    position,
+                    direction,
+                    segment_length,
+                    true,
+                );
+            });
+
+            if let Some((hit, intersection)) = hit_entity {
+                let toi = intersection.time_of_impact;
+                let point = position + direction * toi;
+                let resolved_hit = shootables.resolve(hit, projectile.damage, &modifiers, point);
+                let shootable = resolved_hit.is_some();
+                if !shootable && passed.len() < modifiers.penetration as usize {
+                    // Punched through; ignored from the next sub-step on
+                    passed.push(hit);
+                    position += segment;
+                    projectile.distance += segment_length;
+                    continue;
+                }
+                if let Some(resolved) = resolved_hit {
+                    hit_events.write(resolved.event(
+                        projectile.shooter,
+                        direction,
+                        point,
+                        intersection.normal,
+                        projectile.distance + toi,
+                    ));
+                }
+                shot_events.write(ShotFired {
+                    shooter: projectile.shooter,
+                    kind: ShotKind::Projectile,
+                    origin: position,
+                    direction,
+                    hit: shootable,
+                    point: Some(point),
+                    struck: Some((hit, intersection.normal)),
                 });
+                commands.entity(entity).despawn();
+                resolved = true;
+                break;
+            }
+
+            position += segment;
+            projectile.distance += segment_length;
+        }
+
+        if !resolved {
+            projectile.passed = passed;
+            transform.translation = position;
+            let heading = projectile.velocity.normalize_or_zero();
+            if heading != Vec3::ZERO {
+                transform.look_to(heading, Vec3::Y);
             }
         }
     }
 }
 
-fn generate_spread_directions(forward: Vec3, spread: f32, pellet_count: u8) -> Vec<Vec3> {
+/// Share of each hit a shootable's armor blocks; only zombies wear any
+fn armor_of(zombie: Option<&Zombie>) -> f32 {
+    zombie.map_or(0.0, |zombie| zombie.armor)
+}
+
+impl Shootables<'_, '_> {
+    fn contains(&self, entity: Entity) -> bool {
+        self.bodies.contains(entity)
+    }
+
+    /// Where a round striking `entity` at `point` lands: the body it hits, the HitZone
+    /// struck if it was one, and its damage after the zone and the body's armor. None
+    /// if `entity` can't be shot.
+    fn resolve(
+        &self,
+        entity: Entity,
+        damage: f32,
+        modifiers: &AmmoModifiers,
+        point: Vec3,
+    ) -> Option<ResolvedHit> {
+        let (zombie, zone) = self.bodies.get(entity).ok()?;
+        let (body, zone, damage, crit) = match zone {
+            None => (
+                entity,
+                None,
+                modifiers.damage_against(damage, armor_of(zombie)),
+                false,
+            ),
+            Some(zone) => {
+                let owner = self
+                    .bodies
+                    .get(zone.owner)
+                    .ok()
+                    .and_then(|(zombie, _)| zombie);
+                (
+                    zone.owner,
+                    Some(entity),
+                    modifiers.damage_against(damage * zone.multiplier, armor_of(owner)),
+                    zone.multiplier > 1.0,
+                )
+            }
+        };
+        Some(ResolvedHit {
+            body,
+            zone,
+            damage,
+            headshot: self
+                .zombies
+                .get(body)
+                .is_ok_and(|transform| is_headshot(point, transform)),
+            crit,
+            surface: surface_of(entity, &self.surfaces, &self.parents),
+        })
+    }
+}
+
+fn apply_knockback(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Knockback, &mut KinematicCharacterController)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut knockback, mut controller) in query.iter_mut() {
+        let push = knockback.velocity * dt;
+        controller.translation = Some(controller.translation.unwrap_or(Vec3::ZERO) + push);
+
+        knockback.velocity *= (-6.0 * dt).exp();
+        if knockback.velocity.length_squared() < 0.01 {
+            commands.entity(entity).remove::<Knockback>();
+        }
+    }
+}
+
+fn generate_spread_directions(
+    rng: &mut GameRng,
+    forward: Vec3,
+    spread: f32,
+    pellet_count: u8,
+) -> Vec<Vec3> {
     if pellet_count == 1 && spread == 0.0 {
         return vec![forward];
     }
 
-    let mut rng = rand::rng();
     let mut directions = Vec::with_capacity(pellet_count as usize);
 
     // Find perpendicular vectors for spread calculation
@@ -593,47 +1764,732 @@ fn generate_spread_directions(forward: Vec3, spread: f32, pellet_count: u8) -> V
     directions
 }
 
-fn spawn_debug_ray(
-    commands: &mut Commands,
-    ray_origin: Vec3,
-    ray_end: Vec3,
-    ray_direction: Vec3,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-) {
-    let ray_length = (ray_end - ray_origin).length();
-    let ray_center = (ray_origin + ray_end) / 2.0;
-    let ray_rotation = Quat::from_rotation_arc(Vec3::Y, ray_direction);
-
-    commands.spawn((
-        Mesh3d(meshes.add(Cuboid::new(0.02, ray_length, 0.02))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(1.0, 1.0, 0.0),
-            unlit: true,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::INPUT_BUFFER_WINDOW;
+    use crate::enemies::{spawn_standing_zombie, Team, ZombieAssets, ZombieKind};
+    use crate::player::{PlayerActionsPlugin, PlayerHealth, PLAYER_HALF_HEIGHT, PLAYER_RADIUS};
+    use crate::world::{boot, headless_app, spawn_floor, SIMULATION_HZ};
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll};
+    use std::time::Duration;
+
+    /// One frame on the harness clock
+    const FRAME: f64 = 1.0 / SIMULATION_HZ;
+
+    /// The player holding the pistol, with an SMG in the second slot, and the input to
+    /// reload chain of the shooting systems
+    fn armed_player(buffer_window: f32) -> (App, Entity) {
+        armed_player_with(buffer_window, |_| {})
+    }
+
+    /// `armed_player`, with `extra` adding to the app before it boots
+    fn armed_player_with(buffer_window: f32, extra: impl FnOnce(&mut App)) -> (App, Entity) {
+        let mut app = headless_app(3);
+        app.init_resource::<PlayerActions>()
+            .init_resource::<PlayerPerks>()
+            .init_resource::<BulletTime>()
+            .init_resource::<InputBuffer>()
+            .insert_resource(InputBufferSettings {
+                window: buffer_window,
+            })
+            .insert_resource(AimRay {
+                origin: Vec3::new(0.0, 1.5, 0.0),
+                direction: Vec3::NEG_Z,
+                target: None,
+            })
+            .add_systems(Startup, setup_projectile_assets)
+            .add_systems(
+                Update,
+                (
+                    buffer_combat_inputs,
+                    handle_weapon_switch,
+                    handle_reload_input,
+                    process_reload,
+                    shoot,
+                )
+                    .chain(),
+            );
+        extra(&mut app);
+        boot(&mut app);
+
+        let balance = app.world().resource::<BalanceData>();
+        let inventory = WeaponInventory::starting(WeaponType::Smg, balance);
+        app.world_mut().spawn(ThirdPersonCamera::default());
+        let player = app
+            .world_mut()
+            .spawn((
+                Player::default(),
+                Transform::from_xyz(0.0, 0.9, 0.0),
+                inventory,
+            ))
+            .id();
+        (app, player)
+    }
+
+    /// Run a frame with `actions` pressed, the player's clock moving with it
+    fn frame(app: &mut App, actions: PlayerActions) {
+        *app.world_mut().resource_mut::<PlayerActions>() = actions;
+        app.world_mut().resource_mut::<BulletTime>().player_elapsed += FRAME;
+        app.update();
+    }
+
+    fn idle(app: &mut App, frames: usize) {
+        for _ in 0..frames {
+            frame(app, PlayerActions::default());
+        }
+    }
+
+    /// Start a reload of the current weapon with `frames` left to run
+    fn reloading(app: &mut App, player: Entity, frames: u32) {
+        let mut timer = Timer::from_seconds(1.0, TimerMode::Once);
+        timer.set_elapsed(Duration::from_secs(1) - Duration::from_secs_f64(FRAME) * frames);
+        app.world_mut()
+            .entity_mut(player)
+            .insert(ReloadState(timer));
+    }
+
+    fn weapon(app: &App, player: Entity, slot: usize) -> &Weapon {
+        app.world().get::<WeaponInventory>(player).unwrap().weapons[slot]
+            .as_ref()
+            .unwrap()
+    }
+
+    fn set_ammo(app: &mut App, player: Entity, slot: usize, ammo: u32) {
+        let mut inventory = app.world_mut().get_mut::<WeaponInventory>(player).unwrap();
+        inventory.weapons[slot].as_mut().unwrap().current_ammo = ammo;
+    }
+
+    #[test]
+    fn fire_pressed_as_a_reload_ends_goes_off_once_it_has() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        set_ammo(&mut app, player, 0, 2);
+        reloading(&mut app, player, 3);
+        let magazine = weapon(&app, player, 0).magazine_size;
+
+        frame(
+            &mut app,
+            PlayerActions {
+                fire_pressed: true,
+                ..default()
+            },
+        );
+        assert_eq!(weapon(&app, player, 0).current_ammo, 2);
+
+        idle(&mut app, 3);
+        assert!(app.world().get::<ReloadState>(player).is_none());
+        assert_eq!(weapon(&app, player, 0).current_ammo, magazine - 1);
+        assert_eq!(app.world().resource::<InputBuffer>().pending(), None);
+    }
+
+    #[test]
+    fn fire_pressed_as_a_reload_ends_is_lost_without_the_buffer() {
+        let (mut app, player) = armed_player(0.0);
+        set_ammo(&mut app, player, 0, 2);
+        reloading(&mut app, player, 3);
+        let magazine = weapon(&app, player, 0).magazine_size;
+
+        frame(
+            &mut app,
+            PlayerActions {
+                fire_pressed: true,
+                ..default()
+            },
+        );
+        idle(&mut app, 3);
+        assert_eq!(weapon(&app, player, 0).current_ammo, magazine);
+    }
+
+    #[test]
+    fn reload_pressed_during_a_switch_reloads_the_new_weapon() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        set_ammo(&mut app, player, 0, 2);
+        set_ammo(&mut app, player, 1, 5);
+        // Still putting the pistol's magazine in when the switch is pressed
+        reloading(&mut app, player, 4);
+
+        frame(
+            &mut app,
+            PlayerActions {
+                select_slot: Some(1),
+                ..default()
+            },
+        );
+        assert_eq!(
+            app.world()
+                .get::<WeaponInventory>(player)
+                .unwrap()
+                .current_slot,
+            0
+        );
+        idle(&mut app, 4);
+        assert_eq!(
+            app.world()
+                .get::<WeaponInventory>(player)
+                .unwrap()
+                .current_slot,
+            1
+        );
+
+        frame(
+            &mut app,
+            PlayerActions {
+                reload: true,
+                ..default()
+            },
+        );
+        assert!(app.world().get::<ReloadState>(player).is_some());
+        let reload =
+            compute_effective_stats(weapon(&app, player, 1), &PlayerPerks::default()).reload_time;
+        idle(&mut app, (reload as f64 / FRAME).ceil() as usize + 1);
+        let smg = weapon(&app, player, 1);
+        assert_eq!(smg.current_ammo, smg.magazine_size);
+        assert_eq!(
+            weapon(&app, player, 0).current_ammo,
+            weapon(&app, player, 0).magazine_size
+        );
+    }
+
+    #[test]
+    fn a_newer_press_replaces_the_buffered_one() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        set_ammo(&mut app, player, 0, 2);
+        reloading(&mut app, player, 6);
+
+        frame(
+            &mut app,
+            PlayerActions {
+                select_slot: Some(1),
+                ..default()
+            },
+        );
+        frame(
+            &mut app,
+            PlayerActions {
+                fire_pressed: true,
+                ..default()
+            },
+        );
+        idle(&mut app, 6);
+        // The fire went through on the pistol and the switch was dropped
+        let inventory = app.world().get::<WeaponInventory>(player).unwrap();
+        assert_eq!(inventory.current_slot, 0);
+        let pistol = weapon(&app, player, 0);
+        assert_eq!(pistol.current_ammo, pistol.magazine_size - 1);
+    }
+
+    fn click() -> PlayerActions {
+        PlayerActions {
+            fire_pressed: true,
             ..default()
-        })),
-        Transform::from_translation(ray_center).with_rotation(ray_rotation),
-        DebugRay {
-            timer: Timer::from_seconds(1.5, TimerMode::Once),
-        },
-    ));
-}
+        }
+    }
 
-fn update_shoot_cooldown(time: Res<Time>, mut cooldowns: Query<&mut ShootCooldown>) {
-    for mut cooldown in cooldowns.iter_mut() {
-        cooldown.0.tick(time.delta());
+    #[test]
+    fn cooldown_runs_from_the_last_shot() {
+        let mut pistol = Weapon::new(WeaponType::Pistol, &BalanceData::embedded());
+        assert!(pistol.ready_at(0.0));
+
+        let cooldown = pistol.shot_cooldown() as f64;
+        pistol.last_shot = Some(2.0);
+        assert!((pistol.cooldown_remaining(2.0) - cooldown).abs() < 1e-9);
+        assert!(!pistol.ready_at(2.0 + cooldown / 2.0));
+        assert!(pistol.ready_at(2.0 + cooldown));
+        assert_eq!(pistol.cooldown_remaining(10.0), 0.0);
     }
-}
 
-fn update_debug_rays(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut rays: Query<(Entity, &mut DebugRay)>,
-) {
-    for (entity, mut ray) in rays.iter_mut() {
-        ray.timer.tick(time.delta());
-        if ray.timer.is_finished() {
-            commands.entity(entity).despawn();
+    #[test]
+    fn a_smaller_magazine_returns_the_extra_rounds() {
+        let mut balance = BalanceData::embedded();
+        let mut pistol = Weapon::new(WeaponType::Pistol, &balance);
+        pistol.current_ammo = 12;
+        *pistol.reserve_mut(AmmoType::Standard) = 30;
+
+        balance.weapons.pistol.magazine_size = 8;
+        pistol.apply_balance(balance.weapon(WeaponType::Pistol), &PlayerPerks::default());
+        assert_eq!(pistol.current_ammo, 8);
+        assert_eq!(pistol.reserve(AmmoType::Standard), 34);
+    }
+
+    #[test]
+    fn clicking_every_frame_fires_at_the_weapon_rate() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        let cooldown = weapon(&app, player, 0).shot_cooldown() as f64;
+
+        let mut shots: Vec<f64> = Vec::new();
+        for _ in 0..(2.0 / FRAME) as usize {
+            frame(&mut app, click());
+            if let Some(last) = weapon(&app, player, 0).last_shot {
+                if shots.last() != Some(&last) {
+                    shots.push(last);
+                }
+            }
         }
+        // Two seconds of the pistol's three rounds a second
+        assert_eq!(shots.len(), 6);
+        for pair in shots.windows(2) {
+            let gap = pair[1] - pair[0];
+            assert!(gap >= cooldown - 1e-9, "fired {gap}s after the last round");
+            // No click was eaten: each round went off the first frame it could
+            assert!(gap < cooldown + FRAME, "held back {gap}s");
+        }
+    }
+
+    #[test]
+    fn a_click_just_before_the_cooldown_ends_is_queued() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        let magazine = weapon(&app, player, 0).magazine_size;
+        let frames_to_ready = (weapon(&app, player, 0).shot_cooldown() as f64 / FRAME).ceil();
+        let frames_to_ready = frames_to_ready as usize;
+
+        frame(&mut app, click());
+        let first = weapon(&app, player, 0).last_shot.unwrap();
+
+        // Well inside the cooldown, the click is dropped
+        idle(&mut app, frames_to_ready / 2 - 1);
+        frame(&mut app, click());
+        assert!(app.world().get::<QueuedShot>(player).is_none());
+
+        // A frame short of ready, it waits
+        idle(&mut app, frames_to_ready - frames_to_ready / 2 - 2);
+        frame(&mut app, click());
+        assert!(app.world().get::<QueuedShot>(player).is_some());
+        assert_eq!(weapon(&app, player, 0).current_ammo, magazine - 1);
+
+        idle(&mut app, 1);
+        let pistol = weapon(&app, player, 0);
+        assert_eq!(pistol.current_ammo, magazine - 2);
+        assert_eq!(
+            pistol.last_shot,
+            Some(first + frames_to_ready as f64 * FRAME)
+        );
+        assert!(app.world().get::<QueuedShot>(player).is_none());
+
+        // The early click didn't bank a third round
+        idle(&mut app, 2 * frames_to_ready);
+        assert_eq!(weapon(&app, player, 0).current_ammo, magazine - 2);
+    }
+
+    #[test]
+    fn reload_takes_what_the_reserve_has() {
+        let perks = PlayerPerks::default();
+        let mut pistol = Weapon::new(WeaponType::Pistol, &BalanceData::embedded());
+        pistol.current_ammo = 2;
+        *pistol.reserve_mut(AmmoType::Standard) = 4;
+
+        assert!(pistol.can_reload(&perks));
+        assert_eq!(pistol.reload(&perks), 4);
+        assert_eq!(pistol.current_ammo, 6);
+        assert_eq!(pistol.reserve(AmmoType::Standard), 0);
+        assert!(!pistol.can_reload(&perks));
+
+        *pistol.reserve_mut(AmmoType::Standard) = 40;
+        let topped_up = pistol.magazine_size - 6;
+        assert_eq!(pistol.reload(&perks), topped_up);
+        assert_eq!(pistol.current_ammo, pistol.magazine_size);
+        assert_eq!(pistol.reserve(AmmoType::Standard), 40 - topped_up);
+    }
+
+    #[test]
+    fn reloading_a_full_magazine_does_nothing() {
+        let perks = PlayerPerks::default();
+        let mut pistol = Weapon::new(WeaponType::Pistol, &BalanceData::embedded());
+        let reserve = pistol.reserve(AmmoType::Standard);
+        assert!(!pistol.can_reload(&perks));
+        assert_eq!(pistol.reload(&perks), 0);
+        assert_eq!(pistol.current_ammo, pistol.magazine_size);
+        assert_eq!(pistol.reserve(AmmoType::Standard), reserve);
+
+        // Nor does R start a reload
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        frame(
+            &mut app,
+            PlayerActions {
+                reload: true,
+                ..default()
+            },
+        );
+        assert!(app.world().get::<ReloadState>(player).is_none());
+    }
+
+    #[test]
+    fn each_trigger_pull_spends_one_round() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        let shotgun = Weapon::new(WeaponType::Shotgun, app.world().resource::<BalanceData>());
+        let shells = shotgun.magazine_size;
+        {
+            let mut inventory = app.world_mut().get_mut::<WeaponInventory>(player).unwrap();
+            inventory.weapons[1] = Some(shotgun);
+            inventory.current_slot = 1;
+        }
+
+        frame(&mut app, click());
+        assert_eq!(weapon(&app, player, 1).current_ammo, shells - 1);
+        frame(&mut app, click());
+        // Still cycling the first shell
+        assert_eq!(weapon(&app, player, 1).current_ammo, shells - 1);
+    }
+
+    #[test]
+    fn an_empty_magazine_reloads_instead_of_firing() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        set_ammo(&mut app, player, 0, 0);
+
+        frame(&mut app, click());
+        assert_eq!(weapon(&app, player, 0).last_shot, None);
+        assert!(app.world().get::<ReloadState>(player).is_some());
+    }
+
+    #[test]
+    fn an_empty_weapon_with_no_reserve_dry_fires() {
+        let (mut app, player) = armed_player(INPUT_BUFFER_WINDOW);
+        set_ammo(&mut app, player, 0, 0);
+        {
+            let mut inventory = app.world_mut().get_mut::<WeaponInventory>(player).unwrap();
+            let pistol = inventory.weapons[0].as_mut().unwrap();
+            *pistol.reserve_mut(AmmoType::Standard) = 0;
+        }
+
+        frame(&mut app, click());
+        assert_eq!(weapon(&app, player, 0).last_shot, None);
+        assert!(app.world().get::<ReloadState>(player).is_none());
+        let clicked = app
+            .world()
+            .resource::<Messages<Subtitle>>()
+            .iter_current_update_messages()
+            .any(|subtitle| subtitle.0 == "[Dry fire click]");
+        assert!(clicked);
+    }
+
+    fn slots(inventory: &WeaponInventory) -> Vec<Option<WeaponType>> {
+        inventory
+            .weapons
+            .iter()
+            .map(|slot| slot.as_ref().map(|weapon| weapon.weapon_type))
+            .collect()
+    }
+
+    fn equipped(inventory: &WeaponInventory) -> Option<WeaponType> {
+        inventory.current_weapon().map(|weapon| weapon.weapon_type)
+    }
+
+    #[test]
+    fn a_full_inventory_hands_back_a_new_weapon() {
+        let balance = BalanceData::embedded();
+        let mut inventory = WeaponInventory::starting(WeaponType::Smg, &balance);
+        assert_eq!(inventory.capacity(), STARTING_WEAPON_SLOTS);
+        assert_eq!(inventory.weapon_count(), STARTING_WEAPON_SLOTS);
+
+        let refused = inventory
+            .add(Weapon::new(WeaponType::Shotgun, &balance))
+            .unwrap_err();
+        assert_eq!(refused.weapon_type, WeaponType::Shotgun);
+        assert_eq!(
+            slots(&inventory),
+            [Some(WeaponType::Pistol), Some(WeaponType::Smg)]
+        );
+    }
+
+    #[test]
+    fn expanding_adds_empty_slots_to_fill() {
+        let balance = BalanceData::embedded();
+        let mut inventory = WeaponInventory::starting(WeaponType::Smg, &balance);
+        inventory.expand_to(4);
+        assert_eq!(inventory.capacity(), 4);
+        assert_eq!(inventory.weapon_count(), 2);
+
+        assert_eq!(
+            inventory
+                .add(Weapon::new(WeaponType::Rifle, &balance))
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            inventory
+                .add(Weapon::new(WeaponType::Arc, &balance))
+                .unwrap(),
+            3
+        );
+        assert_eq!(inventory.weapon_count(), 4);
+        assert!(inventory
+            .add(Weapon::new(WeaponType::Shotgun, &balance))
+            .is_err());
+
+        // Never shrinks
+        inventory.expand_to(2);
+        assert_eq!(inventory.capacity(), 4);
+    }
+
+    #[test]
+    fn swapping_slots_keeps_the_equipped_weapon() {
+        let balance = BalanceData::embedded();
+        let mut inventory = WeaponInventory::starting(WeaponType::Smg, &balance);
+        inventory.expand_to(3);
+        inventory
+            .add(Weapon::new(WeaponType::Rifle, &balance))
+            .unwrap();
+
+        inventory.swap_slots(0, 2);
+        assert_eq!(
+            slots(&inventory),
+            [
+                Some(WeaponType::Rifle),
+                Some(WeaponType::Smg),
+                Some(WeaponType::Pistol)
+            ]
+        );
+        assert_eq!(inventory.current_slot, 2);
+        assert_eq!(equipped(&inventory), Some(WeaponType::Pistol));
+
+        // Out of range does nothing
+        inventory.swap_slots(1, 3);
+        assert_eq!(slots(&inventory)[1], Some(WeaponType::Smg));
+    }
+
+    #[test]
+    fn dropping_the_equipped_weapon_equips_the_next() {
+        let balance = BalanceData::embedded();
+        let mut inventory = WeaponInventory::starting(WeaponType::Smg, &balance);
+        inventory.expand_to(3);
+
+        let dropped = inventory.take(0).unwrap();
+        assert_eq!(dropped.weapon_type, WeaponType::Pistol);
+        assert_eq!(equipped(&inventory), Some(WeaponType::Smg));
+        assert_eq!(inventory.weapon_count(), 1);
+        assert!(inventory.take(0).is_none());
+
+        // The freed slot is the first one filled
+        assert_eq!(inventory.add(dropped).unwrap(), 0);
+        assert_eq!(equipped(&inventory), Some(WeaponType::Smg));
+    }
+
+    #[test]
+    fn replacing_swaps_out_the_equipped_weapon() {
+        let balance = BalanceData::embedded();
+        let mut inventory = WeaponInventory::starting(WeaponType::Smg, &balance);
+        inventory.switch_to(1);
+
+        let old = inventory
+            .replace_current(Weapon::new(WeaponType::Marksman, &balance))
+            .unwrap();
+        assert_eq!(old.weapon_type, WeaponType::Smg);
+        assert_eq!(equipped(&inventory), Some(WeaponType::Marksman));
+        assert_eq!(inventory.weapon_count(), 2);
+    }
+
+    /// Inner face of the wall in `zombie_by_a_wall`
+    const WALL_FACE: f32 = 3.0;
+
+    /// A walker 1.5 m from a wall, with the player well off on the other side and
+    /// knockback layered on as in play
+    fn zombie_by_a_wall() -> (App, Entity) {
+        let mut app = headless_app(5);
+        app.add_systems(
+            FixedPostUpdate,
+            apply_knockback.before(PhysicsSet::SyncBackend),
+        );
+        boot(&mut app);
+        spawn_floor(&mut app);
+        app.world_mut().spawn((
+            Transform::from_xyz(WALL_FACE + 0.5, 3.0, 0.0),
+            RigidBody::Fixed,
+            Collider::cuboid(0.5, 3.0, 20.0),
+            PhysicsLayer::World.groups(),
+        ));
+        app.world_mut().spawn((
+            Player::default(),
+            PlayerHealth::default(),
+            Team::Survivors,
+            Transform::from_xyz(-10.0, PLAYER_HALF_HEIGHT + PLAYER_RADIUS, 0.0),
+            RigidBody::KinematicPositionBased,
+            Collider::capsule_y(PLAYER_HALF_HEIGHT, PLAYER_RADIUS),
+            PhysicsLayer::Player.groups(),
+        ));
+        let zombie = app
+            .world_mut()
+            .run_system_once(
+                |mut commands: Commands, assets: Res<ZombieAssets>, balance: Res<BalanceData>| {
+                    let zombie = Zombie::new(ZombieKind::Walker, 0, &balance);
+                    spawn_standing_zombie(&mut commands, &assets, Vec3::new(1.5, 1.0, 0.0), zombie)
+                },
+            )
+            .expect("spawning a walker");
+        (app, zombie)
+    }
+
+    #[test]
+    fn a_blast_pins_a_zombie_to_the_wall_without_pushing_it_through() {
+        let (mut app, zombie) = zombie_by_a_wall();
+        // Harder than a point-blank explosion; with no wall it would slide 5 m
+        app.world_mut().entity_mut(zombie).insert(Knockback {
+            velocity: Vec3::X * 30.0,
+        });
+
+        let mut furthest = f32::MIN;
+        for _ in 0..(2.0 * SIMULATION_HZ) as u32 {
+            app.update();
+            let x = app.world().get::<Transform>(zombie).unwrap().translation.x;
+            furthest = furthest.max(x);
+        }
+        // Reached the wall, but the capsule (radius 0.4) stayed on this side of it
+        assert!(
+            furthest > WALL_FACE - 0.6,
+            "never reached the wall: {furthest}"
+        );
+        assert!(
+            furthest < WALL_FACE - 0.3,
+            "pushed into the wall: {furthest}"
+        );
+    }
+
+    /// What the next shot's HitEvent says it struck: body, normal, surface and headshot
+    fn next_hit(app: &mut App) -> (Entity, Vec3, SurfaceMaterial, bool) {
+        // Let physics pick up anything just spawned first
+        idle(app, 1);
+        frame(app, click());
+        app.world()
+            .resource::<Messages<HitEvent>>()
+            .iter_current_update_messages()
+            .map(|hit| (hit.entity, hit.normal, hit.surface, hit.headshot))
+            .next()
+            .expect("the shot hit nothing")
+    }
+
+    fn aim_from_height(app: &mut App, y: f32) {
+        app.world_mut().insert_resource(AimRay {
+            origin: Vec3::new(0.0, y, 0.0),
+            direction: Vec3::NEG_Z,
+            target: None,
+        });
+    }
+
+    fn walker_ahead(app: &mut App) -> Entity {
+        spawn_floor(app);
+        app.world_mut()
+            .run_system_once(
+                |mut commands: Commands, assets: Res<ZombieAssets>, balance: Res<BalanceData>| {
+                    let zombie = Zombie::new(ZombieKind::Walker, 0, &balance);
+                    spawn_standing_zombie(&mut commands, &assets, Vec3::new(0.0, 1.0, -5.0), zombie)
+                },
+            )
+            .expect("spawning a walker")
+    }
+
+    fn mouse(app: &mut App) -> Mut<'_, ButtonInput<MouseButton>> {
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>()
+    }
+
+    fn nothing_fired(app: &App) -> bool {
+        app.world()
+            .resource::<Messages<ShotFired>>()
+            .iter_current_update_messages()
+            .next()
+            .is_none()
+            && app
+                .world()
+                .resource::<Messages<HitEvent>>()
+                .iter_current_update_messages()
+                .next()
+                .is_none()
+    }
+
+    #[test]
+    fn the_click_on_resume_fires_nothing() {
+        // Actions read off the devices, as in the game, rather than set by `frame`
+        let (mut app, player) = armed_player_with(INPUT_BUFFER_WINDOW, |app| {
+            app.add_plugins(PlayerActionsPlugin)
+                .init_resource::<ButtonInput<KeyCode>>()
+                .init_resource::<ButtonInput<MouseButton>>()
+                .init_resource::<AccumulatedMouseMotion>()
+                .init_resource::<AccumulatedMouseScroll>();
+        });
+        app.world_mut().spawn((
+            Shootable,
+            Transform::from_xyz(0.0, 1.5, -5.0),
+            RigidBody::Fixed,
+            Collider::cuboid(2.0, 2.0, 0.25),
+            PhysicsLayer::Enemies.groups(),
+        ));
+        let magazine = weapon(&app, player, 0).current_ammo;
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Paused);
+        app.update();
+
+        // Resume clicked with the mouse still down over the button
+        mouse(&mut app).press(MouseButton::Left);
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Playing
+        );
+        assert!(nothing_fired(&app));
+
+        // Held on past the resume frame
+        mouse(&mut app).clear();
+        app.update();
+        assert!(nothing_fired(&app));
+        assert_eq!(weapon(&app, player, 0).current_ammo, magazine);
+
+        // A fresh click once it's been let go fires as normal
+        mouse(&mut app).release(MouseButton::Left);
+        app.update();
+        mouse(&mut app).clear();
+        mouse(&mut app).press(MouseButton::Left);
+        app.update();
+        assert!(!nothing_fired(&app));
+        assert_eq!(weapon(&app, player, 0).current_ammo, magazine - 1);
+    }
+
+    #[test]
+    fn a_hit_carries_the_surface_and_normal_it_struck() {
+        let (mut app, _) = armed_player(INPUT_BUFFER_WINDOW);
+        let plate = app
+            .world_mut()
+            .spawn((
+                Shootable,
+                SurfaceMaterial::Metal,
+                Transform::from_xyz(0.0, 1.5, -5.0),
+                RigidBody::Fixed,
+                Collider::cuboid(2.0, 2.0, 0.25),
+                PhysicsLayer::Enemies.groups(),
+            ))
+            .id();
+
+        let (entity, normal, surface, headshot) = next_hit(&mut app);
+        assert_eq!(entity, plate);
+        assert!(normal.distance(Vec3::Z) < 1e-3, "normal {normal}");
+        assert_eq!(surface, SurfaceMaterial::Metal);
+        assert!(!headshot);
+    }
+
+    #[test]
+    fn a_round_above_the_head_line_is_a_headshot() {
+        let (mut app, _) = armed_player(INPUT_BUFFER_WINDOW);
+        let walker = walker_ahead(&mut app);
+        // The walker stands from 0.0 to 2.0 with its head line at 1.6
+        aim_from_height(&mut app, 1.8);
+
+        let (entity, normal, _, headshot) = next_hit(&mut app);
+        assert_eq!(entity, walker);
+        assert!(normal.z > 0.0, "normal {normal}");
+        assert!(headshot);
+    }
+
+    #[test]
+    fn a_round_below_the_head_line_is_a_body_shot() {
+        let (mut app, _) = armed_player(INPUT_BUFFER_WINDOW);
+        let walker = walker_ahead(&mut app);
+        aim_from_height(&mut app, 1.2);
+
+        let (entity, _, surface, headshot) = next_hit(&mut app);
+        assert_eq!(entity, walker);
+        assert_eq!(surface, SurfaceMaterial::default());
+        assert!(!headshot);
     }
 }
@@ -0,0 +1,283 @@
+use super::{AmmoType, HitEvent, WeaponInventory};
+use crate::enemies::Zombie;
+use crate::player::Player;
+use crate::ui::GameState;
+use crate::world::SurfaceMaterial;
+use bevy::prelude::*;
+use std::mem::discriminant;
+
+/// Damage over time on zombies. Ticks are dealt as ordinary HitEvents, so they go
+/// through the same health, flash and kill-credit path as shots, and everything runs
+/// on the game clock so nothing ticks while paused.
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ApplyStatus>().add_systems(
+            Update,
+            (
+                apply_incendiary_hits,
+                tick_status_effects,
+                apply_status_effects,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Seconds between damage ticks
+const STATUS_TICK: f32 = 0.5;
+/// Entries of one kind a zombie can carry; more refresh the oldest instead
+const MAX_STACKS: usize = 3;
+/// Burning zombies set alight one neighbour this close
+const IGNITE_RADIUS: f32 = 1.0;
+
+/// Applied by each incendiary pellet that hits
+pub const INCENDIARY_BURN: StatusEffect = StatusEffect::Burning {
+    dps: 6.0,
+    remaining: 3.0,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusEffect {
+    Burning {
+        dps: f32,
+        remaining: f32,
+    },
+    Poisoned {
+        dps: f32,
+        remaining: f32,
+        /// Share of movement speed taken away, 0.0 to 1.0
+        slow: f32,
+    },
+}
+
+impl StatusEffect {
+    fn dps(&self) -> f32 {
+        match *self {
+            StatusEffect::Burning { dps, .. } | StatusEffect::Poisoned { dps, .. } => dps,
+        }
+    }
+
+    fn remaining_mut(&mut self) -> &mut f32 {
+        match self {
+            StatusEffect::Burning { remaining, .. } | StatusEffect::Poisoned { remaining, .. } => {
+                remaining
+            }
+        }
+    }
+
+    fn remaining(&self) -> f32 {
+        match *self {
+            StatusEffect::Burning { remaining, .. } | StatusEffect::Poisoned { remaining, .. } => {
+                remaining
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEntry {
+    pub effect: StatusEffect,
+    /// Credited with ticks and any kill
+    pub source: Option<Entity>,
+    /// Burning only: whether this entry has already set a neighbour alight
+    spread: bool,
+}
+
+/// Effects running on a zombie; removed once the last one runs out
+#[derive(Component)]
+pub struct StatusEffects {
+    pub entries: Vec<StatusEntry>,
+    tick: Timer,
+}
+
+impl Default for StatusEffects {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            tick: Timer::from_seconds(STATUS_TICK, TimerMode::Repeating),
+        }
+    }
+}
+
+impl StatusEffects {
+    /// Stack an effect, or refresh the entry of its kind closest to running out once
+    /// the stack is full
+    pub fn add(&mut self, effect: StatusEffect, source: Option<Entity>) {
+        let entry = StatusEntry {
+            effect,
+            source,
+            spread: false,
+        };
+        let same_kind =
+            |other: &&mut StatusEntry| discriminant(&other.effect) == discriminant(&effect);
+        if self.entries.iter_mut().filter(same_kind).count() < MAX_STACKS {
+            self.entries.push(entry);
+        } else if let Some(oldest) = self
+            .entries
+            .iter_mut()
+            .filter(same_kind)
+            .min_by(|a, b| a.effect.remaining().total_cmp(&b.effect.remaining()))
+        {
+            *oldest = entry;
+        }
+    }
+
+    pub fn is_burning(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry.effect, StatusEffect::Burning { .. }))
+    }
+
+    /// Movement multiplier; each poison stack slows further
+    pub fn speed_scale(&self) -> f32 {
+        self.entries
+            .iter()
+            .map(|entry| match entry.effect {
+                StatusEffect::Poisoned { slow, .. } => 1.0 - slow,
+                StatusEffect::Burning { .. } => 1.0,
+            })
+            .product()
+    }
+}
+
+/// Ask for an effect to be put on an entity; anything that isn't a zombie ignores it
+#[derive(Message)]
+pub struct ApplyStatus {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+    pub source: Option<Entity>,
+}
+
+/// Shots (not ticks or blasts, which travel no distance) from an incendiary weapon
+/// set what they hit alight
+fn apply_incendiary_hits(
+    mut hit_events: MessageReader<HitEvent>,
+    players: Query<(Entity, &WeaponInventory), With<Player>>,
+    mut status_events: MessageWriter<ApplyStatus>,
+) {
+    let Ok((player, inventory)) = players.single() else {
+        return;
+    };
+    let incendiary = inventory
+        .current_weapon()
+        .is_some_and(|weapon| weapon.loaded_ammo == AmmoType::Incendiary);
+
+    for event in hit_events.read() {
+        if incendiary && event.source == Some(player) && event.distance > 0.0 {
+            status_events.write(ApplyStatus {
+                entity: event.entity,
+                effect: INCENDIARY_BURN,
+                source: Some(player),
+            });
+        }
+    }
+}
+
+fn tick_status_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut zombies: Query<(Entity, &Transform, &Zombie, Option<&mut StatusEffects>)>,
+    mut hit_events: MessageWriter<HitEvent>,
+    mut status_events: MessageWriter<ApplyStatus>,
+) {
+    // Snapshot of who is where and already alight, for burns jumping between zombies
+    let mut candidates: Vec<(Entity, Vec3, bool)> = zombies
+        .iter()
+        .map(|(entity, transform, _, status)| {
+            (
+                entity,
+                transform.translation,
+                status.is_some_and(|status| status.is_burning()),
+            )
+        })
+        .collect();
+
+    for (entity, transform, zombie, status) in zombies.iter_mut() {
+        let Some(mut status) = status else {
+            continue;
+        };
+        // A dead zombie is despawned this frame; don't keep ticking on it
+        if zombie.health <= 0.0 {
+            commands.entity(entity).try_remove::<StatusEffects>();
+            continue;
+        }
+
+        status.tick.tick(time.delta());
+        if status.tick.just_finished() {
+            let mut damage = 0.0;
+            let mut source = None;
+            for entry in status.entries.iter_mut() {
+                damage += entry.effect.dps() * entry.effect.remaining().min(STATUS_TICK);
+                *entry.effect.remaining_mut() -= STATUS_TICK;
+                source = entry.source.or(source);
+            }
+            status
+                .entries
+                .retain(|entry| entry.effect.remaining() > 0.0);
+
+            if damage > 0.0 {
+                hit_events.write(HitEvent {
+                    entity,
+                    damage,
+                    direction: Vec3::ZERO,
+                    point: transform.translation,
+                    normal: Vec3::ZERO,
+                    distance: 0.0,
+                    source,
+                    zone: None,
+                    surface: SurfaceMaterial::default(),
+                    headshot: false,
+                    crit: false,
+                });
+            }
+        }
+
+        // Each burn can jump to one neighbour that isn't already alight
+        for entry in status.entries.iter_mut() {
+            if entry.spread || !matches!(entry.effect, StatusEffect::Burning { .. }) {
+                continue;
+            }
+            let neighbour = candidates.iter_mut().find(|(other, position, burning)| {
+                *other != entity
+                    && !*burning
+                    && position.distance(transform.translation) <= IGNITE_RADIUS
+            });
+            if let Some((neighbour, _, burning)) = neighbour {
+                entry.spread = true;
+                *burning = true;
+                status_events.write(ApplyStatus {
+                    entity: *neighbour,
+                    effect: entry.effect,
+                    source: entry.source,
+                });
+            }
+        }
+
+        if status.entries.is_empty() {
+            commands.entity(entity).try_remove::<StatusEffects>();
+        }
+    }
+}
+
+fn apply_status_effects(
+    mut commands: Commands,
+    mut status_events: MessageReader<ApplyStatus>,
+    mut zombies: Query<Option<&mut StatusEffects>, With<Zombie>>,
+) {
+    for event in status_events.read() {
+        let Ok(status) = zombies.get_mut(event.entity) else {
+            continue;
+        };
+        match status {
+            Some(mut status) => status.add(event.effect, event.source),
+            None => {
+                let mut status = StatusEffects::default();
+                status.add(event.effect, event.source);
+                commands.entity(event.entity).try_insert(status);
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use super::{update_aim_ray, AimRay, Shootable};
+use crate::enemies::Zombie;
+use crate::ui::{ColorPalette, GameState};
+use bevy::prelude::*;
+use bevy::render::render_resource::Face;
+
+/// Outlines the shootable thing under the crosshair, and for zombies shows their name
+/// and a health sliver under it. The outline is a slightly larger copy of the target's
+/// mesh drawn inside out, so shared zombie materials are never touched.
+pub struct TargetHighlightPlugin;
+
+impl Plugin for TargetHighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighlightedTarget>()
+            .add_systems(Startup, setup_outline_material)
+            .add_systems(OnEnter(GameState::Playing), spawn_target_readout)
+            .add_systems(
+                OnExit(GameState::Playing),
+                (clear_highlight, despawn_target_readout),
+            )
+            .add_systems(
+                Update,
+                (highlight_target, update_target_readout)
+                    .chain()
+                    .after(update_aim_ray)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// How much bigger the outline shell is than the target
+const OUTLINE_SCALE: f32 = 1.08;
+const READOUT_WIDTH: f32 = 100.0;
+
+#[derive(Resource)]
+struct OutlineMaterial(Handle<StandardMaterial>);
+
+#[derive(Resource, Default)]
+struct HighlightedTarget {
+    target: Option<Entity>,
+    /// Outline shell, a child of the target
+    outline: Option<Entity>,
+}
+
+#[derive(Component)]
+struct TargetReadout;
+
+#[derive(Component)]
+struct TargetReadoutName;
+
+#[derive(Component)]
+struct TargetReadoutFill;
+
+fn setup_outline_material(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(OutlineMaterial(materials.add(StandardMaterial {
+        base_color: Color::srgb(1.0, 0.85, 0.3),
+        unlit: true,
+        // Only the inside of the shell shows, as a rim around the target
+        cull_mode: Some(Face::Front),
+        ..default()
+    })));
+}
+
+/// Only rebuilds the outline when the crosshair moves to something else
+fn highlight_target(
+    mut commands: Commands,
+    aim: Res<AimRay>,
+    material: Res<OutlineMaterial>,
+    mut highlighted: ResMut<HighlightedTarget>,
+    targets: Query<&Mesh3d, With<Shootable>>,
+) {
+    let wanted = aim.target.filter(|entity| targets.contains(*entity));
+    if wanted == highlighted.target {
+        return;
+    }
+
+    // Already gone if the old target was despawned with it
+    if let Some(outline) = highlighted.outline.take() {
+        commands.entity(outline).try_despawn();
+    }
+    highlighted.target = wanted;
+
+    let Some((target, Ok(mesh))) = wanted.map(|entity| (entity, targets.get(entity))) else {
+        return;
+    };
+    let outline = commands
+        .spawn((
+            Mesh3d(mesh.0.clone()),
+            MeshMaterial3d(material.0.clone()),
+            Transform::from_scale(Vec3::splat(OUTLINE_SCALE)),
+            ChildOf(target),
+        ))
+        .id();
+    highlighted.outline = Some(outline);
+}
+
+fn clear_highlight(mut commands: Commands, mut highlighted: ResMut<HighlightedTarget>) {
+    if let Some(outline) = highlighted.outline.take() {
+        commands.entity(outline).try_despawn();
+    }
+    highlighted.target = None;
+}
+
+/// Name and health sliver just under the crosshair
+fn spawn_target_readout(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(50.0),
+                left: Val::Percent(50.0),
+                margin: UiRect {
+                    left: Val::Px(-READOUT_WIDTH / 2.0),
+                    top: Val::Px(20.0),
+                    ..default()
+                },
+                width: Val::Px(READOUT_WIDTH),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(3.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            TargetReadout,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TargetReadoutName,
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(3.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE),
+                        TargetReadoutFill,
+                    ));
+                });
+        });
+}
+
+fn update_target_readout(
+    highlighted: Res<HighlightedTarget>,
+    palette: Res<ColorPalette>,
+    zombies: Query<(&Zombie, Option<&Name>)>,
+    mut readout_q: Query<&mut Visibility, With<TargetReadout>>,
+    mut name_q: Query<&mut Text, With<TargetReadoutName>>,
+    mut fill_q: Query<(&mut Node, &mut BackgroundColor), With<TargetReadoutFill>>,
+) {
+    let zombie = highlighted
+        .target
+        .and_then(|entity| zombies.get(entity).ok());
+
+    for mut visibility in readout_q.iter_mut() {
+        *visibility = if zombie.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let Some((zombie, name)) = zombie else {
+        return;
+    };
+
+    // Named zombies (the boss) go by their name, the rest by their kind
+    let label = name.map_or(zombie.kind.name(), |name| name.as_str());
+    for mut text in name_q.iter_mut() {
+        if **text != label {
+            **text = label.to_string();
+        }
+    }
+
+    let fraction = (zombie.health / zombie.max_health).clamp(0.0, 1.0);
+    for (mut node, mut color) in fill_q.iter_mut() {
+        node.width = Val::Percent(fraction * 100.0);
+        *color = BackgroundColor(palette.health_color(fraction));
+    }
+}
+
+fn despawn_target_readout(mut commands: Commands, readouts: Query<Entity, With<TargetReadout>>) {
+    for entity in readouts.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,137 @@
+use super::{HitSystems, ShotFired, ShotKind};
+use crate::ui::GameState;
+use crate::world::{BudgetCategory, Budgeted};
+use bevy::prelude::*;
+
+/// A streak along every hitscan round and a glowing beam along rail shots, drawn from
+/// ShotFired
+pub struct TracerPlugin;
+
+impl Plugin for TracerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            spawn_tracers
+                .in_set(HitSystems::Feedback)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // Tracers clean up on every screen but freeze with the paused game, so
+        // they're still in the air on resume and in photo mode shots
+        .add_systems(
+            Update,
+            update_debug_rays.run_if(not(
+                in_state(GameState::Paused).or(in_state(GameState::PhotoMode))
+            )),
+        );
+    }
+}
+
+/// How far a round that hit nothing is drawn
+const TRACER_REACH: f32 = 100.0;
+
+/// Debug ray visualization
+#[derive(Component)]
+pub struct DebugRay {
+    pub timer: Timer,
+}
+
+fn spawn_tracers(
+    mut commands: Commands,
+    mut shots: MessageReader<ShotFired>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for shot in shots.read() {
+        let end = shot
+            .point
+            .unwrap_or(shot.origin + shot.direction * TRACER_REACH);
+        match shot.kind {
+            ShotKind::Hitscan => spawn_debug_ray(
+                &mut commands,
+                shot.origin,
+                end,
+                shot.direction,
+                &mut meshes,
+                &mut materials,
+            ),
+            ShotKind::Rail => spawn_rail_beam(
+                &mut commands,
+                shot.origin,
+                end,
+                shot.direction,
+                &mut meshes,
+                &mut materials,
+            ),
+            ShotKind::Projectile => {}
+        }
+    }
+}
+
+fn spawn_debug_ray(
+    commands: &mut Commands,
+    ray_origin: Vec3,
+    ray_end: Vec3,
+    ray_direction: Vec3,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+) {
+    let ray_length = (ray_end - ray_origin).length();
+    let ray_center = (ray_origin + ray_end) / 2.0;
+    let ray_rotation = Quat::from_rotation_arc(Vec3::Y, ray_direction);
+
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(0.02, ray_length, 0.02))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 1.0, 0.0),
+            unlit: true,
+            ..default()
+        })),
+        Transform::from_translation(ray_center).with_rotation(ray_rotation),
+        DebugRay {
+            timer: Timer::from_seconds(1.5, TimerMode::Once),
+        },
+        Budgeted(BudgetCategory::Tracer),
+    ));
+}
+
+/// Thick glowing tracer for rail shots, sharing the debug ray lifetime
+fn spawn_rail_beam(
+    commands: &mut Commands,
+    ray_origin: Vec3,
+    ray_end: Vec3,
+    ray_direction: Vec3,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+) {
+    let ray_length = (ray_end - ray_origin).length();
+    let ray_center = (ray_origin + ray_end) / 2.0;
+    let ray_rotation = Quat::from_rotation_arc(Vec3::Y, ray_direction);
+
+    commands.spawn((
+        Mesh3d(meshes.add(Cylinder::new(0.08, ray_length))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::srgb(0.4, 0.9, 1.0),
+            emissive: LinearRgba::rgb(1.0, 4.0, 6.0),
+            unlit: true,
+            ..default()
+        })),
+        Transform::from_translation(ray_center).with_rotation(ray_rotation),
+        DebugRay {
+            timer: Timer::from_seconds(0.6, TimerMode::Once),
+        },
+        Budgeted(BudgetCategory::Tracer),
+    ));
+}
+
+fn update_debug_rays(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rays: Query<(Entity, &mut DebugRay)>,
+) {
+    for (entity, mut ray) in rays.iter_mut() {
+        ray.timer.tick(time.delta());
+        if ray.timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
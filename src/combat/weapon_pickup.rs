@@ -0,0 +1,358 @@
+use super::{
+    highlight_pulse, BurstState, ChargingState, Interactable, Pickup, QueuedShot, ReloadState,
+    Weapon, WeaponInventory, WeaponType, BACKPACK_WEAPON_SLOTS,
+};
+use crate::enemies::ZombieDied;
+use crate::player::{PickupCollected, PickupKind, Player, PlayerActions};
+use crate::ui::{DifficultyModifiers, GameState};
+use crate::world::{BalanceData, BudgetCategory, Budgeted, GameRng};
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Weapons lying in the world: a few placed at the start of each run, plus drops the
+/// loadout screen queues while paused, which land at the player's feet on resume.
+/// With every slot full, holding E over one swaps it for the weapon in hand. Kills
+/// rarely drop a backpack, which adds a slot.
+pub struct WeaponPickupPlugin;
+
+impl Plugin for WeaponPickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingWeaponDrops>()
+            .add_systems(
+                Startup,
+                (setup_weapon_pickup_assets, spawn_weapon_swap_prompt),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Paused,
+                    entered: GameState::Playing,
+                },
+                spawn_pending_weapon_drops,
+            )
+            .add_systems(
+                Update,
+                (
+                    spin_weapon_pickups,
+                    collect_weapon_pickups,
+                    drop_backpacks,
+                    collect_backpacks,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), hide_weapon_swap_prompt)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                (despawn_weapon_pickups, spawn_world_weapons).chain(),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                (despawn_weapon_pickups, spawn_world_weapons).chain(),
+            );
+    }
+}
+
+const WEAPON_PICKUP_RADIUS: f32 = 1.2;
+/// Seconds E has to be held to swap the weapon in hand for one on the ground
+const SWAP_HOLD_TIME: f32 = 0.5;
+/// Base chance of a kill leaving a backpack, before the difficulty's drop modifier
+const BACKPACK_DROP_CHANCE: f32 = 0.01;
+
+/// Weapons that aren't in the starting loadout, lying in the arena at the start of a run
+const WORLD_WEAPONS: [(Vec3, WeaponType); 1] = [(Vec3::new(8.0, 0.3, 12.0), WeaponType::Arc)];
+
+/// Weapons dropped from the loadout screen, waiting for the game to resume
+#[derive(Resource, Default)]
+pub struct PendingWeaponDrops(pub Vec<Weapon>);
+
+/// Weapon on the ground, picked up into the first free slot by walking over it
+#[derive(Component)]
+#[require(Interactable)]
+pub struct WeaponPickup {
+    pub weapon: Weapon,
+    /// Set once the player has stepped away, so a fresh drop isn't grabbed straight back
+    armed: bool,
+}
+
+/// Backpack lying in the world; walking over it adds a weapon slot
+#[derive(Component)]
+#[require(Pickup)]
+pub struct BackpackPickup;
+
+/// "Hold [E] to swap", shown while standing on a weapon with every slot full
+#[derive(Component)]
+struct WeaponSwapPrompt;
+
+#[derive(Resource)]
+struct WeaponPickupAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    backpack_mesh: Handle<Mesh>,
+    backpack_material: Handle<StandardMaterial>,
+}
+
+fn setup_weapon_pickup_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(WeaponPickupAssets {
+        mesh: meshes.add(Cuboid::new(0.7, 0.15, 0.2)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.9, 0.7, 0.2),
+            emissive: LinearRgba::rgb(1.2, 0.8, 0.1),
+            ..default()
+        }),
+        backpack_mesh: meshes.add(Cuboid::new(0.4, 0.5, 0.25)),
+        backpack_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.45, 0.2),
+            emissive: LinearRgba::rgb(0.3, 0.6, 0.2),
+            ..default()
+        }),
+    });
+}
+
+fn spawn_weapon_swap_prompt(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(30.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Visibility::Hidden,
+        WeaponSwapPrompt,
+    ));
+}
+
+fn spawn_pending_weapon_drops(
+    mut commands: Commands,
+    assets: Res<WeaponPickupAssets>,
+    mut pending: ResMut<PendingWeaponDrops>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+
+    // Fan several drops out around the player's feet
+    for (i, weapon) in pending.0.drain(..).enumerate() {
+        let angle = i as f32 * 1.2;
+        let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * 0.5;
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation((player_transform.translation + offset).with_y(0.3)),
+            WeaponPickup {
+                weapon,
+                armed: false,
+            },
+        ));
+    }
+}
+
+fn spin_weapon_pickups(time: Res<Time>, mut pickups: Query<&mut Transform, With<WeaponPickup>>) {
+    for mut transform in pickups.iter_mut() {
+        transform.rotate_y(1.5 * time.delta_secs());
+    }
+}
+
+/// Walking over a weapon picks it up into a free slot. With none free, the nearest one
+/// in reach offers a swap, which holding E carries out: the weapon in hand drops in
+/// its place.
+fn collect_weapon_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<PlayerActions>,
+    mut swap_held: Local<f32>,
+    mut player_q: Query<(Entity, &Transform, &mut WeaponInventory), With<Player>>,
+    mut pickups: Query<(Entity, &Transform, &mut WeaponPickup), Without<Player>>,
+    mut prompt_q: Query<(&mut Text, &mut TextColor, &mut Visibility), With<WeaponSwapPrompt>>,
+    mut collected_events: MessageWriter<PickupCollected>,
+) {
+    let Ok((player, player_transform, mut inventory)) = player_q.single_mut() else {
+        return;
+    };
+
+    let mut swap: Option<(Entity, f32)> = None;
+    for (entity, pickup_transform, mut pickup) in pickups.iter_mut() {
+        let distance = (pickup_transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        if distance >= WEAPON_PICKUP_RADIUS {
+            pickup.armed = true;
+            continue;
+        }
+        if !pickup.armed {
+            continue;
+        }
+
+        // Every slot full: leave it on the ground and offer a swap instead
+        if inventory.add(pickup.weapon.clone()).is_err() {
+            if swap.is_none_or(|(_, nearest)| distance < nearest) {
+                swap = Some((entity, distance));
+            }
+            continue;
+        }
+        commands.entity(entity).despawn();
+        collected_events.write(PickupCollected {
+            kind: PickupKind::Weapon(pickup.weapon.weapon_type),
+            position: pickup_transform.translation,
+        });
+    }
+
+    let offer = swap.and_then(|(entity, _)| pickups.get_mut(entity).ok());
+    let Some((_, pickup_transform, mut pickup)) = offer else {
+        *swap_held = 0.0;
+        for (_, _, mut visibility) in prompt_q.iter_mut() {
+            *visibility = Visibility::Hidden;
+        }
+        return;
+    };
+
+    let held_name = inventory
+        .current_weapon()
+        .map_or("nothing", |weapon| weapon.weapon_type.name());
+    // Beats with the focus outline on the weapon, see PickupHighlightPlugin
+    let pulse = highlight_pulse(time.elapsed_secs());
+    for (mut text, mut color, mut visibility) in prompt_q.iter_mut() {
+        **text = format!(
+            "Hold [E] to swap {} for {}",
+            held_name,
+            pickup.weapon.weapon_type.name()
+        );
+        color.0 = Color::WHITE.with_alpha(0.6 + 0.4 * pulse);
+        *visibility = Visibility::Inherited;
+    }
+
+    *swap_held = if actions.interact_held {
+        *swap_held + time.delta_secs()
+    } else {
+        0.0
+    };
+    if *swap_held < SWAP_HOLD_TIME {
+        return;
+    }
+    *swap_held = 0.0;
+
+    // The weapon in hand goes down where the new one was, disarmed so it isn't
+    // swapped straight back
+    let Some(dropped) = inventory.replace_current(pickup.weapon.clone()) else {
+        return;
+    };
+    commands
+        .entity(player)
+        .remove::<(ReloadState, BurstState, QueuedShot, ChargingState)>();
+    collected_events.write(PickupCollected {
+        kind: PickupKind::Weapon(pickup.weapon.weapon_type),
+        position: pickup_transform.translation,
+    });
+    pickup.weapon = dropped;
+    pickup.armed = false;
+}
+
+fn hide_weapon_swap_prompt(mut prompt_q: Query<&mut Visibility, With<WeaponSwapPrompt>>) {
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Kills rarely leave a backpack, while the player has no backpack yet and there
+/// isn't one on the ground already
+fn drop_backpacks(
+    mut commands: Commands,
+    mut died_events: MessageReader<ZombieDied>,
+    assets: Res<WeaponPickupAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    mut rng: ResMut<GameRng>,
+    players: Query<&WeaponInventory, With<Player>>,
+    backpacks: Query<(), With<BackpackPickup>>,
+) {
+    let wanted = players
+        .single()
+        .is_ok_and(|inventory| inventory.capacity() < BACKPACK_WEAPON_SLOTS);
+    let mut dropped = !backpacks.is_empty();
+    for event in died_events.read() {
+        if !wanted || dropped {
+            continue;
+        }
+        if rng.random::<f32>() < BACKPACK_DROP_CHANCE * difficulty.drop_chance {
+            commands.spawn((
+                Mesh3d(assets.backpack_mesh.clone()),
+                MeshMaterial3d(assets.backpack_material.clone()),
+                Transform::from_translation(event.position.with_y(0.3)),
+                BackpackPickup,
+                Budgeted(BudgetCategory::Pickup),
+            ));
+            dropped = true;
+        }
+    }
+}
+
+fn collect_backpacks(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut WeaponInventory), With<Player>>,
+    backpacks: Query<(Entity, &Transform), (With<BackpackPickup>, Without<Player>)>,
+    mut collected_events: MessageWriter<PickupCollected>,
+) {
+    let Ok((player_transform, mut inventory)) = player_q.single_mut() else {
+        return;
+    };
+
+    for (entity, backpack_transform) in backpacks.iter() {
+        let distance = (backpack_transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        // One bought in the meantime makes it useless, so it stays put
+        if distance >= WEAPON_PICKUP_RADIUS || inventory.capacity() >= BACKPACK_WEAPON_SLOTS {
+            continue;
+        }
+        inventory.expand_to(BACKPACK_WEAPON_SLOTS);
+        commands.entity(entity).despawn();
+        collected_events.write(PickupCollected {
+            kind: PickupKind::Backpack,
+            position: backpack_transform.translation,
+        });
+    }
+}
+
+fn spawn_world_weapons(
+    mut commands: Commands,
+    assets: Res<WeaponPickupAssets>,
+    balance: Res<BalanceData>,
+) {
+    for (position, weapon_type) in WORLD_WEAPONS {
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation(position),
+            WeaponPickup {
+                weapon: Weapon::new(weapon_type, &balance),
+                armed: true,
+            },
+        ));
+    }
+}
+
+fn despawn_weapon_pickups(
+    mut commands: Commands,
+    pickups: Query<Entity, Or<(With<WeaponPickup>, With<BackpackPickup>)>>,
+    mut pending: ResMut<PendingWeaponDrops>,
+) {
+    pending.0.clear();
+    for entity in pickups.iter() {
+        commands.entity(entity).despawn();
+    }
+}
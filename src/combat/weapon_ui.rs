@@ -1,29 +1,93 @@
-use super::{ReloadState, WeaponInventory};
-use crate::player::{Player, PlayerHealth};
-use crate::ui::GameState;
+use super::{
+    compute_effective_stats, converged_aim_point, muzzle_blocked, update_aim_ray, AimRay,
+    ChargingState, ClearingJam, CookingGrenade, FireMode, FlareStock, GrenadeStock, ReloadState,
+    Shootable, WeaponInventory,
+};
+use crate::player::{
+    ActionState, Player, PlayerActionState, PlayerArmor, PlayerHealth, PlayerPerks,
+    ThirdPersonCamera,
+};
+use crate::ui::{ColorPalette, DifficultyModifiers, GameState, TextRole, ThemedText, UiTheme};
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::f32::consts::TAU;
 
 pub struct WeaponUiPlugin;
 
 impl Plugin for WeaponUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::Playing),
-            (spawn_weapon_hud, spawn_health_hud),
-        )
-        .add_systems(
-            OnExit(GameState::Playing),
-            (despawn_weapon_hud, despawn_health_hud),
-        )
-        .add_systems(
-            Update,
-            (update_weapon_hud, update_health_hud).run_if(in_state(GameState::Playing)),
-        );
+        app.init_resource::<HudSettings>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (spawn_weapon_hud, spawn_health_hud, spawn_aim_dot),
+            )
+            .add_systems(
+                OnExit(GameState::Playing),
+                (despawn_weapon_hud, despawn_health_hud, despawn_aim_dot),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_weapon_hud,
+                    update_health_hud,
+                    update_aim_dot.after(update_aim_ray),
+                    update_charge_bar,
+                    update_cook_ring,
+                    update_flare_text.run_if(resource_changed::<FlareStock>),
+                    update_grenade_text,
+                    update_ammo_type_text,
+                    update_slot_text,
+                    update_action_state_icon,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
 // === WEAPON HUD (bottom-right) ===
 
+/// How the magazine is shown in the weapon HUD
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AmmoDisplay {
+    #[default]
+    Numbers,
+    /// One pip per round, with the reserve as a small count beside them
+    Pips,
+    Both,
+}
+
+impl AmmoDisplay {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmmoDisplay::Numbers => "Numbers",
+            AmmoDisplay::Pips => "Pips",
+            AmmoDisplay::Both => "Both",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AmmoDisplay::Numbers => AmmoDisplay::Pips,
+            AmmoDisplay::Pips => AmmoDisplay::Both,
+            AmmoDisplay::Both => AmmoDisplay::Numbers,
+        }
+    }
+
+    fn shows_numbers(&self) -> bool {
+        *self != AmmoDisplay::Pips
+    }
+
+    fn shows_pips(&self) -> bool {
+        *self != AmmoDisplay::Numbers
+    }
+}
+
+/// Player preferences for the in-game HUD
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct HudSettings {
+    pub ammo_display: AmmoDisplay,
+}
+
 #[derive(Component)]
 struct WeaponHud;
 
@@ -36,10 +100,58 @@ struct FireModeText;
 #[derive(Component)]
 struct AmmoText;
 
+#[derive(Component)]
+struct AmmoTypeText;
+
+/// Row holding the pips and the reserve count
+#[derive(Component)]
+struct AmmoPipRow;
+
+/// Wrapping grid of AmmoPips, rebuilt only when the magazine size changes
+#[derive(Component, Default)]
+struct AmmoPips {
+    /// Magazine size the pips were built for
+    capacity: u32,
+}
+
+/// One round in the magazine, numbered from the first to be loaded
+#[derive(Component)]
+struct AmmoPip(u32);
+
+#[derive(Component)]
+struct ReserveText;
+
+const PIP_WIDTH: f32 = 5.0;
+const PIP_HEIGHT: f32 = 12.0;
+const PIP_GAP: f32 = 2.0;
+/// Pips per row before wrapping, so a drum mag stacks up instead of running off
+const PIPS_PER_ROW: u32 = 15;
+/// Rounds left at which the remaining pips start flashing
+const PIP_WARNING: u32 = 3;
+
 #[derive(Component)]
 struct ReloadIndicator;
 
-fn spawn_weapon_hud(mut commands: Commands) {
+/// Small badge for what the player is doing, hidden when idle
+#[derive(Component)]
+struct ActionStateIcon;
+
+/// Symbol inside the ActionStateIcon
+#[derive(Component)]
+struct ActionStateGlyph;
+
+const ACTION_ICON_SIZE: f32 = 22.0;
+
+#[derive(Component)]
+struct FlareText;
+
+#[derive(Component)]
+struct GrenadeText;
+
+#[derive(Component)]
+struct SlotText;
+
+fn spawn_weapon_hud(mut commands: Commands, theme: Res<UiTheme>, flares: Res<FlareStock>) {
     commands
         .spawn((
             Node {
@@ -59,47 +171,126 @@ fn spawn_weapon_hud(mut commands: Commands) {
             // Weapon name
             parent.spawn((
                 Text::new("PISTOL"),
-                TextFont {
-                    font_size: 28.0,
-                    ..default()
-                },
-                TextColor(Color::WHITE),
+                theme.text(TextRole::Heading),
                 WeaponNameText,
             ));
 
             // Fire mode
             parent.spawn((
                 Text::new("Semi-Auto"),
-                TextFont {
-                    font_size: 18.0,
-                    ..default()
-                },
-                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                theme.muted_text(TextRole::Caption),
                 FireModeText,
             ));
 
             // Ammo count
             parent.spawn((
                 Text::new("12 / 48"),
-                TextFont {
-                    font_size: 32.0,
-                    ..default()
-                },
-                TextColor(Color::srgb(1.0, 0.9, 0.3)),
+                theme.font(TextRole::Heading),
+                ThemedText(TextRole::Heading),
+                TextColor(theme.accent),
                 AmmoText,
             ));
 
+            // Ammo pips with the reserve beside them (hidden unless chosen in options)
+            parent
+                .spawn((
+                    Node {
+                        display: Display::None,
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::End,
+                        column_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    AmmoPipRow,
+                ))
+                .with_children(|row| {
+                    row.spawn((
+                        Node {
+                            width: Val::Px(PIPS_PER_ROW as f32 * (PIP_WIDTH + PIP_GAP)),
+                            flex_direction: FlexDirection::Row,
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::End,
+                            row_gap: Val::Px(PIP_GAP),
+                            column_gap: Val::Px(PIP_GAP),
+                            ..default()
+                        },
+                        AmmoPips::default(),
+                    ));
+                    row.spawn((
+                        Text::new("48"),
+                        theme.muted_text(TextRole::Caption),
+                        ReserveText,
+                    ));
+                });
+
+            // Ammo type in the magazine
+            parent.spawn((
+                Text::new("Standard"),
+                theme.font(TextRole::Caption),
+                ThemedText(TextRole::Caption),
+                TextColor(Color::srgb(0.9, 0.8, 0.5)),
+                AmmoTypeText,
+            ));
+
             // Reload indicator (hidden by default)
             parent.spawn((
                 Text::new("RELOADING..."),
-                TextFont {
-                    font_size: 20.0,
-                    ..default()
-                },
+                theme.font(TextRole::Body),
+                ThemedText(TextRole::Body),
                 TextColor(Color::srgb(1.0, 0.3, 0.3)),
                 Visibility::Hidden,
                 ReloadIndicator,
             ));
+
+            // Sprinting, firing, reloading or staggered
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(ACTION_ICON_SIZE),
+                        height: Val::Px(ACTION_ICON_SIZE),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderRadius::all(Val::Px(4.0)),
+                    BackgroundColor(Color::NONE),
+                    Visibility::Hidden,
+                    ActionStateIcon,
+                ))
+                .with_children(|icon| {
+                    icon.spawn((
+                        Text::new(""),
+                        theme.font(TextRole::Caption),
+                        ThemedText(TextRole::Caption),
+                        TextColor(Color::BLACK),
+                        ActionStateGlyph,
+                    ));
+                });
+
+            // Flares left (G to throw)
+            parent.spawn((
+                Text::new(format!("FLARES: {}", flares.remaining)),
+                theme.font(TextRole::Caption),
+                ThemedText(TextRole::Caption),
+                TextColor(Color::srgb(1.0, 0.5, 0.3)),
+                FlareText,
+            ));
+
+            // Grenades left (hold T to cook, release to throw)
+            parent.spawn((
+                Text::new("GRENADES: 2"),
+                theme.font(TextRole::Caption),
+                ThemedText(TextRole::Caption),
+                TextColor(Color::srgb(0.6, 0.8, 0.4)),
+                GrenadeText,
+            ));
+
+            // Weapon slots, equipped one bracketed and empty ones dotted
+            parent.spawn((
+                Text::new("[1] 2"),
+                theme.muted_text(TextRole::Caption),
+                SlotText,
+            ));
         });
 }
 
@@ -109,8 +300,31 @@ fn despawn_weapon_hud(mut commands: Commands, hud_query: Query<Entity, With<Weap
     }
 }
 
+/// Loaded pips are lit, spent ones dim, and the last few flash red
+fn pip_color(pip: u32, current_ammo: u32, elapsed: f32) -> Color {
+    if pip >= current_ammo {
+        Color::srgba(1.0, 0.9, 0.3, 0.15)
+    } else if current_ammo <= PIP_WARNING && (elapsed * 6.0).sin() > 0.0 {
+        Color::srgb(1.0, 0.2, 0.2)
+    } else {
+        Color::srgb(1.0, 0.9, 0.3)
+    }
+}
+
 fn update_weapon_hud(
-    player_query: Query<(&WeaponInventory, Option<&ReloadState>), With<Player>>,
+    mut commands: Commands,
+    time: Res<Time>,
+    perks: Res<PlayerPerks>,
+    hud_settings: Res<HudSettings>,
+    player_query: Query<
+        (
+            &WeaponInventory,
+            Option<&ReloadState>,
+            Option<&ChargingState>,
+            Option<&ClearingJam>,
+        ),
+        With<Player>,
+    >,
     mut weapon_name_query: Query<
         &mut Text,
         (
@@ -128,45 +342,139 @@ fn update_weapon_hud(
         ),
     >,
     mut ammo_query: Query<
-        &mut Text,
+        (&mut Text, &mut Node),
         (
             With<AmmoText>,
             Without<WeaponNameText>,
             Without<FireModeText>,
         ),
     >,
-    mut reload_query: Query<&mut Visibility, With<ReloadIndicator>>,
+    mut reload_query: Query<
+        (&mut Text, &mut Visibility),
+        (
+            With<ReloadIndicator>,
+            Without<WeaponNameText>,
+            Without<FireModeText>,
+            Without<AmmoText>,
+        ),
+    >,
+    mut pip_row_query: Query<&mut Node, (With<AmmoPipRow>, Without<AmmoText>)>,
+    mut pip_grid_query: Query<(Entity, &mut AmmoPips)>,
+    mut pip_query: Query<(&AmmoPip, &mut BackgroundColor)>,
+    mut reserve_query: Query<
+        &mut Text,
+        (
+            With<ReserveText>,
+            Without<WeaponNameText>,
+            Without<FireModeText>,
+            Without<AmmoText>,
+            Without<ReloadIndicator>,
+        ),
+    >,
 ) {
-    let Ok((inventory, reload_state)) = player_query.single() else {
+    let Ok((inventory, reload_state, charging, clearing)) = player_query.single() else {
         return;
     };
 
     let Some(weapon) = inventory.current_weapon() else {
         return;
     };
+    let magazine_size = compute_effective_stats(weapon, &perks).magazine_size;
+    let reserve = weapon.reserve(weapon.selected_ammo);
 
     // Update weapon name
     for mut text in weapon_name_query.iter_mut() {
         **text = weapon.weapon_type.name().to_string();
     }
 
-    // Update fire mode
+    // Update fire mode, with the charge percent while the railgun is charging
     for mut text in fire_mode_query.iter_mut() {
-        **text = weapon.fire_mode.name().to_string();
+        **text = match charging {
+            Some(charging) if weapon.fire_mode == FireMode::Charge => {
+                format!("Charge {:.0}%", charging.fraction() * 100.0)
+            }
+            _ => weapon.fire_mode.name().to_string(),
+        };
     }
 
     // Update ammo
-    for mut text in ammo_query.iter_mut() {
-        **text = format!("{} / {}", weapon.current_ammo, weapon.reserve_ammo);
+    let display = |shown: bool| if shown { Display::Flex } else { Display::None };
+    for (mut text, mut node) in ammo_query.iter_mut() {
+        **text = format!("{} / {}", weapon.current_ammo, reserve);
+        let shown = display(hud_settings.ammo_display.shows_numbers());
+        if node.display != shown {
+            node.display = shown;
+        }
     }
 
-    // Update reload indicator visibility
-    for mut visibility in reload_query.iter_mut() {
-        *visibility = if reload_state.is_some() {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        };
+    // Ammo pips, which only recolour unless the magazine size has changed
+    let pips_shown = hud_settings.ammo_display.shows_pips();
+    for mut node in pip_row_query.iter_mut() {
+        if node.display != display(pips_shown) {
+            node.display = display(pips_shown);
+        }
+    }
+    if pips_shown {
+        let elapsed = time.elapsed_secs();
+        for (grid, mut pips) in pip_grid_query.iter_mut() {
+            if pips.capacity == magazine_size {
+                continue;
+            }
+            pips.capacity = magazine_size;
+            commands
+                .entity(grid)
+                .despawn_related::<Children>()
+                .with_children(|grid| {
+                    for pip in 0..magazine_size {
+                        grid.spawn((
+                            Node {
+                                width: Val::Px(PIP_WIDTH),
+                                height: Val::Px(PIP_HEIGHT),
+                                ..default()
+                            },
+                            BackgroundColor(pip_color(pip, weapon.current_ammo, elapsed)),
+                            AmmoPip(pip),
+                        ));
+                    }
+                });
+        }
+        for (pip, mut color) in pip_query.iter_mut() {
+            color.set_if_neq(BackgroundColor(pip_color(
+                pip.0,
+                weapon.current_ammo,
+                elapsed,
+            )));
+        }
+        let label = reserve.to_string();
+        for mut text in reserve_query.iter_mut() {
+            if **text != label {
+                **text = label.clone();
+            }
+        }
+    }
+
+    // Reload indicator doubles as the ammo and jam prompt
+    let prompt = if clearing.is_some() {
+        Some("CLEARING JAM...")
+    } else if weapon.jammed {
+        Some("JAMMED - HOLD R")
+    } else if reload_state.is_some() {
+        Some("RELOADING...")
+    } else if weapon.is_empty() && !weapon.can_reload(&perks) {
+        Some("OUT OF AMMO")
+    } else if weapon.current_ammo * 4 <= magazine_size && weapon.can_reload(&perks) {
+        Some("LOW AMMO - PRESS R")
+    } else {
+        None
+    };
+    for (mut text, mut visibility) in reload_query.iter_mut() {
+        match prompt {
+            Some(prompt) => {
+                **text = prompt.to_string();
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
     }
 }
 
@@ -184,7 +492,14 @@ struct HealthBarContainer;
 #[derive(Component)]
 struct HealthBarFill;
 
-fn spawn_health_hud(mut commands: Commands) {
+#[derive(Component)]
+struct ArmorBarFill;
+
+fn spawn_health_hud(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    difficulty: Res<DifficultyModifiers>,
+) {
     commands
         .spawn((
             Node {
@@ -202,14 +517,29 @@ fn spawn_health_hud(mut commands: Commands) {
         ))
         .with_children(|parent| {
             // Health label
-            parent.spawn((
-                Text::new("HEALTH"),
-                TextFont {
-                    font_size: 18.0,
-                    ..default()
-                },
-                TextColor(Color::srgb(0.7, 0.7, 0.7)),
-            ));
+            parent.spawn((Text::new("HEALTH"), theme.muted_text(TextRole::Caption)));
+
+            // Armor bar, thin and blue above the health bar
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                ))
+                .with_children(|bar_parent| {
+                    bar_parent.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.4, 1.0)),
+                        ArmorBarFill,
+                    ));
+                });
 
             // Health bar container
             parent
@@ -238,13 +568,15 @@ fn spawn_health_hud(mut commands: Commands) {
             // Health text (number)
             parent.spawn((
                 Text::new("100 / 100"),
-                TextFont {
-                    font_size: 24.0,
-                    ..default()
-                },
-                TextColor(Color::WHITE),
+                theme.text(TextRole::Body),
                 HealthText,
             ));
+
+            // Difficulty is fixed for the whole run
+            parent.spawn((
+                Text::new(difficulty.label().to_uppercase()),
+                theme.muted_text(TextRole::Caption),
+            ));
         });
 }
 
@@ -255,14 +587,25 @@ fn despawn_health_hud(mut commands: Commands, hud_query: Query<Entity, With<Heal
 }
 
 fn update_health_hud(
-    player_query: Query<&PlayerHealth, With<Player>>,
+    player_query: Query<(&PlayerHealth, Option<&PlayerArmor>), With<Player>>,
     mut health_text_query: Query<&mut Text, With<HealthText>>,
-    mut health_bar_query: Query<(&mut Node, &mut BackgroundColor), With<HealthBarFill>>,
+    mut health_bar_query: Query<
+        (&mut Node, &mut BackgroundColor),
+        (With<HealthBarFill>, Without<ArmorBarFill>),
+    >,
+    mut armor_bar_query: Query<&mut Node, (With<ArmorBarFill>, Without<HealthBarFill>)>,
+    palette: Res<ColorPalette>,
 ) {
-    let Ok(health) = player_query.single() else {
+    let Ok((health, armor)) = player_query.single() else {
         return;
     };
 
+    // Update armor bar width
+    let armor_percent = armor.map_or(0.0, |armor| (armor.current / armor.max).clamp(0.0, 1.0));
+    for mut node in armor_bar_query.iter_mut() {
+        node.width = Val::Percent(armor_percent * 100.0);
+    }
+
     // Update health text
     for mut text in health_text_query.iter_mut() {
         **text = format!("{:.0} / {:.0}", health.current, health.max);
@@ -273,14 +616,331 @@ fn update_health_hud(
         let health_percent = (health.current / health.max).clamp(0.0, 1.0);
         node.width = Val::Percent(health_percent * 100.0);
 
-        // Change color based on health level
-        let color = if health_percent > 0.5 {
-            Color::srgb(0.2, 0.8, 0.2) // Green
-        } else if health_percent > 0.25 {
-            Color::srgb(0.8, 0.8, 0.2) // Yellow
+        // Change color based on health level, using the colorblind-aware palette
+        *bg_color = BackgroundColor(palette.health_color(health_percent));
+    }
+}
+
+// === AIM DOT ===
+
+/// Dot drawn where the muzzle ray actually lands, so what you see is what you hit
+#[derive(Component)]
+struct AimDot;
+
+const AIM_DOT_SIZE: f32 = 6.0;
+
+/// Charge meter hanging under the aim dot, shown only while the railgun charges
+#[derive(Component)]
+struct ChargeBar;
+
+#[derive(Component)]
+struct ChargeBarFill;
+
+const CHARGE_BAR_WIDTH: f32 = 40.0;
+
+/// Ring of segments round the aim dot that fill as a cooked grenade's fuse burns
+#[derive(Component)]
+struct CookRing;
+
+/// One segment of the CookRing, numbered clockwise from the top
+#[derive(Component)]
+struct CookSegment(usize);
+
+const COOK_SEGMENTS: usize = 24;
+const COOK_RING_RADIUS: f32 = 18.0;
+const COOK_SEGMENT_SIZE: f32 = 4.0;
+
+fn spawn_aim_dot(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(AIM_DOT_SIZE),
+                height: Val::Px(AIM_DOT_SIZE),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.9)),
+            Visibility::Hidden,
+            AimDot,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px((AIM_DOT_SIZE - CHARGE_BAR_WIDTH) / 2.0),
+                        top: Val::Px(AIM_DOT_SIZE + 8.0),
+                        width: Val::Px(CHARGE_BAR_WIDTH),
+                        height: Val::Px(4.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                    Visibility::Hidden,
+                    ChargeBar,
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.4, 0.9, 1.0)),
+                        ChargeBarFill,
+                    ));
+                });
+
+            // Centred on the dot, so the segments can be placed by angle
+            parent
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(AIM_DOT_SIZE / 2.0),
+                        top: Val::Px(AIM_DOT_SIZE / 2.0),
+                        ..default()
+                    },
+                    Visibility::Hidden,
+                    CookRing,
+                ))
+                .with_children(|ring| {
+                    for segment in 0..COOK_SEGMENTS {
+                        let angle = segment as f32 / COOK_SEGMENTS as f32 * TAU;
+                        let offset = Vec2::new(angle.sin(), -angle.cos()) * COOK_RING_RADIUS;
+                        ring.spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(offset.x - COOK_SEGMENT_SIZE / 2.0),
+                                top: Val::Px(offset.y - COOK_SEGMENT_SIZE / 2.0),
+                                width: Val::Px(COOK_SEGMENT_SIZE),
+                                height: Val::Px(COOK_SEGMENT_SIZE),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                            CookSegment(segment),
+                        ));
+                    }
+                });
+        });
+}
+
+fn despawn_aim_dot(mut commands: Commands, dot_query: Query<Entity, With<AimDot>>) {
+    for entity in dot_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Follows this frame's AimRay rather than casting its own
+fn update_aim_dot(
+    rapier_context: ReadRapierContext,
+    aim: Res<AimRay>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<ThirdPersonCamera>>,
+    shootables: Query<Entity, With<Shootable>>,
+    mut dot_query: Query<(&mut Node, &mut BackgroundColor, &mut Visibility), With<AimDot>>,
+) {
+    let Ok((mut node, mut color, mut visibility)) = dot_query.single_mut() else {
+        return;
+    };
+    let (Ok(context), Ok((player_entity, player_transform)), Ok((camera, global))) = (
+        rapier_context.single(),
+        player_query.single(),
+        camera_query.single(),
+    ) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    // Nothing has been aimed at yet
+    if aim.direction == Vec3::ZERO {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    // Red while the barrel is against a wall, since shots won't fire
+    let blocked = muzzle_blocked(
+        &context,
+        player_entity,
+        player_transform,
+        aim.direction,
+        &shootables,
+    );
+    *color = BackgroundColor(if blocked {
+        Color::srgba(1.0, 0.2, 0.2, 0.9)
+    } else {
+        Color::srgba(1.0, 1.0, 1.0, 0.9)
+    });
+
+    let point = converged_aim_point(&context, player_entity, &aim);
+    match camera.world_to_viewport(global, point) {
+        Ok(screen) => {
+            node.left = Val::Px(screen.x - AIM_DOT_SIZE / 2.0);
+            node.top = Val::Px(screen.y - AIM_DOT_SIZE / 2.0);
+            *visibility = Visibility::Inherited;
+        }
+        Err(_) => *visibility = Visibility::Hidden,
+    }
+}
+
+fn update_charge_bar(
+    player_query: Query<Option<&ChargingState>, With<Player>>,
+    mut bar_query: Query<&mut Visibility, With<ChargeBar>>,
+    mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<ChargeBarFill>>,
+) {
+    let charging = player_query.single().ok().flatten();
+
+    for mut visibility in bar_query.iter_mut() {
+        visibility.set_if_neq(if charging.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    let Some(charging) = charging else {
+        return;
+    };
+    let fraction = charging.fraction();
+    for (mut node, mut color) in fill_query.iter_mut() {
+        node.width = Val::Percent(fraction * 100.0);
+        // Full charge turns white so overcharge is easy to anticipate
+        color.0 = if fraction >= 1.0 {
+            Color::WHITE
         } else {
-            Color::srgb(0.8, 0.2, 0.2) // Red
+            Color::srgb(0.4, 0.9, 1.0)
         };
-        *bg_color = BackgroundColor(color);
+    }
+}
+
+/// Names the loaded type, and the one it's switching to until the reload lands
+fn update_ammo_type_text(
+    players: Query<&WeaponInventory, With<Player>>,
+    mut text_query: Query<&mut Text, With<AmmoTypeText>>,
+) {
+    let Some(weapon) = players
+        .single()
+        .ok()
+        .and_then(|inventory| inventory.current_weapon())
+    else {
+        return;
+    };
+    let label = if weapon.selected_ammo == weapon.loaded_ammo {
+        weapon.loaded_ammo.name().to_string()
+    } else {
+        format!(
+            "{} > {}",
+            weapon.loaded_ammo.name(),
+            weapon.selected_ammo.name()
+        )
+    };
+    for mut text in text_query.iter_mut() {
+        if **text != label {
+            **text = label.clone();
+        }
+    }
+}
+
+/// Segments light up clockwise over the fuse, going from white to red towards the end
+fn update_cook_ring(
+    player_query: Query<Option<&CookingGrenade>, With<Player>>,
+    mut ring_query: Query<&mut Visibility, With<CookRing>>,
+    mut segments: Query<(&CookSegment, &mut BackgroundColor)>,
+) {
+    let cooking = player_query.single().ok().flatten();
+    for mut visibility in ring_query.iter_mut() {
+        visibility.set_if_neq(if cooking.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+
+    let Some(cooking) = cooking else {
+        return;
+    };
+    let fraction = cooking.fraction();
+    let lit = Color::srgb(1.0, 1.0 - fraction, 1.0 - fraction);
+    let unlit = Color::srgba(0.0, 0.0, 0.0, 0.5);
+    for (segment, mut color) in segments.iter_mut() {
+        let filled = (segment.0 as f32 + 1.0) / COOK_SEGMENTS as f32 <= fraction;
+        color.set_if_neq(BackgroundColor(if filled { lit } else { unlit }));
+    }
+}
+
+fn update_flare_text(stock: Res<FlareStock>, mut text_query: Query<&mut Text, With<FlareText>>) {
+    for mut text in text_query.iter_mut() {
+        **text = format!("FLARES: {}", stock.remaining);
+    }
+}
+
+fn update_grenade_text(
+    stock: Res<GrenadeStock>,
+    mut text_query: Query<&mut Text, With<GrenadeText>>,
+) {
+    // Checked every frame rather than on change, so a HUD respawned on resume is right
+    let label = format!("GRENADES: {}", stock.remaining);
+    for mut text in text_query.iter_mut() {
+        if **text != label {
+            **text = label.clone();
+        }
+    }
+}
+
+/// "[1] 2 ·": one entry per slot, so a backpack shows up as an extra dot
+fn update_slot_text(
+    players: Query<&WeaponInventory, With<Player>>,
+    mut text_query: Query<&mut Text, With<SlotText>>,
+) {
+    let Ok(inventory) = players.single() else {
+        return;
+    };
+    let label = inventory
+        .weapons
+        .iter()
+        .enumerate()
+        .map(|(slot, weapon)| match weapon {
+            None => "·".to_string(),
+            Some(_) if slot == inventory.current_slot => format!("[{}]", slot + 1),
+            Some(_) => (slot + 1).to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    for mut text in text_query.iter_mut() {
+        if **text != label {
+            **text = label.clone();
+        }
+    }
+}
+
+fn update_action_state_icon(
+    players: Query<&PlayerActionState, With<Player>>,
+    mut icon_query: Query<(&mut Visibility, &mut BackgroundColor), With<ActionStateIcon>>,
+    mut glyph_query: Query<&mut Text, With<ActionStateGlyph>>,
+) {
+    let Ok(action_state) = players.single() else {
+        return;
+    };
+    // A plain reload already has the RELOADING indicator
+    let icon = match action_state.state {
+        ActionState::Idle | ActionState::Reloading { sprinting: false } => None,
+        ActionState::Reloading { sprinting: true } => Some(("R", Color::srgb(1.0, 0.6, 0.3))),
+        ActionState::Staggered => Some(("!", Color::srgb(1.0, 0.3, 0.3))),
+        ActionState::Sprinting => Some((">>", Color::srgb(0.7, 0.7, 0.7))),
+        ActionState::Firing => Some(("*", Color::srgb(0.7, 0.7, 0.7))),
+    };
+    for (mut visibility, mut background) in icon_query.iter_mut() {
+        visibility.set_if_neq(if icon.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if let Some((_, color)) = icon {
+            background.set_if_neq(BackgroundColor(color));
+        }
+    }
+    if let Some((glyph, _)) = icon {
+        for mut text in glyph_query.iter_mut() {
+            if **text != glyph {
+                **text = glyph.to_string();
+            }
+        }
     }
 }
@@ -0,0 +1,442 @@
+use super::{FlareStock, GrenadeStock, WeaponInventory};
+use crate::enemies::WaveState;
+use crate::player::{
+    BulletTime, DeathCamera, FireSuppressed, KillCam, Player, PlayerActions, PlayerActionsClaimSet,
+};
+use crate::ui::{shop_available, GameMode, GameState, Shop, TextRole, UiTheme};
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+/// Hold Tab for a ring of the weapon slots plus grenade and flare quick-use around the
+/// screen center, with the world slowed right down. The cursor stays grabbed: mouse
+/// motion (or the right stick) swings a pointer out from the center, and letting go of
+/// Tab takes whatever it points at. Escape or fire backs out without a switch.
+pub struct WeaponWheelPlugin;
+
+impl Plugin for WeaponWheelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeaponWheel>()
+            .add_systems(
+                PreUpdate,
+                (open_weapon_wheel, steer_weapon_wheel)
+                    .chain()
+                    .in_set(PlayerActionsClaimSet)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                update_wheel_sectors.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), close_weapon_wheel);
+    }
+}
+
+/// Game clock speed while the wheel is up
+const WHEEL_TIME_SCALE: f32 = 0.2;
+/// Mouse travel, in pixels, that swings the pointer all the way out
+const POINTER_RANGE: f32 = 120.0;
+/// Pointer travel under which nothing is picked, so letting go in place is a no-op
+const POINTER_DEAD_ZONE: f32 = 35.0;
+const STICK_DEAD_ZONE: f32 = 0.3;
+/// Distance from the wheel's center to the middle of each sector
+const WHEEL_RADIUS: f32 = 150.0;
+const SECTOR_WIDTH: f32 = 130.0;
+const SECTOR_HEIGHT: f32 = 64.0;
+const POINTER_SIZE: f32 = 12.0;
+
+/// What one sector of the wheel takes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum WheelItem {
+    Slot(usize),
+    Grenade,
+    Flare,
+}
+
+/// The wheel while it's up
+struct OpenWheel {
+    /// Clockwise from the top
+    items: Vec<WheelItem>,
+    pointer: Vec2,
+    highlighted: Option<usize>,
+    /// Clock speed to put back on close
+    time_speed: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct WeaponWheel {
+    open: Option<OpenWheel>,
+    /// Tab is still down from a wheel that was backed out of; it takes a fresh press
+    /// to open again
+    spent: bool,
+    /// Escape arrives in Update; the wheel closes on its next pass
+    cancel_requested: bool,
+}
+
+impl WeaponWheel {
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Close without taking anything
+    pub fn cancel(&mut self) {
+        if self.is_open() {
+            self.cancel_requested = true;
+        }
+    }
+}
+
+#[derive(Component)]
+struct WheelRoot;
+
+#[derive(Component)]
+struct WheelSector(usize);
+
+#[derive(Component)]
+struct WheelPointer;
+
+/// Sector under `pointer` on a wheel of `sectors`, the first centered straight up and
+/// the rest clockwise from it. y is up.
+fn sector_at(pointer: Vec2, sectors: usize) -> Option<usize> {
+    if sectors == 0 || pointer.length() < POINTER_DEAD_ZONE {
+        return None;
+    }
+    let width = TAU / sectors as f32;
+    let angle = pointer.x.atan2(pointer.y).rem_euclid(TAU);
+    Some(((angle + width / 2.0) / width) as usize % sectors)
+}
+
+/// Middle of sector `index` relative to the wheel's center, y down as in UI space
+fn sector_offset(index: usize, sectors: usize) -> Vec2 {
+    let angle = index as f32 / sectors as f32 * TAU;
+    Vec2::new(angle.sin(), -angle.cos()) * WHEEL_RADIUS
+}
+
+fn available(
+    item: WheelItem,
+    inventory: &WeaponInventory,
+    grenades: &GrenadeStock,
+    flares: &FlareStock,
+) -> bool {
+    match item {
+        WheelItem::Slot(slot) => inventory.weapons.get(slot).is_some_and(Option::is_some),
+        WheelItem::Grenade => grenades.remaining > 0,
+        WheelItem::Flare => flares.remaining > 0,
+    }
+}
+
+/// Name and the small print under it
+fn item_labels(
+    item: WheelItem,
+    inventory: &WeaponInventory,
+    grenades: &GrenadeStock,
+    flares: &FlareStock,
+) -> (String, String) {
+    match item {
+        WheelItem::Slot(slot) => match inventory.weapons.get(slot).and_then(Option::as_ref) {
+            Some(weapon) => (
+                format!("{} {}", slot + 1, weapon.weapon_type.name()),
+                format!(
+                    "{} / {}",
+                    weapon.current_ammo,
+                    weapon.reserve(weapon.selected_ammo)
+                ),
+            ),
+            None => (format!("{} EMPTY", slot + 1), String::new()),
+        },
+        WheelItem::Grenade => ("GRENADE".to_string(), format!("x{}", grenades.remaining)),
+        WheelItem::Flare => ("FLARE".to_string(), format!("x{}", flares.remaining)),
+    }
+}
+
+/// Tab opens the wheel unless Tab is busy with the shop, the player is down, a kill cam
+/// owns the clock, or the key is still held from a wheel that was backed out of
+fn open_weapon_wheel(
+    mut commands: Commands,
+    actions: Res<PlayerActions>,
+    mode: Res<GameMode>,
+    waves: Res<WaveState>,
+    shop: Res<Shop>,
+    theme: Res<UiTheme>,
+    grenades: Res<GrenadeStock>,
+    flares: Res<FlareStock>,
+    mut wheel: ResMut<WeaponWheel>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    player_q: Query<&WeaponInventory, (With<Player>, Without<DeathCamera>)>,
+    kill_cams: Query<(), With<KillCam>>,
+) {
+    if !actions.weapon_wheel {
+        wheel.spent = false;
+        return;
+    }
+    if wheel.is_open() || wheel.spent || shop.open || shop_available(&mode, &waves) {
+        return;
+    }
+    // The kill cam puts back the speed it found; opening over it would leave the wheel's
+    if !kill_cams.is_empty() {
+        return;
+    }
+    let Ok(inventory) = player_q.single() else {
+        return;
+    };
+
+    // Bullet time would put the clock back to normal over the wheel's
+    if bullet_time.active {
+        bullet_time.active = false;
+        virtual_time.set_relative_speed(1.0);
+    }
+    let time_speed = virtual_time.relative_speed();
+    virtual_time.set_relative_speed(WHEEL_TIME_SCALE);
+
+    let items: Vec<WheelItem> = (0..inventory.capacity())
+        .map(WheelItem::Slot)
+        .chain([WheelItem::Grenade, WheelItem::Flare])
+        .collect();
+    spawn_wheel(&mut commands, &theme, &items, inventory, &grenades, &flares);
+    wheel.open = Some(OpenWheel {
+        items,
+        pointer: Vec2::ZERO,
+        highlighted: None,
+        time_speed,
+    });
+}
+
+/// Swing the pointer, then take the highlighted item on release. Everything the wheel
+/// is steered with is cleared from PlayerActions so the camera and gun stay put.
+fn steer_weapon_wheel(
+    mut commands: Commands,
+    mut actions: ResMut<PlayerActions>,
+    mut wheel: ResMut<WeaponWheel>,
+    mut suppressed: ResMut<FireSuppressed>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    grenades: Res<GrenadeStock>,
+    flares: Res<FlareStock>,
+    gamepads: Query<&Gamepad>,
+    player_q: Query<&WeaponInventory, With<Player>>,
+    roots: Query<Entity, With<WheelRoot>>,
+) {
+    let cancel_requested = std::mem::take(&mut wheel.cancel_requested);
+    let Some(open) = wheel.open.as_mut() else {
+        return;
+    };
+
+    // Mouse y runs down the screen; the wheel's runs up
+    let motion = Vec2::new(actions.look.x, -actions.look.y);
+    open.pointer = (open.pointer + motion).clamp_length_max(POINTER_RANGE);
+    if let Some(stick) = gamepads
+        .iter()
+        .map(Gamepad::right_stick)
+        .find(|stick| stick.length() > STICK_DEAD_ZONE)
+    {
+        open.pointer = stick.clamp_length_max(1.0) * POINTER_RANGE;
+    }
+    open.highlighted = player_q.single().ok().and_then(|inventory| {
+        sector_at(open.pointer, open.items.len())
+            .filter(|&index| available(open.items[index], inventory, &grenades, &flares))
+    });
+    let picked = open.highlighted.map(|index| open.items[index]);
+
+    let fired = actions.fire_pressed;
+    actions.look = Vec2::ZERO;
+    actions.scroll = 0.0;
+    actions.fire_held = false;
+    actions.fire_pressed = false;
+    actions.select_slot = None;
+    actions.bullet_time = false;
+
+    if cancel_requested || fired {
+        // Tab is still down, and so may the fire button be
+        wheel.spent = true;
+        suppressed.0 |= fired;
+    } else if !actions.weapon_wheel {
+        match picked {
+            Some(WheelItem::Slot(slot)) => actions.select_slot = Some(slot),
+            // Pulled and let go in one frame: thrown on the next with a full fuse
+            Some(WheelItem::Grenade) => {
+                actions.grenade = true;
+                actions.grenade_held = false;
+            }
+            Some(WheelItem::Flare) => actions.throw_flare = true,
+            None => {}
+        }
+    } else {
+        return;
+    }
+    shut_wheel(&mut commands, &mut wheel, &mut virtual_time, &roots);
+}
+
+fn shut_wheel(
+    commands: &mut Commands,
+    wheel: &mut WeaponWheel,
+    virtual_time: &mut Time<Virtual>,
+    roots: &Query<Entity, With<WheelRoot>>,
+) {
+    if let Some(open) = wheel.open.take() {
+        virtual_time.set_relative_speed(open.time_speed);
+    }
+    wheel.cancel_requested = false;
+    for root in roots.iter() {
+        commands.entity(root).despawn();
+    }
+}
+
+/// Leaving Playing backs out of the wheel, so pausing can't strand the clock slowed.
+/// Tab may still be down on return, so it takes a fresh press to reopen.
+fn close_weapon_wheel(
+    mut commands: Commands,
+    mut wheel: ResMut<WeaponWheel>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    roots: Query<Entity, With<WheelRoot>>,
+) {
+    wheel.spent = wheel.is_open();
+    shut_wheel(&mut commands, &mut wheel, &mut virtual_time, &roots);
+}
+
+fn spawn_wheel(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    items: &[WheelItem],
+    inventory: &WeaponInventory,
+    grenades: &GrenadeStock,
+    flares: &FlareStock,
+) {
+    let size = (WHEEL_RADIUS + SECTOR_WIDTH) * 2.0;
+    let center = Vec2::splat(size / 2.0);
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+            GlobalZIndex(5),
+            WheelRoot,
+        ))
+        .with_children(|root| {
+            root.spawn(Node {
+                width: Val::Px(size),
+                height: Val::Px(size),
+                ..default()
+            })
+            .with_children(|ring| {
+                for (index, item) in items.iter().enumerate() {
+                    let middle = center + sector_offset(index, items.len());
+                    let (name, detail) = item_labels(*item, inventory, grenades, flares);
+                    let (name_style, detail_style) =
+                        if available(*item, inventory, grenades, flares) {
+                            (
+                                theme.text(TextRole::Body),
+                                theme.muted_text(TextRole::Caption),
+                            )
+                        } else {
+                            (
+                                theme.muted_text(TextRole::Body),
+                                theme.muted_text(TextRole::Caption),
+                            )
+                        };
+                    ring.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(middle.x - SECTOR_WIDTH / 2.0),
+                            top: Val::Px(middle.y - SECTOR_HEIGHT / 2.0),
+                            width: Val::Px(SECTOR_WIDTH),
+                            height: Val::Px(SECTOR_HEIGHT),
+                            flex_direction: FlexDirection::Column,
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(theme.buttons.normal.with_alpha(0.85)),
+                        WheelSector(index),
+                    ))
+                    .with_children(|sector| {
+                        sector.spawn((Text::new(name), name_style));
+                        sector.spawn((Text::new(detail), detail_style));
+                    });
+                }
+
+                ring.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(center.x - POINTER_SIZE / 2.0),
+                        top: Val::Px(center.y - POINTER_SIZE / 2.0),
+                        width: Val::Px(POINTER_SIZE),
+                        height: Val::Px(POINTER_SIZE),
+                        ..default()
+                    },
+                    BackgroundColor(theme.accent),
+                    WheelPointer,
+                ));
+            });
+        });
+}
+
+/// Light the highlighted sector and move the pointer dot out toward it
+fn update_wheel_sectors(
+    wheel: Res<WeaponWheel>,
+    theme: Res<UiTheme>,
+    mut sectors: Query<(&WheelSector, &mut BackgroundColor)>,
+    mut pointers: Query<&mut UiTransform, With<WheelPointer>>,
+) {
+    let Some(open) = wheel.open.as_ref() else {
+        return;
+    };
+    for (sector, mut color) in sectors.iter_mut() {
+        let fill = if open.highlighted == Some(sector.0) {
+            theme.buttons.pressed
+        } else {
+            theme.buttons.normal
+        };
+        *color = BackgroundColor(fill.with_alpha(0.85));
+    }
+    // Out to the inner edge of the sectors at full swing
+    let reach = (WHEEL_RADIUS - SECTOR_HEIGHT) / POINTER_RANGE;
+    for mut transform in pointers.iter_mut() {
+        transform.translation = Val2::px(open.pointer.x * reach, -open.pointer.y * reach);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pointer well outside the dead zone at `degrees` clockwise from straight up
+    fn at(degrees: f32) -> Vec2 {
+        let angle = degrees.to_radians();
+        Vec2::new(angle.sin(), angle.cos()) * POINTER_RANGE
+    }
+
+    #[test]
+    fn the_dead_zone_picks_nothing() {
+        assert_eq!(sector_at(Vec2::ZERO, 8), None);
+        assert_eq!(sector_at(Vec2::new(0.0, POINTER_DEAD_ZONE - 1.0), 8), None);
+        assert_eq!(sector_at(Vec2::new(0.0, POINTER_DEAD_ZONE), 8), Some(0));
+        assert_eq!(sector_at(at(90.0), 0), None);
+    }
+
+    #[test]
+    fn the_top_sector_wraps_round_through_straight_up() {
+        // Eight sectors 45 degrees wide; the first spans -22.5 to 22.5
+        assert_eq!(sector_at(at(0.0), 8), Some(0));
+        assert_eq!(sector_at(at(20.0), 8), Some(0));
+        assert_eq!(sector_at(at(-20.0), 8), Some(0));
+        assert_eq!(sector_at(at(340.0), 8), Some(0));
+    }
+
+    #[test]
+    fn sectors_change_at_their_edges() {
+        for index in 0..8 {
+            let edge = index as f32 * 45.0 + 22.5;
+            assert_eq!(sector_at(at(edge - 1.0), 8), Some(index));
+            assert_eq!(sector_at(at(edge + 1.0), 8), Some((index + 1) % 8));
+        }
+        // Clockwise: right is a quarter of the way round, left three quarters
+        assert_eq!(sector_at(at(90.0), 4), Some(1));
+        assert_eq!(sector_at(at(270.0), 4), Some(3));
+        assert_eq!(sector_at(Vec2::NEG_Y * POINTER_RANGE, 4), Some(2));
+    }
+}
@@ -0,0 +1,256 @@
+use super::{ConsoleAppExt, ConsoleCommands};
+use crate::combat::{FlareStock, InputBufferSettings, WeaponInventory};
+use crate::enemies::{
+    spawn_zombie, Mover, MoverPath, RangeSettings, WaveState, Zombie, ZombieAssets, ZombieKind,
+};
+use crate::player::{ally_spawn_point, spawn_ally, AllyAssets, Player, PlayerArmor, PlayerHealth};
+use crate::world::{BalanceData, GameRng};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+pub(super) fn register(app: &mut App) {
+    app.register_console_command("help", "", help)
+        .register_console_command("give", "<ammo|health|armor|flares> <amount>", give)
+        .register_console_command("spawn", "zombie [walker|runner] [count]", spawn)
+        .register_console_command("ally", "", ally)
+        .register_console_command("god", "", god)
+        .register_console_command("noclip", "", noclip)
+        .register_console_command("setwave", "<wave>", setwave)
+        .register_console_command("killall", "", killall)
+        .register_console_command("timescale", "<speed>", timescale)
+        .register_console_command("conveyor", "<speed>", conveyor)
+        .register_console_command("inputbuffer", "<ms>", inputbuffer)
+        .add_systems(
+            FixedPostUpdate,
+            apply_noclip.before(PhysicsSet::SyncBackend),
+        );
+}
+
+/// Walk through everything and ignore gravity
+#[derive(Component)]
+struct Noclip;
+
+fn parse<T: std::str::FromStr>(arg: Option<&&str>, what: &str) -> Result<T, String> {
+    let arg = arg.ok_or_else(|| format!("missing {}", what))?;
+    arg.parse()
+        .map_err(|_| format!("'{}' is not a valid {}", arg, what))
+}
+
+fn player_entity(world: &mut World) -> Result<Entity, String> {
+    world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .map_err(|_| "no player".to_string())
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    Ok(world.resource::<ConsoleCommands>().usage_lines().join("\n"))
+}
+
+fn give(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let what = *args.first().ok_or("give what?")?;
+    let amount: u32 = parse(args.get(1), "amount")?;
+    let player = player_entity(world)?;
+    let mut entity = world.entity_mut(player);
+
+    match what {
+        "ammo" => {
+            let mut inventory = entity.get_mut::<WeaponInventory>().ok_or("no inventory")?;
+            let weapon = inventory.current_weapon_mut().ok_or("no weapon equipped")?;
+            *weapon.reserve_mut(weapon.selected_ammo) += amount;
+            Ok(format!(
+                "+{} {} reserve ammo",
+                amount,
+                weapon.selected_ammo.name()
+            ))
+        }
+        "health" => {
+            let mut health = entity.get_mut::<PlayerHealth>().ok_or("no health")?;
+            health.current = (health.current + amount as f32).min(health.max);
+            Ok(format!("health {:.0}", health.current))
+        }
+        "armor" => {
+            let mut armor = entity.get_mut::<PlayerArmor>().ok_or("no armor")?;
+            armor.current = (armor.current + amount as f32).min(armor.max);
+            Ok(format!("armor {:.0}", armor.current))
+        }
+        "flares" => {
+            world.resource_mut::<FlareStock>().remaining += amount;
+            Ok(format!("+{} flares", amount))
+        }
+        _ => Err(format!("can't give '{}'", what)),
+    }
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if args.first() != Some(&"zombie") {
+        return Err("only 'spawn zombie' is supported".to_string());
+    }
+    let (kind, count_arg) = match args.get(1) {
+        Some(&"runner") => (ZombieKind::Runner, args.get(2)),
+        Some(&"walker") => (ZombieKind::Walker, args.get(2)),
+        Some(_) => (ZombieKind::Walker, args.get(1)),
+        None => (ZombieKind::Walker, None),
+    };
+    let count: u32 = match count_arg {
+        Some(_) => parse(count_arg, "count")?,
+        None => 1,
+    };
+
+    let player = player_entity(world)?;
+    let center = world
+        .get::<Transform>(player)
+        .ok_or("no player transform")?
+        .translation;
+
+    let angles: Vec<f32> = {
+        let mut rng = world.resource_mut::<GameRng>();
+        (0..count)
+            .map(|_| rng.random::<f32>() * std::f32::consts::TAU)
+            .collect()
+    };
+    let balance = world.resource::<BalanceData>().clone();
+    world.resource_scope(|world, assets: Mut<ZombieAssets>| {
+        let mut commands = world.commands();
+        for (i, angle) in angles.into_iter().enumerate() {
+            let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * 8.0;
+            let zombie = Zombie::new(kind, i as u32, &balance);
+            spawn_zombie(&mut commands, &assets, center.with_y(1.0) + offset, zombie);
+        }
+    });
+    world.flush();
+    Ok(format!("spawned {} zombie(s)", count))
+}
+
+/// Practice bot a couple of metres in front of the player
+fn ally(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let player = player_entity(world)?;
+    let transform = *world
+        .get::<Transform>(player)
+        .ok_or("no player transform")?;
+    let position = ally_spawn_point(&transform);
+    let balance = world.resource::<BalanceData>().clone();
+    world.resource_scope(|world, assets: Mut<AllyAssets>| {
+        let mut commands = world.commands();
+        spawn_ally(
+            &mut commands,
+            &assets,
+            &balance,
+            position,
+            *transform.forward(),
+        );
+    });
+    world.flush();
+    Ok("practice bot deployed for 60s".to_string())
+}
+
+fn god(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let player = player_entity(world)?;
+    let mut health = world.get_mut::<PlayerHealth>(player).ok_or("no health")?;
+    health.invulnerable = !health.invulnerable;
+    Ok(format!("god mode {}", on_off(health.invulnerable)))
+}
+
+fn noclip(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let player = player_entity(world)?;
+    let mut entity = world.entity_mut(player);
+    let enabled = !entity.contains::<Noclip>();
+
+    let mut controller = entity
+        .get_mut::<KinematicCharacterController>()
+        .ok_or("no character controller")?;
+    controller.filter_flags = if enabled {
+        QueryFilterFlags::EXCLUDE_FIXED
+            | QueryFilterFlags::EXCLUDE_KINEMATIC
+            | QueryFilterFlags::EXCLUDE_DYNAMIC
+    } else {
+        QueryFilterFlags::default()
+    };
+    if enabled {
+        entity.insert(Noclip);
+    } else {
+        entity.remove::<Noclip>();
+    }
+    Ok(format!("noclip {}", on_off(enabled)))
+}
+
+/// Strip the gravity pull from player_movement so noclip doesn't sink through the floor
+fn apply_noclip(mut players: Query<&mut KinematicCharacterController, With<Noclip>>) {
+    for mut controller in players.iter_mut() {
+        if let Some(translation) = controller.translation {
+            controller.translation = Some(translation.with_y(0.0));
+        }
+    }
+}
+
+fn setwave(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let wave: u32 = parse(args.first(), "wave")?;
+    if wave == 0 {
+        return Err("waves start at 1".to_string());
+    }
+    killall(world, &[])?;
+
+    // Skip the intermission so the requested wave starts as soon as the console closes
+    let mut waves = world.resource_mut::<WaveState>();
+    *waves = WaveState::resume(wave - 1, false);
+    let duration = waves.intermission.duration();
+    waves.intermission.set_elapsed(duration);
+    Ok(format!("starting wave {}", wave))
+}
+
+fn killall(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let mut count = 0;
+    let mut zombies = world.query::<&mut Zombie>();
+    for mut zombie in zombies.iter_mut(world) {
+        zombie.health = 0.0;
+        count += 1;
+    }
+    Ok(format!("killed {} zombie(s)", count))
+}
+
+fn timescale(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let speed: f32 = parse(args.first(), "speed")?;
+    if !(0.05..=10.0).contains(&speed) {
+        return Err("speed must be between 0.05 and 10".to_string());
+    }
+    world
+        .resource_mut::<Time<Virtual>>()
+        .set_relative_speed(speed);
+    Ok(format!("timescale {}", speed))
+}
+
+/// Retune the range conveyor; the targets on it speed up or slow down in step
+fn conveyor(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let speed: f32 = parse(args.first(), "speed")?;
+    if !(0.0..=20.0).contains(&speed) {
+        return Err("speed must be between 0 and 20".to_string());
+    }
+    let previous = world.resource::<RangeSettings>().conveyor_speed;
+    world.resource_mut::<RangeSettings>().conveyor_speed = speed;
+    let mut movers = world.query::<&mut Mover>();
+    for mut mover in movers.iter_mut(world) {
+        if matches!(mover.path, MoverPath::Linear { .. }) && mover.speed == previous {
+            mover.speed = speed;
+        }
+    }
+    Ok(format!("conveyor speed {}", speed))
+}
+
+/// How long blocked fire, reload and switch presses wait to go through; 0 turns it off
+fn inputbuffer(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let ms: f32 = parse(args.first(), "ms")?;
+    if !(0.0..=1000.0).contains(&ms) {
+        return Err("ms must be between 0 and 1000".to_string());
+    }
+    world.resource_mut::<InputBufferSettings>().window = ms / 1000.0;
+    Ok(format!("input buffer {}ms", ms))
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "on"
+    } else {
+        "off"
+    }
+}
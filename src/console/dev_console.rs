@@ -0,0 +1,310 @@
+use super::{builtins, stress};
+use crate::ui::GameState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+/// Backtick-toggled developer console. Only compiled with the `dev_console` feature.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleState>()
+            .init_resource::<ConsoleCommands>()
+            .add_systems(OnEnter(GameState::Console), (spawn_console, pause_time))
+            .add_systems(OnExit(GameState::Console), despawn_console)
+            .add_systems(
+                Update,
+                toggle_console
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::Console))),
+            )
+            .add_systems(
+                Update,
+                (console_input, execute_console_command, update_console_text)
+                    .chain()
+                    .run_if(in_state(GameState::Console)),
+            );
+
+        builtins::register(app);
+        stress::register(app);
+    }
+}
+
+/// A console command: receives the world and the words after the command name,
+/// and returns the line to print
+pub type ConsoleHandler = fn(&mut World, &[&str]) -> Result<String, String>;
+
+struct ConsoleCommand {
+    name: &'static str,
+    usage: &'static str,
+    handler: ConsoleHandler,
+}
+
+/// Every command the console knows, in registration order
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(Vec<ConsoleCommand>);
+
+impl ConsoleCommands {
+    fn find(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.0.iter().find(|command| command.name == name)
+    }
+
+    /// `name  usage` lines for `help`
+    pub fn usage_lines(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|command| format!("{}  {}", command.name, command.usage))
+            .collect()
+    }
+}
+
+/// Lets any plugin add console commands, e.g. `app.register_console_command("heal", "", heal)`
+pub trait ConsoleAppExt {
+    fn register_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: ConsoleHandler,
+    ) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn register_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        handler: ConsoleHandler,
+    ) -> &mut Self {
+        let mut commands = self.world_mut().get_resource_or_init::<ConsoleCommands>();
+        commands.0.retain(|command| command.name != name);
+        commands.0.push(ConsoleCommand {
+            name,
+            usage,
+            handler,
+        });
+        self
+    }
+}
+
+/// Input line, scrollback and history; survives closing the console
+#[derive(Resource, Default)]
+pub struct ConsoleState {
+    input: String,
+    log: Vec<String>,
+    history: Vec<String>,
+    /// Position while browsing history with the arrow keys
+    history_index: Option<usize>,
+    /// Line submitted this frame, run by the exclusive executor
+    pending: Option<String>,
+}
+
+impl ConsoleState {
+    fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        let overflow = self.log.len().saturating_sub(MAX_LOG_LINES);
+        self.log.drain(..overflow);
+    }
+}
+
+const MAX_LOG_LINES: usize = 14;
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleLogText;
+
+#[derive(Component)]
+struct ConsoleInputText;
+
+fn toggle_console(
+    keys: Res<ButtonInput<KeyCode>>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let close = *current_state.get() == GameState::Console && keys.just_pressed(KeyCode::Escape);
+    if !keys.just_pressed(KeyCode::Backquote) && !close {
+        return;
+    }
+    match current_state.get() {
+        GameState::Playing => next_state.set(GameState::Console),
+        GameState::Console => next_state.set(GameState::Playing),
+        _ => {}
+    }
+}
+
+/// Gameplay freezes underneath; entering Playing again resumes virtual time
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn console_input(
+    mut keyboard: MessageReader<KeyboardInput>,
+    mut console: ResMut<ConsoleState>,
+    commands: Res<ConsoleCommands>,
+) {
+    for event in keyboard.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input).trim().to_string();
+                console.history_index = None;
+                if line.is_empty() {
+                    continue;
+                }
+                console.print(format!("> {}", line));
+                if console.history.last() != Some(&line) {
+                    console.history.push(line.clone());
+                }
+                console.pending = Some(line);
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Space => console.input.push(' '),
+            Key::ArrowUp => {
+                if console.history.is_empty() {
+                    continue;
+                }
+                let index = console
+                    .history_index
+                    .map_or(console.history.len() - 1, |i| i.saturating_sub(1));
+                console.history_index = Some(index);
+                console.input = console.history[index].clone();
+            }
+            Key::ArrowDown => {
+                let Some(index) = console.history_index else {
+                    continue;
+                };
+                if index + 1 < console.history.len() {
+                    console.history_index = Some(index + 1);
+                    console.input = console.history[index + 1].clone();
+                } else {
+                    console.history_index = None;
+                    console.input.clear();
+                }
+            }
+            Key::Tab => complete_command(&mut console, &commands),
+            // The toggle key shouldn't end up in the input line
+            Key::Character(text) if text.as_str() != "`" => console.input.push_str(text),
+            _ => {}
+        }
+    }
+}
+
+/// Complete the command name; with several matches, list them and fill the shared prefix
+fn complete_command(console: &mut ConsoleState, commands: &ConsoleCommands) {
+    if console.input.contains(' ') {
+        return;
+    }
+    let matches: Vec<&str> = commands
+        .0
+        .iter()
+        .map(|command| command.name)
+        .filter(|name| name.starts_with(console.input.as_str()))
+        .collect();
+
+    match matches.as_slice() {
+        [] => {}
+        [only] => console.input = format!("{} ", only),
+        [first, rest @ ..] => {
+            let mut prefix = first.to_string();
+            for name in rest {
+                while !name.starts_with(prefix.as_str()) {
+                    prefix.pop();
+                }
+            }
+            console.input = prefix;
+            console.print(matches.join("  "));
+        }
+    }
+}
+
+/// Runs with full world access so handlers can touch any resource or component
+fn execute_console_command(world: &mut World) {
+    let Some(line) = world.resource_mut::<ConsoleState>().pending.take() else {
+        return;
+    };
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return;
+    };
+    let args: Vec<&str> = words.collect();
+
+    let handler = world
+        .resource::<ConsoleCommands>()
+        .find(name)
+        .map(|command| command.handler);
+    let output = match handler {
+        Some(handler) => handler(world, &args).unwrap_or_else(|e| format!("error: {}", e)),
+        None => format!("Unknown command '{}' (try help)", name),
+    };
+
+    let mut console = world.resource_mut::<ConsoleState>();
+    for line in output.lines() {
+        console.print(line);
+    }
+}
+
+fn spawn_console(mut commands: Commands, console: Res<ConsoleState>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::End,
+                padding: UiRect::all(Val::Px(12.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            ConsoleRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(console.log.join("\n")),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ConsoleLogText,
+            ));
+            parent.spawn((
+                Text::new(format!("> {}_", console.input)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.4, 1.0, 0.5)),
+                ConsoleInputText,
+            ));
+        });
+}
+
+fn update_console_text(
+    console: Res<ConsoleState>,
+    mut log_text: Query<&mut Text, (With<ConsoleLogText>, Without<ConsoleInputText>)>,
+    mut input_text: Query<&mut Text, (With<ConsoleInputText>, Without<ConsoleLogText>)>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut text in log_text.iter_mut() {
+        **text = console.log.join("\n");
+    }
+    for mut text in input_text.iter_mut() {
+        **text = format!("> {}_", console.input);
+    }
+}
+
+fn despawn_console(mut commands: Commands, roots: Query<Entity, With<ConsoleRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
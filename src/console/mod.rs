@@ -0,0 +1,6 @@
+mod builtins;
+mod dev_console;
+mod stress;
+
+pub use dev_console::*;
+pub use stress::headless_for_stress_bench;
@@ -0,0 +1,464 @@
+//! Zombie horde stress scene, for measuring the enemy systems under load.
+//!
+//! `stress [count]` (or F8) rings the player with `HORDE_SIZE` zombies, or `count` of
+//! them, makes the player invulnerable and shows the profiling overlay; `stress stop`
+//! (or F8 again) removes them. Starting the game with `--stress-bench` runs the same
+//! scene headless in the shooting range (no waves) for `BENCH_FRAMES` fixed-length
+//! frames, prints percentiles for each stage of the zombie update and exits, so two
+//! builds can be compared on the same machine: `make stress-bench`, or
+//! `make stress-bench HORDE=300` for a smaller horde (`--horde 300`).
+//!
+//! Stage times are wall-clock between markers ordered around each `ZombieSystems` set,
+//! so they include anything the executor happens to run alongside that stage. They're
+//! per frame: the fixed-tick stages add up however many ticks the frame ran, which at
+//! the bench's frame length is one or two.
+
+use super::ConsoleAppExt;
+use crate::enemies::{
+    despawn_zombies, spawn_standing_zombie, Zombie, ZombieAssets, ZombieKind, ZombieSystems,
+};
+use crate::player::{Player, PlayerHealth};
+use crate::ui::{GameMode, GameState};
+use crate::world::{BalanceData, GameRng};
+use bevy::app::{PluginGroupBuilder, ScheduleRunnerPlugin};
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+pub(super) fn register(app: &mut App) {
+    if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+        app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+    }
+
+    app.init_resource::<StressHorde>()
+        .init_resource::<ZombieTimings>()
+        .register_console_command("stress", "[stop|count]", stress)
+        .add_systems(
+            Last,
+            record_timings.run_if(in_state(GameState::Playing).and(horde_active)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_horde_key.run_if(in_state(GameState::Playing)),
+                update_stress_overlay.run_if(horde_active),
+            ),
+        )
+        .add_systems(OnEnter(GameState::MainMenu), end_horde_on_menu);
+
+    time_stage::<0>(app);
+    time_stage::<1>(app);
+    time_stage::<2>(app);
+    time_stage::<3>(app);
+    time_stage::<4>(app);
+    time_stage::<5>(app);
+    time_stage::<6>(app);
+    time_stage::<7>(app);
+
+    if stress_bench_requested() {
+        app.init_resource::<StressBench>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(BENCH_FRAME))
+            .add_systems(OnEnter(GameState::MainMenu), start_bench_run)
+            .add_systems(
+                Last,
+                run_bench
+                    .after(record_timings)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Horde size unless the command or `--horde` asks for another
+const HORDE_SIZE: usize = 500;
+/// Rings the horde is spread over, closest first
+const RING_RADII: [f32; 3] = [20.0, 23.0, 26.0];
+/// Keeps ring points that fall past the arena walls inside them
+const ARENA_LIMIT: f32 = 47.0;
+const STAGES: usize = ZombieSystems::ALL.len();
+
+const STRESS_BENCH_ARG: &str = "--stress-bench";
+const HORDE_ARG: &str = "--horde";
+const BENCH_FRAMES: usize = 1000;
+/// Frame length the bench's clock advances by, so every run simulates the same thing
+const BENCH_FRAME: Duration = Duration::from_nanos(16_666_667);
+const BENCH_SEED: u64 = 666;
+
+/// The horde while it's up
+#[derive(Resource, Default)]
+struct StressHorde {
+    zombies: Vec<Entity>,
+    /// God mode as it was before the horde, put back afterwards
+    was_invulnerable: bool,
+    /// Live entities just before the horde arrived
+    entities_before: u32,
+}
+
+impl StressHorde {
+    fn active(&self) -> bool {
+        !self.zombies.is_empty()
+    }
+}
+
+fn horde_active(horde: Res<StressHorde>) -> bool {
+    horde.active()
+}
+
+/// Per-stage durations of the zombie update while the horde is up
+#[derive(Resource, Default)]
+struct ZombieTimings {
+    /// When each stage last started
+    started: [Option<Instant>; STAGES],
+    /// Time spent in each stage so far this frame, over however many ticks it ran
+    elapsed: [Duration; STAGES],
+    /// Microseconds each stage took, most recent frame last
+    samples: [VecDeque<f32>; STAGES],
+}
+
+impl ZombieTimings {
+    fn frames(&self) -> usize {
+        self.samples[0].len()
+    }
+
+    /// One line per stage: p50, p95, p99 and max in microseconds
+    fn report(&self) -> String {
+        let mut lines = vec![format!(
+            "{:<12}{:>9}{:>9}{:>9}{:>9}",
+            "stage (us)", "p50", "p95", "p99", "max"
+        )];
+        for (stage, samples) in ZombieSystems::ALL.iter().zip(&self.samples) {
+            let mut sorted: Vec<f32> = samples.iter().copied().collect();
+            sorted.sort_by(f32::total_cmp);
+            lines.push(format!(
+                "{:<12}{:>9.0}{:>9.0}{:>9.0}{:>9.0}",
+                format!("{:?}", stage),
+                percentile(&sorted, 0.50),
+                percentile(&sorted, 0.95),
+                percentile(&sorted, 0.99),
+                sorted.last().copied().unwrap_or(0.0),
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    sorted[((sorted.len() - 1) as f32 * p).round() as usize]
+}
+
+/// Root of the profiling overlay
+#[derive(Component)]
+struct StressOverlay;
+
+/// Set when the app was started with `--stress-bench`
+#[derive(Resource, Default)]
+struct StressBench {
+    started: bool,
+}
+
+fn stress_bench_requested() -> bool {
+    std::env::args().any(|arg| arg == STRESS_BENCH_ARG)
+}
+
+/// Horde size given after `--horde`, if any
+fn bench_horde_size() -> Option<usize> {
+    let args: Vec<String> = std::env::args().collect();
+    args.windows(2)
+        .find(|pair| pair[0] == HORDE_ARG)
+        .and_then(|pair| pair[1].parse().ok())
+}
+
+/// Strip the window and GPU from the default plugins when running the stress bench;
+/// otherwise return them untouched
+pub fn headless_for_stress_bench(plugins: PluginGroupBuilder) -> PluginGroupBuilder {
+    if !stress_bench_requested() {
+        return plugins;
+    }
+    plugins
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            ..default()
+        })
+        .set(RenderPlugin {
+            render_creation: RenderCreation::Automatic(WgpuSettings {
+                backends: None,
+                ..default()
+            }),
+            ..default()
+        })
+        .disable::<WinitPlugin>()
+        .add(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+}
+
+fn stress(world: &mut World, args: &[&str]) -> Result<String, String> {
+    match args.first() {
+        None => start_horde(world, HORDE_SIZE),
+        Some(&"stop") => stop_horde(world),
+        Some(other) => match other.parse() {
+            Ok(count) if count > 0 => start_horde(world, count),
+            _ => Err(format!("unknown option '{}'", other)),
+        },
+    }
+}
+
+fn toggle_horde_key(keys: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if !keys.just_pressed(KeyCode::F8) {
+        return;
+    }
+    commands.queue(|world: &mut World| {
+        let result = if world.resource::<StressHorde>().active() {
+            stop_horde(world)
+        } else {
+            start_horde(world, HORDE_SIZE)
+        };
+        match result {
+            Ok(message) => info!("{}", message),
+            Err(message) => warn!("{}", message),
+        }
+    });
+}
+
+fn start_horde(world: &mut World, size: usize) -> Result<String, String> {
+    if world.resource::<StressHorde>().active() {
+        return Err("the horde is already up; 'stress stop' removes it".to_string());
+    }
+    let (player, center) = world
+        .query_filtered::<(Entity, &Transform), With<Player>>()
+        .single(world)
+        .map(|(entity, transform)| (entity, transform.translation))
+        .map_err(|_| "no player".to_string())?;
+    let entities_before = world.entities().len();
+
+    let mut health = world
+        .get_mut::<PlayerHealth>(player)
+        .ok_or("no player health")?;
+    let was_invulnerable = health.invulnerable;
+    health.invulnerable = true;
+
+    let per_ring = size.div_ceil(RING_RADII.len());
+    let balance = world.resource::<BalanceData>().clone();
+    let zombies = world.resource_scope(|world, assets: Mut<ZombieAssets>| {
+        let mut commands = world.commands();
+        (0..size)
+            .map(|i| {
+                let ring = i % RING_RADII.len();
+                // Stagger the rings so zombies in neighbouring rings don't line up
+                let angle =
+                    ((i / RING_RADII.len()) as f32 + ring as f32 / 3.0) / per_ring as f32 * TAU;
+                let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * RING_RADII[ring];
+                let position = (center + offset)
+                    .clamp(Vec3::splat(-ARENA_LIMIT), Vec3::splat(ARENA_LIMIT))
+                    .with_y(1.0);
+                // Already standing, so the whole horde is busy from the first frame timed
+                let zombie = Zombie::new(ZombieKind::Walker, i as u32, &balance);
+                spawn_standing_zombie(&mut commands, &assets, position, zombie)
+            })
+            .collect::<Vec<Entity>>()
+    });
+    world.flush();
+
+    world.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.9, 0.3)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(20.0),
+            left: Val::Px(20.0),
+            padding: UiRect::all(Val::Px(12.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        GlobalZIndex(30),
+        StressOverlay,
+    ));
+
+    *world.resource_mut::<ZombieTimings>() = ZombieTimings::default();
+    *world.resource_mut::<StressHorde>() = StressHorde {
+        zombies,
+        was_invulnerable,
+        entities_before,
+    };
+    Ok(format!("{} zombies incoming, god mode on", size))
+}
+
+/// Remove whatever is left of the horde and report whether the entity count went back
+/// to where it was
+fn stop_horde(world: &mut World) -> Result<String, String> {
+    let horde = std::mem::take(&mut *world.resource_mut::<StressHorde>());
+    if !horde.active() {
+        return Err("no horde is up".to_string());
+    }
+
+    let alive: Vec<Entity> = horde
+        .zombies
+        .into_iter()
+        .filter(|entity| world.get_entity(*entity).is_ok())
+        .collect();
+    despawn_zombies(world, &alive);
+
+    let overlays: Vec<Entity> = world
+        .query_filtered::<Entity, With<StressOverlay>>()
+        .iter(world)
+        .collect();
+    for overlay in overlays {
+        world.despawn(overlay);
+    }
+
+    if let Ok(mut health) = world
+        .query_filtered::<&mut PlayerHealth, With<Player>>()
+        .single_mut(world)
+    {
+        health.invulnerable = horde.was_invulnerable;
+    }
+
+    // Pickups, decals and bullets from the fight come and go on their own, so this is
+    // only exact for a horde that was left alone
+    Ok(format!(
+        "removed {} zombies; {} entities now, {} before the horde",
+        alive.len(),
+        world.entities().len(),
+        horde.entities_before
+    ))
+}
+
+/// Leaving the run tears the zombies down anyway; put god mode back and drop the overlay
+fn end_horde_on_menu(world: &mut World) {
+    if world.resource::<StressHorde>().active() {
+        let _ = stop_horde(world);
+    }
+}
+
+/// Time stage `STAGE` of the zombie update, in whichever schedule it runs
+fn time_stage<const STAGE: usize>(app: &mut App) {
+    let stage = ZombieSystems::ALL[STAGE];
+    app.add_systems(
+        stage.schedule(),
+        (
+            start_stage::<STAGE>.before(stage),
+            end_stage::<STAGE>.after(stage),
+        )
+            .run_if(in_state(GameState::Playing).and(horde_active)),
+    );
+}
+
+fn start_stage<const STAGE: usize>(mut timings: ResMut<ZombieTimings>) {
+    timings.started[STAGE] = Some(Instant::now());
+}
+
+fn end_stage<const STAGE: usize>(mut timings: ResMut<ZombieTimings>) {
+    if let Some(started) = timings.started[STAGE].take() {
+        timings.elapsed[STAGE] += started.elapsed();
+    }
+}
+
+/// Turn this frame's stage times into samples, keeping one bench run's worth
+fn record_timings(mut timings: ResMut<ZombieTimings>) {
+    let elapsed = std::mem::take(&mut timings.elapsed);
+    for (samples, elapsed) in timings.samples.iter_mut().zip(elapsed) {
+        if samples.len() == BENCH_FRAMES {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed.as_secs_f32() * 1_000_000.0);
+    }
+}
+
+fn update_stress_overlay(
+    time: Res<Time<Real>>,
+    mut refresh: Local<Timer>,
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    horde: Res<StressHorde>,
+    timings: Res<ZombieTimings>,
+    zombies: Query<(), With<Zombie>>,
+    mut overlays: Query<&mut Text, With<StressOverlay>>,
+) {
+    refresh.tick(time.delta());
+    if !refresh.is_finished() && !refresh.duration().is_zero() {
+        return;
+    }
+    *refresh = Timer::from_seconds(0.25, TimerMode::Once);
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let frame_time = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame_time| frame_time.smoothed())
+        .unwrap_or(0.0);
+    let alive = horde
+        .zombies
+        .iter()
+        .filter(|entity| zombies.contains(**entity))
+        .count();
+
+    for mut text in overlays.iter_mut() {
+        **text = format!(
+            "STRESS  {} / {} zombies  {} entities\n{:.0} fps  {:.1} ms\n{}",
+            alive,
+            horde.zombies.len(),
+            entities.len(),
+            fps,
+            frame_time,
+            timings.report()
+        );
+    }
+}
+
+/// Bench runs go straight from the menu into a seeded shooting range run
+fn start_bench_run(
+    mut bench: ResMut<StressBench>,
+    mut mode: ResMut<GameMode>,
+    mut rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if bench.started {
+        return;
+    }
+    bench.started = true;
+    *mode = GameMode::ShootingRange;
+    rng.reseed(BENCH_SEED);
+    next_state.set(GameState::PrePlaying);
+}
+
+fn run_bench(world: &mut World) {
+    if !world.resource::<StressHorde>().active() {
+        let size = bench_horde_size().unwrap_or(HORDE_SIZE);
+        if let Err(message) = start_horde(world, size) {
+            error!("stress bench: {}", message);
+            world.write_message(AppExit::error());
+        }
+        return;
+    }
+
+    let timings = world.resource::<ZombieTimings>();
+    if timings.frames() < BENCH_FRAMES {
+        return;
+    }
+    println!(
+        "{} frames, {} zombies",
+        timings.frames(),
+        world.resource::<StressHorde>().zombies.len()
+    );
+    println!("{}", timings.report());
+    match stop_horde(world) {
+        Ok(message) => println!("{}", message),
+        Err(message) => println!("{}", message),
+    }
+    world.write_message(AppExit::Success);
+}
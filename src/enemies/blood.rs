@@ -0,0 +1,243 @@
+use super::{Zombie, ZombieDied};
+use crate::combat::{HitEvent, HitSystems};
+use crate::ui::{AccessibilitySettings, GameState};
+use crate::world::{BudgetCategory, Budgeted, Floor, GameRng, ToxicPool};
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+
+/// Splatter on the floor under zombies that get hit, and a pool under each corpse.
+/// Splatters fade by stepping through a few pre-made materials, so they share assets
+/// like everything else on zombies.
+pub struct BloodPlugin;
+
+impl Plugin for BloodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_splatter_assets)
+            .add_systems(
+                Update,
+                (spawn_splatters.in_set(HitSystems::Feedback), fade_splatters)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_splatters,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_splatters,
+            );
+    }
+}
+
+/// Seconds a splatter takes to fade away
+const SPLATTER_LIFETIME: f32 = 20.0;
+/// Distinct splatter shapes
+const SPLATTER_SHAPES: usize = 4;
+/// Opacity levels a splatter steps down through as it fades
+const FADE_STEPS: usize = 8;
+/// Size of a corpse's pool next to a hit's splatter
+const POOL_SCALE: f32 = 2.5;
+/// Furthest below a hit the floor is looked for
+const FLOOR_SEARCH: f32 = 4.0;
+
+#[derive(Component)]
+struct BloodSplatter {
+    age: Timer,
+    scorch: bool,
+    step: usize,
+}
+
+#[derive(Resource)]
+struct SplatterAssets {
+    shapes: [Handle<Mesh>; SPLATTER_SHAPES],
+    /// Blood by fade step, most opaque first
+    blood: [Handle<StandardMaterial>; FADE_STEPS],
+    /// Neutral scorch marks for reduced gore
+    scorch: [Handle<StandardMaterial>; FADE_STEPS],
+}
+
+fn setup_splatter_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rng: Res<GameRng>,
+) {
+    // Off to the side of the gameplay sequence, which the obstacle layout is still
+    // drawing from at startup
+    let mut shape_rng = StdRng::seed_from_u64(rng.map_seed);
+    let shapes = std::array::from_fn(|_| meshes.add(splatter_mesh(&mut shape_rng)));
+    let mut fade = |color: Color| -> [Handle<StandardMaterial>; FADE_STEPS] {
+        std::array::from_fn(|step| {
+            let alpha = 0.9 * (1.0 - step as f32 / FADE_STEPS as f32);
+            materials.add(StandardMaterial {
+                base_color: color.with_alpha(alpha),
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 0.3,
+                ..default()
+            })
+        })
+    };
+    commands.insert_resource(SplatterAssets {
+        shapes,
+        blood: fade(Color::srgb(0.3, 0.02, 0.02)),
+        scorch: fade(Color::srgb(0.12, 0.11, 0.1)),
+    });
+}
+
+/// Irregular flat blob facing up, about a metre across
+fn splatter_mesh(rng: &mut impl Rng) -> Mesh {
+    const RIM: usize = 16;
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    for i in 0..RIM {
+        let angle = i as f32 / RIM as f32 * TAU;
+        let radius = rng.random_range(0.3..0.5);
+        positions.push([angle.cos() * radius, 0.0, angle.sin() * radius]);
+    }
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|[x, _, z]| [x + 0.5, z + 0.5])
+        .collect();
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+    // Wound so the fan faces up
+    let indices = (0..RIM as u32)
+        .flat_map(|i| [0, (i + 1) % RIM as u32 + 1, i + 1])
+        .collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// One splatter per zombie hit each frame; burn and poison ticks, which come with no
+/// direction, don't bleed
+fn spawn_splatters(
+    mut commands: Commands,
+    mut hit_events: MessageReader<HitEvent>,
+    mut died_events: MessageReader<ZombieDied>,
+    rapier_context: ReadRapierContext,
+    assets: Res<SplatterAssets>,
+    settings: Res<AccessibilitySettings>,
+    mut rng: ResMut<GameRng>,
+    zombies: Query<(), With<Zombie>>,
+    floors: Query<(), With<Floor>>,
+    pools: Query<(&Transform, &ToxicPool)>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+
+    let mut bled: Vec<Entity> = Vec::new();
+    let mut splatters: Vec<(Vec3, f32)> = Vec::new();
+    for event in hit_events.read() {
+        if event.direction == Vec3::ZERO
+            || bled.contains(&event.entity)
+            || !zombies.contains(event.entity)
+        {
+            continue;
+        }
+        bled.push(event.entity);
+        splatters.push((event.point, 1.0));
+    }
+    for event in died_events.read() {
+        splatters.push((event.position, POOL_SCALE));
+    }
+
+    // Zombies, the dying one included, are see-through to the floor search
+    let not_zombie = |entity: Entity| !zombies.contains(entity);
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .predicate(&not_zombie);
+
+    for (origin, scale) in splatters {
+        let mut hit: Option<(Entity, f32)> = None;
+        context.with_query_pipeline(filter, |query_pipeline| {
+            hit = query_pipeline.cast_ray(origin, Vec3::NEG_Y, FLOOR_SEARCH, true);
+        });
+        // Nothing on crates, walls or mid-air
+        let Some((_, distance)) = hit.filter(|(entity, _)| floors.contains(*entity)) else {
+            continue;
+        };
+        let point = origin + Vec3::NEG_Y * distance;
+        let in_pool = pools.iter().any(|(transform, pool)| {
+            (point - transform.translation).with_y(0.0).length() <= pool.radius
+        });
+        if in_pool {
+            continue;
+        }
+
+        let scorch = settings.reduced_gore;
+        let materials = if scorch {
+            &assets.scorch
+        } else {
+            &assets.blood
+        };
+        let shape = rng.random_range(0..SPLATTER_SHAPES);
+        // Stacked a hair apart so overlapping splatters don't flicker
+        let lift = rng.random_range(0.005..0.02);
+        commands.spawn((
+            Mesh3d(assets.shapes[shape].clone()),
+            MeshMaterial3d(materials[0].clone()),
+            Transform::from_translation(point + Vec3::Y * lift)
+                .with_rotation(Quat::from_rotation_y(rng.random_range(0.0..TAU)))
+                .with_scale(Vec3::splat(scale * rng.random_range(0.7..1.3))),
+            BloodSplatter {
+                age: Timer::from_seconds(SPLATTER_LIFETIME, TimerMode::Once),
+                scorch,
+                step: 0,
+            },
+            Budgeted(BudgetCategory::Decal),
+        ));
+    }
+}
+
+fn fade_splatters(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<SplatterAssets>,
+    mut splatters: Query<(
+        Entity,
+        &mut BloodSplatter,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    for (entity, mut splatter, mut material) in splatters.iter_mut() {
+        splatter.age.tick(time.delta());
+        if splatter.age.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let step = ((splatter.age.fraction() * FADE_STEPS as f32) as usize).min(FADE_STEPS - 1);
+        if step != splatter.step {
+            splatter.step = step;
+            let materials = if splatter.scorch {
+                &assets.scorch
+            } else {
+                &assets.blood
+            };
+            material.0 = materials[step].clone();
+        }
+    }
+}
+
+fn despawn_splatters(mut commands: Commands, splatters: Query<Entity, With<BloodSplatter>>) {
+    for entity in splatters.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,420 @@
+use super::{grade_cleared_wave, WaveCleared, WaveReports, WaveState};
+use crate::player::{Player, PlayerHealth};
+use crate::save::PendingLoad;
+use crate::ui::{
+    AdaptiveDifficulty, DifficultyModifiers, GameMode, GameState, RunStats, TextRole, ThemedText,
+    UiTheme,
+};
+use crate::world::{BalanceData, DirectorBalance};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Adaptive difficulty: reads how the player has been doing and paces the wave portals
+/// from it, easing off after a near death and pushing harder while they're cruising.
+/// It only ever moves spawn pacing (portal interval and the share of runners), never
+/// zombie or weapon stats, and only when picked on the main menu, never on Hard.
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>()
+            .add_systems(
+                Update,
+                (
+                    watch_player_health,
+                    record_wave_clear.after(grade_cleared_wave),
+                    update_director,
+                    update_director_overlay,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_director_overlay)
+            .add_systems(OnExit(GameState::Playing), despawn_director_overlay)
+            .add_systems(OnEnter(GameState::GameOver), count_death)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_director,
+            )
+            // Retrying a checkpoint carries on the same run, deaths and all
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_director.run_if(not(resource_exists::<PendingLoad>)),
+            );
+
+        #[cfg(feature = "dev_console")]
+        {
+            use crate::console::ConsoleAppExt;
+            app.register_console_command("director", "", director_command);
+        }
+    }
+}
+
+/// Pressure before the Director has seen anything
+const NEUTRAL_PRESSURE: f32 = 0.5;
+
+/// How the player has been doing lately
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PerformanceHistory {
+    /// Health lost over each of the last few updates, most recent last
+    pub damage: VecDeque<f32>,
+    /// Lowest health fraction since the last update
+    pub lowest_health: f32,
+    /// Clear time per kill of the last wave cleared
+    pub seconds_per_kill: Option<f32>,
+    /// Deaths this run
+    pub deaths: u32,
+}
+
+/// Where pressure is headed from `history`: 0.0 when the player is cruising, 1.0 when
+/// they're struggling. A near death is full pressure whatever else went on.
+pub fn target_pressure(balance: &DirectorBalance, history: &PerformanceHistory) -> f32 {
+    if history.lowest_health <= balance.near_death_health {
+        return 1.0;
+    }
+    let minutes = history.damage.len() as f32 * balance.update_interval / 60.0;
+    let damage = if minutes > 0.0 {
+        let per_minute = history.damage.iter().sum::<f32>() / minutes;
+        (per_minute / balance.damage_per_minute).min(1.0)
+    } else {
+        NEUTRAL_PRESSURE
+    };
+    // Par reads as neutral, twice par as full pressure
+    let pressure = match history.seconds_per_kill {
+        Some(seconds) => {
+            let clearing = (seconds / balance.par_seconds_per_kill * 0.5).min(1.0);
+            (damage + clearing) / 2.0
+        }
+        None => damage,
+    };
+    (pressure + history.deaths as f32 * balance.death_pressure).clamp(0.0, 1.0)
+}
+
+/// `current` moved toward `target` by no more than the balance allows per update
+pub fn step_pressure(balance: &DirectorBalance, current: f32, target: f32) -> f32 {
+    current + (target - current).clamp(-balance.max_step, balance.max_step)
+}
+
+/// Reads the player and sets the spawn pacing; see DirectorPlugin
+#[derive(Resource)]
+pub struct Director {
+    /// Decided as each run starts; off, pacing stays exactly as the difficulty has it
+    pub enabled: bool,
+    pub pressure: f32,
+    pub history: PerformanceHistory,
+    /// Game time toward the next update, only counted while a wave is on
+    elapsed: f32,
+    /// RunStats::damage_taken as of the last update
+    damage_seen: f32,
+    /// Show the pressure readout (console `director`)
+    pub overlay: bool,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pressure: NEUTRAL_PRESSURE,
+            history: PerformanceHistory {
+                lowest_health: 1.0,
+                ..default()
+            },
+            elapsed: 0.0,
+            damage_seen: 0.0,
+            overlay: false,
+        }
+    }
+}
+
+impl Director {
+    /// Portal interval multiplier, 1.0 at neutral pressure or when off
+    pub fn spawn_interval(&self, balance: &DirectorBalance) -> f32 {
+        if !self.enabled {
+            return 1.0;
+        }
+        if self.pressure < NEUTRAL_PRESSURE {
+            balance.min_spawn_interval.lerp(1.0, self.pressure * 2.0)
+        } else {
+            1.0f32.lerp(balance.max_spawn_interval, self.pressure * 2.0 - 1.0)
+        }
+    }
+
+    /// Share of portal zombies let out as runners; none from neutral up or when off
+    pub fn runner_share(&self, balance: &DirectorBalance) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        balance.max_runner_share * (1.0 - self.pressure * 2.0).max(0.0)
+    }
+
+    fn describe(&self, balance: &DirectorBalance) -> String {
+        format!(
+            "pressure: {:.2}  spawn interval x{:.2}  runners {:.0}%",
+            self.pressure,
+            self.spawn_interval(balance),
+            self.runner_share(balance) * 100.0
+        )
+    }
+}
+
+#[derive(Component)]
+struct DirectorOverlay;
+
+fn watch_player_health(
+    mut director: ResMut<Director>,
+    player_q: Query<&PlayerHealth, With<Player>>,
+) {
+    if !director.enabled {
+        return;
+    }
+    if let Ok(health) = player_q.single() {
+        let fraction = health.current / health.max;
+        if fraction < director.history.lowest_health {
+            director.history.lowest_health = fraction;
+        }
+    }
+}
+
+fn record_wave_clear(
+    mut cleared_events: MessageReader<WaveCleared>,
+    reports: Res<WaveReports>,
+    mut director: ResMut<Director>,
+) {
+    let Some(event) = cleared_events.read().last() else {
+        return;
+    };
+    let Some(report) = reports
+        .last
+        .as_ref()
+        .filter(|report| report.wave == event.wave)
+    else {
+        return;
+    };
+    if director.enabled && report.tally.stats.kills > 0 {
+        director.history.seconds_per_kill =
+            Some(report.tally.seconds / report.tally.stats.kills as f32);
+    }
+}
+
+/// Every update_interval seconds of wave play, take stock and move the pressure. The
+/// break between waves doesn't count, so a quiet shop visit never reads as cruising.
+fn update_director(
+    time: Res<Time>,
+    waves: Res<WaveState>,
+    stats: Res<RunStats>,
+    balance: Res<BalanceData>,
+    mut director: ResMut<Director>,
+) {
+    if !director.enabled || !waves.active {
+        return;
+    }
+    let balance = &balance.director;
+    director.elapsed += time.delta_secs();
+    if director.elapsed < balance.update_interval {
+        return;
+    }
+    director.elapsed -= balance.update_interval;
+
+    // RunStats starts over on a retry, so never read a drop as negative damage
+    let lost = (stats.damage_taken - director.damage_seen).max(0.0);
+    director.damage_seen = stats.damage_taken;
+    let history = &mut director.history;
+    history.damage.push_back(lost);
+    while history.damage.len() > balance.history {
+        history.damage.pop_front();
+    }
+
+    let target = target_pressure(balance, &director.history);
+    let pressure = step_pressure(balance, director.pressure, target);
+    director.pressure = pressure;
+    director.history.lowest_health = 1.0;
+    info!("Director {}", director.describe(balance));
+}
+
+fn count_death(mut director: ResMut<Director>) {
+    director.history.deaths += 1;
+}
+
+/// Picked afresh for every run, from the menu setting, the difficulty and the mode
+fn reset_director(
+    mut director: ResMut<Director>,
+    adaptive: Res<AdaptiveDifficulty>,
+    difficulty: Res<DifficultyModifiers>,
+    mode: Res<GameMode>,
+) {
+    *director = Director {
+        enabled: adaptive.0 && difficulty.adaptive && mode.has_waves(),
+        overlay: director.overlay,
+        ..default()
+    };
+}
+
+fn spawn_director_overlay(
+    mut commands: Commands,
+    director: Res<Director>,
+    balance: Res<BalanceData>,
+    theme: Res<UiTheme>,
+) {
+    if !director.enabled {
+        return;
+    }
+    commands.spawn((
+        Text::new(director.describe(&balance.director)),
+        theme.font(TextRole::Caption),
+        ThemedText(TextRole::Caption),
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(40.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        overlay_visibility(&director),
+        DirectorOverlay,
+    ));
+}
+
+fn update_director_overlay(
+    director: Res<Director>,
+    balance: Res<BalanceData>,
+    mut overlays: Query<(&mut Text, &mut Visibility), With<DirectorOverlay>>,
+) {
+    if !director.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in overlays.iter_mut() {
+        text.0 = director.describe(&balance.director);
+        *visibility = overlay_visibility(&director);
+    }
+}
+
+fn overlay_visibility(director: &Director) -> Visibility {
+    if director.overlay {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn despawn_director_overlay(
+    mut commands: Commands,
+    overlays: Query<Entity, With<DirectorOverlay>>,
+) {
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Toggle the pressure readout and report the current state
+#[cfg(feature = "dev_console")]
+fn director_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let describe = {
+        let balance = world.resource::<BalanceData>().director.clone();
+        let director = world.resource::<Director>();
+        director.describe(&balance)
+    };
+    let mut director = world.resource_mut::<Director>();
+    if !director.enabled {
+        return Err("Adaptive difficulty is off for this run".to_string());
+    }
+    director.overlay = !director.overlay;
+    Ok(format!(
+        "Director overlay {}: {}",
+        if director.overlay { "on" } else { "off" },
+        describe
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BALANCE: DirectorBalance = DirectorBalance {
+        update_interval: 10.0,
+        history: 6,
+        damage_per_minute: 60.0,
+        near_death_health: 0.2,
+        par_seconds_per_kill: 3.0,
+        death_pressure: 0.2,
+        max_step: 0.15,
+        min_spawn_interval: 0.67,
+        max_spawn_interval: 1.5,
+        max_runner_share: 0.25,
+    };
+
+    /// A full minute of updates losing `per_update` health each, never close to dying
+    fn minute_losing(per_update: f32) -> PerformanceHistory {
+        PerformanceHistory {
+            damage: VecDeque::from(vec![per_update; BALANCE.history]),
+            lowest_health: 1.0,
+            seconds_per_kill: None,
+            deaths: 0,
+        }
+    }
+
+    #[test]
+    fn nothing_seen_is_neutral() {
+        let history = PerformanceHistory {
+            lowest_health: 1.0,
+            ..default()
+        };
+        assert_eq!(target_pressure(&BALANCE, &history), NEUTRAL_PRESSURE);
+    }
+
+    #[test]
+    fn damage_rate_sets_the_pressure() {
+        assert_eq!(target_pressure(&BALANCE, &minute_losing(0.0)), 0.0);
+        // 30 a minute is half of full pressure
+        assert_eq!(target_pressure(&BALANCE, &minute_losing(5.0)), 0.5);
+        assert_eq!(target_pressure(&BALANCE, &minute_losing(10.0)), 1.0);
+        // Past full pressure it stays there
+        assert_eq!(target_pressure(&BALANCE, &minute_losing(50.0)), 1.0);
+    }
+
+    #[test]
+    fn near_death_is_full_pressure() {
+        let mut history = minute_losing(0.0);
+        history.lowest_health = BALANCE.near_death_health;
+        assert_eq!(target_pressure(&BALANCE, &history), 1.0);
+
+        history.lowest_health = 0.5;
+        assert_eq!(target_pressure(&BALANCE, &history), 0.0);
+    }
+
+    #[test]
+    fn clear_times_average_in() {
+        let mut history = minute_losing(0.0);
+        // At par, clearing reads as neutral
+        history.seconds_per_kill = Some(BALANCE.par_seconds_per_kill);
+        assert_eq!(target_pressure(&BALANCE, &history), 0.25);
+        // Twice par or slower reads as full pressure
+        history.seconds_per_kill = Some(BALANCE.par_seconds_per_kill * 4.0);
+        assert_eq!(target_pressure(&BALANCE, &history), 0.5);
+        history.seconds_per_kill = Some(0.0);
+        assert_eq!(target_pressure(&BALANCE, &history), 0.0);
+    }
+
+    #[test]
+    fn deaths_add_pressure() {
+        let mut history = minute_losing(0.0);
+        history.deaths = 2;
+        assert_eq!(target_pressure(&BALANCE, &history), 0.4);
+
+        let mut history = minute_losing(5.0);
+        history.deaths = 5;
+        assert_eq!(target_pressure(&BALANCE, &history), 1.0);
+    }
+
+    #[test]
+    fn pressure_moves_a_step_at_a_time() {
+        assert_eq!(step_pressure(&BALANCE, 0.5, 1.0), 0.5 + BALANCE.max_step);
+        assert_eq!(step_pressure(&BALANCE, 0.5, 0.0), 0.5 - BALANCE.max_step);
+        assert_eq!(step_pressure(&BALANCE, 0.5, 0.55), 0.55);
+    }
+}
@@ -0,0 +1,359 @@
+use super::TargetHitEvent;
+use crate::player::{Player, PlayerActions};
+use crate::ui::{GameState, TextRole, UiTheme};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Damage readouts for training dummies: a panel floating over each dummy, a copy in
+/// the HUD while one was hit recently, and E next to a dummy to start its count over
+pub struct DpsMeterPlugin;
+
+impl Plugin for DpsMeterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_dps_hud)
+            .add_systems(OnExit(GameState::Playing), despawn_dps_ui)
+            .add_systems(
+                Update,
+                (
+                    record_dummy_hits,
+                    reset_dps_meters,
+                    update_dps_panels,
+                    update_dps_hud,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Seconds of hits the DPS figure averages over
+const DPS_WINDOW: f64 = 10.0;
+/// Shortest span the DPS is averaged over, so the first hit doesn't read as a spike
+const MIN_DPS_SPAN: f64 = 1.0;
+/// Hits this far above the dummy's centre count as headshots (top quarter of the body)
+const DUMMY_HEAD_LINE: f32 = 0.5;
+/// Seconds the HUD copy stays up after the last hit
+const HUD_LINGER: f64 = 5.0;
+const DUMMY_RESET_REACH: f32 = 2.0;
+/// Height of a dummy's panel above its centre, clear of the health bar
+const PANEL_HEIGHT: f32 = 2.1;
+const PANEL_WIDTH: f32 = 150.0;
+
+/// Damage a training dummy has taken, averaged over a rolling window
+#[derive(Component, Default, Debug)]
+pub struct DpsMeter {
+    /// Time and damage of each hit still inside the window, oldest first
+    recent: VecDeque<(f64, f32)>,
+    /// When the first hit since the last reset landed
+    started: Option<f64>,
+    pub total: f32,
+    pub hits: u32,
+    pub headshots: u32,
+    pub last_hit: Option<f64>,
+}
+
+impl DpsMeter {
+    pub fn record(&mut self, now: f64, damage: f32, headshot: bool) {
+        self.started.get_or_insert(now);
+        self.recent.push_back((now, damage));
+        self.total += damage;
+        self.hits += 1;
+        if headshot {
+            self.headshots += 1;
+        }
+        self.last_hit = Some(now);
+        while self
+            .recent
+            .front()
+            .is_some_and(|(time, _)| *time <= now - DPS_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Damage per second over the last DPS_WINDOW seconds, or since the first hit if
+    /// that was more recent
+    pub fn dps(&self, now: f64) -> f32 {
+        let Some(started) = self.started else {
+            return 0.0;
+        };
+        let span = (now - started).clamp(MIN_DPS_SPAN, DPS_WINDOW);
+        let damage: f32 = self
+            .recent
+            .iter()
+            .filter(|(time, _)| *time > now - DPS_WINDOW)
+            .map(|(_, damage)| damage)
+            .sum();
+        damage / span as f32
+    }
+
+    pub fn headshot_percent(&self) -> f32 {
+        if self.hits == 0 {
+            0.0
+        } else {
+            self.headshots as f32 / self.hits as f32 * 100.0
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = DpsMeter::default();
+    }
+
+    fn summary(&self, now: f64) -> String {
+        format!(
+            "DPS {:.1}\nTotal {:.0}\nHits {}  Head {:.0}%",
+            self.dps(now),
+            self.total,
+            self.hits,
+            self.headshot_percent()
+        )
+    }
+}
+
+/// Readout floating over one dummy
+#[derive(Component)]
+struct DpsPanel(Entity);
+
+/// HUD copy of the most recently hit dummy's readout
+#[derive(Component)]
+struct DpsHud;
+
+#[derive(Component)]
+struct DpsResetPrompt;
+
+fn spawn_dps_hud(mut commands: Commands, theme: Res<UiTheme>) {
+    commands.spawn((
+        Text::new(""),
+        theme.text(TextRole::Caption),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(140.0),
+            right: Val::Px(20.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+        DpsHud,
+    ));
+
+    commands.spawn((
+        Text::new("[E] Reset DPS meter"),
+        theme.text(TextRole::Body),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(30.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-90.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+        DpsResetPrompt,
+    ));
+}
+
+fn despawn_dps_ui(
+    mut commands: Commands,
+    ui: Query<Entity, Or<(With<DpsPanel>, With<DpsHud>, With<DpsResetPrompt>)>>,
+) {
+    for entity in ui.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn record_dummy_hits(
+    time: Res<Time>,
+    mut hit_events: MessageReader<TargetHitEvent>,
+    mut dummies: Query<(&Transform, &mut DpsMeter)>,
+) {
+    let now = time.elapsed_secs_f64();
+    for event in hit_events.read() {
+        if let Ok((transform, mut meter)) = dummies.get_mut(event.target) {
+            let headshot = event.point.y - transform.translation.y >= DUMMY_HEAD_LINE;
+            meter.record(now, event.damage, headshot);
+        }
+    }
+}
+
+/// E next to a dummy clears its meter
+fn reset_dps_meters(
+    actions: Res<PlayerActions>,
+    player_q: Query<&Transform, With<Player>>,
+    mut dummies: Query<(&Transform, &mut DpsMeter)>,
+    mut prompt_q: Query<&mut Visibility, With<DpsResetPrompt>>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+
+    let mut in_reach = false;
+    for (transform, mut meter) in dummies.iter_mut() {
+        let distance = (transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        if distance > DUMMY_RESET_REACH {
+            continue;
+        }
+        in_reach = true;
+        if actions.interact {
+            meter.reset();
+        }
+    }
+
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = if in_reach {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Keep one panel over each dummy, projected onto the screen so it always faces the
+/// camera
+fn update_dps_panels(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    time: Res<Time>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    dummies: Query<(Entity, &Transform, &DpsMeter)>,
+    mut panels: Query<(Entity, &DpsPanel, &mut Node, &mut Text, &mut Visibility)>,
+) {
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+    let now = time.elapsed_secs_f64();
+
+    let mut covered: Vec<Entity> = Vec::new();
+    for (entity, panel, mut node, mut text, mut visibility) in panels.iter_mut() {
+        let Ok((_, transform, meter)) = dummies.get(panel.0) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        covered.push(panel.0);
+
+        let anchor = transform.translation + Vec3::Y * PANEL_HEIGHT;
+        match camera.world_to_viewport(camera_transform, anchor) {
+            Ok(screen) => {
+                node.left = Val::Px(screen.x - PANEL_WIDTH / 2.0);
+                node.top = Val::Px(screen.y - 60.0);
+                *visibility = Visibility::Inherited;
+            }
+            Err(_) => *visibility = Visibility::Hidden,
+        }
+        **text = meter.summary(now);
+    }
+
+    for (dummy, _, _) in dummies
+        .iter()
+        .filter(|(dummy, ..)| !covered.contains(dummy))
+    {
+        commands.spawn((
+            Text::new(""),
+            theme.text(TextRole::Caption),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(PANEL_WIDTH),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Visibility::Hidden,
+            DpsPanel(dummy),
+        ));
+    }
+}
+
+fn update_dps_hud(
+    time: Res<Time>,
+    dummies: Query<&DpsMeter>,
+    mut hud_q: Query<(&mut Text, &mut Visibility), With<DpsHud>>,
+) {
+    let now = time.elapsed_secs_f64();
+    let latest = dummies
+        .iter()
+        .filter(|meter| meter.last_hit.is_some_and(|last| now - last <= HUD_LINGER))
+        .max_by(|a, b| {
+            a.last_hit
+                .unwrap_or(0.0)
+                .total_cmp(&b.last_hit.unwrap_or(0.0))
+        });
+
+    for (mut text, mut visibility) in hud_q.iter_mut() {
+        match latest {
+            Some(meter) => {
+                **text = format!("DUMMY\n{}", meter.summary(now));
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A hit of `damage` every second from `from` to `to`, inclusive
+    fn steady(meter: &mut DpsMeter, from: u32, to: u32, damage: f32) {
+        for second in from..=to {
+            meter.record(second as f64, damage, false);
+        }
+    }
+
+    #[test]
+    fn nothing_recorded_reads_zero() {
+        let meter = DpsMeter::default();
+        assert_eq!(meter.dps(5.0), 0.0);
+        assert_eq!(meter.headshot_percent(), 0.0);
+    }
+
+    #[test]
+    fn a_first_hit_is_averaged_over_a_second() {
+        let mut meter = DpsMeter::default();
+        meter.record(4.0, 30.0, false);
+        assert_eq!(meter.dps(4.0), 30.0);
+        assert_eq!(meter.dps(4.5), 30.0);
+        assert_eq!(meter.dps(6.0), 15.0);
+    }
+
+    #[test]
+    fn dps_averages_over_the_time_since_the_first_hit() {
+        let mut meter = DpsMeter::default();
+        steady(&mut meter, 0, 3, 10.0);
+        assert_eq!(meter.dps(3.0), 40.0 / 3.0);
+    }
+
+    #[test]
+    fn the_window_rolls_past_old_hits() {
+        let mut meter = DpsMeter::default();
+        steady(&mut meter, 0, 20, 10.0);
+        // Only the hits after 10 s are still in the window
+        assert_eq!(meter.dps(20.0), 10.0);
+        assert_eq!(meter.recent.len(), DPS_WINDOW as usize);
+        assert_eq!(meter.total, 210.0);
+        assert_eq!(meter.hits, 21);
+
+        // Stop shooting and it falls away, the totals staying
+        assert_eq!(meter.dps(25.0), 5.0);
+        assert_eq!(meter.dps(31.0), 0.0);
+        assert_eq!(meter.total, 210.0);
+    }
+
+    #[test]
+    fn headshots_and_reset() {
+        let mut meter = DpsMeter::default();
+        meter.record(0.0, 50.0, true);
+        meter.record(0.5, 25.0, false);
+        meter.record(1.0, 25.0, false);
+        meter.record(1.5, 50.0, true);
+        assert_eq!(meter.headshot_percent(), 50.0);
+        assert_eq!(meter.last_hit, Some(1.5));
+
+        meter.reset();
+        assert_eq!(meter.total, 0.0);
+        assert_eq!(meter.hits, 0);
+        assert_eq!(meter.last_hit, None);
+        assert_eq!(meter.dps(2.0), 0.0);
+    }
+}
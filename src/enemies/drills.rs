@@ -0,0 +1,584 @@
+use super::{
+    spawn_target, ClearTargets, Mover, MoverPath, PopupTarget, TargetAssets, TargetDestroyed,
+    TargetSpawn,
+};
+use crate::player::{Player, PlayerActions};
+use crate::save::{load_versioned, save_versioned};
+use crate::ui::{GameMode, GameState, TextRole, UiTheme};
+use crate::world::PhysicsLayer;
+use bevy::camera::RenderTarget;
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Timed drills in shooting range mode: a panel by the lever starts one, a board over
+/// the drill area keeps the clock and the count, and the best time for each drill is
+/// kept on disk. The drills themselves are data, in assets/drills.ron.
+pub struct DrillPlugin;
+
+impl Plugin for DrillPlugin {
+    fn build(&self, app: &mut App) {
+        let drills =
+            parse_drills(DRILLS).unwrap_or_else(|err| panic!("assets/drills.ron: {}", err));
+        app.insert_resource(drills)
+            .insert_resource(load_drill_records())
+            .init_resource::<Drill>()
+            .add_systems(Startup, spawn_drill_stations)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                abandon_drill,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                abandon_drill,
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                blank_drill_board.run_if(not(resource_equals(GameMode::ShootingRange))),
+            )
+            .add_systems(OnExit(GameState::Playing), hide_drill_prompt)
+            .add_systems(
+                Update,
+                (
+                    use_drill_panels,
+                    count_drill_targets,
+                    tick_drill,
+                    update_drill_board,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_equals(GameMode::ShootingRange)),
+            );
+    }
+}
+
+/// Built in, like the balance table; the drills are part of the game, not tuning
+const DRILLS: &str = include_str!("../../assets/drills.ron");
+
+const DRILL_RECORDS_PATH: &str = "drill_times.json";
+/// Bump whenever DrillRecordFile changes shape; files from other versions are dropped
+const DRILL_RECORDS_VERSION: u32 = 1;
+
+/// First start panel, just east of the range lever; the rest follow on along x
+const DRILL_PANELS_START: Vec3 = Vec3::new(5.0, 0.0, -2.5);
+const DRILL_PANEL_SPACING: f32 = 2.0;
+const DRILL_PANEL_REACH: f32 = 1.2;
+/// Centre of the timer board, behind the drill area and facing back down the range
+const DRILL_BOARD_POSITION: Vec3 = Vec3::new(20.5, 4.0, -34.0);
+const DRILL_BOARD_SIZE: Vec2 = Vec2::new(7.0, 3.0);
+/// Texels per metre of the board's face, which the board's text is drawn into
+const DRILL_BOARD_RESOLUTION: f32 = 100.0;
+
+/// One target of a drill stage, as written in drills.ron
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DrillTargetDef {
+    pub position: (f32, f32, f32),
+    #[serde(default = "default_drill_target_health")]
+    pub health: f32,
+    /// Seconds spent down between pop-ups
+    #[serde(default)]
+    pub popup: Option<f32>,
+    /// Far end and speed of a back and forth run
+    #[serde(default)]
+    pub slide: Option<((f32, f32, f32), f32)>,
+}
+
+fn default_drill_target_health() -> f32 {
+    100.0
+}
+
+impl DrillTargetDef {
+    fn spawn(&self) -> TargetSpawn {
+        let position = Vec3::from(self.position);
+        TargetSpawn {
+            health: self.health,
+            popup: self
+                .popup
+                .map(|down_time| PopupTarget::new(position.y, down_time)),
+            motion: self.slide.map(|(to, speed)| {
+                Mover::new(
+                    MoverPath::Linear {
+                        from: position,
+                        to: Vec3::from(to),
+                    },
+                    speed,
+                )
+            }),
+            ..TargetSpawn::fixed(position)
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DrillDef {
+    /// Shown on the panel and the board, and the key its best time is kept under
+    pub name: String,
+    /// Each stage goes up once the one before is cleared
+    pub stages: Vec<Vec<DrillTargetDef>>,
+}
+
+/// Every drill in assets/drills.ron, in panel order
+#[derive(Resource, Clone, Debug)]
+pub struct Drills(pub Vec<DrillDef>);
+
+fn parse_drills(ron: &str) -> Result<Drills, String> {
+    let drills: Vec<DrillDef> = ron::from_str(ron).map_err(|e| e.to_string())?;
+    for drill in &drills {
+        if drill.stages.is_empty() || drill.stages.iter().any(Vec::is_empty) {
+            return Err(format!("drill \"{}\" has an empty stage", drill.name));
+        }
+        let targets = drill.stages.iter().flatten();
+        if let Some(target) = targets.clone().find(|target| target.health <= 0.0) {
+            return Err(format!(
+                "drill \"{}\": a target at {:?} has no health",
+                drill.name, target.position
+            ));
+        }
+        if targets
+            .filter_map(|target| target.popup)
+            .any(|down| down < 0.0)
+        {
+            return Err(format!("drill \"{}\": negative popup time", drill.name));
+        }
+    }
+    Ok(Drills(drills))
+}
+
+/// Marks targets put up by a drill; they never come back on their own and stay out
+/// of saves
+#[derive(Component)]
+pub struct DrillTarget;
+
+/// Where the range's drill stands
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub enum Drill {
+    #[default]
+    Idle,
+    Running {
+        /// Index into Drills
+        drill: usize,
+        stage: usize,
+        elapsed: f32,
+        /// Targets of the current stage still standing
+        targets: Vec<Entity>,
+    },
+    Finished {
+        drill: usize,
+        time: f32,
+        /// Beat (or set) the best time on record
+        record: bool,
+    },
+}
+
+impl Drill {
+    pub fn is_running(&self) -> bool {
+        matches!(self, Drill::Running { .. })
+    }
+}
+
+/// Best time per drill name, in seconds
+#[derive(Resource, Default, Clone, Debug)]
+pub struct DrillRecords(pub BTreeMap<String, f32>);
+
+#[derive(Serialize, Deserialize)]
+struct DrillRecordFile {
+    version: u32,
+    best: BTreeMap<String, f32>,
+}
+
+/// A missing file means no drill has been finished yet; a bad one starts over
+fn load_drill_records() -> DrillRecords {
+    match load_versioned::<DrillRecordFile>(DRILL_RECORDS_PATH, DRILL_RECORDS_VERSION) {
+        Ok(Some(file)) => DrillRecords(file.best),
+        Ok(None) => DrillRecords::default(),
+        Err(reason) => {
+            warn!("Drill times are {}; starting afresh", reason);
+            DrillRecords::default()
+        }
+    }
+}
+
+fn save_drill_records(records: &DrillRecords) {
+    let file = DrillRecordFile {
+        version: DRILL_RECORDS_VERSION,
+        best: records.0.clone(),
+    };
+    if let Err(e) = save_versioned(DRILL_RECORDS_PATH, &file) {
+        warn!("Could not save drill times: {}", e);
+    }
+}
+
+#[derive(Component)]
+struct DrillPanel(usize);
+
+#[derive(Component)]
+struct DrillPrompt;
+
+/// The text drawn onto the timer board's face
+#[derive(Component)]
+struct DrillBoardText;
+
+fn drill_panel_position(index: usize) -> Vec3 {
+    DRILL_PANELS_START + Vec3::X * DRILL_PANEL_SPACING * index as f32
+}
+
+/// Start panels and the timer board, standing in every mode like the range lever
+fn spawn_drill_stations(
+    mut commands: Commands,
+    drills: Res<Drills>,
+    theme: Res<UiTheme>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let post_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.25, 0.25, 0.28),
+        metallic: 0.8,
+        ..default()
+    });
+    let face_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.1, 0.6, 0.9),
+        emissive: LinearRgba::rgb(0.1, 0.6, 1.2),
+        ..default()
+    });
+    let post_mesh = meshes.add(Cuboid::new(0.2, 1.2, 0.2));
+    let face_mesh = meshes.add(Cuboid::new(0.6, 0.4, 0.08));
+
+    for index in 0..drills.0.len() {
+        commands
+            .spawn((
+                Mesh3d(post_mesh.clone()),
+                MeshMaterial3d(post_material.clone()),
+                Transform::from_translation(drill_panel_position(index) + Vec3::Y * 0.6),
+                RigidBody::Fixed,
+                Collider::cuboid(0.1, 0.6, 0.1),
+                PhysicsLayer::World.groups(),
+                DrillPanel(index),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Mesh3d(face_mesh.clone()),
+                    MeshMaterial3d(face_material.clone()),
+                    Transform::from_xyz(0.0, 0.7, 0.0).with_rotation(Quat::from_rotation_x(-0.6)),
+                ));
+            });
+    }
+
+    let board_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.05, 0.05, 0.06),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    let half = DRILL_BOARD_SIZE / 2.0;
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(DRILL_BOARD_SIZE.x, DRILL_BOARD_SIZE.y, 0.2))),
+        MeshMaterial3d(board_material),
+        Transform::from_translation(DRILL_BOARD_POSITION),
+        RigidBody::Fixed,
+        Collider::cuboid(half.x, half.y, 0.1),
+        PhysicsLayer::World.groups(),
+    ));
+    spawn_drill_board_face(
+        &mut commands,
+        &theme,
+        &mut meshes,
+        &mut materials,
+        &mut images,
+    );
+    let leg_height = DRILL_BOARD_POSITION.y - half.y;
+    let leg_mesh = meshes.add(Cuboid::new(0.2, leg_height, 0.2));
+    for side in [-1.0, 1.0] {
+        commands.spawn((
+            Mesh3d(leg_mesh.clone()),
+            MeshMaterial3d(post_material.clone()),
+            Transform::from_translation(
+                DRILL_BOARD_POSITION.with_y(leg_height / 2.0) + Vec3::X * side * (half.x - 0.4),
+            ),
+        ));
+    }
+
+    commands.spawn((
+        Text::new(""),
+        theme.text(TextRole::Body),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(30.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-110.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+        DrillPrompt,
+    ));
+}
+
+/// A camera draws the board's text into an image, which lights the board's front face
+fn spawn_drill_board_face(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    images: &mut Assets<Image>,
+) {
+    let face = DRILL_BOARD_SIZE - Vec2::splat(0.2);
+    let texels = (face * DRILL_BOARD_RESOLUTION).as_uvec2();
+    let image = images.add(Image::new_target_texture(
+        texels.x,
+        texels.y,
+        TextureFormat::bevy_default(),
+    ));
+    let board_camera = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                // Before the main camera, so the face shows this frame's text
+                order: -1,
+                target: RenderTarget::Image(image.clone().into()),
+                clear_color: ClearColorConfig::Custom(Color::srgb(0.02, 0.02, 0.03)),
+                ..default()
+            },
+        ))
+        .id();
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            UiTargetCamera(board_camera),
+        ))
+        .with_child((
+            Text::new(""),
+            theme.text(TextRole::Title),
+            TextLayout::new_with_justify(Justify::Center),
+            DrillBoardText,
+        ));
+
+    commands.spawn((
+        Mesh3d(meshes.add(Rectangle::from_size(face))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color_texture: Some(image),
+            unlit: true,
+            ..default()
+        })),
+        // Just proud of the board's front, facing back down the range
+        Transform::from_translation(DRILL_BOARD_POSITION + Vec3::Z * 0.101),
+    ));
+}
+
+/// E at a panel starts its drill, or calls off the one under way
+fn use_drill_panels(
+    mut commands: Commands,
+    actions: Res<PlayerActions>,
+    drills: Res<Drills>,
+    assets: Res<TargetAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut drill: ResMut<Drill>,
+    mut clear_events: MessageWriter<ClearTargets>,
+    player_q: Query<&Transform, With<Player>>,
+    panels: Query<(&Transform, &DrillPanel)>,
+    mut prompt_q: Query<(&mut Text, &mut Visibility), With<DrillPrompt>>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+    let feet = player_transform.translation.with_y(0.0);
+    let panel = panels
+        .iter()
+        .find(|(transform, _)| {
+            transform.translation.with_y(0.0).distance(feet) <= DRILL_PANEL_REACH
+        })
+        .map(|(_, panel)| panel.0);
+
+    for (mut text, mut visibility) in prompt_q.iter_mut() {
+        match panel {
+            Some(_) if drill.is_running() => text.0 = "[E] Abort drill".to_string(),
+            Some(index) => text.0 = format!("[E] Start drill: {}", drills.0[index].name),
+            None => {}
+        }
+        *visibility = if panel.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let Some(index) = panel.filter(|_| actions.interact) else {
+        return;
+    };
+    if let Drill::Running { targets, .. } = &*drill {
+        clear_events.write(ClearTargets(targets.clone()));
+        *drill = Drill::Idle;
+        return;
+    }
+    *drill = Drill::Running {
+        drill: index,
+        stage: 0,
+        elapsed: 0.0,
+        targets: spawn_drill_stage(&mut commands, &assets, &mut materials, &drills.0[index], 0),
+    };
+}
+
+fn spawn_drill_stage(
+    commands: &mut Commands,
+    assets: &TargetAssets,
+    materials: &mut Assets<StandardMaterial>,
+    drill: &DrillDef,
+    stage: usize,
+) -> Vec<Entity> {
+    drill.stages[stage]
+        .iter()
+        .map(|target| {
+            let entity = spawn_target(commands, assets, materials, target.spawn());
+            commands.entity(entity).insert(DrillTarget);
+            entity
+        })
+        .collect()
+}
+
+/// Strike off destroyed targets, put up the next stage once one is cleared and stop
+/// the clock on the last
+fn count_drill_targets(
+    mut commands: Commands,
+    mut destroyed_events: MessageReader<TargetDestroyed>,
+    standing: Query<(), With<DrillTarget>>,
+    drills: Res<Drills>,
+    assets: Res<TargetAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut drill: ResMut<Drill>,
+    mut records: ResMut<DrillRecords>,
+) {
+    let Drill::Running {
+        drill: index,
+        stage,
+        elapsed,
+        targets,
+    } = &mut *drill
+    else {
+        destroyed_events.clear();
+        return;
+    };
+    for event in destroyed_events.read() {
+        targets.retain(|&target| target != event.entity);
+    }
+    if !targets.is_empty() {
+        // Gone without being shot down: the lever or a load cleared the range
+        if !targets.iter().any(|&target| standing.contains(target)) {
+            *drill = Drill::Idle;
+        }
+        return;
+    }
+
+    let def = &drills.0[*index];
+    if *stage + 1 < def.stages.len() {
+        *stage += 1;
+        *targets = spawn_drill_stage(&mut commands, &assets, &mut materials, def, *stage);
+        return;
+    }
+
+    let time = *elapsed;
+    let record = records.0.get(&def.name).is_none_or(|&best| time < best);
+    if record {
+        records.0.insert(def.name.clone(), time);
+        save_drill_records(&records);
+    }
+    info!("Drill \"{}\" done in {:.2}s", def.name, time);
+    *drill = Drill::Finished {
+        drill: *index,
+        time,
+        record,
+    };
+}
+
+fn tick_drill(time: Res<Time>, mut drill: ResMut<Drill>) {
+    if let Drill::Running { elapsed, .. } = &mut *drill {
+        *elapsed += time.delta_secs();
+    }
+}
+
+/// Call off the drill when a run starts over. A retry already took its targets down
+/// with everything else; a new run from the menu hasn't.
+fn abandon_drill(mut drill: ResMut<Drill>, mut clear_events: MessageWriter<ClearTargets>) {
+    if let Drill::Running { targets, .. } = &*drill {
+        clear_events.write(ClearTargets(targets.clone()));
+    }
+    *drill = Drill::Idle;
+}
+
+fn format_drill_time(seconds: f32) -> String {
+    format!("{:02}:{:04.1}", (seconds / 60.0) as u32, seconds % 60.0)
+}
+
+/// Keep the board's text up to date with the drill
+fn update_drill_board(
+    drill: Res<Drill>,
+    drills: Res<Drills>,
+    records: Res<DrillRecords>,
+    mut texts: Query<&mut Text, With<DrillBoardText>>,
+) {
+    for mut text in texts.iter_mut() {
+        let board = match &*drill {
+            Drill::Idle => "RANGE DRILLS\nStart one at the panels\nby the lever".to_string(),
+            Drill::Running {
+                drill: index,
+                stage,
+                elapsed,
+                targets,
+            } => {
+                let def = &drills.0[*index];
+                let later: usize = def.stages[stage + 1..].iter().map(Vec::len).sum();
+                format!(
+                    "{}\n{}\n{} left",
+                    def.name.to_uppercase(),
+                    format_drill_time(*elapsed),
+                    targets.len() + later
+                )
+            }
+            Drill::Finished {
+                drill: index,
+                time,
+                record,
+            } => {
+                let def = &drills.0[*index];
+                let best = records.0.get(&def.name).copied().unwrap_or(*time);
+                format!(
+                    "{}\nTIME {}\n{} {}",
+                    def.name.to_uppercase(),
+                    format_drill_time(*time),
+                    if *record { "NEW BEST" } else { "BEST" },
+                    format_drill_time(best)
+                )
+            }
+        };
+        if text.0 != board {
+            text.0 = board;
+        }
+    }
+}
+
+fn hide_drill_prompt(mut prompt_q: Query<&mut Visibility, With<DrillPrompt>>) {
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Outside the range the board stands dark
+fn blank_drill_board(mut texts: Query<&mut Text, With<DrillBoardText>>) {
+    for mut text in texts.iter_mut() {
+        text.0.clear();
+    }
+}
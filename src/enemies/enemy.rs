@@ -1,61 +1,460 @@
-use crate::combat::{HitEvent, Shootable};
-use crate::player::{Player, PlayerHealth};
-use crate::ui::GameState;
-use crate::world::NavGrid;
+use super::{flanking_goal, ExplosionFuse, HitFlash, SquadRole, ZombieSquads};
+use crate::combat::{HitEvent, HitSystems, Shootable, ShotFired, StatusEffects};
+use crate::player::{
+    apply_player_damage, FeedbackEvent, Flinch, Player, PlayerArmor, PlayerDamageSource,
+    PlayerHealth, PlayerHit,
+};
+use crate::ui::{ColorPalette, DifficultyModifiers, GameState, Subtitle};
+use crate::world::{
+    BalanceData, Boss, BudgetCategory, Budgeted, Climbable, Dissolving, Interpolated, NavGrid,
+    PhysicsLayer, SpatialIndex, SpatialKind, SurfaceMaterial,
+};
+#[cfg(feature = "dev_console")]
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use rand::Rng;
+use std::f32::consts::{FRAC_PI_2, TAU};
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<FrameCounter>()
-            .add_systems(Startup, spawn_zombies)
-            .add_systems(
+        app.add_message::<NoiseEvent>()
+            .add_message::<ZombieSpawned>()
+            .add_message::<ZombieDied>()
+            .add_message::<ZombieAttacked>()
+            .init_resource::<FrameCounter>()
+            .init_resource::<GrowlCooldown>()
+            .configure_sets(
+                FixedUpdate,
+                ZombieSystems::Indexing.before(ZombieSystems::Pathing),
+            )
+            // Deaths are sent alongside the hits that caused them, ahead of feedback
+            .configure_sets(
                 Update,
+                (ZombieSystems::Hits, ZombieSystems::Corpses).in_set(HitSystems::Damage),
+            )
+            .add_systems(Startup, setup_zombie_assets)
+            .add_systems(
+                FixedUpdate,
                 (
                     increment_frame_counter,
-                    update_zombie_paths,
-                    move_zombies,
-                    separate_zombies,
-                    zombie_attack,
-                    handle_zombie_hits,
-                    update_zombie_health_bars,
-                    despawn_dead_zombies,
+                    rise_spawning_zombies,
+                    shots_make_noise,
+                    hear_noises,
+                    wake_dormant_zombies.after(ZombieSystems::Indexing),
+                    update_zombie_paths.in_set(ZombieSystems::Pathing),
+                    (move_zombies, climb_zombies).in_set(ZombieSystems::Movement),
+                    separate_zombies.in_set(ZombieSystems::Separation),
+                    zombie_attack.in_set(ZombieSystems::Attacks),
                 )
                     .chain()
                     .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    (puff_spawn_dirt, fly_dirt_puffs),
+                    (
+                        handle_zombie_hits,
+                        recover_from_stagger,
+                        update_zombie_materials,
+                    )
+                        .in_set(ZombieSystems::Hits),
+                    (start_crawling, mark_shielded, update_zombie_health_bars)
+                        .chain()
+                        .in_set(ZombieSystems::HealthBars),
+                    despawn_dead_zombies.in_set(ZombieSystems::Corpses),
+                    zombie_growl_cues,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_zombie_palette.run_if(resource_changed::<ColorPalette>),
+            )
+            // New runs start empty; the wave system brings zombies in
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_all_zombies,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_all_zombies,
             );
     }
 }
 
+/// Stages of the zombie update, in the order they run. Lets other code slot in between
+/// them or time them, as the dev console's `stress` command does. The simulation
+/// stages up to Attacks run on the fixed tick (see FixedTimestepPlugin), the rest once
+/// a frame.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ZombieSystems {
+    /// Rebuilding the SpatialIndex the later stages look things up in
+    Indexing,
+    Pathing,
+    Movement,
+    Separation,
+    Attacks,
+    Hits,
+    HealthBars,
+    Corpses,
+}
+
+impl ZombieSystems {
+    /// Schedule the stage runs in
+    #[cfg(feature = "dev_console")]
+    pub fn schedule(&self) -> InternedScheduleLabel {
+        match self {
+            ZombieSystems::Indexing
+            | ZombieSystems::Pathing
+            | ZombieSystems::Movement
+            | ZombieSystems::Separation
+            | ZombieSystems::Attacks => FixedUpdate.intern(),
+            ZombieSystems::Hits | ZombieSystems::HealthBars | ZombieSystems::Corpses => {
+                Update.intern()
+            }
+        }
+    }
+
+    #[cfg(feature = "dev_console")]
+    pub const ALL: [ZombieSystems; 8] = [
+        ZombieSystems::Indexing,
+        ZombieSystems::Pathing,
+        ZombieSystems::Movement,
+        ZombieSystems::Separation,
+        ZombieSystems::Attacks,
+        ZombieSystems::Hits,
+        ZombieSystems::HealthBars,
+        ZombieSystems::Corpses,
+    ];
+}
+
+/// Time until the next "zombie behind you" caption may be shown
+#[derive(Resource)]
+struct GrowlCooldown(Timer);
+
+impl Default for GrowlCooldown {
+    fn default() -> Self {
+        Self(Timer::from_seconds(5.0, TimerMode::Once))
+    }
+}
+
+/// A sound zombies can hear, e.g. a burning flare
+#[derive(Message)]
+pub struct NoiseEvent {
+    pub source: Entity,
+    pub position: Vec3,
+    pub radius: f32,
+    /// Most zombies this source may hold at once
+    pub max_listeners: usize,
+}
+
+/// Sent whenever a zombie enters the world, whoever spawned it. Nothing in the game
+/// reacts to spawns yet; it's there for tests and other observers.
+#[derive(Message)]
+#[allow(dead_code)]
+pub struct ZombieSpawned {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub kind: ZombieKind,
+}
+
+/// Sent once per zombie as it is removed for running out of health
+#[derive(Message)]
+pub struct ZombieDied {
+    pub position: Vec3,
+    pub kind: ZombieKind,
+    /// Whoever landed the final hit, if anyone
+    pub killer: Option<Entity>,
+    /// The final hit was to the head
+    pub headshot: bool,
+}
+
+/// Sent when a zombie's attack lands, on the player or anyone else
+#[derive(Message)]
+pub struct ZombieAttacked {
+    pub zombie: Entity,
+    /// Who was hit; the boss arena only cares which zombie struck
+    #[allow(dead_code)]
+    pub target: Entity,
+}
+
+/// Side an entity fights on. Zombies chase and bite whatever on the Survivors team is
+/// nearest, the player included.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Team {
+    Survivors,
+    Horde,
+}
+
+/// Closest of `targets` to `from`
+fn nearest_target(targets: &[(Entity, Vec3)], from: Vec3) -> Option<(Entity, Vec3)> {
+    targets.iter().copied().min_by(|(_, a), (_, b)| {
+        a.distance_squared(from)
+            .total_cmp(&b.distance_squared(from))
+    })
+}
+
+/// Zombie chasing a noise instead of the player until the noise stops repeating
+#[derive(Component)]
+pub struct Distracted {
+    pub source: Entity,
+    pub position: Vec3,
+    /// Refreshed by every noise from the source; once it runs out the zombie goes back to the player
+    pub timer: Timer,
+}
+
+/// Zombie reeling from a heavy hit; it stops moving until the timer runs out
+#[derive(Component)]
+pub struct Staggered {
+    pub timer: Timer,
+}
+
+/// Zombie being pushed back by a shove; it slides along `velocity` until the timer
+/// runs out, through its controller so it still collides with the level
+#[derive(Component)]
+pub struct Shoved {
+    pub velocity: Vec3,
+    pub timer: Timer,
+}
+
+/// Zombie whose legs have been shot out; it drags itself along for the rest of its life
+#[derive(Component)]
+pub struct Crawling;
+
+/// Hits this far below a zombie's centre land on its legs (scaled with the zombie)
+const LEG_LINE: f32 = -0.4;
+/// Hits this far above a zombie's centre land on its head (scaled with the zombie)
+const HEAD_LINE: f32 = 0.6;
+/// Leg damage, as a share of max health, that takes a zombie off its feet
+const CRIPPLE_FRACTION: f32 = 0.35;
+
+/// Whether a round striking `point` lands on the head of the zombie at `transform`
+pub fn is_headshot(point: Vec3, transform: &Transform) -> bool {
+    point.y - transform.translation.y > HEAD_LINE * transform.scale.y
+}
+const CRAWL_SPEED_SCALE: f32 = 0.3;
+/// Crawlers are squashed to this share of their height, collider and all
+const CRAWL_HEIGHT_SCALE: f32 = 0.5;
+const CRAWL_ATTACK_RANGE: f32 = 0.9;
+
+/// Zombie running from a lit explosive; it sprints along `direction` until the timer
+/// runs out, then picks its path back up
+#[derive(Component)]
+pub struct Scattering {
+    pub direction: Vec3,
+    pub timer: Timer,
+}
+
+/// Lit explosives closer than this send zombies scattering
+const SCATTER_RADIUS: f32 = 3.0;
+const SCATTER_TIME: f32 = 0.6;
+/// Scattering zombies run this much faster than they walk
+const SCATTER_SPEED_SCALE: f32 = 1.5;
+
+/// Zombie still rising out of the ground after spawning; it can't move, bite or be
+/// hurt, and shows no health bar, until the timer runs out
+#[derive(Component)]
+pub struct SpawnProtection {
+    pub timer: Timer,
+    /// Standing height it rises to
+    pub target_y: f32,
+}
+
+/// Seconds a new zombie takes to rise out of the ground
+const SPAWN_RISE_TIME: f32 = 0.7;
+/// How far below the floor a new zombie starts rising from
+const SPAWN_DEPTH: f32 = 2.0;
+/// Clods of dirt thrown up around a rising zombie
+const DIRT_PUFF_COUNT: usize = 8;
+const DIRT_PUFF_LIFETIME: f32 = 0.6;
+
+/// What a dead zombie leaves lying for a moment; nothing but looks
+#[derive(Component)]
+struct ZombieCorpse;
+
+/// Clod of dirt thrown up by a rising zombie; flies outwards and shrinks away
+#[derive(Component)]
+struct DirtPuff {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+/// Zombie getting up on to cover: moved along a fixed arc, outside its controller, and
+/// unable to bite or be staggered until it's on top. It can still be shot.
+#[derive(Component)]
+pub struct Climbing {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub timer: Timer,
+}
+
+/// Zombie picked out as the priority target, e.g. by the companion drone
+#[derive(Component)]
+pub struct Marked;
+
+/// Zombie behind armor plates (see HitZone), e.g. the boss. A shield mark sits next to
+/// its health bar while `active`, i.e. while the plates soak up most of what hits it.
+#[derive(Component)]
+pub struct Shielded {
+    pub active: bool,
+}
+
+/// Zombie playing dead among the corpses. It lies still, neither pathing nor biting,
+/// until a survivor comes within DORMANT_WAKE_RANGE or a noise reaches it (a shot
+/// landing close by included), then takes DORMANT_RISE_TIME to get up. Until it's on
+/// its feet it takes double damage.
+#[derive(Component)]
+pub struct DormantCorpse {
+    /// Running while it gets up
+    waking: Option<Timer>,
+    /// Which way it faces once up
+    facing: Quat,
+    /// Centre height once up
+    standing_y: f32,
+}
+
+impl DormantCorpse {
+    fn wake(&mut self) {
+        if self.waking.is_none() {
+            self.waking = Some(Timer::from_seconds(DORMANT_RISE_TIME, TimerMode::Once));
+        }
+    }
+
+    fn lying_still(&self) -> bool {
+        self.waking.is_none()
+    }
+}
+
+/// Survivors closer than this wake a corpse
+const DORMANT_WAKE_RANGE: f32 = 4.0;
+/// Rounds landing closer than this wake a corpse
+const SHOT_NOISE_RADIUS: f32 = 2.0;
+/// Seconds a corpse takes to get up
+const DORMANT_RISE_TIME: f32 = 1.0;
+const DORMANT_DAMAGE_SCALE: f32 = 2.0;
+/// Capsule centre above the floor when lying flat, i.e. its radius
+const CORPSE_HEIGHT: f32 = 0.4;
+/// Seconds a dead zombie's corpse lies there before it dissolves
+const CORPSE_LINGER: f32 = 3.0;
+
+/// Hits at least this strong stagger a zombie
+const STAGGER_DAMAGE: f32 = 30.0;
+const STAGGER_TIME: f32 = 0.4;
+
+/// Seconds a walker takes to climb on to cover
+const CLIMB_TIME: f32 = 0.8;
+/// How far ahead of itself a zombie looks for cover its path climbs
+const CLIMB_REACH: f32 = 1.0;
+/// Capsule centre above the feet
+const ZOMBIE_HALF_HEIGHT: f32 = 1.0;
+/// Zombies have no physics body to fall with, so the controller pulls them down at this
+/// rate; it's what brings them back off cover
+const ZOMBIE_FALL_SPEED: f32 = 5.0;
+
 /// Frame counter for staggered updates
 #[derive(Resource, Default)]
 pub struct FrameCounter(pub u32);
 
+/// Zombie variant, carried on lifecycle messages
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ZombieKind {
+    #[default]
+    Walker,
+    Runner,
+}
+
+impl ZombieKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ZombieKind::Walker => "Walker",
+            ZombieKind::Runner => "Runner",
+        }
+    }
+
+    /// Seconds to get up on to cover
+    pub fn climb_time(&self) -> f32 {
+        match self {
+            ZombieKind::Walker => CLIMB_TIME,
+            ZombieKind::Runner => CLIMB_TIME / 1.3,
+        }
+    }
+}
+
 /// Zombie enemy component
 #[derive(Component)]
+#[require(Interpolated)]
 pub struct Zombie {
+    pub kind: ZombieKind,
     pub health: f32,
     pub max_health: f32,
     pub speed: f32,
     pub damage: f32,
+    /// How hard a bite throws the player's aim (see Flinch)
+    pub flinch: f32,
+    /// Share of each shot blocked, 0.0 to 1.0; see AmmoModifiers for what gets through
+    pub armor: f32,
+    /// Gets up on to low cover (see Climbable) when going around would take too long
+    pub can_climb: bool,
+    /// Scatters away from lit explosives close by instead of pressing on
+    pub scatters: bool,
     pub attack_cooldown: Timer,
     pub path_update_offset: u32, // Stagger offset (0-19)
+    /// Source of the most recent hit, credited with the kill
+    pub last_hit_by: Option<Entity>,
+    /// Whether that hit landed above HEAD_LINE
+    pub last_hit_headshot: bool,
+    /// Damage taken by hits below LEG_LINE; past CRIPPLE_FRACTION of max health the
+    /// zombie starts Crawling
+    pub leg_damage: f32,
 }
 
 impl Zombie {
-    pub fn new(path_offset: u32) -> Self {
+    /// A fresh zombie with the balance file's stats for its kind
+    pub fn new(kind: ZombieKind, path_offset: u32, balance: &BalanceData) -> Self {
+        let stats = balance.zombie(kind);
         Self {
-            health: 100.0,
-            max_health: 100.0,
-            speed: 3.0,
-            damage: 10.0,
-            attack_cooldown: Timer::from_seconds(1.0, TimerMode::Once),
+            kind,
+            health: stats.health,
+            max_health: stats.health,
+            speed: stats.speed,
+            damage: stats.damage,
+            flinch: stats.flinch,
+            armor: 0.0,
+            can_climb: true,
+            scatters: true,
+            attack_cooldown: Timer::from_seconds(stats.attack_cooldown, TimerMode::Once),
             path_update_offset: path_offset % 20,
+            last_hit_by: None,
+            last_hit_headshot: false,
+            leg_damage: 0.0,
         }
     }
+
+    /// A zombie of `kind` with the difficulty's health, damage and speed applied
+    pub fn for_difficulty(
+        kind: ZombieKind,
+        path_offset: u32,
+        difficulty: &DifficultyModifiers,
+        balance: &BalanceData,
+    ) -> Self {
+        let mut zombie = Self::new(kind, path_offset, balance);
+        zombie.max_health *= difficulty.zombie_health;
+        zombie.health = zombie.max_health;
+        zombie.damage *= difficulty.zombie_damage;
+        zombie.speed *= difficulty.zombie_speed;
+        zombie
+    }
 }
 
 /// Path component for zombie navigation
@@ -72,9 +471,39 @@ pub struct ZombieHealthBar;
 #[derive(Component)]
 pub struct ZombieHealthBarFill;
 
+/// Mark next to a crawler's health bar
+#[derive(Component)]
+struct ZombieCrippledIcon;
+
+/// Mark next to a Shielded zombie's health bar
+#[derive(Component)]
+struct ZombieShieldIcon;
+
 #[derive(Component)]
 struct ZombieChildOf(Entity);
 
+/// Shared meshes and materials for zombies and their health bars. Zombies swap
+/// between these rather than owning a material, so feedback costs no allocations.
+#[derive(Resource)]
+pub struct ZombieAssets {
+    mesh: Handle<Mesh>,
+    /// Body colour by health, from full to nearly dead
+    health_materials: [Handle<StandardMaterial>; 3],
+    stagger_material: Handle<StandardMaterial>,
+    marked_material: Handle<StandardMaterial>,
+    burning_material: Handle<StandardMaterial>,
+    health_bar_bg_mesh: Handle<Mesh>,
+    health_bar_fill_mesh: Handle<Mesh>,
+    health_bar_bg_material: Handle<StandardMaterial>,
+    health_bar_fill_material: Handle<StandardMaterial>,
+    crippled_icon_mesh: Handle<Mesh>,
+    crippled_icon_material: Handle<StandardMaterial>,
+    shield_icon_mesh: Handle<Mesh>,
+    shield_icon_material: Handle<StandardMaterial>,
+    dirt_mesh: Handle<Mesh>,
+    dirt_material: Handle<StandardMaterial>,
+}
+
 /// Generate a random position at the edges of the map
 fn generate_edge_position(rng: &mut impl Rng, quadrant: i32) -> (f32, f32) {
     match quadrant % 4 {
@@ -91,8 +520,8 @@ fn generate_edge_position(rng: &mut impl Rng, quadrant: i32) -> (f32, f32) {
     }
 }
 
-/// Find a valid spawn position that is walkable and not too close to other zombies
-fn find_valid_spawn_position(
+/// Find a valid spawn position that is walkable and not too close to the others
+pub(super) fn find_valid_spawn_position(
     nav_grid: &NavGrid,
     existing: &[Vec3],
     min_spacing: f32,
@@ -125,112 +554,416 @@ fn find_valid_spawn_position(
     None
 }
 
-fn spawn_zombies(
+fn setup_zombie_assets(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    nav_grid: Res<NavGrid>,
+    palette: Res<ColorPalette>,
 ) {
-    let mut rng = rand::rng();
-
-    let zombie_mesh = meshes.add(Capsule3d::new(0.4, 1.2));
-    let zombie_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.3, 0.5, 0.3),
-        ..default()
+    commands.insert_resource(ZombieAssets {
+        mesh: meshes.add(Capsule3d::new(0.4, 1.2)),
+        health_materials: [
+            Color::srgb(0.3, 0.5, 0.3),
+            Color::srgb(0.22, 0.36, 0.2),
+            Color::srgb(0.14, 0.2, 0.12),
+        ]
+        .map(|base_color| {
+            materials.add(StandardMaterial {
+                base_color,
+                ..default()
+            })
+        }),
+        stagger_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.6, 0.55, 0.25),
+            ..default()
+        }),
+        marked_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.5, 0.3),
+            emissive: LinearRgba::rgb(0.2, 0.8, 1.2),
+            ..default()
+        }),
+        burning_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.5, 0.3, 0.15),
+            emissive: LinearRgba::rgb(1.6, 0.6, 0.1),
+            ..default()
+        }),
+        health_bar_bg_mesh: meshes.add(Cuboid::new(0.8, 0.1, 0.05)),
+        health_bar_fill_mesh: meshes.add(Cuboid::new(0.75, 0.08, 0.06)),
+        health_bar_bg_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.2, 0.2),
+            unlit: true,
+            ..default()
+        }),
+        health_bar_fill_material: materials.add(StandardMaterial {
+            base_color: palette.enemy_health,
+            unlit: true,
+            ..default()
+        }),
+        crippled_icon_mesh: meshes.add(Cuboid::new(0.12, 0.12, 0.05)),
+        crippled_icon_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.55, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        shield_icon_mesh: meshes.add(Cuboid::new(0.14, 0.16, 0.05)),
+        shield_icon_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.5, 0.75, 1.0),
+            unlit: true,
+            ..default()
+        }),
+        dirt_mesh: meshes.add(Cuboid::new(0.15, 0.15, 0.15)),
+        dirt_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.25, 0.15),
+            perceptual_roughness: 1.0,
+            ..default()
+        }),
     });
+}
 
-    // Health bar meshes
-    let health_bar_bg_mesh = meshes.add(Cuboid::new(0.8, 0.1, 0.05));
-    let health_bar_fill_mesh = meshes.add(Cuboid::new(0.75, 0.08, 0.06));
-    let health_bar_bg_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.2, 0.2, 0.2),
-        unlit: true,
-        ..default()
-    });
-    let health_bar_fill_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.8, 0.2, 0.2),
-        unlit: true,
-        ..default()
-    });
+fn apply_zombie_palette(
+    palette: Res<ColorPalette>,
+    assets: Option<Res<ZombieAssets>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(assets) = assets else {
+        return;
+    };
+    if let Some(material) = materials.get_mut(&assets.health_bar_fill_material) {
+        material.base_color = palette.enemy_health;
+    }
+}
 
-    // Track spawned positions for spacing check
-    let mut spawned_positions: Vec<Vec3> = Vec::new();
-    let min_spacing = 2.0; // Minimum distance between zombies
+/// Spawn a single zombie with its health bars. It rises out of the ground to stand at
+/// `pos`, protected until it's up.
+pub fn spawn_zombie(
+    commands: &mut Commands,
+    assets: &ZombieAssets,
+    pos: Vec3,
+    zombie: Zombie,
+) -> Entity {
+    let entity = spawn_standing_zombie(commands, assets, pos, zombie);
+    commands.entity(entity).insert((
+        Transform::from_translation(pos - Vec3::Y * SPAWN_DEPTH),
+        SpawnProtection {
+            timer: Timer::from_seconds(SPAWN_RISE_TIME, TimerMode::Once),
+            target_y: pos.y,
+        },
+        ColliderDisabled,
+    ));
+    entity
+}
 
-    // Spawn 40 zombies around the edges of the map
-    let zombie_count = 40;
+/// Spawn a zombie already standing at `pos` and ready to fight, for restoring saves
+pub fn spawn_standing_zombie(
+    commands: &mut Commands,
+    assets: &ZombieAssets,
+    pos: Vec3,
+    zombie: Zombie,
+) -> Entity {
+    let kind = zombie.kind;
+    let zombie_entity = commands
+        .spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.health_materials[0].clone()),
+            Transform::from_translation(pos),
+            zombie,
+            ZombiePath::default(),
+            Team::Horde,
+            Shootable,
+            RigidBody::KinematicPositionBased,
+            Collider::capsule_y(0.6, 0.4),
+            PhysicsLayer::Enemies.groups(),
+            KinematicCharacterController {
+                filter_groups: Some(PhysicsLayer::Enemies.groups()),
+                ..default()
+            },
+        ))
+        .id();
 
-    for i in 0..zombie_count {
-        // Find a valid spawn position
-        let Some(pos) =
-            find_valid_spawn_position(&nav_grid, &spawned_positions, min_spacing, &mut rng, i)
-        else {
-            continue; // Skip if no valid position found
-        };
+    // Health bar background
+    commands.spawn((
+        Mesh3d(assets.health_bar_bg_mesh.clone()),
+        MeshMaterial3d(assets.health_bar_bg_material.clone()),
+        Transform::from_translation(pos + Vec3::Y * 1.5),
+        ZombieHealthBar,
+        ZombieChildOf(zombie_entity),
+    ));
 
-        spawned_positions.push(pos);
-        let path_offset = i as u32; // Distribute offsets evenly
-
-        let zombie_entity = commands
-            .spawn((
-                Mesh3d(zombie_mesh.clone()),
-                MeshMaterial3d(zombie_material.clone()),
-                Transform::from_translation(pos),
-                Zombie::new(path_offset),
-                ZombiePath::default(),
-                Shootable,
-                RigidBody::KinematicPositionBased,
-                Collider::capsule_y(0.6, 0.4),
-                KinematicCharacterController::default(),
-            ))
-            .id();
-
-        // Health bar background
-        commands.spawn((
-            Mesh3d(health_bar_bg_mesh.clone()),
-            MeshMaterial3d(health_bar_bg_material.clone()),
-            Transform::from_translation(pos + Vec3::Y * 1.5),
-            ZombieHealthBar,
-            ZombieChildOf(zombie_entity),
-        ));
+    // Health bar fill
+    commands.spawn((
+        Mesh3d(assets.health_bar_fill_mesh.clone()),
+        MeshMaterial3d(assets.health_bar_fill_material.clone()),
+        Transform::from_translation(pos + Vec3::Y * 1.5),
+        ZombieHealthBar,
+        ZombieHealthBarFill,
+        ZombieChildOf(zombie_entity),
+    ));
 
-        // Health bar fill
-        commands.spawn((
-            Mesh3d(health_bar_fill_mesh.clone()),
-            MeshMaterial3d(health_bar_fill_material.clone()),
-            Transform::from_translation(pos + Vec3::Y * 1.5),
-            ZombieHealthBar,
-            ZombieHealthBarFill,
-            ZombieChildOf(zombie_entity),
-        ));
-    }
+    commands.write_message(ZombieSpawned {
+        entity: zombie_entity,
+        position: pos,
+        kind,
+    });
+    zombie_entity
+}
+
+/// Spawn a zombie lying at `pos` playing dead (see DormantCorpse); it gets up facing
+/// `yaw`
+pub fn spawn_dormant_zombie(
+    commands: &mut Commands,
+    assets: &ZombieAssets,
+    pos: Vec3,
+    zombie: Zombie,
+    yaw: f32,
+) -> Entity {
+    let entity = spawn_standing_zombie(commands, assets, pos, zombie);
+    let facing = Quat::from_rotation_y(yaw);
+    commands.entity(entity).insert((
+        Transform::from_translation(pos.with_y(pos.y - ZOMBIE_HALF_HEIGHT + CORPSE_HEIGHT))
+            .with_rotation(facing * Quat::from_rotation_x(-FRAC_PI_2)),
+        DormantCorpse {
+            waking: None,
+            facing,
+            standing_y: pos.y,
+        },
+    ));
+    entity
 }
 
 fn increment_frame_counter(mut counter: ResMut<FrameCounter>) {
     counter.0 = counter.0.wrapping_add(1);
 }
 
+/// Throw a ring of dirt up around each zombie that starts rising
+fn puff_spawn_dirt(
+    mut commands: Commands,
+    assets: Res<ZombieAssets>,
+    zombies: Query<(&Transform, &SpawnProtection), Added<SpawnProtection>>,
+) {
+    for (transform, protection) in zombies.iter() {
+        let ground = transform.translation.with_y(protection.target_y - 0.9);
+        for i in 0..DIRT_PUFF_COUNT {
+            let angle = i as f32 / DIRT_PUFF_COUNT as f32 * TAU;
+            let outward = Vec3::new(angle.cos(), 0.0, angle.sin());
+            commands.spawn((
+                Mesh3d(assets.dirt_mesh.clone()),
+                MeshMaterial3d(assets.dirt_material.clone()),
+                Transform::from_translation(ground + outward * 0.4),
+                DirtPuff {
+                    velocity: outward * 1.5 + Vec3::Y * 2.5,
+                    lifetime: Timer::from_seconds(DIRT_PUFF_LIFETIME, TimerMode::Once),
+                },
+                Budgeted(BudgetCategory::Debris),
+            ));
+        }
+    }
+}
+
+/// Lift zombies out of the ground; they join the fight once fully up
+fn rise_spawning_zombies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut zombies: Query<(Entity, &mut Transform, &mut SpawnProtection)>,
+) {
+    for (entity, mut transform, mut protection) in zombies.iter_mut() {
+        protection.timer.tick(time.delta());
+        let risen = protection.timer.fraction();
+        transform.translation.y = protection.target_y - SPAWN_DEPTH * (1.0 - risen);
+        if protection.timer.is_finished() {
+            commands
+                .entity(entity)
+                .remove::<(SpawnProtection, ColliderDisabled)>();
+        }
+    }
+}
+
+fn fly_dirt_puffs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puffs: Query<(Entity, &mut Transform, &mut DirtPuff)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut transform, mut puff) in puffs.iter_mut() {
+        puff.lifetime.tick(time.delta());
+        if puff.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        puff.velocity.y -= 9.8 * dt;
+        transform.translation += puff.velocity * dt;
+        transform.scale = Vec3::splat(1.0 - puff.lifetime.fraction());
+    }
+}
+
+/// Every round that lands makes a small noise. It draws nobody in, but wakes anything
+/// playing dead next to it.
+fn shots_make_noise(
+    mut shots: MessageReader<ShotFired>,
+    mut noise_events: MessageWriter<NoiseEvent>,
+) {
+    for shot in shots.read() {
+        if let Some(point) = shot.point {
+            noise_events.write(NoiseEvent {
+                source: shot.shooter,
+                position: point,
+                radius: SHOT_NOISE_RADIUS,
+                max_listeners: 0,
+            });
+        }
+    }
+}
+
+/// Let the closest zombies in range latch onto a noise, up to its listener cap. Any
+/// corpse in range gets up instead.
+fn hear_noises(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut noise_events: MessageReader<NoiseEvent>,
+    mut zombies: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&mut Distracted>,
+            Option<&mut DormantCorpse>,
+        ),
+        With<Zombie>,
+    >,
+) {
+    // Forget noises that have gone quiet
+    for (entity, _, distracted, _) in zombies.iter_mut() {
+        if let Some(mut distracted) = distracted {
+            distracted.timer.tick(time.delta());
+            if distracted.timer.is_finished() {
+                commands.entity(entity).remove::<Distracted>();
+            }
+        }
+    }
+
+    for noise in noise_events.read() {
+        let mut listening = 0;
+        let mut candidates = Vec::new();
+        for (entity, transform, distracted, dormant) in zombies.iter_mut() {
+            if let Some(mut dormant) = dormant {
+                if transform.translation.distance(noise.position) <= noise.radius {
+                    dormant.wake();
+                }
+                continue;
+            }
+            match distracted {
+                Some(mut distracted) if distracted.source == noise.source => {
+                    distracted.position = noise.position;
+                    distracted.timer.reset();
+                    listening += 1;
+                }
+                Some(_) => {}
+                None => {
+                    let distance = transform.translation.distance(noise.position);
+                    if distance <= noise.radius {
+                        candidates.push((entity, distance));
+                    }
+                }
+            }
+        }
+
+        candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+        for (entity, _) in candidates
+            .into_iter()
+            .take(noise.max_listeners.saturating_sub(listening))
+        {
+            commands.entity(entity).insert(Distracted {
+                source: noise.source,
+                position: noise.position,
+                timer: Timer::from_seconds(1.5, TimerMode::Once),
+            });
+        }
+    }
+}
+
+/// Get corpses up once a survivor comes close, over DORMANT_RISE_TIME
+fn wake_dormant_zombies(
+    mut commands: Commands,
+    time: Res<Time>,
+    index: Res<SpatialIndex>,
+    mut corpses: Query<(Entity, &mut Transform, &mut DormantCorpse)>,
+) {
+    for (entity, mut transform, mut corpse) in corpses.iter_mut() {
+        if corpse.lying_still()
+            && index
+                .neighbors_within(transform.translation, DORMANT_WAKE_RANGE)
+                .any(|entry| entry.kind == SpatialKind::Survivor)
+        {
+            corpse.wake();
+        }
+        let risen = match corpse.waking.as_mut() {
+            Some(waking) => {
+                waking.tick(time.delta());
+                waking.fraction()
+            }
+            None => continue,
+        };
+
+        let lying_y = corpse.standing_y - ZOMBIE_HALF_HEIGHT + CORPSE_HEIGHT;
+        transform.translation.y = lying_y.lerp(corpse.standing_y, risen);
+        transform.rotation = corpse.facing * Quat::from_rotation_x(-FRAC_PI_2 * (1.0 - risen));
+        if risen >= 1.0 {
+            commands.entity(entity).remove::<DormantCorpse>();
+        }
+    }
+}
+
 fn update_zombie_paths(
     frame: Res<FrameCounter>,
     nav_grid: Res<NavGrid>,
-    player_query: Query<&Transform, With<Player>>,
-    mut zombies: Query<(&Transform, &Zombie, &mut ZombiePath)>,
+    squads: Res<ZombieSquads>,
+    team_query: Query<(Entity, &Transform, &Team)>,
+    mut zombies: Query<
+        (
+            Entity,
+            &Transform,
+            &Zombie,
+            &mut ZombiePath,
+            Option<&Distracted>,
+        ),
+        Without<DormantCorpse>,
+    >,
 ) {
-    let Ok(player_transform) = player_query.single() else {
-        return;
-    };
-
-    let player_pos = player_transform.translation;
+    let targets: Vec<(Entity, Vec3)> = team_query
+        .iter()
+        .filter(|(_, _, team)| **team == Team::Survivors)
+        .map(|(entity, transform, _)| (entity, transform.translation))
+        .collect();
     let current_frame = frame.0 % 20;
 
-    for (transform, zombie, mut path) in zombies.iter_mut() {
+    for (entity, transform, zombie, mut path, distracted) in zombies.iter_mut() {
         // Only update if this zombie's offset matches current frame
         if zombie.path_update_offset != current_frame {
             continue;
         }
 
-        // Find path to player
-        if let Some(new_path) = nav_grid.find_path(transform.translation, player_pos) {
+        // Head for whatever the zombie is listening to, otherwise its flank of the
+        // squad's survivor, otherwise the nearest survivor
+        let flank = match squads.role(entity) {
+            SquadRole::Flanker { target, side } => {
+                team_query.get(target).ok().map(|(_, target, _)| {
+                    flanking_goal(&nav_grid, transform.translation, target, side)
+                })
+            }
+            SquadRole::Chaser => None,
+        };
+        let goal = match (distracted, flank) {
+            (Some(distracted), _) => distracted.position,
+            (None, Some(flank)) => flank,
+            (None, None) => match nearest_target(&targets, transform.translation) {
+                Some((_, position)) => position,
+                None => continue,
+            },
+        };
+        let new_path = if zombie.can_climb {
+            nav_grid.find_climbing_path(transform.translation, goal)
+        } else {
+            nav_grid.find_path(transform.translation, goal)
+        };
+        if let Some(new_path) = new_path {
             path.waypoints = new_path;
             path.current_index = 0;
         }
@@ -238,17 +971,90 @@ fn update_zombie_paths(
 }
 
 fn move_zombies(
+    mut commands: Commands,
     time: Res<Time>,
-    mut zombies: Query<(
-        &mut Transform,
-        &Zombie,
-        &mut ZombiePath,
-        &mut KinematicCharacterController,
-    )>,
+    nav_grid: Res<NavGrid>,
+    mut zombies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &Zombie,
+            &mut ZombiePath,
+            &mut KinematicCharacterController,
+            Option<&KinematicCharacterControllerOutput>,
+            Has<Staggered>,
+            Option<&StatusEffects>,
+            Option<&Shoved>,
+            Option<&mut Scattering>,
+            Has<Crawling>,
+        ),
+        (
+            Without<SpawnProtection>,
+            Without<Climbing>,
+            Without<DormantCorpse>,
+        ),
+    >,
+    cover: Query<&Transform, (With<Climbable>, Without<Zombie>)>,
+    fuses: Query<(&Transform, &ExplosionFuse), Without<Zombie>>,
 ) {
-    for (mut transform, zombie, mut path, mut controller) in zombies.iter_mut() {
-        if path.waypoints.is_empty() || path.current_index >= path.waypoints.len() {
-            controller.translation = Some(Vec3::ZERO);
+    let fall = Vec3::NEG_Y * ZOMBIE_FALL_SPEED * time.delta_secs();
+
+    for (
+        entity,
+        mut transform,
+        zombie,
+        mut path,
+        mut controller,
+        output,
+        staggered,
+        status,
+        shoved,
+        scattering,
+        crawling,
+    ) in zombies.iter_mut()
+    {
+        if let Some(shoved) = shoved {
+            controller.translation = Some(shoved.velocity * time.delta_secs() + fall);
+            continue;
+        }
+
+        // A lit explosive close by beats the path: run straight away from it for a moment
+        if let Some(mut scattering) = scattering {
+            scattering.timer.tick(time.delta());
+            if scattering.timer.is_finished() {
+                commands.entity(entity).remove::<Scattering>();
+            } else if !staggered {
+                let mut speed = zombie.speed * SCATTER_SPEED_SCALE;
+                if crawling {
+                    speed *= CRAWL_SPEED_SCALE;
+                }
+                controller.translation =
+                    Some(scattering.direction * speed * time.delta_secs() + fall);
+                let target_rotation =
+                    Quat::from_rotation_y((-scattering.direction.x).atan2(-scattering.direction.z));
+                transform.rotation = transform
+                    .rotation
+                    .slerp(target_rotation, 10.0 * time.delta_secs());
+                continue;
+            }
+        } else if zombie.scatters && !staggered {
+            let fuse_offset = fuses
+                .iter()
+                .filter(|(_, fuse)| !fuse.detonated)
+                .map(|(fuse_transform, _)| {
+                    (transform.translation - fuse_transform.translation).with_y(0.0)
+                })
+                .find(|offset| offset.length() < SCATTER_RADIUS);
+            if let Some(offset) = fuse_offset {
+                commands.entity(entity).insert(Scattering {
+                    direction: offset.try_normalize().unwrap_or(*transform.back()),
+                    timer: Timer::from_seconds(SCATTER_TIME, TimerMode::Once),
+                });
+            }
+        }
+
+        if staggered || path.waypoints.is_empty() || path.current_index >= path.waypoints.len() {
+            controller.translation = Some(fall);
             continue;
         }
 
@@ -265,9 +1071,40 @@ fn move_zombies(
 
         // Move towards waypoint
         let move_dir = direction.normalize_or_zero();
-        let movement = move_dir * zombie.speed * time.delta_secs();
 
-        controller.translation = Some(movement);
+        // Up against cover the path goes over: climb on to it
+        let ahead = nav_grid
+            .world_to_grid(current_pos + move_dir * CLIMB_REACH)
+            .and_then(|(x, y)| nav_grid.climb_height(x, y));
+        let blocking_cover = output.and_then(|output| {
+            output
+                .collisions
+                .iter()
+                .find_map(|collision| cover.get(collision.entity).ok())
+        });
+        if let (Some(height), Some(cover_transform), true) =
+            (ahead, blocking_cover, zombie.can_climb)
+        {
+            let top = height + ZOMBIE_HALF_HEIGHT;
+            if current_pos.y < top - 0.25 {
+                // Land in the middle of the cover so it doesn't step straight off again
+                commands.entity(entity).insert(Climbing {
+                    from: current_pos,
+                    to: cover_transform.translation.with_y(top),
+                    timer: Timer::from_seconds(zombie.kind.climb_time(), TimerMode::Once),
+                });
+                controller.translation = None;
+                continue;
+            }
+        }
+
+        let mut speed = zombie.speed * status.map_or(1.0, |status| status.speed_scale());
+        if crawling {
+            speed *= CRAWL_SPEED_SCALE;
+        }
+        let movement = move_dir * speed * time.delta_secs();
+
+        controller.translation = Some(movement + fall);
 
         // Rotate to face movement direction
         if move_dir.length_squared() > 0.001 {
@@ -279,47 +1116,350 @@ fn move_zombies(
     }
 }
 
+/// Up the side first, then over the lip, so the climb doesn't cut through the cover's edge
+fn climb_zombies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut zombies: Query<(
+        Entity,
+        &mut Transform,
+        &mut Climbing,
+        &mut KinematicCharacterController,
+    )>,
+) {
+    for (entity, mut transform, mut climbing, mut controller) in zombies.iter_mut() {
+        climbing.timer.tick(time.delta());
+        let t = climbing.timer.fraction();
+        let rise = (t / 0.6).min(1.0);
+        let rise = rise * (2.0 - rise);
+        let forward = ((t - 0.4) / 0.6).clamp(0.0, 1.0);
+
+        let horizontal = climbing.from.lerp(climbing.to, forward);
+        transform.translation = horizontal.with_y(climbing.from.y.lerp(climbing.to.y, rise));
+        controller.translation = None;
+
+        if climbing.timer.is_finished() {
+            commands.entity(entity).remove::<Climbing>();
+        }
+    }
+}
+
+/// Reach of a bite, horizontally and vertically
+const ATTACK_RANGE: f32 = 1.5;
+
 fn zombie_attack(
     time: Res<Time>,
-    mut zombies: Query<(&Transform, &mut Zombie)>,
-    mut player_query: Query<(&Transform, &mut PlayerHealth), With<Player>>,
+    virtual_time: Res<Time<Virtual>>,
+    difficulty: Res<DifficultyModifiers>,
+    rapier_context: ReadRapierContext,
+    mut zombies: Query<
+        (Entity, &Transform, &mut Zombie, Has<Crawling>, Has<Boss>),
+        (
+            Without<SpawnProtection>,
+            Without<Climbing>,
+            Without<DormantCorpse>,
+        ),
+    >,
+    index: Res<SpatialIndex>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut PlayerHealth,
+            Option<&mut PlayerArmor>,
+            Option<&mut Flinch>,
+        ),
+        With<Player>,
+    >,
+    mut feedback: MessageWriter<FeedbackEvent>,
+    mut hit_events: MessageWriter<HitEvent>,
+    mut attacked_events: MessageWriter<ZombieAttacked>,
 ) {
-    let Ok((player_transform, mut player_health)) = player_query.single_mut() else {
+    let Ok(context) = rapier_context.single() else {
         return;
     };
+    let mut player = player_query.single_mut().ok();
 
-    let player_pos = player_transform.translation;
+    // The dead can't be hurt any further (the death camera is still running)
+    let dead_player = player
+        .as_ref()
+        .filter(|(_, _, health, ..)| health.current <= 0.0)
+        .map(|(entity, ..)| *entity);
 
-    for (zombie_transform, mut zombie) in zombies.iter_mut() {
+    for (zombie_entity, zombie_transform, mut zombie, crawling, boss) in zombies.iter_mut() {
         zombie.attack_cooldown.tick(time.delta());
+        if !zombie.attack_cooldown.is_finished() {
+            continue;
+        }
+
+        // Cheap distance check first; a zombie below a platform is in horizontal range
+        let Some(nearest) = index
+            .nearest_k(zombie_transform.translation, 1, |entry| {
+                entry.kind == SpatialKind::Survivor && Some(entry.entity) != dead_player
+            })
+            .pop()
+        else {
+            continue;
+        };
+        let (target, target_pos) = (nearest.entity, nearest.position);
+        let offset = target_pos - zombie_transform.translation;
+        let reach = if crawling {
+            CRAWL_ATTACK_RANGE
+        } else {
+            ATTACK_RANGE
+        };
+        if offset.with_y(0.0).length() >= reach || offset.y.abs() > ATTACK_RANGE {
+            continue;
+        }
 
-        let distance = (zombie_transform.translation - player_pos)
-            .with_y(0.0)
-            .length();
+        // Then the bite has to reach the target without passing through a wall
+        let chest = zombie_transform.translation + Vec3::Y * 0.4;
+        let to_target = target_pos - chest;
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(zombie_entity)
+            .exclude_sensors();
+        let mut reaches = false;
+        context.with_query_pipeline(filter, |query_pipeline| {
+            if let Some((hit, _)) = query_pipeline.cast_ray(
+                chest,
+                to_target.normalize_or_zero(),
+                to_target.length() + 0.5,
+                true,
+            ) {
+                reaches = hit == target;
+            }
+        });
+        if !reaches {
+            continue;
+        }
 
-        // Attack if close enough and cooldown finished
-        if distance < 1.5 && zombie.attack_cooldown.is_finished() {
-            player_health.current -= zombie.damage;
-            player_health.current = player_health.current.max(0.0);
-            zombie.attack_cooldown.reset();
+        match player.as_mut() {
+            // The player's bites go through armor and flinch
+            Some((player_entity, _, health, armor, flinch)) if *player_entity == target => {
+                let taken = apply_player_damage(
+                    health,
+                    armor.as_deref_mut(),
+                    flinch.as_deref_mut(),
+                    PlayerHit {
+                        damage: zombie.damage,
+                        flinch: zombie.flinch,
+                        source: if boss {
+                            PlayerDamageSource::Boss
+                        } else {
+                            PlayerDamageSource::Zombie
+                        },
+                        // Time is the fixed clock here; burst windows are in game time
+                        now: virtual_time.elapsed_secs_f64(),
+                    },
+                    &difficulty.burst,
+                );
+                if taken > 0.0 {
+                    feedback.write(FeedbackEvent::Damage { amount: taken });
+                }
+            }
+            // Anyone else on the team handles the bite as a hit
+            _ => {
+                hit_events.write(HitEvent {
+                    entity: target,
+                    damage: zombie.damage,
+                    direction: to_target.normalize_or_zero(),
+                    point: target_pos,
+                    normal: -to_target.normalize_or_zero(),
+                    distance: 0.0,
+                    source: Some(zombie_entity),
+                    zone: None,
+                    surface: SurfaceMaterial::default(),
+                    headshot: false,
+                    crit: false,
+                });
+            }
         }
+        attacked_events.write(ZombieAttacked {
+            zombie: zombie_entity,
+            target,
+        });
+        zombie.attack_cooldown.reset();
     }
 }
 
-fn handle_zombie_hits(mut hit_events: MessageReader<HitEvent>, mut zombies: Query<&mut Zombie>) {
+fn handle_zombie_hits(
+    mut commands: Commands,
+    mut hit_events: MessageReader<HitEvent>,
+    mut zombies: Query<
+        (
+            &mut Zombie,
+            &Transform,
+            Has<Climbing>,
+            Has<Crawling>,
+            Option<&Staggered>,
+            Option<&mut DormantCorpse>,
+        ),
+        Without<SpawnProtection>,
+    >,
+) {
     for event in hit_events.read() {
-        if let Ok(mut zombie) = zombies.get_mut(event.entity) {
-            zombie.health -= event.damage;
+        if let Ok((mut zombie, transform, climbing, crawling, staggered, dormant)) =
+            zombies.get_mut(event.entity)
+        {
+            // Shooting a corpse before it's up pays off, and gets it up
+            let damage = match dormant {
+                Some(mut dormant) => {
+                    dormant.wake();
+                    event.damage * DORMANT_DAMAGE_SCALE
+                }
+                None => event.damage,
+            };
+            zombie.health -= damage;
             zombie.health = zombie.health.max(0.0);
+            zombie.last_hit_by = event.source;
+
+            // Explosions report the zombie's centre as the hit point, so only shots
+            // can land on the head or the legs
+            zombie.last_hit_headshot = event.headshot;
+            let leg_hit = event.point.y - transform.translation.y < LEG_LINE * transform.scale.y;
+            if leg_hit && !crawling {
+                zombie.leg_damage += damage;
+                if zombie.leg_damage >= zombie.max_health * CRIPPLE_FRACTION {
+                    commands.entity(event.entity).insert(Crawling);
+                }
+            }
+
+            // A crawler is already down; only what's left of its legs can knock it about
+            // and no hit cuts a longer stagger short, like the boss reeling from a slam
+            let reeling =
+                staggered.is_some_and(|staggered| staggered.timer.remaining_secs() > STAGGER_TIME);
+            if damage >= STAGGER_DAMAGE && !climbing && (!crawling || leg_hit) && !reeling {
+                commands.entity(event.entity).insert(Staggered {
+                    timer: Timer::from_seconds(STAGGER_TIME, TimerMode::Once),
+                });
+            }
         }
     }
 }
 
+fn recover_from_stagger(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut zombies: Query<(Entity, &mut Staggered)>,
+    mut shoved: Query<(Entity, &mut Shoved)>,
+) {
+    for (entity, mut staggered) in zombies.iter_mut() {
+        staggered.timer.tick(time.delta());
+        if staggered.timer.is_finished() {
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+    for (entity, mut shove) in shoved.iter_mut() {
+        shove.timer.tick(time.delta());
+        if shove.timer.is_finished() {
+            commands.entity(entity).remove::<Shoved>();
+        }
+    }
+}
+
+/// Pick each zombie's resting material: the stagger tint, then fire, then the marked glow, else
+/// darker as health drops.
+/// A running hit flash restores to it once it ends.
+fn update_zombie_materials(
+    assets: Res<ZombieAssets>,
+    mut zombies: Query<(
+        &Zombie,
+        &mut MeshMaterial3d<StandardMaterial>,
+        Option<&mut HitFlash>,
+        Has<Staggered>,
+        Has<Marked>,
+        Option<&StatusEffects>,
+    )>,
+) {
+    for (zombie, mut material, flash, staggered, marked, status) in zombies.iter_mut() {
+        let resting = if staggered {
+            &assets.stagger_material
+        } else if status.is_some_and(|status| status.is_burning()) {
+            &assets.burning_material
+        } else if marked {
+            &assets.marked_material
+        } else {
+            let health = zombie.health / zombie.max_health;
+            match health {
+                h if h > 0.6 => &assets.health_materials[0],
+                h if h > 0.3 => &assets.health_materials[1],
+                _ => &assets.health_materials[2],
+            }
+        };
+
+        match flash {
+            Some(mut flash) => {
+                if flash.restore != *resting {
+                    flash.restore = resting.clone();
+                }
+            }
+            None => {
+                if material.0 != *resting {
+                    material.0 = resting.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Drop newly crippled zombies to the ground and mark their health bar
+fn start_crawling(
+    mut commands: Commands,
+    assets: Res<ZombieAssets>,
+    mut zombies: Query<(Entity, &mut Transform, &mut Zombie), Added<Crawling>>,
+) {
+    for (entity, mut transform, mut zombie) in zombies.iter_mut() {
+        transform.scale.y *= CRAWL_HEIGHT_SCALE;
+        zombie.can_climb = false;
+        commands.spawn((
+            Mesh3d(assets.crippled_icon_mesh.clone()),
+            MeshMaterial3d(assets.crippled_icon_material.clone()),
+            Transform::from_translation(transform.translation),
+            ZombieHealthBar,
+            ZombieCrippledIcon,
+            ZombieChildOf(entity),
+        ));
+    }
+}
+
+/// Put a shield mark by the health bar of zombies that take cover behind plates
+fn mark_shielded(
+    mut commands: Commands,
+    assets: Res<ZombieAssets>,
+    zombies: Query<(Entity, &Transform), Added<Shielded>>,
+) {
+    for (entity, transform) in zombies.iter() {
+        commands.spawn((
+            Mesh3d(assets.shield_icon_mesh.clone()),
+            MeshMaterial3d(assets.shield_icon_material.clone()),
+            Transform::from_translation(transform.translation),
+            ZombieHealthBar,
+            ZombieShieldIcon,
+            ZombieChildOf(entity),
+        ));
+    }
+}
+
 fn update_zombie_health_bars(
-    zombies: Query<(Entity, &Transform, &Zombie)>,
+    zombies: Query<(
+        Entity,
+        &Transform,
+        &Zombie,
+        Has<Crawling>,
+        Has<SpawnProtection>,
+        Option<&Shielded>,
+        Option<&DormantCorpse>,
+    )>,
     camera_query: Query<&Transform, With<Camera3d>>,
     mut health_bars: Query<
-        (&mut Transform, &ZombieChildOf, Option<&ZombieHealthBarFill>),
+        (
+            &mut Transform,
+            &mut Visibility,
+            &ZombieChildOf,
+            Option<&ZombieHealthBarFill>,
+            Has<ZombieCrippledIcon>,
+            Has<ZombieShieldIcon>,
+        ),
         (With<ZombieHealthBar>, Without<Zombie>, Without<Camera3d>),
     >,
 ) {
@@ -327,10 +1467,25 @@ fn update_zombie_health_bars(
         return;
     };
 
-    for (mut bar_transform, child_of, is_fill) in health_bars.iter_mut() {
-        if let Ok((_, zombie_transform, zombie)) = zombies.get(child_of.0) {
+    for (mut bar_transform, mut visibility, child_of, is_fill, is_icon, is_shield) in
+        health_bars.iter_mut()
+    {
+        if let Ok((_, zombie_transform, zombie, crawling, spawning, shielded, dormant)) =
+            zombies.get(child_of.0)
+        {
+            // No bar until the zombie is up out of the ground or done playing dead, and
+            // no shield mark once its plates stop doing most of the work
+            let lowered = is_shield && !shielded.is_some_and(|shielded| shielded.active);
+            let hidden = spawning || dormant.is_some_and(DormantCorpse::lying_still);
+            visibility.set_if_neq(if hidden || lowered {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            });
+
             // Position above zombie
-            bar_transform.translation = zombie_transform.translation + Vec3::Y * 1.8;
+            let height = if crawling { 1.1 } else { 1.8 };
+            bar_transform.translation = zombie_transform.translation + Vec3::Y * height;
 
             // Billboard effect
             let look_dir = camera_transform.translation - bar_transform.translation;
@@ -338,6 +1493,17 @@ fn update_zombie_health_bars(
                 bar_transform.look_to(-look_dir, Vec3::Y);
             }
 
+            // Crippled mark sits just left of the bar
+            if is_icon {
+                let left = bar_transform.left();
+                bar_transform.translation += left * 0.5;
+            }
+            // Shield mark just right of it
+            if is_shield {
+                let right = bar_transform.right();
+                bar_transform.translation += right * 0.5;
+            }
+
             // Scale fill bar based on health
             if is_fill.is_some() {
                 let health_percent = zombie.health / zombie.max_health;
@@ -347,13 +1513,30 @@ fn update_zombie_health_bars(
     }
 }
 
+/// Remove dead zombies; loot, score and feedback react to the ZombieDied message. Each
+/// leaves a corpse behind that lies there a while and then dissolves.
 fn despawn_dead_zombies(
     mut commands: Commands,
-    zombies: Query<(Entity, &Zombie)>,
+    assets: Res<ZombieAssets>,
+    zombies: Query<(
+        Entity,
+        &Transform,
+        &Zombie,
+        &MeshMaterial3d<StandardMaterial>,
+        Option<&HitFlash>,
+    )>,
     health_bars: Query<(Entity, &ZombieChildOf), With<ZombieHealthBar>>,
+    mut died_events: MessageWriter<ZombieDied>,
 ) {
-    for (entity, zombie) in zombies.iter() {
+    for (entity, transform, zombie, material, flash) in zombies.iter() {
         if zombie.health <= 0.0 {
+            died_events.write(ZombieDied {
+                position: transform.translation,
+                kind: zombie.kind,
+                killer: zombie.last_hit_by,
+                headshot: zombie.last_hit_headshot,
+            });
+
             // Despawn health bars first
             for (bar_entity, child_of) in health_bars.iter() {
                 if child_of.0 == entity {
@@ -361,31 +1544,83 @@ fn despawn_dead_zombies(
                 }
             }
             commands.entity(entity).despawn();
+
+            // Flat on the ground where it stood, in its own colours rather than the
+            // flash the killing shot left it in
+            let drop = (ZOMBIE_HALF_HEIGHT - CORPSE_HEIGHT) * transform.scale.y;
+            commands.spawn((
+                Mesh3d(assets.mesh.clone()),
+                MeshMaterial3d(flash.map_or(&material.0, |flash| &flash.restore).clone()),
+                Transform {
+                    translation: transform.translation - Vec3::Y * drop,
+                    rotation: transform.rotation * Quat::from_rotation_x(-FRAC_PI_2),
+                    scale: transform.scale,
+                },
+                ZombieCorpse,
+                Dissolving::after(CORPSE_LINGER),
+                Budgeted(BudgetCategory::Corpse),
+            ));
         }
     }
 }
 
+/// Remove every zombie and its health bars so a new run can start clean
+fn despawn_all_zombies(
+    mut commands: Commands,
+    zombies: Query<Entity, With<Zombie>>,
+    health_bars: Query<Entity, With<ZombieHealthBar>>,
+    leftovers: Query<Entity, Or<(With<DirtPuff>, With<ZombieCorpse>)>>,
+) {
+    for entity in zombies
+        .iter()
+        .chain(health_bars.iter())
+        .chain(leftovers.iter())
+    {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Remove the given zombies and their health bars outright: no death, so no loot, score
+/// or blood
+#[cfg(feature = "dev_console")]
+pub fn despawn_zombies(world: &mut World, zombies: &[Entity]) {
+    let bars: Vec<Entity> = world
+        .query_filtered::<(Entity, &ZombieChildOf), With<ZombieHealthBar>>()
+        .iter(world)
+        .filter(|(_, child_of)| zombies.contains(&child_of.0))
+        .map(|(bar, _)| bar)
+        .collect();
+    for entity in bars.into_iter().chain(zombies.iter().copied()) {
+        world.despawn(entity);
+    }
+}
+
 /// Separation behavior to prevent zombies from clustering
 fn separate_zombies(
     time: Res<Time>,
-    mut zombies: Query<(Entity, &Transform, &mut KinematicCharacterController), With<Zombie>>,
+    index: Res<SpatialIndex>,
+    mut zombies: Query<
+        (Entity, &Transform, &mut KinematicCharacterController),
+        (
+            With<Zombie>,
+            Without<SpawnProtection>,
+            Without<Climbing>,
+            Without<DormantCorpse>,
+        ),
+    >,
 ) {
-    // Collect all zombie positions first
-    let positions: Vec<(Entity, Vec3)> =
-        zombies.iter().map(|(e, t, _)| (e, t.translation)).collect();
-
     let separation_radius = 1.5;
     let separation_strength = 2.0;
 
     for (entity, transform, mut controller) in zombies.iter_mut() {
         let mut separation = Vec3::ZERO;
 
-        for (other_entity, other_pos) in &positions {
-            if *other_entity == entity {
+        for other in index.neighbors_within(transform.translation, separation_radius) {
+            if other.entity == entity || other.kind != SpatialKind::Zombie {
                 continue;
             }
 
-            let diff = transform.translation - *other_pos;
+            let diff = transform.translation - other.position;
             let dist = diff.with_y(0.0).length();
 
             if dist > 0.01 && dist < separation_radius {
@@ -407,3 +1642,34 @@ fn separate_zombies(
         }
     }
 }
+
+/// Caption stand-in for a growl when a zombie creeps up out of view
+fn zombie_growl_cues(
+    time: Res<Time>,
+    mut cooldown: ResMut<GrowlCooldown>,
+    player_q: Query<&Transform, With<Player>>,
+    index: Res<SpatialIndex>,
+    mut subtitles: MessageWriter<Subtitle>,
+) {
+    cooldown.0.tick(time.delta());
+    if !cooldown.0.is_finished() {
+        return;
+    }
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+
+    let forward = player_transform.forward().with_y(0.0).normalize_or_zero();
+    let behind = index
+        .neighbors_within(player_transform.translation, 6.0)
+        .filter(|entry| entry.kind == SpatialKind::Zombie)
+        .any(|entry| {
+            let offset = (entry.position - player_transform.translation).with_y(0.0);
+            offset.normalize_or_zero().dot(forward) < -0.3
+        });
+
+    if behind {
+        subtitles.write(Subtitle("[Zombie growl behind you]".to_string()));
+        cooldown.0.reset();
+    }
+}
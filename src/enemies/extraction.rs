@@ -0,0 +1,693 @@
+use super::{find_valid_spawn_position, spawn_zombie, WaveState, Zombie, ZombieAssets, ZombieKind};
+use crate::player::{FeedbackEvent, Player};
+use crate::ui::{
+    CompassObjective, DifficultyModifiers, GameMode, GameState, RunStats, TextRole, ThemedText,
+    UiTheme,
+};
+use crate::world::{BalanceData, GameRng, NavGrid};
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Extraction mode: survival up to EXTRACTION_WAVE, then an evac point opens in a far
+/// corner and the horde pours in from whichever edge is closest to the player. Standing
+/// in the zone for EXTRACT_TIME in total wins the run, ending it in Victory rather than
+/// GameOver.
+pub struct ExtractionPlugin;
+
+impl Plugin for ExtractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Extraction>()
+            .add_systems(Startup, setup_extraction_assets)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_extraction,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_extraction,
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                spawn_extraction_hud.run_if(resource_equals(GameMode::Extraction)),
+            )
+            .add_systems(OnExit(GameState::Playing), despawn_extraction_hud)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_extraction_results.run_if(resource_equals(GameMode::Extraction)),
+            )
+            .add_systems(OnExit(GameState::GameOver), despawn_extraction_results)
+            .add_systems(OnEnter(GameState::Victory), spawn_extraction_results)
+            .add_systems(OnExit(GameState::Victory), despawn_extraction_results)
+            .add_systems(
+                Update,
+                (
+                    activate_extraction,
+                    spawn_extraction_horde,
+                    track_extraction,
+                    update_extraction_hud,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_equals(GameMode::Extraction)),
+            );
+    }
+}
+
+/// Waves to survive before the evac point opens
+pub const EXTRACTION_WAVE: u32 = 5;
+/// Seconds in the zone, in total, to get out
+pub const EXTRACT_TIME: f32 = 10.0;
+const ZONE_RADIUS: f32 = 3.0;
+/// Zone progress stops for this long after the player is hurt
+const HURT_PAUSE: f32 = 1.0;
+/// How far from the centre the zone's corners sit, clear of the perimeter walls
+const CORNER: f32 = 42.0;
+/// Steps in towards the centre tried when a corner itself is blocked
+const CORNER_STEP: f32 = 3.0;
+const CORNER_TRIES: usize = 5;
+/// Seconds between horde zombies as the zone opens, and once fully ramped up
+const HORDE_START_INTERVAL: f32 = 2.0;
+const HORDE_MIN_INTERVAL: f32 = 0.4;
+/// Seconds the horde takes to ramp up to HORDE_MIN_INTERVAL
+const HORDE_RAMP_TIME: f32 = 60.0;
+/// Horde zombies stop coming while this many are up
+const HORDE_CAP: usize = 40;
+/// Horde zombies rise at least this far from the player, so none appear in their face
+const HORDE_MIN_DISTANCE: f32 = 12.0;
+
+/// Extraction progress for the run in progress
+#[derive(Resource, Default)]
+pub struct Extraction {
+    /// Centre of the evac point, once it's open
+    pub zone: Option<Vec3>,
+    /// Seconds spent in the zone so far
+    pub progress: f32,
+    /// Seconds the zone stays paused after the player was hurt
+    hurt_pause: f32,
+    /// Seconds since the zone opened, for the horde's ramp
+    elapsed: f32,
+    /// Seconds until the next horde zombie
+    next_spawn: f32,
+    pub extracted: bool,
+}
+
+impl Extraction {
+    pub fn is_active(&self) -> bool {
+        self.zone.is_some()
+    }
+
+    /// Open the evac point at `zone`; the horde starts straight away
+    pub fn open(&mut self, zone: Vec3) {
+        *self = Self {
+            zone: Some(zone),
+            ..default()
+        };
+    }
+
+    pub fn fraction(&self) -> f32 {
+        (self.progress / EXTRACT_TIME).clamp(0.0, 1.0)
+    }
+
+    /// Whether standing in the zone right now counts
+    pub fn paused(&self) -> bool {
+        self.hurt_pause > 0.0
+    }
+
+    /// Advance by `dt` with the player `inside` the zone or not, and `hurt` this frame.
+    /// Progress only builds inside and unhurt, and is kept on stepping out. Returns
+    /// true on the frame the player gets out.
+    pub fn tick(&mut self, dt: f32, inside: bool, hurt: bool) -> bool {
+        if !self.is_active() || self.extracted {
+            return false;
+        }
+        self.elapsed += dt;
+        self.hurt_pause = if hurt {
+            HURT_PAUSE
+        } else {
+            (self.hurt_pause - dt).max(0.0)
+        };
+        if inside && !self.paused() {
+            self.progress += dt;
+        }
+        self.extracted = self.progress >= EXTRACT_TIME;
+        self.extracted
+    }
+
+    /// Seconds between horde zombies right now, shrinking as the evac drags on
+    pub fn horde_interval(&self) -> f32 {
+        let ramp = (self.elapsed / HORDE_RAMP_TIME).clamp(0.0, 1.0);
+        HORDE_START_INTERVAL + (HORDE_MIN_INTERVAL - HORDE_START_INTERVAL) * ramp
+    }
+}
+
+/// Whether `position` is inside the zone; height doesn't matter
+fn in_zone(zone: Vec3, position: Vec3) -> bool {
+    (position - zone).with_y(0.0).length() <= ZONE_RADIUS
+}
+
+/// The corner furthest from `from` that can be walked to from there. Blocked corners
+/// are tried a few steps in towards the centre before being given up on.
+fn pick_extraction_zone(nav_grid: &NavGrid, from: Vec3) -> Option<Vec3> {
+    let mut corners = [
+        Vec3::new(-CORNER, 0.0, -CORNER),
+        Vec3::new(CORNER, 0.0, -CORNER),
+        Vec3::new(-CORNER, 0.0, CORNER),
+        Vec3::new(CORNER, 0.0, CORNER),
+    ];
+    corners.sort_by(|a, b| b.distance(from).total_cmp(&a.distance(from)));
+
+    corners.into_iter().find_map(|corner| {
+        let inward = -corner.normalize();
+        (0..CORNER_TRIES)
+            .map(|step| corner + inward * CORNER_STEP * step as f32)
+            .find(|spot| {
+                nav_grid
+                    .world_to_grid(*spot)
+                    .is_some_and(|(x, y)| nav_grid.is_walkable(x, y))
+                    && nav_grid.find_path(from, *spot).is_some()
+            })
+    })
+}
+
+#[derive(Resource)]
+struct ExtractionAssets {
+    pad_mesh: Handle<Mesh>,
+    pad_material: Handle<StandardMaterial>,
+    beam_mesh: Handle<Mesh>,
+    beam_material: Handle<StandardMaterial>,
+}
+
+/// Evac point: a glowing pad with a beacon beam and light
+#[derive(Component)]
+struct ExtractionZone;
+
+#[derive(Component)]
+struct ExtractionHud;
+
+#[derive(Component)]
+struct ExtractionText;
+
+#[derive(Component)]
+struct ExtractionBar;
+
+#[derive(Component)]
+struct ExtractionBarFill;
+
+#[derive(Component)]
+struct ExtractionResults;
+
+const BEAM_HEIGHT: f32 = 30.0;
+const BAR_WIDTH: f32 = 240.0;
+
+fn setup_extraction_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ExtractionAssets {
+        pad_mesh: meshes.add(Cylinder::new(ZONE_RADIUS, 0.05)),
+        pad_material: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.2, 1.0, 0.4, 0.35),
+            emissive: LinearRgba::rgb(0.2, 1.2, 0.4),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        beam_mesh: meshes.add(Cylinder::new(0.15, BEAM_HEIGHT)),
+        beam_material: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.3, 1.0, 0.5, 0.5),
+            emissive: LinearRgba::rgb(0.6, 3.0, 1.0),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn reset_extraction(
+    mut commands: Commands,
+    mut extraction: ResMut<Extraction>,
+    zones: Query<Entity, With<ExtractionZone>>,
+) {
+    *extraction = Extraction::default();
+    for entity in zones.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Open the evac point once the last wave before it has been cleared. Also picks a
+/// loaded run back up, since it goes by the wave rather than the clear message.
+fn activate_extraction(
+    mut commands: Commands,
+    assets: Res<ExtractionAssets>,
+    nav_grid: Res<NavGrid>,
+    mut waves: ResMut<WaveState>,
+    mut extraction: ResMut<Extraction>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    if extraction.is_active() || waves.wave < EXTRACTION_WAVE || waves.active {
+        return;
+    }
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+    let Some(zone) = pick_extraction_zone(&nav_grid, player_transform.translation) else {
+        // Try again next frame; the player may be standing somewhere off the grid
+        return;
+    };
+
+    extraction.open(zone);
+    // The horde is the wave now: the shop stays shut and no further waves start
+    waves.active = true;
+
+    commands
+        .spawn((
+            Mesh3d(assets.pad_mesh.clone()),
+            MeshMaterial3d(assets.pad_material.clone()),
+            Transform::from_translation(zone.with_y(0.03)),
+            ExtractionZone,
+            CompassObjective,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Mesh3d(assets.beam_mesh.clone()),
+                MeshMaterial3d(assets.beam_material.clone()),
+                Transform::from_xyz(0.0, BEAM_HEIGHT / 2.0, 0.0),
+            ));
+            parent.spawn((
+                PointLight {
+                    color: Color::srgb(0.3, 1.0, 0.5),
+                    intensity: 400_000.0,
+                    range: 15.0,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, 3.0, 0.0),
+            ));
+        });
+}
+
+/// Keep zombies coming, ever faster, from the edge spawn point nearest the player
+fn spawn_extraction_horde(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<ZombieAssets>,
+    nav_grid: Res<NavGrid>,
+    difficulty: Res<DifficultyModifiers>,
+    balance: Res<BalanceData>,
+    mut rng: ResMut<GameRng>,
+    mut extraction: ResMut<Extraction>,
+    player_q: Query<&Transform, With<Player>>,
+    zombies: Query<(), With<Zombie>>,
+) {
+    if !extraction.is_active() || extraction.extracted {
+        return;
+    }
+    extraction.next_spawn -= time.delta_secs();
+    if extraction.next_spawn > 0.0 || zombies.iter().count() >= HORDE_CAP {
+        return;
+    }
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+    let player = player_transform.translation;
+
+    // One candidate per map edge; the closest one not right on top of the player
+    let spawn = (0..4)
+        .filter_map(|quadrant| find_valid_spawn_position(&nav_grid, &[], 0.0, &mut **rng, quadrant))
+        .filter(|pos| pos.distance(player) >= HORDE_MIN_DISTANCE)
+        .min_by(|a, b| a.distance(player).total_cmp(&b.distance(player)));
+    let Some(spawn) = spawn else {
+        return;
+    };
+
+    extraction.next_spawn = extraction.horde_interval();
+    let zombie = Zombie::for_difficulty(
+        ZombieKind::Walker,
+        rng.random_range(0..20),
+        &difficulty,
+        &balance,
+    );
+    spawn_zombie(&mut commands, &assets, spawn, zombie);
+}
+
+fn track_extraction(
+    time: Res<Time>,
+    mut extraction: ResMut<Extraction>,
+    mut feedback: MessageReader<FeedbackEvent>,
+    player_q: Query<&Transform, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let hurt = feedback
+        .read()
+        .any(|event| matches!(event, FeedbackEvent::Damage { .. }));
+    let (Some(zone), Ok(player_transform)) = (extraction.zone, player_q.single()) else {
+        return;
+    };
+
+    let inside = in_zone(zone, player_transform.translation);
+    if extraction.tick(time.delta_secs(), inside, hurt) {
+        next_state.set(GameState::Victory);
+    }
+}
+
+fn spawn_extraction_hud(mut commands: Commands, theme: Res<UiTheme>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(50.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-BAR_WIDTH / 2.0 - 10.0)),
+                width: Val::Px(BAR_WIDTH + 20.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ExtractionHud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("EVAC AFTER WAVE {EXTRACTION_WAVE}")),
+                theme.text(TextRole::Caption),
+                ExtractionText,
+            ));
+
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(BAR_WIDTH),
+                        height: Val::Px(10.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                    Visibility::Hidden,
+                    ExtractionBar,
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.3, 1.0, 0.5)),
+                        ExtractionBarFill,
+                    ));
+                });
+        });
+}
+
+fn despawn_extraction_hud(mut commands: Commands, huds: Query<Entity, With<ExtractionHud>>) {
+    for entity in huds.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_extraction_hud(
+    extraction: Res<Extraction>,
+    player_q: Query<&Transform, With<Player>>,
+    mut text_q: Query<(&mut Text, &mut TextColor), With<ExtractionText>>,
+    mut bar_q: Query<&mut Visibility, With<ExtractionBar>>,
+    mut fill_q: Query<(&mut Node, &mut BackgroundColor), With<ExtractionBarFill>>,
+) {
+    let Some(zone) = extraction.zone else {
+        return;
+    };
+    let inside = player_q
+        .single()
+        .is_ok_and(|transform| in_zone(zone, transform.translation));
+
+    let (label, color) = if extraction.paused() {
+        ("HOLD ON", Color::srgb(1.0, 0.4, 0.3))
+    } else if inside {
+        ("EXTRACTING", Color::srgb(0.3, 1.0, 0.5))
+    } else {
+        ("REACH THE EVAC POINT", Color::WHITE)
+    };
+    for (mut text, mut text_color) in text_q.iter_mut() {
+        if **text != label {
+            **text = label.to_string();
+        }
+        text_color.0 = color;
+    }
+    for mut visibility in bar_q.iter_mut() {
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+    for (mut node, mut fill_color) in fill_q.iter_mut() {
+        node.width = Val::Percent(extraction.fraction() * 100.0);
+        *fill_color = BackgroundColor(if extraction.paused() {
+            Color::srgb(0.6, 0.6, 0.6)
+        } else {
+            Color::srgb(0.3, 1.0, 0.5)
+        });
+    }
+}
+
+fn spawn_extraction_results(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    extraction: Res<Extraction>,
+    waves: Res<WaveState>,
+    stats: Res<RunStats>,
+) {
+    let headline = if extraction.extracted {
+        format!("Got out after {:.0}s under siege", extraction.elapsed)
+    } else if extraction.is_active() {
+        format!("Fell {:.0}% of the way out", extraction.fraction() * 100.0)
+    } else {
+        format!("Fell on wave {} of {EXTRACTION_WAVE}", waves.wave)
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            GlobalZIndex(10),
+            ExtractionResults,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(headline),
+                theme.font(TextRole::Heading),
+                ThemedText(TextRole::Heading),
+                TextColor(Color::srgb(0.3, 1.0, 0.5)),
+            ));
+
+            parent.spawn((
+                Text::new(format!(
+                    "Kills: {}  Headshots: {}  Accuracy: {:.0}%",
+                    stats.kills,
+                    stats.headshots,
+                    stats.accuracy()
+                )),
+                theme.text(TextRole::Body),
+            ));
+        });
+}
+
+fn despawn_extraction_results(
+    mut commands: Commands,
+    results: Query<Entity, With<ExtractionResults>>,
+) {
+    for entity in results.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemies::Team;
+    use crate::player::PlayerHealth;
+    use crate::world::{boot, headless_app, SIMULATION_HZ};
+
+    fn open_zone() -> Extraction {
+        let mut extraction = Extraction::default();
+        extraction.open(Vec3::new(CORNER, 0.0, CORNER));
+        extraction
+    }
+
+    #[test]
+    fn progress_builds_inside_and_holds_outside() {
+        let mut extraction = open_zone();
+        extraction.tick(1.0, true, false);
+        assert_eq!(extraction.progress, 1.0);
+        extraction.tick(2.0, false, false);
+        assert_eq!(extraction.progress, 1.0);
+        extraction.tick(1.0, true, false);
+        assert_eq!(extraction.progress, 2.0);
+    }
+
+    #[test]
+    fn getting_hurt_pauses_the_zone() {
+        let mut extraction = open_zone();
+        extraction.tick(0.25, true, true);
+        assert!(extraction.paused());
+        for _ in 0..3 {
+            extraction.tick(0.25, true, false);
+        }
+        assert_eq!(extraction.progress, 0.0);
+        assert!(extraction.paused());
+
+        // The pause runs out a second after the hit
+        extraction.tick(0.25, true, false);
+        assert!(!extraction.paused());
+        assert_eq!(extraction.progress, 0.25);
+    }
+
+    #[test]
+    fn extract_time_inside_gets_out_once() {
+        let mut extraction = open_zone();
+        let steps = (EXTRACT_TIME / 0.25) as usize;
+        let out: Vec<bool> = (0..steps + 2)
+            .map(|_| extraction.tick(0.25, true, false))
+            .collect();
+        assert_eq!(out.iter().position(|&got_out| got_out), Some(steps - 1));
+        assert_eq!(out.iter().filter(|&&got_out| got_out).count(), 1);
+        assert!(extraction.extracted);
+        assert_eq!(extraction.fraction(), 1.0);
+    }
+
+    #[test]
+    fn a_closed_zone_does_nothing() {
+        let mut extraction = Extraction::default();
+        assert!(!extraction.tick(EXTRACT_TIME * 2.0, true, false));
+        assert_eq!(extraction.progress, 0.0);
+    }
+
+    #[test]
+    fn the_horde_ramps_up() {
+        let mut extraction = open_zone();
+        assert_eq!(extraction.horde_interval(), HORDE_START_INTERVAL);
+        extraction.tick(HORDE_RAMP_TIME / 2.0, false, false);
+        let halfway = (HORDE_START_INTERVAL + HORDE_MIN_INTERVAL) / 2.0;
+        assert!((extraction.horde_interval() - halfway).abs() < 1e-5);
+        extraction.tick(HORDE_RAMP_TIME, false, false);
+        assert!((extraction.horde_interval() - HORDE_MIN_INTERVAL).abs() < 1e-5);
+    }
+
+    #[test]
+    fn the_zone_opens_in_the_furthest_walkable_corner() {
+        let mut nav_grid = NavGrid::new(100, 100, 1.0);
+        let from = Vec3::new(-40.0, 0.0, -40.0);
+        assert_eq!(
+            pick_extraction_zone(&nav_grid, from),
+            Some(Vec3::new(CORNER, 0.0, CORNER))
+        );
+
+        // A crate on the corner moves the zone in along the diagonal
+        nav_grid.mark_obstacle_world(Vec3::new(CORNER, 0.0, CORNER), Vec3::splat(4.0));
+        let zone = pick_extraction_zone(&nav_grid, from).unwrap();
+        assert_eq!(zone.x, zone.z);
+        assert!(zone.x > CORNER - CORNER_STEP * CORNER_TRIES as f32 && zone.x < CORNER);
+    }
+
+    /// An Extraction run with the last wave before the evac just cleared and the player
+    /// standing at the centre
+    fn last_wave_cleared() -> (App, Entity) {
+        let mut app = headless_app(11);
+        app.insert_resource(GameMode::Extraction)
+            .add_plugins(ExtractionPlugin);
+        boot(&mut app);
+        let player = app
+            .world_mut()
+            .spawn((
+                Player::default(),
+                PlayerHealth::default(),
+                Team::Survivors,
+                Transform::from_xyz(0.0, 1.0, 0.0),
+            ))
+            .id();
+        *app.world_mut().resource_mut::<WaveState>() = WaveState {
+            wave: EXTRACTION_WAVE,
+            ..default()
+        };
+        (app, player)
+    }
+
+    fn state(app: &App) -> GameState {
+        *app.world().resource::<State<GameState>>().get()
+    }
+
+    #[test]
+    fn clearing_the_last_wave_opens_the_zone() {
+        let (mut app, _) = last_wave_cleared();
+        app.update();
+
+        let zone = app.world().resource::<Extraction>().zone.unwrap();
+        assert_eq!(zone.x.abs(), CORNER);
+        assert_eq!(zone.z.abs(), CORNER);
+        // No more waves or shop once the horde is on
+        assert!(app.world().resource::<WaveState>().active);
+        let mut zones = app.world_mut().query_filtered::<(), With<ExtractionZone>>();
+        assert_eq!(zones.iter(app.world()).count(), 1);
+    }
+
+    #[test]
+    fn standing_in_the_zone_wins_the_run() {
+        let (mut app, player) = last_wave_cleared();
+        app.update();
+        // Keep the horde off, so nothing hurts the player
+        let mut zombies = app.world_mut().query_filtered::<Entity, With<Zombie>>();
+        let risen: Vec<Entity> = zombies.iter(app.world()).collect();
+        for zombie in risen {
+            app.world_mut().despawn(zombie);
+        }
+        let zone = {
+            let mut extraction = app.world_mut().resource_mut::<Extraction>();
+            extraction.next_spawn = f32::INFINITY;
+            extraction.zone.unwrap()
+        };
+        app.world_mut()
+            .get_mut::<Transform>(player)
+            .unwrap()
+            .translation = zone.with_y(1.0);
+
+        let frames = (EXTRACT_TIME * SIMULATION_HZ as f32) as u32 + 8;
+        for _ in 0..frames {
+            app.update();
+            // A win is no death: nothing that hangs off GameOver may run
+            assert_ne!(state(&app), GameState::GameOver);
+            if state(&app) == GameState::Victory {
+                assert!(app.world().resource::<Extraction>().extracted);
+                return;
+            }
+        }
+        panic!("still in {:?} after {frames} frames", state(&app));
+    }
+
+    #[test]
+    fn the_horde_starts_as_the_zone_opens() {
+        let (mut app, player) = last_wave_cleared();
+        app.update();
+
+        let mut zombies = app.world_mut().query_filtered::<&Transform, With<Zombie>>();
+        let spawned: Vec<Vec3> = zombies
+            .iter(app.world())
+            .map(|transform| transform.translation)
+            .collect();
+        assert_eq!(spawned.len(), 1);
+        let position = app.world().get::<Transform>(player).unwrap().translation;
+        assert!((spawned[0] - position).with_y(0.0).length() >= HORDE_MIN_DISTANCE);
+        assert_eq!(
+            app.world().resource::<Extraction>().next_spawn,
+            HORDE_START_INTERVAL
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use super::{SpawnProtection, TargetHitEvent, Zombie};
+use crate::combat::{HitEvent, HitSystems};
+use crate::ui::{AccessibilitySettings, GameState};
+use bevy::prelude::*;
+
+/// Brief white flash on anything that takes a hit. Flashing swaps the entity over to
+/// one shared material and back, so zombies can keep sharing their materials too.
+pub struct HitFlashPlugin;
+
+impl Plugin for HitFlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_hit_flash_material)
+            .add_systems(
+                Update,
+                (flash_on_hit.in_set(HitSystems::Feedback), update_hit_flash)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+const HIT_FLASH_TIME: f32 = 0.1;
+
+#[derive(Component)]
+pub struct HitFlash {
+    pub timer: Timer,
+    /// Material to put back once the flash ends; owners may swap it while flashing
+    pub restore: Handle<StandardMaterial>,
+    /// Scale before the flash, so the pulse returns larger entities to their own size
+    base_scale: Option<Vec3>,
+}
+
+impl HitFlash {
+    pub fn new(restore: Handle<StandardMaterial>) -> Self {
+        Self {
+            timer: Timer::from_seconds(HIT_FLASH_TIME, TimerMode::Once),
+            restore,
+            base_scale: None,
+        }
+    }
+}
+
+/// Start a flash, or restart the one already running so its restore material is kept
+pub fn trigger_hit_flash(
+    commands: &mut Commands,
+    entity: Entity,
+    material: &MeshMaterial3d<StandardMaterial>,
+    flash: Option<Mut<HitFlash>>,
+) {
+    match flash {
+        Some(mut flash) => flash.timer.reset(),
+        None => {
+            commands
+                .entity(entity)
+                .insert(HitFlash::new(material.0.clone()));
+        }
+    }
+}
+
+/// Zombies flash on any hit once they're up out of the ground; targets only on hits
+/// their armor or weak point let through, which is what TargetHitEvent reports
+fn flash_on_hit(
+    mut commands: Commands,
+    mut hit_events: MessageReader<HitEvent>,
+    mut target_hits: MessageReader<TargetHitEvent>,
+    zombies: Query<(), (With<Zombie>, Without<SpawnProtection>)>,
+    mut flashable: Query<(&MeshMaterial3d<StandardMaterial>, Option<&mut HitFlash>)>,
+) {
+    let hit_zombies = hit_events
+        .read()
+        .map(|event| event.entity)
+        .filter(|entity| zombies.contains(*entity));
+    let hit_targets = target_hits.read().map(|event| event.target);
+    for entity in hit_zombies.chain(hit_targets) {
+        if let Ok((material, flash)) = flashable.get_mut(entity) {
+            trigger_hit_flash(&mut commands, entity, material, flash);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct HitFlashMaterial(Handle<StandardMaterial>);
+
+fn setup_hit_flash_material(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(HitFlashMaterial(materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        ..default()
+    })));
+}
+
+fn update_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    accessibility: Res<AccessibilitySettings>,
+    flash_material: Res<HitFlashMaterial>,
+    mut flash_query: Query<(
+        Entity,
+        &mut HitFlash,
+        &mut MeshMaterial3d<StandardMaterial>,
+        &mut Transform,
+    )>,
+) {
+    for (entity, mut flash, mut material, mut transform) in flash_query.iter_mut() {
+        flash.timer.tick(time.delta());
+        let base_scale = *flash.base_scale.get_or_insert(transform.scale);
+
+        let wanted = if flash.timer.is_finished() {
+            transform.scale = base_scale;
+            commands.entity(entity).remove::<HitFlash>();
+            &flash.restore
+        } else if accessibility.reduce_flashing {
+            // Gentle size pulse instead of a bright flash
+            let pulse = (flash.timer.fraction() * std::f32::consts::PI).sin();
+            transform.scale = base_scale * (1.0 + 0.08 * pulse);
+            &flash.restore
+        } else {
+            &flash_material.0
+        };
+
+        if material.0 != *wanted {
+            material.0 = wanted.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{BalanceData, SurfaceMaterial, SIMULATION_HZ};
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    const RED: Color = Color::srgb(0.8, 0.1, 0.1);
+
+    /// Hit flashes on their own, with a zombie in its own red material
+    fn flashing_zombie() -> (App, Entity, Handle<StandardMaterial>) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1.0 / SIMULATION_HZ,
+            )))
+            .insert_resource(AccessibilitySettings::default())
+            .add_message::<HitEvent>()
+            .add_message::<TargetHitEvent>()
+            .add_systems(Startup, setup_hit_flash_material)
+            .add_systems(Update, (flash_on_hit, update_hit_flash).chain());
+        app.update();
+
+        let original = app
+            .world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: RED,
+                ..default()
+            });
+        let zombie = Zombie::new(Default::default(), 0, &BalanceData::embedded());
+        let entity = app
+            .world_mut()
+            .spawn((
+                zombie,
+                MeshMaterial3d(original.clone()),
+                Transform::default(),
+            ))
+            .id();
+        (app, entity, original)
+    }
+
+    fn hit(app: &mut App, entity: Entity) {
+        app.world_mut().write_message(HitEvent {
+            entity,
+            damage: 1.0,
+            direction: Vec3::NEG_Z,
+            point: Vec3::ZERO,
+            normal: Vec3::Z,
+            distance: 5.0,
+            source: None,
+            zone: None,
+            surface: SurfaceMaterial::default(),
+            headshot: false,
+            crit: false,
+        });
+    }
+
+    fn settle(app: &mut App) {
+        let frames = (HIT_FLASH_TIME as f64 * SIMULATION_HZ).ceil() as usize + 2;
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+
+    fn assert_restored(app: &App, entity: Entity, original: &Handle<StandardMaterial>) {
+        let world = app.world();
+        assert!(world.get::<HitFlash>(entity).is_none());
+        let material = world
+            .get::<MeshMaterial3d<StandardMaterial>>(entity)
+            .unwrap();
+        assert_eq!(&material.0, original);
+        let materials = world.resource::<Assets<StandardMaterial>>();
+        assert_eq!(materials.get(original).unwrap().base_color, RED);
+    }
+
+    #[test]
+    fn hits_a_frame_apart_restore_the_original_material() {
+        let (mut app, entity, original) = flashing_zombie();
+        hit(&mut app, entity);
+        app.update();
+        let flashed = app
+            .world()
+            .get::<MeshMaterial3d<StandardMaterial>>(entity)
+            .unwrap();
+        assert_ne!(&flashed.0, &original);
+
+        // The second hit lands while the first flash is showing
+        hit(&mut app, entity);
+        app.update();
+        settle(&mut app);
+        assert_restored(&app, entity, &original);
+    }
+
+    #[test]
+    fn hits_in_the_same_frame_restore_the_original_material() {
+        let (mut app, entity, original) = flashing_zombie();
+        hit(&mut app, entity);
+        hit(&mut app, entity);
+        app.update();
+        settle(&mut app);
+        assert_restored(&app, entity, &original);
+    }
+}
@@ -1,5 +1,29 @@
+mod blood;
+mod director;
+mod dps_meter;
+mod drills;
 mod enemy;
+mod extraction;
+mod hit_flash;
+mod shooting_range;
+mod spawners;
+mod squad;
 mod target;
+#[cfg(test)]
+mod tests;
+mod wave_grade;
+mod waves;
 
+pub use blood::*;
+pub use director::*;
+pub use dps_meter::*;
+pub use drills::*;
 pub use enemy::*;
+pub use extraction::*;
+pub use hit_flash::*;
+pub use shooting_range::*;
+pub use spawners::*;
+pub use squad::*;
 pub use target::*;
+pub use wave_grade::*;
+pub use waves::*;
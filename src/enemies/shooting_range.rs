@@ -0,0 +1,481 @@
+use super::{
+    spawn_target, spawn_target_rig, Drill, Mover, MoverPath, PopupTarget, RangeReset,
+    RangeSettings, Target, TargetAssets, TargetSpawn,
+};
+use crate::combat::{HitEvent, HitSystems, ShotFired, WeaponInventory};
+use crate::player::Player;
+use crate::ui::{GameMode, GameState, TextRole, ThemedText, UiTheme};
+use bevy::prelude::*;
+
+pub struct ShootingRangePlugin;
+
+impl Plugin for ShootingRangePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RangeSession>()
+            .init_resource::<ImpactTrace>()
+            .add_systems(Startup, setup_impact_mark_assets)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                clear_impact_trace,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                clear_impact_trace,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                (reset_range_session, spawn_range_targets)
+                    .run_if(resource_equals(GameMode::ShootingRange)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                (reset_range_session, spawn_range_targets)
+                    .run_if(resource_equals(GameMode::ShootingRange)),
+            )
+            // PostUpdate so the lever's despawns in Update have already been applied
+            .add_systems(
+                PostUpdate,
+                spawn_range_targets
+                    .run_if(on_message::<RangeReset>)
+                    .run_if(resource_equals(GameMode::ShootingRange)),
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                spawn_range_hud.run_if(resource_equals(GameMode::ShootingRange)),
+            )
+            .add_systems(OnExit(GameState::Playing), despawn_range_hud)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_range_results.run_if(resource_equals(GameMode::ShootingRange)),
+            )
+            .add_systems(OnExit(GameState::GameOver), despawn_range_results)
+            .add_systems(
+                Update,
+                (
+                    count_range_shots,
+                    trace_impacts,
+                    tick_range_session,
+                    update_range_hud,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_equals(GameMode::ShootingRange)),
+            );
+    }
+}
+
+/// Points for range hits; part of HitFeedbackPlugins, as it only reads what landed
+pub struct RangeScoringPlugin;
+
+impl Plugin for RangeScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RangeSession>().add_systems(
+            Update,
+            score_range_hits
+                .in_set(HitSystems::Feedback)
+                .after(trace_impacts)
+                .before(tick_range_session)
+                .run_if(in_state(GameState::Playing))
+                .run_if(resource_equals(GameMode::ShootingRange)),
+        );
+    }
+}
+
+/// A timed target practice session
+#[derive(Resource)]
+pub struct RangeSession {
+    pub timer: Timer,
+    pub score: u32,
+    pub shots: u32,
+    pub hits: u32,
+}
+
+impl Default for RangeSession {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(60.0, TimerMode::Once),
+            score: 0,
+            shots: 0,
+            hits: 0,
+        }
+    }
+}
+
+impl RangeSession {
+    /// Percentage of fired rays that hit something shootable
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots as f32 * 100.0
+        }
+    }
+}
+
+/// Colours an impact trace steps through over a magazine, first round first
+const TRACE_STEPS: usize = 6;
+
+/// Impact points of the magazine being fired, so the recoil pattern can be read off
+/// the wall
+#[derive(Resource, Default)]
+struct ImpactTrace {
+    marks: Vec<Entity>,
+    slot: usize,
+    /// Rounds in the magazine before the trace's first shot
+    start_ammo: u32,
+    /// Rounds left after the latest traced shot
+    last_ammo: u32,
+}
+
+#[derive(Resource)]
+struct ImpactMarkAssets {
+    mesh: Handle<Mesh>,
+    materials: [Handle<StandardMaterial>; TRACE_STEPS],
+}
+
+#[derive(Component)]
+struct RangeHud;
+
+#[derive(Component)]
+struct RangeTimeText;
+
+#[derive(Component)]
+struct RangeScoreText;
+
+#[derive(Component)]
+struct RangeResults;
+
+fn reset_range_session(mut session: ResMut<RangeSession>) {
+    *session = RangeSession::default();
+}
+
+/// Targets riding the conveyor, spaced evenly along it
+const CONVEYOR_TARGETS: usize = 3;
+
+/// Moving and pop-up targets added on top of the static layout in range mode
+fn spawn_range_targets(
+    mut commands: Commands,
+    assets: Res<TargetAssets>,
+    settings: Res<RangeSettings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // A conveyor carrying a spaced-out row of targets, a faster lone slider, a pendulum
+    // swinging across the lane and a target circling a post
+    let conveyor = Mover::new(
+        MoverPath::Linear {
+            from: Vec3::new(-8.0, 1.0, -15.0),
+            to: Vec3::new(8.0, 1.0, -15.0),
+        },
+        settings.conveyor_speed,
+    );
+    let mut rigs: Vec<Mover> = (0..CONVEYOR_TARGETS)
+        .map(|i| {
+            conveyor
+                .clone()
+                .with_phase(i as f32 / CONVEYOR_TARGETS as f32)
+        })
+        .collect();
+    rigs.push(Mover::new(
+        MoverPath::Linear {
+            from: Vec3::new(6.0, 1.0, -22.0),
+            to: Vec3::new(-6.0, 1.0, -22.0),
+        },
+        5.0,
+    ));
+    rigs.push(Mover::new(
+        MoverPath::Pendulum {
+            pivot: Vec3::new(0.0, 7.0, -30.0),
+            length: 5.0,
+            arc: 0.6,
+        },
+        4.0,
+    ));
+    rigs.push(Mover::new(
+        MoverPath::Circular {
+            center: Vec3::new(0.0, 1.0, -38.0),
+            radius: 3.5,
+        },
+        3.0,
+    ));
+
+    for (i, mover) in rigs.into_iter().enumerate() {
+        // One frame per rig, not per target riding it
+        if i == 0 || i >= CONVEYOR_TARGETS {
+            spawn_target_rig(&mut commands, &assets, &mover);
+        }
+        spawn_target(
+            &mut commands,
+            &assets,
+            &mut materials,
+            TargetSpawn {
+                motion: Some(mover.clone()),
+                ..TargetSpawn::fixed(mover.position())
+            },
+        );
+    }
+
+    // Turrets that fight back, for practicing dodging
+    for position in [Vec3::new(-12.0, 1.0, -26.0), Vec3::new(12.0, 1.0, -26.0)] {
+        spawn_target(
+            &mut commands,
+            &assets,
+            &mut materials,
+            TargetSpawn {
+                turret: true,
+                ..TargetSpawn::fixed(position)
+            },
+        );
+    }
+
+    let popups = [
+        (Vec3::new(-4.0, 1.0, -11.0), 1.5),
+        (Vec3::new(0.0, 1.0, -18.0), 2.5),
+        (Vec3::new(4.0, 1.0, -11.0), 3.5),
+    ];
+
+    for (position, down_time) in popups {
+        spawn_target(
+            &mut commands,
+            &assets,
+            &mut materials,
+            TargetSpawn {
+                popup: Some(PopupTarget::new(position.y, down_time)),
+                ..TargetSpawn::fixed(position)
+            },
+        );
+    }
+}
+
+fn count_range_shots(mut shots: MessageReader<ShotFired>, mut session: ResMut<RangeSession>) {
+    for shot in shots.read() {
+        session.shots += 1;
+        if shot.hit {
+            session.hits += 1;
+        }
+    }
+}
+
+fn setup_impact_mark_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let first = Color::srgb(1.0, 0.95, 0.3);
+    let last = Color::srgb(1.0, 0.15, 0.1);
+    commands.insert_resource(ImpactMarkAssets {
+        mesh: meshes.add(Sphere::new(0.06)),
+        materials: std::array::from_fn(|step| {
+            materials.add(StandardMaterial {
+                base_color: first.mix(&last, step as f32 / (TRACE_STEPS - 1) as f32),
+                unlit: true,
+                ..default()
+            })
+        }),
+    });
+}
+
+/// Mark where each of the player's rounds landed, yellow through red over the
+/// magazine. A reloaded magazine or another weapon starts a new trace.
+fn trace_impacts(
+    mut commands: Commands,
+    mut shots: MessageReader<ShotFired>,
+    assets: Res<ImpactMarkAssets>,
+    mut trace: ResMut<ImpactTrace>,
+    player_q: Query<(Entity, &WeaponInventory), With<Player>>,
+) {
+    let Ok((player, inventory)) = player_q.single() else {
+        return;
+    };
+    let Some(weapon) = inventory.current_weapon() else {
+        return;
+    };
+
+    for shot in shots.read() {
+        let Some(point) = shot.point.filter(|_| shot.shooter == player) else {
+            continue;
+        };
+
+        // Pellets of one round leave the count where it was, so only a rise means a
+        // new magazine
+        if trace.marks.is_empty()
+            || inventory.current_slot != trace.slot
+            || weapon.current_ammo > trace.last_ammo
+        {
+            for mark in trace.marks.drain(..) {
+                commands.entity(mark).despawn();
+            }
+            trace.slot = inventory.current_slot;
+            trace.start_ammo = weapon.current_ammo + 1;
+        }
+        trace.last_ammo = weapon.current_ammo;
+
+        let round = trace.start_ammo.saturating_sub(weapon.current_ammo + 1) as usize;
+        let step = (round * TRACE_STEPS / trace.start_ammo.max(1) as usize).min(TRACE_STEPS - 1);
+        let mark = commands
+            .spawn((
+                Mesh3d(assets.mesh.clone()),
+                MeshMaterial3d(assets.materials[step].clone()),
+                Transform::from_translation(point),
+            ))
+            .id();
+        trace.marks.push(mark);
+    }
+}
+
+fn clear_impact_trace(mut commands: Commands, mut trace: ResMut<ImpactTrace>) {
+    for mark in trace.marks.drain(..) {
+        commands.entity(mark).despawn();
+    }
+    *trace = ImpactTrace::default();
+}
+
+/// Points per hit, weighted by distance to the target and how fast it moves
+fn score_range_hits(
+    mut hit_events: MessageReader<HitEvent>,
+    player_q: Query<&Transform, With<Player>>,
+    targets: Query<(&Transform, Option<&Mover>, Option<&PopupTarget>), With<Target>>,
+    mut session: ResMut<RangeSession>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+
+    for event in hit_events.read() {
+        let Ok((target_transform, motion, popup)) = targets.get(event.entity) else {
+            continue;
+        };
+
+        let distance = player_transform
+            .translation
+            .distance(target_transform.translation);
+        // Pop-ups are only exposed briefly, so they count as fast targets
+        let speed = motion.map(|m| m.speed).unwrap_or(0.0) + popup.map(|_| 2.0).unwrap_or(0.0);
+
+        let points = 10.0 * (1.0 + distance / 10.0) * (1.0 + speed / 2.0);
+        session.score += points.round() as u32;
+    }
+}
+
+/// The session clock holds while a drill is on, which keeps its own time
+fn tick_range_session(
+    time: Res<Time>,
+    drill: Res<Drill>,
+    mut session: ResMut<RangeSession>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if drill.is_running() {
+        return;
+    }
+    session.timer.tick(time.delta());
+    if session.timer.is_finished() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn spawn_range_hud(mut commands: Commands, theme: Res<UiTheme>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-100.0)),
+                width: Val::Px(200.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            RangeHud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("60"),
+                theme.text(TextRole::Heading),
+                RangeTimeText,
+            ));
+
+            parent.spawn((
+                Text::new("SCORE 0"),
+                theme.font(TextRole::Body),
+                ThemedText(TextRole::Body),
+                TextColor(theme.accent),
+                RangeScoreText,
+            ));
+        });
+}
+
+fn despawn_range_hud(mut commands: Commands, hud_query: Query<Entity, With<RangeHud>>) {
+    for entity in hud_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_range_hud(
+    session: Res<RangeSession>,
+    mut time_query: Query<&mut Text, (With<RangeTimeText>, Without<RangeScoreText>)>,
+    mut score_query: Query<&mut Text, (With<RangeScoreText>, Without<RangeTimeText>)>,
+) {
+    for mut text in time_query.iter_mut() {
+        **text = format!("{:.0}", session.timer.remaining_secs().ceil());
+    }
+
+    for mut text in score_query.iter_mut() {
+        **text = format!("SCORE {}", session.score);
+    }
+}
+
+fn spawn_range_results(mut commands: Commands, theme: Res<UiTheme>, session: Res<RangeSession>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            GlobalZIndex(10),
+            RangeResults,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("Score: {}", session.score)),
+                theme.font(TextRole::Title),
+                ThemedText(TextRole::Title),
+                TextColor(theme.accent),
+            ));
+
+            parent.spawn((
+                Text::new(format!(
+                    "Accuracy: {:.0}%  ({} / {})",
+                    session.accuracy(),
+                    session.hits,
+                    session.shots
+                )),
+                theme.text(TextRole::Body),
+            ));
+        });
+}
+
+fn despawn_range_results(mut commands: Commands, results: Query<Entity, With<RangeResults>>) {
+    for entity in results.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,296 @@
+use super::{
+    find_valid_spawn_position, spawn_dormant_zombie, spawn_zombie, Director, WaveCleared, Zombie,
+    ZombieAssets, ZombieKind,
+};
+use crate::combat::{HitEvent, HitSystems, Shootable};
+use crate::ui::{DifficultyModifiers, GameState};
+use crate::world::{BalanceData, GameRng, NavGrid, PhysicsLayer};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// Glowing portals the wave system places around the arena edge. Each one releases
+/// its share of the wave a zombie at a time; shooting it down stops the rest.
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PortalDestroyed>()
+            .add_systems(Startup, setup_portal_assets)
+            .add_systems(
+                Update,
+                (
+                    damage_portals.in_set(HitSystems::Damage),
+                    emit_zombies,
+                    spin_portals,
+                    close_portals_after_wave,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_portals,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_portals,
+            );
+    }
+}
+
+const PORTAL_HEALTH: f32 = 150.0;
+const PORTAL_RADIUS: f32 = 1.2;
+/// Ring centre height above the floor
+const PORTAL_HEIGHT: f32 = 1.4;
+/// Seconds between zombies from one portal
+const PORTAL_INTERVAL: f32 = 1.5;
+/// Portals stand at least this far apart
+const PORTAL_SPACING: f32 = 12.0;
+/// Zombies step out this far in front of the ring
+const EMERGE_OFFSET: f32 = 1.5;
+/// How far out in front of the ring zombies playing dead are laid, nearest to furthest
+const CORPSE_SCATTER: (f32, f32) = (4.0, 12.0);
+
+/// Zombie portal; `remaining` is what's left of its share of the wave
+#[derive(Component)]
+pub struct Spawner {
+    pub interval: Timer,
+    pub remaining: u32,
+    pub health: f32,
+    /// Zombies released so far, to spread their path updates
+    emitted: u32,
+}
+
+/// Sent when a portal is shot down before it has emptied
+#[derive(Message)]
+pub struct PortalDestroyed {
+    pub destroyed_by: Option<Entity>,
+}
+
+#[derive(Resource)]
+pub struct PortalAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_portal_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(PortalAssets {
+        mesh: meshes.add(Torus::new(PORTAL_RADIUS - 0.15, PORTAL_RADIUS)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.5, 0.1, 0.6),
+            emissive: LinearRgba::rgb(2.0, 0.4, 2.5),
+            ..default()
+        }),
+    });
+}
+
+/// Number of portals opened for a wave
+fn portal_count(wave: u32) -> u32 {
+    (2 + wave / 3).min(4)
+}
+
+/// Open this wave's portals, splitting `budget` zombies between them. Each portal
+/// sits on walkable ground, checked across the whole ring footprint on the NavGrid.
+pub fn open_portals(
+    commands: &mut Commands,
+    assets: &PortalAssets,
+    nav_grid: &NavGrid,
+    rng: &mut GameRng,
+    wave: u32,
+    budget: u32,
+) {
+    let mut positions: Vec<Vec3> = Vec::new();
+    for quadrant in 0..portal_count(wave) as i32 {
+        for _ in 0..10 {
+            let Some(pos) = find_valid_spawn_position(
+                nav_grid,
+                &positions,
+                PORTAL_SPACING,
+                &mut **rng,
+                quadrant,
+            ) else {
+                break;
+            };
+            if footprint_walkable(nav_grid, pos) {
+                positions.push(pos);
+                break;
+            }
+        }
+    }
+
+    let count = positions.len() as u32;
+    if count == 0 {
+        warn!("No walkable ground for wave {} portals", wave);
+        return;
+    }
+    for (index, pos) in positions.into_iter().enumerate() {
+        // Earlier portals take the remainder
+        let share = budget / count + u32::from((index as u32) < budget % count);
+        // Face the arena centre so zombies step out towards the fight
+        let facing = Transform::from_translation(pos.with_y(PORTAL_HEIGHT))
+            .looking_at(Vec3::new(0.0, PORTAL_HEIGHT, 0.0), Vec3::Y);
+        commands.spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            facing,
+            Spawner {
+                interval: Timer::from_seconds(PORTAL_INTERVAL, TimerMode::Repeating),
+                remaining: share,
+                health: PORTAL_HEALTH,
+                emitted: 0,
+            },
+            Shootable,
+            RigidBody::Fixed,
+            Collider::cuboid(PORTAL_RADIUS, PORTAL_RADIUS, 0.15),
+            PhysicsLayer::Enemies.groups(),
+        ));
+    }
+}
+
+/// The portal and the spot zombies step out onto must both be walkable
+fn footprint_walkable(nav_grid: &NavGrid, pos: Vec3) -> bool {
+    let to_centre = (-pos).with_y(0.0).normalize_or_zero();
+    [
+        Vec3::ZERO,
+        Vec3::X * PORTAL_RADIUS,
+        Vec3::NEG_X * PORTAL_RADIUS,
+        Vec3::Z * PORTAL_RADIUS,
+        Vec3::NEG_Z * PORTAL_RADIUS,
+        to_centre * EMERGE_OFFSET,
+    ]
+    .iter()
+    .all(|offset| {
+        nav_grid
+            .world_to_grid(pos + *offset)
+            .is_some_and(|(x, y)| nav_grid.is_walkable(x, y))
+    })
+}
+
+fn damage_portals(
+    mut commands: Commands,
+    mut hit_events: MessageReader<HitEvent>,
+    mut portals: Query<&mut Spawner>,
+    mut destroyed_events: MessageWriter<PortalDestroyed>,
+) {
+    for event in hit_events.read() {
+        let Ok(mut spawner) = portals.get_mut(event.entity) else {
+            continue;
+        };
+        // Several hits can land on the same frame; only the first kill counts
+        if spawner.health <= 0.0 {
+            continue;
+        }
+        spawner.health -= event.damage;
+        if spawner.health <= 0.0 {
+            commands.entity(event.entity).despawn();
+            destroyed_events.write(PortalDestroyed {
+                destroyed_by: event.source,
+            });
+        }
+    }
+}
+
+/// Release the next zombie from each portal that's due. Some, by the difficulty's
+/// dormant_chance, are laid out further in playing dead instead of stepping out. The
+/// Director sets the pace and may send some out as runners.
+fn emit_zombies(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<ZombieAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    balance: Res<BalanceData>,
+    director: Res<Director>,
+    nav_grid: Res<NavGrid>,
+    mut rng: ResMut<GameRng>,
+    mut portals: Query<(&Transform, &mut Spawner)>,
+) {
+    let pace = time
+        .delta()
+        .div_f32(director.spawn_interval(&balance.director));
+    let runner_share = director.runner_share(&balance.director);
+    for (transform, mut spawner) in portals.iter_mut() {
+        if spawner.remaining == 0 || spawner.health <= 0.0 {
+            continue;
+        }
+        spawner.interval.tick(pace);
+        if !spawner.interval.just_finished() {
+            continue;
+        }
+
+        let ground = (transform.translation + transform.forward() * EMERGE_OFFSET).with_y(1.0);
+        // No draw unless runners are on, so runs without the Director keep their numbers
+        let kind = if runner_share > 0.0 && rng.random::<f32>() < runner_share {
+            ZombieKind::Runner
+        } else {
+            ZombieKind::Walker
+        };
+        let zombie = Zombie::for_difficulty(kind, spawner.emitted, &difficulty, &balance);
+        let playing_dead = rng.random::<f32>() < difficulty.dormant_chance;
+        let spot = playing_dead
+            .then(|| corpse_spot(&nav_grid, &mut rng, transform))
+            .flatten();
+        match spot {
+            Some(spot) => {
+                let yaw = rng.random_range(0.0..TAU);
+                spawn_dormant_zombie(&mut commands, &assets, spot, zombie, yaw);
+            }
+            None => {
+                spawn_zombie(&mut commands, &assets, ground, zombie);
+            }
+        }
+        spawner.remaining -= 1;
+        spawner.emitted += 1;
+    }
+}
+
+/// Walkable ground somewhere out in front of `portal` to lay a corpse on
+fn corpse_spot(nav_grid: &NavGrid, rng: &mut GameRng, portal: &Transform) -> Option<Vec3> {
+    (0..10).find_map(|_| {
+        let angle = rng.random_range(-0.8..0.8);
+        let direction = Quat::from_rotation_y(angle) * portal.forward().with_y(0.0);
+        let distance = rng.random_range(CORPSE_SCATTER.0..CORPSE_SCATTER.1);
+        let spot = (portal.translation + direction * distance).with_y(1.0);
+        nav_grid
+            .world_to_grid(spot)
+            .is_some_and(|(x, y)| nav_grid.is_walkable(x, y))
+            .then_some(spot)
+    })
+}
+
+fn spin_portals(time: Res<Time>, mut portals: Query<&mut Transform, With<Spawner>>) {
+    for mut transform in portals.iter_mut() {
+        transform.rotate_local_z(0.8 * time.delta_secs());
+    }
+}
+
+/// Emptied portals close once the wave is over; the next wave opens fresh ones
+fn close_portals_after_wave(
+    mut commands: Commands,
+    mut cleared_events: MessageReader<WaveCleared>,
+    portals: Query<Entity, With<Spawner>>,
+) {
+    if cleared_events.read().last().is_none() {
+        return;
+    }
+    for entity in portals.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn despawn_portals(mut commands: Commands, portals: Query<Entity, With<Spawner>>) {
+    for entity in portals.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,216 @@
+use super::{Crawling, Distracted, DormantCorpse, SpawnProtection, Team, Zombie, ZombieSystems};
+use crate::ui::GameState;
+use crate::world::NavGrid;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Pack tactics: once three or more zombies are after the same survivor, one or two of
+/// them break off to come at it from the side instead of queueing up behind the rest.
+/// Roles are handed out once a second; update_zombie_paths reads them.
+pub struct SquadPlugin;
+
+impl Plugin for SquadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ZombieSquads>()
+            .add_systems(
+                FixedUpdate,
+                assign_squad_roles
+                    .before(ZombieSystems::Pathing)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_squads,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_squads,
+            );
+    }
+}
+
+/// Zombies on one survivor before any of them flank
+const SQUAD_SIZE: usize = 3;
+/// Zombies on one survivor before a second one flanks, from the other side
+const DOUBLE_FLANK_SIZE: usize = 6;
+/// How far to the side of the survivor flankers aim for
+const FLANK_DISTANCE: f32 = 8.0;
+/// Closer fallbacks tried when the full flank point is blocked
+const FLANK_FALLBACKS: [f32; 2] = [6.0, 4.0];
+/// Once a flanker is within this angle of its side it closes straight in
+const FLANK_CONVERGE_ANGLE: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SquadRole {
+    /// Straight at the nearest survivor
+    Chaser,
+    /// Round to `side` (1.0 right, -1.0 left) of `target`'s facing, then in from there
+    Flanker { target: Entity, side: f32 },
+}
+
+/// Role of every zombie in a squad; anyone missing is a Chaser
+#[derive(Resource)]
+pub struct ZombieSquads {
+    timer: Timer,
+    roles: HashMap<Entity, SquadRole>,
+}
+
+impl Default for ZombieSquads {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            roles: HashMap::new(),
+        }
+    }
+}
+
+impl ZombieSquads {
+    pub fn role(&self, zombie: Entity) -> SquadRole {
+        self.roles
+            .get(&zombie)
+            .copied()
+            .unwrap_or(SquadRole::Chaser)
+    }
+}
+
+/// Where a flanker on `side` of a survivor at `position` facing `facing` heads for: a
+/// quarter turn round from where it's looking, pulled in closer if that spot is
+/// blocked. None when nowhere on that side is walkable.
+pub fn flank_point(nav_grid: &NavGrid, position: Vec3, facing: Vec3, side: f32) -> Option<Vec3> {
+    let facing = facing.with_y(0.0).normalize_or(Vec3::NEG_Z);
+    let direction = facing.cross(Vec3::Y) * side;
+    std::iter::once(FLANK_DISTANCE)
+        .chain(FLANK_FALLBACKS)
+        .map(|distance| position + direction * distance)
+        .find(|point| {
+            nav_grid
+                .world_to_grid(*point)
+                .is_some_and(|(x, y)| nav_grid.is_walkable(x, y))
+        })
+}
+
+/// Goal for a flanker at `from`: the flank point until it's come round to that side,
+/// then the survivor itself. Falls back to the survivor when the side is blocked.
+pub fn flanking_goal(nav_grid: &NavGrid, from: Vec3, target: &Transform, side: f32) -> Vec3 {
+    let position = target.translation;
+    let Some(point) = flank_point(nav_grid, position, *target.forward(), side) else {
+        return position;
+    };
+    let to_zombie = (from - position).with_y(0.0);
+    let to_flank = (point - position).with_y(0.0);
+    if to_zombie.angle_between(to_flank) < FLANK_CONVERGE_ANGLE {
+        position
+    } else {
+        point
+    }
+}
+
+/// Group the zombies by the survivor nearest them and pick flankers from any group big
+/// enough. Flankers keep the job while their group lasts, so they don't turn back
+/// halfway round; new ones are the stragglers furthest back.
+fn assign_squad_roles(
+    time: Res<Time>,
+    mut squads: ResMut<ZombieSquads>,
+    survivors: Query<(Entity, &Transform, &Team)>,
+    zombies: Query<
+        (Entity, &Transform),
+        (
+            With<Zombie>,
+            Without<Distracted>,
+            Without<Crawling>,
+            Without<SpawnProtection>,
+            Without<DormantCorpse>,
+        ),
+    >,
+) {
+    squads.timer.tick(time.delta());
+    if !squads.timer.just_finished() {
+        return;
+    }
+
+    let survivors: Vec<(Entity, &Transform)> = survivors
+        .iter()
+        .filter(|(_, _, team)| **team == Team::Survivors)
+        .map(|(entity, transform, _)| (entity, transform))
+        .collect();
+    let mut squads_by_target: HashMap<Entity, Vec<(Entity, Vec3)>> = HashMap::new();
+    for (zombie, transform) in zombies.iter() {
+        let from = transform.translation;
+        let nearest = survivors.iter().min_by(|(_, a), (_, b)| {
+            a.translation
+                .distance_squared(from)
+                .total_cmp(&b.translation.distance_squared(from))
+        });
+        if let Some((target, _)) = nearest {
+            squads_by_target
+                .entry(*target)
+                .or_default()
+                .push((zombie, from));
+        }
+    }
+
+    let previous = std::mem::take(&mut squads.roles);
+    for (target, mut members) in squads_by_target {
+        if members.len() < SQUAD_SIZE {
+            continue;
+        }
+        let Some((_, target_transform)) = survivors.iter().find(|(entity, _)| *entity == target)
+        else {
+            continue;
+        };
+        let position = target_transform.translation;
+        let right = target_transform.forward().cross(Vec3::Y);
+        let flankers = if members.len() >= DOUBLE_FLANK_SIZE {
+            2
+        } else {
+            1
+        };
+
+        // Sitting flankers of this target first, then whoever is furthest away
+        let was_flanking = |zombie: &Entity| {
+            previous.get(zombie).is_some_and(
+                |role| matches!(role, SquadRole::Flanker { target: t, .. } if *t == target),
+            )
+        };
+        members.sort_by(|(a, a_pos), (b, b_pos)| {
+            was_flanking(b).cmp(&was_flanking(a)).then(
+                b_pos
+                    .distance_squared(position)
+                    .total_cmp(&a_pos.distance_squared(position)),
+            )
+        });
+
+        let mut taken_side: Option<f32> = None;
+        for (zombie, from) in members.into_iter().take(flankers) {
+            // Keep a sitting flanker's side; otherwise take the side it's already on,
+            // and a second flanker takes the other one
+            let side = match previous.get(&zombie) {
+                Some(SquadRole::Flanker { target: t, side }) if *t == target => *side,
+                _ => match taken_side {
+                    Some(side) => -side,
+                    None if (from - position).dot(right) < 0.0 => -1.0,
+                    None => 1.0,
+                },
+            };
+            let side = if taken_side == Some(side) {
+                -side
+            } else {
+                side
+            };
+            taken_side = Some(side);
+            squads
+                .roles
+                .insert(zombie, SquadRole::Flanker { target, side });
+        }
+    }
+}
+
+fn reset_squads(mut squads: ResMut<ZombieSquads>) {
+    *squads = ZombieSquads::default();
+}
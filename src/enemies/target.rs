@@ -1,21 +1,86 @@
-use crate::combat::{HitEvent, Shootable};
+use super::{DpsMeter, DrillTarget, HitFlash, Zombie};
+use crate::combat::{HitEvent, HitSystems, HitZone, Knockback, Shootable};
+use crate::player::{
+    apply_player_damage, CameraShake, FeedbackEvent, Flinch, Player, PlayerActions, PlayerArmor,
+    PlayerDamageSource, PlayerHealth, PlayerHit, PlayerPerks, EXPLOSION_FLINCH, TURRET_FLINCH,
+};
+use crate::ui::{ColorPalette, DifficultyModifiers, GameMode, GameState};
+use crate::world::{
+    BudgetCategory, Budgeted, Dissolving, Interpolated, PhysicsLayer, SurfaceMaterial,
+    FIRING_LANE_START,
+};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use std::f32::consts::TAU;
 
 pub struct TargetPlugin;
 
 impl Plugin for TargetPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_targets).add_systems(
-            Update,
-            (
-                handle_target_hits,
-                update_health_bars,
-                update_hit_flash,
-                despawn_dead_targets,
-                billboard_health_bars,
-            ),
-        );
+        app.init_resource::<PendingTargetRespawns>()
+            .init_resource::<RangeSettings>()
+            .add_message::<RangeReset>()
+            .add_message::<TargetHitEvent>()
+            .add_message::<TargetDestroyed>()
+            .add_message::<ClearTargets>()
+            .add_systems(
+                Startup,
+                (setup_target_assets, spawn_targets, spawn_range_lever).chain(),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                (apply_range_settings, despawn_turret_projectiles),
+            )
+            .add_systems(
+                Update,
+                (
+                    (handle_target_hits, despawn_dead_targets).in_set(HitSystems::Damage),
+                    update_health_bars,
+                    billboard_health_bars,
+                    burn_explosion_fuses,
+                    update_shockwaves,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_target_palette.run_if(resource_changed::<ColorPalette>),
+            )
+            .add_systems(
+                Update,
+                (
+                    move_targets,
+                    update_popup_targets,
+                    respawn_targets,
+                    update_turrets,
+                    use_range_lever,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                move_turret_projectiles.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (despawn_all_targets, spawn_targets)
+                    .chain()
+                    .after(use_range_lever)
+                    .run_if(on_message::<RangeReset>),
+            )
+            .add_systems(Update, clear_targets.run_if(on_message::<ClearTargets>))
+            .add_systems(OnExit(GameState::Playing), hide_range_lever_prompt)
+            .add_systems(OnEnter(GameState::GameOver), despawn_turret_projectiles)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                (despawn_all_targets, spawn_targets).chain(),
+            );
     }
 }
 
@@ -23,6 +88,8 @@ impl Plugin for TargetPlugin {
 pub struct Target {
     pub max_health: f32,
     pub current_health: f32,
+    /// Direction of the most recent shot, so the killing blow can scatter the debris
+    pub last_hit_direction: Vec3,
 }
 
 impl Target {
@@ -30,32 +97,382 @@ impl Target {
         Self {
             max_health: health,
             current_health: health,
+            last_hit_direction: Vec3::ZERO,
         }
     }
 }
 
+/// How a target reacts to incoming damage
+#[derive(Component, Clone, Copy, PartialEq, Debug, Default)]
+pub enum TargetKind {
+    #[default]
+    Standard,
+    /// Shrugs off hits below the threshold and halves everything else
+    Armored { threshold: f32 },
+    /// Only takes damage through the weak point on its face, at 3x
+    WeakPoint,
+    /// Blows up shortly after dying, damaging everything shootable nearby
+    Explosive,
+    /// Training dummy: takes every hit at face value and never goes down (see DpsMeter)
+    Dummy,
+}
+
+/// Lit fuse on a dead explosive target; it stays in the world until it detonates
 #[derive(Component)]
-pub struct HealthBar;
+pub struct ExplosionFuse {
+    pub timer: Timer,
+    pub detonated: bool,
+}
 
+/// Expanding, fading sphere left behind by an explosion
 #[derive(Component)]
-pub struct HealthBarBackground;
+struct Shockwave {
+    timer: Timer,
+    radius: f32,
+}
+
+pub const EXPLOSION_RADIUS: f32 = 5.0;
+const EXPLOSION_DAMAGE: f32 = 80.0;
+const EXPLOSION_KNOCKBACK: f32 = 12.0;
 
+/// Small shootable sphere on a weak-point target's face
 #[derive(Component)]
-pub struct HealthBarFill;
+pub struct WeakPoint {
+    pub owner: Entity,
+}
 
+/// Piece of a destroyed target. Deliberately not Shootable and never registered
+/// with the NavGrid, it just tumbles and dissolves away.
 #[derive(Component)]
-pub struct HitFlash {
+pub struct TargetFragment;
+
+/// Seconds debris tumbles before it starts to dissolve
+const FRAGMENT_LINGER: f32 = 2.5;
+
+/// Carries a target round a path at a steady pace, for the range's moving rigs
+#[derive(Component, Clone)]
+pub struct Mover {
+    pub path: MoverPath,
+    /// Units per second along the path
+    pub speed: f32,
+    /// How far round the path the target is, as a fraction of one full cycle
+    pub phase: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoverPath {
+    /// Back and forth along a straight line, like a conveyor
+    Linear { from: Vec3, to: Vec3 },
+    /// Round a level circle
+    Circular { center: Vec3, radius: f32 },
+    /// Hanging `length` below `pivot`, swinging `arc` radians either side across the lane
+    Pendulum { pivot: Vec3, length: f32, arc: f32 },
+}
+
+impl Mover {
+    pub fn new(path: MoverPath, speed: f32) -> Self {
+        Self {
+            path,
+            speed,
+            phase: 0.0,
+        }
+    }
+
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase.rem_euclid(1.0);
+        self
+    }
+
+    /// Where this mover will be after `seconds` more
+    pub fn ahead(&self, seconds: f32) -> Self {
+        let cycle = self.cycle_length().max(f32::EPSILON);
+        self.clone()
+            .with_phase(self.phase + self.speed * seconds / cycle)
+    }
+
+    /// Distance travelled over one full cycle
+    pub fn cycle_length(&self) -> f32 {
+        match self.path {
+            MoverPath::Linear { from, to } => 2.0 * from.distance(to),
+            MoverPath::Circular { radius, .. } => TAU * radius,
+            MoverPath::Pendulum { length, arc, .. } => 4.0 * arc * length,
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        match self.path {
+            MoverPath::Linear { from, to } => from.lerp(to, 1.0 - (2.0 * self.phase - 1.0).abs()),
+            MoverPath::Circular { center, radius } => {
+                let angle = TAU * self.phase;
+                center + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius
+            }
+            MoverPath::Pendulum { pivot, length, .. } => {
+                pivot + self.rotation() * Vec3::NEG_Y * length
+            }
+        }
+    }
+
+    /// Tilt of a pendulum target, so it hangs along its rod
+    pub fn rotation(&self) -> Quat {
+        match self.path {
+            MoverPath::Pendulum { arc, .. } => {
+                Quat::from_rotation_z(arc * (TAU * self.phase).sin())
+            }
+            _ => Quat::IDENTITY,
+        }
+    }
+}
+
+/// Pops up out of the floor, stays up for a while, then drops back down
+#[derive(Component, Clone)]
+pub struct PopupTarget {
+    pub raised_y: f32,
+    pub lowered_y: f32,
+    pub up_time: f32,
+    pub down_time: f32,
+    pub raised: bool,
     pub timer: Timer,
-    pub original_color: Color,
 }
 
+impl PopupTarget {
+    pub fn new(raised_y: f32, down_time: f32) -> Self {
+        Self {
+            raised_y,
+            lowered_y: raised_y - 2.5,
+            up_time: 2.0,
+            down_time,
+            raised: false,
+            timer: Timer::from_seconds(down_time, TimerMode::Once),
+        }
+    }
+}
+
+/// Aggressive target that tracks the player and lobs slow projectiles to dodge
+#[derive(Component)]
+pub struct TurretTarget {
+    pub fire_timer: Timer,
+    pub range: f32,
+    /// Seconds before each shot during which the turret pulses as a warning
+    pub telegraph_time: f32,
+}
+
+impl Default for TurretTarget {
+    fn default() -> Self {
+        Self {
+            fire_timer: Timer::from_seconds(2.0, TimerMode::Once),
+            range: 30.0,
+            telegraph_time: 0.5,
+        }
+    }
+}
+
+/// Visible barrel child that rotates towards the player
+#[derive(Component)]
+struct TurretBarrel;
+
+/// Turret round in flight, moved and collided in move_turret_projectiles
+#[derive(Component)]
+#[require(Interpolated)]
+pub struct TurretProjectile {
+    pub velocity: Vec3,
+    pub damage: f32,
+    pub source: Entity,
+    pub lifetime: Timer,
+}
+
+/// Everything needed to put a target back where it was, used by the respawn path
+#[derive(Component, Clone)]
+pub struct TargetSpawn {
+    pub position: Vec3,
+    pub health: f32,
+    pub motion: Option<Mover>,
+    pub popup: Option<PopupTarget>,
+    pub turret: bool,
+    pub kind: TargetKind,
+}
+
+impl TargetSpawn {
+    pub fn fixed(position: Vec3) -> Self {
+        Self {
+            position,
+            health: 100.0,
+            motion: None,
+            popup: None,
+            turret: false,
+            kind: TargetKind::Standard,
+        }
+    }
+}
+
+/// Controls whether destroyed targets come back on their own, per game mode
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct RangeSettings {
+    pub auto_respawn: bool,
+    pub respawn_delay: f32,
+    /// Units per second the range's conveyor carries its targets
+    pub conveyor_speed: f32,
+}
+
+impl Default for RangeSettings {
+    fn default() -> Self {
+        Self {
+            auto_respawn: true,
+            respawn_delay: 10.0,
+            conveyor_speed: 3.0,
+        }
+    }
+}
+
+/// Sent for every hit that actually damaged a target, for shot feedback
+#[derive(Message)]
+pub struct TargetHitEvent {
+    pub target: Entity,
+    pub damage: f32,
+    pub point: Vec3,
+    pub killed: bool,
+}
+
+/// Sent when a target breaks apart (explosives once they detonate)
+#[derive(Message)]
+pub struct TargetDestroyed {
+    pub entity: Entity,
+    // The kill feed and drills only go by the entity
+    #[allow(dead_code)]
+    pub position: Vec3,
+    #[allow(dead_code)]
+    pub kind: TargetKind,
+}
+
+/// Sent when the player pulls the range lever to restore every target
+#[derive(Message)]
+pub struct RangeReset;
+
+/// Take these targets down quietly, health bars and all, with no debris or respawn
+#[derive(Message)]
+pub struct ClearTargets(pub Vec<Entity>);
+
+#[derive(Component)]
+struct RangeLever;
+
+#[derive(Component)]
+struct RangeLeverPrompt;
+
+const RANGE_LEVER_POSITION: Vec3 = Vec3::new(2.5, 0.0, -2.5);
+/// Where the training dummy stands, away from the lever so E means one thing
+pub const DUMMY_POSITION: Vec3 = Vec3::new(-8.0, 1.0, 2.0);
+const RANGE_LEVER_REACH: f32 = 2.0;
+
+/// Targets waiting to come back after being destroyed
+#[derive(Resource, Default)]
+pub struct PendingTargetRespawns(pub Vec<(Timer, TargetSpawn)>);
+
+/// Shared meshes and materials for targets and their health bars
+#[derive(Resource)]
+pub struct TargetAssets {
+    target_mesh: Handle<Mesh>,
+    health_bar_bg_mesh: Handle<Mesh>,
+    health_bar_fill_mesh: Handle<Mesh>,
+    health_bar_bg_material: Handle<StandardMaterial>,
+    health_bar_fill_material: Handle<StandardMaterial>,
+    armored_fill_material: Handle<StandardMaterial>,
+    weak_point_fill_material: Handle<StandardMaterial>,
+    weak_point_mesh: Handle<Mesh>,
+    weak_point_material: Handle<StandardMaterial>,
+    explosive_mesh: Handle<Mesh>,
+    shockwave_mesh: Handle<Mesh>,
+    barrel_mesh: Handle<Mesh>,
+    barrel_material: Handle<StandardMaterial>,
+    projectile_mesh: Handle<Mesh>,
+    projectile_material: Handle<StandardMaterial>,
+    fragment_mesh: Handle<Mesh>,
+    rig_rod_mesh: Handle<Mesh>,
+    rig_beam_mesh: Handle<Mesh>,
+    rig_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct HealthBar;
+
+#[derive(Component)]
+pub struct HealthBarBackground;
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
 #[derive(Component)]
 struct ChildOf(Entity);
 
-fn spawn_targets(
+/// Fixed frame a Mover runs on: a conveyor's rail or a pendulum's crossbar. Stays put
+/// while its targets are destroyed and respawned.
+#[derive(Component)]
+pub struct TargetRig;
+
+fn setup_target_assets(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    palette: Res<ColorPalette>,
+) {
+    commands.insert_resource(TargetAssets {
+        target_mesh: meshes.add(Cuboid::new(1.5, 2.0, 1.5)),
+        health_bar_bg_mesh: meshes.add(Cuboid::new(1.2, 0.15, 0.05)),
+        health_bar_fill_mesh: meshes.add(Cuboid::new(1.1, 0.1, 0.06)),
+        health_bar_bg_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.2, 0.2),
+            unlit: true,
+            ..default()
+        }),
+        health_bar_fill_material: materials.add(StandardMaterial {
+            base_color: palette.target_health,
+            unlit: true,
+            ..default()
+        }),
+        armored_fill_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.6, 0.7, 0.8),
+            unlit: true,
+            ..default()
+        }),
+        weak_point_fill_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.6, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        weak_point_mesh: meshes.add(Sphere::new(0.25)),
+        explosive_mesh: meshes.add(Cylinder::new(0.75, 2.0)),
+        shockwave_mesh: meshes.add(Sphere::new(1.0)),
+        weak_point_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.9, 0.2),
+            emissive: LinearRgba::rgb(2.0, 1.6, 0.2),
+            ..default()
+        }),
+        barrel_mesh: meshes.add(Cuboid::new(0.25, 0.25, 1.2)),
+        barrel_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.15, 0.15, 0.18),
+            ..default()
+        }),
+        projectile_mesh: meshes.add(Sphere::new(0.2)),
+        fragment_mesh: meshes.add(Cuboid::new(0.7, 0.62, 0.7)),
+        rig_rod_mesh: meshes.add(Cylinder::new(0.04, 1.0)),
+        rig_beam_mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        rig_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.35, 0.38),
+            metallic: 0.8,
+            perceptual_roughness: 0.4,
+            ..default()
+        }),
+        projectile_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.5, 0.1),
+            emissive: LinearRgba::rgb(4.0, 1.5, 0.2),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_targets(
+    mut commands: Commands,
+    assets: Res<TargetAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     let target_positions = [
         Vec3::new(5.0, 1.0, 0.0),
@@ -65,108 +482,313 @@ fn spawn_targets(
         Vec3::new(-4.0, 1.0, -4.0),
     ];
 
-    let target_mesh = meshes.add(Cuboid::new(1.5, 2.0, 1.5));
+    for pos in target_positions {
+        spawn_target(
+            &mut commands,
+            &assets,
+            &mut materials,
+            TargetSpawn::fixed(pos),
+        );
+    }
+
+    // One of each special variant for eyeballing weapon balance
+    spawn_target(
+        &mut commands,
+        &assets,
+        &mut materials,
+        TargetSpawn {
+            kind: TargetKind::Armored { threshold: 20.0 },
+            ..TargetSpawn::fixed(Vec3::new(8.0, 1.0, -6.0))
+        },
+    );
+    spawn_target(
+        &mut commands,
+        &assets,
+        &mut materials,
+        TargetSpawn {
+            kind: TargetKind::WeakPoint,
+            ..TargetSpawn::fixed(Vec3::new(-8.0, 1.0, -6.0))
+        },
+    );
 
-    let health_bar_bg_mesh = meshes.add(Cuboid::new(1.2, 0.15, 0.05));
-    let health_bar_fill_mesh = meshes.add(Cuboid::new(1.1, 0.1, 0.06));
-    let health_bar_bg_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.2, 0.2, 0.2),
-        unlit: true,
-        ..default()
-    });
-    let health_bar_fill_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.1, 0.8, 0.1),
-        unlit: true,
-        ..default()
-    });
+    spawn_target(
+        &mut commands,
+        &assets,
+        &mut materials,
+        TargetSpawn {
+            kind: TargetKind::Dummy,
+            ..TargetSpawn::fixed(DUMMY_POSITION)
+        },
+    );
 
-    for pos in target_positions {
-        // Create a unique material for each target so they can flash independently
-        let target_material = materials.add(StandardMaterial {
+    // Distance markers down the long-range lane
+    for distance in [25.0, 50.0, 75.0, 100.0] {
+        spawn_target(
+            &mut commands,
+            &assets,
+            &mut materials,
+            TargetSpawn::fixed(FIRING_LANE_START + Vec3::new(0.0, 1.0, -distance)),
+        );
+    }
+
+    // Adjacent explosives to show off chain reactions
+    for pos in [Vec3::new(-1.5, 1.0, -10.0), Vec3::new(1.5, 1.0, -10.0)] {
+        spawn_target(
+            &mut commands,
+            &assets,
+            &mut materials,
+            TargetSpawn {
+                kind: TargetKind::Explosive,
+                health: 40.0,
+                ..TargetSpawn::fixed(pos)
+            },
+        );
+    }
+}
+
+/// Spawn a single target with its health bars from a spawn description
+pub fn spawn_target(
+    commands: &mut Commands,
+    assets: &TargetAssets,
+    materials: &mut Assets<StandardMaterial>,
+    spawn: TargetSpawn,
+) -> Entity {
+    // Create a unique material for each target so they can flash independently
+    let target_material = materials.add(match spawn.kind {
+        TargetKind::Armored { .. } => StandardMaterial {
+            base_color: Color::srgb(0.45, 0.47, 0.5),
+            metallic: 0.9,
+            perceptual_roughness: 0.3,
+            ..default()
+        },
+        TargetKind::WeakPoint => StandardMaterial {
+            base_color: Color::srgb(0.4, 0.2, 0.6),
+            ..default()
+        },
+        TargetKind::Explosive => StandardMaterial {
+            base_color: Color::srgb(0.95, 0.45, 0.05),
+            ..default()
+        },
+        TargetKind::Dummy => StandardMaterial {
+            base_color: Color::srgb(0.7, 0.6, 0.4),
+            perceptual_roughness: 0.9,
+            ..default()
+        },
+        TargetKind::Standard if spawn.turret => StandardMaterial {
+            base_color: Color::srgb(0.3, 0.35, 0.45),
+            ..default()
+        },
+        TargetKind::Standard => StandardMaterial {
             base_color: Color::srgb(0.8, 0.2, 0.2),
             ..default()
-        });
+        },
+    });
+    let fill_material = match spawn.kind {
+        TargetKind::Standard | TargetKind::Explosive | TargetKind::Dummy => {
+            assets.health_bar_fill_material.clone()
+        }
+        TargetKind::Armored { .. } => assets.armored_fill_material.clone(),
+        TargetKind::WeakPoint => assets.weak_point_fill_material.clone(),
+    };
 
-        let target_entity = commands
-            .spawn((
-                Mesh3d(target_mesh.clone()),
-                MeshMaterial3d(target_material),
-                Transform::from_translation(pos),
-                Target::new(100.0),
-                Shootable, // Can be shot by the generic shooting system
-                // Rapier physics components
-                RigidBody::Fixed,
-                Collider::cuboid(0.75, 1.0, 0.75),
-            ))
-            .id();
-
-        // Health bar background
-        commands.spawn((
-            Mesh3d(health_bar_bg_mesh.clone()),
-            MeshMaterial3d(health_bar_bg_material.clone()),
-            Transform::from_translation(pos + Vec3::Y * 1.5),
-            HealthBar,
-            HealthBarBackground,
-            ChildOf(target_entity),
-        ));
+    // Moving targets are kinematic so raycasts track their current position
+    let rigid_body = if spawn.motion.is_some() || spawn.popup.is_some() {
+        RigidBody::KinematicPositionBased
+    } else {
+        RigidBody::Fixed
+    };
 
-        // Health bar fill
-        commands.spawn((
-            Mesh3d(health_bar_fill_mesh.clone()),
-            MeshMaterial3d(health_bar_fill_material.clone()),
-            Transform::from_translation(pos + Vec3::Y * 1.5),
-            HealthBar,
-            HealthBarFill,
-            ChildOf(target_entity),
-        ));
+    let (start, rotation) = match (&spawn.popup, &spawn.motion) {
+        (Some(popup), _) => (spawn.position.with_y(popup.lowered_y), Quat::IDENTITY),
+        (None, Some(motion)) => (motion.position(), motion.rotation()),
+        (None, None) => (spawn.position, Quat::IDENTITY),
+    };
+
+    let (mesh, collider) = match spawn.kind {
+        TargetKind::Explosive => (assets.explosive_mesh.clone(), Collider::cylinder(1.0, 0.75)),
+        _ => (
+            assets.target_mesh.clone(),
+            Collider::cuboid(0.75, 1.0, 0.75),
+        ),
+    };
+
+    let mut target = commands.spawn((
+        Mesh3d(mesh),
+        MeshMaterial3d(target_material),
+        Transform::from_translation(start).with_rotation(rotation),
+        Target::new(spawn.health),
+        spawn.kind,
+        Shootable, // Can be shot by the generic shooting system
+        // Rapier physics components
+        rigid_body,
+        collider,
+        PhysicsLayer::Enemies.groups(),
+    ));
+    if let Some(motion) = spawn.motion.clone() {
+        // Pendulum targets hang from a rod up to the pivot
+        if let MoverPath::Pendulum { length, .. } = motion.path {
+            let rod = length - 1.0;
+            target.with_children(|parent| {
+                parent.spawn((
+                    Mesh3d(assets.rig_rod_mesh.clone()),
+                    MeshMaterial3d(assets.rig_material.clone()),
+                    Transform::from_xyz(0.0, 1.0 + rod / 2.0, 0.0)
+                        .with_scale(Vec3::new(1.0, rod, 1.0)),
+                ));
+            });
+        }
+        target.insert(motion);
+    }
+    if let Some(popup) = spawn.popup.clone() {
+        target.insert(popup);
     }
+    if spawn.turret {
+        target.insert(TurretTarget::default());
+        target.with_children(|parent| {
+            parent.spawn((
+                Mesh3d(assets.barrel_mesh.clone()),
+                MeshMaterial3d(assets.barrel_material.clone()),
+                Transform::from_xyz(0.0, 0.6, 0.0),
+                TurretBarrel,
+            ));
+        });
+    }
+    if spawn.kind == TargetKind::Dummy {
+        target.insert(DpsMeter::default());
+    }
+    if spawn.kind == TargetKind::WeakPoint {
+        // Child collider attaches to the target's body, so rays report it separately
+        let owner = target.id();
+        target.with_children(|parent| {
+            parent.spawn((
+                Mesh3d(assets.weak_point_mesh.clone()),
+                MeshMaterial3d(assets.weak_point_material.clone()),
+                Transform::from_xyz(0.0, 0.4, 0.8),
+                Collider::ball(0.25),
+                PhysicsLayer::Enemies.groups(),
+                Shootable,
+                WeakPoint { owner },
+            ));
+        });
+    }
+    let target_entity = target.insert(spawn).id();
+
+    // Health bar background
+    commands.spawn((
+        Mesh3d(assets.health_bar_bg_mesh.clone()),
+        MeshMaterial3d(assets.health_bar_bg_material.clone()),
+        Transform::from_translation(start + Vec3::Y * 1.5),
+        HealthBar,
+        HealthBarBackground,
+        ChildOf(target_entity),
+    ));
+
+    // Health bar fill
+    commands.spawn((
+        Mesh3d(assets.health_bar_fill_mesh.clone()),
+        MeshMaterial3d(fill_material),
+        Transform::from_translation(start + Vec3::Y * 1.5),
+        HealthBar,
+        HealthBarFill,
+        ChildOf(target_entity),
+    ));
+
+    target_entity
+}
+
+/// Spawn the frame for a mover's path; circular paths have none
+pub fn spawn_target_rig(commands: &mut Commands, assets: &TargetAssets, mover: &Mover) {
+    let transform = match mover.path {
+        MoverPath::Linear { from, to } => {
+            // Rail along the floor under the targets
+            let (from, to) = (from.with_y(0.05), to.with_y(0.05));
+            Transform::from_translation((from + to) / 2.0)
+                .looking_to(to - from, Vec3::Y)
+                .with_scale(Vec3::new(0.3, 0.1, from.distance(to) + 1.5))
+        }
+        MoverPath::Pendulum { pivot, .. } => {
+            Transform::from_translation(pivot).with_scale(Vec3::new(0.2, 0.2, 1.5))
+        }
+        MoverPath::Circular { .. } => return,
+    };
+    commands.spawn((
+        Mesh3d(assets.rig_beam_mesh.clone()),
+        MeshMaterial3d(assets.rig_material.clone()),
+        transform,
+        TargetRig,
+    ));
 }
 
 /// Handle hits specifically for Target entities
 fn handle_target_hits(
-    mut commands: Commands,
     mut hit_events: MessageReader<HitEvent>,
-    mut targets: Query<(&mut Target, &MeshMaterial3d<StandardMaterial>)>,
-    materials: Res<Assets<StandardMaterial>>,
+    mut targets: Query<(&mut Target, &TargetKind)>,
+    weak_points: Query<&WeakPoint>,
+    mut target_hit_events: MessageWriter<TargetHitEvent>,
 ) {
     for event in hit_events.read() {
+        // Weak point hits are credited to the target that owns them
+        let (target_entity, on_weak_point) = match weak_points.get(event.entity) {
+            Ok(weak_point) => (weak_point.owner, true),
+            Err(_) => (event.entity, false),
+        };
+
         // Only process if this entity is a Target
-        if let Ok((mut target, material_handle)) = targets.get_mut(event.entity) {
-            target.current_health -= event.damage;
-            target.current_health = target.current_health.max(0.0);
-
-            // Get original color and add flash component
-            let original_color = materials
-                .get(&material_handle.0)
-                .map(|m| m.base_color)
-                .unwrap_or(Color::srgb(0.8, 0.2, 0.2));
-
-            commands.entity(event.entity).insert(HitFlash {
-                timer: Timer::from_seconds(0.1, TimerMode::Once),
-                original_color,
+        if let Ok((mut target, kind)) = targets.get_mut(target_entity) {
+            // Dead targets (including lit explosives) are immune, which also stops chain loops
+            if target.current_health <= 0.0 {
+                continue;
+            }
+
+            let damage = match *kind {
+                TargetKind::Standard | TargetKind::Explosive | TargetKind::Dummy => event.damage,
+                TargetKind::Armored { threshold } => {
+                    if event.damage < threshold {
+                        0.0
+                    } else {
+                        event.damage * 0.5
+                    }
+                }
+                TargetKind::WeakPoint => {
+                    if on_weak_point {
+                        event.damage * 3.0
+                    } else {
+                        0.0
+                    }
+                }
+            };
+            if damage <= 0.0 {
+                continue;
+            }
+
+            let was_alive = target.current_health > 0.0;
+            // Dummies soak everything and stay up
+            if *kind != TargetKind::Dummy {
+                target.current_health -= damage;
+                target.current_health = target.current_health.max(0.0);
+            }
+            target.last_hit_direction = event.direction;
+
+            target_hit_events.write(TargetHitEvent {
+                target: target_entity,
+                damage,
+                point: event.point,
+                killed: was_alive && target.current_health <= 0.0,
             });
         }
     }
 }
 
-fn update_hit_flash(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut flash_query: Query<(Entity, &mut HitFlash, &MeshMaterial3d<StandardMaterial>)>,
+fn apply_target_palette(
+    palette: Res<ColorPalette>,
+    assets: Option<Res<TargetAssets>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    for (entity, mut flash, material_handle) in flash_query.iter_mut() {
-        flash.timer.tick(time.delta());
-
-        if let Some(material) = materials.get_mut(&material_handle.0) {
-            if flash.timer.is_finished() {
-                material.base_color = flash.original_color;
-                commands.entity(entity).remove::<HitFlash>();
-            } else {
-                // Flash white
-                material.base_color = Color::WHITE;
-            }
-        }
+    let Some(assets) = assets else {
+        return;
+    };
+    if let Some(material) = materials.get_mut(&assets.health_bar_fill_material) {
+        material.base_color = palette.target_health;
     }
 }
 
@@ -208,20 +830,629 @@ fn billboard_health_bars(
     }
 }
 
+/// Remove every target, its health bars and rigs so a new run can start clean
+fn despawn_all_targets(
+    mut commands: Commands,
+    targets: Query<Entity, With<Target>>,
+    health_bars: Query<Entity, With<HealthBar>>,
+    fragments: Query<Entity, Or<(With<TargetFragment>, With<Shockwave>, With<TargetRig>)>>,
+    mut pending: ResMut<PendingTargetRespawns>,
+) {
+    pending.0.clear();
+
+    for entity in targets
+        .iter()
+        .chain(health_bars.iter())
+        .chain(fragments.iter())
+    {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn clear_targets(
+    mut commands: Commands,
+    mut clear_events: MessageReader<ClearTargets>,
+    targets: Query<(), With<Target>>,
+    health_bars: Query<(Entity, &ChildOf), With<HealthBar>>,
+) {
+    for event in clear_events.read() {
+        for &entity in event.0.iter().filter(|&&entity| targets.contains(entity)) {
+            for (bar_entity, child_of) in health_bars.iter() {
+                if child_of.0 == entity {
+                    commands.entity(bar_entity).despawn();
+                }
+            }
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 fn despawn_dead_targets(
     mut commands: Commands,
-    targets: Query<(Entity, &Target)>,
+    settings: Res<RangeSettings>,
+    assets: Res<TargetAssets>,
+    targets: Query<(
+        Entity,
+        &Target,
+        &TargetSpawn,
+        &Transform,
+        &MeshMaterial3d<StandardMaterial>,
+        Option<&HitFlash>,
+        Option<&ExplosionFuse>,
+        Option<&Mover>,
+        Has<DrillTarget>,
+    )>,
     health_bars: Query<(Entity, &ChildOf), With<HealthBar>>,
+    mut pending: ResMut<PendingTargetRespawns>,
+    mut destroyed_events: MessageWriter<TargetDestroyed>,
 ) {
-    for (entity, target) in targets.iter() {
+    for (entity, target, spawn, transform, material_handle, flash, fuse, mover, drill) in
+        targets.iter()
+    {
         if target.current_health <= 0.0 {
+            // Explosives light a fuse first and only break apart once they've detonated
+            if spawn.kind == TargetKind::Explosive {
+                match fuse {
+                    None => {
+                        commands.entity(entity).insert(ExplosionFuse {
+                            timer: Timer::from_seconds(0.3, TimerMode::Once),
+                            detonated: false,
+                        });
+                        continue;
+                    }
+                    Some(fuse) if !fuse.detonated => continue,
+                    Some(_) => {}
+                }
+            }
+
+            destroyed_events.write(TargetDestroyed {
+                entity,
+                position: transform.translation,
+                kind: spawn.kind,
+            });
+
+            // A drill's targets are its own to put up
+            if settings.auto_respawn && !drill {
+                // Targets on a rig come back in step with the rest of it
+                let spawn = TargetSpawn {
+                    motion: mover.map(|mover| mover.ahead(settings.respawn_delay)),
+                    ..spawn.clone()
+                };
+                pending.0.push((
+                    Timer::from_seconds(settings.respawn_delay, TimerMode::Once),
+                    spawn,
+                ));
+            }
+
             // Despawn health bars first
             for (bar_entity, child_of) in health_bars.iter() {
                 if child_of.0 == entity {
                     commands.entity(bar_entity).despawn();
                 }
             }
+
+            // Use the real material even if the killing shot left the target mid-flash.
+            // Each piece takes a copy of its own once it starts to dissolve.
+            let material = flash.map_or(&material_handle.0, |flash| &flash.restore);
+
+            // Replace the cuboid with a 2x3x1 stack of fragments. The debris budget
+            // clears the oldest ones so the range doesn't flood the physics world.
+            let push = target.last_hit_direction.normalize_or_zero();
+            for i in 0..6 {
+                let offset = Vec3::new(
+                    if i % 2 == 0 { -0.375 } else { 0.375 },
+                    (i / 2) as f32 * 0.66 - 0.66,
+                    0.0,
+                );
+                let spread = offset.normalize_or_zero() * 0.5;
+
+                commands.spawn((
+                    Mesh3d(assets.fragment_mesh.clone()),
+                    MeshMaterial3d(material.clone()),
+                    Transform::from_translation(transform.translation + offset),
+                    RigidBody::Dynamic,
+                    Collider::cuboid(0.35, 0.31, 0.35),
+                    PhysicsLayer::Debris.groups(),
+                    ExternalImpulse {
+                        impulse: (push * 4.0 + spread + Vec3::Y * 1.5),
+                        torque_impulse: Vec3::new(offset.y, push.x, -offset.x) * 0.5,
+                    },
+                    TargetFragment,
+                    Dissolving::after(FRAGMENT_LINGER),
+                    Budgeted(BudgetCategory::Debris),
+                ));
+            }
+
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn respawn_targets(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<TargetAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pending: ResMut<PendingTargetRespawns>,
+) {
+    let mut ready = Vec::new();
+    pending.0.retain_mut(|(timer, spawn)| {
+        timer.tick(time.delta());
+        if timer.is_finished() {
+            ready.push(spawn.clone());
+            false
+        } else {
+            true
+        }
+    });
+
+    for spawn in ready {
+        spawn_target(&mut commands, &assets, &mut materials, spawn);
+    }
+}
+
+fn move_targets(time: Res<Time>, mut targets: Query<(&mut Transform, &mut Mover)>) {
+    for (mut transform, mut mover) in targets.iter_mut() {
+        let cycle = mover.cycle_length();
+        if cycle <= 0.0 {
+            continue;
+        }
+        mover.phase = (mover.phase + mover.speed * time.delta_secs() / cycle).rem_euclid(1.0);
+        // Kinematic bodies follow the transform, so rays hit the target where it's drawn
+        transform.translation = mover.position();
+        transform.rotation = mover.rotation();
+    }
+}
+
+fn update_popup_targets(time: Res<Time>, mut targets: Query<(&mut Transform, &mut PopupTarget)>) {
+    for (mut transform, mut popup) in targets.iter_mut() {
+        popup.timer.tick(time.delta());
+
+        if popup.timer.is_finished() {
+            popup.raised = !popup.raised;
+            let duration = if popup.raised {
+                popup.up_time
+            } else {
+                popup.down_time
+            };
+            popup.timer = Timer::from_seconds(duration, TimerMode::Once);
+        }
+
+        // Rise and drop quickly rather than teleporting
+        let target_y = if popup.raised {
+            popup.raised_y
+        } else {
+            popup.lowered_y
+        };
+        let t = 1.0 - (-12.0 * time.delta_secs()).exp();
+        transform.translation.y += (target_y - transform.translation.y) * t;
+    }
+}
+
+fn update_turrets(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    assets: Res<TargetAssets>,
+    player_q: Query<(Entity, &Transform), With<Player>>,
+    mut turrets: Query<
+        (
+            Entity,
+            &Transform,
+            &mut TurretTarget,
+            &MeshMaterial3d<StandardMaterial>,
+            Option<&HitFlash>,
+            &Children,
+        ),
+        Without<Player>,
+    >,
+    mut barrels: Query<
+        &mut Transform,
+        (With<TurretBarrel>, Without<TurretTarget>, Without<Player>),
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let Ok((player_entity, player_transform)) = player_q.single() else {
+        return;
+    };
+    let player_chest = player_transform.translation + Vec3::Y * 0.25;
+
+    for (turret_entity, turret_transform, mut turret, material_handle, flash, children) in
+        turrets.iter_mut()
+    {
+        let pivot = turret_transform.translation + Vec3::Y * 0.6;
+        let to_player = player_chest - pivot;
+        let distance = to_player.length();
+        let direction = to_player.normalize_or_zero();
+
+        // Track the player with the barrel
+        for child in children.iter() {
+            if let Ok(mut barrel) = barrels.get_mut(child) {
+                barrel.rotation = Transform::from_translation(pivot)
+                    .looking_at(player_chest, Vec3::Y)
+                    .rotation;
+                barrel.translation = Vec3::Y * 0.6 + direction * 0.4;
+            }
+        }
+
+        let muzzle = pivot + direction * 1.0;
+
+        // Only engage within range and with a clear line of sight (no shooting through crates)
+        let mut has_line_of_sight = false;
+        if distance <= turret.range {
+            let filter = QueryFilter::default()
+                .exclude_rigid_body(turret_entity)
+                .exclude_sensors()
+                .groups(PhysicsLayer::Projectiles.groups());
+            context.with_query_pipeline(filter, |query_pipeline| {
+                if let Some((hit, _)) =
+                    query_pipeline.cast_ray(muzzle, direction, distance + 1.0, true)
+                {
+                    has_line_of_sight = hit == player_entity;
+                }
+            });
+        }
+
+        // Glow on the turret's own material, not the shared flash it may be showing
+        let handle = flash.map_or(&material_handle.0, |flash| &flash.restore);
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+
+        if !has_line_of_sight {
+            turret.fire_timer.reset();
+            material.emissive = LinearRgba::BLACK;
+            continue;
+        }
+
+        turret.fire_timer.tick(time.delta());
+
+        // Telegraph the shot with a pulsing glow
+        let remaining = turret.fire_timer.remaining_secs();
+        if remaining < turret.telegraph_time {
+            let pulse = (remaining * 30.0).sin() * 0.5 + 0.5;
+            material.emissive = LinearRgba::rgb(3.0 * pulse, 1.2 * pulse, 0.0);
+        } else {
+            material.emissive = LinearRgba::BLACK;
+        }
+
+        if turret.fire_timer.is_finished() {
+            turret.fire_timer.reset();
+            material.emissive = LinearRgba::BLACK;
+
+            commands.spawn((
+                Mesh3d(assets.projectile_mesh.clone()),
+                MeshMaterial3d(assets.projectile_material.clone()),
+                Transform::from_translation(muzzle),
+                TurretProjectile {
+                    velocity: direction * 8.0,
+                    damage: 5.0,
+                    source: turret_entity,
+                    lifetime: Timer::from_seconds(6.0, TimerMode::Once),
+                },
+            ));
+        }
+    }
+}
+
+fn move_turret_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    virtual_time: Res<Time<Virtual>>,
+    difficulty: Res<DifficultyModifiers>,
+    rapier_context: ReadRapierContext,
+    mut projectiles: Query<(Entity, &mut Transform, &mut TurretProjectile), Without<Player>>,
+    mut player_q: Query<
+        (
+            Entity,
+            &mut PlayerHealth,
+            Option<&mut PlayerArmor>,
+            Option<&mut Flinch>,
+        ),
+        With<Player>,
+    >,
+    mut feedback: MessageWriter<FeedbackEvent>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let Ok((player_entity, mut player_health, mut armor, mut flinch)) = player_q.single_mut()
+    else {
+        return;
+    };
+
+    for (entity, mut transform, mut projectile) in projectiles.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Cast along this tick's movement so the projectile can't skip through anything
+        let step = projectile.velocity * time.delta_secs();
+        let step_length = step.length();
+        if step_length <= 0.0 {
+            continue;
+        }
+
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(projectile.source)
+            .exclude_sensors()
+            .groups(PhysicsLayer::Projectiles.groups());
+        let mut hit_entity = None;
+        context.with_query_pipeline(filter, |query_pipeline| {
+            hit_entity = query_pipeline.cast_ray(
+                transform.translation,
+                step / step_length,
+                step_length + 0.2,
+                true,
+            );
+        });
+
+        if let Some((hit, _)) = hit_entity {
+            if hit == player_entity {
+                let taken = apply_player_damage(
+                    &mut player_health,
+                    armor.as_deref_mut(),
+                    flinch.as_deref_mut(),
+                    PlayerHit {
+                        damage: projectile.damage,
+                        flinch: TURRET_FLINCH,
+                        source: PlayerDamageSource::Turret,
+                        now: virtual_time.elapsed_secs_f64(),
+                    },
+                    &difficulty.burst,
+                );
+                if taken > 0.0 {
+                    feedback.write(FeedbackEvent::Damage { amount: taken });
+                }
+            }
             commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation += step;
+    }
+}
+
+fn despawn_turret_projectiles(
+    mut commands: Commands,
+    projectiles: Query<Entity, With<TurretProjectile>>,
+) {
+    for entity in projectiles.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Practice mode brings targets back quickly, the main game gives them a longer break
+fn apply_range_settings(mode: Res<GameMode>, mut settings: ResMut<RangeSettings>) {
+    *settings = match *mode {
+        GameMode::Survival | GameMode::Extraction => RangeSettings {
+            conveyor_speed: settings.conveyor_speed,
+            ..default()
+        },
+        GameMode::ShootingRange => RangeSettings {
+            auto_respawn: true,
+            respawn_delay: 2.0,
+            ..*settings
+        },
+    };
+}
+
+fn spawn_range_lever(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let post_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.25, 0.25, 0.28),
+        metallic: 0.8,
+        ..default()
+    });
+    let handle_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.75, 0.1),
+        ..default()
+    });
+
+    commands
+        .spawn((
+            Mesh3d(meshes.add(Cuboid::new(0.3, 1.0, 0.3))),
+            MeshMaterial3d(post_material),
+            Transform::from_translation(RANGE_LEVER_POSITION + Vec3::Y * 0.5),
+            RigidBody::Fixed,
+            Collider::cuboid(0.15, 0.5, 0.15),
+            PhysicsLayer::World.groups(),
+            RangeLever,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Mesh3d(meshes.add(Cylinder::new(0.06, 0.6))),
+                MeshMaterial3d(handle_material),
+                Transform::from_xyz(0.0, 0.7, 0.0).with_rotation(Quat::from_rotation_x(0.5)),
+            ));
+        });
+
+    commands.spawn((
+        Text::new("[E] Reset targets"),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(30.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-80.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+        RangeLeverPrompt,
+    ));
+}
+
+/// Pull the lever with E while standing next to it
+fn use_range_lever(
+    actions: Res<PlayerActions>,
+    player_q: Query<&Transform, With<Player>>,
+    mut prompt_q: Query<&mut Visibility, With<RangeLeverPrompt>>,
+    mut reset_events: MessageWriter<RangeReset>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+
+    let in_reach = player_transform
+        .translation
+        .with_y(0.0)
+        .distance(RANGE_LEVER_POSITION)
+        <= RANGE_LEVER_REACH;
+
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = if in_reach {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if in_reach && actions.interact {
+        reset_events.write(RangeReset);
+    }
+}
+
+fn hide_range_lever_prompt(mut prompt_q: Query<&mut Visibility, With<RangeLeverPrompt>>) {
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Detonate explosives whose fuse ran out. Damage goes out as HitEvents, so any
+/// neighbouring explosive lights its own fuse next frame and the chain resolves
+/// one link at a time.
+fn burn_explosion_fuses(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<TargetAssets>,
+    perks: Res<PlayerPerks>,
+    mut fuses: Query<(Entity, &Transform, &mut ExplosionFuse)>,
+    // Hit zones and weak points pass their hits to the body they're part of, which
+    // the blast already reaches
+    shootables: Query<
+        (Entity, &GlobalTransform),
+        (With<Shootable>, Without<HitZone>, Without<WeakPoint>),
+    >,
+    pushable: Query<(Entity, &Transform), Or<(With<Player>, With<Zombie>)>>,
+    mut flinches: Query<&mut Flinch>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut hit_events: MessageWriter<HitEvent>,
+    mut shake_events: MessageWriter<CameraShake>,
+    mut feedback: MessageWriter<FeedbackEvent>,
+) {
+    for (entity, transform, mut fuse) in fuses.iter_mut() {
+        if fuse.detonated {
+            continue;
+        }
+
+        fuse.timer.tick(time.delta());
+        if !fuse.timer.is_finished() {
+            continue;
+        }
+        fuse.detonated = true;
+
+        let center = transform.translation;
+        let radius = EXPLOSION_RADIUS * perks.explosion_radius_scale();
+
+        for (other, other_transform) in shootables.iter() {
+            if other == entity {
+                continue;
+            }
+            let offset = other_transform.translation() - center;
+            let distance = offset.length();
+            if distance > radius {
+                continue;
+            }
+
+            let falloff = 1.0 - distance / radius;
+            hit_events.write(HitEvent {
+                entity: other,
+                damage: EXPLOSION_DAMAGE * falloff,
+                direction: offset.normalize_or(Vec3::Y),
+                point: other_transform.translation(),
+                normal: -offset.normalize_or(Vec3::Y),
+                distance: 0.0,
+                source: None,
+                zone: None,
+                surface: SurfaceMaterial::default(),
+                headshot: false,
+                crit: false,
+            });
+        }
+
+        for (other, other_transform) in pushable.iter() {
+            let offset = other_transform.translation - center;
+            let distance = offset.length();
+            if distance > radius {
+                continue;
+            }
+
+            let falloff = 1.0 - distance / radius;
+            let push = offset.with_y(0.0).normalize_or_zero() * EXPLOSION_KNOCKBACK * falloff;
+            commands.entity(other).insert(Knockback { velocity: push });
+            if let Ok(mut flinch) = flinches.get_mut(other) {
+                flinch.add(EXPLOSION_FLINCH * falloff);
+            }
+        }
+
+        commands.spawn((
+            Mesh3d(assets.shockwave_mesh.clone()),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(1.0, 0.6, 0.2, 0.6),
+                emissive: LinearRgba::rgb(4.0, 1.5, 0.3),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            Transform::from_translation(center).with_scale(Vec3::splat(0.1)),
+            Shockwave {
+                timer: Timer::from_seconds(0.4, TimerMode::Once),
+                radius,
+            },
+        ));
+
+        shake_events.write(CameraShake { trauma: 0.6 });
+        feedback.write(FeedbackEvent::Explosion {
+            position: center,
+            radius,
+        });
+    }
+}
+
+fn update_shockwaves(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shockwaves: Query<(
+        Entity,
+        &mut Shockwave,
+        &mut Transform,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, mut shockwave, mut transform, material_handle) in shockwaves.iter_mut() {
+        shockwave.timer.tick(time.delta());
+        if shockwave.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let progress = shockwave.timer.fraction();
+        transform.scale = Vec3::splat(shockwave.radius * progress.max(0.02));
+        if let Some(material) = materials.get_mut(&material_handle.0) {
+            material.base_color.set_alpha(0.6 * (1.0 - progress));
         }
     }
 }
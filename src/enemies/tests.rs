@@ -0,0 +1,524 @@
+use super::*;
+use crate::combat::{
+    DamageNumberPlugin, HitEvent, HitFeedbackPlugins, HitMarkerPlugin, ImpactPlugin, ShotFired,
+    ShotKind, TracerPlugin,
+};
+use crate::player::{
+    ArmorPlate, ArmorPlugin, CameraShake, PickupCollected, PickupKind, Player, PlayerActions,
+    PlayerArmor, PlayerHealth, PlayerPerks, PLAYER_HALF_HEIGHT, PLAYER_RADIUS,
+};
+use crate::ui::{AccessibilitySettings, DifficultyModifiers, GameMode};
+use crate::world::{
+    boot, headless_app, spawn_floor, ticks, BalanceData, PhysicsLayer, SurfaceMaterial,
+    SIMULATION_HZ,
+};
+use bevy::audio::Pitch;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Bite reach, as in zombie_attack
+const BITE_RANGE: f32 = 1.5;
+
+/// Arena with a floor and the player standing at the origin
+fn arena(mode: GameMode) -> (App, Entity) {
+    arena_with(mode, |_| {})
+}
+
+/// `arena`, with `extra` adding to the app before it boots
+fn arena_with(mode: GameMode, extra: impl FnOnce(&mut App)) -> (App, Entity) {
+    let mut app = headless_app(7);
+    app.insert_resource(mode);
+    extra(&mut app);
+    boot(&mut app);
+    spawn_floor(&mut app);
+    let player = app
+        .world_mut()
+        .spawn((
+            Player::default(),
+            PlayerHealth::default(),
+            Team::Survivors,
+            Transform::from_xyz(0.0, PLAYER_HALF_HEIGHT + PLAYER_RADIUS, 0.0),
+            RigidBody::KinematicPositionBased,
+            Collider::capsule_y(PLAYER_HALF_HEIGHT, PLAYER_RADIUS),
+            PhysicsLayer::Player.groups(),
+        ))
+        .id();
+    (app, player)
+}
+
+fn spawn_walker(app: &mut App, pos: Vec3) -> Entity {
+    app.world_mut()
+        .run_system_once(
+            move |mut commands: Commands, assets: Res<ZombieAssets>, balance: Res<BalanceData>| {
+                let zombie = Zombie::new(ZombieKind::Walker, 0, &balance);
+                spawn_standing_zombie(&mut commands, &assets, pos, zombie)
+            },
+        )
+        .expect("spawning a walker")
+}
+
+fn horizontal_distance(app: &App, a: Entity, b: Entity) -> f32 {
+    let position = |entity| app.world().get::<Transform>(entity).unwrap().translation;
+    (position(a) - position(b)).with_y(0.0).length()
+}
+
+#[test]
+fn walker_paths_to_the_player() {
+    let (mut app, player) = arena(GameMode::ShootingRange);
+    let walker = spawn_walker(&mut app, Vec3::new(15.0, 1.0, 0.0));
+
+    let speed = app
+        .world()
+        .resource::<BalanceData>()
+        .zombie(ZombieKind::Walker)
+        .speed;
+    let start = horizontal_distance(&app, walker, player);
+    // Twice the straight walk, plus the wait for its first path
+    let limit = (2.0 * start / speed * SIMULATION_HZ as f32) as u32 + 20;
+    for _ in 0..limit {
+        app.update();
+        if horizontal_distance(&app, walker, player) < BITE_RANGE {
+            return;
+        }
+    }
+    panic!(
+        "walker still {:.1} m away after {} ticks",
+        horizontal_distance(&app, walker, player),
+        limit
+    );
+}
+
+#[test]
+fn player_dies_on_the_expected_tick() {
+    let (mut app, player) = arena(GameMode::ShootingRange);
+    spawn_walker(&mut app, Vec3::new(1.0, 1.0, 0.0));
+
+    let stats = app
+        .world()
+        .resource::<BalanceData>()
+        .zombie(ZombieKind::Walker);
+    let (damage, cooldown) = (stats.damage, stats.attack_cooldown);
+    let burst = app.world().resource::<DifficultyModifiers>().burst;
+    // Bites spaced wider than the burst window all land in full
+    assert!(cooldown > burst.window);
+    let bites = (PlayerHealth::default().current / damage).ceil() as u32;
+    let expected = bites * (cooldown * SIMULATION_HZ as f32).ceil() as u32;
+
+    let start = ticks(&app);
+    for _ in 0..expected + 64 {
+        app.update();
+        if app.world().get::<PlayerHealth>(player).unwrap().current <= 0.0 {
+            assert_eq!(ticks(&app) - start, expected);
+            return;
+        }
+    }
+    panic!("player still alive {} ticks in", ticks(&app) - start);
+}
+
+/// Fixed block of level geometry
+fn spawn_block(app: &mut App, center: Vec3, half_extents: Vec3) {
+    app.world_mut().spawn((
+        Transform::from_translation(center),
+        RigidBody::Fixed,
+        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+        PhysicsLayer::World.groups(),
+    ));
+}
+
+/// Health the player has left after a walker has had three bites' worth of time
+fn health_after_three_bites(app: &mut App, player: Entity) -> f32 {
+    let cooldown = app
+        .world()
+        .resource::<BalanceData>()
+        .zombie(ZombieKind::Walker)
+        .attack_cooldown;
+    for _ in 0..(3.0 * cooldown * SIMULATION_HZ as f32).ceil() as u32 {
+        app.update();
+    }
+    app.world().get::<PlayerHealth>(player).unwrap().current
+}
+
+#[test]
+fn walls_stop_bites() {
+    let (mut app, player) = arena(GameMode::ShootingRange);
+    // Between the two, in bite range of each other across it
+    spawn_block(
+        &mut app,
+        Vec3::new(0.7, 1.5, 0.0),
+        Vec3::new(0.1, 1.5, 10.0),
+    );
+    spawn_walker(&mut app, Vec3::new(1.4, 1.0, 0.0));
+
+    let full = PlayerHealth::default().current;
+    assert_eq!(health_after_three_bites(&mut app, player), full);
+}
+
+#[test]
+fn no_bites_from_under_a_platform() {
+    let (mut app, player) = arena(GameMode::ShootingRange);
+    // Slab just clear of a walker's head, the player standing on it
+    let top = 2.3;
+    spawn_block(
+        &mut app,
+        Vec3::new(0.0, top - 0.1, 0.0),
+        Vec3::new(3.0, 0.1, 3.0),
+    );
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .y = top + PLAYER_HALF_HEIGHT + PLAYER_RADIUS;
+    spawn_walker(&mut app, Vec3::new(0.5, 1.0, 0.0));
+
+    let full = PlayerHealth::default().current;
+    assert_eq!(health_after_three_bites(&mut app, player), full);
+}
+
+/// Well past any zombie's health, even one playing dead
+const LETHAL: f32 = 10_000.0;
+
+/// Plays the first survival wave, shooting every zombie dead as soon as it has risen.
+/// Returns the tick the wave was cleared on and the shots it took.
+fn clear_first_wave(seed: u64) -> (u32, u32) {
+    let mut app = headless_app(seed);
+    boot(&mut app);
+    spawn_floor(&mut app);
+
+    let mut shot = HashSet::new();
+    let mut risen = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), (With<Zombie>, Without<SpawnProtection>)>();
+    // Intermission, every zombie through its portal and a few seconds to spare
+    let limit = (20.0 * SIMULATION_HZ) as u32;
+    for _ in 0..limit {
+        let targets: Vec<HitEvent> = risen
+            .iter(app.world())
+            .filter(|(entity, ..)| !shot.contains(entity))
+            .map(|(entity, transform)| HitEvent {
+                entity,
+                damage: LETHAL,
+                direction: Vec3::NEG_Z,
+                point: transform.translation,
+                normal: Vec3::Z,
+                distance: 10.0,
+                source: None,
+                zone: None,
+                surface: SurfaceMaterial::default(),
+                headshot: false,
+                crit: false,
+            })
+            .collect();
+        for hit in targets {
+            shot.insert(hit.entity);
+            app.world_mut().write_message(hit);
+        }
+        app.update();
+
+        let waves = app.world().resource::<WaveState>();
+        if waves.wave == 1 && !waves.active {
+            return (ticks(&app), shot.len() as u32);
+        }
+    }
+    panic!("first wave not cleared after {} ticks", limit);
+}
+
+#[test]
+fn scripted_shots_clear_the_first_wave() {
+    let (cleared_at, shots) = clear_first_wave(7);
+    // One shot for each zombie the first wave sends, as advance_waves budgets it
+    let size = BalanceData::embedded().waves.size(1) as f32;
+    let expected = (size * DifficultyModifiers::default().spawn_count).round() as u32;
+    assert_eq!(shots, expected);
+
+    // The same seed plays out the same
+    assert_eq!(clear_first_wave(7), (cleared_at, shots));
+}
+
+/// Zombie positions at the end of one tick
+#[derive(Resource)]
+struct Snapshot {
+    tick: u32,
+    positions: Option<Vec<(Entity, Vec3)>>,
+}
+
+fn take_snapshot(
+    time: Res<Time>,
+    mut snapshot: ResMut<Snapshot>,
+    zombies: Query<(Entity, &Transform), With<Zombie>>,
+) {
+    let tick = (time.elapsed().as_nanos() / time.delta().as_nanos().max(1)) as u32;
+    if tick == snapshot.tick {
+        let mut positions: Vec<(Entity, Vec3)> = zombies
+            .iter()
+            .map(|(entity, transform)| (entity, transform.translation))
+            .collect();
+        positions.sort_by_key(|(entity, _)| *entity);
+        snapshot.positions = Some(positions);
+    }
+}
+
+/// Four walkers closing in on the player for ten seconds, with the frames drawn at `fps`
+fn walkers_after_ten_seconds(fps: f64) -> Vec<(Entity, Vec3)> {
+    let (mut app, _) = arena(GameMode::ShootingRange);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / fps,
+    )))
+    .insert_resource(Snapshot {
+        tick: (10.0 * SIMULATION_HZ) as u32,
+        positions: None,
+    })
+    .add_systems(FixedLast, take_snapshot);
+    for (x, z) in [(12.0, 0.0), (-9.0, 7.0), (3.0, -14.0), (-6.0, -6.0)] {
+        spawn_walker(&mut app, Vec3::new(x, 1.0, z));
+    }
+
+    // A little over ten seconds, so a slow frame can't stop short of the tick
+    for _ in 0..(11.0 * fps) as u32 {
+        app.update();
+    }
+    app.world_mut()
+        .resource_mut::<Snapshot>()
+        .positions
+        .take()
+        .expect("never reached the ten second tick")
+}
+
+#[test]
+fn zombies_move_the_same_at_any_frame_rate() {
+    let slow = walkers_after_ten_seconds(30.0);
+    let fast = walkers_after_ten_seconds(144.0);
+    assert_eq!(slow.len(), 4);
+    assert_eq!(slow, fast);
+}
+
+/// Shoots a walker three times, then once more to kill it, with whatever hit feedback
+/// `feedback` adds reacting to the shots. Returns its health after the three and
+/// whether the last hit killed it.
+fn walker_shot_down(feedback: fn(&mut App)) -> (f32, bool) {
+    let (mut app, player) = arena_with(GameMode::ShootingRange, |app| {
+        app.init_resource::<AccessibilitySettings>()
+            .init_asset::<Image>()
+            .init_asset::<Pitch>()
+            .add_message::<TargetHitEvent>()
+            .add_message::<TargetDestroyed>()
+            .add_message::<PickupCollected>();
+        feedback(app);
+    });
+    let walker = spawn_walker(&mut app, Vec3::new(15.0, 1.0, 0.0));
+    app.update();
+
+    let hit = |app: &mut App, damage: f32| {
+        let point = app.world().get::<Transform>(walker).unwrap().translation;
+        app.world_mut().write_message(ShotFired {
+            shooter: player,
+            kind: ShotKind::Hitscan,
+            origin: point - Vec3::X * 15.0,
+            direction: Vec3::X,
+            hit: true,
+            point: Some(point),
+            struck: Some((walker, Vec3::NEG_X)),
+        });
+        HitEvent {
+            entity: walker,
+            damage,
+            direction: Vec3::X,
+            point,
+            normal: Vec3::NEG_X,
+            distance: 15.0,
+            source: Some(player),
+            zone: None,
+            surface: SurfaceMaterial::default(),
+            headshot: false,
+            crit: false,
+        }
+    };
+    for _ in 0..3 {
+        let event = hit(&mut app, 10.0);
+        app.world_mut().write_message(event);
+        app.update();
+    }
+    let health = app.world().get::<Zombie>(walker).unwrap().health;
+
+    let event = hit(&mut app, LETHAL);
+    app.world_mut().write_message(event);
+    app.update();
+    let died = app
+        .world()
+        .resource::<Messages<ZombieDied>>()
+        .iter_current_update_messages()
+        .any(|died| died.killer == Some(player));
+    (health, died)
+}
+
+/// All the hit feedback but `T`
+fn feedback_without<T: Plugin>(app: &mut App) {
+    app.add_plugins(HitFeedbackPlugins.build().disable::<T>());
+}
+
+#[test]
+fn hit_feedback_leaves_damage_alone() {
+    let max_health = BalanceData::embedded().zombie(ZombieKind::Walker).health;
+    let bare = walker_shot_down(|_| {});
+    assert_eq!(bare, (max_health - 30.0, true));
+    assert_eq!(
+        walker_shot_down(|app| {
+            app.add_plugins(HitFeedbackPlugins);
+        }),
+        bare
+    );
+
+    let without_each: [fn(&mut App); 7] = [
+        feedback_without::<HitMarkerPlugin>,
+        feedback_without::<ImpactPlugin>,
+        feedback_without::<TracerPlugin>,
+        feedback_without::<DamageNumberPlugin>,
+        feedback_without::<HitFlashPlugin>,
+        feedback_without::<BloodPlugin>,
+        feedback_without::<RangeScoringPlugin>,
+    ];
+    for feedback in without_each {
+        assert_eq!(walker_shot_down(feedback), bare);
+    }
+}
+
+/// Messages of type `M` sent during the last update
+fn sent<M: Message>(app: &App) -> Vec<&M> {
+    app.world()
+        .resource::<Messages<M>>()
+        .iter_current_update_messages()
+        .collect()
+}
+
+fn lethal_hit(app: &App, zombie: Entity, source: Entity, headshot: bool) -> HitEvent {
+    let point = app.world().get::<Transform>(zombie).unwrap().translation;
+    HitEvent {
+        entity: zombie,
+        damage: LETHAL,
+        direction: Vec3::X,
+        point,
+        normal: Vec3::NEG_X,
+        distance: 15.0,
+        source: Some(source),
+        zone: None,
+        surface: SurfaceMaterial::default(),
+        headshot,
+        crit: false,
+    }
+}
+
+#[test]
+fn spawning_a_zombie_announces_it() {
+    let (mut app, _) = arena(GameMode::ShootingRange);
+    let position = Vec3::new(15.0, 1.0, 0.0);
+    let walker = spawn_walker(&mut app, position);
+
+    let spawned = sent::<ZombieSpawned>(&app);
+    assert_eq!(spawned.len(), 1);
+    assert_eq!(spawned[0].entity, walker);
+    assert_eq!(spawned[0].position, position);
+    assert_eq!(spawned[0].kind, ZombieKind::Walker);
+}
+
+#[test]
+fn a_killed_zombie_reports_its_killer_and_the_headshot() {
+    let (mut app, player) = arena(GameMode::ShootingRange);
+    let walker = spawn_walker(&mut app, Vec3::new(15.0, 1.0, 0.0));
+    app.update();
+
+    let hit = lethal_hit(&app, walker, player, true);
+    let position = hit.point;
+    app.world_mut().write_message(hit);
+    app.update();
+
+    let died = sent::<ZombieDied>(&app);
+    assert_eq!(died.len(), 1);
+    assert_eq!(died[0].kind, ZombieKind::Walker);
+    assert_eq!(died[0].killer, Some(player));
+    assert!(died[0].headshot);
+    // At most one tick's walk from where the shot landed
+    assert!(died[0].position.distance(position) < 0.1);
+}
+
+#[test]
+fn a_body_shot_kill_is_no_headshot() {
+    let (mut app, player) = arena(GameMode::ShootingRange);
+    let walker = spawn_walker(&mut app, Vec3::new(15.0, 1.0, 0.0));
+    app.update();
+
+    let hit = lethal_hit(&app, walker, player, false);
+    app.world_mut().write_message(hit);
+    app.update();
+
+    let died = sent::<ZombieDied>(&app);
+    assert_eq!(died.len(), 1);
+    assert!(!died[0].headshot);
+}
+
+#[test]
+fn a_destroyed_target_announces_itself() {
+    let (mut app, player) = arena_with(GameMode::ShootingRange, |app| {
+        app.init_resource::<PlayerActions>()
+            .init_resource::<PlayerPerks>()
+            .add_message::<CameraShake>()
+            .add_plugins(TargetPlugin);
+    });
+    // Well clear of the targets the range puts up itself
+    let position = Vec3::new(0.0, 1.0, -20.0);
+    let target = app
+        .world_mut()
+        .run_system_once(
+            move |mut commands: Commands,
+                  assets: Res<TargetAssets>,
+                  mut materials: ResMut<Assets<StandardMaterial>>| {
+                spawn_target(
+                    &mut commands,
+                    &assets,
+                    &mut materials,
+                    TargetSpawn::fixed(position),
+                )
+            },
+        )
+        .expect("spawning a target");
+    app.update();
+
+    let hit = lethal_hit(&app, target, player, false);
+    app.world_mut().write_message(hit);
+    // Damage and the removal of broken targets share a set, so allow a second update
+    let mut destroyed = Vec::new();
+    for _ in 0..2 {
+        app.update();
+        destroyed.extend(
+            sent::<TargetDestroyed>(&app)
+                .into_iter()
+                .map(|event| (event.entity, event.position, event.kind)),
+        );
+    }
+    assert_eq!(destroyed, [(target, position, TargetKind::Standard)]);
+}
+
+#[test]
+fn walking_over_a_plate_collects_it() {
+    let (mut app, player) = arena_with(GameMode::ShootingRange, |app| {
+        app.add_plugins(ArmorPlugin);
+    });
+    app.world_mut()
+        .entity_mut(player)
+        .insert(PlayerArmor::default());
+    let position = Vec3::new(0.5, 0.5, 0.0);
+    let plate = app
+        .world_mut()
+        .spawn((ArmorPlate, Transform::from_translation(position)))
+        .id();
+    app.update();
+
+    let collected = sent::<PickupCollected>(&app);
+    assert_eq!(collected.len(), 1);
+    assert_eq!(collected[0].kind, PickupKind::ArmorPlate);
+    assert_eq!(collected[0].position, position);
+    assert!(app.world().get_entity(plate).is_err());
+    assert!(app.world().get::<PlayerArmor>(player).unwrap().current > 0.0);
+}
@@ -0,0 +1,332 @@
+use super::{WaveCleared, WaveStarted, WaveState};
+use crate::player::Score;
+use crate::ui::{DifficultyModifiers, RunStats, ShopDiscount};
+use crate::world::{BalanceData, GradeBalance};
+use bevy::prelude::*;
+
+/// How well a wave went, shown on its cleared banner
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WaveGrade {
+    S,
+    A,
+    B,
+    C,
+}
+
+impl WaveGrade {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WaveGrade::S => "S",
+            WaveGrade::A => "A",
+            WaveGrade::B => "B",
+            WaveGrade::C => "C",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            WaveGrade::S => Color::srgb(1.0, 0.85, 0.3),
+            WaveGrade::A => Color::srgb(0.4, 1.0, 0.5),
+            WaveGrade::B => Color::srgb(0.5, 0.8, 1.0),
+            WaveGrade::C => Color::srgb(0.7, 0.7, 0.7),
+        }
+    }
+}
+
+/// What the player did over one wave
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WaveTally {
+    pub stats: RunStats,
+    /// Seconds of play from the portals opening to the last zombie dying
+    pub seconds: f32,
+}
+
+impl WaveTally {
+    pub fn accuracy(&self) -> f32 {
+        ratio(self.stats.hits, self.stats.shots)
+    }
+
+    pub fn headshot_ratio(&self) -> f32 {
+        ratio(self.stats.headshots, self.stats.kills)
+    }
+}
+
+fn ratio(part: u32, whole: u32) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+/// RunStats as they stood when the current wave started. RunStats itself is never
+/// touched, so the run's totals (high scores, unlocks) carry on across waves.
+#[derive(Resource, Default)]
+pub struct WaveWindow {
+    start: RunStats,
+    seconds: f32,
+}
+
+impl WaveWindow {
+    pub fn open(&mut self, stats: &RunStats) {
+        self.start = stats.clone();
+        self.seconds = 0.0;
+    }
+
+    /// The wave so far. Saturating, so stats reset under an open window read as a
+    /// fresh start rather than wrapping.
+    pub fn tally(&self, stats: &RunStats) -> WaveTally {
+        WaveTally {
+            stats: RunStats {
+                kills: stats.kills.saturating_sub(self.start.kills),
+                headshots: stats.headshots.saturating_sub(self.start.headshots),
+                shots: stats.shots.saturating_sub(self.start.shots),
+                hits: stats.hits.saturating_sub(self.start.hits),
+                damage_taken: (stats.damage_taken - self.start.damage_taken).max(0.0),
+            },
+            seconds: self.seconds,
+        }
+    }
+}
+
+/// A graded wave and what it paid out
+#[derive(Clone, Debug)]
+pub struct WaveReport {
+    pub wave: u32,
+    pub grade: WaveGrade,
+    /// Out of 100
+    pub points: u32,
+    pub tally: WaveTally,
+    pub bonus: u32,
+    pub discount: f32,
+}
+
+/// The latest graded wave, for the cleared banner
+#[derive(Resource, Default)]
+pub struct WaveReports {
+    pub last: Option<WaveReport>,
+}
+
+/// Weighted score out of 100 for a wave of `zombies`. Rounded to a whole number so
+/// two waves that played out the same always land on the same side of a threshold.
+pub fn wave_points(grades: &GradeBalance, tally: &WaveTally, zombies: u32) -> u32 {
+    let damage = 1.0 - tally.stats.damage_taken / grades.damage_allowance;
+    let par = grades.par_seconds_per_zombie * zombies.max(1) as f32;
+    let time = if tally.seconds <= par {
+        1.0
+    } else {
+        par / tally.seconds
+    };
+    let parts = [
+        (grades.accuracy_weight, tally.accuracy()),
+        (grades.damage_weight, damage),
+        (grades.time_weight, time),
+        (grades.headshot_weight, tally.headshot_ratio()),
+    ];
+    let total: f32 = parts.iter().map(|(weight, _)| weight).sum();
+    let weighted: f32 = parts
+        .iter()
+        .map(|(weight, part)| weight * part.clamp(0.0, 1.0))
+        .sum();
+    (weighted / total * 100.0).round() as u32
+}
+
+/// A wave exactly on a threshold gets the higher grade
+pub fn wave_grade(grades: &GradeBalance, points: u32) -> WaveGrade {
+    if points >= grades.s {
+        WaveGrade::S
+    } else if points >= grades.a {
+        WaveGrade::A
+    } else if points >= grades.b {
+        WaveGrade::B
+    } else {
+        WaveGrade::C
+    }
+}
+
+pub(super) fn open_wave_window(
+    mut started_events: MessageReader<WaveStarted>,
+    stats: Res<RunStats>,
+    mut window: ResMut<WaveWindow>,
+) {
+    if started_events.read().last().is_some() {
+        window.open(&stats);
+    }
+}
+
+/// Only while a wave is running, and only in play, so pauses and the shop break
+/// don't count against the clear time
+pub(super) fn time_wave_window(
+    time: Res<Time>,
+    waves: Res<WaveState>,
+    mut window: ResMut<WaveWindow>,
+) {
+    if waves.active {
+        window.seconds += time.delta_secs();
+    }
+}
+
+/// Grade the wave just cleared and pay out its bonus
+pub(super) fn grade_cleared_wave(
+    mut cleared_events: MessageReader<WaveCleared>,
+    stats: Res<RunStats>,
+    window: Res<WaveWindow>,
+    balance: Res<BalanceData>,
+    difficulty: Res<DifficultyModifiers>,
+    mut score: ResMut<Score>,
+    mut discount: ResMut<ShopDiscount>,
+    mut reports: ResMut<WaveReports>,
+) {
+    let Some(event) = cleared_events.read().last() else {
+        return;
+    };
+    let grades = &balance.grades;
+    let tally = window.tally(&stats);
+    // The same count advance_waves sent through the portals
+    let zombies = (balance.waves.size(event.wave) as f32 * difficulty.spawn_count).round() as u32;
+    let points = wave_points(grades, &tally, zombies);
+    let grade = wave_grade(grades, points);
+
+    let (bonus, sale) = match grade {
+        WaveGrade::S => (grades.s_bonus, grades.s_discount),
+        WaveGrade::A => (grades.a_bonus, 0.0),
+        WaveGrade::B | WaveGrade::C => (0, 0.0),
+    };
+    score.points += bonus;
+    if sale > 0.0 {
+        *discount = ShopDiscount {
+            wave: event.wave,
+            fraction: sale,
+        };
+    }
+    reports.last = Some(WaveReport {
+        wave: event.wave,
+        grade,
+        points,
+        tally,
+        bonus,
+        discount: sale,
+    });
+}
+
+pub(super) fn reset_wave_grades(
+    mut window: ResMut<WaveWindow>,
+    mut reports: ResMut<WaveReports>,
+    mut discount: ResMut<ShopDiscount>,
+) {
+    *window = WaveWindow::default();
+    *reports = WaveReports::default();
+    *discount = ShopDiscount::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every part weighted the same, so points are a plain average
+    const GRADES: GradeBalance = GradeBalance {
+        s: 90,
+        a: 75,
+        b: 50,
+        accuracy_weight: 1.0,
+        damage_weight: 1.0,
+        time_weight: 1.0,
+        headshot_weight: 1.0,
+        damage_allowance: 100.0,
+        par_seconds_per_zombie: 2.0,
+        s_bonus: 500,
+        a_bonus: 200,
+        s_discount: 0.25,
+    };
+
+    fn stats(kills: u32, headshots: u32, shots: u32, hits: u32, damage_taken: f32) -> RunStats {
+        RunStats {
+            kills,
+            headshots,
+            shots,
+            hits,
+            damage_taken,
+        }
+    }
+
+    fn tally(stats: RunStats, seconds: f32) -> WaveTally {
+        WaveTally { stats, seconds }
+    }
+
+    #[test]
+    fn the_tally_is_only_what_changed_since_the_wave_opened() {
+        let mut window = WaveWindow::default();
+        let lifetime = stats(10, 4, 40, 20, 30.0);
+        window.open(&lifetime);
+
+        let now = stats(15, 7, 50, 28, 45.0);
+        assert_eq!(window.tally(&now).stats, stats(5, 3, 10, 8, 15.0));
+        // The run's own totals are left as they were
+        assert_eq!(now, stats(15, 7, 50, 28, 45.0));
+
+        // The next wave counts from where this one ended
+        window.open(&now);
+        let later = stats(16, 7, 52, 29, 45.0);
+        assert_eq!(window.tally(&later).stats, stats(1, 0, 2, 1, 0.0));
+    }
+
+    #[test]
+    fn stats_reset_under_an_open_window_read_as_a_fresh_start() {
+        let mut window = WaveWindow::default();
+        window.open(&stats(10, 4, 40, 20, 30.0));
+        assert_eq!(
+            window.tally(&stats(1, 0, 3, 2, 5.0)).stats,
+            stats(0, 0, 0, 0, 0.0)
+        );
+    }
+
+    #[test]
+    fn opening_a_window_restarts_its_clock() {
+        let mut window = WaveWindow {
+            seconds: 42.0,
+            ..default()
+        };
+        window.open(&RunStats::default());
+        assert_eq!(window.tally(&RunStats::default()).seconds, 0.0);
+    }
+
+    #[test]
+    fn a_flawless_wave_scores_full_points() {
+        let flawless = tally(stats(10, 10, 10, 10, 0.0), 20.0);
+        assert_eq!(wave_points(&GRADES, &flawless, 10), 100);
+    }
+
+    #[test]
+    fn points_average_the_parts() {
+        // Half the shots hit, half the kills headshots, unhurt and on par
+        let wave = tally(stats(10, 5, 20, 10, 0.0), 20.0);
+        assert_eq!(wave_points(&GRADES, &wave, 10), 75);
+
+        // Twice par halves the time part; more than the allowance zeroes damage
+        let slow = tally(stats(10, 10, 10, 10, 250.0), 40.0);
+        assert_eq!(wave_points(&GRADES, &slow, 10), 63);
+    }
+
+    #[test]
+    fn a_wave_with_no_shots_or_kills_scores_nothing_for_them() {
+        let idle = tally(RunStats::default(), 0.0);
+        assert_eq!(wave_points(&GRADES, &idle, 0), 50);
+    }
+
+    #[test]
+    fn thresholds_give_the_higher_grade() {
+        let cases = [
+            (100, WaveGrade::S),
+            (90, WaveGrade::S),
+            (89, WaveGrade::A),
+            (75, WaveGrade::A),
+            (74, WaveGrade::B),
+            (50, WaveGrade::B),
+            (49, WaveGrade::C),
+            (0, WaveGrade::C),
+        ];
+        for (points, grade) in cases {
+            assert_eq!(wave_grade(&GRADES, points), grade, "{points} points");
+        }
+    }
+}
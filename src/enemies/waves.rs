@@ -0,0 +1,339 @@
+use super::{
+    grade_cleared_wave, open_portals, open_wave_window, reset_wave_grades, time_wave_window,
+    Extraction, PortalAssets, Spawner, WaveReports, WaveWindow, Zombie,
+};
+use crate::player::KillCam;
+use crate::ui::{DifficultyModifiers, GameMode, GameState, TextRole, ThemedText, UiTheme};
+use crate::world::{BalanceData, GameRng, NavGrid};
+use bevy::prelude::*;
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<WaveStarted>()
+            .add_message::<WaveCleared>()
+            .init_resource::<WaveState>()
+            .init_resource::<WaveWindow>()
+            .init_resource::<WaveReports>()
+            .add_systems(
+                Update,
+                (
+                    advance_waves.run_if(waves_running),
+                    open_wave_window,
+                    time_wave_window,
+                    grade_cleared_wave,
+                    show_wave_banner,
+                    show_cleared_banner,
+                    fade_wave_banner,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), despawn_wave_banner)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                (reset_waves, reset_wave_grades),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                (reset_waves, reset_wave_grades),
+            );
+    }
+}
+
+/// Survival progress: the current wave and the break before the next one
+#[derive(Resource)]
+pub struct WaveState {
+    /// Wave number, 0 before the first wave starts
+    pub wave: u32,
+    /// True while the wave's zombies are still alive or yet to come through a portal
+    pub active: bool,
+    pub intermission: Timer,
+}
+
+impl Default for WaveState {
+    fn default() -> Self {
+        Self {
+            wave: 0,
+            active: false,
+            intermission: Timer::from_seconds(WAVE_INTERMISSION, TimerMode::Once),
+        }
+    }
+}
+
+impl WaveState {
+    /// True in the break after a cleared wave, until the next one starts
+    pub fn shop_open(&self) -> bool {
+        self.wave > 0 && !self.active
+    }
+
+    /// Pick a run back up at `wave`; with no zombies left the next wave follows after a break
+    pub fn resume(wave: u32, zombies_alive: bool) -> Self {
+        Self {
+            wave,
+            active: zombies_alive,
+            ..default()
+        }
+    }
+}
+
+/// Sent when a wave's portals have opened
+#[derive(Message)]
+pub struct WaveStarted {
+    pub wave: u32,
+    pub checkpoint: bool,
+}
+
+/// Sent when the last zombie of a wave dies
+#[derive(Message)]
+pub struct WaveCleared {
+    pub wave: u32,
+}
+
+/// Every this many waves the run is checkpointed
+pub const CHECKPOINT_INTERVAL: u32 = 3;
+/// Break before the first wave
+const WAVE_INTERMISSION: f32 = 5.0;
+/// Break after a cleared wave, while the shop is open (see ShopPlugin)
+pub const SHOP_INTERMISSION: f32 = 30.0;
+
+pub fn is_checkpoint_wave(wave: u32) -> bool {
+    wave > 0 && wave.is_multiple_of(CHECKPOINT_INTERVAL)
+}
+
+/// Waves keep coming in survival, and in extraction until the evac point opens
+fn waves_running(mode: Res<GameMode>, extraction: Res<Extraction>) -> bool {
+    match *mode {
+        GameMode::Survival => true,
+        GameMode::Extraction => !extraction.is_active(),
+        GameMode::ShootingRange => false,
+    }
+}
+
+#[derive(Component)]
+struct WaveBanner {
+    timer: Timer,
+}
+
+fn advance_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut waves: ResMut<WaveState>,
+    zombies: Query<(), With<Zombie>>,
+    portals: Query<&Spawner>,
+    assets: Res<PortalAssets>,
+    nav_grid: Res<NavGrid>,
+    difficulty: Res<DifficultyModifiers>,
+    balance: Res<BalanceData>,
+    mut rng: ResMut<GameRng>,
+    mut started_events: MessageWriter<WaveStarted>,
+    mut cleared_events: MessageWriter<WaveCleared>,
+) {
+    if waves.active {
+        // Cleared once every zombie is dead and no portal has any left to send
+        if zombies.is_empty() && portals.iter().all(|portal| portal.remaining == 0) {
+            waves.active = false;
+            waves.intermission = Timer::from_seconds(SHOP_INTERMISSION, TimerMode::Once);
+            cleared_events.write(WaveCleared { wave: waves.wave });
+        }
+        return;
+    }
+
+    waves.intermission.tick(time.delta());
+    if !waves.intermission.is_finished() {
+        return;
+    }
+
+    waves.wave += 1;
+    waves.active = true;
+    let budget = (balance.waves.size(waves.wave) as f32 * difficulty.spawn_count).round() as u32;
+    open_portals(
+        &mut commands,
+        &assets,
+        &nav_grid,
+        &mut rng,
+        waves.wave,
+        budget,
+    );
+    started_events.write(WaveStarted {
+        wave: waves.wave,
+        checkpoint: is_checkpoint_wave(waves.wave),
+    });
+}
+
+fn show_wave_banner(
+    mut commands: Commands,
+    mut started_events: MessageReader<WaveStarted>,
+    banners: Query<Entity, With<WaveBanner>>,
+    theme: Res<UiTheme>,
+) {
+    let Some(event) = started_events.read().last() else {
+        return;
+    };
+    for entity in banners.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(120.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            WaveBanner {
+                timer: Timer::from_seconds(3.0, TimerMode::Once),
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("WAVE {}", event.wave)),
+                theme.font(TextRole::Title),
+                ThemedText(TextRole::Title),
+                TextColor(theme.accent),
+            ));
+            if event.checkpoint {
+                parent.spawn((
+                    Text::new("CHECKPOINT REACHED"),
+                    theme.font(TextRole::Body),
+                    ThemedText(TextRole::Body),
+                    TextColor(Color::srgb(0.4, 1.0, 0.5)),
+                ));
+            }
+        });
+}
+
+/// Held back until the kill-cam on the wave's last zombie has played out. Graded
+/// waves stay up longer, with the grade and what it was made of underneath.
+fn show_cleared_banner(
+    mut commands: Commands,
+    mut cleared_events: MessageReader<WaveCleared>,
+    reports: Res<WaveReports>,
+    mut pending: Local<Option<u32>>,
+    kill_cams: Query<(), With<KillCam>>,
+    banners: Query<Entity, With<WaveBanner>>,
+    theme: Res<UiTheme>,
+) {
+    if let Some(event) = cleared_events.read().last() {
+        *pending = Some(event.wave);
+    }
+    if !kill_cams.is_empty() {
+        return;
+    }
+    let Some(wave) = pending.take() else {
+        return;
+    };
+    for entity in banners.iter() {
+        commands.entity(entity).despawn();
+    }
+    let report = reports.last.as_ref().filter(|report| report.wave == wave);
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(120.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            WaveBanner {
+                timer: Timer::from_seconds(
+                    if report.is_some() { 5.0 } else { 3.0 },
+                    TimerMode::Once,
+                ),
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("WAVE {wave} CLEARED")),
+                theme.font(TextRole::Title),
+                ThemedText(TextRole::Title),
+                TextColor(Color::srgb(0.4, 1.0, 0.5)),
+            ));
+            let Some(report) = report else {
+                return;
+            };
+            parent.spawn((
+                Text::new(format!(
+                    "GRADE {}  ({}/100)",
+                    report.grade.name(),
+                    report.points
+                )),
+                theme.font(TextRole::Heading),
+                ThemedText(TextRole::Heading),
+                TextColor(report.grade.color()),
+            ));
+            let tally = &report.tally;
+            parent.spawn((
+                Text::new(format!(
+                    "Accuracy {:.0}%  -  Damage taken {:.0}  -  Time {:.0}s  -  Headshots {:.0}%",
+                    tally.accuracy() * 100.0,
+                    tally.stats.damage_taken,
+                    tally.seconds,
+                    tally.headshot_ratio() * 100.0
+                )),
+                theme.text(TextRole::Caption),
+            ));
+            let mut rewards = Vec::new();
+            if report.bonus > 0 {
+                rewards.push(format!("+{} pts", report.bonus));
+            }
+            if report.discount > 0.0 {
+                rewards.push(format!("{:.0}% off in the shop", report.discount * 100.0));
+            }
+            if !rewards.is_empty() {
+                parent.spawn((
+                    Text::new(rewards.join("  -  ")),
+                    theme.font(TextRole::Body),
+                    ThemedText(TextRole::Body),
+                    TextColor(theme.accent),
+                ));
+            }
+        });
+}
+
+fn fade_wave_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut WaveBanner, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut banner, children) in banners.iter_mut() {
+        banner.timer.tick(time.delta());
+        if banner.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Fade out over the last second
+        let alpha = banner.timer.remaining_secs().min(1.0);
+        for child in children.iter() {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
+
+fn despawn_wave_banner(mut commands: Commands, banners: Query<Entity, With<WaveBanner>>) {
+    for entity in banners.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn reset_waves(mut waves: ResMut<WaveState>) {
+    *waves = WaveState::default();
+}
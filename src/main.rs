@@ -1,46 +1,154 @@
+// Bevy systems take their resources and queries as arguments, and player::player and
+// world::world predate the module split
+#![allow(
+    clippy::type_complexity,
+    clippy::too_many_arguments,
+    clippy::module_inception
+)]
+
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
 use bevy::window::WindowResolution;
 use bevy_rapier3d::prelude::*;
 
 mod combat;
+#[cfg(feature = "dev_console")]
+mod console;
 mod enemies;
 mod player;
+mod save;
 mod ui;
 mod world;
 
-use combat::{ShootingPlugin, WeaponUiPlugin};
-use enemies::{EnemyPlugin, TargetPlugin};
-use player::{CameraPlugin, PlayerPlugin};
-use ui::MenuPlugin;
-use world::{NavGridPlugin, WorldPlugin};
+use combat::{
+    AmmoPlugin, AttachmentPlugin, ChainLightningPlugin, FlarePlugin, GrenadePlugin,
+    HitFeedbackPlugins, PickupHighlightPlugin, ShootingPlugin, StatusEffectPlugin,
+    TargetHighlightPlugin, WeaponConditionPlugin, WeaponPickupPlugin, WeaponUiPlugin,
+    WeaponWheelPlugin,
+};
+use enemies::{
+    DirectorPlugin, DpsMeterPlugin, DrillPlugin, EnemyPlugin, ExtractionPlugin,
+    ShootingRangePlugin, SpawnerPlugin, SquadPlugin, TargetPlugin, WavePlugin,
+};
+use player::{
+    AllyPlugin, ArmorPlugin, BulletTimePlugin, CameraPlugin, CompanionPlugin, FeedbackPlugin,
+    FlinchPlugin, FootprintPlugin, FootstepAudioPlugin, KillCamPlugin, PhotoModePlugin,
+    PlayerActionsPlugin, PlayerPlugin, PlayerRigPlugin, ProgressionPlugin, ShovePlugin,
+    WeaponAnimationPlugin, WeaponSwayPlugin,
+};
+use save::{CheckpointPlugin, SavePlugin};
+use ui::{
+    AccessibilityPlugin, AudioBusPlugin, CompassPlugin, CountdownPlugin, CursorPlugin,
+    CutscenePlugin, HighScoresPlugin, LoadoutPlugin, MenuFocusPlugin, MenuPlugin, PerkSelectPlugin,
+    ShopPlugin, UiThemePlugin, UnlocksPlugin,
+};
+use world::{
+    BalancePlugin, BossArenaPlugin, DissolvePlugin, EntityBudgetPlugin, FixedTimestepPlugin,
+    HazardPlugin, NavGridPlugin, SpatialIndexPlugin, WorldPlugin,
+};
 
 fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "My Bevy Game".into(),
-                        resolution: WindowResolution::new(1920, 1080),
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .set(AssetPlugin {
-                    meta_check: AssetMetaCheck::Never,
-                    ..default()
-                }),
-            RapierPhysicsPlugin::<NoUserData>::default(),
-            NavGridPlugin,
-            MenuPlugin,
-            PlayerPlugin,
-            CameraPlugin,
-            WorldPlugin,
-            ShootingPlugin,
-            TargetPlugin,
-            EnemyPlugin,
-            WeaponUiPlugin,
-        ))
-        .run();
+    let mut app = App::new();
+    let default_plugins = DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "My Bevy Game".into(),
+                resolution: WindowResolution::new(1920, 1080),
+                // On the web, follow the page's canvas size so UiScale tracks resizes
+                fit_canvas_to_parent: true,
+                ..default()
+            }),
+            ..default()
+        })
+        .set(AssetPlugin {
+            meta_check: AssetMetaCheck::Never,
+            ..default()
+        });
+    #[cfg(feature = "dev_console")]
+    let default_plugins = console::headless_for_stress_bench(default_plugins);
+
+    app.add_plugins((
+        default_plugins,
+        FixedTimestepPlugin,
+        RapierPhysicsPlugin::<NoUserData>::default().in_schedule(FixedPostUpdate),
+        NavGridPlugin,
+        MenuPlugin,
+        PlayerPlugin,
+        CameraPlugin,
+        PhotoModePlugin,
+        WorldPlugin,
+        ShootingPlugin,
+        TargetPlugin,
+        ShootingRangePlugin,
+        EnemyPlugin,
+        WeaponUiPlugin,
+        HitFeedbackPlugins,
+    ))
+    .add_plugins((
+        PlayerRigPlugin,
+        ArmorPlugin,
+        FlarePlugin,
+        WavePlugin,
+        SavePlugin,
+        CheckpointPlugin,
+        AccessibilityPlugin,
+        CursorPlugin,
+        CountdownPlugin,
+        PlayerActionsPlugin,
+        EntityBudgetPlugin,
+        LoadoutPlugin,
+        WeaponPickupPlugin,
+        BulletTimePlugin,
+    ))
+    .add_plugins((
+        CompanionPlugin,
+        AttachmentPlugin,
+        ProgressionPlugin,
+        PerkSelectPlugin,
+        StatusEffectPlugin,
+        HazardPlugin,
+        ShovePlugin,
+        WeaponSwayPlugin,
+        SpawnerPlugin,
+        ChainLightningPlugin,
+        FlinchPlugin,
+        CutscenePlugin,
+        BossArenaPlugin,
+        TargetHighlightPlugin,
+        AmmoPlugin,
+    ))
+    .add_plugins((
+        DpsMeterPlugin,
+        FeedbackPlugin,
+        ShopPlugin,
+        AudioBusPlugin,
+        HighScoresPlugin,
+        AllyPlugin,
+        FootprintPlugin,
+        WeaponConditionPlugin,
+        KillCamPlugin,
+        SquadPlugin,
+        UnlocksPlugin,
+        BalancePlugin,
+        CompassPlugin,
+        ExtractionPlugin,
+    ))
+    .add_plugins((
+        SpatialIndexPlugin,
+        WeaponAnimationPlugin,
+        PickupHighlightPlugin,
+        GrenadePlugin,
+        FootstepAudioPlugin,
+        UiThemePlugin,
+        WeaponWheelPlugin,
+        DirectorPlugin,
+        DrillPlugin,
+        DissolvePlugin,
+        MenuFocusPlugin,
+    ));
+
+    #[cfg(feature = "dev_console")]
+    app.add_plugins((console::ConsolePlugin, save::ReplayPlugin));
+
+    app.run();
 }
@@ -0,0 +1,264 @@
+use super::{BulletTime, Flinch, Player, PlayerActions};
+use crate::combat::{
+    BufferedAction, BurstState, ChargingState, InputBuffer, QueuedShot, ReloadState,
+};
+use bevy::prelude::*;
+
+/// Seconds it takes to bring the weapon down out of a sprint before a shot goes off
+pub const SPRINT_LOWER_TIME: f32 = 0.25;
+/// Reload speed while sprinting, as a share of the usual
+pub const SPRINT_RELOAD_RATE: f32 = 0.8;
+/// Flinch at which a hit staggers the player out of a sprint
+const STAGGER_FLINCH: f32 = 0.6;
+
+/// What the player's hands and feet are busy with, which decides what else they can do
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ActionState {
+    #[default]
+    Idle,
+    /// Moving at sprint speed, with the weapon down so it can't fire
+    Sprinting,
+    /// Trigger held, a burst or charge going, or lowering out of a sprint to shoot
+    Firing,
+    /// Reloads carry on through a sprint, just slower
+    Reloading { sprinting: bool },
+    /// Shaken by a heavy hit (see Flinch); no sprinting until it wears off
+    Staggered,
+}
+
+impl ActionState {
+    /// Whether the player moves at sprint speed
+    pub fn sprinting(&self) -> bool {
+        matches!(
+            self,
+            ActionState::Sprinting | ActionState::Reloading { sprinting: true }
+        )
+    }
+}
+
+/// What an ActionState transition depends on
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ActionInputs {
+    /// Sprint held while moving
+    pub sprint: bool,
+    /// Fire pressed this frame, or still waiting in the InputBuffer
+    pub fire_pressed: bool,
+    pub fire_held: bool,
+    pub reloading: bool,
+    /// A burst or railgun charge still going
+    pub firing: bool,
+    pub staggered: bool,
+}
+
+/// The player's ActionState, moved on once a frame by update_action_state. Movement
+/// reads it to pick a speed, and the combat systems to decide whether a shot can go
+/// and how fast a reload runs.
+#[derive(Component, Default, Debug)]
+pub struct PlayerActionState {
+    pub state: ActionState,
+    /// Seconds left lowering out of a sprint, with a shot waiting on it
+    lowering: f32,
+}
+
+impl PlayerActionState {
+    /// Sprinting holds fire until the weapon has been lowered
+    pub fn can_fire(&self) -> bool {
+        self.state != ActionState::Sprinting && self.lowering <= 0.0
+    }
+
+    /// Share of the usual speed a reload runs at
+    pub fn reload_rate(&self) -> f32 {
+        if self.state.sprinting() {
+            SPRINT_RELOAD_RATE
+        } else {
+            1.0
+        }
+    }
+
+    /// Move on by `dt` seconds. Returns true once lowering out of a sprint has
+    /// finished, when the shot waiting on it should go off.
+    pub fn advance(&mut self, inputs: ActionInputs, dt: f32) -> bool {
+        let wants_fire = inputs.fire_pressed || inputs.fire_held;
+        let mut lowered = false;
+        if self.lowering > 0.0 {
+            self.lowering = (self.lowering - dt).max(0.0);
+            lowered = self.lowering == 0.0;
+        } else if wants_fire && self.state.sprinting() && !inputs.reloading && !inputs.staggered {
+            self.lowering = SPRINT_LOWER_TIME;
+        }
+
+        self.state = if inputs.staggered {
+            ActionState::Staggered
+        } else if inputs.reloading {
+            // Sprinting mid-reload doesn't cancel it, see reload_rate
+            ActionState::Reloading {
+                sprinting: inputs.sprint,
+            }
+        } else if wants_fire || inputs.firing || self.lowering > 0.0 || lowered {
+            ActionState::Firing
+        } else if inputs.sprint {
+            ActionState::Sprinting
+        } else {
+            ActionState::Idle
+        };
+        lowered
+    }
+}
+
+/// Registered in the shooting chain, between buffering the frame's presses and the
+/// systems acting on them, so a fire press from a sprint is seen before shoot drops it
+pub fn update_action_state(
+    mut commands: Commands,
+    time: Res<Time>,
+    bullet_time: Res<BulletTime>,
+    actions: Res<PlayerActions>,
+    buffer: Res<InputBuffer>,
+    mut players: Query<
+        (
+            Entity,
+            &mut PlayerActionState,
+            Option<&Flinch>,
+            Has<ReloadState>,
+            Has<BurstState>,
+            Has<ChargingState>,
+        ),
+        With<Player>,
+    >,
+) {
+    let Ok((entity, mut action_state, flinch, reloading, bursting, charging)) =
+        players.single_mut()
+    else {
+        return;
+    };
+    let inputs = ActionInputs {
+        sprint: actions.sprint && actions.movement != Vec2::ZERO,
+        fire_pressed: actions.fire_pressed || buffer.has(BufferedAction::Fire),
+        fire_held: actions.fire_held,
+        reloading,
+        firing: bursting || charging,
+        staggered: flinch.is_some_and(|flinch| flinch.amount >= STAGGER_FLINCH),
+    };
+    // The press may have aged out of the InputBuffer by now, so queue the shot
+    if action_state.advance(inputs, bullet_time.player_delta(&time)) {
+        commands.entity(entity).insert(QueuedShot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Half the lowering time, exactly
+    const STEP: f32 = SPRINT_LOWER_TIME / 2.0;
+
+    const SPRINT: ActionInputs = ActionInputs {
+        sprint: true,
+        fire_pressed: false,
+        fire_held: false,
+        reloading: false,
+        firing: false,
+        staggered: false,
+    };
+
+    fn sprinting() -> PlayerActionState {
+        let mut state = PlayerActionState::default();
+        state.advance(SPRINT, STEP);
+        state
+    }
+
+    #[test]
+    fn sprinting_holds_fire() {
+        let state = sprinting();
+        assert_eq!(state.state, ActionState::Sprinting);
+        assert!(!state.can_fire());
+    }
+
+    #[test]
+    fn firing_from_a_sprint_waits_for_the_weapon_to_lower() {
+        let mut state = sprinting();
+        let fire = ActionInputs {
+            fire_pressed: true,
+            ..SPRINT
+        };
+        assert!(!state.advance(fire, STEP));
+        assert_eq!(state.state, ActionState::Firing);
+        assert!(!state.can_fire());
+
+        // Let go of fire; the shot still goes once lowered
+        assert!(!state.advance(SPRINT, STEP));
+        assert!(!state.can_fire());
+        assert!(state.advance(SPRINT, STEP));
+        assert!(state.can_fire());
+        assert_eq!(state.state, ActionState::Firing);
+
+        // And then back to the sprint
+        assert!(!state.advance(SPRINT, STEP));
+        assert_eq!(state.state, ActionState::Sprinting);
+    }
+
+    #[test]
+    fn firing_from_a_standstill_goes_straight_away() {
+        let mut state = PlayerActionState::default();
+        let fire = ActionInputs {
+            fire_pressed: true,
+            ..default()
+        };
+        assert!(!state.advance(fire, STEP));
+        assert_eq!(state.state, ActionState::Firing);
+        assert!(state.can_fire());
+    }
+
+    #[test]
+    fn reloading_carries_on_through_a_sprint_slower() {
+        let mut state = PlayerActionState::default();
+        let reload = ActionInputs {
+            reloading: true,
+            ..default()
+        };
+        state.advance(reload, STEP);
+        assert_eq!(state.state, ActionState::Reloading { sprinting: false });
+        assert_eq!(state.reload_rate(), 1.0);
+
+        // Breaking into a sprint mid-reload keeps the reload, at the sprint rate
+        state.advance(
+            ActionInputs {
+                sprint: true,
+                ..reload
+            },
+            STEP,
+        );
+        assert_eq!(state.state, ActionState::Reloading { sprinting: true });
+        assert!(state.state.sprinting());
+        assert_eq!(state.reload_rate(), SPRINT_RELOAD_RATE);
+    }
+
+    #[test]
+    fn fire_during_a_sprint_reload_does_not_lower() {
+        let mut state = sprinting();
+        state.advance(
+            ActionInputs {
+                reloading: true,
+                fire_pressed: true,
+                ..SPRINT
+            },
+            STEP,
+        );
+        assert_eq!(state.state, ActionState::Reloading { sprinting: true });
+        assert!(state.can_fire());
+    }
+
+    #[test]
+    fn a_stagger_cuts_the_sprint() {
+        let mut state = sprinting();
+        state.advance(
+            ActionInputs {
+                staggered: true,
+                ..SPRINT
+            },
+            STEP,
+        );
+        assert_eq!(state.state, ActionState::Staggered);
+        assert!(!state.state.sprinting());
+        assert!(state.can_fire());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::ui::GameState;
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::input::InputSystems;
+use bevy::prelude::*;
+
+/// Turns raw keyboard and mouse state into PlayerActions once per frame, so
+/// gameplay never reads devices directly and a replay can stand in for them
+pub struct PlayerActionsPlugin;
+
+impl Plugin for PlayerActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerActions>()
+            .init_resource::<FireSuppressed>()
+            .add_systems(
+                PreUpdate,
+                (gather_player_actions, suppress_fire)
+                    .chain()
+                    .in_set(PlayerActionsSet)
+                    .after(InputSystems),
+            )
+            .configure_sets(PreUpdate, PlayerActionsClaimSet.after(PlayerActionsSet))
+            .add_systems(OnEnter(GameState::Playing), hold_fire);
+    }
+}
+
+/// Set on entering Playing and cleared once the fire button is up, so the click on
+/// Resume, a perk or a countdown that starts play can't also fire a shot. Overlays
+/// that back out on a click set it too.
+#[derive(Resource, Default)]
+pub struct FireSuppressed(pub bool);
+
+/// Everything the player asked for this frame
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq)]
+pub struct PlayerActions {
+    /// x is strafe right, y is forward; each axis is -1, 0 or 1
+    pub movement: Vec2,
+    /// Mouse movement in pixels
+    pub look: Vec2,
+    /// Wheel movement in lines (up is positive)
+    pub scroll: f32,
+    pub sprint: bool,
+    pub free_look: bool,
+    pub fire_held: bool,
+    pub fire_pressed: bool,
+    /// Held to aim down sights; for now this only changes the audio mix
+    pub aim: bool,
+    pub reload: bool,
+    /// Held to clear a jammed weapon
+    pub reload_held: bool,
+    pub interact: bool,
+    /// Held to swap the weapon in hand for one on the ground
+    pub interact_held: bool,
+    pub shoulder_swap: bool,
+    pub throw_flare: bool,
+    /// Pull a grenade's pin; it's thrown once the key is let go
+    pub grenade: bool,
+    pub grenade_held: bool,
+    /// Held to keep bullet time going
+    pub bullet_time: bool,
+    /// Park the companion drone, or call it back
+    pub toggle_drone: bool,
+    /// Switch ammo type on weapons that have more than one
+    pub toggle_ammo: bool,
+    /// Push back the zombies right in front
+    pub shove: bool,
+    /// Look the weapon over; holding it keeps the weapon turned
+    pub inspect: bool,
+    pub inspect_held: bool,
+    /// Weapon slot picked with the number keys
+    pub select_slot: Option<usize>,
+    /// Held to bring up the weapon wheel
+    pub weapon_wheel: bool,
+}
+
+/// Set containing the device read; anything that rewrites PlayerActions runs after it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerActionsSet;
+
+/// Set for overlays that take input away from gameplay, such as the weapon wheel. It
+/// runs once this frame's actions are final, whether read from devices or a replay, so
+/// a replay records what was pressed and the overlay plays back the same way.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerActionsClaimSet;
+
+const SLOT_KEYS: [KeyCode; 6] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+];
+
+fn gather_player_actions(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut actions: ResMut<PlayerActions>,
+) {
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        keys.pressed(positive) as i32 as f32 - keys.pressed(negative) as i32 as f32
+    };
+
+    *actions = PlayerActions {
+        movement: Vec2::new(
+            axis(KeyCode::KeyD, KeyCode::KeyA),
+            axis(KeyCode::KeyW, KeyCode::KeyS),
+        ),
+        look: mouse_motion.delta,
+        scroll: match scroll.unit {
+            MouseScrollUnit::Line => scroll.delta.y,
+            MouseScrollUnit::Pixel => scroll.delta.y * 0.01,
+        },
+        sprint: keys.pressed(KeyCode::ShiftLeft),
+        free_look: keys.pressed(KeyCode::AltLeft),
+        fire_held: mouse_button.pressed(MouseButton::Left),
+        fire_pressed: mouse_button.just_pressed(MouseButton::Left),
+        aim: mouse_button.pressed(MouseButton::Right),
+        reload: keys.just_pressed(KeyCode::KeyR),
+        reload_held: keys.pressed(KeyCode::KeyR),
+        interact: keys.just_pressed(KeyCode::KeyE),
+        interact_held: keys.pressed(KeyCode::KeyE),
+        shoulder_swap: keys.just_pressed(KeyCode::KeyX),
+        throw_flare: keys.just_pressed(KeyCode::KeyG),
+        grenade: keys.just_pressed(KeyCode::KeyT),
+        grenade_held: keys.pressed(KeyCode::KeyT),
+        bullet_time: keys.pressed(KeyCode::KeyQ),
+        toggle_drone: keys.just_pressed(KeyCode::KeyV),
+        toggle_ammo: keys.just_pressed(KeyCode::KeyB),
+        shove: keys.just_pressed(KeyCode::KeyF),
+        inspect: keys.just_pressed(KeyCode::KeyI),
+        inspect_held: keys.pressed(KeyCode::KeyI),
+        select_slot: SLOT_KEYS.iter().position(|key| keys.just_pressed(*key)),
+        weapon_wheel: keys.pressed(KeyCode::Tab),
+    };
+}
+
+/// The transition runs after this frame's actions were gathered, so clear them here too
+fn hold_fire(mut suppressed: ResMut<FireSuppressed>, mut actions: ResMut<PlayerActions>) {
+    suppressed.0 = true;
+    actions.fire_held = false;
+    actions.fire_pressed = false;
+}
+
+fn suppress_fire(mut suppressed: ResMut<FireSuppressed>, mut actions: ResMut<PlayerActions>) {
+    if !suppressed.0 {
+        return;
+    }
+    if actions.fire_held {
+        actions.fire_held = false;
+        actions.fire_pressed = false;
+    } else {
+        suppressed.0 = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{boot, headless_app};
+
+    /// Paused, with the devices as resources the test presses directly
+    fn paused_app() -> App {
+        let mut app = headless_app(0);
+        app.add_plugins(PlayerActionsPlugin)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<AccumulatedMouseMotion>()
+            .init_resource::<AccumulatedMouseScroll>();
+        boot(&mut app);
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Paused);
+        app.update();
+        app
+    }
+
+    fn mouse(app: &mut App) -> Mut<'_, ButtonInput<MouseButton>> {
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>()
+    }
+
+    fn actions(app: &App) -> PlayerActions {
+        *app.world().resource::<PlayerActions>()
+    }
+
+    #[test]
+    fn the_click_on_resume_does_not_fire() {
+        let mut app = paused_app();
+        mouse(&mut app).press(MouseButton::Left);
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Playing
+        );
+        assert!(!actions(&app).fire_pressed);
+        assert!(!actions(&app).fire_held);
+
+        // Still held from the menu: no fire
+        mouse(&mut app).clear();
+        app.update();
+        assert!(!actions(&app).fire_held);
+    }
+
+    #[test]
+    fn fire_comes_back_once_the_button_is_let_go() {
+        let mut app = paused_app();
+        mouse(&mut app).press(MouseButton::Left);
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+
+        mouse(&mut app).release(MouseButton::Left);
+        app.update();
+        assert!(!app.world().resource::<FireSuppressed>().0);
+
+        mouse(&mut app).clear();
+        mouse(&mut app).press(MouseButton::Left);
+        app.update();
+        assert!(actions(&app).fire_pressed);
+        assert!(actions(&app).fire_held);
+    }
+}
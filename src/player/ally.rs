@@ -0,0 +1,397 @@
+use crate::combat::{HitEvent, HitSystems, Weapon, WeaponType};
+use crate::enemies::{Team, Zombie};
+use crate::ui::{AudioBus, GameState, Subtitle};
+use crate::world::{BalanceData, BudgetCategory, Budgeted, PhysicsLayer, SurfaceMaterial};
+use bevy::audio::{Pitch, Volume};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::time::Duration;
+
+/// Practice bot: a friendly capsule that holds its ground and shoots the nearest
+/// zombie it can see. It's on the Survivors team, so zombies go after it as readily
+/// as the player. It beeps for its last few seconds and powers down after a minute.
+pub struct AllyPlugin;
+
+impl Plugin for AllyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_ally_assets)
+            .add_systems(
+                Update,
+                (
+                    ally_take_hits.in_set(HitSystems::Damage),
+                    ally_fire,
+                    ally_lifetime,
+                    update_ally_health_bars,
+                    fade_ally_tracers,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_allies,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_allies,
+            );
+    }
+}
+
+const ALLY_MAX_HEALTH: f32 = 120.0;
+const ALLY_LIFETIME: f32 = 60.0;
+/// The bot beeps once a second for this long before it powers down
+const ALLY_WARNING_TIME: f32 = 5.0;
+/// Furthest zombie it will shoot at
+const ALLY_RANGE: f32 = 20.0;
+/// Added to the weapon's own fire interval; the bot is a slower shot than the player
+const ALLY_AIM_TIME: f32 = 0.6;
+/// Muzzle height above the bot's centre
+const MUZZLE_HEIGHT: f32 = 0.5;
+const TRACER_TIME: f32 = 0.08;
+const BEEP_FREQUENCY: f32 = 880.0;
+const BEEP_LENGTH: f32 = 0.12;
+
+#[derive(Component)]
+pub struct Ally {
+    pub health: f32,
+    /// Damage per shot and fire rate come from here
+    pub weapon: Weapon,
+    fire: Timer,
+    lifetime: Timer,
+    beep: Timer,
+    /// Has started its power-down warning
+    warned: bool,
+}
+
+/// Overhead bar piece of the ally it belongs to
+#[derive(Component)]
+struct AllyHealthBar(Entity);
+
+#[derive(Component)]
+struct AllyHealthBarFill;
+
+#[derive(Component)]
+struct AllyTracer {
+    timer: Timer,
+}
+
+#[derive(Resource)]
+pub struct AllyAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    health_bar_bg_mesh: Handle<Mesh>,
+    health_bar_fill_mesh: Handle<Mesh>,
+    health_bar_bg_material: Handle<StandardMaterial>,
+    health_bar_fill_material: Handle<StandardMaterial>,
+    tracer_mesh: Handle<Mesh>,
+    tracer_material: Handle<StandardMaterial>,
+    beep: Handle<Pitch>,
+}
+
+fn setup_ally_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    commands.insert_resource(AllyAssets {
+        mesh: meshes.add(Capsule3d::new(0.4, 1.2)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.25, 0.4, 0.8),
+            ..default()
+        }),
+        health_bar_bg_mesh: meshes.add(Cuboid::new(0.8, 0.1, 0.05)),
+        health_bar_fill_mesh: meshes.add(Cuboid::new(0.75, 0.08, 0.06)),
+        health_bar_bg_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.2, 0.2),
+            unlit: true,
+            ..default()
+        }),
+        health_bar_fill_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.6, 1.0),
+            unlit: true,
+            ..default()
+        }),
+        // Unit length, stretched along the shot when spawned
+        tracer_mesh: meshes.add(Cylinder::new(0.015, 1.0)),
+        tracer_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.9, 0.5),
+            emissive: LinearRgba::rgb(4.0, 3.0, 1.0),
+            unlit: true,
+            ..default()
+        }),
+        beep: pitches.add(Pitch::new(
+            BEEP_FREQUENCY,
+            Duration::from_secs_f32(BEEP_LENGTH),
+        )),
+    });
+}
+
+/// Stand a practice bot at `pos` (capsule centre), facing along `facing`. Only the
+/// console summons them for now.
+#[cfg_attr(not(feature = "dev_console"), allow(dead_code))]
+pub fn spawn_ally(
+    commands: &mut Commands,
+    assets: &AllyAssets,
+    balance: &BalanceData,
+    pos: Vec3,
+    facing: Vec3,
+) -> Entity {
+    let weapon = Weapon::new(WeaponType::Pistol, balance);
+    let interval = 1.0 / weapon.fire_rate + ALLY_AIM_TIME;
+    let ally = commands
+        .spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation(pos).looking_to(facing.with_y(0.0), Vec3::Y),
+            Ally {
+                health: ALLY_MAX_HEALTH,
+                weapon,
+                fire: Timer::from_seconds(interval, TimerMode::Repeating),
+                lifetime: Timer::from_seconds(ALLY_LIFETIME, TimerMode::Once),
+                beep: Timer::from_seconds(1.0, TimerMode::Repeating),
+                warned: false,
+            },
+            Team::Survivors,
+            RigidBody::Fixed,
+            Collider::capsule_y(0.6, 0.4),
+            PhysicsLayer::Player.groups(),
+            Name::new("Practice bot"),
+        ))
+        .id();
+
+    commands.spawn((
+        Mesh3d(assets.health_bar_bg_mesh.clone()),
+        MeshMaterial3d(assets.health_bar_bg_material.clone()),
+        Transform::from_translation(pos + Vec3::Y * 1.5),
+        AllyHealthBar(ally),
+    ));
+    commands.spawn((
+        Mesh3d(assets.health_bar_fill_mesh.clone()),
+        MeshMaterial3d(assets.health_bar_fill_material.clone()),
+        Transform::from_translation(pos + Vec3::Y * 1.5),
+        AllyHealthBar(ally),
+        AllyHealthBarFill,
+    ));
+    ally
+}
+
+/// Remove an ally along with its health bar
+fn despawn_ally(commands: &mut Commands, ally: Entity, bars: &Query<(Entity, &AllyHealthBar)>) {
+    for (bar, owner) in bars.iter() {
+        if owner.0 == ally {
+            commands.entity(bar).despawn();
+        }
+    }
+    commands.entity(ally).despawn();
+}
+
+/// Zombie bites arrive as hits
+fn ally_take_hits(
+    mut commands: Commands,
+    mut hit_events: MessageReader<HitEvent>,
+    mut allies: Query<&mut Ally>,
+    bars: Query<(Entity, &AllyHealthBar)>,
+    mut subtitles: MessageWriter<Subtitle>,
+) {
+    for event in hit_events.read() {
+        let Ok(mut ally) = allies.get_mut(event.entity) else {
+            continue;
+        };
+        // Several bites can land on the same frame; only the first kill counts
+        if ally.health <= 0.0 {
+            continue;
+        }
+        ally.health -= event.damage;
+        if ally.health <= 0.0 {
+            despawn_ally(&mut commands, event.entity, &bars);
+            subtitles.write(Subtitle("[Practice bot destroyed]".to_string()));
+        }
+    }
+}
+
+/// Shoot the nearest zombie in range with a clear line to it
+fn ally_fire(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<AllyAssets>,
+    rapier_context: ReadRapierContext,
+    mut allies: Query<(Entity, &mut Transform, &mut Ally)>,
+    zombies: Query<(Entity, &Transform), (With<Zombie>, Without<Ally>)>,
+    mut hit_events: MessageWriter<HitEvent>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+
+    for (ally_entity, mut transform, mut ally) in allies.iter_mut() {
+        ally.fire.tick(time.delta());
+        if !ally.fire.just_finished() {
+            continue;
+        }
+
+        let muzzle = transform.translation + Vec3::Y * MUZZLE_HEIGHT;
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(ally_entity)
+            .exclude_sensors();
+        let mut candidates: Vec<(Entity, Vec3, f32)> = zombies
+            .iter()
+            .map(|(entity, zombie)| {
+                let aim = zombie.translation + Vec3::Y * 0.2;
+                (entity, aim, aim.distance(muzzle))
+            })
+            .filter(|(.., distance)| *distance <= ALLY_RANGE)
+            .collect();
+        candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+        // First zombie, nearest first, that the shot would actually reach
+        let shot = candidates.into_iter().find_map(|(target, aim, distance)| {
+            let direction = (aim - muzzle) / distance;
+            let mut reached = None;
+            context.with_query_pipeline(filter, |query_pipeline| {
+                if let Some((hit, toi)) =
+                    query_pipeline.cast_ray(muzzle, direction, distance + 0.5, true)
+                {
+                    if hit == target {
+                        reached = Some((target, direction, toi));
+                    }
+                }
+            });
+            reached
+        });
+        let Some((target, direction, distance)) = shot else {
+            continue;
+        };
+
+        let facing = direction.with_y(0.0);
+        if facing.length_squared() > 0.001 {
+            transform.look_to(facing, Vec3::Y);
+        }
+        let point = muzzle + direction * distance;
+        hit_events.write(HitEvent {
+            entity: target,
+            damage: ally.weapon.damage,
+            direction,
+            point,
+            normal: -direction,
+            distance,
+            source: Some(ally_entity),
+            zone: None,
+            surface: SurfaceMaterial::default(),
+            headshot: false,
+            crit: false,
+        });
+
+        commands.spawn((
+            Mesh3d(assets.tracer_mesh.clone()),
+            MeshMaterial3d(assets.tracer_material.clone()),
+            Transform::from_translation((muzzle + point) / 2.0)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Y, direction))
+                .with_scale(Vec3::new(1.0, distance, 1.0)),
+            AllyTracer {
+                timer: Timer::from_seconds(TRACER_TIME, TimerMode::Once),
+            },
+            Budgeted(BudgetCategory::Tracer),
+        ));
+    }
+}
+
+/// Beep through the last few seconds, then power down
+fn ally_lifetime(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<AllyAssets>,
+    mut allies: Query<(Entity, &mut Ally)>,
+    bars: Query<(Entity, &AllyHealthBar)>,
+    mut subtitles: MessageWriter<Subtitle>,
+) {
+    for (entity, mut ally) in allies.iter_mut() {
+        ally.lifetime.tick(time.delta());
+        if ally.lifetime.is_finished() {
+            despawn_ally(&mut commands, entity, &bars);
+            subtitles.write(Subtitle("[Practice bot powers down]".to_string()));
+            continue;
+        }
+        if ally.lifetime.remaining_secs() > ALLY_WARNING_TIME {
+            continue;
+        }
+
+        // First beep as soon as the warning starts, then once a second
+        let beep = if ally.warned {
+            ally.beep.tick(time.delta()).just_finished()
+        } else {
+            ally.warned = true;
+            subtitles.write(Subtitle("[Practice bot beeping]".to_string()));
+            true
+        };
+        if beep {
+            commands.spawn((
+                AudioPlayer(assets.beep.clone()),
+                PlaybackSettings::DESPAWN.with_volume(Volume::Linear(0.3)),
+                AudioBus::Sfx,
+            ));
+        }
+    }
+}
+
+fn update_ally_health_bars(
+    allies: Query<(&Transform, &Ally)>,
+    camera_query: Query<&Transform, With<Camera3d>>,
+    mut bars: Query<
+        (&mut Transform, &AllyHealthBar, Has<AllyHealthBarFill>),
+        (Without<Ally>, Without<Camera3d>),
+    >,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    for (mut bar_transform, owner, is_fill) in bars.iter_mut() {
+        let Ok((ally_transform, ally)) = allies.get(owner.0) else {
+            continue;
+        };
+        bar_transform.translation = ally_transform.translation + Vec3::Y * 1.5;
+        let look_dir = camera_transform.translation - bar_transform.translation;
+        if look_dir.length_squared() > 0.001 {
+            bar_transform.look_to(-look_dir, Vec3::Y);
+        }
+        if is_fill {
+            bar_transform.scale.x = (ally.health / ALLY_MAX_HEALTH).max(0.01);
+        }
+    }
+}
+
+fn fade_ally_tracers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tracers: Query<(Entity, &mut AllyTracer)>,
+) {
+    for (entity, mut tracer) in tracers.iter_mut() {
+        tracer.timer.tick(time.delta());
+        if tracer.timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn despawn_allies(
+    mut commands: Commands,
+    allies: Query<Entity, Or<(With<Ally>, With<AllyHealthBar>, With<AllyTracer>)>>,
+) {
+    for entity in allies.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Where a bot summoned by the player stands: a couple of metres ahead of them
+#[cfg_attr(not(feature = "dev_console"), allow(dead_code))]
+pub fn ally_spawn_point(player: &Transform) -> Vec3 {
+    let forward = player.forward().with_y(0.0).normalize_or_zero();
+    player.translation + forward * 2.0
+}
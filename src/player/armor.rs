@@ -0,0 +1,391 @@
+use super::{Flinch, Player, PlayerHealth};
+use crate::combat::{AmmoType, Attachment, Pickup, WeaponType};
+use crate::enemies::ZombieDied;
+use crate::ui::{in_wave_mode, DifficultyModifiers, GameState};
+use crate::world::{BalanceData, BudgetCategory, Budgeted, GameRng};
+use bevy::prelude::*;
+use rand::Rng;
+
+pub struct ArmorPlugin;
+
+impl Plugin for ArmorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<PickupCollected>()
+            .add_systems(Startup, setup_armor_plate_assets)
+            .add_systems(
+                Update,
+                (drop_armor_plates, spin_armor_plates, collect_armor_plates)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                (
+                    despawn_armor_plates,
+                    spawn_world_armor_plates.run_if(in_wave_mode),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                (
+                    despawn_armor_plates,
+                    spawn_world_armor_plates.run_if(in_wave_mode),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Soaks up part of incoming damage until depleted. Never regenerates; only plates refill it.
+#[derive(Component)]
+pub struct PlayerArmor {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for PlayerArmor {
+    fn default() -> Self {
+        Self {
+            current: 0.0,
+            max: 100.0,
+        }
+    }
+}
+
+/// Share of each hit taken by armor while it lasts
+pub const ARMOR_ABSORPTION: f32 = 0.6;
+const ARMOR_PICKUP_RADIUS: f32 = 1.2;
+/// Base chance of a kill leaving an armor plate, before the difficulty's drop modifier
+const ARMOR_DROP_CHANCE: f32 = 0.05;
+
+/// Fixed spots where plates lie around the arena at the start of a survival run
+const WORLD_ARMOR_PLATES: [Vec3; 3] = [
+    Vec3::new(-15.0, 0.4, 10.0),
+    Vec3::new(18.0, 0.4, -12.0),
+    Vec3::new(0.0, 0.4, 25.0),
+];
+
+/// Armor plate lying in the world, picked up by walking over it
+#[derive(Component)]
+#[require(Pickup)]
+pub struct ArmorPlate;
+
+/// Kinds of item the player can pick up
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PickupKind {
+    ArmorPlate,
+    Weapon(WeaponType),
+    Attachment(Attachment),
+    Ammo(AmmoType),
+    CleaningKit,
+    Backpack,
+}
+
+/// Sent when the player collects a pickup
+#[derive(Message)]
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    /// Where it lay; the kill feed only needs the kind
+    #[allow(dead_code)]
+    pub position: Vec3,
+}
+
+/// Shared mesh and material for armor plates
+#[derive(Resource)]
+pub struct ArmorPlateAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// What hit the player. Ordinary hits landing together are softened (see
+/// BurstMitigation); a boss's blows and explosions always land in full.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerDamageSource {
+    Zombie,
+    Boss,
+    Turret,
+    Explosion,
+}
+
+impl PlayerDamageSource {
+    pub fn mitigated(self) -> bool {
+        !matches!(
+            self,
+            PlayerDamageSource::Boss | PlayerDamageSource::Explosion
+        )
+    }
+}
+
+/// One hit on the player, for apply_player_damage
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerHit {
+    pub damage: f32,
+    /// How far it throws their aim (see Flinch)
+    pub flinch: f32,
+    pub source: PlayerDamageSource,
+    /// Time<Virtual> elapsed seconds when it landed
+    pub now: f64,
+}
+
+/// Softening for hits that land together, so a ring of zombies whose cooldowns line up
+/// can't take half the player's health in one frame. Within `window` seconds of each
+/// other the first hit lands in full, the second at `second_hit` and any more at
+/// `later_hits`. Set per difficulty.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BurstMitigation {
+    pub window: f32,
+    pub second_hit: f32,
+    pub later_hits: f32,
+}
+
+impl BurstMitigation {
+    /// Share of a hit at `now` that lands, given when the earlier softened hits came in
+    pub fn factor(&self, earlier: &[f64], now: f64) -> f32 {
+        let within = earlier
+            .iter()
+            .filter(|&&at| now - at < self.window as f64)
+            .count();
+        match within {
+            0 => 1.0,
+            1 => self.second_hit,
+            _ => self.later_hits,
+        }
+    }
+}
+
+/// Deal damage to the player, softened by `burst` if it bunches up with other hits,
+/// letting armor take its share first, and throw their aim (see Flinch). Returns the
+/// damage that got past the armor, 0.0 while invulnerable. Every damage source goes through here so the
+/// split lives in one place.
+pub fn apply_player_damage(
+    health: &mut PlayerHealth,
+    armor: Option<&mut PlayerArmor>,
+    flinch: Option<&mut Flinch>,
+    hit: PlayerHit,
+    burst: &BurstMitigation,
+) -> f32 {
+    if health.invulnerable {
+        return 0.0;
+    }
+    if let Some(flinch) = flinch {
+        flinch.add(hit.flinch);
+    }
+    let damage = if hit.source.mitigated() {
+        let window = burst.window as f64;
+        health.recent_hits.retain(|&at| hit.now - at < window);
+        let factor = burst.factor(&health.recent_hits, hit.now);
+        health.recent_hits.push(hit.now);
+        hit.damage * factor
+    } else {
+        hit.damage
+    };
+    let absorbed = match armor {
+        Some(armor) => {
+            let absorbed = (damage * ARMOR_ABSORPTION).min(armor.current);
+            armor.current -= absorbed;
+            absorbed
+        }
+        None => 0.0,
+    };
+    health.current = (health.current - (damage - absorbed)).max(0.0);
+    if damage > absorbed {
+        health.last_hurt = Some(hit.now);
+    }
+    damage - absorbed
+}
+
+/// Drop a plate at `position`, e.g. from a kill
+pub fn spawn_armor_plate(
+    commands: &mut Commands,
+    assets: &ArmorPlateAssets,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn((
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(assets.material.clone()),
+            Transform::from_translation(position.with_y(0.4)),
+            ArmorPlate,
+        ))
+        .id()
+}
+
+fn setup_armor_plate_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ArmorPlateAssets {
+        mesh: meshes.add(Cuboid::new(0.5, 0.6, 0.1)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.2, 0.4, 1.0),
+            emissive: LinearRgba::rgb(0.2, 0.5, 1.5),
+            ..default()
+        }),
+    });
+}
+
+fn spawn_world_armor_plates(mut commands: Commands, assets: Res<ArmorPlateAssets>) {
+    for position in WORLD_ARMOR_PLATES {
+        spawn_armor_plate(&mut commands, &assets, position);
+    }
+}
+
+/// Rare armor drop from kills
+fn drop_armor_plates(
+    mut commands: Commands,
+    mut died_events: MessageReader<ZombieDied>,
+    assets: Res<ArmorPlateAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in died_events.read() {
+        if rng.random::<f32>() < ARMOR_DROP_CHANCE * difficulty.drop_chance {
+            // Unlike the fixed world plates, drops expire if nobody takes them
+            let plate = spawn_armor_plate(&mut commands, &assets, event.position);
+            commands
+                .entity(plate)
+                .insert(Budgeted(BudgetCategory::Pickup));
+        }
+    }
+}
+
+fn spin_armor_plates(time: Res<Time>, mut plates: Query<&mut Transform, With<ArmorPlate>>) {
+    for mut transform in plates.iter_mut() {
+        transform.rotate_y(1.5 * time.delta_secs());
+    }
+}
+
+fn collect_armor_plates(
+    mut commands: Commands,
+    mut player_q: Query<(&Transform, &mut PlayerArmor), With<Player>>,
+    plates: Query<(Entity, &Transform), (With<ArmorPlate>, Without<Player>)>,
+    mut collected_events: MessageWriter<PickupCollected>,
+    balance: Res<BalanceData>,
+) {
+    let Ok((player_transform, mut armor)) = player_q.single_mut() else {
+        return;
+    };
+
+    for (entity, plate_transform) in plates.iter() {
+        let distance = (plate_transform.translation - player_transform.translation)
+            .with_y(0.0)
+            .length();
+        // Leave plates on the ground when already full
+        if distance < ARMOR_PICKUP_RADIUS && armor.current < armor.max {
+            armor.current = (armor.current + balance.pickups.armor_plate).min(armor.max);
+            commands.entity(entity).despawn();
+            collected_events.write(PickupCollected {
+                kind: PickupKind::ArmorPlate,
+                position: plate_transform.translation,
+            });
+        }
+    }
+}
+
+fn despawn_armor_plates(mut commands: Commands, plates: Query<Entity, With<ArmorPlate>>) {
+    for entity in plates.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BURST: BurstMitigation = BurstMitigation {
+        window: 0.5,
+        second_hit: 0.75,
+        later_hits: 0.5,
+    };
+
+    fn hit(source: PlayerDamageSource, now: f64) -> PlayerHit {
+        PlayerHit {
+            damage: 20.0,
+            flinch: 0.0,
+            source,
+            now,
+        }
+    }
+
+    #[test]
+    fn factor_softens_hits_inside_the_window() {
+        assert_eq!(BURST.factor(&[], 1.0), 1.0);
+        assert_eq!(BURST.factor(&[0.8], 1.0), BURST.second_hit);
+        assert_eq!(BURST.factor(&[0.6, 0.8], 1.0), BURST.later_hits);
+        assert_eq!(BURST.factor(&[0.6, 0.7, 0.8, 0.9], 1.0), BURST.later_hits);
+    }
+
+    #[test]
+    fn factor_forgets_hits_once_the_window_has_passed() {
+        // Exactly a window earlier is already out of it
+        assert_eq!(BURST.factor(&[0.5], 1.0), 1.0);
+        assert_eq!(BURST.factor(&[0.25, 0.75], 1.0), BURST.second_hit);
+        assert_eq!(BURST.factor(&[0.0, 0.25, 0.5], 1.0), 1.0);
+    }
+
+    #[test]
+    fn hits_from_mitigated_sources_soften_each_other() {
+        let mut health = PlayerHealth::default();
+        let taken: Vec<f32> = [
+            hit(PlayerDamageSource::Zombie, 1.0),
+            hit(PlayerDamageSource::Turret, 1.125),
+            hit(PlayerDamageSource::Zombie, 1.25),
+        ]
+        .into_iter()
+        .map(|hit| apply_player_damage(&mut health, None, None, hit, &BURST))
+        .collect();
+        assert_eq!(taken, vec![20.0, 15.0, 10.0]);
+        assert_eq!(health.current, 55.0);
+    }
+
+    #[test]
+    fn boss_blows_and_explosions_land_in_full_and_soften_nothing() {
+        let mut health = PlayerHealth::default();
+        let mut take = |hit| apply_player_damage(&mut health, None, None, hit, &BURST);
+        assert_eq!(take(hit(PlayerDamageSource::Zombie, 1.0)), 20.0);
+        assert_eq!(take(hit(PlayerDamageSource::Boss, 1.125)), 20.0);
+        assert_eq!(take(hit(PlayerDamageSource::Explosion, 1.25)), 20.0);
+        // Only the zombie's bite counts against the next one
+        assert_eq!(take(hit(PlayerDamageSource::Zombie, 1.375)), 15.0);
+        assert_eq!(health.recent_hits, vec![1.0, 1.375]);
+    }
+
+    #[test]
+    fn old_hits_are_dropped_as_new_ones_land() {
+        let mut health = PlayerHealth::default();
+        for now in [1.0, 2.0, 3.0] {
+            let taken = apply_player_damage(
+                &mut health,
+                None,
+                None,
+                hit(PlayerDamageSource::Zombie, now),
+                &BURST,
+            );
+            assert_eq!(taken, 20.0);
+        }
+        assert_eq!(health.recent_hits, vec![3.0]);
+    }
+    #[test]
+    fn armor_takes_its_share_out_of_what_is_returned() {
+        let mut health = PlayerHealth::default();
+        let mut armor = PlayerArmor {
+            current: 5.0,
+            ..default()
+        };
+        let mut take = |armor: &mut PlayerArmor, now| {
+            let hit = hit(PlayerDamageSource::Explosion, now);
+            apply_player_damage(&mut health, Some(armor), None, hit, &BURST)
+        };
+        assert_eq!(take(&mut armor, 1.0), 20.0 - 5.0);
+        assert_eq!(armor.current, 0.0);
+        assert_eq!(take(&mut armor, 2.0), 20.0);
+        assert_eq!(health.current, 100.0 - 15.0 - 20.0);
+    }
+}
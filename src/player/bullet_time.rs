@@ -0,0 +1,193 @@
+use super::{Player, PlayerActions};
+use crate::enemies::ZombieDied;
+use crate::ui::GameState;
+use bevy::prelude::*;
+
+/// Hold Q to slow the world down. Everything on the game clock (zombies, physics,
+/// projectiles, reloads, waves) slows with it; the player's movement, camera and
+/// fire cadence are sped back up so they feel normal. Kills refill the meter.
+pub struct BulletTimePlugin;
+
+impl Plugin for BulletTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BulletTime>()
+            .add_systems(
+                PreUpdate,
+                advance_player_clock.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (update_bullet_time, refill_bullet_time, update_energy_bar)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_energy_bar)
+            // Leaving Playing (pause, console, game over) always restores normal speed,
+            // so resuming never starts from a stale slowed clock
+            .add_systems(
+                OnExit(GameState::Playing),
+                (end_bullet_time, despawn_energy_bar),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                refill_for_new_run,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                refill_for_new_run,
+            );
+    }
+}
+
+/// Game clock speed while bullet time is on
+pub const BULLET_TIME_SCALE: f32 = 0.3;
+const MAX_ENERGY: f32 = 100.0;
+/// Energy used per second of (unslowed) bullet time
+const ENERGY_DRAIN: f32 = 25.0;
+const ENERGY_PER_KILL: f32 = 15.0;
+
+#[derive(Resource)]
+pub struct BulletTime {
+    pub energy: f32,
+    pub active: bool,
+    /// Multiplier that undoes this frame's slow-down for player-side timing
+    pub compensation: f32,
+    /// Game time with bullet time taken back out; weapon cadence runs on this
+    pub player_elapsed: f64,
+}
+
+impl Default for BulletTime {
+    fn default() -> Self {
+        Self {
+            energy: MAX_ENERGY,
+            active: false,
+            compensation: 1.0,
+            player_elapsed: 0.0,
+        }
+    }
+}
+
+impl BulletTime {
+    /// This frame's game delta as the player experiences it
+    pub fn player_delta(&self, time: &Time) -> f32 {
+        time.delta_secs() * self.compensation
+    }
+}
+
+#[derive(Component)]
+struct EnergyBar;
+
+#[derive(Component)]
+struct EnergyBarFill;
+
+/// The delta about to be used elapsed at last frame's speed, so fix the compensation
+/// here before any gameplay system reads it
+fn advance_player_clock(time: Res<Time>, mut bullet_time: ResMut<BulletTime>) {
+    bullet_time.compensation = if bullet_time.active {
+        1.0 / BULLET_TIME_SCALE
+    } else {
+        1.0
+    };
+    bullet_time.player_elapsed += bullet_time.player_delta(&time) as f64;
+}
+
+fn update_bullet_time(
+    time: Res<Time>,
+    actions: Res<PlayerActions>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    if bullet_time.active {
+        let drain = ENERGY_DRAIN * bullet_time.player_delta(&time);
+        bullet_time.energy = (bullet_time.energy - drain).max(0.0);
+    }
+
+    // Takes effect on the next frame's delta
+    let wanted = actions.bullet_time && bullet_time.energy > 0.0;
+    if wanted != bullet_time.active {
+        bullet_time.active = wanted;
+        virtual_time.set_relative_speed(if wanted { BULLET_TIME_SCALE } else { 1.0 });
+    }
+}
+
+fn refill_bullet_time(
+    mut died_events: MessageReader<ZombieDied>,
+    player_q: Query<Entity, With<Player>>,
+    mut bullet_time: ResMut<BulletTime>,
+) {
+    let Ok(player) = player_q.single() else {
+        return;
+    };
+    for event in died_events.read() {
+        if event.killer == Some(player) {
+            bullet_time.energy = (bullet_time.energy + ENERGY_PER_KILL).min(MAX_ENERGY);
+        }
+    }
+}
+
+fn end_bullet_time(mut bullet_time: ResMut<BulletTime>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if bullet_time.active {
+        bullet_time.active = false;
+        virtual_time.set_relative_speed(1.0);
+    }
+    bullet_time.compensation = 1.0;
+}
+
+fn refill_for_new_run(mut bullet_time: ResMut<BulletTime>) {
+    bullet_time.energy = MAX_ENERGY;
+}
+
+fn spawn_energy_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(24.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-100.0)),
+                width: Val::Px(200.0),
+                height: Val::Px(5.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            EnergyBar,
+        ))
+        .with_children(|bar| {
+            bar.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.6, 0.4, 1.0)),
+                EnergyBarFill,
+            ));
+        });
+}
+
+fn update_energy_bar(
+    bullet_time: Res<BulletTime>,
+    mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<EnergyBarFill>>,
+) {
+    for (mut node, mut color) in fill_query.iter_mut() {
+        node.width = Val::Percent(bullet_time.energy / MAX_ENERGY * 100.0);
+        // Brighter while draining
+        *color = BackgroundColor(if bullet_time.active {
+            Color::srgb(0.8, 0.6, 1.0)
+        } else {
+            Color::srgb(0.6, 0.4, 1.0)
+        });
+    }
+}
+
+fn despawn_energy_bar(mut commands: Commands, bars: Query<Entity, With<EnergyBar>>) {
+    for entity in bars.iter() {
+        commands.entity(entity).despawn();
+    }
+}
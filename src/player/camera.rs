@@ -1,18 +1,73 @@
-use super::Player;
+use super::{
+    BulletTime, KillCam, Player, PlayerActionState, PlayerActions, PlayerHealth, PlayerPerks,
+    PLAYER_PIVOT_HEIGHT,
+};
+use crate::combat::{compute_effective_stats, WeaponInventory};
+use crate::enemies::Zombie;
 use crate::ui::GameState;
-use bevy::input::mouse::{AccumulatedMouseMotion, MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera)
+        app.init_resource::<CameraSettings>()
+            .add_message::<CameraShake>()
+            .add_systems(Startup, spawn_camera)
             .add_systems(
                 Update,
-                (camera_pitch, camera_zoom).run_if(in_state(GameState::Playing)),
+                (
+                    camera_pitch,
+                    camera_zoom,
+                    camera_shoulder_swap,
+                    camera_free_look,
+                    recover_from_recoil,
+                    update_camera_effects,
+                )
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::PrePlaying)))
+                    .run_if(not(any_with_component::<DeathCamera>)),
             )
-            .add_systems(Update, follow_player);
+            .add_systems(
+                Update,
+                (start_death_camera, update_death_camera)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                clear_death_camera,
+            )
+            // The photo mode fly camera takes over the Camera3d while active
+            .add_systems(
+                Update,
+                (receive_camera_shake, follow_player)
+                    .chain()
+                    .run_if(not(in_state(GameState::PhotoMode))),
+            );
+    }
+}
+
+/// Player-facing camera preferences, toggled from the options menu
+#[derive(Resource)]
+pub struct CameraSettings {
+    pub smoothing: bool,
+    /// Widen the FOV while sprinting (some players get motion sick from this)
+    pub fov_effects: bool,
+    /// Soften the camera kick when the player flinches from a hit
+    pub reduce_flinch: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            smoothing: true,
+            fov_effects: true,
+            reduce_flinch: false,
+        }
     }
 }
 
@@ -25,6 +80,55 @@ pub struct ThirdPersonCamera {
     pub min_distance: f32,
     pub max_distance: f32,
     pub zoom_speed: f32,
+    /// Radius of the sphere cast used to keep the camera out of geometry
+    pub collision_radius: f32,
+    /// How fast the camera eases back out once an obstruction clears
+    pub collision_recovery_speed: f32,
+    /// Distance actually used this frame after collision, eased towards `distance`
+    pub current_distance: f32,
+    /// Lateral offset of the pivot from the player (ADS can tighten this)
+    pub shoulder_offset: f32,
+    /// 1.0 for the right shoulder, -1.0 for the left
+    pub shoulder_side: f32,
+    pub shoulder_swap_speed: f32,
+    /// Signed lateral offset actually used this frame, eased towards the target shoulder
+    pub current_shoulder_offset: f32,
+    /// Exponential follow rate for the pivot in the XZ plane (higher = tighter)
+    pub horizontal_smoothing: f32,
+    /// Exponential follow rate for the pivot height, kept high so jumps don't float
+    pub vertical_smoothing: f32,
+    /// Exponential rate for the look rotation, 0.0 disables rotational smoothing
+    pub rotation_smoothing: f32,
+    /// Pivot error beyond which the camera snaps instead of easing (teleports, respawn)
+    pub snap_distance: f32,
+    /// Smoothed follow point above the player
+    pub smoothed_pivot: Vec3,
+    /// Extra yaw applied on top of the player's heading while free-looking
+    pub free_look_yaw: f32,
+    /// Furthest free-look can swing either way from the player's heading, in radians
+    pub max_free_look_yaw: f32,
+    pub free_look_active: bool,
+    /// Exponential rate used to ease back behind the player after releasing free-look
+    pub free_look_return_speed: f32,
+    /// User FOV in radians; effects are layered on top and never written back here
+    pub base_fov: f32,
+    /// Eased offsets from gameplay effects (sprint, encirclement) applied in follow_player
+    pub fov_offset: f32,
+    pub effect_distance: f32,
+    pub effect_height: f32,
+    /// Shake intensity in 0..1, decays over time; feed it with CameraShake messages
+    pub shake: f32,
+    /// Weapon recoil on top of the aim in radians, x to the right and y up; feed it
+    /// with add_recoil
+    pub recoil: Vec2,
+    /// Exponential rate the recoil eases back to zero at
+    pub recoil_recovery: f32,
+}
+
+/// Ask the gameplay camera to shake, e.g. from explosions
+#[derive(Message)]
+pub struct CameraShake {
+    pub trauma: f32,
 }
 
 impl Default for ThirdPersonCamera {
@@ -37,10 +141,66 @@ impl Default for ThirdPersonCamera {
             min_distance: 3.0,
             max_distance: 20.0,
             zoom_speed: 1.0,
+            collision_radius: 0.3,
+            collision_recovery_speed: 6.0,
+            current_distance: 8.0,
+            shoulder_offset: 0.6,
+            shoulder_side: 1.0,
+            shoulder_swap_speed: 10.0,
+            current_shoulder_offset: 0.6,
+            horizontal_smoothing: 12.0,
+            vertical_smoothing: 25.0,
+            rotation_smoothing: 0.0,
+            snap_distance: 5.0,
+            smoothed_pivot: Vec3::new(0.0, 1.5, 0.0),
+            free_look_yaw: 0.0,
+            max_free_look_yaw: 2.5,
+            free_look_active: false,
+            free_look_return_speed: 10.0,
+            base_fov: std::f32::consts::FRAC_PI_4,
+            fov_offset: 0.0,
+            effect_distance: 0.0,
+            effect_height: 0.0,
+            shake: 0.0,
+            recoil: Vec2::ZERO,
+            recoil_recovery: 3.0,
         }
     }
 }
 
+impl ThirdPersonCamera {
+    /// True while free-look is held or the camera is still easing back behind the player
+    pub fn is_free_looking(&self) -> bool {
+        self.free_look_active || self.free_look_yaw.abs() > 0.05
+    }
+
+    /// Kick the aim by a round's recoil (see RecoilPattern)
+    pub fn add_recoil(&mut self, kick: Vec2) {
+        self.recoil += kick;
+    }
+}
+
+/// Slow orbit around the player's corpse shown before the game-over screen.
+/// While present it overrides follow_player and disables player input.
+#[derive(Component)]
+pub struct DeathCamera {
+    pub timer: Timer,
+    pub center: Vec3,
+    pub angle: f32,
+    pub distance: f32,
+    pub height: f32,
+}
+
+impl DeathCamera {
+    pub const DURATION: f32 = 3.0;
+    pub const TARGET_DISTANCE: f32 = 12.0;
+    pub const ORBIT_SPEED: f32 = 20.0 * std::f32::consts::PI / 180.0;
+}
+
+/// Camera handed to a cutscene; follow_player leaves it alone until it's removed
+#[derive(Component)]
+pub struct ScriptedCamera;
+
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((
         Camera3d::default(),
@@ -49,53 +209,357 @@ fn spawn_camera(mut commands: Commands) {
     ));
 }
 
-fn camera_pitch(
-    mouse_motion: Res<AccumulatedMouseMotion>,
-    mut camera_q: Query<&mut ThirdPersonCamera>,
-) {
+fn camera_pitch(actions: Res<PlayerActions>, mut camera_q: Query<&mut ThirdPersonCamera>) {
     let Ok(mut camera) = camera_q.single_mut() else {
         return;
     };
 
     let sensitivity = 0.003;
-    camera.pitch -= mouse_motion.delta.y * sensitivity;
+    camera.pitch -= actions.look.y * sensitivity;
     camera.pitch = camera.pitch.clamp(camera.min_pitch, camera.max_pitch);
 }
 
-fn camera_zoom(
-    mut scroll_events: MessageReader<MouseWheel>,
+fn camera_zoom(actions: Res<PlayerActions>, mut camera_q: Query<&mut ThirdPersonCamera>) {
+    let Ok(mut camera) = camera_q.single_mut() else {
+        return;
+    };
+    if actions.scroll == 0.0 {
+        return;
+    }
+
+    camera.distance -= actions.scroll * camera.zoom_speed;
+    camera.distance = camera
+        .distance
+        .clamp(camera.min_distance, camera.max_distance);
+}
+
+fn camera_shoulder_swap(actions: Res<PlayerActions>, mut camera_q: Query<&mut ThirdPersonCamera>) {
+    if !actions.shoulder_swap {
+        return;
+    }
+
+    for mut camera in camera_q.iter_mut() {
+        camera.shoulder_side = -camera.shoulder_side;
+    }
+}
+
+/// Frame-rate independent interpolation factor for an exponential follow at `rate`
+pub(super) fn smoothing_factor(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
+
+fn camera_free_look(
+    actions: Res<PlayerActions>,
+    time: Res<Time>,
+    bullet_time: Res<BulletTime>,
     mut camera_q: Query<&mut ThirdPersonCamera>,
 ) {
     let Ok(mut camera) = camera_q.single_mut() else {
         return;
     };
 
-    for event in scroll_events.read() {
-        let scroll = match event.unit {
-            MouseScrollUnit::Line => event.y * camera.zoom_speed,
-            MouseScrollUnit::Pixel => event.y * camera.zoom_speed * 0.01,
-        };
-        camera.distance -= scroll;
-        camera.distance = camera
-            .distance
-            .clamp(camera.min_distance, camera.max_distance);
+    camera.free_look_active = actions.free_look;
+
+    if camera.free_look_active {
+        // Mouse X orbits the camera instead of turning the player (see player_rotation)
+        let sensitivity = 0.003;
+        camera.free_look_yaw = (camera.free_look_yaw - actions.look.x * sensitivity)
+            .clamp(-camera.max_free_look_yaw, camera.max_free_look_yaw);
+    } else {
+        let t = smoothing_factor(
+            camera.free_look_return_speed,
+            bullet_time.player_delta(&time),
+        );
+        camera.free_look_yaw -= camera.free_look_yaw * t;
+    }
+}
+
+fn recover_from_recoil(
+    time: Res<Time>,
+    bullet_time: Res<BulletTime>,
+    mut camera_q: Query<&mut ThirdPersonCamera>,
+) {
+    for mut camera in camera_q.iter_mut() {
+        let t = smoothing_factor(camera.recoil_recovery, bullet_time.player_delta(&time));
+        let recoil = camera.recoil;
+        camera.recoil -= recoil * t;
+    }
+}
+
+/// Ease the sprint, encirclement and scope effects towards their targets and apply the FOV
+fn update_camera_effects(
+    time: Res<Time>,
+    bullet_time: Res<BulletTime>,
+    settings: Res<CameraSettings>,
+    actions: Res<PlayerActions>,
+    perks: Res<PlayerPerks>,
+    player_q: Query<(&Transform, &PlayerActionState, &WeaponInventory)>,
+    zombies: Query<&Transform, With<Zombie>>,
+    mut camera_q: Query<(&mut ThirdPersonCamera, &mut Projection)>,
+) {
+    let Ok((player_transform, action_state, inventory)) = player_q.single() else {
+        return;
+    };
+    let Ok((mut camera, mut projection)) = camera_q.single_mut() else {
+        return;
+    };
+
+    let surround_radius = 5.0;
+    let nearby_zombies = zombies
+        .iter()
+        .filter(|zombie| {
+            zombie
+                .translation
+                .with_y(0.0)
+                .distance(player_transform.translation.with_y(0.0))
+                < surround_radius
+        })
+        .count();
+    let surrounded = nearby_zombies >= 3;
+
+    let mut target_fov = 0.0;
+    let mut target_distance = 0.0;
+    let mut target_height = 0.0;
+
+    let sprinting = action_state.state.sprinting();
+    if sprinting {
+        if settings.fov_effects {
+            target_fov += 8.0_f32.to_radians();
+        }
+        target_distance += 1.0;
+    }
+    // There's no separate aim button, so a scope zooms in while the trigger is held
+    if actions.fire_held && !sprinting {
+        if let Some(weapon) = inventory.current_weapon() {
+            target_fov -= compute_effective_stats(weapon, &perks).zoom;
+        }
+    }
+    if surrounded {
+        // Up and back so the encirclement is visible
+        target_distance += 1.5;
+        target_height += 0.75;
+    }
+
+    let t = smoothing_factor(4.0, bullet_time.player_delta(&time));
+    camera.fov_offset += (target_fov - camera.fov_offset) * t;
+    camera.effect_distance += (target_distance - camera.effect_distance) * t;
+    camera.effect_height += (target_height - camera.effect_height) * t;
+
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = camera.base_fov + camera.fov_offset;
     }
 }
 
 fn follow_player(
-    player_q: Query<(&Transform, &Player)>,
-    mut camera_q: Query<(&mut Transform, &ThirdPersonCamera), Without<Player>>,
+    time: Res<Time>,
+    bullet_time: Res<BulletTime>,
+    settings: Res<CameraSettings>,
+    rapier_context: ReadRapierContext,
+    player_q: Query<(Entity, &Transform, &Player)>,
+    zombies: Query<(), With<Zombie>>,
+    mut camera_q: Query<
+        (&mut Transform, &mut ThirdPersonCamera),
+        (
+            Without<Player>,
+            Without<DeathCamera>,
+            Without<ScriptedCamera>,
+            Without<KillCam>,
+        ),
+    >,
+) {
+    let Ok((player_entity, player_transform, player)) = player_q.single() else {
+        return;
+    };
+
+    // Smoothing keeps its normal feel through bullet time
+    let dt = bullet_time.player_delta(&time);
+
+    for (mut cam_transform, mut camera) in camera_q.iter_mut() {
+        // Ease the lateral offset towards the selected shoulder
+        let shoulder_target = camera.shoulder_offset * camera.shoulder_side;
+        let shoulder_t = smoothing_factor(camera.shoulder_swap_speed, dt);
+        camera.current_shoulder_offset +=
+            (shoulder_target - camera.current_shoulder_offset) * shoulder_t;
+
+        // Smooth the follow point, with separate horizontal and vertical rates
+        let head_target =
+            player_transform.translation + Vec3::Y * (PLAYER_PIVOT_HEIGHT + camera.effect_height);
+        let snapped = !settings.smoothing
+            || camera.smoothed_pivot.distance(head_target) > camera.snap_distance;
+        let head = if snapped {
+            head_target
+        } else {
+            let previous = camera.smoothed_pivot;
+            let h = smoothing_factor(camera.horizontal_smoothing, dt);
+            let v = smoothing_factor(camera.vertical_smoothing, dt);
+            Vec3::new(
+                previous.x + (head_target.x - previous.x) * h,
+                previous.y + (head_target.y - previous.y) * v,
+                previous.z + (head_target.z - previous.z) * h,
+            )
+        };
+        camera.smoothed_pivot = head;
+
+        // Use player's yaw (plus any free-look) for horizontal rotation, camera's pitch for
+        // vertical, both kicked by recoil
+        let yaw = player.yaw + camera.free_look_yaw - camera.recoil.x;
+        let pitch = (camera.pitch + camera.recoil.y).clamp(camera.min_pitch, camera.max_pitch);
+        let rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+        let right = Quat::from_rotation_y(yaw) * Vec3::X;
+        let back = rotation * Vec3::Z;
+
+        let mut lateral = camera.current_shoulder_offset;
+        let mut allowed_distance = camera.distance + camera.effect_distance;
+        if let Ok(context) = rapier_context.single() {
+            let radius = camera.collision_radius;
+            let not_zombie = |entity: Entity| !zombies.contains(entity);
+            let filter = QueryFilter::default()
+                .exclude_rigid_body(player_entity)
+                .exclude_sensors()
+                .predicate(&not_zombie);
+
+            context.with_query_pipeline(filter, |query_pipeline| {
+                // Keep the shoulder pivot out of walls the player is hugging
+                let side = right * lateral.signum();
+                if let Some((_, hit)) = query_pipeline.cast_shape(
+                    head,
+                    Quat::IDENTITY,
+                    side,
+                    &*Collider::ball(radius).raw,
+                    ShapeCastOptions::with_max_time_of_impact(lateral.abs()),
+                ) {
+                    lateral = hit.time_of_impact * lateral.signum();
+                }
+
+                // Sphere cast from the pivot towards the desired position and stop just before any hit
+                if let Some((_, hit)) = query_pipeline.cast_shape(
+                    head + right * lateral,
+                    Quat::IDENTITY,
+                    back,
+                    &*Collider::ball(radius).raw,
+                    ShapeCastOptions::with_max_time_of_impact(allowed_distance),
+                ) {
+                    allowed_distance = hit.time_of_impact;
+                }
+            });
+        }
+        let pivot = head + right * lateral;
+
+        let target_distance = allowed_distance.max(camera.min_distance);
+        if target_distance < camera.current_distance {
+            // Pull in immediately so the view never clips through the obstruction
+            camera.current_distance = target_distance;
+        } else {
+            // Ease back out once the obstruction clears
+            let t = smoothing_factor(camera.collision_recovery_speed, dt);
+            camera.current_distance += (target_distance - camera.current_distance) * t;
+        }
+
+        // Look over the shoulder along the camera rotation so screen center is the aim line
+        cam_transform.translation = pivot + back * camera.current_distance;
+        let target_rotation = cam_transform.looking_at(pivot, Vec3::Y).rotation;
+        cam_transform.rotation = if snapped || camera.rotation_smoothing <= 0.0 {
+            target_rotation
+        } else {
+            let t = smoothing_factor(camera.rotation_smoothing, dt);
+            cam_transform.rotation.slerp(target_rotation, t)
+        };
+
+        // Jitter the position by squared trauma so small shakes stay subtle
+        if camera.shake > 0.0 {
+            let t = time.elapsed_secs() * 40.0;
+            let jitter = Vec3::new(t.sin(), (t * 1.3).cos(), (t * 0.7).sin());
+            cam_transform.translation += jitter * camera.shake * camera.shake * 0.3;
+            camera.shake = (camera.shake - 1.5 * dt).max(0.0);
+        }
+    }
+}
+
+fn receive_camera_shake(
+    mut shake_events: MessageReader<CameraShake>,
+    mut camera_q: Query<&mut ThirdPersonCamera>,
+) {
+    for event in shake_events.read() {
+        for mut camera in camera_q.iter_mut() {
+            camera.shake = (camera.shake + event.trauma).min(1.0);
+        }
+    }
+}
+
+fn start_death_camera(
+    mut commands: Commands,
+    mut player_q: Query<
+        (&Transform, &PlayerHealth, &mut KinematicCharacterController),
+        With<Player>,
+    >,
+    camera_q: Query<(Entity, &Transform), (With<ThirdPersonCamera>, Without<DeathCamera>)>,
+) {
+    let Ok((player_transform, health, mut controller)) = player_q.single_mut() else {
+        return;
+    };
+
+    if health.current > 0.0 {
+        return;
+    }
+
+    let Ok((camera_entity, cam_transform)) = camera_q.single() else {
+        return;
+    };
+
+    // Stop the corpse from drifting on its last movement input
+    controller.translation = Some(Vec3::ZERO);
+
+    // Start the orbit from wherever the camera currently is
+    let center = player_transform.translation;
+    let offset = cam_transform.translation - center;
+    commands.entity(camera_entity).insert(DeathCamera {
+        timer: Timer::from_seconds(DeathCamera::DURATION, TimerMode::Once),
+        center,
+        angle: offset.x.atan2(offset.z),
+        distance: offset.with_y(0.0).length(),
+        height: offset.y.max(2.0),
+    });
+}
+
+fn update_death_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut camera_q: Query<(&mut Transform, &mut DeathCamera)>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    let Ok((player_transform, player)) = player_q.single() else {
+    let Ok((mut cam_transform, mut death_cam)) = camera_q.single_mut() else {
         return;
     };
 
-    for (mut cam_transform, camera) in camera_q.iter_mut() {
-        // Use player's yaw for horizontal rotation, camera's pitch for vertical
-        let rotation = Quat::from_euler(EulerRot::YXZ, player.yaw, camera.pitch, 0.0);
-        let offset = rotation * Vec3::new(0.0, 0.0, camera.distance);
+    let dt = time.delta_secs();
+    death_cam.timer.tick(time.delta());
+    death_cam.angle += DeathCamera::ORBIT_SPEED * dt;
+
+    // Pull back to the orbit distance
+    let t = smoothing_factor(2.0, dt);
+    death_cam.distance += (DeathCamera::TARGET_DISTANCE - death_cam.distance) * t;
+    death_cam.height += (DeathCamera::TARGET_DISTANCE * 0.4 - death_cam.height) * t;
+
+    let offset = Vec3::new(
+        death_cam.angle.sin() * death_cam.distance,
+        death_cam.height,
+        death_cam.angle.cos() * death_cam.distance,
+    );
+    cam_transform.translation = death_cam.center + offset;
+    cam_transform.look_at(death_cam.center + Vec3::Y * 0.5, Vec3::Y);
+
+    // Any key or click skips straight to the game-over screen
+    let skipped = keys.get_just_pressed().next().is_some()
+        || mouse_button.get_just_pressed().next().is_some();
+
+    if death_cam.timer.is_finished() || skipped {
+        next_state.set(GameState::GameOver);
+    }
+}
 
-        cam_transform.translation = player_transform.translation + offset + Vec3::Y * 1.5;
-        cam_transform.look_at(player_transform.translation + Vec3::Y * 1.0, Vec3::Y);
+fn clear_death_camera(mut commands: Commands, camera_q: Query<Entity, With<DeathCamera>>) {
+    for entity in camera_q.iter() {
+        commands.entity(entity).remove::<DeathCamera>();
     }
 }
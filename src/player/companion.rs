@@ -0,0 +1,468 @@
+use super::{Player, PlayerActions};
+use crate::combat::HitEvent;
+use crate::enemies::{Marked, TurretProjectile, Zombie};
+use crate::ui::GameState;
+use crate::world::{BudgetCategory, Budgeted, SpatialIndex, SpatialKind, SurfaceMaterial};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Hovering drone that trails the player, marks the nearest zombie it can see and
+/// zaps it every few seconds. V parks it in place or calls it back.
+pub struct CompanionPlugin;
+
+impl Plugin for CompanionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_drone_assets)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                spawn_drone,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                spawn_drone,
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_drone_hud)
+            .add_systems(OnExit(GameState::Playing), despawn_drone_hud)
+            .add_systems(
+                Update,
+                (
+                    toggle_drone,
+                    drone_take_hits,
+                    drone_recover,
+                    drone_follow,
+                    drone_scan,
+                    drone_zap,
+                    fade_zap_beams,
+                    update_drone_hud,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Where the drone hovers relative to the player's facing: over the right shoulder
+const FOLLOW_OFFSET: Vec3 = Vec3::new(0.9, 1.6, 0.6);
+/// Hiding spot while damaged: low and directly behind the player
+const RETREAT_OFFSET: Vec3 = Vec3::new(0.0, 1.2, 1.8);
+const SPRING_STIFFNESS: f32 = 30.0;
+const SCAN_RANGE: f32 = 12.0;
+const SCAN_INTERVAL: f32 = 0.25;
+const ZAP_INTERVAL: f32 = 4.0;
+const ZAP_DAMAGE: f32 = 10.0;
+const ZAP_BEAM_TIME: f32 = 0.15;
+/// Enemy projectiles closer than this hit the drone
+const DRONE_RADIUS: f32 = 0.35;
+const DRONE_MAX_HEALTH: f32 = 50.0;
+/// Health regained per second
+const DRONE_REGEN: f32 = 4.0;
+/// Below this share of health the drone hides; it comes back out once above the second
+const RETREAT_BELOW: f32 = 0.3;
+const RETURN_ABOVE: f32 = 0.7;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DroneMode {
+    Following,
+    /// Hiding behind the player until it has recovered
+    Retreating,
+    /// Holding position; doesn't mark or zap
+    Parked,
+}
+
+impl DroneMode {
+    fn label(&self) -> &'static str {
+        match self {
+            DroneMode::Following => "DRONE",
+            DroneMode::Retreating => "DRONE - HURT",
+            DroneMode::Parked => "DRONE - PARKED",
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Drone {
+    pub mode: DroneMode,
+    pub health: f32,
+    pub velocity: Vec3,
+    /// Spot it was parked at
+    pub park_position: Vec3,
+    pub scan: Timer,
+    pub zap: Timer,
+}
+
+/// Short-lived zap beam
+#[derive(Component)]
+struct ZapBeam {
+    timer: Timer,
+}
+
+#[derive(Resource)]
+struct DroneAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    beam_mesh: Handle<Mesh>,
+    beam_material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct DroneHud;
+
+#[derive(Component)]
+struct DroneHudText;
+
+#[derive(Component)]
+struct DroneHudFill;
+
+fn setup_drone_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(DroneAssets {
+        mesh: meshes.add(Sphere::new(0.18)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.3, 0.3, 0.35),
+            emissive: LinearRgba::rgb(0.3, 1.2, 2.0),
+            ..default()
+        }),
+        // Unit length, stretched along the beam when spawned
+        beam_mesh: meshes.add(Cylinder::new(0.03, 1.0)),
+        beam_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.5, 0.9, 1.0),
+            emissive: LinearRgba::rgb(1.5, 4.0, 6.0),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Fresh drone for every run, placed at its follow spot
+fn spawn_drone(
+    mut commands: Commands,
+    assets: Res<DroneAssets>,
+    drones: Query<Entity, With<Drone>>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    for entity in drones.iter() {
+        commands.entity(entity).despawn();
+    }
+    let start = player_q
+        .single()
+        .map(|player| player.translation + player.rotation * FOLLOW_OFFSET)
+        .unwrap_or(FOLLOW_OFFSET);
+
+    commands.spawn((
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(assets.material.clone()),
+        Transform::from_translation(start),
+        Drone {
+            mode: DroneMode::Following,
+            health: DRONE_MAX_HEALTH,
+            velocity: Vec3::ZERO,
+            park_position: start,
+            scan: Timer::from_seconds(SCAN_INTERVAL, TimerMode::Repeating),
+            zap: Timer::from_seconds(ZAP_INTERVAL, TimerMode::Once),
+        },
+    ));
+}
+
+fn toggle_drone(actions: Res<PlayerActions>, mut drones: Query<(&Transform, &mut Drone)>) {
+    if !actions.toggle_drone {
+        return;
+    }
+    for (transform, mut drone) in drones.iter_mut() {
+        drone.mode = match drone.mode {
+            DroneMode::Parked => DroneMode::Following,
+            _ => {
+                drone.park_position = transform.translation;
+                DroneMode::Parked
+            }
+        };
+    }
+}
+
+/// Enemy projectiles that pass through the drone damage it
+fn drone_take_hits(
+    mut commands: Commands,
+    mut drones: Query<(&Transform, &mut Drone)>,
+    projectiles: Query<(Entity, &Transform, &TurretProjectile), Without<Drone>>,
+) {
+    for (drone_transform, mut drone) in drones.iter_mut() {
+        for (entity, transform, projectile) in projectiles.iter() {
+            if transform.translation.distance(drone_transform.translation) < DRONE_RADIUS {
+                drone.health = (drone.health - projectile.damage).max(0.0);
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn drone_recover(time: Res<Time>, mut drones: Query<&mut Drone>) {
+    for mut drone in drones.iter_mut() {
+        drone.health = (drone.health + DRONE_REGEN * time.delta_secs()).min(DRONE_MAX_HEALTH);
+
+        let fraction = drone.health / DRONE_MAX_HEALTH;
+        match drone.mode {
+            DroneMode::Following if fraction < RETREAT_BELOW => {
+                drone.mode = DroneMode::Retreating;
+            }
+            DroneMode::Retreating if fraction >= RETURN_ABOVE => {
+                drone.mode = DroneMode::Following;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Critically damped spring towards the hover spot, with a slight bob
+fn drone_follow(
+    time: Res<Time>,
+    player_q: Query<&Transform, (With<Player>, Without<Drone>)>,
+    mut drones: Query<(&mut Transform, &mut Drone)>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+    let dt = time.delta_secs();
+    let damping = 2.0 * SPRING_STIFFNESS.sqrt();
+    let bob = Vec3::Y * (time.elapsed_secs() * 2.0).sin() * 0.08;
+
+    for (mut transform, mut drone) in drones.iter_mut() {
+        let anchor = match drone.mode {
+            DroneMode::Following => {
+                player_transform.translation + player_transform.rotation * FOLLOW_OFFSET
+            }
+            DroneMode::Retreating => {
+                player_transform.translation + player_transform.rotation * RETREAT_OFFSET
+            }
+            DroneMode::Parked => drone.park_position,
+        };
+
+        let accel =
+            (anchor + bob - transform.translation) * SPRING_STIFFNESS - drone.velocity * damping;
+        drone.velocity += accel * dt;
+        transform.translation += drone.velocity * dt;
+    }
+}
+
+/// Mark the nearest zombie the drone can see, clearing the old mark
+fn drone_scan(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    player_q: Query<Entity, With<Player>>,
+    mut drones: Query<(&Transform, &mut Drone)>,
+    zombies: Query<(Entity, &Transform), With<Zombie>>,
+    index: Res<SpatialIndex>,
+    marked: Query<Entity, With<Marked>>,
+) {
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let Ok(player) = player_q.single() else {
+        return;
+    };
+    let Ok((drone_transform, mut drone)) = drones.single_mut() else {
+        return;
+    };
+    drone.scan.tick(time.delta());
+    if !drone.scan.just_finished() {
+        return;
+    }
+
+    let origin = drone_transform.translation;
+    let mut target = None;
+    if drone.mode == DroneMode::Following {
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(player)
+            .exclude_sensors();
+        // Closest first, so the first one in sight is the one to mark
+        let mut candidates: Vec<(Entity, Vec3, f32)> = index
+            .neighbors_within(origin, SCAN_RANGE)
+            .filter(|entry| entry.kind == SpatialKind::Zombie)
+            .filter_map(|entry| zombies.get(entry.entity).ok())
+            .map(|(entity, transform)| {
+                let offset = transform.translation - origin;
+                (entity, offset, offset.length())
+            })
+            .filter(|(_, _, distance)| *distance <= SCAN_RANGE)
+            .collect();
+        candidates.sort_by(|a, b| a.2.total_cmp(&b.2));
+        target = candidates
+            .into_iter()
+            .find(|(entity, offset, distance)| {
+                let mut visible = false;
+                context.with_query_pipeline(filter, |query_pipeline| {
+                    if let Some((hit, _)) =
+                        query_pipeline.cast_ray(origin, *offset / *distance, distance + 0.5, true)
+                    {
+                        visible = hit == *entity;
+                    }
+                });
+                visible
+            })
+            .map(|(entity, ..)| entity);
+    }
+
+    for entity in marked.iter() {
+        if Some(entity) != target {
+            commands.entity(entity).try_remove::<Marked>();
+        }
+    }
+    if let Some(target) = target {
+        commands.entity(target).try_insert(Marked);
+    }
+}
+
+fn drone_zap(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<DroneAssets>,
+    mut drones: Query<(Entity, &Transform, &mut Drone)>,
+    marked: Query<(Entity, &Transform), (With<Marked>, Without<Drone>)>,
+    mut hit_events: MessageWriter<HitEvent>,
+) {
+    for (drone_entity, drone_transform, mut drone) in drones.iter_mut() {
+        drone.zap.tick(time.delta());
+        if !drone.zap.is_finished() || drone.mode != DroneMode::Following {
+            continue;
+        }
+        // Stays charged until something is marked
+        let Some((target, target_transform)) = marked.iter().next() else {
+            continue;
+        };
+        drone.zap.reset();
+
+        let origin = drone_transform.translation;
+        let point = target_transform.translation;
+        let offset = point - origin;
+        let distance = offset.length();
+        let direction = offset.normalize_or_zero();
+        hit_events.write(HitEvent {
+            entity: target,
+            damage: ZAP_DAMAGE,
+            direction,
+            point,
+            normal: -direction,
+            distance,
+            source: Some(drone_entity),
+            zone: None,
+            surface: SurfaceMaterial::default(),
+            headshot: false,
+            crit: false,
+        });
+
+        commands.spawn((
+            Mesh3d(assets.beam_mesh.clone()),
+            MeshMaterial3d(assets.beam_material.clone()),
+            Transform::from_translation((origin + point) / 2.0)
+                .with_rotation(Quat::from_rotation_arc(Vec3::Y, direction))
+                .with_scale(Vec3::new(1.0, distance, 1.0)),
+            ZapBeam {
+                timer: Timer::from_seconds(ZAP_BEAM_TIME, TimerMode::Once),
+            },
+            Budgeted(BudgetCategory::Tracer),
+        ));
+    }
+}
+
+/// Thin the beam out over its short life, then remove it
+fn fade_zap_beams(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut beams: Query<(Entity, &mut Transform, &mut ZapBeam)>,
+) {
+    for (entity, mut transform, mut beam) in beams.iter_mut() {
+        beam.timer.tick(time.delta());
+        if beam.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let width = 1.0 - beam.timer.fraction();
+        transform.scale.x = width;
+        transform.scale.z = width;
+    }
+}
+
+fn spawn_drone_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(24.0),
+                left: Val::Px(24.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(3.0),
+                ..default()
+            },
+            DroneHud,
+        ))
+        .with_children(|hud| {
+            hud.spawn((
+                Text::new(DroneMode::Following.label()),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.4, 0.8, 1.0)),
+                DroneHudText,
+            ));
+            hud.spawn((
+                Node {
+                    width: Val::Px(80.0),
+                    height: Val::Px(4.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.4, 0.8, 1.0)),
+                    DroneHudFill,
+                ));
+            });
+        });
+}
+
+fn update_drone_hud(
+    drones: Query<&Drone>,
+    mut text_query: Query<(&mut Text, &mut TextColor), With<DroneHudText>>,
+    mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<DroneHudFill>>,
+) {
+    let Ok(drone) = drones.single() else {
+        return;
+    };
+    let color = match drone.mode {
+        DroneMode::Following => Color::srgb(0.4, 0.8, 1.0),
+        DroneMode::Retreating => Color::srgb(1.0, 0.4, 0.3),
+        DroneMode::Parked => Color::srgb(0.6, 0.6, 0.6),
+    };
+
+    for (mut text, mut text_color) in text_query.iter_mut() {
+        if text.0 != drone.mode.label() {
+            text.0 = drone.mode.label().to_string();
+        }
+        text_color.0 = color;
+    }
+    for (mut node, mut background) in fill_query.iter_mut() {
+        node.width = Val::Percent(drone.health / DRONE_MAX_HEALTH * 100.0);
+        background.0 = color;
+    }
+}
+
+fn despawn_drone_hud(mut commands: Commands, huds: Query<Entity, With<DroneHud>>) {
+    for entity in huds.iter() {
+        commands.entity(entity).despawn();
+    }
+}
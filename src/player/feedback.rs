@@ -0,0 +1,185 @@
+use super::Player;
+use crate::ui::{AccessibilitySettings, GameState};
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Rumble and a red screen-edge flash when the player gets hurt or something blows up
+/// close by. Anything can ask for it by sending a FeedbackEvent.
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FeedbackSettings>()
+            .add_message::<FeedbackEvent>()
+            .add_systems(Startup, spawn_damage_flash)
+            .add_systems(
+                Update,
+                (play_feedback, fade_damage_flash)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), hide_damage_flash)
+            .add_systems(OnEnter(GameState::Paused), stop_rumble)
+            .add_systems(OnEnter(GameState::GameOver), stop_rumble)
+            .add_systems(OnEnter(GameState::Victory), stop_rumble);
+    }
+}
+
+/// Something the player should feel
+#[derive(Message, Clone, Copy, Debug)]
+pub enum FeedbackEvent {
+    /// The player took `amount` damage
+    Damage { amount: f32 },
+    /// An explosion went off; rumbles if the player is within a few blast radii, hurt
+    /// or not
+    Explosion { position: Vec3, radius: f32 },
+}
+
+/// Rumble preferences, set from the options menu
+#[derive(Resource)]
+pub struct FeedbackSettings {
+    pub rumble: bool,
+    /// Scale on every rumble, 0.0 to 1.0
+    pub rumble_intensity: f32,
+}
+
+impl Default for FeedbackSettings {
+    fn default() -> Self {
+        Self {
+            rumble: true,
+            rumble_intensity: 1.0,
+        }
+    }
+}
+
+impl FeedbackSettings {
+    /// Steps the options menu cycles the intensity through
+    pub const INTENSITY_STEPS: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+
+    pub fn next_intensity(&self) -> f32 {
+        Self::INTENSITY_STEPS
+            .into_iter()
+            .find(|step| *step > self.rumble_intensity + 0.01)
+            .unwrap_or(Self::INTENSITY_STEPS[0])
+    }
+}
+
+/// Damage that maxes out the strong motor
+const FULL_RUMBLE_DAMAGE: f32 = 30.0;
+const DAMAGE_RUMBLE: Duration = Duration::from_millis(150);
+const EXPLOSION_RUMBLE: Duration = Duration::from_millis(500);
+/// Explosions rumble out to this many blast radii
+const EXPLOSION_FEEL_RANGE: f32 = 3.0;
+/// Seconds the edge flash takes to fade
+const FLASH_FADE: f32 = 0.35;
+/// Damage that flashes at full strength
+const FULL_FLASH_DAMAGE: f32 = 20.0;
+
+/// Red border over the screen edge, faded in by damage
+#[derive(Component)]
+struct DamageFlash {
+    strength: f32,
+}
+
+fn spawn_damage_flash(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            border: UiRect::all(Val::Px(36.0)),
+            ..default()
+        },
+        BorderColor::all(Color::NONE),
+        GlobalZIndex(5),
+        Pickable::IGNORE,
+        DamageFlash { strength: 0.0 },
+    ));
+}
+
+fn play_feedback(
+    settings: Res<FeedbackSettings>,
+    mut feedback: MessageReader<FeedbackEvent>,
+    player_q: Query<&Transform, With<Player>>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    mut flash_q: Query<&mut DamageFlash>,
+    mut rumble: MessageWriter<GamepadRumbleRequest>,
+) {
+    for event in feedback.read() {
+        let (intensity, duration) = match *event {
+            FeedbackEvent::Damage { amount } => {
+                for mut flash in flash_q.iter_mut() {
+                    flash.strength = flash.strength.max((amount / FULL_FLASH_DAMAGE).min(1.0));
+                }
+                let strong = (amount / FULL_RUMBLE_DAMAGE).clamp(0.2, 1.0);
+                (GamepadRumbleIntensity::strong_motor(strong), DAMAGE_RUMBLE)
+            }
+            FeedbackEvent::Explosion { position, radius } => {
+                let Ok(player_transform) = player_q.single() else {
+                    continue;
+                };
+                let range = radius * EXPLOSION_FEEL_RANGE;
+                let distance = player_transform.translation.distance(position);
+                if distance > range {
+                    continue;
+                }
+                // Low rumble that fades with distance, on the big motor only
+                let strong = 0.6 * (1.0 - distance / range);
+                (
+                    GamepadRumbleIntensity::strong_motor(strong),
+                    EXPLOSION_RUMBLE,
+                )
+            }
+        };
+
+        if !settings.rumble {
+            continue;
+        }
+        let intensity = GamepadRumbleIntensity {
+            strong_motor: intensity.strong_motor * settings.rumble_intensity,
+            weak_motor: intensity.weak_motor * settings.rumble_intensity,
+        };
+        for gamepad in gamepads.iter() {
+            rumble.write(GamepadRumbleRequest::Add {
+                gamepad,
+                duration,
+                intensity,
+            });
+        }
+    }
+}
+
+fn fade_damage_flash(
+    time: Res<Time>,
+    accessibility: Res<AccessibilitySettings>,
+    mut flash_q: Query<(&mut DamageFlash, &mut BorderColor)>,
+) {
+    // Reduced flashing keeps the edge visible but never bright
+    let peak = if accessibility.reduce_flashing {
+        0.25
+    } else {
+        0.6
+    };
+    for (mut flash, mut border) in flash_q.iter_mut() {
+        flash.strength = (flash.strength - time.delta_secs() / FLASH_FADE).max(0.0);
+        *border = BorderColor::all(Color::srgba(0.8, 0.0, 0.0, flash.strength * peak));
+    }
+}
+
+fn hide_damage_flash(mut flash_q: Query<(&mut DamageFlash, &mut BorderColor)>) {
+    for (mut flash, mut border) in flash_q.iter_mut() {
+        flash.strength = 0.0;
+        *border = BorderColor::all(Color::NONE);
+    }
+}
+
+/// Cut any rumble still playing the moment the game stops
+fn stop_rumble(
+    gamepads: Query<Entity, With<Gamepad>>,
+    mut rumble: MessageWriter<GamepadRumbleRequest>,
+) {
+    for gamepad in gamepads.iter() {
+        rumble.write(GamepadRumbleRequest::Stop { gamepad });
+    }
+}
@@ -0,0 +1,77 @@
+use super::{CameraSettings, ThirdPersonCamera};
+use crate::ui::GameState;
+use bevy::prelude::*;
+
+/// Getting hurt throws the player's aim: each hit adds flinch, which widens the
+/// spread of the next shots and kicks the camera up, then wears off.
+pub struct FlinchPlugin;
+
+impl Plugin for FlinchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            recover_from_flinch.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Seconds a full flinch takes to wear off
+const FLINCH_DECAY: f32 = 1.5;
+/// Spread added at full flinch, in radians
+const FLINCH_SPREAD: f32 = 0.07; // ~4 degrees
+/// Share of the flinch spread left while looking through a scope
+const FLINCH_ADS_SCALE: f32 = 0.5;
+/// Camera pitch kick per unit of flinch, in radians
+const FLINCH_KICK: f32 = 0.06;
+/// Share of the camera kick kept with reduced flinch motion
+const REDUCED_KICK_SCALE: f32 = 0.2;
+
+/// Flinch from a turret bolt
+pub const TURRET_FLINCH: f32 = 0.4;
+/// Flinch at the heart of an explosion, falling off with distance
+pub const EXPLOSION_FLINCH: f32 = 1.0;
+
+/// How shaken the player is, from 0.0 (steady) to 1.0
+#[derive(Component, Default)]
+pub struct Flinch {
+    pub amount: f32,
+    /// Camera kick still to be applied
+    kick: f32,
+}
+
+impl Flinch {
+    pub fn add(&mut self, amount: f32) {
+        self.amount = (self.amount + amount).min(1.0);
+        self.kick += amount;
+    }
+
+    /// Extra spread in radians for the next shot
+    pub fn spread(&self, aiming: bool) -> f32 {
+        let scale = if aiming { FLINCH_ADS_SCALE } else { 1.0 };
+        self.amount * FLINCH_SPREAD * scale
+    }
+}
+
+fn recover_from_flinch(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    mut players: Query<&mut Flinch>,
+    mut camera_q: Query<&mut ThirdPersonCamera>,
+) {
+    for mut flinch in players.iter_mut() {
+        flinch.amount = (flinch.amount - time.delta_secs() / FLINCH_DECAY).max(0.0);
+
+        if flinch.kick > 0.0 {
+            let scale = if settings.reduce_flinch {
+                REDUCED_KICK_SCALE
+            } else {
+                1.0
+            };
+            for mut camera in camera_q.iter_mut() {
+                camera.pitch = (camera.pitch + flinch.kick * FLINCH_KICK * scale)
+                    .clamp(camera.min_pitch, camera.max_pitch);
+            }
+            flinch.kick = 0.0;
+        }
+    }
+}
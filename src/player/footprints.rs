@@ -0,0 +1,323 @@
+use super::{Player, PlayerActionState, PlayerAnimation, PLAYER_HALF_HEIGHT, PLAYER_RADIUS};
+use crate::ui::GameState;
+use crate::world::{BudgetCategory, Budgeted, Floor, GameRng, ToxicPool};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use std::f32::consts::{PI, TAU};
+
+/// Dust kicked up at the player's feet while sprinting and on landing, and faint
+/// footprints left on the floor in step with the legs. Feet stay stained for a while
+/// after wading through a toxic pool, and those prints glow and linger.
+pub struct FootprintPlugin;
+
+impl Plugin for FootprintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FootprintSettings>()
+            .add_systems(Startup, setup_footprint_assets)
+            .add_systems(
+                Update,
+                (leave_footprints, fade_footprints, fly_dust_puffs)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                despawn_footprints,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                despawn_footprints,
+            );
+    }
+}
+
+/// Seconds a dry footprint takes to fade away
+const FOOTPRINT_LIFETIME: f32 = 10.0;
+/// Seconds a toxic footprint takes to fade away
+const WET_FOOTPRINT_LIFETIME: f32 = 25.0;
+/// Seconds the feet stay stained after leaving a pool
+const WET_FEET_TIME: f32 = 4.0;
+/// Opacity levels a footprint steps down through as it fades
+const FADE_STEPS: usize = 6;
+/// Sideways distance from the player's centre to each foot, matching the rig's hips
+const FOOT_OFFSET: f32 = 0.13;
+/// Dust puffs per sprinting footstep
+const STEP_DUST: usize = 2;
+/// Dust puffs per foot on landing
+const LANDING_DUST: usize = 4;
+const DUST_LIFETIME: f32 = 0.5;
+
+/// Off on low-end machines, since every print and puff is its own draw
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct FootprintSettings {
+    pub enabled: bool,
+}
+
+impl Default for FootprintSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Component)]
+struct Footprint {
+    age: Timer,
+    wet: bool,
+    step: usize,
+}
+
+/// Puff of dust from the feet; drifts outwards and spreads before it's gone
+#[derive(Component)]
+struct DustPuff {
+    velocity: Vec3,
+    lifetime: Timer,
+}
+
+#[derive(Resource)]
+struct FootprintAssets {
+    print: Handle<Mesh>,
+    /// Dry prints by fade step, most opaque first
+    dry: [Handle<StandardMaterial>; FADE_STEPS],
+    /// Toxic prints by fade step, most opaque first
+    wet: [Handle<StandardMaterial>; FADE_STEPS],
+    dust_mesh: Handle<Mesh>,
+    dust_material: Handle<StandardMaterial>,
+}
+
+/// Where the player was in the footstep cycle last frame
+#[derive(Default)]
+struct Footsteps {
+    /// Which half of the stride the legs were in; each change is a footstep
+    half: u8,
+    airborne: bool,
+    /// Seconds the feet stay stained
+    wet: f32,
+}
+
+fn setup_footprint_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut fade = |color: Color, opacity: f32, emissive: LinearRgba| {
+        std::array::from_fn(|step| {
+            let alpha = opacity * (1.0 - step as f32 / FADE_STEPS as f32);
+            materials.add(StandardMaterial {
+                base_color: color.with_alpha(alpha),
+                emissive: emissive * alpha,
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 1.0,
+                ..default()
+            })
+        })
+    };
+    let dry = fade(Color::srgb(0.12, 0.1, 0.08), 0.35, LinearRgba::BLACK);
+    let wet = fade(
+        Color::srgb(0.3, 0.8, 0.2),
+        0.7,
+        LinearRgba::rgb(0.2, 0.6, 0.1),
+    );
+
+    commands.insert_resource(FootprintAssets {
+        print: meshes.add(Plane3d::new(Vec3::Y, Vec2::new(0.07, 0.14))),
+        dry,
+        wet,
+        dust_mesh: meshes.add(Sphere::new(0.08)),
+        dust_material: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.6, 0.55, 0.45, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// A footprint each time a sprinting stride puts a foot down, alternating sides, and a
+/// burst of dust when the player comes back down on to the ground
+fn leave_footprints(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<FootprintSettings>,
+    assets: Res<FootprintAssets>,
+    mut rng: ResMut<GameRng>,
+    mut steps: Local<Footsteps>,
+    rapier_context: ReadRapierContext,
+    player_q: Query<(
+        Entity,
+        &Transform,
+        &Player,
+        &PlayerActionState,
+        &PlayerAnimation,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+    floors: Query<(), With<Floor>>,
+    pools: Query<(&Transform, &ToxicPool)>,
+) {
+    let Ok((player, transform, state, action_state, animation, output)) = player_q.single() else {
+        return;
+    };
+    let feet = transform.translation - Vec3::Y * (PLAYER_HALF_HEIGHT + PLAYER_RADIUS);
+
+    let in_pool = pools.iter().any(|(pool_transform, pool)| {
+        (feet - pool_transform.translation).with_y(0.0).length() <= pool.radius
+    });
+    steps.wet = if in_pool {
+        WET_FEET_TIME
+    } else {
+        (steps.wet - time.delta_secs()).max(0.0)
+    };
+
+    let grounded = output.is_none_or(|output| output.grounded);
+    let landed = grounded && steps.airborne;
+    steps.airborne = !grounded;
+
+    let half = (animation.stride_phase() / PI) as u8;
+    let stepped = half != steps.half;
+    steps.half = half;
+
+    if !settings.enabled || !grounded {
+        return;
+    }
+
+    let right = *transform.right();
+    if landed {
+        for side in [-1.0, 1.0] {
+            spawn_dust(
+                &mut commands,
+                &assets,
+                &mut rng,
+                feet + right * side * FOOT_OFFSET,
+                LANDING_DUST,
+            );
+        }
+    }
+    if !stepped || !action_state.state.sprinting() {
+        return;
+    }
+
+    let side = if half == 0 { 1.0 } else { -1.0 };
+    let foot = feet + right * side * FOOT_OFFSET;
+    spawn_dust(&mut commands, &assets, &mut rng, foot, STEP_DUST);
+
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .exclude_collider(player);
+    let mut hit: Option<(Entity, f32)> = None;
+    context.with_query_pipeline(filter, |query_pipeline| {
+        hit = query_pipeline.cast_ray(foot + Vec3::Y * 0.5, Vec3::NEG_Y, 1.0, true);
+    });
+    // Prints only take on the floor, not on crates or ramps
+    let Some((_, distance)) = hit.filter(|(entity, _)| floors.contains(*entity)) else {
+        return;
+    };
+
+    let wet = steps.wet > 0.0;
+    let (materials, lifetime) = if wet {
+        (&assets.wet, WET_FOOTPRINT_LIFETIME)
+    } else {
+        (&assets.dry, FOOTPRINT_LIFETIME)
+    };
+    // Lifted a hair, and above blood splatters, so they don't flicker
+    let point = foot + Vec3::Y * (0.5 - distance + 0.025);
+    commands.spawn((
+        Mesh3d(assets.print.clone()),
+        MeshMaterial3d(materials[0].clone()),
+        Transform::from_translation(point).with_rotation(Quat::from_rotation_y(state.yaw)),
+        Footprint {
+            age: Timer::from_seconds(lifetime, TimerMode::Once),
+            wet,
+            step: 0,
+        },
+        Budgeted(BudgetCategory::Decal),
+    ));
+}
+
+fn spawn_dust(
+    commands: &mut Commands,
+    assets: &FootprintAssets,
+    rng: &mut GameRng,
+    foot: Vec3,
+    count: usize,
+) {
+    for _ in 0..count {
+        let angle = rng.random_range(0.0..TAU);
+        let outward = Vec3::new(angle.cos(), 0.0, angle.sin());
+        commands.spawn((
+            Mesh3d(assets.dust_mesh.clone()),
+            MeshMaterial3d(assets.dust_material.clone()),
+            Transform::from_translation(foot + outward * 0.1 + Vec3::Y * 0.05),
+            DustPuff {
+                velocity: outward * rng.random_range(0.5..1.2) + Vec3::Y * 0.6,
+                lifetime: Timer::from_seconds(DUST_LIFETIME, TimerMode::Once),
+            },
+            Budgeted(BudgetCategory::Debris),
+        ));
+    }
+}
+
+fn fade_footprints(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<FootprintAssets>,
+    mut footprints: Query<(
+        Entity,
+        &mut Footprint,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    for (entity, mut footprint, mut material) in footprints.iter_mut() {
+        footprint.age.tick(time.delta());
+        if footprint.age.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let step = ((footprint.age.fraction() * FADE_STEPS as f32) as usize).min(FADE_STEPS - 1);
+        if step != footprint.step {
+            footprint.step = step;
+            let materials = if footprint.wet {
+                &assets.wet
+            } else {
+                &assets.dry
+            };
+            material.0 = materials[step].clone();
+        }
+    }
+}
+
+fn fly_dust_puffs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut puffs: Query<(Entity, &mut Transform, &mut DustPuff)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut transform, mut puff) in puffs.iter_mut() {
+        puff.lifetime.tick(time.delta());
+        if puff.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Dust hangs in the air rather than falling
+        puff.velocity *= 1.0 - (4.0 * dt).min(1.0);
+        transform.translation += puff.velocity * dt;
+        transform.scale = Vec3::splat(1.0 + puff.lifetime.fraction());
+    }
+}
+
+fn despawn_footprints(
+    mut commands: Commands,
+    effects: Query<Entity, Or<(With<Footprint>, With<DustPuff>)>>,
+) {
+    for entity in effects.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,96 @@
+use super::{PlayerActionState, PlayerAnimation, PLAYER_HALF_HEIGHT, PLAYER_RADIUS};
+use crate::ui::{AudioBus, GameState};
+use crate::world::{ground_below, surface_of, SurfaceMaterial};
+use bevy::audio::{Pitch, Volume};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+/// A footstep sound each time the player's stride puts a foot down, picked by the
+/// SurfaceMaterial under them, and louder at a sprint
+pub struct FootstepAudioPlugin;
+
+impl Plugin for FootstepAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_footstep_sounds)
+            .add_systems(Update, play_footsteps.run_if(in_state(GameState::Playing)));
+    }
+}
+
+const FOOTSTEP_VOLUME: f32 = 0.06;
+const SPRINT_FOOTSTEP_VOLUME: f32 = 0.1;
+
+/// Left and right foot on each surface: frequency and length of the tone
+fn footstep_tones(surface: SurfaceMaterial) -> [(f32, f32); 2] {
+    match surface {
+        SurfaceMaterial::Concrete => [(180.0, 0.05), (165.0, 0.05)],
+        SurfaceMaterial::Wood => [(240.0, 0.06), (225.0, 0.06)],
+        SurfaceMaterial::Metal => [(880.0, 0.04), (820.0, 0.04)],
+        SurfaceMaterial::Dirt => [(110.0, 0.07), (100.0, 0.07)],
+        SurfaceMaterial::Water => [(420.0, 0.09), (380.0, 0.09)],
+    }
+}
+
+/// Sound sets indexed by SurfaceMaterial::index; synthesized, like the stinger
+#[derive(Resource)]
+struct FootstepSounds(Vec<[Handle<Pitch>; 2]>);
+
+fn setup_footstep_sounds(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    let sets = SurfaceMaterial::ALL
+        .iter()
+        .map(|surface| {
+            footstep_tones(*surface).map(|(frequency, seconds)| {
+                pitches.add(Pitch::new(frequency, Duration::from_secs_f32(seconds)))
+            })
+        })
+        .collect();
+    commands.insert_resource(FootstepSounds(sets));
+}
+
+/// Steps follow the rig's stride like the footprints do, each one probing the ground
+/// under the player for its surface
+fn play_footsteps(
+    mut commands: Commands,
+    sounds: Res<FootstepSounds>,
+    mut last_half: Local<u8>,
+    rapier_context: ReadRapierContext,
+    player_q: Query<(
+        &Transform,
+        &PlayerActionState,
+        &PlayerAnimation,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+    surfaces: Query<&SurfaceMaterial>,
+    parents: Query<&ChildOf>,
+) {
+    let Ok((transform, action_state, animation, output)) = player_q.single() else {
+        return;
+    };
+    let half = (animation.stride_phase() / PI) as u8;
+    let stepped = half != *last_half;
+    *last_half = half;
+    if !stepped || !output.is_none_or(|output| output.grounded) {
+        return;
+    }
+
+    let Ok(context) = rapier_context.single() else {
+        return;
+    };
+    let feet = transform.translation - Vec3::Y * (PLAYER_HALF_HEIGHT + PLAYER_RADIUS);
+    let Some((ground, _)) = ground_below(&context, feet + Vec3::Y * 0.5) else {
+        return;
+    };
+    let surface = surface_of(ground, &surfaces, &parents);
+
+    let volume = if action_state.state.sprinting() {
+        SPRINT_FOOTSTEP_VOLUME
+    } else {
+        FOOTSTEP_VOLUME
+    };
+    commands.spawn((
+        AudioPlayer(sounds.0[surface.index()][half as usize % 2].clone()),
+        PlaybackSettings::DESPAWN.with_volume(Volume::Linear(volume)),
+        AudioBus::Sfx,
+    ));
+}
@@ -0,0 +1,206 @@
+use super::{
+    BulletTime, DeathCamera, Player, PlayerActions, PlayerActionsSet, PlayerHealth, ScriptedCamera,
+    ThirdPersonCamera,
+};
+use crate::combat::DebugRay;
+use crate::enemies::{Spawner, WaveState, Zombie, ZombieDied};
+use crate::ui::GameState;
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy_rapier3d::prelude::*;
+
+/// Slow-motion side shot of the last zombie of a wave going down. While it runs the
+/// game clock drops to KILL_CAM_TIME_SCALE, the camera leaves follow_player, and the
+/// tracer of the killing shot hangs thick in the air. Any key or click skips it, and
+/// the player dying hands the camera straight to the death camera.
+pub struct KillCamPlugin;
+
+impl Plugin for KillCamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            lock_player_actions
+                .after(PlayerActionsSet)
+                .run_if(any_with_component::<KillCam>),
+        )
+        .add_systems(
+            Update,
+            (start_kill_cam, update_kill_cam)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), end_kill_cam);
+    }
+}
+
+/// Seconds the kill-cam lasts, on the real clock
+const KILL_CAM_DURATION: f32 = 1.5;
+/// Game clock speed while the kill-cam runs
+const KILL_CAM_TIME_SCALE: f32 = 0.2;
+/// Real seconds the camera takes to swing round to the kill
+const KILL_CAM_SWING: f32 = 0.3;
+/// How far to the side of the kill the camera sits
+const KILL_CAM_DISTANCE: f32 = 4.0;
+/// How much thicker the killing shot's tracer is drawn
+const TRACER_THICKNESS: f32 = 3.0;
+
+/// Camera framing the last kill of a wave; follow_player leaves it alone until it's
+/// removed
+#[derive(Component)]
+pub struct KillCam {
+    timer: Timer,
+    from: Transform,
+    to: Transform,
+    /// Game clock speed to go back to
+    time_speed: f32,
+}
+
+/// The player watches; nothing they press reaches gameplay
+fn lock_player_actions(mut actions: ResMut<PlayerActions>) {
+    *actions = PlayerActions::default();
+}
+
+/// Starts when a zombie dies with no other zombie left alive and no portal left to
+/// send more, so it plays just before WaveCleared
+fn start_kill_cam(
+    mut commands: Commands,
+    mut died_events: MessageReader<ZombieDied>,
+    waves: Res<WaveState>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    rapier_context: ReadRapierContext,
+    player_q: Query<(Entity, &Transform, &PlayerHealth), With<Player>>,
+    zombies: Query<&Zombie>,
+    portals: Query<&Spawner>,
+    camera_q: Query<
+        (Entity, &Transform),
+        (
+            With<ThirdPersonCamera>,
+            Without<KillCam>,
+            Without<DeathCamera>,
+            Without<ScriptedCamera>,
+        ),
+    >,
+    mut rays: Query<(&mut Transform, &mut DebugRay), (Without<ThirdPersonCamera>, Without<Player>)>,
+) {
+    let Some(event) = died_events.read().last() else {
+        return;
+    };
+    if !waves.active
+        || zombies.iter().any(|zombie| zombie.health > 0.0)
+        || portals.iter().any(|portal| portal.remaining > 0)
+    {
+        return;
+    }
+    let Ok((player, player_transform, health)) = player_q.single() else {
+        return;
+    };
+    if health.current <= 0.0 {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+
+    // Side-on to the line from the player, on whichever side the camera already is
+    let target = event.position + Vec3::Y * 0.8;
+    let towards = (event.position - player_transform.translation)
+        .with_y(0.0)
+        .normalize_or(Vec3::Z);
+    let mut side = towards.cross(Vec3::Y);
+    if side.dot(camera_transform.translation - event.position) < 0.0 {
+        side = -side;
+    }
+    let direction = (side * KILL_CAM_DISTANCE - towards + Vec3::Y * 0.8).normalize();
+    let mut distance = KILL_CAM_DISTANCE;
+    if let Ok(context) = rapier_context.single() {
+        let filter = QueryFilter::default()
+            .exclude_sensors()
+            .exclude_rigid_body(player);
+        context.with_query_pipeline(filter, |query_pipeline| {
+            if let Some((_, toi)) = query_pipeline.cast_ray(target, direction, distance, true) {
+                distance = (toi - 0.3).max(1.0);
+            }
+        });
+    }
+    let to = Transform::from_translation(target + direction * distance).looking_at(target, Vec3::Y);
+
+    // The killing shot's tracer was drawn this frame or the last; thicken it and keep
+    // it up for the whole shot
+    for (mut transform, mut ray) in rays.iter_mut() {
+        let young = ray.timer.elapsed_secs() < 0.1;
+        let axis = transform.rotation * Vec3::Y;
+        let offset = event.position - transform.translation;
+        let passes_through = (offset - axis * offset.dot(axis)).length() < 1.0;
+        if young && passes_through {
+            transform.scale.x *= TRACER_THICKNESS;
+            transform.scale.z *= TRACER_THICKNESS;
+            ray.timer =
+                Timer::from_seconds(KILL_CAM_DURATION * KILL_CAM_TIME_SCALE, TimerMode::Once);
+        }
+    }
+
+    // Bullet time would put the clock back to normal over the kill-cam's
+    if bullet_time.active {
+        bullet_time.active = false;
+        virtual_time.set_relative_speed(1.0);
+    }
+    let time_speed = virtual_time.relative_speed();
+    virtual_time.set_relative_speed(KILL_CAM_TIME_SCALE);
+
+    commands.entity(camera).insert(KillCam {
+        timer: Timer::from_seconds(KILL_CAM_DURATION, TimerMode::Once),
+        from: *camera_transform,
+        to,
+        time_speed,
+    });
+}
+
+/// Runs on real time so the shot keeps its pace on the slowed clock
+fn update_kill_cam(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    player_q: Query<&PlayerHealth, With<Player>>,
+    mut camera_q: Query<(Entity, &mut Transform, &mut KillCam, Has<DeathCamera>)>,
+) {
+    let Ok((camera, mut cam_transform, mut kill_cam, death_camera)) = camera_q.single_mut() else {
+        return;
+    };
+
+    kill_cam.timer.tick(time.delta());
+    // The click that fired the killing shot mustn't count as a skip
+    let skipped = kill_cam.timer.elapsed_secs() > KILL_CAM_SWING
+        && (keys.get_just_pressed().next().is_some()
+            || mouse_button.get_just_pressed().next().is_some());
+    let died = death_camera || player_q.single().is_ok_and(|health| health.current <= 0.0);
+    if kill_cam.timer.is_finished() || skipped || died {
+        virtual_time.set_relative_speed(kill_cam.time_speed);
+        commands.entity(camera).remove::<KillCam>();
+        // The death camera orbits from wherever the camera is, so leave it be
+        if !died {
+            *cam_transform = kill_cam.from;
+        }
+        return;
+    }
+
+    let t = (kill_cam.timer.elapsed_secs() / KILL_CAM_SWING).min(1.0);
+    // Ease in and out so the swing doesn't jolt at either end
+    let t = t * t * (3.0 - 2.0 * t);
+    cam_transform.translation = kill_cam.from.translation.lerp(kill_cam.to.translation, t);
+    cam_transform.rotation = kill_cam.from.rotation.slerp(kill_cam.to.rotation, t);
+}
+
+/// Leaving Playing puts the clock back, so pausing mid-shot can't strand it slowed
+fn end_kill_cam(
+    mut commands: Commands,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    camera_q: Query<(Entity, &KillCam)>,
+) {
+    for (camera, kill_cam) in camera_q.iter() {
+        virtual_time.set_relative_speed(kill_cam.time_speed);
+        commands.entity(camera).remove::<KillCam>();
+    }
+}
@@ -1,5 +1,39 @@
+mod action_state;
+mod actions;
+mod ally;
+mod armor;
+mod bullet_time;
 mod camera;
+mod companion;
+mod feedback;
+mod flinch;
+mod footprints;
+mod footstep_audio;
+mod kill_cam;
+mod photo_mode;
 mod player;
+mod progression;
+mod rig;
+mod shove;
+mod weapon_animation;
+mod weapon_sway;
 
+pub use action_state::*;
+pub use actions::*;
+pub use ally::*;
+pub use armor::*;
+pub use bullet_time::*;
 pub use camera::*;
+pub use companion::*;
+pub use feedback::*;
+pub use flinch::*;
+pub use footprints::*;
+pub use footstep_audio::*;
+pub use kill_cam::*;
+pub use photo_mode::*;
 pub use player::*;
+pub use progression::*;
+pub use rig::*;
+pub use shove::*;
+pub use weapon_animation::*;
+pub use weapon_sway::*;
@@ -0,0 +1,275 @@
+use super::ThirdPersonCamera;
+use crate::ui::GameState;
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{save_to_disk, Screenshot};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::PhotoMode),
+            (enter_photo_mode, spawn_photo_mode_ui),
+        )
+        .add_systems(
+            OnExit(GameState::PhotoMode),
+            (exit_photo_mode, despawn_photo_mode_ui),
+        )
+        .add_systems(
+            Update,
+            (
+                fly_camera_look,
+                fly_camera_move,
+                fly_camera_lens,
+                toggle_photo_mode_ui,
+                capture_screenshot,
+            )
+                .run_if(in_state(GameState::PhotoMode)),
+        );
+    }
+}
+
+/// Camera state captured when entering photo mode so it can be restored exactly
+#[derive(Resource)]
+struct PhotoModeSnapshot {
+    transform: Transform,
+    fov: f32,
+}
+
+/// Detached fly camera controller that replaces follow_player in photo mode
+#[derive(Component)]
+struct FlyCamera {
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+    speed: f32,
+}
+
+#[derive(Component)]
+struct PhotoModeUi;
+
+fn enter_photo_mode(
+    mut commands: Commands,
+    camera_q: Query<(Entity, &Transform, &Projection), With<ThirdPersonCamera>>,
+) {
+    let Ok((entity, transform, projection)) = camera_q.single() else {
+        return;
+    };
+
+    let fov = match projection {
+        Projection::Perspective(perspective) => perspective.fov,
+        _ => std::f32::consts::FRAC_PI_4,
+    };
+    commands.insert_resource(PhotoModeSnapshot {
+        transform: *transform,
+        fov,
+    });
+
+    let (yaw, pitch, roll) = transform.rotation.to_euler(EulerRot::YXZ);
+    commands.entity(entity).insert(FlyCamera {
+        yaw,
+        pitch,
+        roll,
+        speed: 5.0,
+    });
+}
+
+fn exit_photo_mode(
+    mut commands: Commands,
+    snapshot: Option<Res<PhotoModeSnapshot>>,
+    mut camera_q: Query<(Entity, &mut Transform, &mut Projection), With<ThirdPersonCamera>>,
+) {
+    let Ok((entity, mut transform, mut projection)) = camera_q.single_mut() else {
+        return;
+    };
+
+    // Restore the exact third-person view the player left
+    if let Some(snapshot) = snapshot {
+        *transform = snapshot.transform;
+        if let Projection::Perspective(perspective) = projection.as_mut() {
+            perspective.fov = snapshot.fov;
+        }
+    }
+
+    commands.entity(entity).remove::<FlyCamera>();
+    commands.remove_resource::<PhotoModeSnapshot>();
+}
+
+fn fly_camera_look(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    mut camera_q: Query<(&mut Transform, &mut FlyCamera)>,
+) {
+    let Ok((mut transform, mut fly)) = camera_q.single_mut() else {
+        return;
+    };
+
+    let sensitivity = 0.003;
+    fly.yaw -= mouse_motion.delta.x * sensitivity;
+    fly.pitch = (fly.pitch - mouse_motion.delta.y * sensitivity).clamp(-1.5, 1.5);
+
+    // Roll on Z/C, reset with R
+    let roll_speed = 1.0;
+    if keys.pressed(KeyCode::KeyZ) {
+        fly.roll += roll_speed * time.delta_secs();
+    }
+    if keys.pressed(KeyCode::KeyC) {
+        fly.roll -= roll_speed * time.delta_secs();
+    }
+    if keys.just_pressed(KeyCode::KeyR) {
+        fly.roll = 0.0;
+    }
+
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, fly.yaw, fly.pitch, fly.roll);
+}
+
+fn fly_camera_move(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut camera_q: Query<(&mut Transform, &mut FlyCamera)>,
+) {
+    let Ok((mut transform, mut fly)) = camera_q.single_mut() else {
+        return;
+    };
+
+    // Scroll changes fly speed
+    if scroll.delta.y != 0.0 {
+        fly.speed = (fly.speed * (1.0 + scroll.delta.y * 0.1)).clamp(0.5, 50.0);
+    }
+
+    let forward = *transform.forward();
+    let right = *transform.right();
+
+    let mut direction = Vec3::ZERO;
+    if keys.pressed(KeyCode::KeyW) {
+        direction += forward;
+    }
+    if keys.pressed(KeyCode::KeyS) {
+        direction -= forward;
+    }
+    if keys.pressed(KeyCode::KeyD) {
+        direction += right;
+    }
+    if keys.pressed(KeyCode::KeyA) {
+        direction -= right;
+    }
+    if keys.pressed(KeyCode::KeyE) {
+        direction += Vec3::Y;
+    }
+    if keys.pressed(KeyCode::KeyQ) {
+        direction -= Vec3::Y;
+    }
+
+    transform.translation += direction.normalize_or_zero() * fly.speed * time.delta_secs();
+}
+
+fn fly_camera_lens(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+    mut camera_q: Query<&mut Projection, With<FlyCamera>>,
+) {
+    let Ok(mut projection) = camera_q.single_mut() else {
+        return;
+    };
+
+    let Projection::Perspective(perspective) = projection.as_mut() else {
+        return;
+    };
+
+    // FOV on +/- (narrow to zoom in)
+    let fov_speed = 0.5;
+    if keys.pressed(KeyCode::Equal) {
+        perspective.fov -= fov_speed * time.delta_secs();
+    }
+    if keys.pressed(KeyCode::Minus) {
+        perspective.fov += fov_speed * time.delta_secs();
+    }
+    perspective.fov = perspective.fov.clamp(0.2, 2.0);
+}
+
+fn spawn_photo_mode_ui(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(12.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            PhotoModeUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("PHOTO MODE"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+
+            for line in [
+                "WASD / Q E - Move",
+                "Mouse - Look    Scroll - Speed",
+                "Z / C - Roll    R - Reset roll",
+                "+ / - - Field of view",
+                "P - Screenshot    H - Hide UI",
+                "Esc - Back to pause menu",
+            ] {
+                parent.spawn((
+                    Text::new(line),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                ));
+            }
+        });
+}
+
+fn despawn_photo_mode_ui(mut commands: Commands, ui_query: Query<Entity, With<PhotoModeUi>>) {
+    for entity in ui_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn toggle_photo_mode_ui(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut ui_query: Query<&mut Visibility, With<PhotoModeUi>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+
+    for mut visibility in ui_query.iter_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn capture_screenshot(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
+    if !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let path = format!("photo-{}.png", timestamp);
+
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+}
@@ -1,6 +1,11 @@
-use crate::combat::{ShootCooldown, WeaponInventory};
-use crate::ui::GameState;
-use bevy::input::mouse::AccumulatedMouseMotion;
+use super::{
+    spawn_player_rig, AnimationState, BulletTime, DeathCamera, Flinch, PlayerActionState,
+    PlayerActions, PlayerAnimation, PlayerArmor, PlayerPerks, ThirdPersonCamera,
+};
+use crate::combat::{BurstState, ChargingState, QueuedShot, ReloadState, WeaponInventory};
+use crate::enemies::Team;
+use crate::ui::{DifficultyModifiers, GameState, StartingLoadout};
+use crate::world::{BalanceData, Interpolated, PhysicsLayer};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
@@ -8,30 +13,58 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_player).add_systems(
-            Update,
-            (player_rotation, player_movement)
-                .chain()
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.add_systems(Startup, spawn_player)
+            .add_systems(
+                Update,
+                player_rotation
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(any_with_component::<DeathCamera>)),
+            )
+            .add_systems(
+                Update,
+                regenerate_health
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(any_with_component::<DeathCamera>)),
+            )
+            .add_systems(
+                FixedUpdate,
+                player_movement
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(any_with_component::<DeathCamera>)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_player,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_player,
+            );
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Default)]
+#[require(Interpolated, PlayerActionState)]
 pub struct Player {
     pub yaw: f32,
 }
 
-impl Default for Player {
-    fn default() -> Self {
-        Self { yaw: 0.0 }
-    }
-}
-
 #[derive(Component)]
 pub struct PlayerHealth {
     pub current: f32,
     pub max: f32,
+    /// Ignore all damage (dev console `god`)
+    pub invulnerable: bool,
+    /// When recent softened hits landed, for BurstMitigation
+    pub recent_hits: Vec<f64>,
+    /// Game time health was last lost, which holds off regeneration
+    pub last_hurt: Option<f64>,
 }
 
 impl Default for PlayerHealth {
@@ -39,74 +72,242 @@ impl Default for PlayerHealth {
         Self {
             current: 100.0,
             max: 100.0,
+            invulnerable: false,
+            recent_hits: Vec::new(),
+            last_hurt: None,
+        }
+    }
+}
+
+/// Health regained per second at a regen scale of 1.0
+const HEALTH_REGEN_RATE: f32 = 2.0;
+/// Seconds without being hurt before health starts coming back
+const HEALTH_REGEN_DELAY: f64 = 5.0;
+
+impl PlayerHealth {
+    /// Regain health over `dt` seconds at `scale` times the base rate, unless hurt too
+    /// recently. The dead stay dead.
+    pub fn regenerate(&mut self, now: f64, dt: f32, scale: f32) {
+        if self.current <= 0.0 {
+            return;
+        }
+        if self
+            .last_hurt
+            .is_some_and(|at| now - at < HEALTH_REGEN_DELAY)
+        {
+            return;
         }
+        self.current = (self.current + HEALTH_REGEN_RATE * scale * dt).min(self.max);
     }
 }
 
 #[derive(Component)]
 struct Speed {
     value: f32,
+    sprint_multiplier: f32,
 }
 
 fn player_rotation(
-    mouse_motion: Res<AccumulatedMouseMotion>,
+    actions: Res<PlayerActions>,
+    camera_q: Query<&ThirdPersonCamera>,
     mut player_q: Query<(&mut Transform, &mut Player)>,
 ) {
+    // While free-looking the mouse orbits the camera and the heading stays put
+    if camera_q.iter().any(|camera| camera.free_look_active) {
+        return;
+    }
+
     let sensitivity = 0.003;
 
     for (mut transform, mut player) in player_q.iter_mut() {
-        player.yaw -= mouse_motion.delta.x * sensitivity;
+        player.yaw -= actions.look.x * sensitivity;
         transform.rotation = Quat::from_rotation_y(player.yaw);
     }
 }
 
+fn regenerate_health(
+    time: Res<Time<Virtual>>,
+    difficulty: Res<DifficultyModifiers>,
+    mut player_q: Query<&mut PlayerHealth, With<Player>>,
+) {
+    for mut health in player_q.iter_mut() {
+        health.regenerate(
+            time.elapsed_secs_f64(),
+            time.delta_secs(),
+            difficulty.health_regen,
+        );
+    }
+}
+
 fn player_movement(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<PlayerActions>,
     time: Res<Time>,
-    mut player_q: Query<(&Transform, &Speed, &mut KinematicCharacterController), With<Player>>,
+    bullet_time: Res<BulletTime>,
+    perks: Res<PlayerPerks>,
+    mut player_q: Query<(
+        &Transform,
+        &Speed,
+        &PlayerActionState,
+        &mut KinematicCharacterController,
+        &mut PlayerAnimation,
+    )>,
 ) {
-    for (player_transform, player_speed, mut controller) in player_q.iter_mut() {
+    for (player_transform, player_speed, action_state, mut controller, mut animation) in
+        player_q.iter_mut()
+    {
         let forward = player_transform.forward();
         let right = player_transform.right();
 
-        let mut direction = Vec3::ZERO;
+        let mut direction = *forward * actions.movement.y + *right * actions.movement.x;
+        direction.y = 0.0;
+        let direction = direction.normalize_or_zero();
 
-        if keys.pressed(KeyCode::KeyW) {
-            direction += *forward;
-        }
-        if keys.pressed(KeyCode::KeyS) {
-            direction -= *forward;
-        }
-        if keys.pressed(KeyCode::KeyD) {
-            direction += *right;
-        }
-        if keys.pressed(KeyCode::KeyA) {
-            direction -= *right;
+        // Left Shift while moving, unless firing or staggered (see ActionState)
+        let base_speed = player_speed.value * perks.move_speed_scale();
+        let speed = if action_state.state.sprinting() {
+            base_speed * player_speed.sprint_multiplier
+        } else {
+            base_speed
+        };
+
+        if direction == Vec3::ZERO {
+            animation.state = AnimationState::Idle;
+            animation.speed = 0.0;
+        } else {
+            animation.state = AnimationState::Walk;
+            animation.speed = speed;
         }
 
-        direction.y = 0.0;
-        let movement = direction.normalize_or_zero() * player_speed.value * time.delta_secs();
-        controller.translation = Some(movement);
+        // Constant downward pull keeps the capsule planted on the floor. Bullet time
+        // slows the clock but not the player.
+        let dt = bullet_time.player_delta(&time);
+        let movement = direction * speed * dt;
+        controller.translation = Some(movement + Vec3::NEG_Y * 9.81 * dt);
     }
 }
 
+/// Capsule segment half-length; with the radius the player stands 1.8m tall
+pub const PLAYER_HALF_HEIGHT: f32 = 0.5;
+pub const PLAYER_RADIUS: f32 = 0.4;
+/// Muzzle height above the capsule centre, where shots leave from
+pub const PLAYER_MUZZLE_HEIGHT: f32 = 0.5;
+/// Camera pivot height above the capsule centre
+pub const PLAYER_PIVOT_HEIGHT: f32 = 1.1;
+
+/// Capsule centre with its feet resting on the floor
+const PLAYER_SPAWN: Vec3 = Vec3::new(0.0, PLAYER_HALF_HEIGHT + PLAYER_RADIUS, 0.0);
+
 fn spawn_player(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    loadout: Res<StartingLoadout>,
+    balance: Res<BalanceData>,
+) {
+    commands
+        .spawn((
+            Transform::from_translation(PLAYER_SPAWN),
+            Visibility::default(),
+            Speed {
+                value: 5.0,
+                sprint_multiplier: 1.6,
+            },
+            Player::default(),
+            Team::Survivors,
+            PlayerHealth::default(),
+            PlayerArmor::default(),
+            PlayerAnimation::default(),
+            Flinch::default(),
+            WeaponInventory::starting(loadout.weapon(), &balance),
+            // Physics components
+            RigidBody::KinematicPositionBased,
+            Collider::capsule_y(PLAYER_HALF_HEIGHT, PLAYER_RADIUS),
+            PhysicsLayer::Player.groups(),
+            KinematicCharacterController {
+                snap_to_ground: Some(CharacterLength::Absolute(0.2)),
+                filter_groups: Some(PhysicsLayer::Player.groups()),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            spawn_player_rig(parent, &mut meshes, &mut materials, loadout.skin())
+        });
+}
+
+/// Put the player back at the spawn point with fresh health and weapons for a new run
+fn reset_player(
+    mut commands: Commands,
+    loadout: Res<StartingLoadout>,
+    balance: Res<BalanceData>,
+    mut player_q: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Player,
+            &mut PlayerHealth,
+            &mut WeaponInventory,
+        ),
+        With<Player>,
+    >,
 ) {
-    commands.spawn((
-        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
-        MeshMaterial3d(materials.add(Color::srgb(0.0, 0.0, 1.0))),
-        Transform::from_xyz(0.0, 0.5, 0.0),
-        Speed { value: 5.0 },
-        Player::default(),
-        PlayerHealth::default(),
-        WeaponInventory::default(),
-        ShootCooldown::default(),
-        // Physics components
-        RigidBody::KinematicPositionBased,
-        Collider::cuboid(0.5, 0.5, 0.5),
-        KinematicCharacterController::default(),
-    ));
+    for (entity, mut transform, mut player, mut health, mut inventory) in player_q.iter_mut() {
+        *transform = Transform::from_translation(PLAYER_SPAWN);
+        *player = Player::default();
+        *health = PlayerHealth::default();
+        *inventory = WeaponInventory::starting(loadout.weapon(), &balance);
+        commands
+            .entity(entity)
+            .insert((
+                PlayerArmor::default(),
+                Flinch::default(),
+                PlayerActionState::default(),
+            ))
+            .remove::<(ReloadState, BurstState, QueuedShot, ChargingState)>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hurt_at(current: f32, at: f64) -> PlayerHealth {
+        PlayerHealth {
+            current,
+            last_hurt: Some(at),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn no_regen_straight_after_a_hit() {
+        let mut health = hurt_at(50.0, 10.0);
+        health.regenerate(10.0 + HEALTH_REGEN_DELAY - 0.1, 1.0, 1.0);
+        assert_eq!(health.current, 50.0);
+
+        health.regenerate(10.0 + HEALTH_REGEN_DELAY, 1.0, 1.0);
+        assert_eq!(health.current, 50.0 + HEALTH_REGEN_RATE);
+    }
+
+    #[test]
+    fn difficulty_scales_regen() {
+        let mut easy = hurt_at(50.0, 0.0);
+        easy.regenerate(60.0, 1.0, 1.5);
+        assert_eq!(easy.current, 50.0 + HEALTH_REGEN_RATE * 1.5);
+
+        // Hardcore turns it off
+        let mut hardcore = hurt_at(50.0, 0.0);
+        hardcore.regenerate(60.0, 1.0, 0.0);
+        assert_eq!(hardcore.current, 50.0);
+    }
+
+    #[test]
+    fn regen_stops_at_max_and_not_for_the_dead() {
+        let mut nearly = hurt_at(99.5, 0.0);
+        nearly.regenerate(60.0, 1.0, 1.0);
+        assert_eq!(nearly.current, nearly.max);
+
+        let mut dead = hurt_at(0.0, 0.0);
+        dead.regenerate(60.0, 1.0, 1.0);
+        assert_eq!(dead.current, 0.0);
+    }
 }
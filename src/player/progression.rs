@@ -0,0 +1,520 @@
+use super::{Player, PlayerHealth};
+use crate::enemies::{PortalDestroyed, WaveCleared, ZombieDied, ZombieKind};
+use crate::ui::{GameState, TextRole, UiTheme};
+use crate::world::GameRng;
+use bevy::prelude::*;
+use rand::Rng;
+
+/// Kills earn XP towards levels; each level banks a perk, chosen from three random
+/// offers once the current wave is cleared (see PerkSelectPlugin). Perks last for the
+/// run and feed into compute_effective_stats alongside weapon attachments.
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Progression>()
+            .init_resource::<Score>()
+            .init_resource::<PlayerPerks>()
+            .init_resource::<PerkOffer>()
+            .add_systems(
+                Update,
+                (
+                    grant_kill_xp,
+                    grant_wave_bonus,
+                    offer_perks_after_wave,
+                    update_progression_hud,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_progression_hud)
+            .add_systems(OnExit(GameState::Playing), despawn_progression_hud)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_progression,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_progression,
+            );
+    }
+}
+
+/// Perks offered per level-up
+const PERK_CHOICES: usize = 3;
+const XP_BAR_WIDTH: f32 = 200.0;
+const TOUGH_HEALTH: f32 = 20.0;
+/// Extra XP when the killing shot was to the head
+const HEADSHOT_XP: u32 = 5;
+/// Bonus for shooting down a zombie portal
+const PORTAL_XP: u32 = 50;
+/// Points for clearing a wave, per wave number
+const WAVE_CLEAR_POINTS: u32 = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Perk {
+    /// +15% reload speed
+    QuickHands,
+    /// +20 max health
+    Tough,
+    /// +10% movement speed
+    FleetFoot,
+    /// Explosions reach 25% further
+    Demolitions,
+    /// -15% spread
+    SteadyAim,
+}
+
+impl Perk {
+    pub const ALL: [Perk; 5] = [
+        Perk::QuickHands,
+        Perk::Tough,
+        Perk::FleetFoot,
+        Perk::Demolitions,
+        Perk::SteadyAim,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Perk::QuickHands => "Quick Hands",
+            Perk::Tough => "Tough",
+            Perk::FleetFoot => "Fleet Foot",
+            Perk::Demolitions => "Demolitions",
+            Perk::SteadyAim => "Steady Aim",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Perk::QuickHands => "+15% reload speed",
+            Perk::Tough => "+20 max health",
+            Perk::FleetFoot => "+10% movement speed",
+            Perk::Demolitions => "Explosions reach 25% further",
+            Perk::SteadyAim => "-15% spread",
+        }
+    }
+
+    /// Two-letter badge for the HUD
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Perk::QuickHands => "QH",
+            Perk::Tough => "TG",
+            Perk::FleetFoot => "FF",
+            Perk::Demolitions => "DM",
+            Perk::SteadyAim => "SA",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Perk::QuickHands => Color::srgb(0.9, 0.75, 0.3),
+            Perk::Tough => Color::srgb(0.9, 0.3, 0.3),
+            Perk::FleetFoot => Color::srgb(0.3, 0.8, 0.4),
+            Perk::Demolitions => Color::srgb(1.0, 0.5, 0.2),
+            Perk::SteadyAim => Color::srgb(0.4, 0.6, 1.0),
+        }
+    }
+}
+
+/// Perks taken this run. The same perk can be taken more than once and stacks.
+#[derive(Resource, Default)]
+pub struct PlayerPerks {
+    pub taken: Vec<Perk>,
+}
+
+impl PlayerPerks {
+    /// Take a perk, applying the ones that change the player straight away
+    pub fn take(&mut self, perk: Perk, health: Option<&mut PlayerHealth>) {
+        self.taken.push(perk);
+        if let (Perk::Tough, Some(health)) = (perk, health) {
+            health.max += TOUGH_HEALTH;
+            health.current += TOUGH_HEALTH;
+        }
+    }
+
+    /// Give back the most recent perk, undoing what `take` applied straight away
+    pub fn drop_last(&mut self, health: Option<&mut PlayerHealth>) -> Option<Perk> {
+        let perk = self.taken.pop()?;
+        if let (Perk::Tough, Some(health)) = (perk, health) {
+            health.max -= TOUGH_HEALTH;
+            health.current = health.current.min(health.max);
+        }
+        Some(perk)
+    }
+
+    fn count(&self, perk: Perk) -> i32 {
+        self.taken.iter().filter(|taken| **taken == perk).count() as i32
+    }
+
+    /// Multiplier on reload time; each Quick Hands reloads 15% faster
+    pub fn reload_time_scale(&self) -> f32 {
+        1.0 / (1.0 + 0.15 * self.count(Perk::QuickHands) as f32)
+    }
+
+    pub fn spread_scale(&self) -> f32 {
+        0.85_f32.powi(self.count(Perk::SteadyAim))
+    }
+
+    pub fn move_speed_scale(&self) -> f32 {
+        1.0 + 0.1 * self.count(Perk::FleetFoot) as f32
+    }
+
+    pub fn explosion_radius_scale(&self) -> f32 {
+        1.0 + 0.25 * self.count(Perk::Demolitions) as f32
+    }
+}
+
+/// XP and levels for the current run
+#[derive(Resource)]
+pub struct Progression {
+    pub level: u32,
+    /// XP towards the next level
+    pub xp: u32,
+    /// Level-ups whose perk hasn't been picked yet
+    pub pending_perks: u32,
+}
+
+impl Default for Progression {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            xp: 0,
+            pending_perks: 0,
+        }
+    }
+}
+
+impl Progression {
+    /// XP needed to go from `level` to the next
+    pub fn xp_to_next(level: u32) -> u32 {
+        40 + 20 * level
+    }
+
+    /// Add XP, returning how many levels were gained
+    pub fn add_xp(&mut self, xp: u32) -> u32 {
+        self.xp += xp;
+        let mut gained = 0;
+        while self.xp >= Self::xp_to_next(self.level) {
+            self.xp -= Self::xp_to_next(self.level);
+            self.level += 1;
+            gained += 1;
+        }
+        self.pending_perks += gained;
+        gained
+    }
+}
+
+/// Points banked this run, spent in the shop between waves
+#[derive(Resource, Default)]
+pub struct Score {
+    pub points: u32,
+}
+
+/// XP for a kill, by the kind of zombie and whether it was a headshot
+fn kill_xp(died: &ZombieDied) -> u32 {
+    let base = match died.kind {
+        ZombieKind::Walker => 10,
+        ZombieKind::Runner => 15,
+    };
+    if died.headshot {
+        base + HEADSHOT_XP
+    } else {
+        base
+    }
+}
+
+/// Perks currently on offer
+#[derive(Resource, Default)]
+pub struct PerkOffer {
+    pub perks: Vec<Perk>,
+    /// Wave whose end brought up the offer
+    pub after_wave: u32,
+}
+
+/// Pick distinct perks to offer. Rolled on the game RNG so a fixed seed always offers
+/// the same perks.
+pub fn roll_perk_offer(rng: &mut impl Rng) -> Vec<Perk> {
+    rand::seq::index::sample(rng, Perk::ALL.len(), PERK_CHOICES)
+        .into_iter()
+        .map(|index| Perk::ALL[index])
+        .collect()
+}
+
+#[derive(Component)]
+struct ProgressionHud;
+
+#[derive(Component)]
+struct LevelText;
+
+#[derive(Component)]
+struct XpBarFill;
+
+#[derive(Component)]
+struct PerkBadges;
+
+fn grant_kill_xp(
+    mut died_events: MessageReader<ZombieDied>,
+    mut portal_events: MessageReader<PortalDestroyed>,
+    player_q: Query<Entity, With<Player>>,
+    mut progression: ResMut<Progression>,
+    mut score: ResMut<Score>,
+) {
+    let Ok(player) = player_q.single() else {
+        return;
+    };
+    for event in died_events.read() {
+        if event.killer == Some(player) {
+            // A kill is worth as many points as XP
+            let xp = kill_xp(event);
+            progression.add_xp(xp);
+            score.points += xp;
+        }
+    }
+    for event in portal_events.read() {
+        if event.destroyed_by == Some(player) {
+            progression.add_xp(PORTAL_XP);
+        }
+    }
+}
+
+fn grant_wave_bonus(mut cleared_events: MessageReader<WaveCleared>, mut score: ResMut<Score>) {
+    for cleared in cleared_events.read() {
+        score.points += WAVE_CLEAR_POINTS * cleared.wave;
+    }
+}
+
+/// Level-ups wait for the wave to be cleared so the choice never interrupts a fight
+fn offer_perks_after_wave(
+    mut cleared_events: MessageReader<WaveCleared>,
+    progression: Res<Progression>,
+    mut offer: ResMut<PerkOffer>,
+    mut rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(cleared) = cleared_events.read().last() else {
+        return;
+    };
+    if progression.pending_perks == 0 {
+        return;
+    }
+    offer.after_wave = cleared.wave;
+    offer.perks = roll_perk_offer(&mut **rng);
+    next_state.set(GameState::PerkSelect);
+}
+
+fn reset_progression(
+    mut progression: ResMut<Progression>,
+    mut score: ResMut<Score>,
+    mut perks: ResMut<PlayerPerks>,
+    mut offer: ResMut<PerkOffer>,
+) {
+    *progression = Progression::default();
+    *score = Score::default();
+    perks.taken.clear();
+    offer.perks.clear();
+}
+
+fn spawn_progression_hud(mut commands: Commands, theme: Res<UiTheme>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(36.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-XP_BAR_WIDTH / 2.0)),
+                width: Val::Px(XP_BAR_WIDTH),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ProgressionHud,
+        ))
+        .with_children(|hud| {
+            hud.spawn((Text::new("LV 1"), theme.text(TextRole::Caption), LevelText));
+            hud.spawn((
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(3.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.3, 0.9, 0.5)),
+                    XpBarFill,
+                ));
+            });
+        });
+
+    // Badges for the perks taken, above the drone readout
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(64.0),
+            left: Val::Px(24.0),
+            column_gap: Val::Px(4.0),
+            ..default()
+        },
+        ProgressionHud,
+        PerkBadges,
+    ));
+}
+
+fn update_progression_hud(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    progression: Res<Progression>,
+    score: Res<Score>,
+    perks: Res<PlayerPerks>,
+    mut level_text: Query<&mut Text, With<LevelText>>,
+    mut fill_query: Query<&mut Node, With<XpBarFill>>,
+    badges: Query<(Entity, Option<&Children>), With<PerkBadges>>,
+) {
+    for mut text in level_text.iter_mut() {
+        let label = if progression.pending_perks > 0 {
+            format!(
+                "LV {} - perk after this wave - {} pts",
+                progression.level, score.points
+            )
+        } else {
+            format!("LV {} - {} pts", progression.level, score.points)
+        };
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+    let fraction = progression.xp as f32 / Progression::xp_to_next(progression.level) as f32;
+    for mut node in fill_query.iter_mut() {
+        node.width = Val::Percent(fraction * 100.0);
+    }
+
+    // Perks are mostly only added, so append badges for the new ones; a reroll from
+    // the shop takes one back, and then the row is rebuilt
+    for (entity, children) in badges.iter() {
+        let mut shown = children.map_or(0, |children| children.len());
+        if shown > perks.taken.len() {
+            commands.entity(entity).despawn_children();
+            shown = 0;
+        }
+        for perk in perks.taken.iter().skip(shown) {
+            let badge = commands
+                .spawn((
+                    Node {
+                        width: Val::Px(24.0),
+                        height: Val::Px(24.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(perk.color()),
+                ))
+                .with_child((
+                    Text::new(perk.badge()),
+                    // Sized to fit the badge rather than following the theme
+                    TextFont {
+                        font_size: 11.0,
+                        ..theme.font(TextRole::Caption)
+                    },
+                    TextColor(Color::BLACK),
+                ))
+                .id();
+            commands.entity(entity).add_child(badge);
+        }
+    }
+}
+
+fn despawn_progression_hud(mut commands: Commands, huds: Query<Entity, With<ProgressionHud>>) {
+    for entity in huds.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xp_carries_over_into_the_next_level() {
+        let mut progression = Progression::default();
+        let first = Progression::xp_to_next(1);
+        let second = Progression::xp_to_next(2);
+
+        assert_eq!(progression.add_xp(first - 1), 0);
+        assert_eq!(progression.add_xp(11), 1);
+        assert_eq!(progression.level, 2);
+        assert_eq!(progression.xp, 10);
+
+        // Enough for two levels at once banks two perks
+        assert_eq!(
+            progression.add_xp(second - 10 + Progression::xp_to_next(3)),
+            2
+        );
+        assert_eq!(progression.level, 4);
+        assert_eq!(progression.xp, 0);
+        assert_eq!(progression.pending_perks, 3);
+    }
+
+    #[test]
+    fn headshots_are_worth_more() {
+        let died = |kind, headshot| ZombieDied {
+            position: Vec3::ZERO,
+            kind,
+            killer: None,
+            headshot,
+        };
+        assert_eq!(kill_xp(&died(ZombieKind::Walker, false)), 10);
+        assert_eq!(kill_xp(&died(ZombieKind::Walker, true)), 10 + HEADSHOT_XP);
+        assert_eq!(kill_xp(&died(ZombieKind::Runner, true)), 15 + HEADSHOT_XP);
+    }
+
+    #[test]
+    fn perk_offers_follow_the_seed() {
+        let offer = roll_perk_offer(&mut *GameRng::seeded(21));
+        assert_eq!(offer.len(), PERK_CHOICES);
+        for (index, perk) in offer.iter().enumerate() {
+            assert!(!offer[..index].contains(perk), "{perk:?} offered twice");
+        }
+        assert_eq!(roll_perk_offer(&mut *GameRng::seeded(21)), offer);
+    }
+
+    #[test]
+    fn perks_stack() {
+        let perks = PlayerPerks {
+            taken: vec![Perk::QuickHands, Perk::FleetFoot, Perk::QuickHands],
+        };
+        assert!((perks.reload_time_scale() - 1.0 / 1.3).abs() < 1e-6);
+        assert!((perks.move_speed_scale() - 1.1).abs() < 1e-6);
+        assert_eq!(perks.spread_scale(), 1.0);
+        assert_eq!(perks.explosion_radius_scale(), 1.0);
+    }
+
+    #[test]
+    fn tough_comes_and_goes_with_its_health() {
+        let mut perks = PlayerPerks::default();
+        let mut health = PlayerHealth::default();
+        let (max, current) = (health.max, health.current);
+
+        perks.take(Perk::Tough, Some(&mut health));
+        assert_eq!(health.max, max + TOUGH_HEALTH);
+        assert_eq!(health.current, current + TOUGH_HEALTH);
+
+        assert_eq!(perks.drop_last(Some(&mut health)), Some(Perk::Tough));
+        assert_eq!(health.max, max);
+        assert_eq!(health.current, current);
+        assert!(perks.taken.is_empty());
+        assert_eq!(perks.drop_last(Some(&mut health)), None);
+    }
+}
@@ -0,0 +1,268 @@
+use super::{
+    Player, PlayerActions, ThirdPersonCamera, WeaponMagazine, WeaponModel, WeaponSocket,
+    MAGAZINE_REST, PLAYER_PIVOT_HEIGHT, WEAPON_MODEL_REST, WEAPON_SOCKET_REST,
+};
+use crate::ui::{GameState, StartingLoadout};
+use bevy::prelude::*;
+
+pub struct PlayerRigPlugin;
+
+impl Plugin for PlayerRigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (animate_rig, hide_rig_from_camera)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            apply_player_skin.run_if(resource_changed::<StartingLoadout>),
+        );
+    }
+}
+
+/// Colours for the player's torso and legs
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerSkin {
+    pub name: &'static str,
+    body: Color,
+    legs: Color,
+}
+
+/// Every skin, in the order the Unlocks screen cycles them; the first is always available
+pub const PLAYER_SKINS: &[PlayerSkin] = &[
+    PlayerSkin {
+        name: "Standard",
+        body: Color::srgb(0.0, 0.0, 1.0),
+        legs: Color::srgb(0.1, 0.1, 0.3),
+    },
+    PlayerSkin {
+        name: "Crimson",
+        body: Color::srgb(0.7, 0.05, 0.05),
+        legs: Color::srgb(0.2, 0.05, 0.05),
+    },
+    PlayerSkin {
+        name: "Woodland",
+        body: Color::srgb(0.3, 0.4, 0.2),
+        legs: Color::srgb(0.25, 0.2, 0.1),
+    },
+    PlayerSkin {
+        name: "Gilded",
+        body: Color::srgb(0.85, 0.65, 0.15),
+        legs: Color::srgb(0.15, 0.12, 0.08),
+    },
+];
+
+/// What the body is doing, set by player_movement every frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum AnimationState {
+    #[default]
+    Idle,
+    Walk,
+}
+
+/// Animation driver for the procedural rig
+#[derive(Component, Default)]
+pub struct PlayerAnimation {
+    pub state: AnimationState,
+    /// Ground speed this frame in metres per second
+    pub speed: f32,
+    /// Walk cycle phase in radians
+    phase: f32,
+    /// Running clock for the idle sway
+    idle_time: f32,
+}
+
+impl PlayerAnimation {
+    /// Walk cycle phase in radians; one full turn is a stride of two footsteps
+    pub fn stride_phase(&self) -> f32 {
+        self.phase
+    }
+}
+
+/// Hip joint; the leg mesh hangs below it and swings around X
+#[derive(Component)]
+struct RigLeg {
+    /// 1.0 or -1.0 so the legs swing in opposite directions
+    side: f32,
+}
+
+/// Torso and head, pitched with the camera so aiming up or down reads on the model
+#[derive(Component)]
+struct RigUpperBody;
+
+/// Mesh recoloured by the selected skin
+#[derive(Component, Clone, Copy)]
+enum SkinPart {
+    Body,
+    Legs,
+}
+
+const HIP_HEIGHT: f32 = -0.1;
+const LEG_LENGTH: f32 = 0.8;
+/// Metres covered by one full stride, so the legs match the actual ground speed
+const STRIDE_LENGTH: f32 = 1.6;
+const LEG_SWING: f32 = 0.6;
+/// Camera pitch at which the upper body stands straight
+const NEUTRAL_PITCH: f32 = -0.3;
+
+/// Build the torso, head, held weapon and legs under the player entity.
+///
+/// Everything is made of primitive meshes, so the rig is in place on the first
+/// frame without waiting on the AssetServer.
+pub fn spawn_player_rig(
+    parent: &mut ChildSpawnerCommands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    skin: &PlayerSkin,
+) {
+    let body_material = materials.add(skin.body);
+    let head_material = materials.add(Color::srgb(0.9, 0.75, 0.6));
+    let leg_material = materials.add(skin.legs);
+    let weapon_material = materials.add(Color::srgb(0.15, 0.15, 0.15));
+    let magazine_material = materials.add(Color::srgb(0.3, 0.3, 0.28));
+    let leg_mesh = meshes.add(Cuboid::new(0.2, LEG_LENGTH, 0.2));
+
+    parent
+        .spawn((
+            Transform::from_xyz(0.0, HIP_HEIGHT, 0.0),
+            Visibility::default(),
+            RigUpperBody,
+        ))
+        .with_children(|upper| {
+            upper.spawn((
+                Mesh3d(meshes.add(Cuboid::new(0.5, 0.6, 0.3))),
+                MeshMaterial3d(body_material),
+                Transform::from_xyz(0.0, 0.3, 0.0),
+                SkinPart::Body,
+            ));
+            upper.spawn((
+                Mesh3d(meshes.add(Sphere::new(0.2))),
+                MeshMaterial3d(head_material),
+                Transform::from_xyz(0.0, 0.8, 0.0),
+            ));
+            upper
+                .spawn((
+                    Transform::from_translation(WEAPON_SOCKET_REST),
+                    Visibility::default(),
+                    WeaponSocket::default(),
+                ))
+                .with_children(|socket| {
+                    socket
+                        .spawn((
+                            Mesh3d(meshes.add(Cuboid::new(0.08, 0.12, 0.5))),
+                            MeshMaterial3d(weapon_material),
+                            Transform::from_translation(WEAPON_MODEL_REST),
+                            WeaponModel,
+                        ))
+                        .with_child((
+                            Mesh3d(meshes.add(Cuboid::new(0.05, 0.14, 0.07))),
+                            MeshMaterial3d(magazine_material),
+                            Transform::from_translation(MAGAZINE_REST),
+                            WeaponMagazine,
+                        ));
+                });
+        });
+
+    for side in [-1.0, 1.0] {
+        parent
+            .spawn((
+                Transform::from_xyz(side * 0.13, HIP_HEIGHT, 0.0),
+                Visibility::default(),
+                RigLeg { side },
+            ))
+            .with_children(|hip| {
+                hip.spawn((
+                    Mesh3d(leg_mesh.clone()),
+                    MeshMaterial3d(leg_material.clone()),
+                    Transform::from_xyz(0.0, -LEG_LENGTH / 2.0, 0.0),
+                    SkinPart::Legs,
+                ));
+            });
+    }
+}
+
+fn animate_rig(
+    time: Res<Time>,
+    camera_q: Query<&ThirdPersonCamera>,
+    mut player_q: Query<&mut PlayerAnimation, With<Player>>,
+    mut legs: Query<(&RigLeg, &mut Transform), Without<RigUpperBody>>,
+    mut upper_body: Query<&mut Transform, (With<RigUpperBody>, Without<RigLeg>)>,
+) {
+    let Ok(mut animation) = player_q.single_mut() else {
+        return;
+    };
+    let dt = time.delta_secs();
+
+    let swing = match animation.state {
+        AnimationState::Walk => {
+            animation.phase += animation.speed / STRIDE_LENGTH * std::f32::consts::TAU * dt;
+            animation.phase %= std::f32::consts::TAU;
+            animation.phase.sin() * LEG_SWING
+        }
+        AnimationState::Idle => {
+            // Ease the legs back together instead of freezing mid-stride
+            animation.phase *= 1.0 - (10.0 * dt).min(1.0);
+            animation.phase.sin() * LEG_SWING
+        }
+    };
+
+    for (leg, mut transform) in legs.iter_mut() {
+        transform.rotation = Quat::from_rotation_x(swing * leg.side);
+    }
+
+    animation.idle_time += dt;
+    let sway = if animation.state == AnimationState::Idle {
+        (animation.idle_time * 1.5).sin() * 0.03
+    } else {
+        0.0
+    };
+    let pitch = camera_q
+        .single()
+        .map(|camera| (camera.pitch - NEUTRAL_PITCH) * 0.5)
+        .unwrap_or(0.0);
+
+    for mut transform in upper_body.iter_mut() {
+        transform.rotation = Quat::from_rotation_x(pitch) * Quat::from_rotation_z(sway);
+    }
+}
+
+/// Hide the body while aiming down sights, and when the camera is pushed right up
+/// against it, e.g. backed into a wall
+fn hide_rig_from_camera(
+    actions: Res<PlayerActions>,
+    camera_q: Query<&Transform, With<ThirdPersonCamera>>,
+    mut player_q: Query<(&Transform, &mut Visibility), (With<Player>, Without<ThirdPersonCamera>)>,
+) {
+    let (Ok(camera_transform), Ok((player_transform, mut visibility))) =
+        (camera_q.single(), player_q.single_mut())
+    else {
+        return;
+    };
+
+    let pivot = player_transform.translation + Vec3::Y * PLAYER_PIVOT_HEIGHT;
+    let target = if actions.aim || camera_transform.translation.distance(pivot) < 1.0 {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    visibility.set_if_neq(target);
+}
+
+/// Recolour the rig whenever a different skin is picked
+fn apply_player_skin(
+    loadout: Res<StartingLoadout>,
+    parts: Query<(&SkinPart, &MeshMaterial3d<StandardMaterial>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let skin = loadout.skin();
+    for (part, material) in parts.iter() {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = match part {
+                SkinPart::Body => skin.body,
+                SkinPart::Legs => skin.legs,
+            };
+        }
+    }
+}
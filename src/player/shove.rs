@@ -0,0 +1,133 @@
+use super::{CameraShake, Player, PlayerActions};
+use crate::combat::HitEvent;
+use crate::enemies::{Climbing, Shoved, SpawnProtection, Staggered, Zombie};
+use crate::ui::{GameState, Subtitle};
+use crate::world::SurfaceMaterial;
+use bevy::prelude::*;
+
+/// F pushes back the nearest zombies in front of the player, staggering them and
+/// resetting their bites, to make room when swarmed
+pub struct ShovePlugin;
+
+impl Plugin for ShovePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShoveCooldown>()
+            .add_systems(Update, shove.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_shove,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_shove,
+            );
+    }
+}
+
+const SHOVE_RANGE: f32 = 2.0;
+/// Half the cone's angle; the cone is 90 degrees wide
+const SHOVE_HALF_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+const SHOVE_MAX_TARGETS: usize = 3;
+const SHOVE_DISTANCE: f32 = 1.5;
+/// Seconds the push is spread over
+const SHOVE_TIME: f32 = 0.2;
+const SHOVE_STAGGER: f32 = 0.6;
+const SHOVE_DAMAGE: f32 = 5.0;
+const SHOVE_COOLDOWN: f32 = 3.0;
+
+/// Time until the next shove; starts ready
+#[derive(Resource)]
+pub struct ShoveCooldown(pub Timer);
+
+impl Default for ShoveCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(SHOVE_COOLDOWN, TimerMode::Once);
+        timer.finish();
+        Self(timer)
+    }
+}
+
+fn shove(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<PlayerActions>,
+    mut cooldown: ResMut<ShoveCooldown>,
+    player_q: Query<(Entity, &Transform), With<Player>>,
+    // Climbers are out of reach of a shove until they're up
+    mut zombies: Query<
+        (Entity, &Transform, &mut Zombie),
+        (Without<SpawnProtection>, Without<Climbing>),
+    >,
+    mut hit_events: MessageWriter<HitEvent>,
+    mut shake_events: MessageWriter<CameraShake>,
+    mut subtitles: MessageWriter<Subtitle>,
+) {
+    cooldown.0.tick(time.delta());
+    if !actions.shove || !cooldown.0.is_finished() {
+        return;
+    }
+    let Ok((player, player_transform)) = player_q.single() else {
+        return;
+    };
+
+    let origin = player_transform.translation;
+    let forward = player_transform.forward().with_y(0.0).normalize_or_zero();
+    let min_dot = SHOVE_HALF_ANGLE.cos();
+
+    let mut targets: Vec<(Entity, Vec3, f32)> = zombies
+        .iter()
+        .filter_map(|(entity, transform, _)| {
+            let offset = (transform.translation - origin).with_y(0.0);
+            let distance = offset.length();
+            let direction = offset.normalize_or_zero();
+            (distance <= SHOVE_RANGE && direction.dot(forward) >= min_dot)
+                .then_some((entity, direction, distance))
+        })
+        .collect();
+    targets.sort_by(|a, b| a.2.total_cmp(&b.2));
+    targets.truncate(SHOVE_MAX_TARGETS);
+
+    // A shove into thin air still costs the cooldown
+    cooldown.0.reset();
+    shake_events.write(CameraShake { trauma: 0.25 });
+    subtitles.write(Subtitle("[Shove]".to_string()));
+
+    for (entity, direction, _) in targets {
+        if let Ok((_, transform, mut zombie)) = zombies.get_mut(entity) {
+            // Knocked out of its bite: the wind-up starts over
+            zombie.attack_cooldown.reset();
+            hit_events.write(HitEvent {
+                entity,
+                damage: SHOVE_DAMAGE,
+                direction,
+                point: transform.translation,
+                normal: -direction,
+                distance: 0.0,
+                source: Some(player),
+                zone: None,
+                surface: SurfaceMaterial::default(),
+                headshot: false,
+                crit: false,
+            });
+        }
+        commands.entity(entity).try_insert((
+            Shoved {
+                velocity: direction * (SHOVE_DISTANCE / SHOVE_TIME),
+                timer: Timer::from_seconds(SHOVE_TIME, TimerMode::Once),
+            },
+            Staggered {
+                timer: Timer::from_seconds(SHOVE_STAGGER, TimerMode::Once),
+            },
+        ));
+    }
+}
+
+fn reset_shove(mut cooldown: ResMut<ShoveCooldown>) {
+    *cooldown = ShoveCooldown::default();
+}
@@ -0,0 +1,208 @@
+use super::{Player, PlayerActions, PlayerActionsSet};
+use crate::combat::{ChargingState, ReloadState};
+use crate::ui::GameState;
+use bevy::prelude::*;
+
+/// Keyframed moves on the held weapon model. Holding I lifts it for a look over, and a
+/// reload drops it and swaps the magazine in step with the reload timer, so progress
+/// can be read off the weapon. Only the model under the WeaponSocket moves, so sway
+/// and kick still apply on top.
+pub struct WeaponAnimationPlugin;
+
+impl Plugin for WeaponAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            update_inspect
+                .after(PlayerActionsSet)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            animate_weapon_model.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Where the weapon model sits in the socket's space
+pub const WEAPON_MODEL_REST: Vec3 = Vec3::new(0.0, 0.0, -0.15);
+/// Where the magazine sits in the weapon model's space
+pub const MAGAZINE_REST: Vec3 = Vec3::new(0.0, -0.1, -0.05);
+
+/// Seconds a full inspect takes when I is tapped
+const INSPECT_TIME: f32 = 1.6;
+/// Point the inspect holds at while I stays down
+const INSPECT_HOLD: f32 = 0.6;
+
+/// Offset from a part's rest pose at one point in an animation
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe {
+    /// From 0 at the start of the animation to 1 at the end
+    pub at: f32,
+    pub translation: Vec3,
+    /// Euler angles in radians, applied in XYZ order
+    pub rotation: Vec3,
+}
+
+impl Keyframe {
+    pub const fn new(at: f32, translation: Vec3, rotation: Vec3) -> Self {
+        Self {
+            at,
+            translation,
+            rotation,
+        }
+    }
+}
+
+/// Offset at `t` along `keys` (in order of `at`), eased in and out between each pair
+pub fn sample_keyframes(keys: &[Keyframe], t: f32) -> Transform {
+    let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+        return Transform::IDENTITY;
+    };
+    let t = t.clamp(0.0, 1.0);
+    let (from, to) = match keys.iter().position(|key| key.at > t) {
+        Some(0) => (first, first),
+        Some(next) => (&keys[next - 1], &keys[next]),
+        None => (last, last),
+    };
+    let span = to.at - from.at;
+    let s = if span > 0.0 {
+        let s = (t - from.at) / span;
+        s * s * (3.0 - 2.0 * s)
+    } else {
+        0.0
+    };
+
+    let rotation = from.rotation.lerp(to.rotation, s);
+    Transform {
+        translation: from.translation.lerp(to.translation, s),
+        rotation: Quat::from_euler(EulerRot::XYZ, rotation.x, rotation.y, rotation.z),
+        scale: Vec3::ONE,
+    }
+}
+
+/// Lift towards the camera, roll over to show the side, settle back
+const INSPECT_KEYS: [Keyframe; 5] = [
+    Keyframe::new(0.0, Vec3::ZERO, Vec3::ZERO),
+    Keyframe::new(0.25, Vec3::new(-0.05, 0.15, 0.1), Vec3::new(0.3, 0.5, 0.0)),
+    Keyframe::new(
+        INSPECT_HOLD,
+        Vec3::new(-0.05, 0.15, 0.1),
+        Vec3::new(0.3, 0.5, 1.2),
+    ),
+    Keyframe::new(0.8, Vec3::new(0.0, 0.05, 0.05), Vec3::new(0.1, 0.2, 0.2)),
+    Keyframe::new(1.0, Vec3::ZERO, Vec3::ZERO),
+];
+
+/// Dip and cant the weapon for the length of the reload
+const RELOAD_KEYS: [Keyframe; 4] = [
+    Keyframe::new(0.0, Vec3::ZERO, Vec3::ZERO),
+    Keyframe::new(0.15, Vec3::new(0.0, -0.06, 0.02), Vec3::new(0.2, 0.0, 0.35)),
+    Keyframe::new(0.85, Vec3::new(0.0, -0.06, 0.02), Vec3::new(0.2, 0.0, 0.35)),
+    Keyframe::new(1.0, Vec3::ZERO, Vec3::ZERO),
+];
+
+/// Magazine drops out once the weapon is down and is seated again before it comes up
+const MAGAZINE_KEYS: [Keyframe; 5] = [
+    Keyframe::new(0.15, Vec3::ZERO, Vec3::ZERO),
+    Keyframe::new(0.35, Vec3::new(0.0, -0.3, 0.05), Vec3::new(0.5, 0.0, 0.0)),
+    Keyframe::new(0.6, Vec3::new(0.0, -0.3, 0.05), Vec3::new(0.5, 0.0, 0.0)),
+    Keyframe::new(0.8, Vec3::ZERO, Vec3::ZERO),
+    Keyframe::new(1.0, Vec3::ZERO, Vec3::ZERO),
+];
+
+/// Held weapon mesh, under the WeaponSocket
+#[derive(Component)]
+pub struct WeaponModel;
+
+/// Magazine cube, under the WeaponModel
+#[derive(Component)]
+pub struct WeaponMagazine;
+
+/// Weapon being looked over; it can't fire until the inspect ends or is cancelled
+#[derive(Component, Default)]
+pub struct Inspecting {
+    /// 0 to 1 through INSPECT_KEYS
+    progress: f32,
+}
+
+/// Start an inspect on I, and drop it the moment the player fires, reloads or switches
+fn update_inspect(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<PlayerActions>,
+    mut players: Query<
+        (
+            Entity,
+            Option<&mut Inspecting>,
+            Has<ReloadState>,
+            Has<ChargingState>,
+        ),
+        With<Player>,
+    >,
+) {
+    let Ok((entity, inspecting, reloading, charging)) = players.single_mut() else {
+        return;
+    };
+    let interrupted = actions.fire_pressed
+        || actions.reload
+        || actions.select_slot.is_some()
+        || actions.scroll != 0.0
+        || reloading
+        || charging;
+
+    let Some(mut inspecting) = inspecting else {
+        if actions.inspect && !interrupted {
+            commands.entity(entity).insert(Inspecting::default());
+        }
+        return;
+    };
+    if interrupted {
+        commands.entity(entity).remove::<Inspecting>();
+        return;
+    }
+
+    let before = inspecting.progress;
+    inspecting.progress += time.delta_secs() / INSPECT_TIME;
+    // Holding I keeps the weapon turned over
+    if actions.inspect_held && before <= INSPECT_HOLD {
+        inspecting.progress = inspecting.progress.min(INSPECT_HOLD);
+    }
+    if inspecting.progress >= 1.0 {
+        commands.entity(entity).remove::<Inspecting>();
+    }
+}
+
+fn animate_weapon_model(
+    players: Query<(Option<&Inspecting>, Option<&ReloadState>), With<Player>>,
+    mut models: Query<&mut Transform, (With<WeaponModel>, Without<WeaponMagazine>)>,
+    mut magazines: Query<&mut Transform, (With<WeaponMagazine>, Without<WeaponModel>)>,
+) {
+    let Ok((inspecting, reload)) = players.single() else {
+        return;
+    };
+    let (model_offset, magazine_offset) = match (reload, inspecting) {
+        // Follows the timer, so a longer reload plays out slower
+        (Some(reload), _) => {
+            let progress = reload.0.fraction();
+            (
+                sample_keyframes(&RELOAD_KEYS, progress),
+                sample_keyframes(&MAGAZINE_KEYS, progress),
+            )
+        }
+        (None, Some(inspecting)) => (
+            sample_keyframes(&INSPECT_KEYS, inspecting.progress),
+            Transform::IDENTITY,
+        ),
+        (None, None) => (Transform::IDENTITY, Transform::IDENTITY),
+    };
+
+    for mut transform in models.iter_mut() {
+        transform.translation = WEAPON_MODEL_REST + model_offset.translation;
+        transform.rotation = model_offset.rotation;
+    }
+    for mut transform in magazines.iter_mut() {
+        transform.translation = MAGAZINE_REST + magazine_offset.translation;
+        transform.rotation = magazine_offset.rotation;
+    }
+}
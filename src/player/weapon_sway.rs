@@ -0,0 +1,160 @@
+use super::{
+    smoothing_factor, AnimationState, Player, PlayerActionState, PlayerActions, PlayerAnimation,
+    PlayerPerks, ThirdPersonCamera,
+};
+use crate::combat::{compute_effective_stats, ShotFired, WeaponInventory};
+use crate::ui::GameState;
+use bevy::prelude::*;
+
+/// Procedural sway, bob and kick on the held weapon. Only the socket's local transform
+/// moves; shots are aimed from the player and camera, so none of this reaches the
+/// firing direction.
+pub struct WeaponSwayPlugin;
+
+impl Plugin for WeaponSwayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WeaponSwaySettings>()
+            .add_systems(Update, sway_weapon.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Where the weapon rests in the upper body's space, at the right hand
+pub const WEAPON_SOCKET_REST: Vec3 = Vec3::new(0.3, 0.35, -0.3);
+/// Furthest the weapon may lag behind the camera, in radians
+const MAX_SWAY: f32 = 0.15;
+/// Ground speed at which the bob reaches full strength
+const WALK_SPEED: f32 = 5.0;
+/// Furthest a run of shots may push the weapon back, in metres
+const MAX_KICK: f32 = 0.15;
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct WeaponSwaySettings {
+    /// Off for motion-sensitive players; the weapon then stays at rest
+    pub enabled: bool,
+    /// Radians of lag per radian the camera turns
+    pub sway_amount: f32,
+    /// Spring pulling the lag back to rest
+    pub sway_stiffness: f32,
+    pub sway_damping: f32,
+    /// Bobs per stride (two footsteps); 2.0 bobs once per footstep
+    pub bob_frequency: f32,
+    /// Vertical bob in metres at full walking speed
+    pub bob_amplitude: f32,
+    /// Side-to-side bob in metres at full walking speed
+    pub bob_lateral: f32,
+    /// Scale on everything while looking through a scope
+    pub ads_multiplier: f32,
+    /// Metres of kick per point of shot damage
+    pub kick_per_damage: f32,
+    /// How quickly the kick recovers
+    pub kick_recovery: f32,
+}
+
+impl Default for WeaponSwaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sway_amount: 0.5,
+            sway_stiffness: 120.0,
+            sway_damping: 18.0,
+            bob_frequency: 2.0,
+            bob_amplitude: 0.025,
+            bob_lateral: 0.015,
+            ads_multiplier: 0.25,
+            kick_per_damage: 0.002,
+            kick_recovery: 12.0,
+        }
+    }
+}
+
+/// Attachment point for the held weapon model, under the rig's upper body
+#[derive(Component, Default)]
+pub struct WeaponSocket {
+    /// Lag behind the camera as (yaw, pitch) in radians
+    sway: Vec2,
+    sway_velocity: Vec2,
+    /// Backwards offset from recent shots
+    kick: f32,
+    /// Camera angles last frame, to turn rotation into lag
+    last_look: Option<Vec2>,
+}
+
+fn sway_weapon(
+    time: Res<Time>,
+    settings: Res<WeaponSwaySettings>,
+    actions: Res<PlayerActions>,
+    perks: Res<PlayerPerks>,
+    mut shots: MessageReader<ShotFired>,
+    player_q: Query<(
+        Entity,
+        &Player,
+        &PlayerActionState,
+        &PlayerAnimation,
+        &WeaponInventory,
+    )>,
+    camera_q: Query<&ThirdPersonCamera>,
+    mut sockets: Query<(&mut WeaponSocket, &mut Transform)>,
+) {
+    let Ok((player_entity, player, action_state, animation, inventory)) = player_q.single() else {
+        return;
+    };
+    let weapon = inventory.current_weapon();
+    let shots_fired = shots
+        .read()
+        .filter(|shot| shot.shooter == player_entity)
+        .count();
+
+    if !settings.enabled {
+        for (mut socket, mut transform) in sockets.iter_mut() {
+            *socket = WeaponSocket::default();
+            *transform = Transform::from_translation(WEAPON_SOCKET_REST);
+        }
+        return;
+    }
+
+    let dt = time.delta_secs();
+    let pitch = camera_q.single().map_or(0.0, |camera| camera.pitch);
+    let look = Vec2::new(player.yaw, pitch);
+    // Same rule as the camera zoom: a scope is looked through while the trigger is held
+    let aiming = actions.fire_held
+        && !action_state.state.sprinting()
+        && weapon.is_some_and(|weapon| compute_effective_stats(weapon, &perks).zoom > 0.0);
+    let scale = if aiming { settings.ads_multiplier } else { 1.0 };
+
+    for (mut socket, mut transform) in sockets.iter_mut() {
+        // Turning drags the weapon behind, then a spring brings it back
+        if let Some(last_look) = socket.last_look {
+            socket.sway -= (look - last_look) * settings.sway_amount;
+        }
+        socket.last_look = Some(look);
+        let spring =
+            -settings.sway_stiffness * socket.sway - settings.sway_damping * socket.sway_velocity;
+        socket.sway_velocity += spring * dt;
+        let velocity = socket.sway_velocity;
+        socket.sway =
+            (socket.sway + velocity * dt).clamp(Vec2::splat(-MAX_SWAY), Vec2::splat(MAX_SWAY));
+
+        if let Some(weapon) = weapon {
+            socket.kick += shots_fired as f32 * weapon.damage * settings.kick_per_damage;
+            socket.kick = socket.kick.min(MAX_KICK);
+        }
+        socket.kick *= 1.0 - smoothing_factor(settings.kick_recovery, dt);
+
+        // Bob follows the legs' stride, fading out with speed and when standing still
+        let bob = if animation.state == AnimationState::Walk {
+            let strength = (animation.speed / WALK_SPEED).min(1.0);
+            let step_phase = animation.stride_phase() * settings.bob_frequency;
+            Vec3::new(
+                (step_phase / 2.0).sin() * settings.bob_lateral,
+                (1.0 - step_phase.cos()) / 2.0 * settings.bob_amplitude,
+                0.0,
+            ) * strength
+        } else {
+            Vec3::ZERO
+        };
+
+        transform.translation = WEAPON_SOCKET_REST + (bob + Vec3::Z * socket.kick) * scale;
+        transform.rotation = Quat::from_rotation_y(socket.sway.x * scale)
+            * Quat::from_rotation_x(socket.sway.y * scale + socket.kick * 2.0 * scale);
+    }
+}
@@ -0,0 +1,98 @@
+use super::{snapshot_run, PendingLoad, RunPlayer, SaveData};
+use crate::enemies::{
+    Crawling, DrillTarget, Mover, RangeSession, Target, TargetSpawn, WaveStarted, Zombie,
+};
+use crate::ui::{GameMode, GameState};
+use bevy::prelude::*;
+
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Checkpoint>()
+            .add_message::<RetryCheckpoint>()
+            .add_systems(
+                Update,
+                capture_checkpoint
+                    .run_if(on_message::<WaveStarted>)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                retry_checkpoint.run_if(on_message::<RetryCheckpoint>),
+            )
+            // A fresh run forgets the old checkpoint; retrying keeps it for the next attempt
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                clear_checkpoint,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                clear_checkpoint.run_if(not(resource_exists::<PendingLoad>)),
+            );
+    }
+}
+
+/// In-memory snapshot taken at the start of every checkpoint wave
+#[derive(Resource, Default)]
+pub struct Checkpoint(Option<SaveData>);
+
+impl Checkpoint {
+    pub fn is_set(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// Restart the run from the last checkpoint (game-over menu)
+#[derive(Message)]
+pub struct RetryCheckpoint;
+
+fn capture_checkpoint(
+    mut checkpoint: ResMut<Checkpoint>,
+    mut started_events: MessageReader<WaveStarted>,
+    mode: Res<GameMode>,
+    session: Res<RangeSession>,
+    player_q: Query<RunPlayer>,
+    zombies: Query<(&Transform, &Zombie, Has<Crawling>)>,
+    targets: Query<(&Target, &TargetSpawn, Option<&Mover>), Without<DrillTarget>>,
+) {
+    let Some(event) = started_events
+        .read()
+        .filter(|event| event.checkpoint)
+        .last()
+    else {
+        return;
+    };
+    let Ok(player) = player_q.single() else {
+        return;
+    };
+
+    // The wave's zombies are dropped, so a retry starts the checkpoint wave fresh
+    let mut data = snapshot_run(*mode, &session, event.wave, player, &zombies, &targets);
+    data.rewind_to_wave_start();
+    checkpoint.0 = Some(data);
+}
+
+fn retry_checkpoint(
+    mut commands: Commands,
+    mut retry_events: MessageReader<RetryCheckpoint>,
+    checkpoint: Res<Checkpoint>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    retry_events.clear();
+    if let Some(data) = &checkpoint.0 {
+        // Leaving GameOver cleans up the failed attempt, then the save system applies the snapshot
+        commands.insert_resource(PendingLoad(data.clone()));
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn clear_checkpoint(mut checkpoint: ResMut<Checkpoint>) {
+    checkpoint.0 = None;
+}
@@ -0,0 +1,11 @@
+mod checkpoint;
+mod quicksave;
+#[cfg(feature = "dev_console")]
+mod replay;
+mod versioned;
+
+pub use checkpoint::*;
+pub use quicksave::*;
+#[cfg(feature = "dev_console")]
+pub use replay::*;
+pub use versioned::*;
@@ -0,0 +1,734 @@
+use crate::combat::{
+    compute_effective_stats, AmmoType, Attachment, Flare, Projectile, Weapon, WeaponInventory,
+    WeaponType, AMMO_TYPES, ATTACHMENT_SLOTS, BACKPACK_WEAPON_SLOTS,
+};
+use crate::enemies::{
+    spawn_standing_zombie, spawn_target, Crawling, DrillTarget, HealthBar, Mover, MoverPath,
+    PendingTargetRespawns, PopupTarget, RangeSession, Target, TargetAssets, TargetFragment,
+    TargetKind, TargetSpawn, TurretProjectile, WaveState, Zombie, ZombieAssets, ZombieHealthBar,
+    ZombieKind,
+};
+use crate::player::{DeathCamera, Player, PlayerArmor, PlayerHealth, PlayerPerks};
+use crate::ui::{GameMode, GameState, TextRole, UiTheme};
+use crate::world::BalanceData;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<LoadGame>()
+            .add_systems(
+                Update,
+                quicksave_input
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(any_with_component::<DeathCamera>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    read_save_file.run_if(on_message::<LoadGame>),
+                    apply_pending_load
+                        .run_if(resource_exists::<PendingLoad>)
+                        .run_if(in_state(GameState::Playing).or(in_state(GameState::PrePlaying))),
+                    update_save_notices,
+                )
+                    .chain(),
+            );
+    }
+}
+
+const SAVE_PATH: &str = "quicksave.json";
+
+/// Bump whenever SaveData changes shape; older files are rejected with a message
+const SAVE_VERSION: u32 = 7;
+
+/// Ask for the quicksave to be loaded (F9 or the main menu Continue button)
+#[derive(Message)]
+pub struct LoadGame;
+
+pub fn save_exists() -> bool {
+    Path::new(SAVE_PATH).exists()
+}
+
+/// Only the version, parsed first so incompatible files fail before the full decode
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Everything needed to rebuild a run. Score and map seeds will join this
+/// (with a version bump) once the game has them.
+#[derive(Serialize, Deserialize, Clone)]
+pub(super) struct SaveData {
+    version: u32,
+    mode: SavedMode,
+    /// Survival wave in progress, 0 outside survival
+    wave: u32,
+    player: SavedPlayer,
+    /// One entry per slot, so the length is the inventory's capacity
+    weapons: Vec<Option<SavedWeapon>>,
+    current_slot: usize,
+    zombies: Vec<SavedZombie>,
+    targets: Vec<SavedTarget>,
+    range: Option<SavedRangeSession>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub(super) enum SavedMode {
+    Survival,
+    ShootingRange,
+    Extraction,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedPlayer {
+    translation: [f32; 3],
+    yaw: f32,
+    health: f32,
+    max_health: f32,
+    armor: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedWeapon {
+    name: String,
+    current_ammo: u32,
+    /// AmmoType name of the rounds in the magazine
+    loaded_ammo: String,
+    reserves: [u32; AMMO_TYPES],
+    condition: f32,
+    /// Stuck until cleared, as it was when saved
+    jammed: bool,
+    /// Attachment names, one per slot
+    attachments: [Option<String>; ATTACHMENT_SLOTS],
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedZombie {
+    translation: [f32; 3],
+    health: f32,
+    max_health: f32,
+    #[serde(default)]
+    crawling: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+enum SavedTargetKind {
+    Standard,
+    Armored { threshold: f32 },
+    WeakPoint,
+    Explosive,
+    Dummy,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedTarget {
+    position: [f32; 3],
+    max_health: f32,
+    current_health: f32,
+    kind: SavedTargetKind,
+    turret: bool,
+    mover: Option<SavedMover>,
+    popup: Option<SavedPopup>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedMover {
+    path: SavedMoverPath,
+    speed: f32,
+    phase: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+enum SavedMoverPath {
+    Linear {
+        from: [f32; 3],
+        to: [f32; 3],
+    },
+    Circular {
+        center: [f32; 3],
+        radius: f32,
+    },
+    Pendulum {
+        pivot: [f32; 3],
+        length: f32,
+        arc: f32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedPopup {
+    raised_y: f32,
+    down_time: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedRangeSession {
+    elapsed: f32,
+    score: u32,
+    shots: u32,
+    hits: u32,
+}
+
+impl From<&Weapon> for SavedWeapon {
+    fn from(weapon: &Weapon) -> Self {
+        Self {
+            name: weapon.weapon_type.name().to_string(),
+            current_ammo: weapon.current_ammo,
+            loaded_ammo: weapon.loaded_ammo.name().to_string(),
+            reserves: weapon.reserves,
+            condition: weapon.condition,
+            jammed: weapon.jammed,
+            attachments: weapon
+                .attachments
+                .map(|attachment| attachment.map(|attachment| attachment.name().to_string())),
+        }
+    }
+}
+
+impl SavedWeapon {
+    /// A weapon of the saved type with its rounds, attachments, wear and jam; None if the
+    /// type is one this build doesn't know
+    fn restore(&self, balance: &BalanceData, perks: &PlayerPerks) -> Option<Weapon> {
+        let weapon_type = WeaponType::ALL
+            .into_iter()
+            .find(|weapon_type| weapon_type.name() == self.name)?;
+        let mut weapon = Weapon::new(weapon_type, balance);
+        weapon.attachments = self.attachments.clone().map(|name| {
+            Attachment::ALL
+                .into_iter()
+                .find(|attachment| name.as_deref() == Some(attachment.name()))
+        });
+        weapon.current_ammo = self
+            .current_ammo
+            .min(compute_effective_stats(&weapon, perks).magazine_size);
+        weapon.loaded_ammo = AmmoType::ALL
+            .into_iter()
+            .find(|ammo| ammo.name() == self.loaded_ammo)
+            .unwrap_or_default();
+        weapon.selected_ammo = weapon.loaded_ammo;
+        weapon.reserves = self.reserves;
+        weapon.condition = self.condition.clamp(0.0, 1.0);
+        weapon.jammed = self.jammed;
+        Some(weapon)
+    }
+}
+
+/// Every slot of `inventory`, empty ones included
+fn save_inventory(inventory: &WeaponInventory) -> Vec<Option<SavedWeapon>> {
+    inventory
+        .weapons
+        .iter()
+        .map(|slot| slot.as_ref().map(SavedWeapon::from))
+        .collect()
+}
+
+/// Rebuild every slot from the save, growing the inventory to the saved capacity
+fn restore_inventory(
+    inventory: &mut WeaponInventory,
+    saved: &[Option<SavedWeapon>],
+    current_slot: usize,
+    balance: &BalanceData,
+    perks: &PlayerPerks,
+) {
+    inventory.expand_to(saved.len().min(BACKPACK_WEAPON_SLOTS));
+    for (index, slot) in inventory.weapons.iter_mut().enumerate() {
+        *slot = saved
+            .get(index)
+            .and_then(Option::as_ref)
+            .and_then(|weapon| weapon.restore(balance, perks));
+    }
+    inventory.current_slot = 0;
+    inventory.switch_to(current_slot);
+    if inventory.current_weapon().is_none() {
+        inventory.cycle_next();
+    }
+}
+
+impl SaveData {
+    /// Drop the live zombies and step back a wave, so loading replays the current wave from its start
+    pub(super) fn rewind_to_wave_start(&mut self) {
+        self.zombies.clear();
+        self.wave = self.wave.saturating_sub(1);
+    }
+}
+
+/// Decoded save waiting for the Playing state before it is applied
+#[derive(Resource)]
+pub struct PendingLoad(pub(super) SaveData);
+
+/// Short-lived on-screen message for save/load results
+#[derive(Component)]
+struct SaveNotice {
+    timer: Timer,
+}
+
+impl From<&Mover> for SavedMover {
+    fn from(mover: &Mover) -> Self {
+        let path = match mover.path {
+            MoverPath::Linear { from, to } => SavedMoverPath::Linear {
+                from: from.to_array(),
+                to: to.to_array(),
+            },
+            MoverPath::Circular { center, radius } => SavedMoverPath::Circular {
+                center: center.to_array(),
+                radius,
+            },
+            MoverPath::Pendulum { pivot, length, arc } => SavedMoverPath::Pendulum {
+                pivot: pivot.to_array(),
+                length,
+                arc,
+            },
+        };
+        Self {
+            path,
+            speed: mover.speed,
+            phase: mover.phase,
+        }
+    }
+}
+
+impl From<&SavedMover> for Mover {
+    fn from(saved: &SavedMover) -> Self {
+        let path = match saved.path {
+            SavedMoverPath::Linear { from, to } => MoverPath::Linear {
+                from: Vec3::from_array(from),
+                to: Vec3::from_array(to),
+            },
+            SavedMoverPath::Circular { center, radius } => MoverPath::Circular {
+                center: Vec3::from_array(center),
+                radius,
+            },
+            SavedMoverPath::Pendulum { pivot, length, arc } => MoverPath::Pendulum {
+                pivot: Vec3::from_array(pivot),
+                length,
+                arc,
+            },
+        };
+        Mover::new(path, saved.speed).with_phase(saved.phase)
+    }
+}
+
+impl From<TargetKind> for SavedTargetKind {
+    fn from(kind: TargetKind) -> Self {
+        match kind {
+            TargetKind::Standard => SavedTargetKind::Standard,
+            TargetKind::Armored { threshold } => SavedTargetKind::Armored { threshold },
+            TargetKind::WeakPoint => SavedTargetKind::WeakPoint,
+            TargetKind::Explosive => SavedTargetKind::Explosive,
+            TargetKind::Dummy => SavedTargetKind::Dummy,
+        }
+    }
+}
+
+impl From<&SavedTargetKind> for TargetKind {
+    fn from(kind: &SavedTargetKind) -> Self {
+        match *kind {
+            SavedTargetKind::Standard => TargetKind::Standard,
+            SavedTargetKind::Armored { threshold } => TargetKind::Armored { threshold },
+            SavedTargetKind::WeakPoint => TargetKind::WeakPoint,
+            SavedTargetKind::Explosive => TargetKind::Explosive,
+            SavedTargetKind::Dummy => TargetKind::Dummy,
+        }
+    }
+}
+
+fn quicksave_input(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mode: Res<GameMode>,
+    session: Res<RangeSession>,
+    waves: Res<WaveState>,
+    player_q: Query<RunPlayer>,
+    zombies: Query<(&Transform, &Zombie, Has<Crawling>)>,
+    targets: Query<(&Target, &TargetSpawn, Option<&Mover>), Without<DrillTarget>>,
+    mut load_events: MessageWriter<LoadGame>,
+) {
+    if keys.just_pressed(KeyCode::F9) {
+        load_events.write(LoadGame);
+        return;
+    }
+
+    if !keys.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    let Ok(player) = player_q.single() else {
+        return;
+    };
+    let data = snapshot_run(*mode, &session, waves.wave, player, &zombies, &targets);
+
+    let result = serde_json::to_string_pretty(&data)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(SAVE_PATH, json).map_err(|e| e.to_string()));
+
+    match result {
+        Ok(()) => spawn_save_notice(&mut commands, &theme, "Game saved".to_string()),
+        Err(e) => spawn_save_notice(&mut commands, &theme, format!("Save failed: {}", e)),
+    }
+}
+
+/// Player components captured in a snapshot
+pub(super) type RunPlayer = (
+    &'static Transform,
+    &'static Player,
+    &'static PlayerHealth,
+    Option<&'static PlayerArmor>,
+    &'static WeaponInventory,
+);
+
+/// Capture the current run in the same shape the quicksave file uses
+pub(super) fn snapshot_run(
+    mode: GameMode,
+    session: &RangeSession,
+    wave: u32,
+    (player_transform, player, health, armor, inventory): (
+        &Transform,
+        &Player,
+        &PlayerHealth,
+        Option<&PlayerArmor>,
+        &WeaponInventory,
+    ),
+    zombies: &Query<(&Transform, &Zombie, Has<Crawling>)>,
+    targets: &Query<(&Target, &TargetSpawn, Option<&Mover>), Without<DrillTarget>>,
+) -> SaveData {
+    SaveData {
+        version: SAVE_VERSION,
+        mode: match mode {
+            GameMode::Survival => SavedMode::Survival,
+            GameMode::ShootingRange => SavedMode::ShootingRange,
+            GameMode::Extraction => SavedMode::Extraction,
+        },
+        wave,
+        player: SavedPlayer {
+            translation: player_transform.translation.to_array(),
+            yaw: player.yaw,
+            health: health.current,
+            max_health: health.max,
+            armor: armor.map_or(0.0, |armor| armor.current),
+        },
+        weapons: save_inventory(inventory),
+        current_slot: inventory.current_slot,
+        zombies: zombies
+            .iter()
+            .filter(|(_, zombie, _)| zombie.health > 0.0)
+            .map(|(transform, zombie, crawling)| SavedZombie {
+                translation: transform.translation.to_array(),
+                health: zombie.health,
+                max_health: zombie.max_health,
+                crawling,
+            })
+            .collect(),
+        targets: targets
+            .iter()
+            .filter(|(target, _, _)| target.current_health > 0.0)
+            .map(|(target, spawn, mover)| SavedTarget {
+                position: spawn.position.to_array(),
+                max_health: target.max_health,
+                current_health: target.current_health,
+                kind: spawn.kind.into(),
+                turret: spawn.turret,
+                mover: mover.map(SavedMover::from),
+                popup: spawn.popup.as_ref().map(|p| SavedPopup {
+                    raised_y: p.raised_y,
+                    down_time: p.down_time,
+                }),
+            })
+            .collect(),
+        range: (mode == GameMode::ShootingRange).then(|| SavedRangeSession {
+            elapsed: session.timer.elapsed_secs(),
+            score: session.score,
+            shots: session.shots,
+            hits: session.hits,
+        }),
+    }
+}
+
+/// Parse a save, rejecting other versions before attempting the full decode
+fn parse_save(json: &str) -> Result<SaveData, String> {
+    let header: SaveHeader =
+        serde_json::from_str(json).map_err(|e| format!("Save file is corrupt: {}", e))?;
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "Save is from an incompatible version (v{}, expected v{})",
+            header.version, SAVE_VERSION
+        ));
+    }
+    serde_json::from_str(json).map_err(|e| format!("Save file is corrupt: {}", e))
+}
+
+fn read_save_file(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    mut load_events: MessageReader<LoadGame>,
+    mut mode: ResMut<GameMode>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    load_events.clear();
+
+    let data = fs::read_to_string(SAVE_PATH)
+        .map_err(|e| format!("Could not read save: {}", e))
+        .and_then(|json| parse_save(&json));
+
+    match data {
+        Ok(data) => {
+            *mode = match data.mode {
+                SavedMode::Survival => GameMode::Survival,
+                SavedMode::ShootingRange => GameMode::ShootingRange,
+                SavedMode::Extraction => GameMode::Extraction,
+            };
+            commands.insert_resource(PendingLoad(data));
+            // Applied once the run starts, after any run setup on the transition.
+            // From the main menu that goes through the countdown like a new run.
+            next_state.set(if *state.get() == GameState::MainMenu {
+                GameState::PrePlaying
+            } else {
+                GameState::Playing
+            });
+        }
+        Err(message) => spawn_save_notice(&mut commands, &theme, message),
+    }
+}
+
+/// Tear down the current run and rebuild it from the pending save
+fn apply_pending_load(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    pending: Res<PendingLoad>,
+    zombie_assets: Res<ZombieAssets>,
+    target_assets: Res<TargetAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut player_q: Query<(
+        &mut Transform,
+        &mut Player,
+        &mut PlayerHealth,
+        Option<&mut PlayerArmor>,
+        &mut WeaponInventory,
+    )>,
+    run_entities: Query<
+        Entity,
+        Or<(
+            With<Zombie>,
+            With<ZombieHealthBar>,
+            With<Target>,
+            With<HealthBar>,
+            With<TargetFragment>,
+            With<Projectile>,
+            With<TurretProjectile>,
+            With<Flare>,
+        )>,
+    >,
+    mut pending_respawns: ResMut<PendingTargetRespawns>,
+    mut session: ResMut<RangeSession>,
+    mut waves: ResMut<WaveState>,
+    perks: Res<PlayerPerks>,
+    balance: Res<BalanceData>,
+) {
+    let data = &pending.0;
+    commands.remove_resource::<PendingLoad>();
+
+    for entity in run_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+    pending_respawns.0.clear();
+
+    if let Ok((mut transform, mut player, mut health, armor, mut inventory)) = player_q.single_mut()
+    {
+        transform.translation = Vec3::from_array(data.player.translation);
+        transform.rotation = Quat::from_rotation_y(data.player.yaw);
+        player.yaw = data.player.yaw;
+        health.current = data.player.health;
+        health.max = data.player.max_health;
+        if let Some(mut armor) = armor {
+            armor.current = data.player.armor.min(armor.max);
+        }
+
+        restore_inventory(
+            &mut inventory,
+            &data.weapons,
+            data.current_slot,
+            &balance,
+            &perks,
+        );
+    }
+
+    for (i, saved) in data.zombies.iter().enumerate() {
+        let mut zombie = Zombie::new(ZombieKind::Walker, i as u32, &balance);
+        zombie.health = saved.health;
+        zombie.max_health = saved.max_health;
+        // Restored zombies were already up, so they skip rising out of the ground
+        let entity = spawn_standing_zombie(
+            &mut commands,
+            &zombie_assets,
+            Vec3::from_array(saved.translation),
+            zombie,
+        );
+        if saved.crawling {
+            commands.entity(entity).insert(Crawling);
+        }
+    }
+
+    for saved in &data.targets {
+        let position = Vec3::from_array(saved.position);
+        let spawn = TargetSpawn {
+            position,
+            health: saved.max_health,
+            motion: saved.mover.as_ref().map(Mover::from),
+            popup: saved
+                .popup
+                .as_ref()
+                .map(|p| PopupTarget::new(p.raised_y, p.down_time)),
+            turret: saved.turret,
+            kind: (&saved.kind).into(),
+        };
+
+        let entity = spawn_target(&mut commands, &target_assets, &mut materials, spawn);
+        let mut target = Target::new(saved.max_health);
+        target.current_health = saved.current_health;
+        commands.entity(entity).insert(target);
+    }
+
+    *waves = WaveState::resume(data.wave, !data.zombies.is_empty());
+
+    if let Some(range) = &data.range {
+        *session = RangeSession::default();
+        session
+            .timer
+            .set_elapsed(Duration::from_secs_f32(range.elapsed));
+        session.score = range.score;
+        session.shots = range.shots;
+        session.hits = range.hits;
+    }
+
+    spawn_save_notice(&mut commands, &theme, "Game loaded".to_string());
+}
+
+pub(super) fn spawn_save_notice(commands: &mut Commands, theme: &UiTheme, message: String) {
+    commands.spawn((
+        Text::new(message),
+        theme.text(TextRole::Body),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            ..default()
+        },
+        GlobalZIndex(20),
+        SaveNotice {
+            timer: Timer::from_seconds(3.0, TimerMode::Once),
+        },
+    ));
+}
+
+fn update_save_notices(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut notices: Query<(Entity, &mut SaveNotice)>,
+) {
+    for (entity, mut notice) in notices.iter_mut() {
+        notice.timer.tick(time.delta());
+        if notice.timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pistol and SMG, plus a worn shotgun with an extended mag in a third slot,
+    /// holding it with the magazine topped up past its base size
+    fn backpack(balance: &BalanceData, perks: &PlayerPerks) -> WeaponInventory {
+        let mut inventory = WeaponInventory::starting(WeaponType::Smg, balance);
+        inventory.expand_to(BACKPACK_WEAPON_SLOTS);
+        let mut shotgun = Weapon::new(WeaponType::Shotgun, balance);
+        shotgun.attach(1, Attachment::ExtendedMag, perks);
+        shotgun.current_ammo = compute_effective_stats(&shotgun, perks).magazine_size;
+        shotgun.loaded_ammo = AmmoType::Incendiary;
+        shotgun.reserves = [3, 0, 0, 7];
+        shotgun.condition = 0.4;
+        shotgun.jammed = true;
+        let slot = inventory.add(shotgun).unwrap();
+        inventory.switch_to(slot);
+        inventory
+    }
+
+    /// Saved through the file format, as a quicksave would be
+    fn through_file(inventory: &WeaponInventory) -> Vec<Option<SavedWeapon>> {
+        let json = serde_json::to_string(&save_inventory(inventory)).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn every_slot_survives_a_save_and_load() {
+        let (balance, perks) = (BalanceData::embedded(), PlayerPerks::default());
+        let saved = backpack(&balance, &perks);
+
+        // A new run only has the two starting slots
+        let mut loaded = WeaponInventory::starting(WeaponType::Smg, &balance);
+        restore_inventory(
+            &mut loaded,
+            &through_file(&saved),
+            saved.current_slot,
+            &balance,
+            &perks,
+        );
+
+        assert_eq!(loaded.capacity(), BACKPACK_WEAPON_SLOTS);
+        assert_eq!(loaded.current_slot, 2);
+        for (loaded, saved) in loaded.weapons.iter().zip(&saved.weapons) {
+            let (loaded, saved) = (loaded.as_ref().unwrap(), saved.as_ref().unwrap());
+            assert_eq!(loaded.weapon_type, saved.weapon_type);
+            assert_eq!(loaded.attachments, saved.attachments);
+            assert_eq!(loaded.current_ammo, saved.current_ammo);
+            assert_eq!(loaded.loaded_ammo, saved.loaded_ammo);
+            assert_eq!(loaded.reserves, saved.reserves);
+            assert_eq!(loaded.condition, saved.condition);
+            assert_eq!(loaded.jammed, saved.jammed);
+        }
+    }
+
+    #[test]
+    fn loading_replaces_weapons_the_new_run_started_with() {
+        let (balance, perks) = (BalanceData::embedded(), PlayerPerks::default());
+        let saved = WeaponInventory::starting(WeaponType::Rifle, &balance);
+
+        let mut loaded = WeaponInventory::starting(WeaponType::Smg, &balance);
+        loaded.expand_to(BACKPACK_WEAPON_SLOTS);
+        loaded.add(Weapon::new(WeaponType::Arc, &balance)).unwrap();
+        loaded.switch_to(2);
+        restore_inventory(&mut loaded, &through_file(&saved), 0, &balance, &perks);
+
+        let types: Vec<Option<WeaponType>> = loaded
+            .weapons
+            .iter()
+            .map(|slot| slot.as_ref().map(|weapon| weapon.weapon_type))
+            .collect();
+        assert_eq!(
+            types,
+            [Some(WeaponType::Pistol), Some(WeaponType::Rifle), None]
+        );
+        assert_eq!(loaded.current_slot, 0);
+    }
+
+    #[test]
+    fn an_unknown_weapon_loads_as_an_empty_slot() {
+        let (balance, perks) = (BalanceData::embedded(), PlayerPerks::default());
+        let mut saved = through_file(&WeaponInventory::starting(WeaponType::Smg, &balance));
+        saved[0].as_mut().unwrap().name = "BLUNDERBUSS".to_string();
+
+        let mut loaded = WeaponInventory::starting(WeaponType::Smg, &balance);
+        restore_inventory(&mut loaded, &saved, 0, &balance, &perks);
+        assert!(loaded.weapons[0].is_none());
+        // The one that's left is held instead
+        assert_eq!(loaded.current_slot, 1);
+    }
+}
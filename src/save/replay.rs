@@ -0,0 +1,567 @@
+//! Input replays: record the per-frame PlayerActions of a run and feed them back later.
+//! Driven from the dev console (`record`, `record stop`, `replay`).
+//!
+//! A replay stores the map seed, a fresh seed for the run's GameRng, the settings that
+//! affect gameplay, and for every Playing frame its delta time and the player's actions.
+//! Playback drives the frame clock with the recorded deltas, so with the same build the
+//! run reproduces exactly. Known sources of drift, which the playback report measures
+//! against `REPLAY_DRIFT_TOLERANCE`:
+//! - pausing while recording: the frame that resumes play isn't recorded
+//! - fire-rate cooldowns compare absolute elapsed time, so a shot landing exactly on the
+//!   cooldown boundary can round differently
+//! - rapier results are only bit-identical on the same platform and build
+
+use super::{spawn_save_notice, SavedMode};
+use crate::console::ConsoleAppExt;
+use crate::player::{
+    CameraSettings, Player, PlayerActions, PlayerActionsClaimSet, PlayerActionsSet,
+};
+use crate::ui::{
+    AdaptiveDifficulty, Difficulty, DifficultyModifiers, GameMode, GameState, StartingLoadout,
+    UiTheme,
+};
+use crate::world::GameRng;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::time::Duration;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Replay>()
+            .add_systems(OnEnter(GameState::MainMenu), start_queued_replay)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::PrePlaying,
+                    entered: GameState::Playing,
+                },
+                begin_replay,
+            )
+            .add_systems(
+                PreUpdate,
+                replay_player_actions
+                    .after(PlayerActionsSet)
+                    .before(PlayerActionsClaimSet)
+                    .run_if(not(resource_equals(Replay::Off))),
+            )
+            .add_systems(Last, pace_replay.run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::GameOver), end_replay_on_game_over)
+            .add_systems(OnEnter(GameState::Victory), end_replay_on_game_over)
+            .register_console_command("record", "[stop]", record_command)
+            .register_console_command("replay", "", replay_command);
+    }
+}
+
+const REPLAY_PATH: &str = "replay.json";
+const REPLAY_VERSION: u32 = 7;
+/// Player position difference at the end of playback still counted as a faithful replay
+const REPLAY_DRIFT_TOLERANCE: f32 = 0.25;
+
+/// Recorder and player state
+#[derive(Resource, Default, PartialEq)]
+pub enum Replay {
+    #[default]
+    Off,
+    /// Recording starts when the next run leaves the countdown
+    Armed,
+    Recording(ReplayFile),
+    /// Loaded and waiting for its run to start
+    Queued(ReplayFile),
+    Playing {
+        file: ReplayFile,
+        frame: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReplayFile {
+    version: u32,
+    map_seed: u64,
+    run_seed: u64,
+    mode: SavedMode,
+    difficulty: usize,
+    /// Starting weapon, skin and hardcore pick
+    loadout: StartingLoadout,
+    camera_smoothing: bool,
+    /// The Director paces spawns from play, so playback needs it on or off to match
+    adaptive: bool,
+    frames: Vec<ReplayFrame>,
+    /// Where the player finished, to measure drift on playback
+    end_position: [f32; 3],
+}
+
+/// One frame, serialized as a tuple to keep the file small:
+/// real delta seconds, button bits, look delta, scroll lines, weapon slot + 1 (0 for none).
+/// The real delta is what pacing feeds back in; bullet time scales it the same way again.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+struct ReplayFrame(f32, u32, [f32; 2], f32, u8);
+
+const FORWARD: u32 = 1 << 0;
+const BACK: u32 = 1 << 1;
+const RIGHT: u32 = 1 << 2;
+const LEFT: u32 = 1 << 3;
+const SPRINT: u32 = 1 << 4;
+const FREE_LOOK: u32 = 1 << 5;
+const FIRE_HELD: u32 = 1 << 6;
+const FIRE_PRESSED: u32 = 1 << 7;
+const RELOAD: u32 = 1 << 8;
+const INTERACT: u32 = 1 << 9;
+const SHOULDER_SWAP: u32 = 1 << 10;
+const THROW_FLARE: u32 = 1 << 11;
+const BULLET_TIME: u32 = 1 << 12;
+const TOGGLE_DRONE: u32 = 1 << 13;
+const TOGGLE_AMMO: u32 = 1 << 14;
+const SHOVE: u32 = 1 << 15;
+const RELOAD_HELD: u32 = 1 << 16;
+const INTERACT_HELD: u32 = 1 << 17;
+const INSPECT: u32 = 1 << 18;
+const INSPECT_HELD: u32 = 1 << 19;
+const GRENADE: u32 = 1 << 20;
+const GRENADE_HELD: u32 = 1 << 21;
+const WEAPON_WHEEL: u32 = 1 << 22;
+
+impl ReplayFrame {
+    fn capture(delta: f32, actions: &PlayerActions) -> Self {
+        let flags = [
+            (actions.movement.y > 0.0, FORWARD),
+            (actions.movement.y < 0.0, BACK),
+            (actions.movement.x > 0.0, RIGHT),
+            (actions.movement.x < 0.0, LEFT),
+            (actions.sprint, SPRINT),
+            (actions.free_look, FREE_LOOK),
+            (actions.fire_held, FIRE_HELD),
+            (actions.fire_pressed, FIRE_PRESSED),
+            (actions.reload, RELOAD),
+            (actions.interact, INTERACT),
+            (actions.shoulder_swap, SHOULDER_SWAP),
+            (actions.throw_flare, THROW_FLARE),
+            (actions.bullet_time, BULLET_TIME),
+            (actions.toggle_drone, TOGGLE_DRONE),
+            (actions.toggle_ammo, TOGGLE_AMMO),
+            (actions.shove, SHOVE),
+            (actions.reload_held, RELOAD_HELD),
+            (actions.interact_held, INTERACT_HELD),
+            (actions.inspect, INSPECT),
+            (actions.inspect_held, INSPECT_HELD),
+            (actions.grenade, GRENADE),
+            (actions.grenade_held, GRENADE_HELD),
+            (actions.weapon_wheel, WEAPON_WHEEL),
+        ];
+        let buttons = flags
+            .iter()
+            .filter(|(set, _)| *set)
+            .fold(0, |bits, (_, bit)| bits | bit);
+        let slot = actions.select_slot.map_or(0, |slot| slot as u8 + 1);
+        Self(
+            delta,
+            buttons,
+            actions.look.to_array(),
+            actions.scroll,
+            slot,
+        )
+    }
+
+    fn actions(&self) -> PlayerActions {
+        let Self(_, buttons, look, scroll, slot) = *self;
+        let has = |bit: u32| buttons & bit != 0;
+        let axis = |positive: u32, negative: u32| {
+            has(positive) as i32 as f32 - has(negative) as i32 as f32
+        };
+        PlayerActions {
+            movement: Vec2::new(axis(RIGHT, LEFT), axis(FORWARD, BACK)),
+            look: Vec2::from_array(look),
+            scroll,
+            sprint: has(SPRINT),
+            free_look: has(FREE_LOOK),
+            fire_held: has(FIRE_HELD),
+            fire_pressed: has(FIRE_PRESSED),
+            // Aiming only changes the mix, so it isn't recorded
+            aim: false,
+            reload: has(RELOAD),
+            reload_held: has(RELOAD_HELD),
+            interact: has(INTERACT),
+            interact_held: has(INTERACT_HELD),
+            shoulder_swap: has(SHOULDER_SWAP),
+            throw_flare: has(THROW_FLARE),
+            grenade: has(GRENADE),
+            grenade_held: has(GRENADE_HELD),
+            bullet_time: has(BULLET_TIME),
+            toggle_drone: has(TOGGLE_DRONE),
+            toggle_ammo: has(TOGGLE_AMMO),
+            shove: has(SHOVE),
+            inspect: has(INSPECT),
+            inspect_held: has(INSPECT_HELD),
+            select_slot: (slot > 0).then(|| slot as usize - 1),
+            weapon_wheel: has(WEAPON_WHEEL),
+        }
+    }
+}
+
+fn write_replay(mut file: ReplayFile, end_position: Vec3) -> Result<usize, String> {
+    file.end_position = end_position.to_array();
+    let frames = file.frames.len();
+    serde_json::to_string(&file)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(REPLAY_PATH, json).map_err(|e| e.to_string()))?;
+    Ok(frames)
+}
+
+fn read_replay() -> Result<ReplayFile, String> {
+    let json =
+        fs::read_to_string(REPLAY_PATH).map_err(|e| format!("Could not read replay: {}", e))?;
+    let file: ReplayFile =
+        serde_json::from_str(&json).map_err(|e| format!("Replay file is corrupt: {}", e))?;
+    if file.version != REPLAY_VERSION {
+        return Err(format!(
+            "Replay is from an incompatible version (v{}, expected v{})",
+            file.version, REPLAY_VERSION
+        ));
+    }
+    Ok(file)
+}
+
+/// A loaded replay sets up the same run it was recorded in and starts it straight away
+fn start_queued_replay(
+    replay: Res<Replay>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut modifiers: ResMut<DifficultyModifiers>,
+    mut loadout: ResMut<StartingLoadout>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Replay::Queued(file) = &*replay else {
+        return;
+    };
+    *mode = match file.mode {
+        SavedMode::Survival => GameMode::Survival,
+        SavedMode::ShootingRange => GameMode::ShootingRange,
+        SavedMode::Extraction => GameMode::Extraction,
+    };
+    *difficulty = Difficulty(file.difficulty);
+    *modifiers = difficulty.modifiers();
+    *loadout = file.loadout;
+    camera_settings.smoothing = file.camera_smoothing;
+    adaptive.0 = file.adaptive;
+    next_state.set(GameState::PrePlaying);
+}
+
+/// Reseed the run's randomness as play begins, so both recording and playback draw the same numbers
+fn begin_replay(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    mut replay: ResMut<Replay>,
+    mut rng: ResMut<GameRng>,
+    mut time: ResMut<Time<Virtual>>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    loadout: Res<StartingLoadout>,
+    camera_settings: Res<CameraSettings>,
+    adaptive: Res<AdaptiveDifficulty>,
+) {
+    match std::mem::take(&mut *replay) {
+        Replay::Armed => {
+            let run_seed = rng.random();
+            rng.reseed(run_seed);
+            *replay = Replay::Recording(ReplayFile {
+                version: REPLAY_VERSION,
+                map_seed: rng.map_seed,
+                run_seed,
+                mode: match *mode {
+                    GameMode::Survival => SavedMode::Survival,
+                    GameMode::ShootingRange => SavedMode::ShootingRange,
+                    GameMode::Extraction => SavedMode::Extraction,
+                },
+                difficulty: difficulty.0,
+                loadout: *loadout,
+                camera_smoothing: camera_settings.smoothing,
+                adaptive: adaptive.0,
+                frames: Vec::new(),
+                end_position: [0.0; 3],
+            });
+            spawn_save_notice(&mut commands, &theme, "Recording replay".to_string());
+        }
+        Replay::Queued(file) => {
+            if file.map_seed != rng.map_seed {
+                spawn_save_notice(
+                    &mut commands,
+                    &theme,
+                    format!(
+                        "Replay was recorded on another map; restart with GAME_SEED={}",
+                        file.map_seed
+                    ),
+                );
+            }
+            rng.reseed(file.run_seed);
+            time.set_relative_speed(1.0);
+            *replay = Replay::Playing { file, frame: 0 };
+        }
+        other => *replay = other,
+    }
+}
+
+/// Record or replace this frame's actions. Outside Playing (the countdown) input is
+/// blanked, so recording and playback both start the run from the same state.
+fn replay_player_actions(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    mut replay: ResMut<Replay>,
+    mut actions: ResMut<PlayerActions>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    if *state.get() != GameState::Playing {
+        *actions = PlayerActions::default();
+        return;
+    }
+
+    match &mut *replay {
+        Replay::Recording(file) => {
+            file.frames
+                .push(ReplayFrame::capture(time.delta_secs(), &actions));
+            return;
+        }
+        Replay::Playing { file, frame } => {
+            if let Some(recorded) = file.frames.get(*frame) {
+                *actions = recorded.actions();
+                *frame += 1;
+                return;
+            }
+        }
+        _ => return,
+    }
+
+    // Playback ran out of frames: report how far the run drifted and hand control back
+    let Replay::Playing { file, .. } = std::mem::take(&mut *replay) else {
+        return;
+    };
+    let end = Vec3::from_array(file.end_position);
+    let drift = player_q
+        .single()
+        .map(|transform| transform.translation.distance(end))
+        .unwrap_or(0.0);
+    let verdict = if drift <= REPLAY_DRIFT_TOLERANCE {
+        "matched"
+    } else {
+        "drifted"
+    };
+    spawn_save_notice(
+        &mut commands,
+        &theme,
+        format!("Replay finished: {} ({:.2}m off)", verdict, drift),
+    );
+    *time_strategy = TimeUpdateStrategy::Automatic;
+}
+
+/// Set up the next frame's clock with the delta it had when recorded
+fn pace_replay(replay: Res<Replay>, mut time_strategy: ResMut<TimeUpdateStrategy>) {
+    let Replay::Playing { file, frame } = &*replay else {
+        return;
+    };
+    *time_strategy = match file.frames.get(*frame) {
+        Some(ReplayFrame(delta, ..)) => {
+            TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(*delta))
+        }
+        None => TimeUpdateStrategy::Automatic,
+    };
+}
+
+/// A run ending saves the recording, or stops playback that outlasted it
+fn end_replay_on_game_over(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    mut replay: ResMut<Replay>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    player_q: Query<&Transform, With<Player>>,
+) {
+    match std::mem::take(&mut *replay) {
+        Replay::Recording(file) => {
+            let end = player_q
+                .single()
+                .map(|transform| transform.translation)
+                .unwrap_or_default();
+            let message = match write_replay(file, end) {
+                Ok(frames) => format!("Replay saved ({} frames)", frames),
+                Err(e) => format!("Replay save failed: {}", e),
+            };
+            spawn_save_notice(&mut commands, &theme, message);
+        }
+        Replay::Playing { .. } => {
+            spawn_save_notice(&mut commands, &theme, "Replay ended early".to_string());
+        }
+        other => {
+            *replay = other;
+            return;
+        }
+    }
+    *time_strategy = TimeUpdateStrategy::Automatic;
+}
+
+/// Arm recording and go back to the menu; the next run started there is recorded
+fn record_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    if args.first() == Some(&"stop") {
+        let Replay::Recording(file) = std::mem::take(&mut *world.resource_mut::<Replay>()) else {
+            return Err("not recording".to_string());
+        };
+        let end = world
+            .query_filtered::<&Transform, With<Player>>()
+            .single(world)
+            .map(|transform| transform.translation)
+            .unwrap_or_default();
+        let frames = write_replay(file, end)?;
+        return Ok(format!("saved {} frames to {}", frames, REPLAY_PATH));
+    }
+
+    *world.resource_mut::<Replay>() = Replay::Armed;
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::MainMenu);
+    Ok("recording starts with the next run".to_string())
+}
+
+fn replay_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let file = read_replay()?;
+    let frames = file.frames.len();
+    *world.resource_mut::<Replay>() = Replay::Queued(file);
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::MainMenu);
+    Ok(format!("playing back {} frames", frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{boot, headless_app, spawn_floor};
+    use bevy_rapier3d::prelude::*;
+
+    fn busy_actions() -> PlayerActions {
+        PlayerActions {
+            movement: Vec2::new(-1.0, 1.0),
+            look: Vec2::new(3.5, -1.25),
+            scroll: -1.0,
+            sprint: true,
+            fire_held: true,
+            fire_pressed: true,
+            reload_held: true,
+            grenade: true,
+            bullet_time: true,
+            toggle_ammo: true,
+            inspect_held: true,
+            select_slot: Some(2),
+            weapon_wheel: true,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn frames_keep_every_recorded_action() {
+        let actions = busy_actions();
+        let frame = ReplayFrame::capture(0.016, &actions);
+        assert_eq!(frame.actions(), actions);
+
+        let idle = ReplayFrame::capture(0.016, &PlayerActions::default());
+        assert_eq!(idle.actions(), PlayerActions::default());
+
+        // Aiming only changes the mix, so it's dropped
+        let aiming = PlayerActions {
+            aim: true,
+            ..default()
+        };
+        assert_eq!(
+            ReplayFrame::capture(0.016, &aiming).actions(),
+            PlayerActions::default()
+        );
+    }
+
+    #[test]
+    fn frames_survive_the_file() {
+        let frame = ReplayFrame::capture(0.007, &busy_actions());
+        let json = serde_json::to_string(&frame).unwrap();
+        let read: ReplayFrame = serde_json::from_str(&json).unwrap();
+        assert!(read == frame);
+    }
+
+    /// Frame deltas as uneven as a real run's
+    fn recorded_deltas() -> Vec<f32> {
+        (0..400)
+            .map(|frame| [0.016, 0.007, 0.033, 0.011, 0.021][frame % 5])
+            .collect()
+    }
+
+    /// A run where the physics moves the player: a ball thrown across the floor
+    fn physics_run(replay: Replay) -> App {
+        let mut app = headless_app(11);
+        app.init_resource::<PlayerActions>()
+            .insert_resource(replay)
+            .add_systems(PreUpdate, replay_player_actions)
+            .add_systems(Last, pace_replay);
+        boot(&mut app);
+        spawn_floor(&mut app);
+        app.world_mut().spawn((
+            Player::default(),
+            Transform::from_xyz(0.0, 3.0, 0.0),
+            RigidBody::Dynamic,
+            Collider::ball(0.5),
+            Velocity::linear(Vec3::new(4.0, 2.0, -3.0)),
+        ));
+        app
+    }
+
+    fn player_position(app: &mut App) -> Vec3 {
+        app.world_mut()
+            .query_filtered::<&Transform, With<Player>>()
+            .single(app.world())
+            .unwrap()
+            .translation
+    }
+
+    #[test]
+    fn playback_lands_within_the_drift_tolerance() {
+        let mut recording = physics_run(Replay::Recording(ReplayFile {
+            version: REPLAY_VERSION,
+            map_seed: 0,
+            run_seed: 11,
+            mode: SavedMode::ShootingRange,
+            difficulty: 0,
+            loadout: StartingLoadout::default(),
+            camera_smoothing: false,
+            adaptive: false,
+            frames: Vec::new(),
+            end_position: [0.0; 3],
+        }));
+        for delta in recorded_deltas() {
+            recording.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                delta,
+            )));
+            recording.update();
+        }
+        let end = player_position(&mut recording);
+        let Replay::Recording(mut file) =
+            std::mem::take(&mut *recording.world_mut().resource_mut::<Replay>())
+        else {
+            panic!("stopped recording");
+        };
+        file.end_position = end.to_array();
+        // Along with the frame that booted the app
+        assert_eq!(file.frames.len(), recorded_deltas().len() + 1);
+
+        let mut playback = physics_run(Replay::Playing { file, frame: 0 });
+        for _ in recorded_deltas() {
+            playback.update();
+        }
+        let drift = player_position(&mut playback).distance(end);
+        assert!(drift <= REPLAY_DRIFT_TOLERANCE, "drifted {drift}m");
+
+        // Out of frames, playback hands the clock back
+        playback.update();
+        assert!(*playback.world().resource::<Replay>() == Replay::Off);
+    }
+}
@@ -0,0 +1,35 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Only the version, parsed first so files from other versions are never decoded
+#[derive(Deserialize)]
+struct VersionHeader {
+    version: u32,
+}
+
+/// Read one of the small JSON files kept next to the game (high scores, unlocks, drill
+/// times), whose top level carries a `version`. None if there is no file yet; the
+/// reason, worded to follow "is", if it's corrupt or from another version.
+pub fn load_versioned<T: DeserializeOwned>(path: &str, version: u32) -> Result<Option<T>, String> {
+    let Ok(json) = fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    let header =
+        serde_json::from_str::<VersionHeader>(&json).map_err(|e| format!("corrupt: {}", e))?;
+    if header.version != version {
+        return Err(format!(
+            "from an incompatible version (v{}, expected v{})",
+            header.version, version
+        ));
+    }
+    serde_json::from_str(&json)
+        .map(Some)
+        .map_err(|e| format!("corrupt: {}", e))
+}
+
+/// Write `file` for load_versioned to read back
+pub fn save_versioned<T: Serialize>(path: &str, file: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
@@ -0,0 +1,250 @@
+use super::{GameState, TextRole, UiTheme};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = load_accessibility_settings();
+        app.insert_resource(ColorPalette::from_preset(settings.palette))
+            .insert_resource(settings)
+            .add_message::<Subtitle>()
+            .add_systems(Startup, spawn_subtitle_area)
+            .add_systems(
+                Update,
+                (
+                    apply_accessibility_settings.run_if(resource_changed::<AccessibilitySettings>),
+                    show_subtitles,
+                    expire_subtitles,
+                )
+                    .chain(),
+            )
+            .add_systems(OnExit(GameState::Playing), clear_subtitles);
+    }
+}
+
+const SETTINGS_PATH: &str = "accessibility.json";
+
+/// Color presets for common kinds of colour blindness
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PalettePreset {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl PalettePreset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PalettePreset::Standard => "Standard",
+            PalettePreset::Deuteranopia => "Deuteranopia",
+            PalettePreset::Protanopia => "Protanopia",
+            PalettePreset::Tritanopia => "Tritanopia",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            PalettePreset::Standard => PalettePreset::Deuteranopia,
+            PalettePreset::Deuteranopia => PalettePreset::Protanopia,
+            PalettePreset::Protanopia => PalettePreset::Tritanopia,
+            PalettePreset::Tritanopia => PalettePreset::Standard,
+        }
+    }
+}
+
+/// Player accessibility preferences, saved to disk whenever they change
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub palette: PalettePreset,
+    /// Replace white hit flashes with a gentle pulse
+    pub reduce_flashing: bool,
+    /// Show text captions for important audio cues
+    pub subtitles: bool,
+    /// Zombies leave neutral scorch marks instead of blood
+    pub reduced_gore: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            palette: PalettePreset::Standard,
+            reduce_flashing: false,
+            subtitles: true,
+            reduced_gore: false,
+        }
+    }
+}
+
+/// Gameplay colours that must stay distinguishable; every health bar and hit
+/// marker reads from here instead of hard-coding its colour
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct ColorPalette {
+    pub health_high: Color,
+    pub health_mid: Color,
+    pub health_low: Color,
+    pub enemy_health: Color,
+    pub target_health: Color,
+    pub hit_marker: Color,
+    pub kill_marker: Color,
+}
+
+impl ColorPalette {
+    pub fn from_preset(preset: PalettePreset) -> Self {
+        match preset {
+            PalettePreset::Standard => Self {
+                health_high: Color::srgb(0.2, 0.8, 0.2),
+                health_mid: Color::srgb(0.8, 0.8, 0.2),
+                health_low: Color::srgb(0.8, 0.2, 0.2),
+                enemy_health: Color::srgb(0.8, 0.2, 0.2),
+                target_health: Color::srgb(0.1, 0.8, 0.1),
+                hit_marker: Color::srgb(1.0, 0.9, 0.3),
+                kill_marker: Color::srgb(1.0, 0.3, 0.2),
+            },
+            // Red/green confusion: lean on blue versus orange and brightness
+            PalettePreset::Deuteranopia | PalettePreset::Protanopia => Self {
+                health_high: Color::srgb(0.2, 0.5, 1.0),
+                health_mid: Color::srgb(0.95, 0.75, 0.2),
+                health_low: Color::srgb(0.9, 0.4, 0.0),
+                enemy_health: Color::srgb(0.9, 0.4, 0.0),
+                target_health: Color::srgb(0.2, 0.5, 1.0),
+                hit_marker: Color::WHITE,
+                kill_marker: Color::srgb(0.95, 0.75, 0.2),
+            },
+            // Blue/yellow confusion: use red versus teal
+            PalettePreset::Tritanopia => Self {
+                health_high: Color::srgb(0.0, 0.7, 0.7),
+                health_mid: Color::srgb(0.9, 0.6, 0.7),
+                health_low: Color::srgb(0.9, 0.1, 0.2),
+                enemy_health: Color::srgb(0.9, 0.1, 0.2),
+                target_health: Color::srgb(0.0, 0.7, 0.7),
+                hit_marker: Color::WHITE,
+                kill_marker: Color::srgb(0.9, 0.1, 0.2),
+            },
+        }
+    }
+
+    /// Three-step colour for a health fraction in 0..1
+    pub fn health_color(&self, fraction: f32) -> Color {
+        if fraction > 0.5 {
+            self.health_high
+        } else if fraction > 0.25 {
+            self.health_mid
+        } else {
+            self.health_low
+        }
+    }
+}
+
+/// Caption for a sound cue, e.g. "Zombie growl behind you"
+#[derive(Message)]
+pub struct Subtitle(pub String);
+
+#[derive(Component)]
+pub(super) struct SubtitleArea;
+
+#[derive(Component)]
+struct SubtitleLine {
+    timer: Timer,
+}
+
+fn load_accessibility_settings() -> AccessibilitySettings {
+    fs::read_to_string(SETTINGS_PATH)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Rebuild the palette and write the settings out so they survive a restart
+fn apply_accessibility_settings(
+    settings: Res<AccessibilitySettings>,
+    mut palette: ResMut<ColorPalette>,
+) {
+    let new_palette = ColorPalette::from_preset(settings.palette);
+    if *palette != new_palette {
+        *palette = new_palette;
+    }
+
+    if settings.is_added() {
+        return;
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&*settings) {
+        if let Err(e) = fs::write(SETTINGS_PATH, json) {
+            warn!("Could not save accessibility settings: {}", e);
+        }
+    }
+}
+
+fn spawn_subtitle_area(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(120.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        SubtitleArea,
+    ));
+}
+
+fn show_subtitles(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    settings: Res<AccessibilitySettings>,
+    mut subtitles: MessageReader<Subtitle>,
+    area: Query<Entity, With<SubtitleArea>>,
+) {
+    if !settings.subtitles {
+        subtitles.clear();
+        return;
+    }
+    let Ok(area) = area.single() else {
+        return;
+    };
+
+    for subtitle in subtitles.read() {
+        commands.entity(area).with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                    SubtitleLine {
+                        timer: Timer::from_seconds(3.0, TimerMode::Once),
+                    },
+                ))
+                .with_children(|line| {
+                    line.spawn((Text::new(subtitle.0.clone()), theme.text(TextRole::Caption)));
+                });
+        });
+    }
+}
+
+fn expire_subtitles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lines: Query<(Entity, &mut SubtitleLine)>,
+) {
+    for (entity, mut line) in lines.iter_mut() {
+        line.timer.tick(time.delta());
+        if line.timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn clear_subtitles(mut commands: Commands, lines: Query<Entity, With<SubtitleLine>>) {
+    for entity in lines.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,267 @@
+use super::GameState;
+use crate::enemies::ZombieDied;
+use crate::player::{Player, PlayerActions};
+use bevy::audio::{AudioSinkPlayback, Pitch, Volume};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// Mixer for everything the game plays: a master gain over Music, SFX and Ambient
+/// buses. Sounds join a bus by carrying an AudioBus next to their AudioPlayer, and
+/// their sink volume follows the bus from then on. Aiming ducks the background so
+/// zombies are easier to pick out, and headshot kills play a short stinger.
+pub struct AudioBusPlugin;
+
+impl Plugin for AudioBusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioBuses>()
+            .init_resource::<StingerQueue>()
+            .add_systems(Startup, setup_stinger)
+            .add_systems(
+                Update,
+                (
+                    duck_while_aiming,
+                    queue_headshot_stingers.run_if(in_state(GameState::Playing)),
+                    play_stingers,
+                    ramp_bus_gains,
+                    apply_bus_gains,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Ambient,
+}
+
+impl AudioBus {
+    pub const ALL: [AudioBus; 3] = [AudioBus::Music, AudioBus::Sfx, AudioBus::Ambient];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AudioBus::Music => "Music",
+            AudioBus::Sfx => "SFX",
+            AudioBus::Ambient => "Ambient",
+        }
+    }
+}
+
+/// One bus: the player's volume setting times a mix level that ramps towards its
+/// target instead of jumping
+#[derive(Clone, Copy, Debug)]
+pub struct BusGain {
+    /// Set from the options menu, 0.0 to 1.0
+    pub volume: f32,
+    mix: f32,
+    target: f32,
+}
+
+impl Default for BusGain {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            mix: 1.0,
+            target: 1.0,
+        }
+    }
+}
+
+impl BusGain {
+    pub fn gain(&self) -> f32 {
+        self.volume * self.mix
+    }
+
+    /// Ramp the mix level towards `target` (1.0 is unducked)
+    pub fn set_mix(&mut self, target: f32) {
+        self.target = target;
+    }
+
+    fn ramp(&mut self, dt: f32) {
+        let step = MIX_RAMP_RATE * dt;
+        self.mix += (self.target - self.mix).clamp(-step, step);
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct AudioBuses {
+    /// Set from the options menu, 0.0 to 1.0
+    pub master: f32,
+    pub music: BusGain,
+    pub sfx: BusGain,
+    pub ambient: BusGain,
+    /// Extra mix on positional SFX (zombies and the like) on top of the SFX bus
+    pub spatial: BusGain,
+}
+
+impl Default for AudioBuses {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: BusGain::default(),
+            sfx: BusGain::default(),
+            ambient: BusGain::default(),
+            spatial: BusGain::default(),
+        }
+    }
+}
+
+impl AudioBuses {
+    pub fn bus(&self, bus: AudioBus) -> &BusGain {
+        match bus {
+            AudioBus::Music => &self.music,
+            AudioBus::Sfx => &self.sfx,
+            AudioBus::Ambient => &self.ambient,
+        }
+    }
+
+    pub fn bus_mut(&mut self, bus: AudioBus) -> &mut BusGain {
+        match bus {
+            AudioBus::Music => &mut self.music,
+            AudioBus::Sfx => &mut self.sfx,
+            AudioBus::Ambient => &mut self.ambient,
+        }
+    }
+
+    /// Final gain for a sound on `bus`, master included
+    pub fn gain(&self, bus: AudioBus) -> f32 {
+        self.master * self.bus(bus).gain()
+    }
+}
+
+/// Volume settings the options menu steps through
+pub const VOLUME_STEPS: [f32; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+/// The volume step after `volume`, wrapping from full back to silent
+pub fn next_volume_step(volume: f32) -> f32 {
+    VOLUME_STEPS
+        .into_iter()
+        .find(|step| *step > volume + 0.01)
+        .unwrap_or(VOLUME_STEPS[0])
+}
+
+/// Mix level change per second, so a full duck takes about a quarter second
+const MIX_RAMP_RATE: f32 = 1.6;
+/// Music and ambience drop to this while aiming
+const AIM_DUCK: f32 = 0.6;
+/// Positional SFX rise to this while aiming
+const AIM_SPATIAL_BOOST: f32 = 1.2;
+/// Shortest gap between stingers; longer than the stinger itself, so they never overlap
+const STINGER_COOLDOWN: f32 = 2.0;
+/// Notes of the headshot stinger (an A major triad) and how long each rings
+const STINGER_NOTES: [(f32, f32); 3] = [(220.0, 0.6), (277.18, 0.5), (329.63, 0.4)];
+const STINGER_VOLUME: f32 = 0.2;
+
+/// Headshot kills waiting for their stinger. Kills inside the cooldown fold into a
+/// single stinger played once it runs out.
+#[derive(Resource)]
+struct StingerQueue {
+    cooldown: Timer,
+    pending: bool,
+}
+
+impl Default for StingerQueue {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(STINGER_COOLDOWN, TimerMode::Once);
+        cooldown.tick(Duration::from_secs_f32(STINGER_COOLDOWN));
+        Self {
+            cooldown,
+            pending: false,
+        }
+    }
+}
+
+/// Synthesized tones for the stinger; the game ships no audio files
+#[derive(Resource)]
+struct StingerNotes(Vec<Handle<Pitch>>);
+
+fn setup_stinger(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    let notes = STINGER_NOTES
+        .iter()
+        .map(|(frequency, seconds)| {
+            pitches.add(Pitch::new(*frequency, Duration::from_secs_f32(*seconds)))
+        })
+        .collect();
+    commands.insert_resource(StingerNotes(notes));
+}
+
+fn duck_while_aiming(
+    actions: Res<PlayerActions>,
+    state: Res<State<GameState>>,
+    mut buses: ResMut<AudioBuses>,
+) {
+    let aiming = actions.aim && *state.get() == GameState::Playing;
+    let (duck, boost) = if aiming {
+        (AIM_DUCK, AIM_SPATIAL_BOOST)
+    } else {
+        (1.0, 1.0)
+    };
+    buses.music.set_mix(duck);
+    buses.ambient.set_mix(duck);
+    buses.spatial.set_mix(boost);
+}
+
+fn queue_headshot_stingers(
+    mut died_events: MessageReader<ZombieDied>,
+    player_q: Query<Entity, With<Player>>,
+    mut queue: ResMut<StingerQueue>,
+) {
+    let player = player_q.single().ok();
+    if died_events
+        .read()
+        .any(|event| event.headshot && event.killer.is_some() && event.killer == player)
+    {
+        queue.pending = true;
+    }
+}
+
+fn play_stingers(
+    mut commands: Commands,
+    time: Res<Time>,
+    notes: Res<StingerNotes>,
+    mut queue: ResMut<StingerQueue>,
+) {
+    queue.cooldown.tick(time.delta());
+    if !queue.pending || !queue.cooldown.is_finished() {
+        return;
+    }
+    queue.pending = false;
+    queue.cooldown.reset();
+
+    for note in notes.0.iter() {
+        commands.spawn((
+            AudioPlayer(note.clone()),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(STINGER_VOLUME)),
+            AudioBus::Music,
+        ));
+    }
+}
+
+fn ramp_bus_gains(time: Res<Time<Real>>, mut buses: ResMut<AudioBuses>) {
+    let dt = time.delta_secs();
+    for bus in AudioBus::ALL {
+        buses.bus_mut(bus).ramp(dt);
+    }
+    buses.spatial.ramp(dt);
+}
+
+/// Scale each bus sound's own PlaybackSettings volume by its bus
+fn apply_bus_gains(
+    buses: Res<AudioBuses>,
+    mut sinks: Query<(&AudioBus, &PlaybackSettings, &mut AudioSink)>,
+    mut spatial_sinks: Query<(&AudioBus, &PlaybackSettings, &mut SpatialAudioSink)>,
+) {
+    for (bus, settings, mut sink) in sinks.iter_mut() {
+        let gain = buses.gain(*bus);
+        sink.set_volume(Volume::Linear(settings.volume.to_linear() * gain));
+    }
+    for (bus, settings, mut sink) in spatial_sinks.iter_mut() {
+        let mut gain = buses.gain(*bus);
+        if *bus == AudioBus::Sfx {
+            gain *= buses.spatial.mix;
+        }
+        sink.set_volume(Volume::Linear(settings.volume.to_linear() * gain));
+    }
+}
@@ -0,0 +1,284 @@
+use super::GameState;
+use crate::enemies::Spawner;
+use crate::player::{Player, ThirdPersonCamera};
+use crate::world::{SpatialIndex, SpatialKind};
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+/// Strip across the top of the HUD: N/E/S/W scroll past as the camera turns, with icons
+/// at the bearings of objectives, open portals and the nearest zombies. Bearings only
+/// follow yaw; markers on another floor sit at the strip's top or bottom edge instead.
+pub struct CompassPlugin;
+
+impl Plugin for CompassPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_compass)
+            .add_systems(OnExit(GameState::Playing), despawn_compass)
+            .add_systems(Update, update_compass.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Something the player is working towards, pinned to the compass wherever it is
+#[derive(Component)]
+pub struct CompassObjective;
+
+const STRIP_WIDTH: f32 = 480.0;
+const STRIP_HEIGHT: f32 = 28.0;
+/// Bearings either side of straight ahead that fit on the strip
+const HALF_SPAN: f32 = PI / 2.0;
+/// Zombies further away than this stay off the compass
+const ZOMBIE_RANGE: f32 = 15.0;
+const MAX_ZOMBIES: usize = 3;
+/// Portals open at once never go past this many (see portal_count)
+const MAX_PORTALS: usize = 4;
+const MAX_OBJECTIVES: usize = 2;
+/// Icons are solid up to NEAR and faded to FAR_ALPHA from FAR out
+const NEAR: f32 = 5.0;
+const FAR: f32 = 60.0;
+const FAR_ALPHA: f32 = 0.25;
+/// Height difference past which a marker counts as being on another floor
+const FLOOR_HEIGHT: f32 = 2.5;
+
+const HEADINGS: [(&str, f32); 8] = [
+    ("N", 0.0),
+    ("NE", PI / 4.0),
+    ("E", PI / 2.0),
+    ("SE", 3.0 * PI / 4.0),
+    ("S", PI),
+    ("SW", -3.0 * PI / 4.0),
+    ("W", -PI / 2.0),
+    ("NW", -PI / 4.0),
+];
+
+#[derive(Component)]
+struct Compass;
+
+/// Heading label, at a fixed bearing
+#[derive(Component)]
+struct CompassHeading(f32);
+
+/// Pooled icon, handed out to markers each frame
+#[derive(Component)]
+struct CompassIcon {
+    kind: MarkerKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MarkerKind {
+    Objective,
+    Portal,
+    Zombie,
+}
+
+impl MarkerKind {
+    fn color(&self) -> Color {
+        match self {
+            MarkerKind::Objective => Color::srgb(1.0, 0.8, 0.2),
+            MarkerKind::Portal => Color::srgb(0.8, 0.3, 1.0),
+            MarkerKind::Zombie => Color::srgb(1.0, 0.25, 0.2),
+        }
+    }
+
+    fn size(&self) -> f32 {
+        match self {
+            MarkerKind::Objective => 12.0,
+            MarkerKind::Portal => 10.0,
+            MarkerKind::Zombie => 6.0,
+        }
+    }
+}
+
+fn spawn_compass(mut commands: Commands) {
+    let icons = [
+        (MarkerKind::Objective, MAX_OBJECTIVES),
+        (MarkerKind::Portal, MAX_PORTALS),
+        (MarkerKind::Zombie, MAX_ZOMBIES),
+    ];
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-STRIP_WIDTH / 2.0)),
+                width: Val::Px(STRIP_WIDTH),
+                height: Val::Px(STRIP_HEIGHT),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            Compass,
+        ))
+        .with_children(|parent| {
+            // Straight ahead
+            parent.spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(STRIP_WIDTH / 2.0 - 1.0),
+                    width: Val::Px(2.0),
+                    height: Val::Px(6.0),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE),
+            ));
+
+            for (label, bearing) in HEADINGS {
+                let cardinal = label.len() == 1;
+                parent.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: if cardinal { 18.0 } else { 12.0 },
+                        ..default()
+                    },
+                    TextColor(if cardinal {
+                        Color::WHITE
+                    } else {
+                        Color::srgb(0.6, 0.6, 0.6)
+                    }),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(if cardinal { 4.0 } else { 7.0 }),
+                        ..default()
+                    },
+                    CompassHeading(bearing),
+                ));
+            }
+
+            for (kind, count) in icons {
+                for _ in 0..count {
+                    parent.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            width: Val::Px(kind.size()),
+                            height: Val::Px(kind.size()),
+                            ..default()
+                        },
+                        BackgroundColor(kind.color()),
+                        Visibility::Hidden,
+                        CompassIcon { kind },
+                    ));
+                }
+            }
+        });
+}
+
+fn despawn_compass(mut commands: Commands, compasses: Query<Entity, With<Compass>>) {
+    for entity in compasses.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Compass bearing of a flat direction: 0 is north (-Z), increasing clockwise to east (+X)
+fn bearing(direction: Vec3) -> f32 {
+    direction.x.atan2(-direction.z)
+}
+
+/// `angle` brought into -PI..PI, so bearings either side of south meet up
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// Pixels from the strip's centre to `bearing`, or None if it's off the strip
+fn strip_offset(bearing: f32, yaw: f32) -> Option<f32> {
+    let relative = wrap_angle(bearing - yaw);
+    (relative.abs() <= HALF_SPAN).then(|| relative / HALF_SPAN * STRIP_WIDTH / 2.0)
+}
+
+/// Opacity of an icon `distance` away
+fn distance_alpha(distance: f32) -> f32 {
+    let t = ((distance - NEAR) / (FAR - NEAR)).clamp(0.0, 1.0);
+    1.0 - t * (1.0 - FAR_ALPHA)
+}
+
+fn update_compass(
+    camera_q: Query<&Transform, With<ThirdPersonCamera>>,
+    player_q: Query<&Transform, With<Player>>,
+    objectives: Query<&GlobalTransform, With<CompassObjective>>,
+    portals: Query<(&Transform, &Spawner)>,
+    index: Res<SpatialIndex>,
+    mut headings: Query<(&mut Node, &mut Visibility, &CompassHeading), Without<CompassIcon>>,
+    mut icons: Query<
+        (
+            &mut Node,
+            &mut Visibility,
+            &mut BackgroundColor,
+            &CompassIcon,
+        ),
+        Without<CompassHeading>,
+    >,
+) {
+    let (Ok(camera), Ok(player)) = (camera_q.single(), player_q.single()) else {
+        return;
+    };
+    let forward = camera.forward().with_y(0.0);
+    if forward.length_squared() < f32::EPSILON {
+        return;
+    }
+    let yaw = bearing(forward);
+    let origin = player.translation;
+
+    for (mut node, mut visibility, heading) in headings.iter_mut() {
+        match strip_offset(heading.0, yaw) {
+            Some(offset) => {
+                // Labels are roughly centred on their bearing
+                node.left = Val::Px(STRIP_WIDTH / 2.0 + offset - 6.0);
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+
+    let nearby = index.nearest_k(origin, MAX_ZOMBIES, |entry| {
+        entry.kind == SpatialKind::Zombie && entry.position.distance(origin) <= ZOMBIE_RANGE
+    });
+
+    let markers: Vec<(MarkerKind, Vec3)> = objectives
+        .iter()
+        .map(|transform| (MarkerKind::Objective, transform.translation()))
+        .chain(
+            portals
+                .iter()
+                .filter(|(_, spawner)| spawner.remaining > 0)
+                .map(|(transform, _)| (MarkerKind::Portal, transform.translation)),
+        )
+        .chain(
+            nearby
+                .into_iter()
+                .map(|entry| (MarkerKind::Zombie, entry.position)),
+        )
+        .collect();
+
+    // Hand each marker the next free icon of its kind; the rest stay hidden
+    let mut used = vec![false; markers.len()];
+    for (mut node, mut visibility, mut color, icon) in icons.iter_mut() {
+        let placed = markers
+            .iter()
+            .enumerate()
+            .find(|(index, (kind, _))| !used[*index] && *kind == icon.kind);
+        let Some((index, (kind, position))) = placed else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        used[index] = true;
+
+        let to_marker = *position - origin;
+        let Some(offset) = strip_offset(bearing(to_marker.with_y(0.0)), yaw) else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let size = kind.size();
+        node.left = Val::Px(STRIP_WIDTH / 2.0 + offset - size / 2.0);
+        node.top = Val::Px(if to_marker.y > FLOOR_HEIGHT {
+            0.0
+        } else if to_marker.y < -FLOOR_HEIGHT {
+            STRIP_HEIGHT - size
+        } else {
+            (STRIP_HEIGHT - size) / 2.0
+        });
+        *color = BackgroundColor(kind.color().with_alpha(distance_alpha(to_marker.length())));
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
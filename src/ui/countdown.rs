@@ -0,0 +1,96 @@
+use super::{GameState, TextRole, UiTheme};
+use bevy::prelude::*;
+
+/// "3, 2, 1, GO" between the main menu and the run. The world and camera are live,
+/// but everything gated on Playing (player input, zombies, waves) waits for it.
+pub struct CountdownPlugin;
+
+impl Plugin for CountdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::PrePlaying), spawn_countdown)
+            .add_systems(OnExit(GameState::PrePlaying), despawn_countdown)
+            .add_systems(
+                Update,
+                update_countdown.run_if(in_state(GameState::PrePlaying)),
+            );
+    }
+}
+
+const COUNTDOWN_SECONDS: u32 = 3;
+/// How long "GO" stays up before play starts
+const GO_TIME: f32 = 0.5;
+
+#[derive(Component)]
+struct Countdown {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct CountdownText;
+
+fn spawn_countdown(mut commands: Commands, theme: Res<UiTheme>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            Countdown {
+                timer: Timer::from_seconds(COUNTDOWN_SECONDS as f32 + GO_TIME, TimerMode::Once),
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(COUNTDOWN_SECONDS.to_string()),
+                theme.text(TextRole::Title),
+                CountdownText,
+            ));
+            parent.spawn((
+                Text::new("Press Space to skip"),
+                theme.muted_text(TextRole::Caption),
+            ));
+        });
+}
+
+/// Runs on real time: virtual time may still be paused if the menu was reached from the pause screen
+fn update_countdown(
+    time: Res<Time<Real>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut countdowns: Query<&mut Countdown>,
+    mut texts: Query<&mut Text, With<CountdownText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok(mut countdown) = countdowns.single_mut() else {
+        return;
+    };
+
+    countdown.timer.tick(time.delta());
+    if countdown.timer.is_finished() || keys.just_pressed(KeyCode::Space) {
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    let elapsed = countdown.timer.elapsed_secs();
+    let label = if elapsed >= COUNTDOWN_SECONDS as f32 {
+        "GO".to_string()
+    } else {
+        (COUNTDOWN_SECONDS - elapsed as u32).to_string()
+    };
+    for mut text in texts.iter_mut() {
+        if **text != label {
+            **text = label.clone();
+        }
+    }
+}
+
+fn despawn_countdown(mut commands: Commands, countdowns: Query<Entity, With<Countdown>>) {
+    for entity in countdowns.iter() {
+        commands.entity(entity).despawn();
+    }
+}
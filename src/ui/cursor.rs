@@ -0,0 +1,106 @@
+use super::GameState;
+use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, CursorOptions};
+
+/// Owns cursor grabbing. Natively the grab always succeeds, but browsers only
+/// grant pointer lock from a user gesture and can drop it at any time (Esc),
+/// so the lock is requested on click and a lost lock pauses the game.
+pub struct CursorPlugin;
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorLock>()
+            // Locked as the countdown starts so the first mouse movement already aims
+            .add_systems(OnEnter(GameState::PrePlaying), lock_cursor)
+            .add_systems(OnEnter(GameState::Playing), lock_cursor)
+            .add_systems(OnEnter(GameState::PhotoMode), lock_cursor)
+            .add_systems(OnEnter(GameState::MainMenu), unlock_cursor)
+            .add_systems(OnEnter(GameState::Paused), unlock_cursor)
+            .add_systems(OnEnter(GameState::GameOver), unlock_cursor)
+            .add_systems(OnEnter(GameState::Victory), unlock_cursor)
+            .add_systems(OnEnter(GameState::PerkSelect), unlock_cursor)
+            .add_systems(
+                Update,
+                (request_lock_on_click, detect_lost_lock).chain().run_if(
+                    in_state(GameState::PrePlaying)
+                        .or(in_state(GameState::Playing))
+                        .or(in_state(GameState::PhotoMode)),
+                ),
+            );
+    }
+}
+
+/// Whether the game wants the pointer locked and whether the platform granted it
+#[derive(Resource, Default)]
+pub struct CursorLock {
+    pub wanted: bool,
+    pub acquired: bool,
+}
+
+fn lock_cursor(mut lock: ResMut<CursorLock>, mut cursor_options: Single<&mut CursorOptions>) {
+    lock.wanted = true;
+    lock.acquired = false;
+    cursor_options.visible = false;
+
+    // In the browser this has to wait for a click (see request_lock_on_click)
+    if !cfg!(target_arch = "wasm32") {
+        cursor_options.grab_mode = CursorGrabMode::Locked;
+        lock.acquired = true;
+    }
+}
+
+fn unlock_cursor(mut lock: ResMut<CursorLock>, mut cursor_options: Single<&mut CursorOptions>) {
+    lock.wanted = false;
+    lock.acquired = false;
+    cursor_options.grab_mode = CursorGrabMode::None;
+    cursor_options.visible = true;
+}
+
+/// A click is a user gesture, so it's the moment the browser will accept a lock request
+fn request_lock_on_click(
+    mouse: Res<ButtonInput<MouseButton>>,
+    lock: Res<CursorLock>,
+    mut cursor_options: Single<&mut CursorOptions>,
+) {
+    if !lock.wanted || lock.acquired || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    // Re-assign even if already Locked so the window backend issues a fresh request
+    cursor_options.grab_mode = CursorGrabMode::Locked;
+}
+
+/// Track the real pointer lock and pause if the platform takes it away
+fn detect_lost_lock(
+    mut lock: ResMut<CursorLock>,
+    mut cursor_options: Single<&mut CursorOptions>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !lock.wanted {
+        return;
+    }
+
+    let locked = pointer_is_locked();
+    if !lock.acquired && locked {
+        lock.acquired = true;
+    } else if lock.acquired && !locked {
+        // Lock lost outside our control (browser-level Esc, tab switch)
+        lock.acquired = false;
+        cursor_options.grab_mode = CursorGrabMode::None;
+        cursor_options.visible = true;
+        next_state.set(GameState::Paused);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pointer_is_locked() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.pointer_lock_element())
+        .is_some()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn pointer_is_locked() -> bool {
+    true
+}
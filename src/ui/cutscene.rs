@@ -0,0 +1,308 @@
+use super::{GameState, SubtitleArea, TextRole, ThemedText, UiTheme};
+use crate::player::{
+    BulletTime, PlayerActions, PlayerActionsSet, ScriptedCamera, ThirdPersonCamera,
+};
+use bevy::prelude::*;
+
+/// Scripted moments: a CutsceneEvent lists timed steps (camera moves, time scale,
+/// gameplay cues, banners) that play out while player input is locked and the HUD is
+/// hidden. Skipping, or leaving Playing, fires any cues still pending and puts the
+/// camera, clock and HUD back exactly as they were.
+pub struct CutscenePlugin;
+
+impl Plugin for CutscenePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<CutsceneEvent>()
+            .add_message::<CutsceneCue>()
+            .add_systems(
+                PreUpdate,
+                lock_player_actions
+                    .after(PlayerActionsSet)
+                    .run_if(resource_exists::<ActiveCutscene>),
+            )
+            .add_systems(
+                Update,
+                (
+                    start_cutscene,
+                    run_cutscene.run_if(resource_exists::<ActiveCutscene>),
+                    end_cutscene.run_if(cutscene_finished),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), end_cutscene);
+    }
+}
+
+/// Play a cutscene; ignored while another one is running
+#[derive(Message, Clone)]
+pub struct CutsceneEvent {
+    /// Steps in order of `at`
+    pub steps: Vec<CutsceneStep>,
+    /// Seconds until control is handed back
+    pub duration: f32,
+}
+
+#[derive(Clone)]
+pub struct CutsceneStep {
+    /// Seconds from the start of the cutscene, on the real clock
+    pub at: f32,
+    pub action: CutsceneAction,
+}
+
+#[derive(Clone)]
+pub enum CutsceneAction {
+    /// Glide the camera from wherever it is to `eye`, looking at `target`
+    Camera {
+        eye: Vec3,
+        target: Vec3,
+        duration: f32,
+    },
+    /// Game clock speed from here on
+    TimeScale(f32),
+    /// Gameplay for the scene's owner to carry out; always sent, even when skipped
+    Cue(CutsceneCue),
+    /// Title across the screen, replacing the previous one
+    Banner(String),
+}
+
+/// Gameplay beats a cutscene hands back to the systems that own them
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CutsceneCue {
+    BreachBossDoor,
+    SpawnBoss,
+}
+
+/// The running cutscene and everything needed to undo it
+#[derive(Resource)]
+struct ActiveCutscene {
+    steps: Vec<CutsceneStep>,
+    /// Index of the next step to run
+    next: usize,
+    elapsed: f32,
+    duration: f32,
+    finished: bool,
+    track: Option<CameraTrack>,
+    camera: Entity,
+    camera_snapshot: Transform,
+    time_speed: f32,
+    /// HUD roots that were hidden, with the visibility they had
+    hud: Vec<(Entity, Visibility)>,
+}
+
+struct CameraTrack {
+    from: Transform,
+    to: Transform,
+    start: f32,
+    duration: f32,
+}
+
+/// Banner and skip hint; not part of the HUD, so never hidden
+#[derive(Component)]
+struct CutsceneUi;
+
+#[derive(Component)]
+struct CutsceneBanner;
+
+fn cutscene_finished(cutscene: Option<Res<ActiveCutscene>>) -> bool {
+    cutscene.is_some_and(|cutscene| cutscene.finished)
+}
+
+/// The player watches; nothing they press reaches gameplay
+fn lock_player_actions(mut actions: ResMut<PlayerActions>) {
+    *actions = PlayerActions::default();
+}
+
+fn start_cutscene(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    mut events: MessageReader<CutsceneEvent>,
+    active: Option<Res<ActiveCutscene>>,
+    mut bullet_time: ResMut<BulletTime>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    camera_q: Query<(Entity, &Transform), With<ThirdPersonCamera>>,
+    mut hud_q: Query<
+        (Entity, &mut Visibility),
+        (With<Node>, Without<ChildOf>, Without<SubtitleArea>),
+    >,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    if active.is_some() {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_q.single() else {
+        return;
+    };
+
+    // With input locked Q reads as released, and bullet time would put the clock
+    // back to normal over the cutscene's own time scale
+    if bullet_time.active {
+        bullet_time.active = false;
+        virtual_time.set_relative_speed(1.0);
+    }
+
+    let hud = hud_q
+        .iter_mut()
+        .map(|(entity, mut visibility)| {
+            let previous = *visibility;
+            *visibility = Visibility::Hidden;
+            (entity, previous)
+        })
+        .collect();
+
+    commands.entity(camera).insert(ScriptedCamera);
+    commands.insert_resource(ActiveCutscene {
+        steps: event.steps.clone(),
+        next: 0,
+        elapsed: 0.0,
+        duration: event.duration,
+        finished: false,
+        track: None,
+        camera,
+        camera_snapshot: *camera_transform,
+        time_speed: virtual_time.relative_speed(),
+        hud,
+    });
+
+    commands.spawn((
+        Text::new("Press Space to skip"),
+        theme.muted_text(TextRole::Caption),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(24.0),
+            right: Val::Px(24.0),
+            ..default()
+        },
+        CutsceneUi,
+    ));
+}
+
+/// Runs on real time so the scene keeps its pace whatever it does to the game clock
+fn run_cutscene(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    time: Res<Time<Real>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cutscene: ResMut<ActiveCutscene>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut camera_q: Query<&mut Transform, With<ScriptedCamera>>,
+    banners: Query<Entity, With<CutsceneBanner>>,
+    mut cues: MessageWriter<CutsceneCue>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        cutscene.finished = true;
+        return;
+    }
+    cutscene.elapsed += time.delta_secs();
+    let Ok(mut camera_transform) = camera_q.get_mut(cutscene.camera) else {
+        cutscene.finished = true;
+        return;
+    };
+
+    while let Some(step) = cutscene
+        .steps
+        .get(cutscene.next)
+        .filter(|step| step.at <= cutscene.elapsed)
+        .cloned()
+    {
+        cutscene.next += 1;
+        match step.action {
+            CutsceneAction::Camera {
+                eye,
+                target,
+                duration,
+            } => {
+                cutscene.track = Some(CameraTrack {
+                    from: *camera_transform,
+                    to: Transform::from_translation(eye).looking_at(target, Vec3::Y),
+                    start: step.at,
+                    duration,
+                });
+            }
+            CutsceneAction::TimeScale(scale) => virtual_time.set_relative_speed(scale),
+            CutsceneAction::Cue(cue) => {
+                cues.write(cue);
+            }
+            CutsceneAction::Banner(text) => {
+                for entity in banners.iter() {
+                    commands.entity(entity).despawn();
+                }
+                spawn_banner(&mut commands, &theme, text);
+            }
+        }
+    }
+
+    if let Some(track) = &cutscene.track {
+        let t =
+            ((cutscene.elapsed - track.start) / track.duration.max(f32::EPSILON)).clamp(0.0, 1.0);
+        // Ease in and out so the move doesn't jolt at either end
+        let t = t * t * (3.0 - 2.0 * t);
+        camera_transform.translation = track.from.translation.lerp(track.to.translation, t);
+        camera_transform.rotation = track.from.rotation.slerp(track.to.rotation, t);
+    }
+
+    if cutscene.elapsed >= cutscene.duration {
+        cutscene.finished = true;
+    }
+}
+
+/// Hand control back: pending cues still fire so a skip never loses gameplay
+fn end_cutscene(
+    mut commands: Commands,
+    cutscene: Option<Res<ActiveCutscene>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut camera_q: Query<&mut Transform>,
+    mut visibilities: Query<&mut Visibility>,
+    cutscene_ui: Query<Entity, With<CutsceneUi>>,
+    mut cues: MessageWriter<CutsceneCue>,
+) {
+    let Some(cutscene) = cutscene else {
+        return;
+    };
+
+    for step in &cutscene.steps[cutscene.next..] {
+        if let CutsceneAction::Cue(cue) = step.action {
+            cues.write(cue);
+        }
+    }
+
+    virtual_time.set_relative_speed(cutscene.time_speed);
+    if let Ok(mut camera_transform) = camera_q.get_mut(cutscene.camera) {
+        *camera_transform = cutscene.camera_snapshot;
+    }
+    commands.entity(cutscene.camera).remove::<ScriptedCamera>();
+    for (entity, previous) in &cutscene.hud {
+        if let Ok(mut visibility) = visibilities.get_mut(*entity) {
+            *visibility = *previous;
+        }
+    }
+    for entity in cutscene_ui.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<ActiveCutscene>();
+}
+
+fn spawn_banner(commands: &mut Commands, theme: &UiTheme, text: String) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            CutsceneUi,
+            CutsceneBanner,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(text),
+                theme.font(TextRole::Title),
+                ThemedText(TextRole::Title),
+                TextColor(Color::srgb(0.9, 0.2, 0.15)),
+            ));
+        });
+}
@@ -0,0 +1,142 @@
+use crate::player::BurstMitigation;
+use bevy::prelude::*;
+
+/// Tuning multipliers for the selected difficulty, read by spawn, regen and loot code
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyModifiers {
+    pub name: &'static str,
+    pub zombie_health: f32,
+    pub zombie_damage: f32,
+    pub zombie_speed: f32,
+    /// Scales how many zombies spawn per wave
+    pub spawn_count: f32,
+    /// Share of wave zombies that lie in wait playing dead (see DormantCorpse)
+    pub dormant_chance: f32,
+    /// Scales the player's health regeneration rate
+    pub health_regen: f32,
+    /// Scales the chance of pickups dropping from kills
+    pub drop_chance: f32,
+    /// Scales how fast weapons wear out; 0.0 turns weapon condition and jams off
+    pub weapon_wear: f32,
+    /// Hardcore rules on top, unlocked across runs and toggled on the Unlocks screen
+    pub hardcore: bool,
+    /// Whether the Director may pace spawns when adaptive difficulty is picked
+    pub adaptive: bool,
+    /// How much hits landing together on the player are softened
+    pub burst: BurstMitigation,
+}
+
+impl Default for DifficultyModifiers {
+    fn default() -> Self {
+        DIFFICULTIES[DEFAULT_DIFFICULTY]
+    }
+}
+
+/// Every selectable difficulty, in menu order. Add a row here to add a difficulty.
+pub const DIFFICULTIES: &[DifficultyModifiers] = &[
+    DifficultyModifiers {
+        name: "Easy",
+        zombie_health: 0.7,
+        zombie_damage: 0.5,
+        zombie_speed: 0.85,
+        spawn_count: 0.6,
+        dormant_chance: 0.0,
+        health_regen: 1.5,
+        drop_chance: 1.5,
+        weapon_wear: 0.0,
+        hardcore: false,
+        adaptive: true,
+        burst: BurstMitigation {
+            window: 0.6,
+            second_hit: 0.7,
+            later_hits: 0.4,
+        },
+    },
+    DifficultyModifiers {
+        name: "Normal",
+        zombie_health: 1.0,
+        zombie_damage: 1.0,
+        zombie_speed: 1.0,
+        spawn_count: 1.0,
+        dormant_chance: 0.0,
+        health_regen: 1.0,
+        drop_chance: 1.0,
+        weapon_wear: 0.0,
+        hardcore: false,
+        adaptive: true,
+        burst: BurstMitigation {
+            window: 0.5,
+            second_hit: 0.75,
+            later_hits: 0.5,
+        },
+    },
+    DifficultyModifiers {
+        name: "Hard",
+        zombie_health: 1.5,
+        zombie_damage: 1.5,
+        zombie_speed: 1.2,
+        spawn_count: 1.4,
+        dormant_chance: 0.1,
+        health_regen: 0.5,
+        drop_chance: 0.7,
+        weapon_wear: 1.0,
+        hardcore: false,
+        adaptive: false,
+        burst: BurstMitigation {
+            window: 0.4,
+            second_hit: 0.85,
+            later_hits: 0.7,
+        },
+    },
+];
+
+const DEFAULT_DIFFICULTY: usize = 1;
+
+impl DifficultyModifiers {
+    /// These modifiers with hardcore rules on top: no regeneration, half the drops,
+    /// harder bites, and weapons wearing out even where the difficulty has them last
+    pub fn hardcore(self) -> Self {
+        Self {
+            zombie_damage: self.zombie_damage * 1.25,
+            health_regen: 0.0,
+            drop_chance: self.drop_chance * 0.5,
+            weapon_wear: self.weapon_wear.max(1.0),
+            hardcore: true,
+            ..self
+        }
+    }
+
+    /// Name as shown to the player, with the hardcore tag
+    pub fn label(&self) -> String {
+        if self.hardcore {
+            format!("{} Hardcore", self.name)
+        } else {
+            self.name.to_string()
+        }
+    }
+}
+
+/// Adaptive difficulty picked on the main menu (see Director). Runs that use it
+/// aren't ranked on the leaderboard.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AdaptiveDifficulty(pub bool);
+
+/// Index into DIFFICULTIES chosen on the main menu
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty(pub usize);
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self(DEFAULT_DIFFICULTY)
+    }
+}
+
+impl Difficulty {
+    pub fn modifiers(&self) -> DifficultyModifiers {
+        DIFFICULTIES[self.0 % DIFFICULTIES.len()]
+    }
+
+    pub fn cycle(&mut self) {
+        self.0 = (self.0 + 1) % DIFFICULTIES.len();
+    }
+}
@@ -0,0 +1,274 @@
+use super::UiTheme;
+use bevy::prelude::*;
+use bevy::ui::UiSystems;
+
+/// Keyboard and gamepad focus for menu buttons. The arrows (or WASD) and the d-pad
+/// walk the buttons of the topmost FocusScope in spawn order, wrapping at the ends,
+/// and Enter, Space or South presses the focused one just as a click would, so each
+/// screen's own Interaction handling works unchanged. Hovering with the mouse moves
+/// focus along with it.
+pub struct MenuFocusPlugin;
+
+impl Plugin for MenuFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>().add_systems(
+            PreUpdate,
+            (
+                track_focus_scopes,
+                release_focus_press,
+                follow_pointer,
+                navigate_focus,
+                draw_focus_ring.run_if(resource_changed::<MenuFocus>),
+            )
+                .chain()
+                .after(UiSystems::Focus),
+        );
+    }
+}
+
+const FOCUS_RING_WIDTH: f32 = 3.0;
+const FOCUS_RING_OFFSET: f32 = 2.0;
+
+/// Root of a screen whose buttons take focus. With several up at once, the one drawn
+/// on top has it, or the newest among equals.
+#[derive(Component, Clone, Copy, Default)]
+pub struct FocusScope {
+    /// The screen takes typed letters, so only the arrows and the gamepad move focus
+    /// and only the gamepad presses
+    pub typing: bool,
+    /// The screen is up over live play, so WASD and Space stay with the player: only
+    /// the arrows and the gamepad move focus, and Enter or the gamepad presses
+    pub over_play: bool,
+}
+
+impl FocusScope {
+    pub fn typing() -> Self {
+        Self {
+            typing: true,
+            ..default()
+        }
+    }
+
+    pub fn over_play() -> Self {
+        Self {
+            over_play: true,
+            ..default()
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MenuFocus {
+    /// Open scopes, oldest first
+    scopes: Vec<Entity>,
+    /// The scope taking input
+    scope: Option<Entity>,
+    pub focused: Option<Entity>,
+    /// Pressed from the keys or the gamepad, let go of the frame after
+    pressed: Option<Entity>,
+}
+
+fn track_focus_scopes(
+    mut focus: ResMut<MenuFocus>,
+    added: Query<Entity, Added<FocusScope>>,
+    scopes: Query<Option<&GlobalZIndex>, With<FocusScope>>,
+    buttons: Query<(), With<Button>>,
+) {
+    if !added.is_empty() {
+        focus.scopes.extend(added.iter());
+    }
+    if focus.scopes.iter().any(|&scope| !scopes.contains(scope)) {
+        focus.scopes.retain(|&scope| scopes.contains(scope));
+    }
+
+    let top = focus
+        .scopes
+        .iter()
+        .enumerate()
+        .max_by_key(|&(order, &scope)| {
+            let z = scopes.get(scope).ok().flatten().map_or(0, |z| z.0);
+            (z, order)
+        })
+        .map(|(_, &scope)| scope);
+    if top != focus.scope {
+        focus.scope = top;
+        focus.focused = None;
+    }
+    if focus
+        .focused
+        .is_some_and(|button| !buttons.contains(button))
+    {
+        focus.focused = None;
+    }
+}
+
+fn release_focus_press(mut focus: ResMut<MenuFocus>, mut interactions: Query<&mut Interaction>) {
+    let Some(button) = focus.pressed else {
+        return;
+    };
+    focus.pressed = None;
+    if let Ok(mut interaction) = interactions.get_mut(button) {
+        if *interaction == Interaction::Pressed {
+            *interaction = Interaction::None;
+        }
+    }
+}
+
+/// The pointer coming onto a button takes the focus there
+fn follow_pointer(
+    mut focus: ResMut<MenuFocus>,
+    hovered: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
+    parents: Query<&ChildOf>,
+) {
+    let Some(scope) = focus.scope else {
+        return;
+    };
+    for (button, interaction) in hovered.iter() {
+        if *interaction == Interaction::Hovered
+            && focus.focused != Some(button)
+            && parents.iter_ancestors(button).any(|parent| parent == scope)
+        {
+            focus.focused = Some(button);
+        }
+    }
+}
+
+fn navigate_focus(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    theme: Res<UiTheme>,
+    mut focus: ResMut<MenuFocus>,
+    scopes: Query<&FocusScope>,
+    children: Query<&Children>,
+    visible: Query<&InheritedVisibility, With<Button>>,
+    mut buttons: Query<(Entity, &mut Interaction, &mut BackgroundColor), With<Button>>,
+) {
+    let Some(scope) = focus.scope else {
+        return;
+    };
+    let Ok(settings) = scopes.get(scope) else {
+        return;
+    };
+    let pressed = |key_list: &[KeyCode], button_list: &[GamepadButton]| {
+        key_list.iter().any(|key| keys.just_pressed(*key))
+            || gamepads.iter().any(|gamepad| {
+                button_list
+                    .iter()
+                    .any(|button| gamepad.just_pressed(*button))
+            })
+    };
+
+    let (previous, next, activate): (&[KeyCode], &[KeyCode], &[KeyCode]) = if settings.typing {
+        (
+            &[KeyCode::ArrowUp, KeyCode::ArrowLeft],
+            &[KeyCode::ArrowDown, KeyCode::ArrowRight],
+            &[],
+        )
+    } else if settings.over_play {
+        (
+            &[KeyCode::ArrowUp, KeyCode::ArrowLeft],
+            &[KeyCode::ArrowDown, KeyCode::ArrowRight],
+            &[KeyCode::Enter],
+        )
+    } else {
+        (
+            &[
+                KeyCode::ArrowUp,
+                KeyCode::ArrowLeft,
+                KeyCode::KeyW,
+                KeyCode::KeyA,
+            ],
+            &[
+                KeyCode::ArrowDown,
+                KeyCode::ArrowRight,
+                KeyCode::KeyS,
+                KeyCode::KeyD,
+            ],
+            &[KeyCode::Enter, KeyCode::Space],
+        )
+    };
+    let step = if pressed(previous, &[GamepadButton::DPadUp, GamepadButton::DPadLeft]) {
+        -1
+    } else if pressed(next, &[GamepadButton::DPadDown, GamepadButton::DPadRight]) {
+        1
+    } else {
+        0
+    };
+
+    if step != 0 {
+        let mut order = Vec::new();
+        collect_buttons(scope, &children, &visible, &mut order);
+        if order.is_empty() {
+            return;
+        }
+        let count = order.len() as isize;
+        let index = match focus
+            .focused
+            .and_then(|button| order.iter().position(|&entry| entry == button))
+        {
+            Some(index) => (index as isize + step).rem_euclid(count),
+            // Nothing focused yet: down starts at the top, up at the bottom
+            None if step > 0 => 0,
+            None => count - 1,
+        };
+        let focused = order[index as usize];
+        focus.focused = Some(focused);
+
+        // Don't leave the button under a pointer that hasn't moved looking hovered
+        for (button, interaction, mut bg_color) in buttons.iter_mut() {
+            if button != focused && *interaction == Interaction::Hovered {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+
+    if pressed(activate, &[GamepadButton::South]) {
+        let Some(focused) = focus
+            .focused
+            .filter(|&button| visible.get(button).is_ok_and(|visibility| visibility.get()))
+        else {
+            return;
+        };
+        if let Ok((_, mut interaction, _)) = buttons.get_mut(focused) {
+            *interaction = Interaction::Pressed;
+            focus.pressed = Some(focused);
+        }
+    }
+}
+
+/// Visible buttons under `entity`, depth first, which is the order they were spawned in
+fn collect_buttons(
+    entity: Entity,
+    children: &Query<&Children>,
+    visible: &Query<&InheritedVisibility, With<Button>>,
+    order: &mut Vec<Entity>,
+) {
+    if visible.get(entity).is_ok_and(|visibility| visibility.get()) {
+        order.push(entity);
+    }
+    if let Ok(entity_children) = children.get(entity) {
+        for &child in entity_children {
+            collect_buttons(child, children, visible, order);
+        }
+    }
+}
+
+fn draw_focus_ring(
+    mut commands: Commands,
+    focus: Res<MenuFocus>,
+    theme: Res<UiTheme>,
+    rings: Query<Entity, (With<Button>, With<Outline>)>,
+) {
+    for button in rings.iter() {
+        if Some(button) != focus.focused {
+            commands.entity(button).remove::<Outline>();
+        }
+    }
+    if let Some(focused) = focus.focused {
+        commands.entity(focused).try_insert(Outline::new(
+            Val::Px(FOCUS_RING_WIDTH),
+            Val::Px(FOCUS_RING_OFFSET),
+            theme.buttons.focus,
+        ));
+    }
+}
@@ -0,0 +1,642 @@
+use super::{
+    DifficultyModifiers, FocusScope, GameMode, GameState, MenuStack, MenuState, TextRole,
+    ThemedText, UiTheme,
+};
+use crate::combat::ShotFired;
+use crate::enemies::{Director, WaveState, ZombieDied};
+use crate::player::{Player, PlayerHealth, Score};
+use crate::save::{load_versioned, save_versioned};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Local top-10 leaderboard for survival runs, kept in a file next to the game. A
+/// run that makes the table asks for a three-letter name on the game over screen;
+/// the table itself is under "High Scores" on the main menu.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_high_scores())
+            .init_resource::<RunStats>()
+            .add_systems(Update, count_run_stats.run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::GameOver), offer_high_score)
+            .add_systems(OnExit(GameState::GameOver), abandon_name_entry)
+            .add_systems(
+                Update,
+                (
+                    type_high_score_name,
+                    handle_name_entry_buttons,
+                    update_name_entry,
+                )
+                    .chain()
+                    .run_if(resource_exists::<PendingHighScore>),
+            )
+            .add_systems(OnEnter(MenuState::HighScores), show_high_scores)
+            .add_systems(OnExit(MenuState::HighScores), cleanup_high_scores)
+            .add_systems(
+                Update,
+                handle_high_score_buttons.run_if(in_state(MenuState::HighScores)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                reset_run_stats,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::GameOver,
+                    entered: GameState::Playing,
+                },
+                reset_run_stats,
+            );
+    }
+}
+
+const HIGH_SCORES_PATH: &str = "high_scores.json";
+
+/// Bump whenever HighScoreEntry changes shape; files from other versions are dropped
+/// and the table starts fresh
+const HIGH_SCORES_VERSION: u32 = 1;
+
+pub const HIGH_SCORE_SLOTS: usize = 10;
+const NAME_LENGTH: usize = 3;
+/// Shown for a run whose name was never entered
+const NO_NAME: &str = "???";
+
+/// One finished run on the leaderboard
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub wave: u32,
+    pub kills: u32,
+    /// Percentage of shots that hit something shootable
+    pub accuracy: f32,
+    pub difficulty: String,
+    /// YYYY-MM-DD, blank where there's no system clock
+    pub date: String,
+}
+
+impl HighScoreEntry {
+    /// Higher score first; ties go to the deeper wave, then the most kills
+    fn rank(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .cmp(&self.score)
+            .then(other.wave.cmp(&self.wave))
+            .then(other.kills.cmp(&self.kills))
+    }
+}
+
+/// Best runs, best first, never more than HIGH_SCORE_SLOTS
+#[derive(Resource, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// Where `entry` would land in the table, if it makes it at all. An exact tie
+    /// goes below the run that got there first.
+    pub fn placement(&self, entry: &HighScoreEntry) -> Option<usize> {
+        let place = self
+            .entries
+            .iter()
+            .position(|held| entry.rank(held) == Ordering::Less)
+            .unwrap_or(self.entries.len());
+        (place < HIGH_SCORE_SLOTS).then_some(place)
+    }
+
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let place = self.placement(&entry)?;
+        self.entries.insert(place, entry);
+        self.entries.truncate(HIGH_SCORE_SLOTS);
+        Some(place)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct HighScoreFile {
+    version: u32,
+    entries: Vec<HighScoreEntry>,
+}
+
+/// Kills, shots and damage for the run in progress. Only ever counts up over a run,
+/// so a wave's share is the difference from a copy taken when it started (see
+/// WaveWindow).
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub struct RunStats {
+    pub kills: u32,
+    pub headshots: u32,
+    pub shots: u32,
+    pub hits: u32,
+    /// Health the player has lost, armor soaking up the rest
+    pub damage_taken: f32,
+}
+
+impl RunStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots as f32 * 100.0
+        }
+    }
+}
+
+/// A run that made the table, waiting on its name
+#[derive(Resource)]
+struct PendingHighScore {
+    entry: HighScoreEntry,
+}
+
+#[derive(Component)]
+struct NameEntryRoot;
+
+#[derive(Component)]
+struct NameEntryText;
+
+#[derive(Component, Clone, Copy)]
+enum NameEntryButton {
+    Letter(char),
+    Delete,
+    Done,
+}
+
+#[derive(Component)]
+struct HighScoresRoot;
+
+#[derive(Component)]
+enum HighScoresButton {
+    Back,
+}
+
+/// A missing file is a first run; a corrupt or outdated one is replaced by a fresh table
+fn load_high_scores() -> HighScores {
+    match load_versioned::<HighScoreFile>(HIGH_SCORES_PATH, HIGH_SCORES_VERSION) {
+        Ok(Some(file)) => {
+            let mut scores = HighScores::default();
+            for entry in file.entries {
+                scores.insert(entry);
+            }
+            scores
+        }
+        Ok(None) => HighScores::default(),
+        Err(reason) => {
+            warn!("High score table is {}; starting a fresh one", reason);
+            HighScores::default()
+        }
+    }
+}
+
+fn save_high_scores(scores: &HighScores) {
+    let file = HighScoreFile {
+        version: HIGH_SCORES_VERSION,
+        entries: scores.entries.clone(),
+    };
+    if let Err(e) = save_versioned(HIGH_SCORES_PATH, &file) {
+        warn!("Could not save high scores: {}", e);
+    }
+}
+
+/// Today's date as YYYY-MM-DD (UTC); blank on the web, which has no system clock
+fn today() -> String {
+    if cfg!(target_arch = "wasm32") {
+        return String::new();
+    }
+    let Ok(since_epoch) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return String::new();
+    };
+    // Day count to civil date, after Howard Hinnant's days_from_civil inverse
+    let days = (since_epoch.as_secs() / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn count_run_stats(
+    mut died_events: MessageReader<ZombieDied>,
+    mut shots: MessageReader<ShotFired>,
+    player_q: Query<(Entity, &PlayerHealth), With<Player>>,
+    mut last_health: Local<Option<f32>>,
+    mut stats: ResMut<RunStats>,
+) {
+    let Ok((player, health)) = player_q.single() else {
+        *last_health = None;
+        return;
+    };
+    // Only drops count; healing and respawning don't give any back
+    if let Some(last) = last_health.replace(health.current) {
+        stats.damage_taken += (last - health.current).max(0.0);
+    }
+    for event in died_events.read() {
+        if event.killer == Some(player) {
+            stats.kills += 1;
+            if event.headshot {
+                stats.headshots += 1;
+            }
+        }
+    }
+    for shot in shots.read() {
+        if shot.shooter == player {
+            stats.shots += 1;
+            if shot.hit {
+                stats.hits += 1;
+            }
+        }
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+/// Survival runs that make the table ask for a name over the game over menu
+fn offer_high_score(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    score: Res<Score>,
+    waves: Res<WaveState>,
+    stats: Res<RunStats>,
+    difficulty: Res<DifficultyModifiers>,
+    director: Res<Director>,
+    scores: Res<HighScores>,
+    theme: Res<UiTheme>,
+) {
+    // Adaptive runs paced themselves to the player, so they aren't ranked
+    if *mode != GameMode::Survival || score.points == 0 || director.enabled {
+        return;
+    }
+    let entry = HighScoreEntry {
+        name: String::new(),
+        score: score.points,
+        wave: waves.wave,
+        kills: stats.kills,
+        accuracy: stats.accuracy(),
+        difficulty: difficulty.label(),
+        date: today(),
+    };
+    let Some(place) = scores.placement(&entry) else {
+        return;
+    };
+    spawn_name_entry(&mut commands, &theme, place);
+    commands.insert_resource(PendingHighScore { entry });
+}
+
+fn spawn_name_entry(commands: &mut Commands, theme: &UiTheme, place: usize) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.92)),
+            GlobalZIndex(10),
+            NameEntryRoot,
+            FocusScope::typing(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("New High Score - #{}", place + 1)),
+                theme.font(TextRole::Title),
+                ThemedText(TextRole::Title),
+                TextColor(theme.accent),
+            ));
+            parent.spawn((
+                Text::new("Type or pick your initials"),
+                theme.muted_text(TextRole::Body),
+            ));
+            parent.spawn((Text::new(""), theme.text(TextRole::Title), NameEntryText));
+
+            // Letter grid, seven to a row, with delete and done at the end
+            parent
+                .spawn(Node {
+                    width: Val::Px(7.0 * 60.0),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(6.0),
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    let buttons = ('A'..='Z')
+                        .map(|letter| (letter.to_string(), 54.0, NameEntryButton::Letter(letter)))
+                        .chain([
+                            ("Del".to_string(), 114.0, NameEntryButton::Delete),
+                            ("Done".to_string(), 114.0, NameEntryButton::Done),
+                        ]);
+                    for (label, width, button) in buttons {
+                        grid.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(width),
+                                height: Val::Px(54.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(theme.buttons.normal),
+                            button,
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((Text::new(label), theme.text(TextRole::Body)));
+                        });
+                    }
+                });
+        });
+}
+
+fn type_high_score_name(
+    mut commands: Commands,
+    mut keyboard: MessageReader<KeyboardInput>,
+    mut pending: ResMut<PendingHighScore>,
+    scores: ResMut<HighScores>,
+    roots: Query<Entity, With<NameEntryRoot>>,
+) {
+    let mut done = false;
+    for event in keyboard.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => done = true,
+            Key::Backspace => {
+                pending.entry.name.pop();
+            }
+            Key::Character(text) => {
+                for letter in text.chars().filter(char::is_ascii_alphanumeric) {
+                    push_letter(&mut pending.entry.name, letter);
+                }
+            }
+            _ => {}
+        }
+    }
+    if done {
+        record_high_score(&mut commands, pending.entry.clone(), scores, &roots);
+    }
+}
+
+fn push_letter(name: &mut String, letter: char) {
+    if name.chars().count() < NAME_LENGTH {
+        name.push(letter.to_ascii_uppercase());
+    }
+}
+
+fn handle_name_entry_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &NameEntryButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    theme: Res<UiTheme>,
+    pending: Option<ResMut<PendingHighScore>>,
+    scores: ResMut<HighScores>,
+    roots: Query<Entity, With<NameEntryRoot>>,
+) {
+    // Typing Enter may already have recorded the run this frame
+    let Some(mut pending) = pending else {
+        return;
+    };
+    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *bg_color = theme.buttons.pressed.into();
+                match *button {
+                    NameEntryButton::Letter(letter) => {
+                        push_letter(&mut pending.entry.name, letter);
+                    }
+                    NameEntryButton::Delete => {
+                        pending.entry.name.pop();
+                    }
+                    NameEntryButton::Done => {
+                        record_high_score(&mut commands, pending.entry.clone(), scores, &roots);
+                        return;
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *bg_color = theme.buttons.hovered.into();
+            }
+            Interaction::None => {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+}
+
+fn update_name_entry(
+    pending: Option<Res<PendingHighScore>>,
+    mut text_q: Query<&mut Text, With<NameEntryText>>,
+) {
+    let Some(pending) = pending else {
+        return;
+    };
+    if !pending.is_changed() {
+        return;
+    }
+    // Blanks for the letters still to come
+    let name = &pending.entry.name;
+    let shown = format!(
+        "{}{}",
+        name,
+        "_".repeat(NAME_LENGTH.saturating_sub(name.chars().count()))
+    );
+    for mut text in text_q.iter_mut() {
+        text.0 = shown.clone();
+    }
+}
+
+/// Put the run in the table, write the file and close the name entry
+fn record_high_score(
+    commands: &mut Commands,
+    mut entry: HighScoreEntry,
+    mut scores: ResMut<HighScores>,
+    roots: &Query<Entity, With<NameEntryRoot>>,
+) {
+    if entry.name.is_empty() {
+        entry.name = NO_NAME.to_string();
+    }
+    scores.insert(entry);
+    save_high_scores(&scores);
+    commands.remove_resource::<PendingHighScore>();
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Leaving game over with the name still blank keeps the run, just unnamed
+fn abandon_name_entry(
+    mut commands: Commands,
+    pending: Option<Res<PendingHighScore>>,
+    scores: ResMut<HighScores>,
+    roots: Query<Entity, With<NameEntryRoot>>,
+) {
+    if let Some(pending) = pending {
+        record_high_score(&mut commands, pending.entry.clone(), scores, &roots);
+    }
+}
+
+/// Column titles and widths of the high score table
+const COLUMNS: [(&str, f32); 8] = [
+    ("#", 40.0),
+    ("Name", 80.0),
+    ("Score", 100.0),
+    ("Wave", 70.0),
+    ("Kills", 70.0),
+    ("Acc.", 70.0),
+    ("Difficulty", 170.0),
+    ("Date", 130.0),
+];
+
+fn show_high_scores(mut commands: Commands, scores: Res<HighScores>, theme: Res<UiTheme>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            HighScoresRoot,
+            FocusScope::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("High Scores"),
+                theme.text(TextRole::Title),
+                Node {
+                    margin: UiRect::bottom(Val::Px(12.0)),
+                    ..default()
+                },
+            ));
+
+            let header = COLUMNS.map(|(title, _)| title.to_string());
+            spawn_table_row(parent, &theme, header, theme.muted);
+
+            if scores.entries.is_empty() {
+                parent.spawn((
+                    Text::new("No runs recorded yet"),
+                    theme.muted_text(TextRole::Body),
+                ));
+            }
+            for (place, entry) in scores.entries.iter().enumerate() {
+                let cells = [
+                    (place + 1).to_string(),
+                    entry.name.clone(),
+                    entry.score.to_string(),
+                    entry.wave.to_string(),
+                    entry.kills.to_string(),
+                    format!("{:.0}%", entry.accuracy),
+                    entry.difficulty.clone(),
+                    entry.date.clone(),
+                ];
+                let color = if place == 0 { theme.accent } else { theme.text };
+                spawn_table_row(parent, &theme, cells, color);
+            }
+
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        margin: UiRect::top(Val::Px(12.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(theme.buttons.normal),
+                    HighScoresButton::Back,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((Text::new("Back"), theme.text(TextRole::Body)));
+                });
+        });
+}
+
+fn spawn_table_row(
+    parent: &mut ChildSpawnerCommands,
+    theme: &UiTheme,
+    cells: [String; COLUMNS.len()],
+    color: Color,
+) {
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        })
+        .with_children(|row| {
+            for (cell, (_, width)) in cells.into_iter().zip(COLUMNS) {
+                row.spawn((
+                    Text::new(cell),
+                    theme.font(TextRole::Caption),
+                    ThemedText(TextRole::Caption),
+                    TextColor(color),
+                    Node {
+                        width: Val::Px(width),
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+fn cleanup_high_scores(mut commands: Commands, roots: Query<Entity, With<HighScoresRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn handle_high_score_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &HighScoresButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    theme: Res<UiTheme>,
+    mut menu_stack: ResMut<MenuStack>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *bg_color = theme.buttons.pressed.into();
+                match button {
+                    HighScoresButton::Back => {
+                        menu_stack.back(&mut next_menu_state);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *bg_color = theme.buttons.hovered.into();
+            }
+            Interaction::None => {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use super::{FocusScope, MenuStack, MenuState, TextRole, UiTheme};
+use crate::combat::{
+    cycle_attachment, Attachment, AttachmentInventory, BurstState, ChargingState,
+    PendingWeaponDrops, QueuedShot, ReloadState, WeaponInventory,
+};
+use crate::player::{Player, PlayerPerks};
+use bevy::prelude::*;
+
+/// Pause-menu screen listing every carried weapon with its stats, where slots can
+/// be reordered, attachments fitted or a weapon dropped
+pub struct LoadoutPlugin;
+
+impl Plugin for LoadoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::Loadout), show_loadout)
+            .add_systems(OnExit(MenuState::Loadout), cleanup_loadout)
+            .add_systems(
+                Update,
+                handle_loadout_buttons.run_if(in_state(MenuState::Loadout)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct LoadoutRoot;
+
+#[derive(Component)]
+enum LoadoutButton {
+    MoveUp(usize),
+    MoveDown(usize),
+    Drop(usize),
+    /// Weapon slot and attachment slot; each press steps to the next spare
+    Attachment(usize, usize),
+    Back,
+}
+
+const BAR_WIDTH: f32 = 140.0;
+
+fn show_loadout(
+    mut commands: Commands,
+    players: Query<&WeaponInventory, With<Player>>,
+    attachments: Res<AttachmentInventory>,
+    perks: Res<PlayerPerks>,
+    theme: Res<UiTheme>,
+) {
+    if let Ok(inventory) = players.single() {
+        spawn_loadout(&mut commands, &theme, inventory, &attachments, &perks);
+    }
+}
+
+fn spawn_loadout(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    inventory: &WeaponInventory,
+    attachments: &AttachmentInventory,
+    perks: &PlayerPerks,
+) {
+    // The last weapon can't be dropped, or there'd be nothing left to shoot with
+    let can_drop = inventory.weapon_count() > 1;
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            LoadoutRoot,
+            FocusScope::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::new("Loadout"), theme.text(TextRole::Title)));
+            parent.spawn((
+                Text::new(format!(
+                    "Spare attachments: {}",
+                    spares_label(&attachments.spares)
+                )),
+                theme.muted_text(TextRole::Caption),
+            ));
+
+            for (slot, weapon) in inventory.weapons.iter().enumerate() {
+                let Some(weapon) = weapon else {
+                    continue;
+                };
+                let equipped = slot == inventory.current_slot;
+
+                parent
+                    .spawn((
+                        Node {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(16.0),
+                            padding: UiRect::all(Val::Px(8.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
+                        BorderColor::all(if equipped {
+                            Color::srgb(1.0, 0.8, 0.2)
+                        } else {
+                            Color::NONE
+                        }),
+                    ))
+                    .with_children(|row| {
+                        row.spawn((
+                            Node {
+                                width: Val::Px(170.0),
+                                ..default()
+                            },
+                            Text::new(format!(
+                                "{}  {}\n{}",
+                                slot + 1,
+                                weapon.weapon_type.name(),
+                                weapon.fire_mode.name()
+                            )),
+                            theme.text(TextRole::Body),
+                        ));
+
+                        row.spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(3.0),
+                            ..default()
+                        })
+                        .with_children(|bars| {
+                            for stat in weapon.stats(perks) {
+                                spawn_stat_bar(bars, theme, stat.label, &stat.value, stat.fill);
+                            }
+                        });
+
+                        for (index, attachment) in weapon.attachments.iter().enumerate() {
+                            spawn_loadout_button(
+                                row,
+                                theme,
+                                attachment.map_or("Empty", |attachment| attachment.name()),
+                                LoadoutButton::Attachment(slot, index),
+                            );
+                        }
+
+                        if slot > 0 {
+                            spawn_loadout_button(row, theme, "Up", LoadoutButton::MoveUp(slot));
+                        }
+                        if slot + 1 < inventory.capacity() {
+                            spawn_loadout_button(row, theme, "Down", LoadoutButton::MoveDown(slot));
+                        }
+                        if can_drop {
+                            spawn_loadout_button(row, theme, "Drop", LoadoutButton::Drop(slot));
+                        }
+                    });
+            }
+
+            spawn_loadout_button(parent, theme, "Back", LoadoutButton::Back);
+        });
+}
+
+/// "Scope x2, Compensator", or "none"
+fn spares_label(spares: &[Attachment]) -> String {
+    let labels: Vec<String> = Attachment::ALL
+        .iter()
+        .filter_map(
+            |attachment| match spares.iter().filter(|spare| *spare == attachment).count() {
+                0 => None,
+                1 => Some(attachment.name().to_string()),
+                count => Some(format!("{} x{}", attachment.name(), count)),
+            },
+        )
+        .collect();
+    if labels.is_empty() {
+        "none".to_string()
+    } else {
+        labels.join(", ")
+    }
+}
+
+fn spawn_stat_bar(
+    parent: &mut ChildSpawnerCommands,
+    theme: &UiTheme,
+    label: &str,
+    value: &str,
+    fill: f32,
+) {
+    parent
+        .spawn(Node {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(8.0),
+            ..default()
+        })
+        .with_children(|line| {
+            line.spawn((
+                Node {
+                    width: Val::Px(80.0),
+                    ..default()
+                },
+                Text::new(label),
+                theme.muted_text(TextRole::Caption),
+            ));
+            line.spawn((
+                Node {
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(8.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.25, 0.25, 0.25)),
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    Node {
+                        width: Val::Px(BAR_WIDTH * fill),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.9, 0.75, 0.3)),
+                ));
+            });
+            line.spawn((Text::new(value), theme.text(TextRole::Caption)));
+        });
+}
+
+fn spawn_loadout_button(
+    parent: &mut ChildSpawnerCommands,
+    theme: &UiTheme,
+    label: &str,
+    button: LoadoutButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(match button {
+                    LoadoutButton::Back => 300.0,
+                    LoadoutButton::Attachment(..) => 120.0,
+                    _ => 80.0,
+                }),
+                height: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(theme.buttons.normal),
+            button,
+        ))
+        .with_children(|btn| {
+            btn.spawn((Text::new(label), theme.text(TextRole::Caption)));
+        });
+}
+
+fn cleanup_loadout(mut commands: Commands, roots: Query<Entity, With<LoadoutRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn handle_loadout_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &LoadoutButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    theme: Res<UiTheme>,
+    roots: Query<Entity, With<LoadoutRoot>>,
+    mut players: Query<(Entity, &mut WeaponInventory), With<Player>>,
+    mut pending_drops: ResMut<PendingWeaponDrops>,
+    mut attachments: ResMut<AttachmentInventory>,
+    perks: Res<PlayerPerks>,
+    mut menu_stack: ResMut<MenuStack>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+    let Ok((player, mut inventory)) = players.single_mut() else {
+        return;
+    };
+
+    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *bg_color = theme.buttons.pressed.into();
+                match *button {
+                    LoadoutButton::MoveUp(slot) => inventory.swap_slots(slot, slot - 1),
+                    LoadoutButton::MoveDown(slot) => inventory.swap_slots(slot, slot + 1),
+                    LoadoutButton::Drop(slot) => {
+                        if inventory.weapon_count() > 1 {
+                            // Dropping the weapon in hand abandons whatever it was doing
+                            if slot == inventory.current_slot {
+                                commands.entity(player).remove::<(
+                                    ReloadState,
+                                    BurstState,
+                                    QueuedShot,
+                                    ChargingState,
+                                )>();
+                            }
+                            if let Some(weapon) = inventory.take(slot) {
+                                pending_drops.0.push(weapon);
+                            }
+                        }
+                    }
+                    LoadoutButton::Attachment(slot, index) => {
+                        if let Some(weapon) = inventory.weapons[slot].as_mut() {
+                            cycle_attachment(weapon, index, &mut attachments.spares, &perks);
+                        }
+                    }
+                    LoadoutButton::Back => {
+                        menu_stack.back(&mut next_menu_state);
+                        continue;
+                    }
+                }
+
+                // Rebuild so the rows show the new order
+                for root in roots.iter() {
+                    commands.entity(root).despawn();
+                }
+                spawn_loadout(&mut commands, &theme, &inventory, &attachments, &perks);
+                return;
+            }
+            Interaction::Hovered => {
+                *bg_color = theme.buttons.hovered.into();
+            }
+            Interaction::None => {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+}
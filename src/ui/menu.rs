@@ -1,6 +1,16 @@
+use super::{
+    next_volume_step, AccessibilitySettings, AdaptiveDifficulty, AudioBus, AudioBuses, Difficulty,
+    DifficultyModifiers, FocusScope, Shop, TextRole, UiTheme,
+};
+use crate::combat::{HudSettings, PickupHighlightSettings, WeaponWheel};
+use crate::player::{CameraSettings, FeedbackSettings, FootprintSettings, WeaponSwaySettings};
+use crate::save::{save_exists, Checkpoint, LoadGame, RetryCheckpoint};
 use bevy::prelude::*;
 use bevy::ui::UiScale;
-use bevy::window::{CursorGrabMode, CursorOptions, WindowMode, WindowResolution};
+use bevy::window::{
+    CursorGrabMode, CursorOptions, Monitor, PrimaryMonitor, WindowBackendScaleFactorChanged,
+    WindowMode, WindowPosition, WindowResolution,
+};
 use std::process;
 
 const BASE_HEIGHT: f32 = 1080.0;
@@ -8,6 +18,30 @@ const BASE_HEIGHT: f32 = 1080.0;
 #[derive(Resource, Default)]
 struct LastWindowHeight(f32);
 
+/// Window size to come back to from fullscreen, and the monitor fullscreen goes to
+/// (None for whichever one the window is on)
+#[derive(Resource)]
+struct DisplaySettings {
+    windowed: UVec2,
+    monitor: Option<Entity>,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            windowed: UVec2::new(1920, 1080),
+            monitor: None,
+        }
+    }
+}
+
+impl DisplaySettings {
+    fn monitor_selection(&self) -> MonitorSelection {
+        self.monitor
+            .map_or(MonitorSelection::Current, MonitorSelection::Entity)
+    }
+}
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -15,24 +49,39 @@ impl Plugin for MenuPlugin {
         app.init_state::<GameState>()
             .init_state::<MenuState>()
             .init_resource::<LastWindowHeight>()
-            .add_systems(Startup, setup_menu)
+            .init_resource::<DisplaySettings>()
+            .init_resource::<GameMode>()
+            .init_resource::<Difficulty>()
+            .init_resource::<AdaptiveDifficulty>()
+            .init_resource::<DifficultyModifiers>()
+            .init_resource::<MenuStack>()
+            .add_systems(OnEnter(GameState::MainMenu), show_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), (cleanup_menu, close_submenus))
             .add_systems(
-                OnEnter(GameState::MainMenu),
-                (show_main_menu, unlock_cursor),
+                OnEnter(GameState::Paused),
+                (show_pause_menu, pause_virtual_time),
             )
-            .add_systems(OnExit(GameState::MainMenu), cleanup_menu)
-            .add_systems(OnEnter(GameState::Paused), (show_pause_menu, unlock_cursor))
-            .add_systems(OnExit(GameState::Paused), cleanup_menu)
+            .add_systems(OnExit(GameState::Paused), (cleanup_menu, close_submenus))
             .add_systems(OnEnter(MenuState::Options), show_options_menu)
             .add_systems(OnExit(MenuState::Options), cleanup_options)
-            .add_systems(OnEnter(GameState::Playing), lock_cursor)
+            .add_systems(OnEnter(GameState::Playing), resume_virtual_time)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (show_game_over_menu, pause_virtual_time),
+            )
+            .add_systems(OnExit(GameState::GameOver), cleanup_menu)
+            .add_systems(
+                OnEnter(GameState::Victory),
+                (show_victory_menu, pause_virtual_time),
+            )
+            .add_systems(OnExit(GameState::Victory), cleanup_menu)
             .add_systems(
                 Update,
                 (
                     handle_menu_buttons,
                     handle_options_buttons,
                     handle_pause_input,
-                    update_ui_scale_on_change,
+                    (handle_monitor_changes, update_ui_scale_on_change).chain(),
                     update_resolution_buttons_state,
                 ),
             );
@@ -43,8 +92,42 @@ impl Plugin for MenuPlugin {
 pub enum GameState {
     #[default]
     MainMenu,
+    /// Countdown before a run from the main menu; the world is visible but frozen
+    PrePlaying,
     Playing,
     Paused,
+    PhotoMode,
+    GameOver,
+    /// The run was won (see ExtractionPlugin); its stats screen, with none of a death's
+    /// bookkeeping
+    Victory,
+    /// Picking a perk between waves; gameplay is paused underneath
+    PerkSelect,
+    /// Developer console overlay; gameplay is paused underneath
+    #[cfg(feature = "dev_console")]
+    Console,
+}
+
+/// Which kind of run the main menu started
+#[derive(Resource, Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum GameMode {
+    #[default]
+    Survival,
+    ShootingRange,
+    /// Survival up to a set wave, then a run for the evac point (see ExtractionPlugin)
+    Extraction,
+}
+
+impl GameMode {
+    /// Modes played out in waves of zombies, with pickups and the shop between them
+    pub fn has_waves(&self) -> bool {
+        matches!(self, GameMode::Survival | GameMode::Extraction)
+    }
+}
+
+/// Run condition for systems that only belong in wave modes
+pub fn in_wave_mode(mode: Res<GameMode>) -> bool {
+    mode.has_waves()
 }
 
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
@@ -52,6 +135,38 @@ pub enum MenuState {
     #[default]
     None,
     Options,
+    /// Weapon stats and slot order, opened from the pause menu
+    Loadout,
+    /// Local leaderboard, opened from the main menu
+    HighScores,
+    /// Lifetime progress and the starting loadout, opened from the main menu
+    Unlocks,
+}
+
+/// Submenus opened on top of the main or pause menu, innermost last, so Back
+/// and Escape return to whichever screen opened them
+#[derive(Resource, Default)]
+pub(super) struct MenuStack(Vec<MenuState>);
+
+impl MenuStack {
+    pub(super) fn open(&mut self, menu: MenuState, next: &mut NextState<MenuState>) {
+        self.0.push(menu);
+        next.set(menu);
+    }
+
+    /// Closes the innermost submenu; false if there was none to close
+    pub(super) fn back(&mut self, next: &mut NextState<MenuState>) -> bool {
+        if self.0.pop().is_none() {
+            return false;
+        }
+        next.set(self.0.last().copied().unwrap_or(MenuState::None));
+        true
+    }
+
+    pub(super) fn close_all(&mut self, next: &mut NextState<MenuState>) {
+        self.0.clear();
+        next.set(MenuState::None);
+    }
 }
 
 #[derive(Component)]
@@ -62,15 +177,44 @@ struct OptionsRoot;
 
 #[derive(Component)]
 enum MenuButton {
+    Continue,
     Start,
+    ShootingRange,
+    Extraction,
+    Difficulty,
+    Adaptive,
     Resume,
+    Restart,
+    /// Back to the main menu, which starts the next run afresh
+    MainMenu,
+    RetryCheckpoint,
+    PhotoMode,
     Options,
+    Loadout,
+    HighScores,
+    Unlocks,
     Close,
 }
 
 #[derive(Component)]
 enum OptionsButton {
     Fullscreen,
+    Monitor,
+    CameraSmoothing,
+    FovEffects,
+    WeaponSway,
+    Footprints,
+    ReduceFlinch,
+    AmmoDisplay,
+    PickupBeams,
+    Rumble,
+    RumbleIntensity,
+    MasterVolume,
+    BusVolume(AudioBus),
+    ColorPalette,
+    ReduceFlashing,
+    Subtitles,
+    ReducedGore,
     Resolution(u32, u32),
     Back,
 }
@@ -81,62 +225,109 @@ struct ResolutionButton;
 #[derive(Component)]
 struct ButtonText;
 
-#[derive(Resource)]
-struct MenuColors {
-    normal: Color,
-    hovered: Color,
-    pressed: Color,
-}
-
-impl Default for MenuColors {
-    fn default() -> Self {
-        Self {
-            normal: Color::srgb(0.15, 0.15, 0.15),
-            hovered: Color::srgb(0.25, 0.25, 0.25),
-            pressed: Color::srgb(0.35, 0.55, 0.35),
-        }
-    }
-}
-
-fn setup_menu(mut commands: Commands) {
-    commands.insert_resource(MenuColors::default());
+/// Freeze gameplay timers and physics while the game isn't being played
+pub fn pause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
 }
 
-fn unlock_cursor(mut cursor_options: Single<&mut CursorOptions>) {
-    cursor_options.grab_mode = CursorGrabMode::None;
-    cursor_options.visible = true;
+fn resume_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
 }
 
-fn lock_cursor(mut cursor_options: Single<&mut CursorOptions>) {
-    cursor_options.grab_mode = CursorGrabMode::Locked;
-    cursor_options.visible = false;
+fn show_main_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    difficulty: Res<Difficulty>,
+    adaptive: Res<AdaptiveDifficulty>,
+) {
+    let mut buttons = Vec::new();
+    if save_exists() {
+        buttons.push(("Continue", MenuButton::Continue));
+    }
+    buttons.extend([
+        ("Start", MenuButton::Start),
+        ("Shooting Range", MenuButton::ShootingRange),
+        ("Extraction", MenuButton::Extraction),
+    ]);
+    // Difficulty can only be changed here, never mid-run
+    let difficulty_label = difficulty_label(&difficulty);
+    buttons.push((difficulty_label.as_str(), MenuButton::Difficulty));
+    let adaptive_label = adaptive_label(&adaptive);
+    buttons.push((adaptive_label.as_str(), MenuButton::Adaptive));
+    buttons.extend([
+        ("High Scores", MenuButton::HighScores),
+        ("Unlocks", MenuButton::Unlocks),
+        ("Options", MenuButton::Options),
+        ("Close", MenuButton::Close),
+    ]);
+    spawn_menu(&mut commands, &theme, "My Bevy Game", None, buttons);
 }
 
-fn show_main_menu(mut commands: Commands) {
+fn show_pause_menu(mut commands: Commands, theme: Res<UiTheme>) {
     spawn_menu(
         &mut commands,
-        "My Bevy Game",
+        &theme,
+        "Paused",
+        None,
         vec![
-            ("Start", MenuButton::Start),
+            ("Resume", MenuButton::Resume),
+            ("Loadout", MenuButton::Loadout),
+            ("Photo Mode", MenuButton::PhotoMode),
             ("Options", MenuButton::Options),
             ("Close", MenuButton::Close),
         ],
     );
 }
 
-fn show_pause_menu(mut commands: Commands) {
+fn show_game_over_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    mode: Res<GameMode>,
+    difficulty: Res<DifficultyModifiers>,
+    checkpoint: Res<Checkpoint>,
+) {
+    let title = match *mode {
+        GameMode::Survival | GameMode::Extraction => "Game Over",
+        GameMode::ShootingRange => "Time's Up",
+    };
+    let subtitle = format!("Difficulty: {}", difficulty.label());
+    let mut buttons = Vec::new();
+    if checkpoint.is_set() {
+        buttons.push(("Retry from checkpoint", MenuButton::RetryCheckpoint));
+    }
+    buttons.extend([
+        ("Restart", MenuButton::Restart),
+        ("Close", MenuButton::Close),
+    ]);
+    spawn_menu(&mut commands, &theme, title, Some(&subtitle), buttons);
+}
+
+/// The run's stats go on top (see spawn_extraction_results)
+fn show_victory_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    difficulty: Res<DifficultyModifiers>,
+) {
+    let subtitle = format!("Difficulty: {}", difficulty.label());
     spawn_menu(
         &mut commands,
-        "Paused",
+        &theme,
+        "Extracted",
+        Some(&subtitle),
         vec![
-            ("Resume", MenuButton::Resume),
-            ("Options", MenuButton::Options),
+            ("Main Menu", MenuButton::MainMenu),
             ("Close", MenuButton::Close),
         ],
     );
 }
 
-fn spawn_menu(commands: &mut Commands, title: &str, buttons: Vec<(&str, MenuButton)>) {
+fn spawn_menu(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    title: &str,
+    subtitle: Option<&str>,
+    buttons: Vec<(&str, MenuButton)>,
+) {
     commands
         .spawn((
             Node {
@@ -148,19 +339,17 @@ fn spawn_menu(commands: &mut Commands, title: &str, buttons: Vec<(&str, MenuButt
                 row_gap: Val::Px(20.0),
                 ..default()
             },
-            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            BackgroundColor(theme.overlay),
             MenuRoot,
+            FocusScope::default(),
         ))
         .with_children(|parent| {
             // Title
-            parent.spawn((
-                Text::new(title),
-                TextFont {
-                    font_size: 60.0,
-                    ..default()
-                },
-                TextColor(Color::WHITE),
-            ));
+            parent.spawn((Text::new(title), theme.text(TextRole::Title)));
+
+            if let Some(subtitle) = subtitle {
+                parent.spawn((Text::new(subtitle), theme.muted_text(TextRole::Body)));
+            }
 
             // Buttons
             for (text, button_type) in buttons {
@@ -174,31 +363,93 @@ fn spawn_menu(commands: &mut Commands, title: &str, buttons: Vec<(&str, MenuButt
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                        BackgroundColor(theme.buttons.normal),
                         button_type,
                     ))
                     .with_children(|parent| {
-                        parent.spawn((
-                            Text::new(text),
-                            TextFont {
-                                font_size: 30.0,
-                                ..default()
-                            },
-                            TextColor(Color::WHITE),
-                        ));
+                        parent.spawn((Text::new(text), theme.text(TextRole::Heading), ButtonText));
                     });
             }
         });
 }
 
-fn show_options_menu(mut commands: Commands, window: Single<&Window>) {
+fn difficulty_label(difficulty: &Difficulty) -> String {
+    format!("Difficulty: {}", difficulty.modifiers().name)
+}
+
+fn adaptive_label(adaptive: &AdaptiveDifficulty) -> String {
+    format!("Adaptive: {}", if adaptive.0 { "On" } else { "Off" })
+}
+
+fn palette_label(settings: &AccessibilitySettings) -> String {
+    format!("Colors: {}", settings.palette.name())
+}
+
+fn ammo_display_label(settings: &HudSettings) -> String {
+    format!("Ammo display: {}", settings.ammo_display.name())
+}
+
+fn rumble_intensity_label(settings: &FeedbackSettings) -> String {
+    format!("Rumble strength: {:.0}%", settings.rumble_intensity * 100.0)
+}
+
+fn volume_label(label: &str, volume: f32) -> String {
+    format!("{} {:.0}%", label, volume * 100.0)
+}
+
+fn on_off(label: &str, enabled: bool) -> String {
+    format!("{}: {}", label, if enabled { "ON" } else { "OFF" })
+}
+
+/// Monitors left to right, the order the picker steps through them
+fn ordered_monitors<'a>(monitors: &'a Query<(Entity, &Monitor)>) -> Vec<(Entity, &'a Monitor)> {
+    let mut ordered: Vec<_> = monitors.iter().collect();
+    ordered.sort_by_key(|(_, monitor)| (monitor.physical_position.x, monitor.physical_position.y));
+    ordered
+}
+
+fn monitor_label(display: &DisplaySettings, monitors: &Query<(Entity, &Monitor)>) -> String {
+    let ordered = ordered_monitors(monitors);
+    let name = display
+        .monitor
+        .and_then(|picked| ordered.iter().position(|(entity, _)| *entity == picked))
+        .map_or("Current".to_string(), |index| {
+            ordered[index]
+                .1
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Display {}", index + 1))
+        });
+    format!("Monitor: {}", name)
+}
+
+fn show_options_menu(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    window: Single<&Window>,
+    camera_settings: Res<CameraSettings>,
+    sway_settings: Res<WeaponSwaySettings>,
+    footprint_settings: Res<FootprintSettings>,
+    feedback_settings: Res<FeedbackSettings>,
+    hud_settings: Res<HudSettings>,
+    highlight_settings: Res<PickupHighlightSettings>,
+    buses: Res<AudioBuses>,
+    accessibility: Res<AccessibilitySettings>,
+    display: Res<DisplaySettings>,
+    monitors: Query<(Entity, &Monitor)>,
+) {
     let current_mode = &window.mode;
     let is_fullscreen = matches!(
         current_mode,
         WindowMode::Fullscreen(..) | WindowMode::BorderlessFullscreen(_)
     );
 
-    let fullscreen_text = if is_fullscreen {
+    // Browsers own the window size, so only a fullscreen request makes sense there
+    let is_web = cfg!(target_arch = "wasm32");
+
+    let fullscreen_text = if is_web {
+        "Fullscreen (browser)"
+    } else if is_fullscreen {
         "Fullscreen: ON"
     } else {
         "Fullscreen: OFF"
@@ -215,19 +466,13 @@ fn show_options_menu(mut commands: Commands, window: Single<&Window>) {
                 row_gap: Val::Px(15.0),
                 ..default()
             },
-            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            BackgroundColor(theme.overlay),
             OptionsRoot,
+            FocusScope::default(),
         ))
         .with_children(|parent| {
             // Title
-            parent.spawn((
-                Text::new("Options"),
-                TextFont {
-                    font_size: 50.0,
-                    ..default()
-                },
-                TextColor(Color::WHITE),
-            ));
+            parent.spawn((Text::new("Options"), theme.text(TextRole::Title)));
 
             // Fullscreen toggle
             parent
@@ -240,36 +485,285 @@ fn show_options_menu(mut commands: Commands, window: Single<&Window>) {
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    BackgroundColor(theme.buttons.normal),
                     OptionsButton::Fullscreen,
                 ))
                 .with_children(|btn| {
                     btn.spawn((
                         Text::new(fullscreen_text),
-                        TextFont {
-                            font_size: 24.0,
+                        theme.text(TextRole::Body),
+                        ButtonText,
+                    ));
+                });
+
+            // Which monitor fullscreen goes to, once there's a choice
+            if !is_web && monitors.iter().count() > 1 {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(300.0),
+                            height: Val::Px(50.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
                             ..default()
                         },
-                        TextColor(Color::WHITE),
+                        BackgroundColor(theme.buttons.normal),
+                        OptionsButton::Monitor,
+                    ))
+                    .with_children(|btn| {
+                        btn.spawn((
+                            Text::new(monitor_label(&display, &monitors)),
+                            theme.text(TextRole::Body),
+                            ButtonText,
+                        ));
+                    });
+            }
+
+            // Camera smoothing toggle
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(theme.buttons.normal),
+                    OptionsButton::CameraSmoothing,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(on_off("Camera smoothing", camera_settings.smoothing)),
+                        theme.text(TextRole::Body),
                         ButtonText,
                     ));
                 });
 
-            // Resolution label
-            parent.spawn((
-                Text::new("Resolution:"),
-                TextFont {
-                    font_size: 25.0,
+            // FOV effects toggle
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(theme.buttons.normal),
+                    OptionsButton::FovEffects,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(on_off("FOV effects", camera_settings.fov_effects)),
+                        theme.text(TextRole::Body),
+                        ButtonText,
+                    ));
+                });
+
+            // Weapon sway and footprints, side by side to keep the column short
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(15.0),
                     ..default()
-                },
-                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                })
+                .with_children(|row| {
+                    for (label, button) in [
+                        (
+                            on_off("Weapon sway", sway_settings.enabled),
+                            OptionsButton::WeaponSway,
+                        ),
+                        (
+                            on_off("Footprints", footprint_settings.enabled),
+                            OptionsButton::Footprints,
+                        ),
+                    ] {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(theme.buttons.normal),
+                            button,
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((Text::new(label), theme.text(TextRole::Body), ButtonText));
+                        });
+                    }
+                });
+
+            // Reduced flinch camera kick toggle
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(theme.buttons.normal),
+                    OptionsButton::ReduceFlinch,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(on_off("Reduce flinch", camera_settings.reduce_flinch)),
+                        theme.text(TextRole::Body),
+                        ButtonText,
+                    ));
+                });
+
+            // Ammo as numbers, pips or both
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(theme.buttons.normal),
+                    OptionsButton::AmmoDisplay,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(ammo_display_label(&hud_settings)),
+                        theme.text(TextRole::Body),
+                        ButtonText,
+                    ));
+                });
+
+            // Light beams over nearby pickups, worth turning off on slower machines
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(theme.buttons.normal),
+                    OptionsButton::PickupBeams,
+                ))
+                .with_children(|btn| {
+                    btn.spawn((
+                        Text::new(on_off("Pickup beams", highlight_settings.beams)),
+                        theme.text(TextRole::Body),
+                        ButtonText,
+                    ));
+                });
+
+            // Controller rumble, side by side to keep the column short
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(15.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for (label, button) in [
+                        (
+                            on_off("Rumble", feedback_settings.rumble),
+                            OptionsButton::Rumble,
+                        ),
+                        (
+                            rumble_intensity_label(&feedback_settings),
+                            OptionsButton::RumbleIntensity,
+                        ),
+                    ] {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(theme.buttons.normal),
+                            button,
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((Text::new(label), theme.text(TextRole::Body), ButtonText));
+                        });
+                    }
+                });
+
+            // Volume per audio bus, one row
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    let buttons = [(
+                        volume_label("Master", buses.master),
+                        OptionsButton::MasterVolume,
+                    )]
+                    .into_iter()
+                    .chain(AudioBus::ALL.into_iter().map(|bus| {
+                        (
+                            volume_label(bus.name(), buses.bus(bus).volume),
+                            OptionsButton::BusVolume(bus),
+                        )
+                    }));
+                    for (label, button) in buttons {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(150.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(theme.buttons.normal),
+                            button,
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((
+                                Text::new(label),
+                                theme.text(TextRole::Caption),
+                                ButtonText,
+                            ));
+                        });
+                    }
+                });
+
+            // Accessibility section
+            parent.spawn((
+                Text::new("Accessibility:"),
+                theme.muted_text(TextRole::Body),
             ));
 
-            // Resolution buttons
-            for (w, h, label) in [
-                (1280u32, 720u32, "1280 x 720"),
-                (1920, 1080, "1920 x 1080"),
-                (2560, 1440, "2560 x 1440"),
+            for (label, button) in [
+                (palette_label(&accessibility), OptionsButton::ColorPalette),
+                (
+                    on_off("Reduce flashing", accessibility.reduce_flashing),
+                    OptionsButton::ReduceFlashing,
+                ),
+                (
+                    on_off("Subtitles", accessibility.subtitles),
+                    OptionsButton::Subtitles,
+                ),
+                (
+                    on_off("Reduced gore", accessibility.reduced_gore),
+                    OptionsButton::ReducedGore,
+                ),
             ] {
                 parent
                     .spawn((
@@ -281,20 +775,42 @@ fn show_options_menu(mut commands: Commands, window: Single<&Window>) {
                             align_items: AlignItems::Center,
                             ..default()
                         },
-                        BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                        OptionsButton::Resolution(w, h),
-                        ResolutionButton,
+                        BackgroundColor(theme.buttons.normal),
+                        button,
                     ))
                     .with_children(|btn| {
-                        btn.spawn((
-                            Text::new(label),
-                            TextFont {
-                                font_size: 24.0,
+                        btn.spawn((Text::new(label), theme.text(TextRole::Body), ButtonText));
+                    });
+            }
+
+            if !is_web {
+                // Resolution label
+                parent.spawn((Text::new("Resolution:"), theme.muted_text(TextRole::Body)));
+
+                // Resolution buttons
+                for (w, h, label) in [
+                    (1280u32, 720u32, "1280 x 720"),
+                    (1920, 1080, "1920 x 1080"),
+                    (2560, 1440, "2560 x 1440"),
+                ] {
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(300.0),
+                                height: Val::Px(50.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
                                 ..default()
                             },
-                            TextColor(Color::WHITE),
-                        ));
-                    });
+                            BackgroundColor(theme.buttons.normal),
+                            OptionsButton::Resolution(w, h),
+                            ResolutionButton,
+                        ))
+                        .with_children(|btn| {
+                            btn.spawn((Text::new(label), theme.text(TextRole::Body)));
+                        });
+                }
             }
 
             // Back button
@@ -308,18 +824,11 @@ fn show_options_menu(mut commands: Commands, window: Single<&Window>) {
                         align_items: AlignItems::Center,
                         ..default()
                     },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+                    BackgroundColor(theme.buttons.normal),
                     OptionsButton::Back,
                 ))
                 .with_children(|btn| {
-                    btn.spawn((
-                        Text::new("Back"),
-                        TextFont {
-                            font_size: 24.0,
-                            ..default()
-                        },
-                        TextColor(Color::WHITE),
-                    ));
+                    btn.spawn((Text::new("Back"), theme.text(TextRole::Body)));
                 });
         });
 }
@@ -338,26 +847,91 @@ fn cleanup_options(mut commands: Commands, options_query: Query<Entity, With<Opt
 
 fn handle_menu_buttons(
     mut interaction_query: Query<
-        (&Interaction, &MenuButton, &mut BackgroundColor),
+        (&Interaction, &MenuButton, &mut BackgroundColor, &Children),
         Changed<Interaction>,
     >,
-    colors: Res<MenuColors>,
+    mut text_query: Query<&mut Text, With<ButtonText>>,
+    theme: Res<UiTheme>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut modifiers: ResMut<DifficultyModifiers>,
+    mut adaptive: ResMut<AdaptiveDifficulty>,
     mut next_game_state: ResMut<NextState<GameState>>,
+    mut menu_stack: ResMut<MenuStack>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
+    mut load_events: MessageWriter<LoadGame>,
+    mut retry_events: MessageWriter<RetryCheckpoint>,
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
 ) {
-    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+    for (interaction, button, mut bg_color, children) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                *bg_color = colors.pressed.into();
+                *bg_color = theme.buttons.pressed.into();
+                // The click belongs to the button; nothing later this frame sees it
+                mouse_button.clear_just_pressed(MouseButton::Left);
                 match button {
+                    MenuButton::Continue => {
+                        // The save system starts the countdown once the file loads
+                        load_events.write(LoadGame);
+                    }
                     MenuButton::Start => {
-                        next_game_state.set(GameState::Playing);
+                        *mode = GameMode::Survival;
+                        next_game_state.set(GameState::PrePlaying);
+                    }
+                    MenuButton::ShootingRange => {
+                        *mode = GameMode::ShootingRange;
+                        next_game_state.set(GameState::PrePlaying);
+                    }
+                    MenuButton::Extraction => {
+                        *mode = GameMode::Extraction;
+                        next_game_state.set(GameState::PrePlaying);
+                    }
+                    MenuButton::Difficulty => {
+                        difficulty.cycle();
+                        *modifiers = difficulty.modifiers();
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = difficulty_label(&difficulty);
+                            }
+                        }
+                    }
+                    MenuButton::Adaptive => {
+                        adaptive.0 = !adaptive.0;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = adaptive_label(&adaptive);
+                            }
+                        }
                     }
                     MenuButton::Resume => {
                         next_game_state.set(GameState::Playing);
                     }
+                    MenuButton::Restart => {
+                        // Leaving GameOver for Playing resets the run (see OnTransition systems)
+                        next_game_state.set(GameState::Playing);
+                    }
+                    MenuButton::MainMenu => {
+                        // Leaving MainMenu for PrePlaying resets the run
+                        next_game_state.set(GameState::MainMenu);
+                    }
+                    MenuButton::RetryCheckpoint => {
+                        // The checkpoint system switches to Playing and restores the snapshot
+                        retry_events.write(RetryCheckpoint);
+                    }
+                    MenuButton::PhotoMode => {
+                        next_game_state.set(GameState::PhotoMode);
+                    }
                     MenuButton::Options => {
-                        next_menu_state.set(MenuState::Options);
+                        menu_stack.open(MenuState::Options, &mut next_menu_state);
+                    }
+                    MenuButton::Loadout => {
+                        menu_stack.open(MenuState::Loadout, &mut next_menu_state);
+                    }
+                    MenuButton::HighScores => {
+                        menu_stack.open(MenuState::HighScores, &mut next_menu_state);
+                    }
+                    MenuButton::Unlocks => {
+                        menu_stack.open(MenuState::Unlocks, &mut next_menu_state);
                     }
                     MenuButton::Close => {
                         // Use immediate exit to avoid slow cleanup with many physics entities
@@ -366,10 +940,10 @@ fn handle_menu_buttons(
                 }
             }
             Interaction::Hovered => {
-                *bg_color = colors.hovered.into();
+                *bg_color = theme.buttons.hovered.into();
             }
             Interaction::None => {
-                *bg_color = colors.normal.into();
+                *bg_color = theme.buttons.normal.into();
             }
         }
     }
@@ -386,14 +960,25 @@ fn handle_options_buttons(
         Changed<Interaction>,
     >,
     mut text_query: Query<&mut Text, With<ButtonText>>,
-    colors: Res<MenuColors>,
+    theme: Res<UiTheme>,
+    mut menu_stack: ResMut<MenuStack>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
     mut window: Single<&mut Window>,
+    mut camera_settings: ResMut<CameraSettings>,
+    mut sway_settings: ResMut<WeaponSwaySettings>,
+    mut footprint_settings: ResMut<FootprintSettings>,
+    mut feedback_settings: ResMut<FeedbackSettings>,
+    mut hud_settings: ResMut<HudSettings>,
+    mut highlight_settings: ResMut<PickupHighlightSettings>,
+    mut buses: ResMut<AudioBuses>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut display: ResMut<DisplaySettings>,
+    monitors: Query<(Entity, &Monitor)>,
 ) {
     for (interaction, button, mut bg_color, children) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
-                *bg_color = colors.pressed.into();
+                *bg_color = theme.buttons.pressed.into();
                 match button {
                     OptionsButton::Fullscreen => {
                         let is_fullscreen = matches!(
@@ -403,17 +988,23 @@ fn handle_options_buttons(
 
                         if is_fullscreen {
                             window.mode = WindowMode::Windowed;
-                            // Reset to default resolution when exiting fullscreen
-                            window.resolution = WindowResolution::new(1920, 1080);
+                            // Back to the picked windowed size when exiting fullscreen
+                            // (the page sizes the canvas in the browser)
+                            if !cfg!(target_arch = "wasm32") {
+                                window.resolution =
+                                    WindowResolution::new(display.windowed.x, display.windowed.y);
+                            }
                         } else {
                             window.mode =
-                                WindowMode::BorderlessFullscreen(MonitorSelection::Current);
+                                WindowMode::BorderlessFullscreen(display.monitor_selection());
                         }
 
                         // Update button text
                         for child in children.iter() {
                             if let Ok(mut text) = text_query.get_mut(child) {
-                                let new_text = if is_fullscreen {
+                                let new_text = if cfg!(target_arch = "wasm32") {
+                                    "Fullscreen (browser)"
+                                } else if is_fullscreen {
                                     "Fullscreen: OFF"
                                 } else {
                                     "Fullscreen: ON"
@@ -422,50 +1013,225 @@ fn handle_options_buttons(
                             }
                         }
                     }
+                    OptionsButton::Monitor => {
+                        let ordered = ordered_monitors(&monitors);
+                        let next = match display.monitor.and_then(|picked| {
+                            ordered.iter().position(|(entity, _)| *entity == picked)
+                        }) {
+                            None => ordered.first(),
+                            Some(index) => ordered.get(index + 1),
+                        };
+                        display.monitor = next.map(|(entity, _)| *entity);
+                        // Already fullscreen, so move there now
+                        if matches!(window.mode, WindowMode::BorderlessFullscreen(_)) {
+                            window.mode =
+                                WindowMode::BorderlessFullscreen(display.monitor_selection());
+                        }
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = monitor_label(&display, &monitors);
+                            }
+                        }
+                    }
+                    OptionsButton::CameraSmoothing => {
+                        camera_settings.smoothing = !camera_settings.smoothing;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Camera smoothing", camera_settings.smoothing);
+                            }
+                        }
+                    }
+                    OptionsButton::FovEffects => {
+                        camera_settings.fov_effects = !camera_settings.fov_effects;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("FOV effects", camera_settings.fov_effects);
+                            }
+                        }
+                    }
+                    OptionsButton::ReduceFlinch => {
+                        camera_settings.reduce_flinch = !camera_settings.reduce_flinch;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Reduce flinch", camera_settings.reduce_flinch);
+                            }
+                        }
+                    }
+                    OptionsButton::AmmoDisplay => {
+                        hud_settings.ammo_display = hud_settings.ammo_display.next();
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = ammo_display_label(&hud_settings);
+                            }
+                        }
+                    }
+                    OptionsButton::PickupBeams => {
+                        highlight_settings.beams = !highlight_settings.beams;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Pickup beams", highlight_settings.beams);
+                            }
+                        }
+                    }
+                    OptionsButton::WeaponSway => {
+                        sway_settings.enabled = !sway_settings.enabled;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Weapon sway", sway_settings.enabled);
+                            }
+                        }
+                    }
+                    OptionsButton::Footprints => {
+                        footprint_settings.enabled = !footprint_settings.enabled;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Footprints", footprint_settings.enabled);
+                            }
+                        }
+                    }
+                    OptionsButton::Rumble => {
+                        feedback_settings.rumble = !feedback_settings.rumble;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Rumble", feedback_settings.rumble);
+                            }
+                        }
+                    }
+                    OptionsButton::RumbleIntensity => {
+                        feedback_settings.rumble_intensity = feedback_settings.next_intensity();
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = rumble_intensity_label(&feedback_settings);
+                            }
+                        }
+                    }
+                    OptionsButton::MasterVolume => {
+                        buses.master = next_volume_step(buses.master);
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = volume_label("Master", buses.master);
+                            }
+                        }
+                    }
+                    OptionsButton::BusVolume(bus) => {
+                        let gain = buses.bus_mut(*bus);
+                        gain.volume = next_volume_step(gain.volume);
+                        let volume = gain.volume;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = volume_label(bus.name(), volume);
+                            }
+                        }
+                    }
+                    OptionsButton::ColorPalette => {
+                        accessibility.palette = accessibility.palette.next();
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = palette_label(&accessibility);
+                            }
+                        }
+                    }
+                    OptionsButton::ReduceFlashing => {
+                        accessibility.reduce_flashing = !accessibility.reduce_flashing;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Reduce flashing", accessibility.reduce_flashing);
+                            }
+                        }
+                    }
+                    OptionsButton::Subtitles => {
+                        accessibility.subtitles = !accessibility.subtitles;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Subtitles", accessibility.subtitles);
+                            }
+                        }
+                    }
+                    OptionsButton::ReducedGore => {
+                        accessibility.reduced_gore = !accessibility.reduced_gore;
+                        for child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                **text = on_off("Reduced gore", accessibility.reduced_gore);
+                            }
+                        }
+                    }
                     OptionsButton::Resolution(w, h) => {
                         // Only change resolution in windowed mode
                         if matches!(window.mode, WindowMode::Windowed) {
                             window.resolution = WindowResolution::new(*w, *h);
+                            display.windowed = UVec2::new(*w, *h);
                         }
                     }
                     OptionsButton::Back => {
-                        next_menu_state.set(MenuState::None);
+                        menu_stack.back(&mut next_menu_state);
                     }
                 }
             }
             Interaction::Hovered => {
-                *bg_color = colors.hovered.into();
+                *bg_color = theme.buttons.hovered.into();
             }
             Interaction::None => {
-                *bg_color = colors.normal.into();
+                *bg_color = theme.buttons.normal.into();
             }
         }
     }
 }
 
+/// Escape backs out one layer at a time: the innermost submenu, then the shop or
+/// the weapon wheel, then the pause menu itself
 fn handle_pause_input(
     keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
     current_state: Res<State<GameState>>,
+    mut menu_stack: ResMut<MenuStack>,
+    mut shop: ResMut<Shop>,
+    mut wheel: ResMut<WeaponWheel>,
     mut next_state: ResMut<NextState<GameState>>,
     mut next_menu_state: ResMut<NextState<MenuState>>,
 ) {
-    if keys.just_pressed(KeyCode::Escape) {
-        match current_state.get() {
-            GameState::Playing => {
-                next_state.set(GameState::Paused);
-                next_menu_state.set(MenuState::None);
-            }
-            GameState::Paused => {
-                next_state.set(GameState::Playing);
-                next_menu_state.set(MenuState::None);
-            }
-            _ => {}
+    // East backs out of menus too, but is left alone in play
+    let gamepad_back = *current_state.get() != GameState::Playing
+        && gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::East));
+    if !keys.just_pressed(KeyCode::Escape) && !gamepad_back {
+        return;
+    }
+    if menu_stack.back(&mut next_menu_state) {
+        return;
+    }
+    match current_state.get() {
+        GameState::Playing if shop.open => {
+            shop.open = false;
+        }
+        GameState::Playing if wheel.is_open() => {
+            wheel.cancel();
         }
+        GameState::Playing => {
+            next_state.set(GameState::Paused);
+        }
+        GameState::Paused => {
+            next_state.set(GameState::Playing);
+        }
+        GameState::PhotoMode => {
+            // Back to the pause menu, which is re-shown on entering Paused
+            next_state.set(GameState::Paused);
+        }
+        _ => {}
     }
 }
 
+/// Submenus belong to the menu they were opened from, so they go when it does
+fn close_submenus(
+    mut menu_stack: ResMut<MenuStack>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+    menu_stack.close_all(&mut next_menu_state);
+}
+
 fn update_resolution_buttons_state(
     window: Single<&Window>,
+    theme: Res<UiTheme>,
     mut buttons: Query<(&mut BackgroundColor, &Children), With<ResolutionButton>>,
     mut text_query: Query<&mut TextColor>,
 ) {
@@ -479,7 +1245,7 @@ fn update_resolution_buttons_state(
         (Color::srgb(0.1, 0.1, 0.1), Color::srgb(0.4, 0.4, 0.4))
     } else {
         // Normal in windowed
-        (Color::srgb(0.15, 0.15, 0.15), Color::WHITE)
+        (theme.buttons.normal, theme.text)
     };
 
     for (mut bg, children) in buttons.iter_mut() {
@@ -492,6 +1258,93 @@ fn update_resolution_buttons_state(
     }
 }
 
+/// A monitor unplugged or added, or the window landing on a display with another
+/// scale, can leave the window bigger than its screen and the cursor grabbed to a
+/// stale region. Play pauses, the window is fitted back onto its monitor and
+/// fullscreen leaves a monitor that's gone.
+fn handle_monitor_changes(
+    mut scale_changes: MessageReader<WindowBackendScaleFactorChanged>,
+    added: Query<(), Added<Monitor>>,
+    mut removed: RemovedComponents<Monitor>,
+    monitors: Query<(&Monitor, Has<PrimaryMonitor>)>,
+    mut window: Single<&mut Window>,
+    mut cursor_options: Single<&mut CursorOptions>,
+    mut display: ResMut<DisplaySettings>,
+    mut last_height: ResMut<LastWindowHeight>,
+    current_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let rescaled = scale_changes.read().count() > 0;
+    let unplugged = removed.read().count() > 0;
+    if !rescaled && !unplugged && added.is_empty() {
+        return;
+    }
+
+    if display
+        .monitor
+        .is_some_and(|picked| !monitors.contains(picked))
+    {
+        display.monitor = None;
+        match window.mode {
+            WindowMode::BorderlessFullscreen(_) => {
+                window.mode = WindowMode::BorderlessFullscreen(MonitorSelection::Current);
+            }
+            WindowMode::Fullscreen(_, video_mode) => {
+                window.mode = WindowMode::Fullscreen(MonitorSelection::Current, video_mode);
+            }
+            WindowMode::Windowed => {}
+        }
+    }
+
+    // The page sizes the canvas in the browser
+    if !cfg!(target_arch = "wasm32") {
+        if let Some(monitor) = window_monitor(&window, &monitors) {
+            let fits = (monitor.physical_size().as_vec2() / monitor.scale_factor as f32).as_uvec2();
+            display.windowed = display.windowed.min(fits);
+            let size = window.resolution.size();
+            if matches!(window.mode, WindowMode::Windowed)
+                && (size.x > fits.x as f32 || size.y > fits.y as f32)
+            {
+                window
+                    .resolution
+                    .set(display.windowed.x as f32, display.windowed.y as f32);
+            }
+        }
+    }
+
+    match current_state.get() {
+        // Pausing frees the cursor too
+        GameState::Playing => next_state.set(GameState::Paused),
+        // Re-assigned so the window backend grabs again within the new bounds
+        _ if cursor_options.grab_mode != CursorGrabMode::None => cursor_options.set_changed(),
+        _ => {}
+    }
+
+    // Rescale against the corrected height
+    last_height.0 = 0.0;
+}
+
+/// The monitor under the window's top-left corner, else the primary one
+fn window_monitor<'a>(
+    window: &Window,
+    monitors: &'a Query<(&Monitor, Has<PrimaryMonitor>)>,
+) -> Option<&'a Monitor> {
+    let on_window = match window.position {
+        WindowPosition::At(position) => monitors.iter().find(|(monitor, _)| {
+            let offset = position - monitor.physical_position;
+            offset.cmpge(IVec2::ZERO).all()
+                && offset.cmplt(monitor.physical_size().as_ivec2()).all()
+        }),
+        _ => None,
+    };
+    on_window
+        .or_else(|| monitors.iter().find(|(_, primary)| *primary))
+        .or_else(|| monitors.iter().next())
+        .map(|(monitor, _)| monitor)
+}
+
+/// Scale the UI with window height. On the web the canvas follows the page, so this
+/// also picks up page-driven resizes.
 fn update_ui_scale_on_change(
     window: Single<&Window>,
     mut ui_scale: ResMut<UiScale>,
@@ -499,6 +1352,11 @@ fn update_ui_scale_on_change(
 ) {
     let current_height = window.height();
 
+    // A collapsed or hidden canvas reports zero height; keep the last usable scale
+    if current_height <= 0.0 {
+        return;
+    }
+
     // Only update if height actually changed
     if (current_height - last_height.0).abs() > 0.1 {
         last_height.0 = current_height;
@@ -506,3 +1364,55 @@ fn update_ui_scale_on_change(
         ui_scale.0 = scale;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(next: &NextState<MenuState>) -> Option<MenuState> {
+        match next {
+            NextState::Pending(menu) => Some(*menu),
+            NextState::Unchanged => None,
+        }
+    }
+
+    #[test]
+    fn backing_out_of_the_only_submenu_returns_to_the_menu_under_it() {
+        let (mut stack, mut next) = (MenuStack::default(), NextState::default());
+        stack.open(MenuState::Options, &mut next);
+        assert_eq!(pending(&next), Some(MenuState::Options));
+
+        assert!(stack.back(&mut next));
+        assert_eq!(pending(&next), Some(MenuState::None));
+    }
+
+    #[test]
+    fn back_closes_one_submenu_at_a_time() {
+        let (mut stack, mut next) = (MenuStack::default(), NextState::default());
+        stack.open(MenuState::Loadout, &mut next);
+        stack.open(MenuState::Options, &mut next);
+
+        assert!(stack.back(&mut next));
+        assert_eq!(pending(&next), Some(MenuState::Loadout));
+        assert!(stack.back(&mut next));
+        assert_eq!(pending(&next), Some(MenuState::None));
+    }
+
+    #[test]
+    fn back_with_nothing_open_leaves_the_state_alone() {
+        let (mut stack, mut next) = (MenuStack::default(), NextState::default());
+        assert!(!stack.back(&mut next));
+        assert_eq!(pending(&next), None);
+    }
+
+    #[test]
+    fn close_all_drops_every_submenu() {
+        let (mut stack, mut next) = (MenuStack::default(), NextState::default());
+        stack.open(MenuState::HighScores, &mut next);
+        stack.open(MenuState::Unlocks, &mut next);
+
+        stack.close_all(&mut next);
+        assert_eq!(pending(&next), Some(MenuState::None));
+        assert!(!stack.back(&mut next));
+    }
+}
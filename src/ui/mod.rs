@@ -1,3 +1,31 @@
+mod accessibility;
+mod audio;
+mod compass;
+mod countdown;
+mod cursor;
+mod cutscene;
+mod difficulty;
+mod focus;
+mod high_scores;
+mod loadout;
 mod menu;
+mod perk_select;
+mod shop;
+mod theme;
+mod unlocks;
 
+pub use accessibility::*;
+pub use audio::*;
+pub use compass::*;
+pub use countdown::*;
+pub use cursor::*;
+pub use cutscene::*;
+pub use difficulty::*;
+pub use focus::*;
+pub use high_scores::*;
+pub use loadout::*;
 pub use menu::*;
+pub use perk_select::*;
+pub use shop::*;
+pub use theme::*;
+pub use unlocks::*;
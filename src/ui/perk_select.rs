@@ -0,0 +1,168 @@
+use super::{pause_virtual_time, FocusScope, GameState, TextRole, ThemedText, UiTheme};
+use crate::player::{
+    roll_perk_offer, Perk, PerkOffer, Player, PlayerHealth, PlayerPerks, Progression,
+};
+use crate::world::GameRng;
+use bevy::prelude::*;
+
+/// Overlay shown between waves after a level-up, offering perks to pick from.
+/// Gameplay stays paused underneath until every banked level-up is spent.
+pub struct PerkSelectPlugin;
+
+impl Plugin for PerkSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::PerkSelect),
+            (show_perk_select, pause_virtual_time),
+        )
+        .add_systems(OnExit(GameState::PerkSelect), cleanup_perk_select)
+        .add_systems(
+            Update,
+            handle_perk_buttons.run_if(in_state(GameState::PerkSelect)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct PerkSelectRoot;
+
+#[derive(Component)]
+struct PerkButton(Perk);
+
+fn show_perk_select(
+    mut commands: Commands,
+    offer: Res<PerkOffer>,
+    progression: Res<Progression>,
+    theme: Res<UiTheme>,
+) {
+    spawn_perk_select(&mut commands, &theme, &offer, &progression);
+}
+
+fn spawn_perk_select(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    offer: &PerkOffer,
+    progression: &Progression,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            PerkSelectRoot,
+            FocusScope::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("Wave {} cleared", offer.after_wave)),
+                theme.muted_text(TextRole::Body),
+            ));
+            parent.spawn((
+                Text::new(format!(
+                    "Level {} - choose a perk",
+                    progression.level - progression.pending_perks + 1
+                )),
+                theme.text(TextRole::Title),
+            ));
+
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for perk in offer.perks.iter() {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(220.0),
+                                height: Val::Px(120.0),
+                                flex_direction: FlexDirection::Column,
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                row_gap: Val::Px(8.0),
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            BackgroundColor(theme.buttons.normal),
+                            BorderColor::all(perk.color()),
+                            PerkButton(*perk),
+                        ))
+                        .with_children(|card| {
+                            card.spawn((
+                                Text::new(perk.name()),
+                                theme.font(TextRole::Body),
+                                ThemedText(TextRole::Body),
+                                TextColor(perk.color()),
+                            ));
+                            card.spawn((
+                                Text::new(perk.description()),
+                                theme.text(TextRole::Caption),
+                            ));
+                        });
+                    }
+                });
+        });
+}
+
+fn cleanup_perk_select(mut commands: Commands, roots: Query<Entity, With<PerkSelectRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn handle_perk_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &PerkButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    theme: Res<UiTheme>,
+    roots: Query<Entity, With<PerkSelectRoot>>,
+    mut perks: ResMut<PlayerPerks>,
+    mut progression: ResMut<Progression>,
+    mut offer: ResMut<PerkOffer>,
+    mut rng: ResMut<GameRng>,
+    mut health_q: Query<&mut PlayerHealth, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut mouse_button: ResMut<ButtonInput<MouseButton>>,
+) {
+    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *bg_color = theme.buttons.pressed.into();
+                // The click belongs to the button; nothing later this frame sees it
+                mouse_button.clear_just_pressed(MouseButton::Left);
+                let mut health = health_q.single_mut().ok();
+                perks.take(button.0, health.as_deref_mut());
+                progression.pending_perks = progression.pending_perks.saturating_sub(1);
+
+                if progression.pending_perks == 0 {
+                    next_state.set(GameState::Playing);
+                    return;
+                }
+
+                // Several level-ups in one wave: offer again straight away
+                offer.perks = roll_perk_offer(&mut **rng);
+                for root in roots.iter() {
+                    commands.entity(root).despawn();
+                }
+                spawn_perk_select(&mut commands, &theme, &offer, &progression);
+                return;
+            }
+            Interaction::Hovered => {
+                *bg_color = theme.buttons.hovered.into();
+            }
+            Interaction::None => {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+}
@@ -0,0 +1,424 @@
+use super::{FocusScope, GameMode, GameState, TextRole, ThemedText, UiTheme};
+use crate::combat::{
+    AmmoType, FlareStock, Weapon, WeaponInventory, WeaponType, BACKPACK_WEAPON_SLOTS,
+};
+use crate::enemies::WaveState;
+use crate::player::{
+    roll_perk_offer, PerkOffer, Player, PlayerArmor, PlayerHealth, PlayerPerks, Progression, Score,
+};
+use crate::world::{BalanceData, GameRng};
+use bevy::prelude::*;
+
+/// Shop open during the break after each survival wave: Tab brings up a list of
+/// items priced in Score points, one button each, picked like any menu's with the
+/// mouse, the arrow keys or a gamepad's d-pad. It closes for good the moment the next
+/// wave starts.
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Shop>()
+            .init_resource::<ShopDiscount>()
+            .add_systems(OnEnter(GameState::Playing), spawn_shop_ui)
+            .add_systems(OnExit(GameState::Playing), despawn_shop_ui)
+            .add_systems(
+                Update,
+                (toggle_shop, handle_shop_buttons, update_shop_ui)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShopItem {
+    /// Two magazines of standard rounds for every weapon carried
+    Ammo,
+    ArmorPlate,
+    /// Draws zombies off with light and noise, see FlarePlugin
+    Flare,
+    /// The first weapon the player doesn't own yet
+    Weapon,
+    /// Trade the most recent perk for a fresh pick
+    PerkReroll,
+    /// An extra weapon slot
+    Backpack,
+}
+
+impl ShopItem {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShopItem::Ammo => "Ammo refill",
+            ShopItem::ArmorPlate => "Armor plate",
+            ShopItem::Flare => "Flare",
+            ShopItem::Weapon => "New weapon",
+            ShopItem::PerkReroll => "Perk reroll",
+            ShopItem::Backpack => "Backpack",
+        }
+    }
+}
+
+/// Item, base price and extra per wave survived; later waves pay out more, so they
+/// cost more too
+const SHOP_PRICES: [(ShopItem, u32, u32); 6] = [
+    (ShopItem::Ammo, 40, 10),
+    (ShopItem::ArmorPlate, 60, 15),
+    (ShopItem::Flare, 50, 10),
+    (ShopItem::Weapon, 250, 50),
+    (ShopItem::PerkReroll, 150, 30),
+    (ShopItem::Backpack, 400, 50),
+];
+
+/// Weapons the shop sells, in the order it offers them
+const SHOP_WEAPONS: [WeaponType; 7] = [
+    WeaponType::Arc,
+    WeaponType::Railgun,
+    WeaponType::Marksman,
+    WeaponType::Shotgun,
+    WeaponType::Rifle,
+    WeaponType::Smg,
+    WeaponType::Pistol,
+];
+
+pub fn shop_price(item: ShopItem, wave: u32) -> u32 {
+    SHOP_PRICES
+        .iter()
+        .find(|(priced, ..)| *priced == item)
+        .map_or(0, |(_, base, per_wave)| base + per_wave * wave)
+}
+
+/// Share off every price in the break after one wave, awarded for an S grade
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct ShopDiscount {
+    pub wave: u32,
+    pub fraction: f32,
+}
+
+impl ShopDiscount {
+    /// `price` in the break after `wave`, rounded down in the player's favour
+    pub fn apply(&self, price: u32, wave: u32) -> u32 {
+        if wave == self.wave && self.fraction > 0.0 {
+            (price as f32 * (1.0 - self.fraction)).floor() as u32
+        } else {
+            price
+        }
+    }
+}
+
+/// Whether the shop panel is up
+#[derive(Resource, Default)]
+pub struct Shop {
+    pub open: bool,
+    /// Result of the last purchase attempt
+    message: String,
+}
+
+#[derive(Component)]
+struct ShopTimer;
+
+#[derive(Component)]
+struct ShopPanel;
+
+/// Buys the item at this index of SHOP_PRICES
+#[derive(Component)]
+struct ShopButton(usize);
+
+/// Name and price on a ShopButton
+#[derive(Component)]
+struct ShopRow(usize);
+
+#[derive(Component)]
+struct ShopMessage;
+
+/// Whether the shop can be opened (and Tab belongs to it) right now
+pub fn shop_available(mode: &GameMode, waves: &WaveState) -> bool {
+    mode.has_waves() && waves.shop_open()
+}
+
+fn spawn_shop_ui(mut commands: Commands, mut shop: ResMut<Shop>, theme: Res<UiTheme>) {
+    *shop = Shop::default();
+
+    commands.spawn((
+        Text::new(""),
+        theme.font(TextRole::Body),
+        ThemedText(TextRole::Body),
+        TextColor(theme.accent),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(80.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Visibility::Hidden,
+        ShopTimer,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(120.0),
+                left: Val::Percent(50.0),
+                margin: UiRect::left(Val::Px(-170.0)),
+                width: Val::Px(340.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            Visibility::Hidden,
+            ShopPanel,
+            FocusScope::over_play(),
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("SHOP  [Up/Down] choose  [Enter] buy  [Tab] close"),
+                theme.muted_text(TextRole::Caption),
+            ));
+            for index in 0..SHOP_PRICES.len() {
+                panel
+                    .spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(theme.buttons.normal),
+                        ShopButton(index),
+                    ))
+                    .with_children(|btn| {
+                        btn.spawn((Text::new(""), theme.text(TextRole::Body), ShopRow(index)));
+                    });
+            }
+            panel.spawn((
+                Text::new(""),
+                theme.font(TextRole::Caption),
+                ThemedText(TextRole::Caption),
+                TextColor(theme.accent),
+                ShopMessage,
+            ));
+        });
+}
+
+fn despawn_shop_ui(
+    mut commands: Commands,
+    ui: Query<Entity, Or<(With<ShopTimer>, With<ShopPanel>)>>,
+    mut shop: ResMut<Shop>,
+) {
+    for entity in ui.iter() {
+        commands.entity(entity).despawn();
+    }
+    shop.open = false;
+}
+
+fn toggle_shop(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mode: Res<GameMode>,
+    waves: Res<WaveState>,
+    mut shop: ResMut<Shop>,
+) {
+    if !shop_available(&mode, &waves) {
+        // The wave has started: shut the shop and keep it shut
+        shop.open = false;
+        return;
+    }
+
+    let toggle = keys.just_pressed(KeyCode::Tab)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Select));
+    if toggle {
+        shop.open = !shop.open;
+        shop.message.clear();
+    }
+}
+
+fn handle_shop_buttons(
+    mut interaction_query: Query<
+        (&Interaction, &ShopButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    theme: Res<UiTheme>,
+    mut shop: ResMut<Shop>,
+    waves: Res<WaveState>,
+    discount: Res<ShopDiscount>,
+    mut score: ResMut<Score>,
+    mut player_q: Query<
+        (
+            &mut WeaponInventory,
+            &mut PlayerArmor,
+            Option<&mut PlayerHealth>,
+        ),
+        With<Player>,
+    >,
+    mut flares: ResMut<FlareStock>,
+    mut perks: ResMut<PlayerPerks>,
+    mut progression: ResMut<Progression>,
+    mut offer: ResMut<PerkOffer>,
+    mut rng: ResMut<GameRng>,
+    balance: Res<BalanceData>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let mut bought_index = None;
+    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *bg_color = theme.buttons.pressed.into();
+                bought_index = Some(button.0);
+            }
+            Interaction::Hovered => {
+                *bg_color = theme.buttons.hovered.into();
+            }
+            Interaction::None => {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+    let Some(index) = bought_index.filter(|_| shop.open) else {
+        return;
+    };
+
+    let (item, ..) = SHOP_PRICES[index];
+    let price = discount.apply(shop_price(item, waves.wave), waves.wave);
+    if score.points < price {
+        shop.message = format!("Need {} more points", price - score.points);
+        return;
+    }
+    let Ok((mut inventory, mut armor, mut health)) = player_q.single_mut() else {
+        return;
+    };
+
+    let bought = match item {
+        ShopItem::Ammo => {
+            for weapon in inventory.weapons.iter_mut().flatten() {
+                *weapon.reserve_mut(AmmoType::Standard) += weapon.magazine_size * 2;
+            }
+            Ok("Ammo topped up".to_string())
+        }
+        ShopItem::ArmorPlate => {
+            if armor.current >= armor.max {
+                Err("Armor is already full")
+            } else {
+                armor.current = (armor.current + balance.pickups.armor_plate).min(armor.max);
+                Ok("Armor plate fitted".to_string())
+            }
+        }
+        ShopItem::Flare => {
+            flares.remaining += 1;
+            Ok(format!("Flares: {}", flares.remaining))
+        }
+        ShopItem::Weapon => {
+            let new_weapon = SHOP_WEAPONS.into_iter().find(|&weapon_type| {
+                !inventory
+                    .weapons
+                    .iter()
+                    .flatten()
+                    .any(|owned| owned.weapon_type == weapon_type)
+            });
+            match new_weapon {
+                None => Err("You already have every weapon"),
+                Some(weapon_type) => {
+                    let name = weapon_type.name();
+                    let weapon = Weapon::new(weapon_type, &balance);
+                    // With every slot full, the held weapon is traded in
+                    if let Err(weapon) = inventory.add(weapon) {
+                        inventory.replace_current(weapon);
+                    }
+                    Ok(format!("Bought the {}", name))
+                }
+            }
+        }
+        ShopItem::Backpack => {
+            if inventory.capacity() >= BACKPACK_WEAPON_SLOTS {
+                Err("You already have a backpack")
+            } else {
+                inventory.expand_to(BACKPACK_WEAPON_SLOTS);
+                Ok(format!("{} weapon slots", inventory.capacity()))
+            }
+        }
+        ShopItem::PerkReroll => match perks.drop_last(health.as_deref_mut()) {
+            None => Err("No perk to reroll yet"),
+            Some(perk) => {
+                progression.pending_perks += 1;
+                offer.after_wave = waves.wave;
+                offer.perks = roll_perk_offer(&mut **rng);
+                next_state.set(GameState::PerkSelect);
+                Ok(format!("Gave back {}", perk.name()))
+            }
+        },
+    };
+
+    match bought {
+        Ok(message) => {
+            score.points -= price;
+            shop.message = message;
+        }
+        Err(reason) => shop.message = reason.to_string(),
+    }
+}
+
+fn update_shop_ui(
+    shop: Res<Shop>,
+    mode: Res<GameMode>,
+    waves: Res<WaveState>,
+    discount: Res<ShopDiscount>,
+    score: Res<Score>,
+    mut timer_q: Query<(&mut Text, &mut Visibility), With<ShopTimer>>,
+    mut panel_q: Query<&mut Visibility, (With<ShopPanel>, Without<ShopTimer>)>,
+    mut rows: Query<(&ShopRow, &mut Text), Without<ShopTimer>>,
+    mut message_q: Query<&mut Text, (With<ShopMessage>, Without<ShopTimer>, Without<ShopRow>)>,
+) {
+    let available = shop_available(&mode, &waves);
+    for (mut text, mut visibility) in timer_q.iter_mut() {
+        *visibility = if available {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if available {
+            let sale = if discount.wave == waves.wave && discount.fraction > 0.0 {
+                format!("  -  {:.0}% OFF", discount.fraction * 100.0)
+            } else {
+                String::new()
+            };
+            let label = format!(
+                "SHOP OPEN  {:.0}s  -  {} pts{}  -  [Tab]",
+                waves.intermission.remaining_secs().ceil(),
+                score.points,
+                sale
+            );
+            if text.0 != label {
+                text.0 = label;
+            }
+        }
+    }
+
+    let open = available && shop.open;
+    for mut visibility in panel_q.iter_mut() {
+        *visibility = if open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !open {
+        return;
+    }
+
+    for (row, mut text) in rows.iter_mut() {
+        let (item, ..) = SHOP_PRICES[row.0];
+        let price = discount.apply(shop_price(item, waves.wave), waves.wave);
+        let label = format!("{}  -  {} pts", item.name(), price);
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+    for mut text in message_q.iter_mut() {
+        if text.0 != shop.message {
+            text.0 = shop.message.clone();
+        }
+    }
+}
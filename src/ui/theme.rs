@@ -0,0 +1,241 @@
+use bevy::asset::AssetLoadFailedEvent;
+use bevy::prelude::*;
+
+/// Fonts, text sizes and colors shared by the menus and the HUD. Screens build their
+/// text from UiTheme as they spawn. Text tagged ThemedText follows any later change to
+/// the fonts, sizes or text colors, and buttons showing a theme color follow the new one.
+pub struct UiThemePlugin;
+
+impl Plugin for UiThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiTheme>().add_systems(
+            Update,
+            (
+                fall_back_on_missing_fonts,
+                restyle_themed_text.run_if(resource_changed::<UiTheme>),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Under assets/; the built-in font stands in for either one that's missing
+const TITLE_FONT_PATH: &str = "fonts/title.ttf";
+const BODY_FONT_PATH: &str = "fonts/body.ttf";
+
+/// Which size and font a piece of text takes from the theme
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextRole {
+    /// Screen titles
+    Title,
+    /// Menu buttons and the HUD's headline numbers
+    Heading,
+    Body,
+    /// Labels and small print
+    Caption,
+}
+
+/// Button backgrounds as the pointer moves over and presses them
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ButtonColors {
+    pub normal: Color,
+    pub hovered: Color,
+    pub pressed: Color,
+    /// Ring around the button with keyboard or gamepad focus
+    pub focus: Color,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct UiTheme {
+    /// Titles only; everything else is set in the body font
+    pub title_font: Handle<Font>,
+    pub body_font: Handle<Font>,
+    pub title_size: f32,
+    pub heading_size: f32,
+    pub body_size: f32,
+    pub caption_size: f32,
+    pub text: Color,
+    /// Subtitles, labels and other secondary text
+    pub muted: Color,
+    /// Highlights such as the ammo count
+    pub accent: Color,
+    /// Full-screen backdrop behind menus
+    pub overlay: Color,
+    pub buttons: ButtonColors,
+}
+
+impl FromWorld for UiTheme {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            title_font: asset_server.load(TITLE_FONT_PATH),
+            body_font: asset_server.load(BODY_FONT_PATH),
+            title_size: 60.0,
+            heading_size: 30.0,
+            body_size: 24.0,
+            caption_size: 18.0,
+            text: Color::WHITE,
+            muted: Color::srgb(0.7, 0.7, 0.7),
+            accent: Color::srgb(1.0, 0.9, 0.3),
+            overlay: Color::srgba(0.0, 0.0, 0.0, 0.8),
+            buttons: ButtonColors {
+                normal: Color::srgb(0.15, 0.15, 0.15),
+                hovered: Color::srgb(0.25, 0.25, 0.25),
+                pressed: Color::srgb(0.35, 0.55, 0.35),
+                focus: Color::srgb(1.0, 0.9, 0.3),
+            },
+        }
+    }
+}
+
+impl UiTheme {
+    pub fn font(&self, role: TextRole) -> TextFont {
+        let (font, font_size) = match role {
+            TextRole::Title => (&self.title_font, self.title_size),
+            TextRole::Heading => (&self.body_font, self.heading_size),
+            TextRole::Body => (&self.body_font, self.body_size),
+            TextRole::Caption => (&self.body_font, self.caption_size),
+        };
+        TextFont {
+            font: font.clone(),
+            font_size,
+            ..default()
+        }
+    }
+
+    /// Font and size for `role` in the theme's text color, kept up to date with the
+    /// theme; pair with a TextColor of its own to override the color
+    pub fn text(&self, role: TextRole) -> (TextFont, TextColor, ThemedText) {
+        (self.font(role), TextColor(self.text), ThemedText(role))
+    }
+
+    /// Like `text`, in the muted color
+    pub fn muted_text(&self, role: TextRole) -> (TextFont, TextColor, ThemedText) {
+        (self.font(role), TextColor(self.muted), ThemedText(role))
+    }
+}
+
+/// Text whose font, size and, when it's in one of the theme's text colors, color
+/// follow the theme
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ThemedText(pub TextRole);
+
+/// A font that can't be loaded is swapped for the built-in one rather than leaving
+/// its text blank
+fn fall_back_on_missing_fonts(
+    mut failures: MessageReader<AssetLoadFailedEvent<Font>>,
+    mut theme: ResMut<UiTheme>,
+) {
+    // Through a plain reference, so both fonts can be borrowed at once
+    let theme = &mut *theme;
+    for failure in failures.read() {
+        warn!(
+            "Couldn't load font {}, using the built-in one: {}",
+            failure.path, failure.error
+        );
+        for font in [&mut theme.title_font, &mut theme.body_font] {
+            if font.id() == failure.id {
+                *font = Handle::default();
+            }
+        }
+    }
+}
+
+/// The theme colors last put on screen, to tell which text and buttons are showing them
+#[derive(Clone, Copy, PartialEq)]
+struct AppliedColors {
+    text: Color,
+    muted: Color,
+    accent: Color,
+    buttons: ButtonColors,
+}
+
+impl AppliedColors {
+    fn of(theme: &UiTheme) -> Self {
+        Self {
+            text: theme.text,
+            muted: theme.muted,
+            accent: theme.accent,
+            buttons: theme.buttons,
+        }
+    }
+}
+
+/// `current` moved from `old` to `new` if it's showing `old`, keeping its own alpha so
+/// faded text stays faded
+fn follow_color(current: Color, old: Color, new: Color) -> Option<Color> {
+    (current.with_alpha(1.0) == old.with_alpha(1.0)).then(|| new.with_alpha(current.alpha()))
+}
+
+fn restyle_themed_text(
+    theme: Res<UiTheme>,
+    mut applied: Local<Option<AppliedColors>>,
+    mut texts: Query<(&ThemedText, &mut TextFont, &mut TextColor)>,
+    mut buttons: Query<&mut BackgroundColor, With<Button>>,
+) {
+    let colors = AppliedColors::of(&theme);
+    let previous = applied.replace(colors);
+
+    for (themed, mut font, mut color) in texts.iter_mut() {
+        let styled = theme.font(themed.0);
+        font.font = styled.font;
+        font.font_size = styled.font_size;
+
+        let Some(old) = previous.filter(|old| *old != colors) else {
+            continue;
+        };
+        let followed = [
+            (old.text, colors.text),
+            (old.muted, colors.muted),
+            (old.accent, colors.accent),
+        ]
+        .into_iter()
+        .find_map(|(from, to)| follow_color(color.0, from, to));
+        if let Some(followed) = followed {
+            color.0 = followed;
+        }
+    }
+
+    let Some(old) = previous.filter(|old| old.buttons != colors.buttons) else {
+        return;
+    };
+    for mut background in buttons.iter_mut() {
+        let followed = [
+            (old.buttons.normal, colors.buttons.normal),
+            (old.buttons.hovered, colors.buttons.hovered),
+            (old.buttons.pressed, colors.buttons.pressed),
+        ]
+        .into_iter()
+        .find_map(|(from, to)| follow_color(background.0, from, to));
+        if let Some(followed) = followed {
+            background.0 = followed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::text::FontLoader;
+    use std::time::Duration;
+
+    #[test]
+    fn missing_fonts_fall_back_to_the_built_in_one() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_asset_loader::<FontLoader>()
+            .add_plugins(UiThemePlugin);
+
+        // Neither font ships under assets/, so both loads fail
+        for _ in 0..200 {
+            app.update();
+            let theme = app.world().resource::<UiTheme>();
+            if theme.title_font == Handle::default() && theme.body_font == Handle::default() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("the theme still holds the missing fonts");
+    }
+}
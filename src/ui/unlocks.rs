@@ -0,0 +1,548 @@
+use super::{
+    Difficulty, DifficultyModifiers, FocusScope, GameMode, GameState, MenuStack, MenuState,
+    RunStats, TextRole, ThemedText, UiTheme,
+};
+use crate::combat::WeaponType;
+use crate::enemies::WaveState;
+use crate::player::{PlayerSkin, PLAYER_SKINS};
+use crate::save::{load_versioned, save_versioned};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Meta-progression: kills, headshots and waves add up across survival runs in a file
+/// next to the game, and passing a threshold unlocks another starting weapon, a player
+/// skin or hardcore mode. Picks are made under "Unlocks" on the main menu and kept in
+/// the same file.
+pub struct UnlocksPlugin;
+
+impl Plugin for UnlocksPlugin {
+    fn build(&self, app: &mut App) {
+        let (stats, loadout) = load_unlocks();
+        app.insert_resource(stats)
+            .insert_resource(loadout)
+            .add_systems(OnEnter(GameState::GameOver), record_run)
+            .add_systems(Update, update_unlock_toasts)
+            .add_systems(
+                OnTransition {
+                    exited: GameState::MainMenu,
+                    entered: GameState::PrePlaying,
+                },
+                apply_hardcore,
+            )
+            .add_systems(OnEnter(MenuState::Unlocks), show_unlocks)
+            .add_systems(OnExit(MenuState::Unlocks), cleanup_unlocks)
+            .add_systems(
+                Update,
+                handle_unlock_buttons.run_if(in_state(MenuState::Unlocks)),
+            );
+    }
+}
+
+const UNLOCKS_PATH: &str = "unlocks.json";
+
+/// Bump whenever UnlocksFile changes shape; files from other versions are dropped and
+/// progress starts over
+const UNLOCKS_VERSION: u32 = 1;
+
+/// Seconds an unlock toast stays up, on the real clock since game over pauses the game
+const TOAST_SECONDS: f32 = 4.0;
+
+/// Weapon carried next to the pistol at the start of a run, in the order the Unlocks
+/// screen cycles them; the first is always available
+const STARTING_WEAPONS: [WeaponType; 4] = [
+    WeaponType::Smg,
+    WeaponType::Shotgun,
+    WeaponType::Rifle,
+    WeaponType::Marksman,
+];
+
+/// Totals over every survival run
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, Debug)]
+struct LifetimeStats {
+    kills: u32,
+    headshots: u32,
+    best_wave: u32,
+    runs: u32,
+}
+
+/// What the next run starts with, picked from whatever is unlocked
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+pub struct StartingLoadout {
+    /// Index into STARTING_WEAPONS
+    weapon: usize,
+    /// Index into PLAYER_SKINS
+    skin: usize,
+    hardcore: bool,
+}
+
+impl StartingLoadout {
+    pub fn weapon(&self) -> WeaponType {
+        STARTING_WEAPONS[self.weapon % STARTING_WEAPONS.len()]
+    }
+
+    pub fn skin(&self) -> &'static PlayerSkin {
+        &PLAYER_SKINS[self.skin % PLAYER_SKINS.len()]
+    }
+
+    /// Drop any pick that isn't unlocked (yet), e.g. from a hand-edited file
+    fn sanitized(self, stats: &LifetimeStats) -> Self {
+        Self {
+            weapon: if is_unlocked(Reward::StartingWeapon(self.weapon), stats) {
+                self.weapon
+            } else {
+                0
+            },
+            skin: if is_unlocked(Reward::Skin(self.skin), stats) {
+                self.skin
+            } else {
+                0
+            },
+            hardcore: self.hardcore && is_unlocked(Reward::Hardcore, stats),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Requirement {
+    Kills(u32),
+    Headshots(u32),
+    Wave(u32),
+}
+
+impl Requirement {
+    /// Lifetime progress towards the requirement, and the amount needed
+    fn progress(&self, stats: &LifetimeStats) -> (u32, u32) {
+        match *self {
+            Requirement::Kills(needed) => (stats.kills, needed),
+            Requirement::Headshots(needed) => (stats.headshots, needed),
+            Requirement::Wave(needed) => (stats.best_wave, needed),
+        }
+    }
+
+    fn is_met(&self, stats: &LifetimeStats) -> bool {
+        let (have, needed) = self.progress(stats);
+        have >= needed
+    }
+
+    fn describe(&self) -> String {
+        match *self {
+            Requirement::Kills(needed) => format!("Kill {} zombies", needed),
+            Requirement::Headshots(needed) => format!("Finish {} zombies with headshots", needed),
+            Requirement::Wave(needed) => format!("Reach wave {}", needed),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Reward {
+    /// Index into STARTING_WEAPONS
+    StartingWeapon(usize),
+    /// Index into PLAYER_SKINS
+    Skin(usize),
+    Hardcore,
+}
+
+struct Unlock {
+    name: &'static str,
+    requirement: Requirement,
+    reward: Reward,
+}
+
+/// Everything that can be unlocked, in the order the Unlocks screen lists it. Rewards
+/// with no row here are available from the start.
+const UNLOCKS: &[Unlock] = &[
+    Unlock {
+        name: "Shotgun start",
+        requirement: Requirement::Kills(250),
+        reward: Reward::StartingWeapon(1),
+    },
+    Unlock {
+        name: "Rifle start",
+        requirement: Requirement::Wave(8),
+        reward: Reward::StartingWeapon(2),
+    },
+    Unlock {
+        name: "Marksman start",
+        requirement: Requirement::Headshots(100),
+        reward: Reward::StartingWeapon(3),
+    },
+    Unlock {
+        name: "Crimson skin",
+        requirement: Requirement::Kills(500),
+        reward: Reward::Skin(1),
+    },
+    Unlock {
+        name: "Woodland skin",
+        requirement: Requirement::Wave(12),
+        reward: Reward::Skin(2),
+    },
+    Unlock {
+        name: "Gilded skin",
+        requirement: Requirement::Headshots(300),
+        reward: Reward::Skin(3),
+    },
+    Unlock {
+        name: "Hardcore mode",
+        requirement: Requirement::Wave(15),
+        reward: Reward::Hardcore,
+    },
+];
+
+fn is_unlocked(reward: Reward, stats: &LifetimeStats) -> bool {
+    UNLOCKS
+        .iter()
+        .filter(|unlock| unlock.reward == reward)
+        .all(|unlock| unlock.requirement.is_met(stats))
+}
+
+#[derive(Serialize, Deserialize)]
+struct UnlocksFile {
+    version: u32,
+    stats: LifetimeStats,
+    loadout: StartingLoadout,
+}
+
+#[derive(Component)]
+struct UnlockToast {
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct UnlocksRoot;
+
+#[derive(Component)]
+enum UnlocksButton {
+    Weapon,
+    Skin,
+    Hardcore,
+    Back,
+}
+
+/// A missing file is a first run; a corrupt or outdated one starts progress over
+fn load_unlocks() -> (LifetimeStats, StartingLoadout) {
+    match load_versioned::<UnlocksFile>(UNLOCKS_PATH, UNLOCKS_VERSION) {
+        Ok(Some(file)) => (file.stats, file.loadout.sanitized(&file.stats)),
+        Ok(None) => Default::default(),
+        Err(reason) => {
+            warn!("Unlocks file is {}; starting over", reason);
+            Default::default()
+        }
+    }
+}
+
+fn save_unlocks(stats: &LifetimeStats, loadout: &StartingLoadout) {
+    let file = UnlocksFile {
+        version: UNLOCKS_VERSION,
+        stats: *stats,
+        loadout: *loadout,
+    };
+    if let Err(e) = save_versioned(UNLOCKS_PATH, &file) {
+        warn!("Could not save unlocks: {}", e);
+    }
+}
+
+/// Add a finished wave-mode run to the lifetime totals and announce anything it unlocked
+fn record_run(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    run: Res<RunStats>,
+    waves: Res<WaveState>,
+    mut stats: ResMut<LifetimeStats>,
+    loadout: Res<StartingLoadout>,
+    theme: Res<UiTheme>,
+) {
+    if !mode.has_waves() {
+        return;
+    }
+    let before = *stats;
+    stats.kills += run.kills;
+    stats.headshots += run.headshots;
+    stats.best_wave = stats.best_wave.max(waves.wave);
+    stats.runs += 1;
+
+    let unlocked = UNLOCKS
+        .iter()
+        .filter(|unlock| !unlock.requirement.is_met(&before) && unlock.requirement.is_met(&stats));
+    for (index, unlock) in unlocked.enumerate() {
+        spawn_unlock_toast(&mut commands, &theme, unlock, index);
+    }
+    save_unlocks(&stats, &loadout);
+}
+
+/// Stacked down the top right corner, above the game over menu and name entry
+fn spawn_unlock_toast(commands: &mut Commands, theme: &UiTheme, unlock: &Unlock, index: usize) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0 + index as f32 * 80.0),
+                width: Val::Px(280.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.95)),
+            GlobalZIndex(20),
+            UnlockToast {
+                timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once),
+            },
+        ))
+        .with_children(|toast| {
+            toast.spawn((
+                Text::new("UNLOCKED"),
+                theme.font(TextRole::Caption),
+                ThemedText(TextRole::Caption),
+                TextColor(theme.accent),
+            ));
+            toast.spawn((Text::new(unlock.name), theme.text(TextRole::Body)));
+        });
+}
+
+fn update_unlock_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toasts: Query<(Entity, &mut UnlockToast)>,
+) {
+    for (entity, mut toast) in toasts.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// The Difficulty button only ever sets the plain modifiers, so hardcore goes on top
+/// as each run leaves the main menu
+fn apply_hardcore(
+    difficulty: Res<Difficulty>,
+    loadout: Res<StartingLoadout>,
+    mut modifiers: ResMut<DifficultyModifiers>,
+) {
+    *modifiers = if loadout.hardcore {
+        difficulty.modifiers().hardcore()
+    } else {
+        difficulty.modifiers()
+    };
+}
+
+fn show_unlocks(
+    mut commands: Commands,
+    stats: Res<LifetimeStats>,
+    loadout: Res<StartingLoadout>,
+    theme: Res<UiTheme>,
+) {
+    spawn_unlocks(&mut commands, &theme, &stats, &loadout);
+}
+
+fn spawn_unlocks(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    stats: &LifetimeStats,
+    loadout: &StartingLoadout,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+            UnlocksRoot,
+            FocusScope::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::new("Unlocks"), theme.text(TextRole::Title)));
+            parent.spawn((
+                Text::new(format!(
+                    "{} kills - {} headshots - best wave {} - {} runs",
+                    stats.kills, stats.headshots, stats.best_wave, stats.runs
+                )),
+                theme.muted_text(TextRole::Body),
+                Node {
+                    margin: UiRect::bottom(Val::Px(12.0)),
+                    ..default()
+                },
+            ));
+
+            let hardcore = if !is_unlocked(Reward::Hardcore, stats) {
+                "Locked"
+            } else if loadout.hardcore {
+                "On"
+            } else {
+                "Off"
+            };
+            spawn_unlocks_button(
+                parent,
+                theme,
+                &format!("Starting weapon: {}", loadout.weapon().name()),
+                UnlocksButton::Weapon,
+            );
+            spawn_unlocks_button(
+                parent,
+                theme,
+                &format!("Skin: {}", loadout.skin().name),
+                UnlocksButton::Skin,
+            );
+            spawn_unlocks_button(
+                parent,
+                theme,
+                &format!("Hardcore: {}", hardcore),
+                UnlocksButton::Hardcore,
+            );
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    margin: UiRect::vertical(Val::Px(12.0)),
+                    ..default()
+                })
+                .with_children(|list| {
+                    for unlock in UNLOCKS {
+                        spawn_unlock_row(list, theme, unlock, stats);
+                    }
+                });
+
+            spawn_unlocks_button(parent, theme, "Back", UnlocksButton::Back);
+        });
+}
+
+fn spawn_unlock_row(
+    parent: &mut ChildSpawnerCommands,
+    theme: &UiTheme,
+    unlock: &Unlock,
+    stats: &LifetimeStats,
+) {
+    let (have, needed) = unlock.requirement.progress(stats);
+    let (status, color) = if have >= needed {
+        ("Unlocked".to_string(), theme.accent)
+    } else {
+        (format!("{} / {}", have, needed), theme.muted)
+    };
+    let cells = [
+        (unlock.name.to_string(), 200.0),
+        (unlock.requirement.describe(), 340.0),
+        (status, 120.0),
+    ];
+    parent
+        .spawn(Node {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        })
+        .with_children(|row| {
+            for (cell, width) in cells {
+                row.spawn((
+                    Text::new(cell),
+                    theme.font(TextRole::Caption),
+                    ThemedText(TextRole::Caption),
+                    TextColor(color),
+                    Node {
+                        width: Val::Px(width),
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+fn spawn_unlocks_button(
+    parent: &mut ChildSpawnerCommands,
+    theme: &UiTheme,
+    label: &str,
+    button: UnlocksButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(300.0),
+                height: Val::Px(50.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(theme.buttons.normal),
+            button,
+        ))
+        .with_children(|btn| {
+            btn.spawn((Text::new(label), theme.text(TextRole::Body)));
+        });
+}
+
+/// Step from `current` to the next unlocked option, wrapping round to the first
+fn next_unlocked(current: usize, count: usize, unlocked: impl Fn(usize) -> bool) -> usize {
+    (1..=count)
+        .map(|step| (current + step) % count)
+        .find(|&index| unlocked(index))
+        .unwrap_or(0)
+}
+
+fn cleanup_unlocks(mut commands: Commands, roots: Query<Entity, With<UnlocksRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn handle_unlock_buttons(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &UnlocksButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    theme: Res<UiTheme>,
+    roots: Query<Entity, With<UnlocksRoot>>,
+    stats: Res<LifetimeStats>,
+    mut loadout: ResMut<StartingLoadout>,
+    mut menu_stack: ResMut<MenuStack>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+    for (interaction, button, mut bg_color) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *bg_color = theme.buttons.pressed.into();
+                match button {
+                    UnlocksButton::Weapon => {
+                        loadout.weapon =
+                            next_unlocked(loadout.weapon, STARTING_WEAPONS.len(), |index| {
+                                is_unlocked(Reward::StartingWeapon(index), &stats)
+                            });
+                    }
+                    UnlocksButton::Skin => {
+                        loadout.skin = next_unlocked(loadout.skin, PLAYER_SKINS.len(), |index| {
+                            is_unlocked(Reward::Skin(index), &stats)
+                        });
+                    }
+                    UnlocksButton::Hardcore => {
+                        if !is_unlocked(Reward::Hardcore, &stats) {
+                            continue;
+                        }
+                        loadout.hardcore = !loadout.hardcore;
+                    }
+                    UnlocksButton::Back => {
+                        menu_stack.back(&mut next_menu_state);
+                        continue;
+                    }
+                }
+                save_unlocks(&stats, &loadout);
+
+                // Rebuild so the buttons show the new picks
+                for root in roots.iter() {
+                    commands.entity(root).despawn();
+                }
+                spawn_unlocks(&mut commands, &theme, &stats, &loadout);
+            }
+            Interaction::Hovered => {
+                *bg_color = theme.buttons.hovered.into();
+            }
+            Interaction::None => {
+                *bg_color = theme.buttons.normal.into();
+            }
+        }
+    }
+}
@@ -0,0 +1,616 @@
+use crate::combat::{
+    PendingWeaponDrops, Weapon, WeaponInventory, WeaponPickup, WeaponType, AMMO_TYPES,
+};
+use crate::enemies::{Zombie, ZombieKind};
+use crate::player::{Ally, PlayerPerks};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoadFailedEvent, AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+/// Path under assets/ of the tuning file
+const BALANCE_PATH: &str = "balance.ron";
+/// Built-in copy, so the numbers are there from the first frame (and on the web,
+/// before the asset request comes back)
+const EMBEDDED_BALANCE: &str = include_str!("../../assets/balance.ron");
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BalanceData::embedded())
+            .init_asset::<BalanceFile>()
+            .register_asset_loader(BalanceLoader)
+            .add_systems(Startup, load_balance_file)
+            .add_systems(
+                Update,
+                (
+                    apply_balance_file,
+                    report_balance_errors,
+                    propagate_balance.run_if(resource_changed::<BalanceData>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+// =============================================================================
+// DATA
+// =============================================================================
+
+/// Tuning numbers from assets/balance.ron. Run with `--features hot_reload` and
+/// saving the file retunes the game in place: weapons and zombies already out
+/// there take the new values too.
+#[derive(Resource, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BalanceData {
+    pub weapons: WeaponTable,
+    pub zombies: ZombieTable,
+    pub waves: WaveBalance,
+    pub pickups: PickupBalance,
+    pub grades: GradeBalance,
+    pub director: DirectorBalance,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponTable {
+    pub pistol: WeaponBalance,
+    pub smg: WeaponBalance,
+    pub rifle: WeaponBalance,
+    pub shotgun: WeaponBalance,
+    pub marksman: WeaponBalance,
+    pub railgun: WeaponBalance,
+    pub arc: WeaponBalance,
+}
+
+/// The tunable half of a Weapon; fire mode, recoil and the rest stay in Weapon::new
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponBalance {
+    pub damage: f32,
+    /// Shots per second
+    pub fire_rate: f32,
+    pub pellets: u8,
+    /// Spread angle in radians
+    pub spread: f32,
+    pub magazine_size: u32,
+    /// Starting spare rounds of each AmmoType, indexed by AmmoType::index
+    pub reserves: [u32; AMMO_TYPES],
+    /// Seconds
+    pub reload_time: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ZombieTable {
+    pub walker: ZombieBalance,
+    pub runner: ZombieBalance,
+}
+
+/// A zombie kind's stats before difficulty (and boss) multipliers
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ZombieBalance {
+    pub health: f32,
+    pub speed: f32,
+    pub damage: f32,
+    /// How hard a bite throws the player's aim (see Flinch)
+    pub flinch: f32,
+    /// Seconds between bites
+    pub attack_cooldown: f32,
+}
+
+/// Zombies per survival wave, before the difficulty's spawn count
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WaveBalance {
+    pub base_size: u32,
+    /// Added for every wave after the first
+    pub size_per_wave: u32,
+    pub max_size: u32,
+}
+
+impl WaveBalance {
+    pub fn size(&self, wave: u32) -> u32 {
+        self.size_per_wave
+            .saturating_mul(wave.saturating_sub(1))
+            .saturating_add(self.base_size)
+            .min(self.max_size)
+    }
+}
+
+/// How a cleared wave is graded (see WaveGrade) and what the top grades pay out
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GradeBalance {
+    /// Points out of 100 needed for S, A and B; anything less is a C
+    pub s: u32,
+    pub a: u32,
+    pub b: u32,
+    /// Share of the points each part of the wave is worth, relative to the others
+    pub accuracy_weight: f32,
+    pub damage_weight: f32,
+    pub time_weight: f32,
+    pub headshot_weight: f32,
+    /// Health lost over a wave that scores nothing for damage taken
+    pub damage_allowance: f32,
+    /// Clear time per zombie that still scores full marks for time
+    pub par_seconds_per_zombie: f32,
+    /// Score points for clearing a wave on S or A
+    pub s_bonus: u32,
+    pub a_bonus: u32,
+    /// Share off every shop price in the break after an S wave
+    pub s_discount: f32,
+}
+
+/// Adaptive difficulty (see Director): how often it reads the player's recent play and
+/// how far it may move spawn pacing either way. Zombie and weapon stats are never touched.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DirectorBalance {
+    /// Seconds of wave play between pressure updates
+    pub update_interval: f32,
+    /// Updates the damage rate is averaged over
+    pub history: usize,
+    /// Health lost per minute that reads as full pressure
+    pub damage_per_minute: f32,
+    /// Health fraction at or under which the player counts as nearly dead
+    pub near_death_health: f32,
+    /// Wave clear time per kill that reads as neither struggling nor cruising
+    pub par_seconds_per_kill: f32,
+    /// Pressure added for every death this run (a retried checkpoint)
+    pub death_pressure: f32,
+    /// Most the pressure moves in one update
+    pub max_step: f32,
+    /// Portal interval multipliers at no pressure and at full pressure
+    pub min_spawn_interval: f32,
+    pub max_spawn_interval: f32,
+    /// Share of portal zombies let out as runners at no pressure; none from halfway up
+    pub max_runner_share: f32,
+}
+
+/// What one pickup is worth
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PickupBalance {
+    /// Magazines of its type an ammo box gives each weapon that takes it
+    pub ammo_box_magazines: u32,
+    /// Armor restored by one plate, picked up or bought
+    pub armor_plate: f32,
+    /// Condition a cleaning kit gives back to every carried weapon
+    pub repair_kit: f32,
+}
+
+impl BalanceData {
+    pub fn weapon(&self, weapon_type: WeaponType) -> &WeaponBalance {
+        match weapon_type {
+            WeaponType::Pistol => &self.weapons.pistol,
+            WeaponType::Smg => &self.weapons.smg,
+            WeaponType::Rifle => &self.weapons.rifle,
+            WeaponType::Shotgun => &self.weapons.shotgun,
+            WeaponType::Marksman => &self.weapons.marksman,
+            WeaponType::Railgun => &self.weapons.railgun,
+            WeaponType::Arc => &self.weapons.arc,
+        }
+    }
+
+    pub fn zombie(&self, kind: ZombieKind) -> &ZombieBalance {
+        match kind {
+            ZombieKind::Walker => &self.zombies.walker,
+            ZombieKind::Runner => &self.zombies.runner,
+        }
+    }
+
+    /// The copy of assets/balance.ron built into the game
+    pub fn embedded() -> Self {
+        Self::parse(EMBEDDED_BALANCE.as_bytes())
+            .unwrap_or_else(|err| panic!("assets/{}: {}", BALANCE_PATH, err))
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, BalanceError> {
+        let data: Self = ron::de::from_bytes(bytes).map_err(BalanceError::Parse)?;
+        data.validate().map_err(BalanceError::Invalid)?;
+        Ok(data)
+    }
+
+    /// Catches values that parse but would break the game, naming the field
+    fn validate(&self) -> Result<(), String> {
+        let weapons = [
+            ("pistol", &self.weapons.pistol),
+            ("smg", &self.weapons.smg),
+            ("rifle", &self.weapons.rifle),
+            ("shotgun", &self.weapons.shotgun),
+            ("marksman", &self.weapons.marksman),
+            ("railgun", &self.weapons.railgun),
+            ("arc", &self.weapons.arc),
+        ];
+        for (name, weapon) in weapons {
+            let field = |field: &str| format!("weapons.{}.{}", name, field);
+            not_negative(&field("damage"), weapon.damage)?;
+            positive(&field("fire_rate"), weapon.fire_rate)?;
+            positive(&field("pellets"), weapon.pellets as f32)?;
+            not_negative(&field("spread"), weapon.spread)?;
+            positive(&field("magazine_size"), weapon.magazine_size as f32)?;
+            positive(&field("reload_time"), weapon.reload_time)?;
+        }
+
+        for (name, zombie) in [
+            ("walker", &self.zombies.walker),
+            ("runner", &self.zombies.runner),
+        ] {
+            let field = |field: &str| format!("zombies.{}.{}", name, field);
+            positive(&field("health"), zombie.health)?;
+            positive(&field("speed"), zombie.speed)?;
+            not_negative(&field("damage"), zombie.damage)?;
+            not_negative(&field("flinch"), zombie.flinch)?;
+            positive(&field("attack_cooldown"), zombie.attack_cooldown)?;
+        }
+
+        positive("waves.base_size", self.waves.base_size as f32)?;
+        if self.waves.max_size < self.waves.base_size {
+            return Err(format!(
+                "waves.max_size ({}) is below waves.base_size ({})",
+                self.waves.max_size, self.waves.base_size
+            ));
+        }
+
+        let grades = &self.grades;
+        if !(grades.s >= grades.a && grades.a >= grades.b && grades.s <= 100) {
+            return Err(format!(
+                "grades thresholds must run s >= a >= b with s at most 100, got {} / {} / {}",
+                grades.s, grades.a, grades.b
+            ));
+        }
+        not_negative("grades.accuracy_weight", grades.accuracy_weight)?;
+        not_negative("grades.damage_weight", grades.damage_weight)?;
+        not_negative("grades.time_weight", grades.time_weight)?;
+        not_negative("grades.headshot_weight", grades.headshot_weight)?;
+        positive(
+            "the sum of the grades weights",
+            grades.accuracy_weight
+                + grades.damage_weight
+                + grades.time_weight
+                + grades.headshot_weight,
+        )?;
+        positive("grades.damage_allowance", grades.damage_allowance)?;
+        positive(
+            "grades.par_seconds_per_zombie",
+            grades.par_seconds_per_zombie,
+        )?;
+        not_negative("grades.s_discount", grades.s_discount)?;
+        if grades.s_discount > 1.0 {
+            return Err(format!(
+                "grades.s_discount must be at most 1.0, got {}",
+                grades.s_discount
+            ));
+        }
+
+        let director = &self.director;
+        positive("director.update_interval", director.update_interval)?;
+        positive("director.history", director.history as f32)?;
+        positive("director.damage_per_minute", director.damage_per_minute)?;
+        not_negative("director.near_death_health", director.near_death_health)?;
+        positive(
+            "director.par_seconds_per_kill",
+            director.par_seconds_per_kill,
+        )?;
+        not_negative("director.death_pressure", director.death_pressure)?;
+        positive("director.max_step", director.max_step)?;
+        positive("director.min_spawn_interval", director.min_spawn_interval)?;
+        if !(director.min_spawn_interval <= 1.0 && director.max_spawn_interval >= 1.0) {
+            return Err(format!(
+                "director spawn interval bounds must take in 1.0, got {} to {}",
+                director.min_spawn_interval, director.max_spawn_interval
+            ));
+        }
+        not_negative("director.max_runner_share", director.max_runner_share)?;
+        if director.max_runner_share > 1.0 {
+            return Err(format!(
+                "director.max_runner_share must be at most 1.0, got {}",
+                director.max_runner_share
+            ));
+        }
+
+        positive(
+            "pickups.ammo_box_magazines",
+            self.pickups.ammo_box_magazines as f32,
+        )?;
+        positive("pickups.armor_plate", self.pickups.armor_plate)?;
+        positive("pickups.repair_kit", self.pickups.repair_kit)?;
+        if self.pickups.repair_kit > 1.0 {
+            return Err(format!(
+                "pickups.repair_kit must be at most 1.0, got {}",
+                self.pickups.repair_kit
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn positive(field: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} must be above zero, got {}", field, value))
+    }
+}
+
+fn not_negative(field: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} can't be negative, got {}", field, value))
+    }
+}
+
+// =============================================================================
+// LOADING
+// =============================================================================
+
+/// balance.ron as loaded by the asset server, which watches it for edits
+#[derive(Asset, TypePath)]
+struct BalanceFile(BalanceData);
+
+#[derive(Resource)]
+struct BalanceHandle(Handle<BalanceFile>);
+
+#[derive(Debug)]
+enum BalanceError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BalanceError::Io(err) => write!(f, "couldn't read the file: {}", err),
+            BalanceError::Parse(err) => write!(f, "not valid balance data: {}", err),
+            BalanceError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for BalanceError {}
+
+impl From<std::io::Error> for BalanceError {
+    fn from(err: std::io::Error) -> Self {
+        BalanceError::Io(err)
+    }
+}
+
+#[derive(TypePath)]
+struct BalanceLoader;
+
+impl AssetLoader for BalanceLoader {
+    type Asset = BalanceFile;
+    type Settings = ();
+    type Error = BalanceError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<BalanceFile, BalanceError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        BalanceData::parse(&bytes).map(BalanceFile)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["balance.ron"]
+    }
+}
+
+fn load_balance_file(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(BalanceHandle(asset_server.load(BALANCE_PATH)));
+}
+
+/// Copies the file into BalanceData when it first loads and on every good save
+fn apply_balance_file(
+    mut events: MessageReader<AssetEvent<BalanceFile>>,
+    handle: Option<Res<BalanceHandle>>,
+    files: Res<Assets<BalanceFile>>,
+    mut balance: ResMut<BalanceData>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+        let Some(file) = files.get(&handle.0) else {
+            continue;
+        };
+        if file.0 != *balance {
+            info!("Balance updated from assets/{}", BALANCE_PATH);
+            *balance = file.0.clone();
+        }
+    }
+}
+
+/// A bad save never reaches BalanceData, so the game carries on with the last good values
+fn report_balance_errors(mut failures: MessageReader<AssetLoadFailedEvent<BalanceFile>>) {
+    for failure in failures.read() {
+        error!(
+            "Keeping the previous balance; assets/{} has a problem: {}",
+            BALANCE_PATH, failure.error
+        );
+    }
+}
+
+// =============================================================================
+// PROPAGATION
+// =============================================================================
+
+/// Pushes changed numbers into weapons and zombies that already exist. Zombies are
+/// scaled by how far each stat moved, so difficulty and boss multipliers hold and a
+/// wounded zombie stays just as wounded.
+fn propagate_balance(
+    balance: Res<BalanceData>,
+    mut previous: Local<Option<BalanceData>>,
+    mut inventories: Query<&mut WeaponInventory>,
+    mut allies: Query<&mut Ally>,
+    mut pickups: Query<&mut WeaponPickup>,
+    mut drops: ResMut<PendingWeaponDrops>,
+    mut zombies: Query<&mut Zombie>,
+    perks: Res<PlayerPerks>,
+) {
+    // The first run is the resource being added; nothing to retune yet
+    let Some(old) = previous.replace((*balance).clone()) else {
+        return;
+    };
+
+    let retune =
+        |weapon: &mut Weapon| weapon.apply_balance(balance.weapon(weapon.weapon_type), &perks);
+    for mut inventory in inventories.iter_mut() {
+        inventory.weapons.iter_mut().flatten().for_each(retune);
+    }
+    for mut ally in allies.iter_mut() {
+        retune(&mut ally.weapon);
+    }
+    for mut pickup in pickups.iter_mut() {
+        retune(&mut pickup.weapon);
+    }
+    drops.0.iter_mut().for_each(retune);
+
+    for mut zombie in zombies.iter_mut() {
+        let (old, new) = (old.zombie(zombie.kind), balance.zombie(zombie.kind));
+        let health = new.health / old.health;
+        zombie.max_health *= health;
+        zombie.health *= health;
+        zombie.speed = rescale(zombie.speed, old.speed, new.speed);
+        zombie.damage = rescale(zombie.damage, old.damage, new.damage);
+        zombie.flinch = rescale(zombie.flinch, old.flinch, new.flinch);
+        let cooldown = rescale(
+            zombie.attack_cooldown.duration().as_secs_f32(),
+            old.attack_cooldown,
+            new.attack_cooldown,
+        );
+        zombie
+            .attack_cooldown
+            .set_duration(Duration::from_secs_f32(cooldown));
+    }
+}
+
+/// `value` moved by the same factor as `old` to `new`; a stat that was zero just takes `new`
+fn rescale(value: f32, old: f32, new: f32) -> f32 {
+    if old > 0.0 {
+        value * new / old
+    } else {
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::LoadState;
+    use std::fs;
+
+    /// The built-in file with the pistol's magazine set to `size`
+    fn with_pistol_magazine(size: &str) -> String {
+        EMBEDDED_BALANCE.replacen(
+            "magazine_size: 12,",
+            &format!("magazine_size: {},", size),
+            1,
+        )
+    }
+
+    #[test]
+    fn the_built_in_file_checks_out() {
+        assert_eq!(BalanceData::embedded().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_names_the_bad_field() {
+        let broken = |edit: fn(&mut BalanceData)| {
+            let mut balance = BalanceData::embedded();
+            edit(&mut balance);
+            balance.validate().unwrap_err()
+        };
+
+        let err = broken(|balance| balance.weapons.pistol.magazine_size = 0);
+        assert!(err.contains("weapons.pistol.magazine_size"), "{}", err);
+        let err = broken(|balance| balance.weapons.shotgun.damage = -1.0);
+        assert!(err.contains("weapons.shotgun.damage"), "{}", err);
+        let err = broken(|balance| balance.zombies.runner.speed = f32::NAN);
+        assert!(err.contains("zombies.runner.speed"), "{}", err);
+        let err = broken(|balance| balance.waves.max_size = balance.waves.base_size - 1);
+        assert!(err.contains("waves.max_size"), "{}", err);
+        let err = broken(|balance| balance.grades.b = balance.grades.a + 1);
+        assert!(err.contains("grades thresholds"), "{}", err);
+        let err = broken(|balance| balance.pickups.repair_kit = 1.5);
+        assert!(err.contains("pickups.repair_kit"), "{}", err);
+    }
+
+    #[test]
+    fn parse_rejects_what_validate_does() {
+        let err = BalanceData::parse(with_pistol_magazine("0").as_bytes()).unwrap_err();
+        assert!(matches!(err, BalanceError::Invalid(_)), "{}", err);
+        let err = BalanceData::parse(b"(weapons: oops").unwrap_err();
+        assert!(matches!(err, BalanceError::Parse(_)), "{}", err);
+    }
+
+    /// Starts on the built-in numbers, then loads `contents` as the balance file and
+    /// runs until the load has been taken or turned down
+    fn load(name: &str, contents: &str) -> App {
+        let dir = std::env::temp_dir().join(format!("balance-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(BALANCE_PATH), contents).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().into_owned(),
+                ..default()
+            },
+            BalancePlugin,
+        ))
+        .init_resource::<PendingWeaponDrops>()
+        .init_resource::<PlayerPerks>();
+        for _ in 0..200 {
+            app.update();
+            let handle = &app.world().resource::<BalanceHandle>().0;
+            match app.world().resource::<AssetServer>().load_state(handle) {
+                LoadState::Loaded | LoadState::Failed(_) => {
+                    app.update();
+                    return app;
+                }
+                _ => std::thread::sleep(Duration::from_millis(5)),
+            }
+        }
+        panic!("{} never finished loading", BALANCE_PATH);
+    }
+
+    #[test]
+    fn a_good_file_replaces_the_table() {
+        let app = load("good", &with_pistol_magazine("20"));
+        let balance = app.world().resource::<BalanceData>();
+        assert_eq!(balance.weapons.pistol.magazine_size, 20);
+    }
+
+    #[test]
+    fn a_file_that_fails_to_parse_keeps_the_previous_table() {
+        let app = load("unparsable", "(weapons: oops");
+        assert_eq!(
+            *app.world().resource::<BalanceData>(),
+            BalanceData::embedded()
+        );
+    }
+
+    #[test]
+    fn a_file_that_fails_to_validate_keeps_the_previous_table() {
+        let app = load("invalid", &with_pistol_magazine("0"));
+        assert_eq!(
+            *app.world().resource::<BalanceData>(),
+            BalanceData::embedded()
+        );
+    }
+}
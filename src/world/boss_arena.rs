@@ -0,0 +1,535 @@
+use super::{BalanceData, NavGrid, Obstacle, PhysicsLayer, SurfaceMaterial};
+use crate::combat::{HitZone, Shootable};
+use crate::enemies::{
+    spawn_zombie, Shielded, Staggered, Zombie, ZombieAssets, ZombieAttacked, ZombieKind,
+    ZombieSystems,
+};
+use crate::player::{CameraShake, Player, PlayerActions};
+use crate::ui::{
+    CompassObjective, CutsceneAction, CutsceneCue, CutsceneEvent, CutsceneStep,
+    DifficultyModifiers, GameState, Subtitle,
+};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use std::f32::consts::TAU;
+
+/// Sealed room in the south-east corner. Breaching its door with E plays a slow-motion
+/// push-in as the door swings open and the boss steps out.
+///
+/// The boss is plated front and sides, with a weak point on its back. Each slam leaves
+/// it reeling for a moment with the weak point swung round to the front.
+pub struct BossArenaPlugin;
+
+impl Plugin for BossArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            (setup_boss_arena_assets, spawn_boss_room, seal_boss_room).chain(),
+        )
+        .add_systems(
+            Update,
+            breach_boss_door.run_if(in_state(GameState::Playing)),
+        )
+        // Cues can arrive after leaving Playing, when a cutscene is cut short
+        .add_systems(Update, handle_boss_arena_cues)
+        .add_systems(
+            FixedUpdate,
+            recover_from_slams
+                .after(ZombieSystems::Attacks)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            show_boss_exposure.run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), hide_boss_door_prompt)
+        .add_systems(
+            OnTransition {
+                exited: GameState::MainMenu,
+                entered: GameState::PrePlaying,
+            },
+            seal_boss_room,
+        )
+        .add_systems(
+            OnTransition {
+                exited: GameState::GameOver,
+                entered: GameState::Playing,
+            },
+            seal_boss_room,
+        );
+    }
+}
+
+/// Middle of the doorway in the room's north wall
+const DOOR_POSITION: Vec3 = Vec3::new(45.0, 0.0, 41.0);
+const DOOR_WIDTH: f32 = 1.9;
+const DOOR_HEIGHT: f32 = 2.5;
+const DOOR_THICKNESS: f32 = 0.2;
+const DOOR_REACH: f32 = 2.5;
+/// Spin given to the door as it's kicked in, swinging it into the room
+const DOOR_KICK: f32 = 3.0;
+/// Room floor, clear of the cells its walls and the perimeter walls cover
+const ROOM_CENTER: Vec3 = Vec3::new(45.0, 0.0, 45.0);
+const ROOM_HALF_EXTENTS: Vec3 = Vec3::new(2.9, 0.0, 2.9);
+
+const BOSS_SPAWN: Vec3 = Vec3::new(45.0, 1.6, 46.0);
+const BOSS_SCALE: f32 = 1.6;
+const BOSS_HEALTH_SCALE: f32 = 15.0;
+const BOSS_DAMAGE_SCALE: f32 = 2.5;
+const BOSS_SPEED_SCALE: f32 = 0.75;
+/// Plated enough that hollow-points barely scratch it; AP is the answer
+const BOSS_ARMOR: f32 = 0.5;
+/// Share of a hit that gets through the armor plates
+const PLATE_MULTIPLIER: f32 = 0.1;
+const WEAK_POINT_MULTIPLIER: f32 = 3.0;
+/// Seconds the boss reels after a slam, weak point to the front
+const SLAM_RECOVERY_TIME: f32 = 2.0;
+/// Weak point positions on the unscaled body; the boss faces -Z
+const WEAK_POINT_BACK: Vec3 = Vec3::new(0.0, 0.35, 0.45);
+const WEAK_POINT_FRONT: Vec3 = Vec3::new(0.0, 0.35, -0.55);
+/// Pulses per second of the weak point's glow while it's exposed
+const WEAK_POINT_PULSE_RATE: f32 = 3.0;
+const WEAK_POINT_GLOW: LinearRgba = LinearRgba::rgb(2.0, 0.5, 0.1);
+const PLATE_DIM: Color = Color::srgb(0.2, 0.2, 0.22);
+const PLATE_BRIGHT: Color = Color::srgb(0.55, 0.55, 0.6);
+
+/// The boss zombie; see HitZone for how its plates and weak point take hits
+#[derive(Component)]
+pub struct Boss {
+    weak_point: Entity,
+    /// Seconds left reeling from the last slam, weak point exposed
+    recovering: f32,
+}
+
+#[derive(Component)]
+struct BossWeakPoint;
+
+#[derive(Component)]
+struct BossDoor {
+    breached: bool,
+}
+
+/// Fixed anchor the breached door swings on
+#[derive(Component)]
+struct BossDoorHinge;
+
+#[derive(Component)]
+struct BossDoorPrompt;
+
+#[derive(Resource)]
+struct BossArenaAssets {
+    door_mesh: Handle<Mesh>,
+    door_material: Handle<StandardMaterial>,
+    front_plate_mesh: Handle<Mesh>,
+    side_plate_mesh: Handle<Mesh>,
+    /// Shared by every plate; dimmed while they're shielding the boss
+    plate_material: Handle<StandardMaterial>,
+    weak_point_mesh: Handle<Mesh>,
+    /// Pulses while the weak point is exposed
+    weak_point_material: Handle<StandardMaterial>,
+}
+
+fn setup_boss_arena_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BossArenaAssets {
+        door_mesh: meshes.add(Cuboid::new(DOOR_WIDTH, DOOR_HEIGHT, DOOR_THICKNESS)),
+        door_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(0.35, 0.15, 0.1),
+            metallic: 0.6,
+            ..default()
+        }),
+        front_plate_mesh: meshes.add(Cuboid::new(0.7, 1.0, 0.08)),
+        side_plate_mesh: meshes.add(Cuboid::new(0.08, 1.0, 0.6)),
+        plate_material: materials.add(StandardMaterial {
+            base_color: PLATE_DIM,
+            metallic: 0.9,
+            perceptual_roughness: 0.5,
+            ..default()
+        }),
+        weak_point_mesh: meshes.add(Sphere::new(0.15)),
+        weak_point_material: materials.add(StandardMaterial {
+            base_color: Color::srgb(1.0, 0.3, 0.1),
+            emissive: WEAK_POINT_GLOW,
+            ..default()
+        }),
+    });
+}
+
+/// Two walls close off the corner; the perimeter walls make up the rest
+fn spawn_boss_room(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut nav_grid: ResMut<NavGrid>,
+) {
+    let wall_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.3, 0.25, 0.25),
+        ..default()
+    });
+    let walls = [
+        // North wall, either side of the doorway (x 44.05 to 45.95)
+        (Vec3::new(42.4, 1.5, 41.0), Vec3::new(3.3, 3.0, 0.5)),
+        (Vec3::new(47.35, 1.5, 41.0), Vec3::new(2.8, 3.0, 0.5)),
+        // West wall, up to the perimeter
+        (Vec3::new(41.0, 1.5, 45.0), Vec3::new(0.5, 3.0, 7.5)),
+    ];
+
+    for (pos, size) in walls {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_translation(pos),
+            Obstacle::indestructible(),
+            RigidBody::Fixed,
+            Collider::cuboid(size.x / 2.0, size.y / 2.0, size.z / 2.0),
+            PhysicsLayer::World.groups(),
+        ));
+        nav_grid.mark_obstacle_world(pos, size / 2.0);
+    }
+
+    commands.spawn((
+        Text::new("[E] Breach the door"),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(30.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-90.0)),
+            ..default()
+        },
+        Visibility::Hidden,
+        BossDoorPrompt,
+    ));
+}
+
+/// Put a closed door back for a new run. The room stays off the NavGrid while it's
+/// shut, so neither spawns nor paths end up inside.
+fn seal_boss_room(
+    mut commands: Commands,
+    assets: Res<BossArenaAssets>,
+    mut nav_grid: ResMut<NavGrid>,
+    old_doors: Query<Entity, Or<(With<BossDoor>, With<BossDoorHinge>)>>,
+) {
+    for entity in old_doors.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    commands.spawn((
+        Mesh3d(assets.door_mesh.clone()),
+        MeshMaterial3d(assets.door_material.clone()),
+        Transform::from_translation(DOOR_POSITION + Vec3::Y * (DOOR_HEIGHT / 2.0 + 0.05)),
+        BossDoor { breached: false },
+        CompassObjective,
+        RigidBody::Fixed,
+        Collider::cuboid(DOOR_WIDTH / 2.0, DOOR_HEIGHT / 2.0, DOOR_THICKNESS / 2.0),
+        PhysicsLayer::World.groups(),
+        SurfaceMaterial::Metal,
+    ));
+    nav_grid.mark_obstacle_world(ROOM_CENTER, ROOM_HALF_EXTENTS);
+    nav_grid.mark_obstacle_world(DOOR_POSITION, door_half_extents());
+}
+
+/// Doorway cells, kept off the neighbouring wall segments
+fn door_half_extents() -> Vec3 {
+    Vec3::new(DOOR_WIDTH / 2.0 - 0.05, 0.0, 0.25)
+}
+
+fn breach_boss_door(
+    actions: Res<PlayerActions>,
+    player_q: Query<&Transform, With<Player>>,
+    mut doors: Query<&mut BossDoor>,
+    mut prompt_q: Query<&mut Visibility, With<BossDoorPrompt>>,
+    mut cutscenes: MessageWriter<CutsceneEvent>,
+) {
+    let Ok(player_transform) = player_q.single() else {
+        return;
+    };
+    let Ok(mut door) = doors.single_mut() else {
+        return;
+    };
+
+    let in_reach = !door.breached
+        && player_transform
+            .translation
+            .with_y(0.0)
+            .distance(DOOR_POSITION)
+            <= DOOR_REACH;
+
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = if in_reach {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if !in_reach || !actions.interact {
+        return;
+    }
+    door.breached = true;
+
+    // Low in front of the doorway, pushing in as the door gives way
+    let eye = DOOR_POSITION + Vec3::new(0.0, 1.6, -3.5);
+    let target = DOOR_POSITION + Vec3::new(0.0, 1.4, 4.0);
+    cutscenes.write(CutsceneEvent {
+        steps: vec![
+            CutsceneStep {
+                at: 0.0,
+                action: CutsceneAction::TimeScale(0.3),
+            },
+            CutsceneStep {
+                at: 0.0,
+                action: CutsceneAction::Camera {
+                    eye,
+                    target,
+                    duration: 2.0,
+                },
+            },
+            CutsceneStep {
+                at: 0.0,
+                action: CutsceneAction::Cue(CutsceneCue::BreachBossDoor),
+            },
+            CutsceneStep {
+                at: 1.2,
+                action: CutsceneAction::Cue(CutsceneCue::SpawnBoss),
+            },
+            CutsceneStep {
+                at: 1.4,
+                action: CutsceneAction::Banner("THE ABOMINATION".to_string()),
+            },
+            CutsceneStep {
+                at: 2.0,
+                action: CutsceneAction::TimeScale(1.0),
+            },
+        ],
+        duration: 3.0,
+    });
+}
+
+fn handle_boss_arena_cues(
+    mut commands: Commands,
+    mut cues: MessageReader<CutsceneCue>,
+    zombie_assets: Res<ZombieAssets>,
+    assets: Res<BossArenaAssets>,
+    difficulty: Res<DifficultyModifiers>,
+    balance: Res<BalanceData>,
+    mut nav_grid: ResMut<NavGrid>,
+    doors: Query<(Entity, &Transform), With<BossDoor>>,
+    mut shake_events: MessageWriter<CameraShake>,
+    mut subtitles: MessageWriter<Subtitle>,
+) {
+    for cue in cues.read() {
+        match cue {
+            CutsceneCue::BreachBossDoor => {
+                for (entity, transform) in doors.iter() {
+                    // Hinged on the doorway's west edge
+                    let hinge_offset = Vec3::X * (DOOR_WIDTH / 2.0);
+                    let hinge = commands
+                        .spawn((
+                            Transform::from_translation(transform.translation - hinge_offset),
+                            RigidBody::Fixed,
+                            BossDoorHinge,
+                        ))
+                        .id();
+                    let joint = RevoluteJointBuilder::new(Vec3::Y)
+                        .local_anchor2(-hinge_offset)
+                        // Inward only, a little past square
+                        .limits([-1.7, 0.0]);
+                    // The boss behind it is the objective now
+                    commands
+                        .entity(entity)
+                        .remove::<CompassObjective>()
+                        .insert((
+                            RigidBody::Dynamic,
+                            ImpulseJoint::new(hinge, joint),
+                            ExternalImpulse {
+                                torque_impulse: Vec3::NEG_Y * DOOR_KICK,
+                                ..default()
+                            },
+                            Damping {
+                                linear_damping: 0.5,
+                                angular_damping: 0.8,
+                            },
+                        ));
+                }
+                nav_grid.clear_obstacle_world(ROOM_CENTER, ROOM_HALF_EXTENTS);
+                nav_grid.clear_obstacle_world(DOOR_POSITION, door_half_extents());
+                shake_events.write(CameraShake { trauma: 0.5 });
+                subtitles.write(Subtitle("[Door bursts open]".to_string()));
+            }
+            CutsceneCue::SpawnBoss => {
+                let mut boss = Zombie::for_difficulty(ZombieKind::Walker, 0, &difficulty, &balance);
+                boss.max_health *= BOSS_HEALTH_SCALE;
+                boss.health = boss.max_health;
+                boss.damage *= BOSS_DAMAGE_SCALE;
+                boss.speed *= BOSS_SPEED_SCALE;
+                boss.flinch = 1.0;
+                boss.armor = BOSS_ARMOR;
+                // Far too heavy to get up on cover
+                boss.can_climb = false;
+                // Nor does it flinch from explosives underfoot
+                boss.scatters = false;
+                let entity = spawn_zombie(&mut commands, &zombie_assets, BOSS_SPAWN, boss);
+                let weak_point = spawn_boss_armor(&mut commands, &assets, entity);
+                commands.entity(entity).insert((
+                    Transform::from_translation(BOSS_SPAWN).with_scale(Vec3::splat(BOSS_SCALE)),
+                    Name::new("Abomination"),
+                    Boss {
+                        weak_point,
+                        recovering: 0.0,
+                    },
+                    Shielded { active: true },
+                    CompassObjective,
+                ));
+                shake_events.write(CameraShake { trauma: 0.3 });
+                subtitles.write(Subtitle("[Guttural roar]".to_string()));
+            }
+        }
+    }
+}
+
+fn hide_boss_door_prompt(mut prompt_q: Query<&mut Visibility, With<BossDoorPrompt>>) {
+    for mut visibility in prompt_q.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Plate the boss's front and sides and put its weak point on its back, all as child
+/// colliders so rays report which one they struck. Returns the weak point.
+fn spawn_boss_armor(commands: &mut Commands, assets: &BossArenaAssets, boss: Entity) -> Entity {
+    let plates = [
+        (
+            &assets.front_plate_mesh,
+            Vec3::new(0.0, 0.2, -0.45),
+            Vec3::new(0.35, 0.5, 0.04),
+        ),
+        (
+            &assets.side_plate_mesh,
+            Vec3::new(-0.45, 0.2, 0.0),
+            Vec3::new(0.04, 0.5, 0.3),
+        ),
+        (
+            &assets.side_plate_mesh,
+            Vec3::new(0.45, 0.2, 0.0),
+            Vec3::new(0.04, 0.5, 0.3),
+        ),
+    ];
+    for (mesh, position, half_extents) in plates {
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(assets.plate_material.clone()),
+            Transform::from_translation(position),
+            Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+            PhysicsLayer::Enemies.groups(),
+            Shootable,
+            HitZone {
+                owner: boss,
+                multiplier: PLATE_MULTIPLIER,
+            },
+            ChildOf(boss),
+        ));
+    }
+
+    commands
+        .spawn((
+            Mesh3d(assets.weak_point_mesh.clone()),
+            MeshMaterial3d(assets.weak_point_material.clone()),
+            Transform::from_translation(WEAK_POINT_BACK),
+            Collider::ball(0.15),
+            PhysicsLayer::Enemies.groups(),
+            Shootable,
+            HitZone {
+                owner: boss,
+                multiplier: WEAK_POINT_MULTIPLIER,
+            },
+            BossWeakPoint,
+            ChildOf(boss),
+        ))
+        .id()
+}
+
+/// Each slam leaves the boss reeling with its weak point swung round to the front, and
+/// its plates no longer doing most of the work
+fn recover_from_slams(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut slams: MessageReader<ZombieAttacked>,
+    mut bosses: Query<(Entity, &mut Boss, &mut Shielded)>,
+    mut weak_points: Query<&mut Transform, With<BossWeakPoint>>,
+    mut shake_events: MessageWriter<CameraShake>,
+) {
+    for slam in slams.read() {
+        let Ok((entity, mut boss, _)) = bosses.get_mut(slam.zombie) else {
+            continue;
+        };
+        boss.recovering = SLAM_RECOVERY_TIME;
+        commands.entity(entity).try_insert(Staggered {
+            timer: Timer::from_seconds(SLAM_RECOVERY_TIME, TimerMode::Once),
+        });
+        shake_events.write(CameraShake { trauma: 0.4 });
+    }
+
+    for (_, mut boss, mut shielded) in bosses.iter_mut() {
+        boss.recovering = (boss.recovering - time.delta_secs()).max(0.0);
+        let exposed = boss.recovering > 0.0;
+        if shielded.active == exposed {
+            shielded.active = !exposed;
+        }
+        if let Ok(mut transform) = weak_points.get_mut(boss.weak_point) {
+            transform.translation = if exposed {
+                WEAK_POINT_FRONT
+            } else {
+                WEAK_POINT_BACK
+            };
+        }
+    }
+}
+
+/// Plates dim while they shield the boss and the weak point pulses while it's exposed.
+/// There's only ever one boss, so its shared materials are edited in place.
+fn show_boss_exposure(
+    time: Res<Time>,
+    assets: Res<BossArenaAssets>,
+    bosses: Query<&Boss>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(boss) = bosses.iter().next() else {
+        return;
+    };
+    let exposed = boss.recovering > 0.0;
+
+    let plate_color = if exposed { PLATE_BRIGHT } else { PLATE_DIM };
+    let glow = if exposed {
+        let pulse = (time.elapsed_secs() * WEAK_POINT_PULSE_RATE * TAU)
+            .sin()
+            .abs();
+        WEAK_POINT_GLOW * (1.0 + 2.0 * pulse)
+    } else {
+        WEAK_POINT_GLOW
+    };
+
+    // Only touch the materials when they change, so a steady boss re-uploads nothing
+    if materials
+        .get(&assets.plate_material)
+        .is_some_and(|material| material.base_color != plate_color)
+    {
+        if let Some(material) = materials.get_mut(&assets.plate_material) {
+            material.base_color = plate_color;
+        }
+    }
+    if materials
+        .get(&assets.weak_point_material)
+        .is_some_and(|material| material.emissive != glow)
+    {
+        if let Some(material) = materials.get_mut(&assets.weak_point_material) {
+            material.emissive = glow;
+        }
+    }
+}
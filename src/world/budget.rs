@@ -0,0 +1,320 @@
+use super::Popping;
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+
+/// Keeps short-lived clutter from piling up over long runs. Anything spawned with
+/// `Budgeted(category)` is counted, and the oldest of a category goes first once
+/// it's over its cap.
+pub struct EntityBudgetPlugin;
+
+impl Plugin for EntityBudgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityBudget>()
+            .add_systems(PostUpdate, (stamp_budgeted, enforce_entity_budget).chain());
+
+        #[cfg(feature = "dev_console")]
+        {
+            use crate::console::ConsoleAppExt;
+            app.register_console_command("budget", "", budget_command);
+        }
+    }
+}
+
+/// What kind of clutter an entity is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BudgetCategory {
+    /// Items lying in the world that nobody has picked up
+    Pickup,
+    /// Physics debris such as target fragments
+    Debris,
+    /// Dead zombies lying where they fell
+    Corpse,
+    /// Tracers and beams
+    Tracer,
+    /// Marks left on surfaces, such as blood splatter
+    Decal,
+}
+
+impl BudgetCategory {
+    pub const ALL: [BudgetCategory; 5] = [
+        BudgetCategory::Pickup,
+        BudgetCategory::Debris,
+        BudgetCategory::Corpse,
+        BudgetCategory::Tracer,
+        BudgetCategory::Decal,
+    ];
+}
+
+/// Marks an entity as counted against its category's cap
+#[derive(Component)]
+pub struct Budgeted(pub BudgetCategory);
+
+/// When the janitor first saw a budgeted entity, for oldest-first removal
+#[derive(Component)]
+struct BudgetAge(f64);
+
+/// Caps per category, plus the whole-world soft cap that tightens them under load
+#[derive(Resource)]
+pub struct EntityBudget {
+    pub pickups: usize,
+    pub debris: usize,
+    pub corpses: usize,
+    pub tracers: usize,
+    pub decals: usize,
+    /// Pickups older than this are removed even when under the cap
+    pub pickup_lifetime: f32,
+    /// Total entity count above which cleanup gets more aggressive
+    pub soft_cap: u32,
+    /// Total entity count from the last pass
+    pub total: u32,
+}
+
+impl Default for EntityBudget {
+    fn default() -> Self {
+        Self {
+            pickups: 24,
+            debris: 48,
+            corpses: 32,
+            tracers: 64,
+            decals: 96,
+            pickup_lifetime: 120.0,
+            soft_cap: 4000,
+            total: 0,
+        }
+    }
+}
+
+impl EntityBudget {
+    pub fn cap(&self, category: BudgetCategory) -> usize {
+        match category {
+            BudgetCategory::Pickup => self.pickups,
+            BudgetCategory::Debris => self.debris,
+            BudgetCategory::Corpse => self.corpses,
+            BudgetCategory::Tracer => self.tracers,
+            BudgetCategory::Decal => self.decals,
+        }
+    }
+
+    /// How far over the soft cap the world is: 1.0 at or below it, 2.0 at double
+    pub fn pressure(&self) -> f32 {
+        (self.total as f32 / self.soft_cap as f32).max(1.0)
+    }
+}
+
+fn stamp_budgeted(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    added: Query<Entity, Added<Budgeted>>,
+) {
+    let now = time.elapsed_secs_f64();
+    for entity in added.iter() {
+        commands.entity(entity).insert(BudgetAge(now));
+    }
+}
+
+/// Over the soft cap every cap shrinks and pickups expire sooner, in proportion to the
+/// overload, and corpses go at once. Expired pickups pop rather than vanish. Ages run
+/// on game time, so nothing expires while paused.
+fn enforce_entity_budget(
+    mut commands: Commands,
+    time: Res<Time<Virtual>>,
+    entities: &Entities,
+    mut budget: ResMut<EntityBudget>,
+    budgeted: Query<(Entity, &Budgeted, Option<&BudgetAge>)>,
+) {
+    let now = time.elapsed_secs_f64();
+    budget.total = entities.len();
+    let pressure = budget.pressure();
+
+    for category in BudgetCategory::ALL {
+        // Not stamped yet means spawned this frame, so newest
+        let mut members: Vec<(Entity, f64)> = budgeted
+            .iter()
+            .filter(|(_, budgeted, _)| budgeted.0 == category)
+            .map(|(entity, _, age)| (entity, age.map_or(now, |age| age.0)))
+            .collect();
+
+        if category == BudgetCategory::Pickup {
+            let lifetime = (budget.pickup_lifetime / (pressure * pressure)) as f64;
+            members.retain(|(entity, spawned)| {
+                let expired = now - spawned > lifetime;
+                if expired {
+                    // No longer counted while it pops
+                    commands
+                        .entity(*entity)
+                        .remove::<Budgeted>()
+                        .insert(Popping::default());
+                }
+                !expired
+            });
+        }
+
+        let cap = match category {
+            BudgetCategory::Corpse if pressure > 1.0 => 0,
+            _ => (budget.cap(category) as f32 / pressure) as usize,
+        };
+        let overflow = members.len().saturating_sub(cap);
+        if overflow > 0 {
+            members.sort_by(|a, b| a.1.total_cmp(&b.1));
+            for (entity, _) in members.drain(..overflow) {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dev_console")]
+fn budget_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let mut counts = [0; BudgetCategory::ALL.len()];
+    for budgeted in world.query::<&Budgeted>().iter(world) {
+        if let Some(index) = BudgetCategory::ALL.iter().position(|c| *c == budgeted.0) {
+            counts[index] += 1;
+        }
+    }
+
+    let budget = world.resource::<EntityBudget>();
+    let mut lines = vec![format!(
+        "entities {} / soft cap {} (pressure {:.2})",
+        budget.total,
+        budget.soft_cap,
+        budget.pressure()
+    )];
+    for (category, count) in BudgetCategory::ALL.into_iter().zip(counts) {
+        lines.push(format!(
+            "{:?} {} / {}",
+            category,
+            count,
+            budget.cap(category)
+        ));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    fn janitor() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, EntityBudgetPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                1.0 / 60.0,
+            )));
+        // Let a single long step through, for lifetimes
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs(3600));
+        // Start the clock, so every batch after this is stamped later than the last
+        app.update();
+        app
+    }
+
+    fn spawn_batch(app: &mut App, category: BudgetCategory, count: usize) -> Vec<Entity> {
+        (0..count)
+            .map(|_| app.world_mut().spawn(Budgeted(category)).id())
+            .collect()
+    }
+
+    fn count(app: &mut App, category: BudgetCategory) -> usize {
+        app.world_mut()
+            .query::<&Budgeted>()
+            .iter(app.world())
+            .filter(|budgeted| budgeted.0 == category)
+            .count()
+    }
+
+    #[test]
+    fn ten_times_the_cap_is_cut_back_oldest_first() {
+        let mut app = janitor();
+        let cap = app.world().resource::<EntityBudget>().debris;
+
+        let mut newest = Vec::new();
+        for _ in 0..10 {
+            newest = spawn_batch(&mut app, BudgetCategory::Debris, cap);
+            app.update();
+            assert_eq!(count(&mut app, BudgetCategory::Debris), cap);
+        }
+        assert!(newest
+            .iter()
+            .all(|&entity| app.world().get_entity(entity).is_ok()));
+    }
+
+    #[test]
+    fn categories_are_capped_apart() {
+        let mut app = janitor();
+        let (debris, tracers) = {
+            let budget = app.world().resource::<EntityBudget>();
+            (budget.debris, budget.tracers)
+        };
+        spawn_batch(&mut app, BudgetCategory::Debris, debris * 10);
+        spawn_batch(&mut app, BudgetCategory::Tracer, tracers / 2);
+        app.update();
+        assert_eq!(count(&mut app, BudgetCategory::Debris), debris);
+        assert_eq!(count(&mut app, BudgetCategory::Tracer), tracers / 2);
+    }
+
+    #[test]
+    fn double_the_soft_cap_halves_caps_and_lifetimes() {
+        let mut app = janitor();
+        let base = app.world().resource::<EntityBudget>().total;
+        let (pickups, lifetime) = {
+            let mut budget = app.world_mut().resource_mut::<EntityBudget>();
+            budget.soft_cap = 1000;
+            (budget.pickups, budget.pickup_lifetime)
+        };
+        spawn_batch(&mut app, BudgetCategory::Pickup, pickups);
+        // Unbudgeted filler bringing the world to twice the soft cap
+        let filler = 2000 - base as usize - pickups;
+        app.world_mut()
+            .spawn_batch((0..filler).map(|_| Transform::default()));
+        app.update();
+        assert_eq!(app.world().resource::<EntityBudget>().pressure(), 2.0);
+        assert_eq!(count(&mut app, BudgetCategory::Pickup), pickups / 2);
+
+        // Pickups now last a quarter as long, and pop rather than vanish
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            lifetime / 4.0 + 1.0,
+        )));
+        app.update();
+        assert_eq!(count(&mut app, BudgetCategory::Pickup), 0);
+        let popping = app
+            .world_mut()
+            .query_filtered::<(), With<Popping>>()
+            .iter(app.world())
+            .count();
+        assert_eq!(popping, pickups / 2);
+    }
+
+    #[test]
+    fn corpses_go_at_once_over_the_soft_cap() {
+        let mut app = janitor();
+        let corpses = app.world().resource::<EntityBudget>().corpses;
+        spawn_batch(&mut app, BudgetCategory::Corpse, corpses);
+        app.update();
+        assert_eq!(count(&mut app, BudgetCategory::Corpse), corpses);
+
+        let total = app.world().resource::<EntityBudget>().total;
+        app.world_mut().resource_mut::<EntityBudget>().soft_cap = total / 2;
+        spawn_batch(&mut app, BudgetCategory::Debris, 1);
+        app.update();
+        assert_eq!(count(&mut app, BudgetCategory::Corpse), 0);
+        assert_eq!(count(&mut app, BudgetCategory::Debris), 1);
+    }
+
+    #[test]
+    fn pickups_keep_while_the_game_is_paused() {
+        let mut app = janitor();
+        let lifetime = app.world().resource::<EntityBudget>().pickup_lifetime;
+        spawn_batch(&mut app, BudgetCategory::Pickup, 1);
+        app.update();
+
+        app.world_mut().resource_mut::<Time<Virtual>>().pause();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            lifetime + 1.0,
+        )));
+        app.update();
+        assert_eq!(count(&mut app, BudgetCategory::Pickup), 1);
+    }
+}
@@ -0,0 +1,112 @@
+use bevy_rapier3d::prelude::*;
+
+/// Which collision layer a collider is on. Every collider gets one at spawn through
+/// `groups()`, and character controllers filter their moves with the same groups, so
+/// physics is what keeps bodies out of walls and off each other.
+///
+/// Pairs marked x collide (or, for rays, are hit); the table must stay symmetric,
+/// since rapier only pairs two colliders when each one's filter takes the other.
+///
+/// |             | World | Player | Enemies | Projectiles | Pickups | Debris |
+/// |-------------|-------|--------|---------|-------------|---------|--------|
+/// | World       |       |   x    |    x    |      x      |    x    |   x    |
+/// | Player      |   x   |   x    |    x    |      x      |    x    |        |
+/// | Enemies     |   x   |   x    |         |      x      |         |        |
+/// | Projectiles |   x   |   x    |    x    |             |         |        |
+/// | Pickups     |   x   |   x    |         |             |         |        |
+/// | Debris      |   x   |        |         |             |         |        |
+///
+/// Zombies don't block each other's controllers; separate_zombies spaces them
+/// out instead, without the jitter of capsules shoving at each other.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PhysicsLayer {
+    /// Floor, walls, cover, doors and other level geometry, shootable or not
+    World,
+    /// The player and allies
+    Player,
+    /// Zombies, plus range targets and portals: whatever is shot at that isn't level
+    Enemies,
+    /// Thrown flares and grenades, and the rays and sweeps that stand in for bullets
+    Projectiles,
+    /// Anything lying around to be picked up
+    Pickups,
+    /// Loose bits from broken targets; they only land on the level
+    Debris,
+}
+
+impl PhysicsLayer {
+    fn membership(self) -> Group {
+        match self {
+            PhysicsLayer::World => Group::GROUP_1,
+            PhysicsLayer::Player => Group::GROUP_2,
+            PhysicsLayer::Enemies => Group::GROUP_3,
+            PhysicsLayer::Projectiles => Group::GROUP_4,
+            PhysicsLayer::Pickups => Group::GROUP_5,
+            PhysicsLayer::Debris => Group::GROUP_6,
+        }
+    }
+
+    /// One row of the table above
+    fn collides_with(self) -> &'static [PhysicsLayer] {
+        use PhysicsLayer::*;
+        match self {
+            World => &[Player, Enemies, Projectiles, Pickups, Debris],
+            Player => &[World, Player, Enemies, Projectiles, Pickups],
+            Enemies => &[World, Player, Projectiles],
+            Projectiles => &[World, Player, Enemies],
+            Pickups => &[World, Player],
+            Debris => &[World],
+        }
+    }
+
+    /// Groups for a collider on this layer, or a controller or query moving on it
+    pub fn groups(self) -> CollisionGroups {
+        let filter = self
+            .collides_with()
+            .iter()
+            .fold(Group::NONE, |filter, layer| filter | layer.membership());
+        CollisionGroups::new(self.membership(), filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PhysicsLayer::*;
+
+    const LAYERS: [PhysicsLayer; 6] = [World, Player, Enemies, Projectiles, Pickups, Debris];
+
+    #[test]
+    fn the_table_is_symmetric() {
+        for a in LAYERS {
+            for b in LAYERS {
+                assert_eq!(
+                    a.collides_with().contains(&b),
+                    b.collides_with().contains(&a),
+                    "{a:?} and {b:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn groups_pair_exactly_the_table() {
+        for a in LAYERS {
+            for b in LAYERS {
+                let (a_groups, b_groups) = (a.groups(), b.groups());
+                let pairs = a_groups.filters.contains(b_groups.memberships)
+                    && b_groups.filters.contains(a_groups.memberships);
+                assert_eq!(pairs, a.collides_with().contains(&b), "{a:?} and {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn zombies_pass_through_each_other_but_not_walls_or_the_player() {
+        let zombies = Enemies.collides_with();
+        assert!(!zombies.contains(&Enemies));
+        assert!(zombies.contains(&World));
+        assert!(zombies.contains(&Player));
+        assert!(!Projectiles.collides_with().contains(&Pickups));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::ui::GameState;
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+/// Ways for things to leave the world other than blinking out. Dissolving entities
+/// glow and fade through a material of their own, taken from a capped pool so a
+/// crowd dying at once can't allocate materials without end; Popping ones swell and
+/// shrink away.
+pub struct DissolvePlugin;
+
+impl Plugin for DissolvePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DissolveMaterials>().add_systems(
+            Update,
+            (
+                reclaim_dissolve_materials,
+                (dissolve, pop).run_if(in_state(GameState::Playing)),
+            )
+                .chain(),
+        );
+    }
+}
+
+const DISSOLVE_TIME: f32 = 1.5;
+const POP_TIME: f32 = 0.35;
+/// Most dissolves running at once; past this, things just go
+const DISSOLVE_MATERIAL_CAP: usize = 64;
+/// Glow at the height of a dissolve
+const DISSOLVE_GLOW: LinearRgba = LinearRgba::rgb(2.5, 0.9, 0.2);
+
+/// Fades the entity out over DISSOLVE_TIME once `delay` runs out, then despawns it
+#[derive(Component)]
+pub struct Dissolving {
+    delay: Timer,
+    timer: Timer,
+    /// Taken from DissolveMaterials when the fade starts
+    owned: Option<Handle<StandardMaterial>>,
+}
+
+impl Dissolving {
+    /// Starts fading after `delay` seconds, e.g. once a corpse has lain there a while
+    pub fn after(delay: f32) -> Self {
+        Self {
+            delay: Timer::from_seconds(delay, TimerMode::Once),
+            timer: Timer::from_seconds(DISSOLVE_TIME, TimerMode::Once),
+            owned: None,
+        }
+    }
+}
+
+/// Swells a little, then shrinks to nothing and despawns; for pickups left too long
+#[derive(Component)]
+pub struct Popping {
+    timer: Timer,
+    base_scale: Option<Vec3>,
+}
+
+impl Default for Popping {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(POP_TIME, TimerMode::Once),
+            base_scale: None,
+        }
+    }
+}
+
+/// Materials owned by one dissolving entity each, handed back once it's gone
+#[derive(Resource, Default)]
+pub struct DissolveMaterials {
+    free: Vec<Handle<StandardMaterial>>,
+    in_use: Vec<(Entity, Handle<StandardMaterial>)>,
+}
+
+impl DissolveMaterials {
+    /// A blended copy of `source` for `entity` alone to animate, reusing a returned
+    /// material where there is one. None once the cap is reached.
+    pub fn own(
+        &mut self,
+        entity: Entity,
+        source: &Handle<StandardMaterial>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Option<Handle<StandardMaterial>> {
+        let copy = StandardMaterial {
+            alpha_mode: AlphaMode::Blend,
+            ..materials.get(source)?.clone()
+        };
+        let handle = match self.free.pop() {
+            Some(handle) => {
+                if let Some(material) = materials.get_mut(&handle) {
+                    *material = copy;
+                }
+                handle
+            }
+            None if self.in_use.len() < DISSOLVE_MATERIAL_CAP => materials.add(copy),
+            None => return None,
+        };
+        self.in_use.push((entity, handle.clone()));
+        Some(handle)
+    }
+}
+
+/// Take back the materials of dissolves that ended, however they ended: finished,
+/// cleared by the budget or swept up with the rest of a run
+fn reclaim_dissolve_materials(
+    mut pool: ResMut<DissolveMaterials>,
+    dissolving: Query<(), With<Dissolving>>,
+) {
+    let DissolveMaterials { free, in_use } = &mut *pool;
+    in_use.retain(|(entity, handle)| {
+        let running = dissolving.contains(*entity);
+        if !running {
+            free.push(handle.clone());
+        }
+        running
+    });
+}
+
+fn dissolve(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<DissolveMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut dissolving: Query<(
+        Entity,
+        &mut Dissolving,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    for (entity, mut dissolve, mut material) in dissolving.iter_mut() {
+        if !dissolve.delay.tick(time.delta()).is_finished() {
+            continue;
+        }
+        if dissolve.owned.is_none() {
+            match pool.own(entity, &material.0, &mut materials) {
+                Some(owned) => {
+                    material.0 = owned.clone();
+                    dissolve.owned = Some(owned);
+                }
+                None => {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+            }
+        }
+
+        dissolve.timer.tick(time.delta());
+        if dissolve.timer.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = dissolve.timer.fraction();
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color.set_alpha(1.0 - t);
+            // Flares up through the first half, then burns out with the rest
+            material.emissive = DISSOLVE_GLOW * (t * PI).sin();
+        }
+    }
+}
+
+fn pop(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popping: Query<(Entity, &mut Popping, &mut Transform)>,
+) {
+    for (entity, mut popping, mut transform) in popping.iter_mut() {
+        let base_scale = *popping.base_scale.get_or_insert(transform.scale);
+        if popping.timer.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = popping.timer.fraction();
+        let size = if t < 0.3 {
+            1.0 + 0.25 * (t / 0.3)
+        } else {
+            1.25 * (1.0 - (t - 0.3) / 0.7)
+        };
+        transform.scale = base_scale * size;
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::app::RunFixedMainLoopSystems;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Runs the simulation (zombie and player movement, attacks, projectiles and physics)
+/// at a fixed `SIMULATION_HZ`, so it plays out the same at any frame rate. Camera, UI
+/// and input stay on the frame. Physics steps in FixedPostUpdate, after the gameplay
+/// in FixedUpdate, the way it followed Update before. Anything `Interpolated` is drawn
+/// between its last two simulated poses, so motion stays smooth between ticks.
+pub struct FixedTimestepPlugin;
+
+impl Plugin for FixedTimestepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ))
+            .insert_resource(TimestepMode::Fixed {
+                dt: 1.0 / SIMULATION_HZ as f32,
+                substeps: 1,
+            })
+            .add_systems(
+                RunFixedMainLoop,
+                (
+                    restore_simulated_poses.in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+                    interpolate_poses.in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+                ),
+            )
+            .add_systems(FixedLast, record_simulated_poses);
+    }
+}
+
+/// Simulation ticks per second
+pub const SIMULATION_HZ: f64 = 64.0;
+
+/// Entity drawn between its last two simulated poses. Its Transform holds the drawn
+/// pose between ticks and the simulated one during them; anything that moves it
+/// outside the simulation (a teleport, turning the player with the mouse) is kept.
+#[derive(Component, Default)]
+pub struct Interpolated {
+    previous: Transform,
+    current: Transform,
+    /// What interpolate_poses last wrote, to tell it apart from outside changes
+    drawn: Transform,
+    /// False until the first tick has recorded a pose
+    simulated: bool,
+}
+
+/// Put the simulated poses back before the ticks run
+fn restore_simulated_poses(mut interpolated: Query<(&mut Interpolated, &mut Transform)>) {
+    for (mut poses, mut transform) in interpolated.iter_mut() {
+        if !poses.simulated {
+            continue;
+        }
+        let mut restored = *transform;
+        if transform.translation == poses.drawn.translation {
+            restored.translation = poses.current.translation;
+        } else {
+            // Moved since it was drawn: carry on from there rather than sliding over
+            poses.current.translation = transform.translation;
+            poses.previous.translation = transform.translation;
+        }
+        if transform.rotation == poses.drawn.rotation {
+            restored.rotation = poses.current.rotation;
+        } else {
+            poses.current.rotation = transform.rotation;
+            poses.previous.rotation = transform.rotation;
+        }
+        transform.set_if_neq(restored);
+    }
+}
+
+fn record_simulated_poses(mut interpolated: Query<(&mut Interpolated, &Transform)>) {
+    for (mut poses, transform) in interpolated.iter_mut() {
+        poses.previous = if poses.simulated {
+            poses.current
+        } else {
+            *transform
+        };
+        poses.current = *transform;
+        poses.simulated = true;
+    }
+}
+
+/// Draw everything as far between its last two ticks as the clock is past the last one
+fn interpolate_poses(
+    time: Res<Time<Fixed>>,
+    mut interpolated: Query<(&mut Interpolated, &mut Transform)>,
+) {
+    let t = time.overstep_fraction();
+    for (mut poses, mut transform) in interpolated.iter_mut() {
+        if !poses.simulated {
+            continue;
+        }
+        let drawn = Transform {
+            translation: poses
+                .previous
+                .translation
+                .lerp(poses.current.translation, t),
+            rotation: poses.previous.rotation.slerp(poses.current.rotation, t),
+            scale: transform.scale,
+        };
+        transform.set_if_neq(drawn);
+        poses.drawn = drawn;
+    }
+}
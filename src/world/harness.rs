@@ -0,0 +1,88 @@
+use super::{
+    BalanceData, FixedTimestepPlugin, GameRng, NavGrid, PhysicsLayer, SpatialIndexPlugin,
+    SIMULATION_HZ,
+};
+use crate::combat::{HitEvent, ShotFired};
+use crate::enemies::{Director, EnemyPlugin, Extraction, SpawnerPlugin, SquadPlugin, WavePlugin};
+use crate::player::{FeedbackEvent, Score};
+use crate::ui::{
+    ColorPalette, DifficultyModifiers, GameMode, GameState, PalettePreset, RunStats, ShopDiscount,
+    Subtitle, UiTheme,
+};
+use bevy::mesh::MeshPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier3d::prelude::*;
+use std::time::Duration;
+
+/// Headless app for tests: MinimalPlugins with states, assets, transforms and physics,
+/// no window or renderer, plus the zombie, squad, wave and portal plugins and the resources
+/// they share with the rest of the game. The simulation runs on its fixed tick and the
+/// clock advances exactly one tick per update, so with the same `seed` the same number
+/// of updates plays out the same way every time. Starts in Playing; add anything else
+/// under test, then call `boot`.
+pub fn headless_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        AssetPlugin::default(),
+        MeshPlugin,
+        TransformPlugin,
+        FixedTimestepPlugin,
+        RapierPhysicsPlugin::<NoUserData>::default().in_schedule(FixedPostUpdate),
+        SpatialIndexPlugin,
+        EnemyPlugin,
+        SquadPlugin,
+        SpawnerPlugin,
+        WavePlugin,
+    ))
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Font>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        1.0 / SIMULATION_HZ,
+    )))
+    .insert_resource(GameRng::seeded(seed))
+    .insert_resource(BalanceData::embedded())
+    .insert_resource(NavGrid::new(100, 100, 1.0))
+    .insert_resource(ColorPalette::from_preset(PalettePreset::Standard))
+    .init_resource::<DifficultyModifiers>()
+    .init_resource::<GameMode>()
+    .init_resource::<Extraction>()
+    .init_resource::<Director>()
+    .init_resource::<RunStats>()
+    .init_resource::<Score>()
+    .init_resource::<ShopDiscount>()
+    .init_resource::<UiTheme>()
+    .add_message::<HitEvent>()
+    .add_message::<ShotFired>()
+    .add_message::<FeedbackEvent>()
+    .add_message::<Subtitle>()
+    .insert_state(GameState::Playing);
+    app
+}
+
+/// Finish building and run Startup, ready for the test to spawn into. The clock only
+/// starts on this first update, so no tick has run yet.
+pub fn boot(app: &mut App) {
+    app.finish();
+    app.cleanup();
+    app.update();
+}
+
+/// Simulation ticks run so far
+pub fn ticks(app: &App) -> u32 {
+    let time = app.world().resource::<Time<Fixed>>();
+    (time.elapsed().as_nanos() / time.timestep().as_nanos()) as u32
+}
+
+/// Floor for the arena NavGrid covers, its top at y = 0
+pub fn spawn_floor(app: &mut App) {
+    app.world_mut().spawn((
+        Transform::from_xyz(0.0, -0.5, 0.0),
+        RigidBody::Fixed,
+        Collider::cuboid(50.0, 0.5, 50.0),
+        PhysicsLayer::World.groups(),
+    ));
+}
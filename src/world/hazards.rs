@@ -0,0 +1,146 @@
+use super::{NavGrid, PhysicsLayer, SurfaceMaterial};
+use crate::combat::{ApplyStatus, StatusEffect};
+use crate::enemies::{ExplosionFuse, Zombie, ZombieSystems, EXPLOSION_RADIUS};
+use crate::player::PlayerPerks;
+use crate::ui::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// Toxic pools on the arena floor that poison and slow zombies wading through them,
+/// and the NavGrid danger that steers paths around them and around lit explosives
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_toxic_pools)
+            .add_systems(
+                Update,
+                poison_zombies_in_pools.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                paint_hazard_danger
+                    .before(ZombieSystems::Pathing)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+const TOXIC_POOLS: [(Vec3, f32); 3] = [
+    (Vec3::new(-20.0, 0.0, -20.0), 3.0),
+    (Vec3::new(22.0, 0.0, 15.0), 2.5),
+    (Vec3::new(-10.0, 0.0, 30.0), 3.5),
+];
+
+/// Seconds between doses for a zombie standing in a pool
+const POOL_INTERVAL: f32 = 1.0;
+const POOL_POISON: StatusEffect = StatusEffect::Poisoned {
+    dps: 3.0,
+    remaining: 3.0,
+    slow: 0.35,
+};
+
+/// Extra path cost per cell inside a pool; a short wade beats a long detour
+const POOL_DANGER_COST: f32 = 3.0;
+/// Extra path cost per cell inside a lit explosive's blast; worth almost any detour
+const FUSE_DANGER_COST: f32 = 20.0;
+/// Seconds painted danger outlives its hazard
+const DANGER_LIFETIME: f32 = 0.25;
+
+#[derive(Component)]
+pub struct ToxicPool {
+    pub radius: f32,
+    timer: Timer,
+}
+
+fn spawn_toxic_pools(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.3, 0.8, 0.2, 0.8),
+        emissive: LinearRgba::rgb(0.2, 0.6, 0.1),
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+
+    for (position, radius) in TOXIC_POOLS {
+        commands.spawn((
+            Mesh3d(meshes.add(Cylinder::new(radius, 0.02))),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position.with_y(0.02)),
+            ToxicPool {
+                radius,
+                timer: Timer::from_seconds(POOL_INTERVAL, TimerMode::Repeating),
+            },
+            // Only there for surface rays, so nothing stands on it
+            Collider::cylinder(0.01, radius),
+            Sensor,
+            PhysicsLayer::World.groups(),
+            SurfaceMaterial::Water,
+        ));
+    }
+}
+
+fn poison_zombies_in_pools(
+    time: Res<Time>,
+    mut pools: Query<(&Transform, &mut ToxicPool)>,
+    zombies: Query<(Entity, &Transform), With<Zombie>>,
+    mut status_events: MessageWriter<ApplyStatus>,
+) {
+    for (pool_transform, mut pool) in pools.iter_mut() {
+        pool.timer.tick(time.delta());
+        if !pool.timer.just_finished() {
+            continue;
+        }
+        for (entity, transform) in zombies.iter() {
+            let distance = (transform.translation - pool_transform.translation)
+                .with_y(0.0)
+                .length();
+            if distance <= pool.radius {
+                status_events.write(ApplyStatus {
+                    entity,
+                    effect: POOL_POISON,
+                    source: None,
+                });
+            }
+        }
+    }
+}
+
+/// Repaint danger over every live hazard before zombies replan. Anything that stops
+/// being painted (a fuse that went off, a despawned pool) drops out on its own.
+fn paint_hazard_danger(
+    time: Res<Time>,
+    perks: Res<PlayerPerks>,
+    mut nav_grid: ResMut<NavGrid>,
+    pools: Query<(Entity, &Transform, &ToxicPool)>,
+    fuses: Query<(Entity, &Transform, &ExplosionFuse)>,
+) {
+    nav_grid.expire_danger(time.delta_secs());
+
+    for (entity, transform, pool) in pools.iter() {
+        nav_grid.paint_danger(
+            entity,
+            transform.translation,
+            pool.radius,
+            POOL_DANGER_COST,
+            DANGER_LIFETIME,
+        );
+    }
+
+    let blast_radius = EXPLOSION_RADIUS * perks.explosion_radius_scale();
+    for (entity, transform, fuse) in fuses.iter() {
+        if fuse.detonated {
+            continue;
+        }
+        nav_grid.paint_danger(
+            entity,
+            transform.translation,
+            blast_radius,
+            FUSE_DANGER_COST,
+            DANGER_LIFETIME,
+        );
+    }
+}
@@ -1,5 +1,29 @@
+mod balance;
+mod boss_arena;
+mod budget;
+mod collision;
+mod dissolve;
+mod fixed_step;
+#[cfg(test)]
+mod harness;
+mod hazards;
 mod nav_grid;
+mod rng;
+mod spatial_index;
+mod surface;
 mod world;
 
+pub use balance::*;
+pub use boss_arena::*;
+pub use budget::*;
+pub use collision::*;
+pub use dissolve::*;
+pub use fixed_step::*;
+#[cfg(test)]
+pub use harness::*;
+pub use hazards::*;
 pub use nav_grid::*;
+pub use rng::*;
+pub use spatial_index::*;
+pub use surface::*;
 pub use world::*;
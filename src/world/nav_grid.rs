@@ -17,12 +17,36 @@ pub struct NavGrid {
     pub height: usize,
     pub cell_size: f32,
     grid: Vec<bool>, // true = walkable
-    offset: Vec2,    // World offset (grid center at world origin)
+    /// Cost layer: top height of low cover a climber can get over, 0.0 where there's none
+    climb: Vec<f32>,
+    /// Cost layer: extra path cost of entering a cell, painted from `dangers`
+    danger: Vec<f32>,
+    dangers: Vec<DangerZone>,
+    danger_dirty: bool,
+    offset: Vec2, // World offset (grid center at world origin)
 }
 
+/// Temporary high-cost circle, e.g. over a hazard or a lit explosive. Painted by its
+/// owner every frame it's live and wiped once it stops being painted.
+#[derive(Clone, Copy, Debug)]
+struct DangerZone {
+    owner: Entity,
+    center: Vec3,
+    radius: f32,
+    cost: f32,
+    /// Seconds left until the circle expires unless it's painted again
+    remaining: f32,
+}
+
+/// Extra path cost of climbing on to cover, in cells; any detour shorter than this
+/// is walked instead
+const CLIMB_COST: f32 = 8.0;
+
 impl NavGrid {
     pub fn new(width: usize, height: usize, cell_size: f32) -> Self {
         let grid = vec![true; width * height];
+        let climb = vec![0.0; width * height];
+        let danger = vec![0.0; width * height];
         let offset = Vec2::new(
             -(width as f32 * cell_size) / 2.0,
             -(height as f32 * cell_size) / 2.0,
@@ -32,6 +56,10 @@ impl NavGrid {
             height,
             cell_size,
             grid,
+            climb,
+            danger,
+            dangers: Vec::new(),
+            danger_dirty: false,
             offset,
         }
     }
@@ -66,6 +94,107 @@ impl NavGrid {
         }
     }
 
+    /// Top height of climbable cover in a cell, None if there's none to climb
+    pub fn climb_height(&self, x: usize, y: usize) -> Option<f32> {
+        if x < self.width && y < self.height {
+            let height = self.climb[y * self.width + x];
+            (height > 0.0).then_some(height)
+        } else {
+            None
+        }
+    }
+
+    /// Extra cost of entering a cell, 0.0 where nothing dangerous is painted
+    pub fn danger_cost(&self, x: usize, y: usize) -> f32 {
+        if x < self.width && y < self.height {
+            self.danger[y * self.width + x]
+        } else {
+            0.0
+        }
+    }
+
+    /// Paint (or refresh) `owner`'s danger circle. Paths avoid it while it costs more
+    /// than going around. It's removed `lifetime` seconds after the last paint, so
+    /// owners keep painting while they're dangerous and simply stop when they aren't.
+    pub fn paint_danger(
+        &mut self,
+        owner: Entity,
+        center: Vec3,
+        radius: f32,
+        cost: f32,
+        lifetime: f32,
+    ) {
+        match self.dangers.iter_mut().find(|zone| zone.owner == owner) {
+            Some(zone) => {
+                if zone.center != center || zone.radius != radius || zone.cost != cost {
+                    self.danger_dirty = true;
+                }
+                *zone = DangerZone {
+                    owner,
+                    center,
+                    radius,
+                    cost,
+                    remaining: lifetime,
+                };
+            }
+            None => {
+                self.dangers.push(DangerZone {
+                    owner,
+                    center,
+                    radius,
+                    cost,
+                    remaining: lifetime,
+                });
+                self.danger_dirty = true;
+            }
+        }
+    }
+
+    /// Age painted danger by `dt` seconds, dropping circles that weren't refreshed,
+    /// then repaint the cost layer if anything changed
+    pub fn expire_danger(&mut self, dt: f32) {
+        let before = self.dangers.len();
+        for zone in self.dangers.iter_mut() {
+            zone.remaining -= dt;
+        }
+        self.dangers.retain(|zone| zone.remaining > 0.0);
+        if self.dangers.len() != before {
+            self.danger_dirty = true;
+        }
+        self.repaint_danger();
+    }
+
+    fn repaint_danger(&mut self) {
+        if !self.danger_dirty {
+            return;
+        }
+        self.danger_dirty = false;
+        self.danger.fill(0.0);
+
+        for zone in &self.dangers {
+            let reach = Vec3::new(zone.radius, 0.0, zone.radius);
+            let min = self.world_to_grid(zone.center - reach);
+            let max = self.world_to_grid(zone.center + reach);
+            let (min_x, min_y) = min.unwrap_or((0, 0));
+            let (max_x, max_y) = max.unwrap_or((self.width - 1, self.height - 1));
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let cell_center = self.grid_to_world(x, y);
+                    if (cell_center - zone.center).with_y(0.0).length() > zone.radius {
+                        continue;
+                    }
+                    let cell = &mut self.danger[y * self.width + x];
+                    *cell = cell.max(zone.cost);
+                }
+            }
+        }
+    }
+
+    /// Walkable, or climbable when the path may climb
+    fn is_passable(&self, x: usize, y: usize, climb: bool) -> bool {
+        self.is_walkable(x, y) || (climb && self.climb_height(x, y).is_some())
+    }
+
     /// Mark a cell as an obstacle (not walkable)
     pub fn set_obstacle(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
@@ -77,7 +206,7 @@ impl NavGrid {
     pub fn set_obstacle_rect(&mut self, min_x: usize, min_y: usize, max_x: usize, max_y: usize) {
         for y in min_y..=max_y.min(self.height - 1) {
             for x in min_x..=max_x.min(self.width - 1) {
-                self.grid[y * self.width + x] = false;
+                self.set_obstacle(x, y);
             }
         }
     }
@@ -94,13 +223,58 @@ impl NavGrid {
         }
     }
 
+    /// Mark an obstacle that climbers can get on top of, `height` above the floor
+    pub fn mark_climbable_world(&mut self, pos: Vec3, half_extents: Vec3, height: f32) {
+        self.mark_obstacle_world(pos, half_extents);
+        let min_world = pos - Vec3::new(half_extents.x, 0.0, half_extents.z);
+        let max_world = pos + Vec3::new(half_extents.x, 0.0, half_extents.z);
+
+        if let (Some((min_x, min_y)), Some((max_x, max_y))) =
+            (self.world_to_grid(min_world), self.world_to_grid(max_world))
+        {
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    let cell = &mut self.climb[y * self.width + x];
+                    *cell = cell.max(height);
+                }
+            }
+        }
+    }
+
+    /// Make a rectangular area walkable again, e.g. once a door is opened
+    pub fn clear_obstacle_world(&mut self, pos: Vec3, half_extents: Vec3) {
+        let min_world = pos - Vec3::new(half_extents.x, 0.0, half_extents.z);
+        let max_world = pos + Vec3::new(half_extents.x, 0.0, half_extents.z);
+
+        if let (Some((min_x, min_y)), Some((max_x, max_y))) =
+            (self.world_to_grid(min_world), self.world_to_grid(max_world))
+        {
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    self.grid[y * self.width + x] = true;
+                    self.climb[y * self.width + x] = 0.0;
+                }
+            }
+        }
+    }
+
     /// Find path using A* algorithm
     pub fn find_path(&self, start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+        self.search(start, end, false)
+    }
+
+    /// Like find_path, but may go over climbable cover when walking around it would
+    /// cost more than CLIMB_COST. Can start on top of cover.
+    pub fn find_climbing_path(&self, start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+        self.search(start, end, true)
+    }
+
+    fn search(&self, start: Vec3, end: Vec3, climb: bool) -> Option<Vec<Vec3>> {
         let start_node = self.world_to_grid(start)?;
         let end_node = self.world_to_grid(end)?;
 
         // If start or end is not walkable, return None
-        if !self.is_walkable(start_node.0, start_node.1) {
+        if !self.is_passable(start_node.0, start_node.1, climb) {
             return None;
         }
 
@@ -133,16 +307,23 @@ impl NavGrid {
             closed_set.insert(current.pos);
 
             // Check 8 neighbors (including diagonals)
-            for neighbor in self.get_neighbors(current.pos) {
+            for neighbor in self.get_neighbors(current.pos, climb) {
                 if closed_set.contains(&neighbor) {
                     continue;
                 }
 
-                let move_cost = if neighbor.0 != current.pos.0 && neighbor.1 != current.pos.1 {
+                let mut move_cost = if neighbor.0 != current.pos.0 && neighbor.1 != current.pos.1 {
                     1.414 // Diagonal movement
                 } else {
                     1.0 // Cardinal movement
                 };
+                // Getting up on cover is the slow part; moving across it is walking
+                if !self.is_walkable(neighbor.0, neighbor.1)
+                    && self.is_walkable(current.pos.0, current.pos.1)
+                {
+                    move_cost += CLIMB_COST;
+                }
+                move_cost += self.danger_cost(neighbor.0, neighbor.1);
 
                 let tentative_g = g_score.get(&current.pos).unwrap_or(&f32::MAX) + move_cost;
 
@@ -168,12 +349,23 @@ impl NavGrid {
         (dx * dx + dy * dy).sqrt()
     }
 
-    fn get_neighbors(&self, pos: (usize, usize)) -> Vec<(usize, usize)> {
-        let mut neighbors = Vec::with_capacity(8);
-        let (x, y) = pos;
+    /// Grid cell at signed coordinates, or None off the edge of the grid
+    fn cell(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let x = usize::try_from(x).ok().filter(|&x| x < self.width)?;
+        let y = usize::try_from(y).ok().filter(|&y| y < self.height)?;
+        Some((x, y))
+    }
 
+    /// Passable cells around `pos` in all 8 directions. Diagonals need both cells they
+    /// pass between to be passable too, so paths don't cut corners through walls.
+    /// Lazy, since this is the inner loop of A*.
+    fn get_neighbors(
+        &self,
+        pos: (usize, usize),
+        climb: bool,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
         // 8 directions: N, S, E, W, NE, NW, SE, SW
-        let directions: [(i32, i32); 8] = [
+        const DIRECTIONS: [(i32, i32); 8] = [
             (0, 1),
             (0, -1),
             (1, 0),
@@ -183,32 +375,22 @@ impl NavGrid {
             (1, -1),
             (-1, -1),
         ];
+        let (x, y) = (pos.0 as i32, pos.1 as i32);
+        let passable = move |cell: Option<(usize, usize)>| {
+            cell.is_some_and(|(cx, cy)| self.is_passable(cx, cy, climb))
+        };
 
-        for (dx, dy) in directions {
-            let nx = x as i32 + dx;
-            let ny = y as i32 + dy;
-
-            if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny < self.height as i32 {
-                let nx = nx as usize;
-                let ny = ny as usize;
-
-                if self.is_walkable(nx, ny) {
-                    // For diagonal movement, check that we can actually move diagonally
-                    // (not cutting corners through walls)
-                    if dx != 0 && dy != 0 {
-                        let can_move_x = self.is_walkable((x as i32 + dx) as usize, y);
-                        let can_move_y = self.is_walkable(x, (y as i32 + dy) as usize);
-                        if can_move_x && can_move_y {
-                            neighbors.push((nx, ny));
-                        }
-                    } else {
-                        neighbors.push((nx, ny));
-                    }
-                }
+        DIRECTIONS.into_iter().filter_map(move |(dx, dy)| {
+            let neighbor = self.cell(x + dx, y + dy)?;
+            if !passable(Some(neighbor)) {
+                return None;
             }
-        }
-
-        neighbors
+            let diagonal = dx != 0 && dy != 0;
+            if diagonal && !(passable(self.cell(x + dx, y)) && passable(self.cell(x, y + dy))) {
+                return None;
+            }
+            Some(neighbor)
+        })
     }
 
     fn reconstruct_path(
@@ -263,12 +445,18 @@ impl NavGrid {
             return true;
         }
 
+        // Shortcuts mustn't cut through danger the path went around; one that starts
+        // inside it is on its way out anyway
+        let avoid_danger = self
+            .world_to_grid(start)
+            .is_some_and(|(x, y)| self.danger_cost(x, y) == 0.0);
+
         for i in 1..steps {
             let t = i as f32 / steps as f32;
             let pos = start.lerp(end, t);
 
             if let Some((x, y)) = self.world_to_grid(pos) {
-                if !self.is_walkable(x, y) {
+                if !self.is_walkable(x, y) || (avoid_danger && self.danger_cost(x, y) > 0.0) {
                     return false;
                 }
             } else {
@@ -281,10 +469,10 @@ impl NavGrid {
 
     fn find_nearest_walkable(&self, pos: (usize, usize)) -> Option<(usize, usize)> {
         // Search in expanding squares around the target
-        for radius in 1..10 {
-            for dx in -(radius as i32)..=(radius as i32) {
-                for dy in -(radius as i32)..=(radius as i32) {
-                    if dx.abs() != radius as i32 && dy.abs() != radius as i32 {
+        for radius in 1..10i32 {
+            for dx in -radius..=radius {
+                for dy in -radius..=radius {
+                    if dx.abs() != radius && dy.abs() != radius {
                         continue; // Only check perimeter
                     }
 
@@ -335,3 +523,135 @@ impl PartialOrd for Node {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::GameRng;
+    use rand::Rng;
+
+    /// Random grids each property is checked against; a failure names its seed
+    const CASES: u64 = 200;
+
+    /// A grid of random size and cell size with its border walkable and the inside
+    /// blocked with probability `fill`, plus the border cells in order round the edge
+    fn bordered_grid(rng: &mut GameRng, fill: f64) -> (NavGrid, Vec<(usize, usize)>) {
+        let (width, height) = (rng.random_range(3..=24), rng.random_range(3..=24));
+        let mut grid = NavGrid::new(width, height, rng.random_range(0.5..=2.0));
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                if rng.random_bool(fill) {
+                    grid.set_obstacle(x, y);
+                }
+            }
+        }
+
+        let (right, bottom) = (width - 1, height - 1);
+        let border = (0..right)
+            .map(|x| (x, 0))
+            .chain((0..bottom).map(|y| (right, y)))
+            .chain((1..=right).rev().map(|x| (x, bottom)))
+            .chain((1..=bottom).rev().map(|y| (0, y)))
+            .collect();
+        (grid, border)
+    }
+
+    fn pick(rng: &mut GameRng, cells: &[(usize, usize)]) -> (usize, usize) {
+        cells[rng.random_range(0..cells.len())]
+    }
+
+    fn on_border(grid: &NavGrid, point: Vec3) -> bool {
+        let (x, y) = grid.world_to_grid(point).unwrap();
+        x == 0 || y == 0 || x == grid.width - 1 || y == grid.height - 1
+    }
+
+    #[test]
+    fn border_cells_are_in_order_and_each_listed_once() {
+        for seed in 0..CASES {
+            let (grid, border) = bordered_grid(&mut GameRng::seeded(seed), 0.0);
+            assert_eq!(
+                border.len(),
+                2 * (grid.width + grid.height) - 4,
+                "seed {seed}"
+            );
+            let unique: HashSet<_> = border.iter().collect();
+            assert_eq!(unique.len(), border.len(), "seed {seed}");
+            for (i, &(x, y)) in border.iter().enumerate() {
+                let (nx, ny) = border[(i + 1) % border.len()];
+                assert_eq!(x.abs_diff(nx) + y.abs_diff(ny), 1, "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn any_two_border_cells_are_joined_by_a_walkable_path() {
+        for seed in 0..CASES {
+            let mut rng = GameRng::seeded(seed);
+            let fill = rng.random_range(0.0..=1.0);
+            let (grid, border) = bordered_grid(&mut rng, fill);
+            let (from, to) = (pick(&mut rng, &border), pick(&mut rng, &border));
+            let start = grid.grid_to_world(from.0, from.1);
+            let end = grid.grid_to_world(to.0, to.1);
+
+            let path = grid
+                .find_path(start, end)
+                .unwrap_or_else(|| panic!("seed {seed}: no path from {from:?} to {to:?}"));
+            assert_eq!(path.first(), Some(&start), "seed {seed}");
+            assert_eq!(path.last(), Some(&end), "seed {seed}");
+            for leg in path.windows(2) {
+                assert!(
+                    grid.can_walk_straight(leg[0], leg[1]),
+                    "seed {seed}: {leg:?} crosses an obstacle"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn paths_stay_on_the_border_when_the_inside_is_blocked() {
+        for seed in 0..CASES {
+            let mut rng = GameRng::seeded(seed);
+            let (grid, border) = bordered_grid(&mut rng, 1.0);
+            let (from, to) = (pick(&mut rng, &border), pick(&mut rng, &border));
+            let start = grid.grid_to_world(from.0, from.1);
+            let end = grid.grid_to_world(to.0, to.1);
+
+            let path = grid
+                .find_path(start, end)
+                .unwrap_or_else(|| panic!("seed {seed}: no path from {from:?} to {to:?}"));
+            assert_eq!(path.last(), Some(&end), "seed {seed}");
+            assert!(
+                path.iter().all(|&point| on_border(&grid, point)),
+                "seed {seed}: {path:?} leaves the border"
+            );
+        }
+    }
+
+    #[test]
+    fn a_border_cell_walled_in_on_both_sides_has_no_path_out() {
+        for seed in 0..CASES {
+            let mut rng = GameRng::seeded(seed);
+            let (mut grid, border) = bordered_grid(&mut rng, 1.0);
+            let i = rng.random_range(0..border.len());
+            let before = border[(i + border.len() - 1) % border.len()];
+            let after = border[(i + 1) % border.len()];
+            grid.set_obstacle(before.0, before.1);
+            grid.set_obstacle(after.0, after.1);
+
+            let from = border[i];
+            let rest: Vec<_> = border
+                .iter()
+                .copied()
+                .filter(|&cell| cell != from && grid.is_walkable(cell.0, cell.1))
+                .collect();
+            let to = pick(&mut rng, &rest);
+            let start = grid.grid_to_world(from.0, from.1);
+            let end = grid.grid_to_world(to.0, to.1);
+            assert_eq!(
+                grid.find_path(start, end),
+                None,
+                "seed {seed}: {from:?} reached {to:?}"
+            );
+        }
+    }
+}
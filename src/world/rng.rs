@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Environment variable that fixes the seed, e.g. `GAME_SEED=42 make run`
+const SEED_ENV: &str = "GAME_SEED";
+
+/// The one random source for gameplay. Every system that rolls dice takes this
+/// instead of `rand::rng()`, so a fixed seed replays the same layout, spawns and spread.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    #[deref]
+    rng: StdRng,
+    /// Seed the app started with, which decides the obstacle layout
+    pub map_seed: u64,
+}
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            map_seed: seed,
+        }
+    }
+
+    /// Restart the sequence from `seed`, keeping the map seed
+    #[cfg(feature = "dev_console")]
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        match std::env::var(SEED_ENV).ok().and_then(|s| s.parse().ok()) {
+            Some(seed) => {
+                info!("Using fixed RNG seed {}", seed);
+                Self::seeded(seed)
+            }
+            None => Self::seeded(rand::random()),
+        }
+    }
+}
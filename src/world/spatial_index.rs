@@ -0,0 +1,412 @@
+use super::{BudgetCategory, Budgeted};
+use crate::enemies::{DormantCorpse, Team, ZombieSystems};
+use crate::ui::GameState;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Where every zombie, survivor and pickup stands, bucketed into a uniform grid over
+/// the ground plane. Rebuilt every tick ahead of the zombie update, so systems looking
+/// for what's nearby check a handful of cells instead of every entity.
+pub struct SpatialIndexPlugin;
+
+impl Plugin for SpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialIndex>().add_systems(
+            FixedUpdate,
+            rebuild_spatial_index
+                .in_set(ZombieSystems::Indexing)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Width of a grid cell. Most lookups (separation, bites, chain jumps) reach less than
+/// a cell, so they touch at most four.
+const CELL_SIZE: f32 = 4.0;
+
+/// What an indexed entity is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpatialKind {
+    Zombie,
+    /// Zombie playing dead (see DormantCorpse), no threat until it's up
+    Corpse,
+    /// The player and allies, anything zombies go for
+    Survivor,
+    /// Items lying in the world, see BudgetCategory::Pickup
+    Pickup,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    /// Position when the index was last rebuilt
+    pub position: Vec3,
+    pub kind: SpatialKind,
+}
+
+/// Entities by grid cell, as of the start of the latest zombie update. Anything
+/// despawned since is still listed, so look entities up again before relying on them.
+#[derive(Resource)]
+pub struct SpatialIndex {
+    entries: Vec<SpatialEntry>,
+    /// Indices into `entries`; emptied rather than dropped on rebuild so the
+    /// allocations carry over between frames
+    cells: HashMap<IVec2, Vec<usize>>,
+    /// Corners of the cells anything is in, bounding how far searches go
+    min_cell: IVec2,
+    max_cell: IVec2,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            cells: HashMap::new(),
+            min_cell: IVec2::MAX,
+            max_cell: IVec2::MIN,
+        }
+    }
+}
+
+fn cell_of(position: Vec3) -> IVec2 {
+    IVec2::new(
+        (position.x / CELL_SIZE).floor() as i32,
+        (position.z / CELL_SIZE).floor() as i32,
+    )
+}
+
+fn flat_distance_squared(a: Vec3, b: Vec3) -> f32 {
+    (a - b).with_y(0.0).length_squared()
+}
+
+/// Cells exactly `ring` steps out from `center`, counting diagonals as one step
+fn ring_cells(center: IVec2, ring: i32) -> impl Iterator<Item = IVec2> {
+    (-ring..=ring)
+        .flat_map(move |x| (-ring..=ring).map(move |z| IVec2::new(x, z)))
+        .filter(move |offset| offset.x.abs() == ring || offset.y.abs() == ring)
+        .map(move |offset| center + offset)
+}
+
+impl SpatialIndex {
+    fn clear(&mut self) {
+        self.entries.clear();
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        self.min_cell = IVec2::MAX;
+        self.max_cell = IVec2::MIN;
+    }
+
+    fn insert(&mut self, entry: SpatialEntry) {
+        let cell = cell_of(entry.position);
+        self.cells.entry(cell).or_default().push(self.entries.len());
+        self.entries.push(entry);
+        self.min_cell = self.min_cell.min(cell);
+        self.max_cell = self.max_cell.max(cell);
+    }
+
+    /// Everything within `radius` of `position` on the ground plane, in no particular
+    /// order. Height is ignored; callers that care check it themselves.
+    pub fn neighbors_within(
+        &self,
+        position: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = &SpatialEntry> + '_ {
+        let reach = Vec3::new(radius, 0.0, radius);
+        let min = cell_of(position - reach).max(self.min_cell);
+        let max = cell_of(position + reach).min(self.max_cell);
+        let radius_squared = radius * radius;
+
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |z| IVec2::new(x, z)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flat_map(|indices| indices.iter().map(|&index| &self.entries[index]))
+            .filter(move |entry| flat_distance_squared(entry.position, position) <= radius_squared)
+    }
+
+    /// Up to `k` entries that pass `filter`, nearest to `position` on the ground plane
+    /// first
+    pub fn nearest_k(
+        &self,
+        position: Vec3,
+        k: usize,
+        filter: impl Fn(&SpatialEntry) -> bool,
+    ) -> Vec<SpatialEntry> {
+        if k == 0 || self.entries.is_empty() {
+            return Vec::new();
+        }
+        let center = cell_of(position);
+        let last_ring = (center - self.min_cell)
+            .abs()
+            .max((self.max_cell - center).abs())
+            .max_element();
+
+        let mut found: Vec<(f32, SpatialEntry)> = Vec::new();
+        for ring in 0..=last_ring {
+            for cell in ring_cells(center, ring) {
+                let Some(indices) = self.cells.get(&cell) else {
+                    continue;
+                };
+                for &index in indices {
+                    let entry = self.entries[index];
+                    if filter(&entry) {
+                        found.push((flat_distance_squared(entry.position, position), entry));
+                    }
+                }
+            }
+
+            // Cells past this ring are at least `ring` whole cells away, so once the
+            // k-th nearest is closer than that nothing further out can beat it
+            if found.len() >= k {
+                found.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                found.truncate(k);
+                let searched = ring as f32 * CELL_SIZE;
+                if found[k - 1].0 <= searched * searched {
+                    break;
+                }
+            }
+        }
+
+        found.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        found.truncate(k);
+        found.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+fn rebuild_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    indexed: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Team>,
+            Option<&Budgeted>,
+            Has<DormantCorpse>,
+        ),
+        Or<(With<Team>, With<Budgeted>)>,
+    >,
+) {
+    index.clear();
+    for (entity, transform, team, budgeted, dormant) in indexed.iter() {
+        let kind = match (team, budgeted) {
+            (Some(Team::Horde), _) if dormant => SpatialKind::Corpse,
+            (Some(Team::Horde), _) => SpatialKind::Zombie,
+            (Some(Team::Survivors), _) => SpatialKind::Survivor,
+            (None, Some(Budgeted(BudgetCategory::Pickup))) => SpatialKind::Pickup,
+            _ => continue,
+        };
+        index.insert(SpatialEntry {
+            entity,
+            position: transform.translation,
+            kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// `count` entries laid out on a half-metre lattice around the origin, so plenty sit
+    /// exactly on cell boundaries, on both sides of zero. Every fifth is a survivor.
+    fn scattered(count: usize) -> SpatialIndex {
+        let mut world = World::new();
+        let mut index = SpatialIndex::default();
+        for i in 0..count {
+            index.insert(SpatialEntry {
+                entity: world.spawn_empty().id(),
+                position: lattice_point(i),
+                kind: if i % 5 == 0 {
+                    SpatialKind::Survivor
+                } else {
+                    SpatialKind::Zombie
+                },
+            });
+        }
+        index
+    }
+
+    fn lattice_point(i: usize) -> Vec3 {
+        let x = ((i * 37) % 61) as f32 * 0.5 - 15.0;
+        let z = ((i * 23) % 53) as f32 * 0.5 - 13.0;
+        Vec3::new(x, (i % 3) as f32, z)
+    }
+
+    fn brute_force_within(index: &SpatialIndex, position: Vec3, radius: f32) -> Vec<Entity> {
+        let mut found: Vec<Entity> = index
+            .entries
+            .iter()
+            .filter(|entry| flat_distance_squared(entry.position, position) <= radius * radius)
+            .map(|entry| entry.entity)
+            .collect();
+        found.sort();
+        found
+    }
+
+    /// Query points on cell corners and edges as well as in between
+    fn probes() -> impl Iterator<Item = Vec3> {
+        (-5..=5).flat_map(|x| {
+            (-5..=5).map(move |z| Vec3::new(x as f32 * 2.0, 0.0, z as f32 * 2.0 - 0.25))
+        })
+    }
+
+    #[test]
+    fn neighbors_within_reaches_across_cell_boundaries() {
+        let mut world = World::new();
+        let mut index = SpatialIndex::default();
+        let (left, right) = (world.spawn_empty().id(), world.spawn_empty().id());
+        // Either side of the boundary between two cells
+        for (entity, x) in [(left, CELL_SIZE - 0.25), (right, CELL_SIZE)] {
+            index.insert(SpatialEntry {
+                entity,
+                position: Vec3::new(x, 0.0, 0.0),
+                kind: SpatialKind::Zombie,
+            });
+        }
+        assert_ne!(
+            cell_of(index.entries[0].position),
+            cell_of(index.entries[1].position)
+        );
+
+        let found: Vec<Entity> = index
+            .neighbors_within(Vec3::new(CELL_SIZE - 0.25, 0.0, 0.0), 0.25)
+            .map(|entry| entry.entity)
+            .collect();
+        assert_eq!(found.len(), 2, "the radius is inclusive");
+        let found: Vec<Entity> = index
+            .neighbors_within(Vec3::new(CELL_SIZE + 0.125, 0.0, 0.0), 0.25)
+            .map(|entry| entry.entity)
+            .collect();
+        assert_eq!(found, vec![right]);
+    }
+
+    #[test]
+    fn neighbors_within_matches_a_full_scan() {
+        let index = scattered(300);
+        for position in probes() {
+            for radius in [0.0, 0.5, CELL_SIZE, CELL_SIZE * 1.5, 10.0] {
+                let mut found: Vec<Entity> = index
+                    .neighbors_within(position, radius)
+                    .map(|entry| entry.entity)
+                    .collect();
+                found.sort();
+                assert_eq!(
+                    found,
+                    brute_force_within(&index, position, radius),
+                    "within {} of {}",
+                    radius,
+                    position
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_k_matches_a_full_scan() {
+        let index = scattered(300);
+        let survivors = |entry: &SpatialEntry| entry.kind == SpatialKind::Survivor;
+        for position in probes() {
+            for k in [1, 3, 10] {
+                let distances = |entries: Vec<&SpatialEntry>| -> Vec<f32> {
+                    entries
+                        .iter()
+                        .map(|entry| flat_distance_squared(entry.position, position))
+                        .collect()
+                };
+                let found = index.nearest_k(position, k, survivors);
+                let mut expected: Vec<&SpatialEntry> = index
+                    .entries
+                    .iter()
+                    .filter(|entry| survivors(entry))
+                    .collect();
+                expected.sort_by(|a, b| {
+                    flat_distance_squared(a.position, position)
+                        .total_cmp(&flat_distance_squared(b.position, position))
+                });
+                expected.truncate(k);
+                // Ties may come back in either order, so compare how far they are
+                assert_eq!(
+                    distances(found.iter().collect()),
+                    distances(expected),
+                    "{} nearest to {}",
+                    k,
+                    position
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_k_stops_at_what_there_is() {
+        let index = scattered(10);
+        assert_eq!(index.nearest_k(Vec3::ZERO, 50, |_| true).len(), 10);
+        assert!(index.nearest_k(Vec3::ZERO, 0, |_| true).is_empty());
+        assert!(SpatialIndex::default()
+            .nearest_k(Vec3::ZERO, 1, |_| true)
+            .is_empty());
+    }
+
+    /// The swarm's neighbour work for one frame, 300 zombies each looking for the
+    /// survivor to bite and the zombies to keep apart from: scanning every zombie as
+    /// the systems did before, against the index. `make index-bench` runs it.
+    #[test]
+    #[ignore]
+    fn bench_300_zombie_frame() {
+        const ZOMBIES: usize = 300;
+        const FRAMES: u32 = 200;
+        const SEPARATION: f32 = 1.2;
+        let index = scattered(ZOMBIES);
+        let entries = index.entries.clone();
+
+        let start = Instant::now();
+        let mut checksum = 0usize;
+        for _ in 0..FRAMES {
+            for zombie in &entries {
+                checksum += entries
+                    .iter()
+                    .filter(|other| {
+                        flat_distance_squared(other.position, zombie.position)
+                            <= SEPARATION * SEPARATION
+                    })
+                    .count();
+                checksum += entries
+                    .iter()
+                    .filter(|other| other.kind == SpatialKind::Survivor)
+                    .min_by(|a, b| {
+                        flat_distance_squared(a.position, zombie.position)
+                            .total_cmp(&flat_distance_squared(b.position, zombie.position))
+                    })
+                    .is_some() as usize;
+            }
+        }
+        let before = start.elapsed() / FRAMES;
+
+        let start = Instant::now();
+        let mut rebuilt = SpatialIndex::default();
+        let mut indexed = 0usize;
+        for _ in 0..FRAMES {
+            rebuilt.clear();
+            for entry in &entries {
+                rebuilt.insert(*entry);
+            }
+            for zombie in &entries {
+                indexed += rebuilt
+                    .neighbors_within(zombie.position, SEPARATION)
+                    .count();
+                indexed += rebuilt
+                    .nearest_k(zombie.position, 1, |entry| {
+                        entry.kind == SpatialKind::Survivor
+                    })
+                    .len();
+            }
+        }
+        let after = start.elapsed() / FRAMES;
+
+        assert_eq!(checksum, indexed);
+        println!(
+            "{} zombies, per frame: full scans {:?}, spatial index {:?} (rebuild included)",
+            ZOMBIES, before, after
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use super::PhysicsLayer;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// What a piece of level geometry is made of, which picks its footstep sounds and how
+/// bullets strike it. Untagged geometry counts as Concrete.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SurfaceMaterial {
+    #[default]
+    Concrete,
+    Wood,
+    Metal,
+    Dirt,
+    Water,
+}
+
+impl SurfaceMaterial {
+    pub const ALL: [SurfaceMaterial; 5] = [
+        SurfaceMaterial::Concrete,
+        SurfaceMaterial::Wood,
+        SurfaceMaterial::Metal,
+        SurfaceMaterial::Dirt,
+        SurfaceMaterial::Water,
+    ];
+
+    #[cfg(feature = "dev_console")]
+    pub fn name(&self) -> &'static str {
+        match self {
+            SurfaceMaterial::Concrete => "Concrete",
+            SurfaceMaterial::Wood => "Wood",
+            SurfaceMaterial::Metal => "Metal",
+            SurfaceMaterial::Dirt => "Dirt",
+            SurfaceMaterial::Water => "Water",
+        }
+    }
+
+    /// Position in ALL, for per-surface asset tables
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Surface of `entity`, taken from the nearest tagged ancestor when it's a child
+/// collider of something bigger
+pub fn surface_of(
+    entity: Entity,
+    surfaces: &Query<&SurfaceMaterial>,
+    parents: &Query<&ChildOf>,
+) -> SurfaceMaterial {
+    let mut current = entity;
+    loop {
+        if let Ok(surface) = surfaces.get(current) {
+            return *surface;
+        }
+        let Ok(child_of) = parents.get(current) else {
+            return SurfaceMaterial::default();
+        };
+        current = child_of.parent();
+    }
+}
+
+/// How far below `origin` a surface is picked up
+const SURFACE_PROBE_DEPTH: f32 = 1.5;
+
+/// Level collider straight below `origin` and how far down it is. Pools are sensors,
+/// so sensors are kept in, and only level geometry is hit.
+pub fn ground_below(context: &RapierContext, origin: Vec3) -> Option<(Entity, f32)> {
+    // Debris only collides with World, so its groups make a level-only ray
+    let filter = QueryFilter::default().groups(PhysicsLayer::Debris.groups());
+    let mut hit = None;
+    context.with_query_pipeline(filter, |query_pipeline| {
+        hit = query_pipeline.cast_ray(origin, Vec3::NEG_Y, SURFACE_PROBE_DEPTH, true);
+    });
+    hit
+}
+
+/// Prints what the player is standing on, for checking the tags level geometry got
+#[cfg(feature = "dev_console")]
+pub(super) fn surface_command(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    use crate::player::Player;
+    use bevy::ecs::system::SystemState;
+
+    let position = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .map(|transform| transform.translation)
+        .map_err(|_| "no player".to_string())?;
+    // From the player's middle, which is well inside the probe depth of their feet
+    let mut rapier_context = SystemState::<ReadRapierContext>::new(world);
+    let hit = rapier_context
+        .get(world)
+        .single()
+        .ok()
+        .and_then(|context| ground_below(&context, position));
+    let Some((entity, distance)) = hit else {
+        return Err("nothing under the player".to_string());
+    };
+
+    let mut surfaces = world.query::<&SurfaceMaterial>();
+    let mut parents = world.query::<&ChildOf>();
+    let tagged = surfaces.get(world, entity).is_ok();
+    let surface = surface_of(entity, &surfaces.query(world), &parents.query(world));
+    Ok(format!(
+        "{} under the player ({:.2}m down, collider {}{})",
+        surface.name(),
+        distance,
+        entity,
+        if tagged { "" } else { ", inherited or default" }
+    ))
+}
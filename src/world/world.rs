@@ -1,4 +1,4 @@
-use super::NavGrid;
+use super::{GameRng, NavGrid, PhysicsLayer, SurfaceMaterial};
 use crate::combat::Shootable;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
@@ -8,12 +8,28 @@ pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (spawn_light, spawn_floor, spawn_obstacles).chain());
+        app.init_resource::<GameRng>().add_systems(
+            Startup,
+            (spawn_light, spawn_floor, spawn_obstacles, spawn_firing_lane).chain(),
+        );
+
+        #[cfg(feature = "dev_console")]
+        {
+            use crate::console::ConsoleAppExt;
+            app.register_console_command("surface", "", super::surface::surface_command);
+        }
     }
 }
 
+/// Firing line of the long-range lane, just inside the north wall opening
+pub const FIRING_LANE_START: Vec3 = Vec3::new(0.0, 0.0, -47.0);
+const FIRING_LANE_LENGTH: f32 = 110.0;
+const FIRING_LANE_WIDTH: f32 = 8.0;
+
 /// Obstacle component - marks entities as obstacles
 #[derive(Component)]
+// Health is set per obstacle but nothing damages them yet
+#[allow(dead_code)]
 pub struct Obstacle {
     pub destructible: bool,
     pub health: f32,
@@ -38,6 +54,17 @@ impl Obstacle {
     }
 }
 
+/// The ground plane, as opposed to anything standing on it
+#[derive(Component)]
+pub struct Floor;
+
+/// Low cover zombies can climb over instead of walking around (see NavGrid's climb layer)
+#[derive(Component)]
+pub struct Climbable;
+
+/// Tallest cover that can be marked Climbable
+pub const MAX_CLIMB_HEIGHT: f32 = 1.5;
+
 fn spawn_light(mut commands: Commands) {
     // Main directional light (sun-like)
     commands.spawn((
@@ -68,6 +95,9 @@ fn spawn_floor(
         MeshMaterial3d(materials.add(Color::srgb(0.15, 0.35, 0.15))),
         RigidBody::Fixed,
         Collider::cuboid(50.0, 0.01, 50.0),
+        PhysicsLayer::World.groups(),
+        Floor,
+        SurfaceMaterial::Dirt,
     ));
 }
 
@@ -76,9 +106,8 @@ fn spawn_obstacles(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut nav_grid: ResMut<NavGrid>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = rand::rng();
-
     // Materials
     let wall_material = materials.add(StandardMaterial {
         base_color: Color::srgb(0.4, 0.4, 0.45),
@@ -104,18 +133,22 @@ fn spawn_obstacles(
     let pillar_mesh = meshes.add(Cuboid::new(1.0, 4.0, 1.0));
 
     // === PERIMETER WALLS ===
-    // North wall
-    spawn_wall(
-        &mut commands,
-        &wall_mesh,
-        &wall_material,
-        Vec3::new(0.0, 1.5, -49.0),
-        100.0,
-        3.0,
-        0.5,
-        0.0,
-        &mut nav_grid,
-    );
+    // North wall, split around the opening into the firing lane
+    let gap = FIRING_LANE_WIDTH / 2.0;
+    let segment = 50.0 - gap;
+    for side in [-1.0, 1.0] {
+        spawn_wall(
+            &mut commands,
+            &wall_mesh,
+            &wall_material,
+            Vec3::new(side * (gap + segment / 2.0), 1.5, -49.0),
+            segment,
+            3.0,
+            0.5,
+            0.0,
+            &mut nav_grid,
+        );
+    }
     // South wall
     spawn_wall(
         &mut commands,
@@ -177,6 +210,7 @@ fn spawn_obstacles(
             Obstacle::indestructible(),
             RigidBody::Fixed,
             Collider::cuboid(5.0, 1.5, 0.25),
+            PhysicsLayer::World.groups(),
         ));
 
         // Mark in nav grid (approximate - walls can be rotated)
@@ -200,12 +234,15 @@ fn spawn_obstacles(
             MeshMaterial3d(crate_material.clone()),
             Transform::from_translation(pos),
             Obstacle::destructible(50.0),
+            Climbable,
             Shootable,
             RigidBody::Fixed,
             Collider::cuboid(0.75, 0.75, 0.75),
+            PhysicsLayer::World.groups(),
+            SurfaceMaterial::Wood,
         ));
 
-        nav_grid.mark_obstacle_world(pos, Vec3::new(0.75, 0.0, 0.75));
+        nav_grid.mark_climbable_world(pos, Vec3::new(0.75, 0.0, 0.75), MAX_CLIMB_HEIGHT);
     }
 
     // === BARRELS (10-15, shootable) ===
@@ -228,6 +265,8 @@ fn spawn_obstacles(
             Shootable,
             RigidBody::Fixed,
             Collider::cylinder(0.75, 0.5),
+            PhysicsLayer::World.groups(),
+            SurfaceMaterial::Metal,
         ));
 
         nav_grid.mark_obstacle_world(pos, Vec3::new(0.6, 0.0, 0.6));
@@ -253,6 +292,7 @@ fn spawn_obstacles(
             Obstacle::indestructible(),
             RigidBody::Fixed,
             Collider::cuboid(0.5, 2.0, 0.5),
+            PhysicsLayer::World.groups(),
         ));
 
         nav_grid.mark_obstacle_world(pos, Vec3::new(0.5, 0.0, 0.5));
@@ -280,7 +320,71 @@ fn spawn_wall(
         Obstacle::indestructible(),
         RigidBody::Fixed,
         Collider::cuboid(width / 2.0, height / 2.0, depth / 2.0),
+        PhysicsLayer::World.groups(),
     ));
 
     nav_grid.mark_obstacle_world(pos, Vec3::new(width / 2.0, 0.0, depth / 2.0));
 }
+
+/// Long walled lane running north out of the arena for practicing bullet drop
+fn spawn_firing_lane(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let lane_end = FIRING_LANE_START.z - FIRING_LANE_LENGTH;
+    let center_z = (FIRING_LANE_START.z - 2.0 + lane_end) / 2.0;
+    let length = FIRING_LANE_START.z - 2.0 - lane_end;
+
+    let floor_material = materials.add(Color::srgb(0.3, 0.28, 0.22));
+    let wall_material = materials.add(Color::srgb(0.35, 0.35, 0.4));
+    let marker_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.9, 0.9),
+        unlit: true,
+        ..default()
+    });
+
+    // Floor beyond the arena edge
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(FIRING_LANE_WIDTH, length))),
+        MeshMaterial3d(floor_material),
+        Transform::from_xyz(0.0, 0.0, center_z),
+        RigidBody::Fixed,
+        Collider::cuboid(FIRING_LANE_WIDTH / 2.0, 0.01, length / 2.0),
+        PhysicsLayer::World.groups(),
+        SurfaceMaterial::Concrete,
+    ));
+
+    // Side walls and backstop
+    let side_mesh = meshes.add(Cuboid::new(0.5, 3.0, length));
+    for side in [-1.0, 1.0] {
+        commands.spawn((
+            Mesh3d(side_mesh.clone()),
+            MeshMaterial3d(wall_material.clone()),
+            Transform::from_xyz(side * (FIRING_LANE_WIDTH / 2.0 + 0.25), 1.5, center_z),
+            Obstacle::indestructible(),
+            RigidBody::Fixed,
+            Collider::cuboid(0.25, 1.5, length / 2.0),
+            PhysicsLayer::World.groups(),
+        ));
+    }
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(FIRING_LANE_WIDTH + 1.0, 6.0, 0.5))),
+        MeshMaterial3d(wall_material),
+        Transform::from_xyz(0.0, 3.0, lane_end - 0.25),
+        Obstacle::indestructible(),
+        RigidBody::Fixed,
+        Collider::cuboid(FIRING_LANE_WIDTH / 2.0 + 0.5, 3.0, 0.25),
+        PhysicsLayer::World.groups(),
+    ));
+
+    // Floor stripes every 25m so the marker distances read at a glance
+    let stripe_mesh = meshes.add(Plane3d::default().mesh().size(FIRING_LANE_WIDTH, 0.3));
+    for distance in [25.0, 50.0, 75.0, 100.0] {
+        commands.spawn((
+            Mesh3d(stripe_mesh.clone()),
+            MeshMaterial3d(marker_material.clone()),
+            Transform::from_xyz(0.0, 0.02, FIRING_LANE_START.z - distance),
+        ));
+    }
+}